@@ -26,6 +26,9 @@ use crate::webgl_thread::{WebGLThread, WebGLThreadInit, WebXRBridgeInit};
 pub struct WebGLComm {
     pub webgl_threads: WebGLThreads,
     pub image_handler: Box<dyn WebrenderExternalImageApi>,
+    /// A second view of the same swap chains, for embedders that sample the front
+    /// buffer of a WebGL canvas themselves instead of going through WebRender.
+    pub canvas_texture_handler: Box<dyn WebrenderExternalImageApi>,
     pub webxr_layer_grand_manager: WebXRLayerGrandManager<WebXRSurfman>,
 }
 
@@ -58,6 +61,8 @@ impl WebGLComm {
             webxr_init,
         };
 
+        let canvas_textures =
+            WebGLExternalImages::new(surfman.clone(), webrender_swap_chains.clone());
         let external = WebGLExternalImages::new(surfman, webrender_swap_chains);
 
         WebGLThread::run_on_own_thread(init);
@@ -65,6 +70,7 @@ impl WebGLComm {
         WebGLComm {
             webgl_threads: WebGLThreads(sender),
             image_handler: Box::new(external),
+            canvas_texture_handler: Box::new(canvas_textures),
             webxr_layer_grand_manager,
         }
     }
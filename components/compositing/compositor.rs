@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::OnceCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs::{create_dir_all, File};
 use std::io::Write;
@@ -17,13 +17,14 @@ use compositing_traits::{
     ConstellationMsg, FontToCompositorMsg, ForwardedToCompositorMsg, SendableFrameTree,
 };
 use crossbeam_channel::Sender;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, EventLoopWaker, Screenshot, ScreenshotError};
 use euclid::{Point2D, Rect, Scale, Transform3D, Vector2D};
 use fnv::{FnvHashMap, FnvHashSet};
 use gfx::rendering_context::RenderingContext;
 use gfx_traits::{Epoch, FontData, WebRenderEpochToU16};
 use image::{DynamicImage, ImageFormat};
 use ipc_channel::ipc;
+use keyboard_types::Modifiers;
 use libc::c_void;
 use log::{debug, error, info, trace, warn};
 use msg::constellation_msg::{
@@ -41,24 +42,31 @@ use script_traits::{
     UntrustedNodeAddress, WheelDelta, WindowSizeData, WindowSizeType,
 };
 use servo_geometry::{DeviceIndependentPixel, FramebufferUintLength};
-use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
+use style_traits::{CSSPixel, DevicePixel};
+use surfman::Surface;
 use webrender;
 use webrender::{CaptureBits, RenderApi, Transaction};
 use webrender_api::units::{
-    DeviceIntPoint, DeviceIntSize, DevicePoint, LayoutPoint, LayoutRect, LayoutSize,
-    LayoutVector2D, WorldPoint,
+    DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePoint, DeviceRect, DeviceVector2D,
+    LayoutPoint, LayoutRect, LayoutSize, LayoutVector2D, WorldPoint,
 };
 use webrender_api::{
-    self, BuiltDisplayList, ClipId, DirtyRect, DocumentId, Epoch as WebRenderEpoch,
-    ExternalScrollId, HitTestFlags, PipelineId as WebRenderPipelineId, PropertyBinding,
-    ReferenceFrameKind, ScrollClamping, ScrollLocation, SpaceAndClipInfo, SpatialId,
-    TransformStyle, ZoomFactor,
+    self, BuiltDisplayList, ClipId, ColorF, CommonItemProperties, DirtyRect, DocumentId,
+    Epoch as WebRenderEpoch, ExternalScrollId, HitTestFlags, PipelineId as WebRenderPipelineId,
+    PropertyBinding, ReferenceFrameKind, ScrollClamping, ScrollLocation, SpaceAndClipInfo,
+    SpatialId, TransformStyle, ZoomFactor,
 };
 
+#[cfg(target_os = "linux")]
+use crate::dmabuf::DmabufExporter;
 use crate::gl::RenderTargetInfo;
-use crate::touch::{TouchAction, TouchHandler};
+use crate::input_routing::{self, InputRoute, InputRouter};
+use crate::readback::FrameReader;
+use crate::screenshot::{self, PendingScreenshot};
+use crate::touch::{Fling, TouchAction, TouchHandler};
 use crate::windowing::{
-    self, EmbedderCoordinates, MouseWindowEvent, WebRenderDebugOption, WindowMethods,
+    self, DmabufFormat, EmbedderCoordinates, GestureSettings, InputEvent, MouseWindowEvent,
+    WebRenderDebugOption, WebViewFrame, WebViewRenderTarget, WindowMethods,
 };
 use crate::{gl, InitialCompositorState};
 
@@ -72,12 +80,28 @@ enum NotReadyToPaint {
     AnimationsActive,
     JustNotifiedConstellation,
     WaitingOnConstellation,
+    TakingScreenshot,
 }
 
 // Default viewport constraints
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
 
+/// The colors that the matches of the search of a webview, and its active match, are
+/// highlighted with.
+const FIND_HIGHLIGHT_COLOR: ColorF = ColorF {
+    r: 1.,
+    g: 1.,
+    b: 0.,
+    a: 0.4,
+};
+const ACTIVE_FIND_HIGHLIGHT_COLOR: ColorF = ColorF {
+    r: 1.,
+    g: 0.6,
+    b: 0.,
+    a: 0.6,
+};
+
 trait ConvertPipelineIdFromWebRender {
     fn from_webrender(&self) -> PipelineId;
 }
@@ -134,18 +158,44 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// pipeline which applies a pinch zoom transformation.
     root_content_pipeline: RootPipeline,
 
+    /// The root pipelines of the webviews to paint, bottom first. When empty, only the
+    /// root content pipeline is painted.
+    painting_order: Vec<PipelineId>,
+
+    /// The device pixel ratios of the webviews that don't follow the window.
+    webview_device_pixel_ratios:
+        HashMap<TopLevelBrowsingContextId, Scale<f32, CSSPixel, DevicePixel>>,
+
+    /// The sizes of the viewports of the webviews that aren't the size of the window.
+    webview_sizes: HashMap<TopLevelBrowsingContextId, DeviceIntSize>,
+
+    /// The colors painted behind the webviews that the embedder gave one.
+    webview_background_colors: HashMap<TopLevelBrowsingContextId, ColorF>,
+
+    /// The webviews whose viewports are cleared to transparent before they are painted.
+    transparent_webviews: HashSet<TopLevelBrowsingContextId>,
+
+    /// The boxes of the matches of the searches of webviews, and of their active matches,
+    /// in device pixels relative to their viewports.
+    find_highlights: HashMap<TopLevelBrowsingContextId, (Vec<DeviceIntRect>, Vec<DeviceIntRect>)>,
+
     /// Tracks details about each active pipeline that the compositor knows about.
     pipeline_details: HashMap<PipelineId, PipelineDetails>,
 
     /// The scene scale, to allow for zooming and high-resolution painting.
     scale: Scale<f32, LayerPixel, DevicePixel>,
 
-    /// "Mobile-style" zoom that does not reflow the page.
-    viewport_zoom: PinchZoomFactor,
+    /// The "mobile-style" zoom of the webviews that are zoomed, which does not reflow them.
+    webview_pinch_zooms: HashMap<TopLevelBrowsingContextId, PinchZoom>,
+
+    /// The gesture settings of the webviews that don't have the default ones.
+    webview_gesture_settings: HashMap<TopLevelBrowsingContextId, GestureSettings>,
 
-    /// Viewport zoom constraints provided by @viewport.
-    min_viewport_zoom: Option<PinchZoomFactor>,
-    max_viewport_zoom: Option<PinchZoomFactor>,
+    /// The webview that the current touch sequence started in, which its gestures apply to.
+    touch_webview: Option<TopLevelBrowsingContextId>,
+
+    /// The fling that scrolls a webview after a pan was released.
+    fling: Option<(TopLevelBrowsingContextId, Fling)>,
 
     /// "Desktop-style" zoom that resizes the viewport to fit the window.
     page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
@@ -178,6 +228,9 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Touch input state machine
     touch_handler: TouchHandler,
 
+    /// The webviews that captured the pointers that are pressed.
+    input_router: InputRouter,
+
     /// Pending scroll/zoom events.
     pending_scroll_zoom_events: Vec<ScrollZoomEvent>,
 
@@ -232,6 +285,33 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Whether to invalidate `prev_offscreen_framebuffer` at the end of the next frame.
     invalidate_prev_offscreen_framebuffer: bool,
 
+    /// The framebuffers of the embedder that webviews are rendered into when compositing
+    /// to [`CompositeTarget::WebViewTextures`].
+    webview_render_targets: HashMap<TopLevelBrowsingContextId, WebViewRenderTarget>,
+
+    /// The framebuffer that webviews are composited into before they are copied into their
+    /// render targets, with its size.
+    webview_texture_framebuffer: Option<(DeviceIntSize, gl::RenderTargetInfo)>,
+
+    /// The native surfaces of the webviews that are presented in their own surface when
+    /// compositing to [`CompositeTarget::WebViewTextures`].
+    webview_surfaces: HashMap<TopLevelBrowsingContextId, WebViewSurface>,
+
+    /// The webviews whose frames are exported as DMA-BUFs.
+    #[cfg(target_os = "linux")]
+    dmabuf_exporter: DmabufExporter,
+
+    /// The webviews whose frames are read back into memory, with their frames in flight.
+    frame_reader: FrameReader,
+
+    /// Wakes the event loop of the embedder while frames are read back, so that they are
+    /// handed to it even if nothing else happens.
+    event_loop_waker: Box<dyn EventLoopWaker>,
+
+    /// The screenshots that the embedder asked for, oldest first. The oldest is being
+    /// taken.
+    pending_screenshots: VecDeque<PendingScreenshot>,
+
     /// True to exit after page load ('-x').
     exit_after_load: bool,
 
@@ -257,12 +337,46 @@ struct ScrollEvent {
     cursor: DeviceIntPoint,
     /// The number of OS events that have been coalesced together into this one event.
     event_count: u32,
+    /// The webview that the embedder injected the scroll into, or `None` for the one under
+    /// the cursor.
+    webview_id: Option<TopLevelBrowsingContextId>,
+}
+
+/// The pinch zoom of a webview, which magnifies it about its origin and shows the part of
+/// it that its visual viewport is over, in its viewport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PinchZoom {
+    /// How much the webview is magnified.
+    factor: f32,
+    /// Where the visual viewport is in the magnified webview, in device pixels.
+    offset: DeviceVector2D,
+}
+
+impl Default for PinchZoom {
+    fn default() -> Self {
+        PinchZoom {
+            factor: 1.0,
+            offset: DeviceVector2D::zero(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PinchZoomEvent {
+    webview_id: TopLevelBrowsingContextId,
+    /// Magnify the webview by this factor.
+    magnification: f32,
+    /// Keep the part of the webview at this point where it is while magnifying it.
+    focus: DevicePoint,
+    /// Then pan the visual viewport by this offset, and scroll the document of the webview
+    /// by the part of it that the visual viewport can't follow.
+    pan: DeviceVector2D,
 }
 
 #[derive(Clone, Copy)]
 enum ScrollZoomEvent {
-    /// An pinch zoom event that magnifies the view by the given factor.
-    PinchZoom(f32),
+    /// A pinch zoom event that magnifies and pans a webview.
+    PinchZoom(PinchZoomEvent),
     /// A scroll event that scrolls the scroll node at the given location by the
     /// given amount.
     Scroll(ScrollEvent),
@@ -361,6 +475,14 @@ impl PipelineDetails {
     }
 }
 
+/// The native surface that a webview is presented in.
+struct WebViewSurface {
+    /// The surface, unless it is being drawn into.
+    surface: Option<Surface>,
+    /// Whether the embedder was told that the surface is shown.
+    shown: bool,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum CompositeTarget {
     /// Draw directly to a window.
@@ -370,6 +492,11 @@ pub enum CompositeTarget {
     /// [`IOCompositor::offscreen_framebuffer_id`].
     Fbo,
 
+    /// Draw each webview that the embedder gave a [`WebViewRenderTarget`] or a native surface
+    /// into it, or export it, rather than drawing to the window. These webviews are
+    /// composited side by side in painting order, and aren't pinch zoomed.
+    WebViewTextures,
+
     /// Draw to an uncompressed image in shared memory.
     SharedMemory,
 
@@ -390,6 +517,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             embedder_coordinates: window.get_coordinates(),
             window,
             port: state.receiver,
+            painting_order: Vec::new(),
+            webview_device_pixel_ratios: HashMap::new(),
+            webview_background_colors: HashMap::new(),
+            transparent_webviews: HashSet::new(),
+            find_highlights: HashMap::new(),
+            webview_sizes: HashMap::new(),
             root_content_pipeline: RootPipeline {
                 top_level_browsing_context_id,
                 id: None,
@@ -398,13 +531,24 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             scale: Scale::new(1.0),
             composition_request: CompositionRequest::NoCompositingNecessary,
             touch_handler: TouchHandler::new(),
+            input_router: InputRouter::default(),
+            webview_surfaces: HashMap::new(),
+            #[cfg(target_os = "linux")]
+            dmabuf_exporter: DmabufExporter::new(
+                state.webrender_gl.clone(),
+                state.rendering_context.clone(),
+            ),
+            frame_reader: FrameReader::new(state.webrender_gl.clone()),
+            event_loop_waker: state.sender.event_loop_waker.clone(),
+            pending_screenshots: VecDeque::new(),
             pending_scroll_zoom_events: Vec::new(),
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
             page_zoom: Scale::new(1.0),
-            viewport_zoom: PinchZoomFactor::new(1.0),
-            min_viewport_zoom: Some(PinchZoomFactor::new(1.0)),
-            max_viewport_zoom: None,
+            webview_pinch_zooms: HashMap::new(),
+            webview_gesture_settings: HashMap::new(),
+            touch_webview: None,
+            fling: None,
             zoom_action: false,
             zoom_time: 0f64,
             frame_tree_id: FrameTreeId(0),
@@ -423,6 +567,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             next_offscreen_framebuffer: OnceCell::new(),
             prev_offscreen_framebuffer: None,
             invalidate_prev_offscreen_framebuffer: false,
+            webview_render_targets: HashMap::new(),
+            webview_texture_framebuffer: None,
             exit_after_load,
             convert_mouse_to_touch,
             pending_frames: 0,
@@ -540,7 +686,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
     fn handle_browser_message(&mut self, msg: CompositorMsg) -> bool {
         if matches!(msg, CompositorMsg::NewWebRenderFrameReady(..)) {
-            self.pending_frames -= 1;
+            self.count_ready_frame();
         }
 
         match (msg, self.shutdown_state) {
@@ -566,6 +712,105 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.send_scroll_positions_to_layout_for_pipeline(&frame_tree.pipeline.id);
             },
 
+            (CompositorMsg::SetPaintingOrder(pipeline_ids), ShutdownState::NotShuttingDown) => {
+                self.painting_order = pipeline_ids;
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                self.generate_frame(&mut txn);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
+            (
+                CompositorMsg::SetWebViewBackgroundColor(top_level_browsing_context_id, color),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                match color {
+                    Some(color) => self
+                        .webview_background_colors
+                        .insert(top_level_browsing_context_id, color),
+                    None => self
+                        .webview_background_colors
+                        .remove(&top_level_browsing_context_id),
+                };
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                self.generate_frame(&mut txn);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
+            (
+                CompositorMsg::SetWebViewTransparent(top_level_browsing_context_id, transparent),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                if transparent {
+                    self.transparent_webviews
+                        .insert(top_level_browsing_context_id);
+                } else {
+                    self.transparent_webviews
+                        .remove(&top_level_browsing_context_id);
+                }
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                self.generate_frame(&mut txn);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
+            (
+                CompositorMsg::SetFindHighlights(top_level_browsing_context_id, matches, active),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                if matches.is_empty() && active.is_empty() {
+                    self.find_highlights.remove(&top_level_browsing_context_id);
+                } else {
+                    self.find_highlights
+                        .insert(top_level_browsing_context_id, (matches, active));
+                }
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                self.generate_frame(&mut txn);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
+            (
+                CompositorMsg::SetWebViewDevicePixelRatio(top_level_browsing_context_id, ratio),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                match ratio {
+                    Some(ratio) => self
+                        .webview_device_pixel_ratios
+                        .insert(top_level_browsing_context_id, ratio),
+                    None => self
+                        .webview_device_pixel_ratios
+                        .remove(&top_level_browsing_context_id),
+                };
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                self.generate_frame(&mut txn);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
+            (
+                CompositorMsg::ResizeWebView(top_level_browsing_context_id, size),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                match size {
+                    Some(size) => self
+                        .webview_sizes
+                        .insert(top_level_browsing_context_id, size),
+                    None => self.webview_sizes.remove(&top_level_browsing_context_id),
+                };
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                self.generate_frame(&mut txn);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
             (CompositorMsg::TouchEventProcessed(result), ShutdownState::NotShuttingDown) => {
                 self.touch_handler.on_event_processed(result);
             },
@@ -581,6 +826,33 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (
+                CompositorMsg::CaptureScreenshot(
+                    webview_id,
+                    pipeline_id,
+                    layout,
+                    rect,
+                    scale_mode,
+                    format,
+                    sender,
+                ),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                self.pending_screenshots.push_back(PendingScreenshot {
+                    webview_id,
+                    pipeline_id,
+                    layout,
+                    rect,
+                    scale_mode,
+                    format,
+                    sender,
+                    frames_until_ready: None,
+                });
+                if self.pending_screenshots.len() == 1 {
+                    self.send_screenshot_scene();
+                }
+            },
+
             (CompositorMsg::IsReadyToSaveImageReply(is_ready), ShutdownState::NotShuttingDown) => {
                 assert_eq!(
                     self.ready_to_save_state,
@@ -612,8 +884,21 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 CompositorMsg::NewWebRenderFrameReady(recomposite_needed),
                 ShutdownState::NotShuttingDown,
             ) => {
+                let screenshot_ready = self
+                    .pending_screenshots
+                    .front()
+                    .map_or(false, |screenshot| screenshot.frames_until_ready == Some(0));
+                if screenshot_ready {
+                    self.take_screenshot();
+                    return true;
+                }
+
                 if recomposite_needed {
-                    if let Some(result) = self.hit_test_at_device_point(self.cursor_pos) {
+                    let route =
+                        input_routing::route_at_point(&self.input_routes(), self.cursor_pos);
+                    if let Some(result) = route.and_then(|route| {
+                        self.hit_test_at_device_point_in_webview(self.cursor_pos, route.webview_id)
+                    }) {
                         self.update_cursor(result);
                     }
                     self.composition_request =
@@ -640,6 +925,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (CompositorMsg::WebViewSuspended(_), ShutdownState::NotShuttingDown) => {
+                // WebRender shares its caches between webviews, but the textures that only
+                // the suspended one used are unused from now on.
+                self.notify_memory_pressure();
+            },
+
             (
                 CompositorMsg::WebDriverMouseButtonEvent(mouse_event_type, mouse_button, x, y),
                 ShutdownState::NotShuttingDown,
@@ -760,6 +1051,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.generate_frame(&mut txn);
                 self.webrender_api
                     .send_transaction(self.webrender_document, txn);
+
+                let awaits_display_list = self
+                    .pending_screenshots
+                    .front()
+                    .map_or(false, |screenshot| screenshot.frames_until_ready.is_none());
+                if awaits_display_list {
+                    self.send_screenshot_scene();
+                }
             },
 
             ForwardedToCompositorMsg::Layout(script_traits::ScriptToCompositorMsg::HitTest(
@@ -831,6 +1130,15 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                     .send_transaction(self.webrender_document, txn);
             },
 
+            ForwardedToCompositorMsg::Net(net_traits::NetToCompositorMsg::DeleteImages(keys)) => {
+                let mut txn = Transaction::new();
+                for key in keys {
+                    txn.delete_image(key);
+                }
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
             ForwardedToCompositorMsg::Font(FontToCompositorMsg::AddFontInstance(
                 font_key,
                 size,
@@ -885,6 +1193,16 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         transaction.generate_frame(0);
     }
 
+    /// Decrease the pending frames count, as WebRender has produced a frame.
+    fn count_ready_frame(&mut self) {
+        self.pending_frames -= 1;
+        if let Some(screenshot) = self.pending_screenshots.front_mut() {
+            if let Some(ref mut frames) = screenshot.frames_until_ready {
+                *frames = frames.saturating_sub(1);
+            }
+        }
+    }
+
     /// Sets or unsets the animations-running flag for the given pipeline, and schedules a
     /// recomposite if necessary.
     fn change_running_animations_state(
@@ -941,19 +1259,73 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
-    /// Set the root pipeline for our WebRender scene. If there is no pinch zoom applied,
-    /// the root pipeline is the root content pipeline. If there is pinch zoom, the root
-    /// content pipeline is wrapped in a display list that applies a pinch zoom
-    /// transformation to it.
+    /// Set the root pipeline for our WebRender scene. If there is no pinch zoom applied
+    /// and a single webview to paint, which has the size, device pixel ratio and
+    /// background of the window, the root pipeline is the root content pipeline.
+    /// Otherwise, the root pipelines of the webviews are wrapped in painting order in a
+    /// display list that applies their pinch zoom transformations to them, over their
+    /// background colors. The viewports of transparent webviews are cleared first, so
+    /// that they don't show the background color of the shell. While a screenshot is
+    /// taken, its webview is the only one painted.
     fn set_root_content_pipeline_handling_pinch_zoom(&self, transaction: &mut Transaction) {
-        let root_content_pipeline = match self.root_content_pipeline.id {
-            Some(id) => id.to_webrender(),
-            None => return,
-        };
+        let mut content_pipelines: Vec<(WebRenderPipelineId, Option<TopLevelBrowsingContextId>)> =
+            if let Some(screenshot) = self.pending_screenshots.front() {
+                vec![(
+                    screenshot.pipeline_id.to_webrender(),
+                    Some(screenshot.webview_id),
+                )]
+            } else if self.painting_order.is_empty() {
+                match self.root_content_pipeline.id {
+                    Some(id) => vec![(
+                        id.to_webrender(),
+                        Some(self.root_content_pipeline.top_level_browsing_context_id),
+                    )],
+                    None => return,
+                }
+            } else {
+                self.painting_order
+                    .iter()
+                    .map(|id| {
+                        let webview_id = self
+                            .pipeline_details
+                            .get(id)
+                            .and_then(|details| details.pipeline.as_ref())
+                            .map(|pipeline| pipeline.top_level_browsing_context_id);
+                        (id.to_webrender(), webview_id)
+                    })
+                    .collect()
+            };
+        if self.composite_target == CompositeTarget::WebViewTextures &&
+            self.pending_screenshots.is_empty()
+        {
+            content_pipelines.retain(|(_, webview_id)| {
+                webview_id.map_or(false, |id| self.is_rendered_to_texture(id))
+            });
+        }
+        transaction.set_document_view(
+            self.document_view(),
+            self.embedder_coordinates.hidpi_factor.get(),
+        );
 
-        let zoom_factor = self.pinch_zoom_level();
-        if zoom_factor == 1.0 {
-            transaction.set_root_pipeline(root_content_pipeline);
+        let has_own_viewport = |webview_id: Option<TopLevelBrowsingContextId>| {
+            webview_id.map_or(false, |webview_id| {
+                self.webview_device_pixel_ratios.contains_key(&webview_id) ||
+                    self.webview_sizes.contains_key(&webview_id) ||
+                    self.webview_background_colors.contains_key(&webview_id) ||
+                    self.transparent_webviews.contains(&webview_id) ||
+                    self.find_highlights.contains_key(&webview_id)
+            })
+        };
+        let is_pinch_zoomed = |webview_id: Option<TopLevelBrowsingContextId>| {
+            webview_id.map_or(false, |webview_id| {
+                self.painting_pinch_zoom(webview_id) != PinchZoom::default()
+            })
+        };
+        if content_pipelines.len() == 1 &&
+            !has_own_viewport(content_pipelines[0].1) &&
+            !is_pinch_zoomed(content_pipelines[0].1)
+        {
+            transaction.set_root_pipeline(content_pipelines[0].0);
             return;
         }
 
@@ -969,28 +1341,126 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             self.embedder_coordinates.get_viewport().height() as f32,
         );
         let viewport_rect = LayoutRect::new(LayoutPoint::zero(), viewport_size);
-        let zoom_reference_frame = builder.push_reference_frame(
-            LayoutPoint::zero(),
-            SpatialId::root_reference_frame(root_pipeline),
-            TransformStyle::Flat,
-            PropertyBinding::Value(Transform3D::scale(zoom_factor, zoom_factor, 1.)),
-            ReferenceFrameKind::Transform {
-                is_2d_scale_translation: true,
-                should_snap: true,
-            },
-        );
-
-        builder.push_iframe(
-            viewport_rect,
-            viewport_rect,
-            &SpaceAndClipInfo {
-                spatial_id: zoom_reference_frame,
-                clip_id: ClipId::root(root_pipeline),
-            },
-            root_content_pipeline,
-            true,
-        );
+        let root_space_and_clip = SpaceAndClipInfo {
+            spatial_id: SpatialId::root_reference_frame(root_pipeline),
+            clip_id: ClipId::root(root_pipeline),
+        };
+        let dppx = self.device_pixels_per_page_px().get();
+
+        for (content_pipeline, webview_id) in content_pipelines {
+            let scale = webview_id.map_or(1., |webview_id| self.webview_scale(webview_id));
+            let origin = webview_id.map_or(DeviceIntPoint::zero(), |id| self.webview_origin(id));
+            let pinch_zoom =
+                webview_id.map_or_else(PinchZoom::default, |id| self.painting_pinch_zoom(id));
+            // A pinch zoomed webview is magnified beyond its viewport, which it is clipped to.
+            let clip_id = match webview_id {
+                Some(webview_id) if pinch_zoom != PinchZoom::default() => {
+                    let size = self.webview_viewport_size(webview_id).to_f32() / dppx;
+                    builder.define_clip_rect(
+                        &root_space_and_clip,
+                        LayoutRect::new(
+                            LayoutPoint::new(origin.x as f32 / dppx, origin.y as f32 / dppx),
+                            LayoutSize::new(size.width, size.height),
+                        ),
+                    )
+                },
+                _ => root_space_and_clip.clip_id,
+            };
+            let has_reference_frame =
+                origin != DeviceIntPoint::zero() || pinch_zoom != PinchZoom::default();
+            let parent_frame = if !has_reference_frame {
+                root_space_and_clip.spatial_id
+            } else {
+                let frame_origin = (origin.to_f32() - pinch_zoom.offset) / dppx;
+                builder.push_reference_frame(
+                    LayoutPoint::new(frame_origin.x, frame_origin.y),
+                    root_space_and_clip.spatial_id,
+                    TransformStyle::Flat,
+                    PropertyBinding::Value(Transform3D::scale(
+                        pinch_zoom.factor,
+                        pinch_zoom.factor,
+                        1.,
+                    )),
+                    ReferenceFrameKind::Transform {
+                        is_2d_scale_translation: true,
+                        should_snap: true,
+                    },
+                )
+            };
+            // A webview with its own size, or the one of a screenshot, is clipped to it,
+            // which is in device pixels rather than the layout pixels of this display list.
+            let webview_size = webview_id
+                .filter(|id| {
+                    self.webview_sizes.contains_key(id) || !self.pending_screenshots.is_empty()
+                })
+                .map(|id| self.webview_viewport_size(id));
+            let webview_rect = match webview_size {
+                Some(size) => LayoutRect::new(
+                    LayoutPoint::zero(),
+                    LayoutSize::new(size.width as f32, size.height as f32) /
+                        (self.device_pixels_per_page_px().get() * scale),
+                ),
+                None => viewport_rect.scale(1. / scale, 1. / scale),
+            };
+            // Webviews with their own device pixel ratio are scaled from the ratio that
+            // WebRender paints everything else with.
+            let (spatial_id, rect) = if scale == 1.0 {
+                (parent_frame, webview_rect)
+            } else {
+                let reference_frame = builder.push_reference_frame(
+                    LayoutPoint::zero(),
+                    parent_frame,
+                    TransformStyle::Flat,
+                    PropertyBinding::Value(Transform3D::scale(scale, scale, 1.)),
+                    ReferenceFrameKind::Transform {
+                        is_2d_scale_translation: true,
+                        should_snap: true,
+                    },
+                );
+                (reference_frame, webview_rect)
+            };
+            let space_and_clip = SpaceAndClipInfo {
+                spatial_id,
+                clip_id,
+            };
+            let common = CommonItemProperties::new(rect, space_and_clip);
+            if webview_id.map_or(false, |id| self.transparent_webviews.contains(&id)) {
+                builder.push_clear_rect(&common, rect);
+            }
+            let background_color =
+                webview_id.and_then(|id| self.webview_background_colors.get(&id));
+            if let Some(color) = background_color {
+                builder.push_rect(&common, rect, *color);
+            }
+            builder.push_iframe(rect, rect, &space_and_clip, content_pipeline, true);
+            // The highlights are over the documents, in the device pixels of the webview.
+            let find_highlights = webview_id.and_then(|id| self.find_highlights.get(&id));
+            if let Some((matches, active)) = find_highlights {
+                let highlights = [
+                    (matches, FIND_HIGHLIGHT_COLOR),
+                    (active, ACTIVE_FIND_HIGHLIGHT_COLOR),
+                ];
+                for (rects, color) in highlights {
+                    for highlight in rects {
+                        let highlight = highlight.to_f32().to_untyped();
+                        let highlight = LayoutRect::from_untyped(&highlight)
+                            .scale(1. / (dppx * scale), 1. / (dppx * scale));
+                        if let Some(highlight) = highlight.intersection(&rect) {
+                            let common = CommonItemProperties::new(highlight, space_and_clip);
+                            builder.push_rect(&common, highlight, color);
+                        }
+                    }
+                }
+            }
+            if scale != 1.0 {
+                builder.pop_reference_frame();
+            }
+            if has_reference_frame {
+                builder.pop_reference_frame();
+            }
+        }
         let built_display_list = builder.finalize();
+        let document_view = self.document_view();
 
         // NB: We are always passing 0 as the epoch here, but this doesn't seem to
         // be an issue. WebRender will still update the scene and generate a new
@@ -998,7 +1468,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         transaction.set_display_list(
             WebRenderEpoch(0),
             None,
-            viewport_rect.size,
+            LayoutSize::new(document_view.width() as f32, document_view.height() as f32),
             built_display_list,
             false,
         );
@@ -1071,7 +1541,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         let mut transaction = Transaction::new();
         transaction.set_document_view(
-            self.embedder_coordinates.get_viewport(),
+            self.document_view(),
             self.embedder_coordinates.hidpi_factor.get(),
         );
         self.webrender_api
@@ -1141,17 +1611,40 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             MouseWindowEvent::MouseUp(_, p) => p,
         };
 
-        let result = match self.hit_test_at_device_point(point) {
-            Some(result) => result,
-            None => return,
-        };
-
         let (button, event_type) = match mouse_window_event {
             MouseWindowEvent::Click(button, _) => (button, MouseEventType::Click),
             MouseWindowEvent::MouseDown(button, _) => (button, MouseEventType::MouseDown),
             MouseWindowEvent::MouseUp(button, _) => (button, MouseEventType::MouseUp),
         };
 
+        let routes = self.input_routes();
+        let route = self
+            .input_router
+            .route_mouse_button(&routes, event_type, button, point);
+        if let Some(route) = route {
+            self.send_mouse_button_event(
+                route.webview_id,
+                event_type,
+                button,
+                point,
+                Modifiers::empty(),
+            );
+        }
+    }
+
+    fn send_mouse_button_event(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        event_type: MouseEventType,
+        button: MouseButton,
+        point: DevicePoint,
+        modifiers: Modifiers,
+    ) {
+        let result = match self.hit_test_at_device_point_in_webview(point, webview_id) {
+            Some(result) => result,
+            None => return,
+        };
+
         let event_to_send = MouseButtonEvent(
             event_type,
             button,
@@ -1159,6 +1652,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             Some(result.node),
             Some(result.point_relative_to_item),
             button as u16,
+            modifiers,
         );
 
         let msg = ConstellationMsg::ForwardEvent(result.pipeline_id, event_to_send);
@@ -1167,18 +1661,149 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
-    fn hit_test_at_device_point(&self, point: DevicePoint) -> Option<CompositorHitTestResult> {
+    /// The webviews that are painted, bottom first.
+    fn painted_webviews(&self) -> Vec<TopLevelBrowsingContextId> {
+        let webviews: Vec<TopLevelBrowsingContextId> = if self.painting_order.is_empty() {
+            self.root_content_pipeline
+                .id
+                .map(|_| self.root_content_pipeline.top_level_browsing_context_id)
+                .into_iter()
+                .collect()
+        } else {
+            self.painting_order
+                .iter()
+                .filter_map(|id| self.pipeline(*id))
+                .map(|pipeline| pipeline.top_level_browsing_context_id)
+                .collect()
+        };
+        if self.composite_target != CompositeTarget::WebViewTextures {
+            return webviews;
+        }
+        webviews
+            .into_iter()
+            .filter(|id| self.is_rendered_to_texture(*id))
+            .collect()
+    }
+
+    /// The size of the viewport of a webview, in device pixels, or of the one it is laid
+    /// out with for the screenshot being taken of it.
+    fn webview_viewport_size(&self, webview_id: TopLevelBrowsingContextId) -> DeviceIntSize {
+        let screenshot_layout = self
+            .pending_screenshots
+            .front()
+            .filter(|screenshot| screenshot.webview_id == webview_id)
+            .and_then(|screenshot| screenshot.layout);
+        if let Some(layout) = screenshot_layout {
+            return layout.viewport_size;
+        }
+        self.webview_sizes
+            .get(&webview_id)
+            .copied()
+            .unwrap_or_else(|| self.embedder_coordinates.get_viewport().size)
+    }
+
+    /// Where a webview is composited, in device pixels. Webviews are composited over each
+    /// other, except when they are rendered to textures, where they are side by side in
+    /// painting order so that each of them can be copied into its render target.
+    fn webview_origin(&self, webview_id: TopLevelBrowsingContextId) -> DeviceIntPoint {
+        if self.composite_target != CompositeTarget::WebViewTextures ||
+            !self.pending_screenshots.is_empty()
+        {
+            return DeviceIntPoint::zero();
+        }
+        let x = self
+            .painted_webviews()
+            .into_iter()
+            .take_while(|id| *id != webview_id)
+            .map(|id| self.webview_viewport_size(id).width)
+            .sum();
+        DeviceIntPoint::new(x, 0)
+    }
+
+    /// The part of the frame that WebRender renders, in device pixels. This is the
+    /// viewport of the window, except when webviews are rendered to textures and while
+    /// a screenshot is taken, which is of the size of its webview.
+    fn document_view(&self) -> DeviceIntRect {
+        if let Some(screenshot) = self.pending_screenshots.front() {
+            return DeviceIntRect::from_size(self.webview_viewport_size(screenshot.webview_id));
+        }
+        if self.composite_target != CompositeTarget::WebViewTextures {
+            return self.embedder_coordinates.get_viewport();
+        }
+        let size = self
+            .painted_webviews()
+            .into_iter()
+            .map(|id| self.webview_viewport_size(id))
+            .fold(DeviceIntSize::zero(), |frame, size| {
+                DeviceIntSize::new(frame.width + size.width, frame.height.max(size.height))
+            });
+        DeviceIntRect::from_size(size.max(DeviceIntSize::new(1, 1)))
+    }
+
+    fn pinch_zoom(&self, webview_id: TopLevelBrowsingContextId) -> PinchZoom {
+        self.webview_pinch_zooms
+            .get(&webview_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The pinch zoom that a webview is painted with, which screenshots don't get.
+    fn painting_pinch_zoom(&self, webview_id: TopLevelBrowsingContextId) -> PinchZoom {
+        if !self.pending_screenshots.is_empty() {
+            return PinchZoom::default();
+        }
+        self.pinch_zoom(webview_id)
+    }
+
+    fn gesture_settings(&self, webview_id: TopLevelBrowsingContextId) -> GestureSettings {
+        self.webview_gesture_settings
+            .get(&webview_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The webviews that pointer events can be routed to, topmost first, with their
+    /// viewports, which pinch zoomed webviews are clipped to when they are painted.
+    fn input_routes(&self) -> Vec<InputRoute> {
+        self.painted_webviews()
+            .into_iter()
+            .rev()
+            .map(|webview_id| {
+                let size = self.webview_viewport_size(webview_id).to_f32();
+                let origin = self.webview_origin(webview_id).to_f32();
+                InputRoute {
+                    webview_id,
+                    rect: DeviceRect::new(origin, size),
+                }
+            })
+            .collect()
+    }
+
+    fn hit_test_at_device_point_in_webview(
+        &self,
+        point: DevicePoint,
+        webview_id: TopLevelBrowsingContextId,
+    ) -> Option<CompositorHitTestResult> {
         let dppx = self.page_zoom * self.hidpi_factor();
         let scaled_point = (point / dppx).to_untyped();
         let world_point = WorldPoint::from_untyped(scaled_point);
-        return self.hit_test_at_point(world_point);
+        self.hit_test_at_point_in_webview(world_point, webview_id)
     }
 
-    fn hit_test_at_point(&self, point: WorldPoint) -> Option<CompositorHitTestResult> {
-        return self
-            .hit_test_at_point_with_flags_and_pipeline(point, HitTestFlags::empty(), None)
-            .first()
-            .cloned();
+    /// The topmost item at `point` that belongs to a document of the webview, skipping
+    /// the items of the webviews painted over it, which the webview was routed around.
+    fn hit_test_at_point_in_webview(
+        &self,
+        point: WorldPoint,
+        webview_id: TopLevelBrowsingContextId,
+    ) -> Option<CompositorHitTestResult> {
+        self.hit_test_at_point_with_flags_and_pipeline(point, HitTestFlags::FIND_ALL, None)
+            .into_iter()
+            .find(|result| {
+                self.pipeline(result.pipeline_id)
+                    .map(|pipeline| pipeline.top_level_browsing_context_id) ==
+                    Some(webview_id)
+            })
     }
 
     fn hit_test_at_point_with_flags_and_pipeline(
@@ -1239,7 +1864,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn dispatch_mouse_window_move_event_class(&mut self, cursor: DevicePoint) {
-        let result = match self.hit_test_at_device_point(cursor) {
+        let routes = self.input_routes();
+        if let Some(route) = self.input_router.route_mouse_move(&routes, cursor) {
+            self.send_mouse_move_event(route.webview_id, cursor);
+        }
+    }
+
+    fn send_mouse_move_event(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        cursor: DevicePoint,
+    ) {
+        let result = match self.hit_test_at_device_point_in_webview(cursor, webview_id) {
             Some(result) => result,
             None => return,
         };
@@ -1253,12 +1889,28 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn send_touch_event(
-        &self,
+        &mut self,
+        event_type: TouchEventType,
+        identifier: TouchId,
+        point: DevicePoint,
+    ) {
+        let routes = self.input_routes();
+        let route = self
+            .input_router
+            .route_touch(&routes, event_type, identifier, point);
+        if let Some(route) = route {
+            self.send_touch_event_to_webview(route.webview_id, event_type, identifier, point);
+        }
+    }
+
+    fn send_touch_event_to_webview(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
         event_type: TouchEventType,
         identifier: TouchId,
         point: DevicePoint,
     ) {
-        if let Some(result) = self.hit_test_at_device_point(point) {
+        if let Some(result) = self.hit_test_at_device_point_in_webview(point, webview_id) {
             let event = TouchEvent(
                 event_type,
                 identifier,
@@ -1273,7 +1925,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn send_wheel_event(&mut self, delta: WheelDelta, point: DevicePoint) {
-        if let Some(result) = self.hit_test_at_device_point(point) {
+        if let Some(route) = input_routing::route_at_point(&self.input_routes(), point) {
+            self.send_wheel_event_to_webview(route.webview_id, delta, point);
+        }
+    }
+
+    fn send_wheel_event_to_webview(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        delta: WheelDelta,
+        point: DevicePoint,
+    ) {
+        if let Some(result) = self.hit_test_at_device_point_in_webview(point, webview_id) {
             let event = WheelEvent(delta, result.point_in_viewport, Some(result.node));
             let msg = ConstellationMsg::ForwardEvent(result.pipeline_id, event);
             if let Err(e) = self.constellation_chan.send(msg) {
@@ -1282,6 +1945,49 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Dispatch an input event that the embedder injected into a webview, with a point
+    /// in its viewport, other than a key event, which goes to the constellation.
+    pub fn on_injected_input_event(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        event: InputEvent,
+    ) {
+        let origin = self.webview_origin(webview_id).to_f32().to_vector();
+        match event {
+            InputEvent::Keyboard(..) => {},
+            InputEvent::MouseButton(event_type, button, point, modifiers) => {
+                self.send_mouse_button_event(
+                    webview_id,
+                    event_type,
+                    button,
+                    point + origin,
+                    modifiers,
+                );
+            },
+            InputEvent::MouseMove(point) => self.send_mouse_move_event(webview_id, point + origin),
+            InputEvent::Touch(event_type, identifier, point) => {
+                self.send_touch_event_to_webview(
+                    webview_id,
+                    event_type,
+                    identifier,
+                    point + origin,
+                );
+            },
+            InputEvent::Wheel(delta, point) => {
+                self.send_wheel_event_to_webview(webview_id, delta, point + origin);
+            },
+            InputEvent::Scroll(scroll_location, point) => {
+                self.pending_scroll_zoom_events
+                    .push(ScrollZoomEvent::Scroll(ScrollEvent {
+                        scroll_location,
+                        cursor: (point + origin).round().to_i32(),
+                        event_count: 1,
+                        webview_id: Some(webview_id),
+                    }));
+            },
+        }
+    }
+
     pub fn on_touch_event(
         &mut self,
         event_type: TouchEventType,
@@ -1297,32 +2003,20 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn on_touch_down(&mut self, identifier: TouchId, point: DevicePoint) {
+        if self.touch_handler.active_touch_points.is_empty() {
+            self.fling = None;
+            self.touch_webview = input_routing::route_at_point(&self.input_routes(), point)
+                .map(|route| route.webview_id);
+        }
         self.touch_handler.on_touch_down(identifier, point);
         self.send_touch_event(TouchEventType::Down, identifier, point);
     }
 
     fn on_touch_move(&mut self, identifier: TouchId, point: DevicePoint) {
         match self.touch_handler.on_touch_move(identifier, point) {
-            TouchAction::Scroll(delta) => self.on_scroll_window_event(
-                ScrollLocation::Delta(LayoutVector2D::from_untyped(delta.to_untyped())),
-                point.cast(),
-            ),
-            TouchAction::Zoom(magnification, scroll_delta) => {
-                let cursor = Point2D::new(-1, -1); // Make sure this hits the base layer.
-
-                // The order of these events doesn't matter, because zoom is handled by
-                // a root display list and the scroll event here is handled by the scroll
-                // applied to the content display list.
-                self.pending_scroll_zoom_events
-                    .push(ScrollZoomEvent::PinchZoom(magnification));
-                self.pending_scroll_zoom_events
-                    .push(ScrollZoomEvent::Scroll(ScrollEvent {
-                        scroll_location: ScrollLocation::Delta(LayoutVector2D::from_untyped(
-                            scroll_delta.to_untyped(),
-                        )),
-                        cursor: cursor,
-                        event_count: 1,
-                    }));
+            TouchAction::Scroll(delta) => self.on_touch_gesture(1.0, point, delta),
+            TouchAction::Zoom(magnification, focus, scroll_delta) => {
+                self.on_touch_gesture(magnification, focus, scroll_delta)
             },
             TouchAction::DispatchEvent => {
                 self.send_touch_event(TouchEventType::Move, identifier, point);
@@ -1334,11 +2028,57 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     fn on_touch_up(&mut self, identifier: TouchId, point: DevicePoint) {
         self.send_touch_event(TouchEventType::Up, identifier, point);
 
-        if let TouchAction::Click = self.touch_handler.on_touch_up(identifier, point) {
-            self.simulate_mouse_click(point);
+        match self.touch_handler.on_touch_up(identifier, point) {
+            TouchAction::Click => self.simulate_mouse_click(point),
+            TouchAction::DoubleTap(point) => self.on_double_tap(point),
+            TouchAction::Fling(velocity) => {
+                let webview_id = match self.touch_webview {
+                    Some(webview_id) => webview_id,
+                    None => return,
+                };
+                if self.gesture_settings(webview_id).fling {
+                    self.fling = Some((webview_id, Fling::new(point, velocity)));
+                    self.event_loop_waker.wake();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Pinch zoom and pan the webview that the current touch sequence started in.
+    fn on_touch_gesture(&mut self, magnification: f32, focus: DevicePoint, pan: DeviceVector2D) {
+        if let Some(webview_id) = self.touch_webview {
+            self.pending_scroll_zoom_events
+                .push(ScrollZoomEvent::PinchZoom(PinchZoomEvent {
+                    webview_id,
+                    magnification,
+                    focus,
+                    pan,
+                }));
         }
     }
 
+    /// Zoom the webview that was double tapped in to the pinch zoom of its gesture settings
+    /// about the point of the tap, or back out if it is zoomed. Double taps are clicks in
+    /// webviews that don't zoom on them.
+    fn on_double_tap(&mut self, point: DevicePoint) {
+        let webview_id = match self.touch_webview {
+            Some(webview_id) => webview_id,
+            None => return,
+        };
+        let double_tap_zoom = match self.gesture_settings(webview_id).double_tap_zoom {
+            Some(zoom) => zoom,
+            None => return self.simulate_mouse_click(point),
+        };
+        let factor = self.pinch_zoom(webview_id).factor;
+        let target = if (factor - 1.0).abs() < 0.01 {
+            double_tap_zoom
+        } else {
+            1.0
+        };
+        self.on_touch_gesture(target / factor, point, DeviceVector2D::zero());
+    }
+
     fn on_touch_cancel(&mut self, identifier: TouchId, point: DevicePoint) {
         // Send the event to script.
         self.touch_handler.on_touch_cancel(identifier, point);
@@ -1381,65 +2121,81 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 scroll_location: scroll_location,
                 cursor,
                 event_count: 1,
+                webview_id: None,
             }));
     }
 
     fn process_pending_scroll_events(&mut self) {
         // Batch up all scroll events into one, or else we'll do way too much painting.
         let mut combined_scroll_event: Option<ScrollEvent> = None;
-        let mut combined_magnification = 1.0;
-        for scroll_event in self.pending_scroll_zoom_events.drain(..) {
-            match scroll_event {
-                ScrollZoomEvent::PinchZoom(magnification) => {
-                    combined_magnification *= magnification
-                },
-                ScrollZoomEvent::Scroll(scroll_event_info) => {
-                    let combined_event = match combined_scroll_event.as_mut() {
-                        None => {
-                            combined_scroll_event = Some(scroll_event_info);
-                            continue;
-                        },
-                        Some(combined_event) => combined_event,
-                    };
-
-                    match (
-                        combined_event.scroll_location,
-                        scroll_event_info.scroll_location,
-                    ) {
-                        (ScrollLocation::Delta(old_delta), ScrollLocation::Delta(new_delta)) => {
-                            // Mac OS X sometimes delivers scroll events out of vsync during a
-                            // fling. This causes events to get bunched up occasionally, causing
-                            // nasty-looking "pops". To mitigate this, during a fling we average
-                            // deltas instead of summing them.
-                            let old_event_count = Scale::new(combined_event.event_count as f32);
-                            combined_event.event_count += 1;
-                            let new_event_count = Scale::new(combined_event.event_count as f32);
-                            combined_event.scroll_location = ScrollLocation::Delta(
-                                (old_delta * old_event_count + new_delta) / new_event_count,
-                            );
-                        },
-                        (ScrollLocation::Start, _) | (ScrollLocation::End, _) => {
-                            // Once we see Start or End, we shouldn't process any more events.
-                            break;
-                        },
-                        (_, ScrollLocation::Start) | (_, ScrollLocation::End) => {
-                            // If this is an event which is scrolling to the start or end of the page,
-                            // disregard other pending events and exit the loop.
-                            *combined_event = scroll_event_info;
-                            break;
-                        },
+        let mut zoom_changed = false;
+        for scroll_event in std::mem::take(&mut self.pending_scroll_zoom_events) {
+            let scroll_event_info = match scroll_event {
+                ScrollZoomEvent::PinchZoom(pinch_zoom_event) => {
+                    // Pinch zooms are applied right away, since the part of their pan that
+                    // is left depends on where the visual viewport is.
+                    let (changed, scroll_event_info) = self.pinch_zoom_webview(pinch_zoom_event);
+                    zoom_changed |= changed;
+                    match scroll_event_info {
+                        Some(scroll_event_info) => scroll_event_info,
+                        None => continue,
                     }
                 },
+                ScrollZoomEvent::Scroll(scroll_event_info) => scroll_event_info,
+            };
+            let combined_event = match combined_scroll_event.as_mut() {
+                None => {
+                    combined_scroll_event = Some(scroll_event_info);
+                    continue;
+                },
+                Some(combined_event) => combined_event,
+            };
+
+            match (
+                combined_event.scroll_location,
+                scroll_event_info.scroll_location,
+            ) {
+                (ScrollLocation::Delta(old_delta), ScrollLocation::Delta(new_delta)) => {
+                    // Mac OS X sometimes delivers scroll events out of vsync during a
+                    // fling. This causes events to get bunched up occasionally, causing
+                    // nasty-looking "pops". To mitigate this, during a fling we average
+                    // deltas instead of summing them.
+                    let old_event_count = Scale::new(combined_event.event_count as f32);
+                    combined_event.event_count += 1;
+                    let new_event_count = Scale::new(combined_event.event_count as f32);
+                    combined_event.scroll_location = ScrollLocation::Delta(
+                        (old_delta * old_event_count + new_delta) / new_event_count,
+                    );
+                },
+                (ScrollLocation::Start, _) | (ScrollLocation::End, _) => {
+                    // Once we see Start or End, we shouldn't process any more events.
+                    break;
+                },
+                (_, ScrollLocation::Start) | (_, ScrollLocation::End) => {
+                    // If this is an event which is scrolling to the start or end of the page,
+                    // disregard other pending events and exit the loop.
+                    *combined_event = scroll_event_info;
+                    break;
+                },
             }
         }
 
-        let zoom_changed =
-            self.set_pinch_zoom_level(self.pinch_zoom_level() * combined_magnification);
         let scroll_result = combined_scroll_event.and_then(|combined_event| {
+            let webview_id = match combined_event.webview_id {
+                Some(webview_id) => webview_id,
+                None => {
+                    input_routing::route_at_point(
+                        &self.input_routes(),
+                        combined_event.cursor.to_f32(),
+                    )?
+                    .webview_id
+                },
+            };
             let cursor = (combined_event.cursor.to_f32() / self.scale).to_untyped();
             self.scroll_node_at_world_point(
                 WorldPoint::from_untyped(cursor),
                 combined_event.scroll_location,
+                webview_id,
             )
         });
         if !zoom_changed && scroll_result.is_none() {
@@ -1462,14 +2218,62 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             .send_transaction(self.webrender_document, transaction);
     }
 
-    /// Perform a hit test at the given [`WorldPoint`] and apply the [`ScrollLocation`]
-    /// scrolling to the applicable scroll node under that point. If a scroll was
+    /// Magnify a webview about the focus of a pinch zoom event, then pan its visual
+    /// viewport, within the limits of its gesture settings. Returns whether its pinch zoom
+    /// changed, and the scroll of its document by the part of the pan that the visual
+    /// viewport couldn't follow.
+    fn pinch_zoom_webview(&mut self, event: PinchZoomEvent) -> (bool, Option<ScrollEvent>) {
+        let settings = self.gesture_settings(event.webview_id);
+        let size = self.webview_viewport_size(event.webview_id).to_f32();
+        let old_zoom = self.pinch_zoom(event.webview_id);
+        let factor = (old_zoom.factor * event.magnification)
+            .min(settings.max_zoom)
+            .max(settings.min_zoom);
+        let max_offset =
+            DeviceVector2D::new(size.width * (factor - 1.0), size.height * (factor - 1.0))
+                .max(DeviceVector2D::zero());
+        let clamp = |offset: DeviceVector2D| offset.max(DeviceVector2D::zero()).min(max_offset);
+
+        // Keep the part of the webview under the focus under it.
+        let focus = event.focus - self.webview_origin(event.webview_id).to_f32();
+        let offset = clamp((old_zoom.offset + focus) * (factor / old_zoom.factor) - focus);
+        let panned_offset = offset - event.pan;
+        let new_zoom = PinchZoom {
+            factor,
+            offset: clamp(panned_offset),
+        };
+        if new_zoom == PinchZoom::default() {
+            self.webview_pinch_zooms.remove(&event.webview_id);
+        } else {
+            self.webview_pinch_zooms.insert(event.webview_id, new_zoom);
+        }
+
+        // The document is scrolled in the pixels of the webview before it is magnified.
+        let remaining_pan = (new_zoom.offset - panned_offset) / factor;
+        let scroll_event = if remaining_pan == DeviceVector2D::zero() {
+            None
+        } else {
+            Some(ScrollEvent {
+                scroll_location: ScrollLocation::Delta(LayoutVector2D::from_untyped(
+                    remaining_pan.to_untyped(),
+                )),
+                cursor: event.focus.round().to_i32(),
+                event_count: 1,
+                webview_id: Some(event.webview_id),
+            })
+        };
+        (new_zoom != old_zoom, scroll_event)
+    }
+
+    /// Perform a hit test at the given [`WorldPoint`] in a webview and apply the
+    /// [`ScrollLocation`] scrolling to the applicable scroll node under that point. If a scroll was
     /// performed, returns the [`PipelineId`] of the node scrolled, the id, and the final
     /// scroll delta.
     fn scroll_node_at_world_point(
         &mut self,
         cursor: WorldPoint,
         scroll_location: ScrollLocation,
+        webview_id: TopLevelBrowsingContextId,
     ) -> Option<(PipelineId, ExternalScrollId, LayoutVector2D)> {
         let scroll_location = match scroll_location {
             ScrollLocation::Delta(delta) => {
@@ -1482,7 +2286,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             ScrollLocation::Start | ScrollLocation::End => scroll_location,
         };
 
-        let hit_test_result = match self.hit_test_at_point(cursor) {
+        let hit_test_result = match self.hit_test_at_point_in_webview(cursor, webview_id) {
             Some(result) => result,
             None => return None,
         };
@@ -1563,6 +2367,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.page_zoom * self.hidpi_factor()
     }
 
+    /// How much to scale the content of a webview, for it to be painted with its own
+    /// device pixel ratio rather than the one of the window.
+    fn webview_scale(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) -> f32 {
+        match self
+            .webview_device_pixel_ratios
+            .get(&top_level_browsing_context_id)
+        {
+            Some(ratio) => ratio.get() / self.device_pixels_per_page_px().get(),
+            None => 1.,
+        }
+    }
+
     fn update_zoom_transform(&mut self) {
         let scale = self.device_pixels_per_page_px();
         self.scale = Scale::new(scale.get());
@@ -1595,11 +2411,36 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             .send_transaction(self.webrender_document, txn);
     }
 
-    /// Simulate a pinch zoom
+    /// Simulate a pinch zoom about the cursor, of the webview under it.
     pub fn on_pinch_zoom_window_event(&mut self, magnification: f32) {
-        // TODO: Scroll to keep the center in view?
+        let webview_id = input_routing::route_at_point(&self.input_routes(), self.cursor_pos)
+            .map_or(
+                self.root_content_pipeline.top_level_browsing_context_id,
+                |route| route.webview_id,
+            );
         self.pending_scroll_zoom_events
-            .push(ScrollZoomEvent::PinchZoom(magnification));
+            .push(ScrollZoomEvent::PinchZoom(PinchZoomEvent {
+                webview_id,
+                magnification,
+                focus: self.cursor_pos,
+                pan: DeviceVector2D::zero(),
+            }));
+    }
+
+    /// Change the gesture settings of a webview, and zoom it into their limits.
+    pub fn set_webview_gesture_settings(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        settings: GestureSettings,
+    ) {
+        self.webview_gesture_settings.insert(webview_id, settings);
+        self.pending_scroll_zoom_events
+            .push(ScrollZoomEvent::PinchZoom(PinchZoomEvent {
+                webview_id,
+                magnification: 1.0,
+                focus: self.webview_origin(webview_id).to_f32(),
+                pan: DeviceVector2D::zero(),
+            }));
     }
 
     fn send_scroll_positions_to_layout_for_pipeline(&self, pipeline_id: &PipelineId) {
@@ -1734,8 +2575,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 NotReadyToPaint::WaitingOnConstellation,
             ));
         }
+        // The scene only has the webview of the screenshot, which mustn't be composited.
+        if !self.pending_screenshots.is_empty() {
+            return Err(UnableToComposite::NotReadyToPaintImage(
+                NotReadyToPaint::TakingScreenshot,
+            ));
+        }
 
-        let size = self.embedder_coordinates.framebuffer.to_u32();
+        let frame_size = match target {
+            CompositeTarget::WebViewTextures => self.document_view().size,
+            _ => self.embedder_coordinates.framebuffer,
+        };
+        let size = frame_size.to_u32();
 
         if let Err(err) = self.rendering_context.make_gl_context_current() {
             warn!("Failed to make GL context current: {:?}", err);
@@ -1768,7 +2619,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             }
         }
 
-        if use_offscreen_framebuffer {
+        if target == CompositeTarget::WebViewTextures {
+            self.bind_webview_texture_framebuffer(frame_size);
+        } else if use_offscreen_framebuffer {
             self.next_offscreen_framebuffer
                 .get_or_init(|| {
                     RenderTargetInfo::new(
@@ -1798,13 +2651,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             || {
                 trace!("Compositing");
 
-                let size =
-                    DeviceIntSize::from_untyped(self.embedder_coordinates.framebuffer.to_untyped());
-
                 // Paint the scene.
                 // TODO(gw): Take notice of any errors the renderer returns!
                 self.clear_background();
-                self.webrender.render(size, 0 /* buffer_age */).ok();
+                self.webrender.render(frame_size, 0 /* buffer_age */).ok();
             },
         );
 
@@ -1847,6 +2697,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             }
         }
 
+        self.start_frame_readbacks(frame_size);
+
         let (x, y, width, height) = if let Some(rect) = rect {
             let rect = self.device_pixels_per_page_px().transform_rect(&rect);
 
@@ -1864,6 +2716,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         let rv = match target {
             CompositeTarget::Window => None,
+            CompositeTarget::WebViewTextures => {
+                self.copy_to_webview_render_targets(frame_size);
+                None
+            },
             CompositeTarget::Fbo => {
                 self.next_offscreen_framebuffer
                     .get()
@@ -1945,6 +2801,17 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         self.process_animations(true);
 
+        // Until the content changes nothing will be painted, which leaves script threads
+        // time to collect garbage.
+        if !self.animations_active() {
+            if let Err(e) = self
+                .constellation_chan
+                .send(ConstellationMsg::CompositorIdle)
+            {
+                warn!("Sending CompositorIdle to constellation failed ({:?}).", e);
+            }
+        }
+
         Ok(rv)
     }
 
@@ -1956,6 +2823,419 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             .map(|info| info.framebuffer_id())
     }
 
+    /// Send WebRender the scene with only the webview of the oldest pending screenshot,
+    /// counting the frames before it, unless the display list of its layout hasn't
+    /// arrived yet.
+    fn send_screenshot_scene(&mut self) {
+        let screenshot = match self.pending_screenshots.front() {
+            Some(screenshot) => screenshot,
+            None => return,
+        };
+        if let Some(layout) = screenshot.layout {
+            let epoch = self
+                .pipeline_details
+                .get(&screenshot.pipeline_id)
+                .and_then(|details| details.most_recent_display_list_epoch);
+            if epoch.map_or(true, |epoch| epoch.0 < layout.epoch.0) {
+                return;
+            }
+        }
+
+        let mut txn = Transaction::new();
+        self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+        self.generate_frame(&mut txn);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        let pending_frames = self.pending_frames;
+        if let Some(screenshot) = self.pending_screenshots.front_mut() {
+            screenshot.frames_until_ready = Some(pending_frames);
+        }
+    }
+
+    /// Take the oldest pending screenshot, whose frame is ready, then send the scene of
+    /// the next one or the one with every painted webview again.
+    fn take_screenshot(&mut self) {
+        let result = self.render_screenshot();
+        if let Some(screenshot) = self.pending_screenshots.pop_front() {
+            if let Err(error) = screenshot.sender.send(result) {
+                warn!("Sending screenshot failed ({:?}).", error);
+            }
+        }
+        if !self.pending_screenshots.is_empty() {
+            self.send_screenshot_scene();
+        } else {
+            let mut txn = Transaction::new();
+            self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+            self.generate_frame(&mut txn);
+            self.webrender_api
+                .send_transaction(self.webrender_document, txn);
+        }
+    }
+
+    fn render_screenshot(&mut self) -> Result<Screenshot, ScreenshotError> {
+        let screenshot = self
+            .pending_screenshots
+            .front()
+            .ok_or(ScreenshotError::Failed)?;
+        let webview_id = screenshot.webview_id;
+        let size = self.webview_viewport_size(webview_id);
+        let device_pixel_ratio = self
+            .webview_device_pixel_ratios
+            .get(&webview_id)
+            .copied()
+            .unwrap_or_else(|| self.device_pixels_per_page_px());
+        let rect = screenshot::device_rect(screenshot.rect, device_pixel_ratio, size)
+            .ok_or(ScreenshotError::Failed)?;
+
+        if let Err(err) = self.rendering_context.make_gl_context_current() {
+            warn!("Failed to make GL context current: {:?}", err);
+            return Err(ScreenshotError::Failed);
+        }
+        self.webrender.update();
+        let framebuffer = RenderTargetInfo::new_rgba(
+            self.webrender_gl.clone(),
+            FramebufferUintLength::new(size.width as u32),
+            FramebufferUintLength::new(size.height as u32),
+        );
+        framebuffer.bind();
+        let color = if self.transparent_webviews.contains(&webview_id) {
+            [0.; 4]
+        } else {
+            servo_config::pref!(shell.background_color.rgba)
+        };
+        let gl = &self.webrender_gl;
+        gl.clear_color(
+            color[0] as f32,
+            color[1] as f32,
+            color[2] as f32,
+            color[3] as f32,
+        );
+        gl.clear(gleam::gl::COLOR_BUFFER_BIT);
+        if let Err(errors) = self.webrender.render(size, 0 /* buffer_age */) {
+            warn!("Rendering screenshot failed ({:?}).", errors);
+            return Err(ScreenshotError::Failed);
+        }
+        // A pipeline that WebRender has no display list of has no document yet, or was
+        // closed since.
+        let pipeline_id = self.pending_screenshots[0].pipeline_id;
+        if self
+            .webrender
+            .current_epoch(self.webrender_document, pipeline_id.to_webrender())
+            .is_none()
+        {
+            return Err(ScreenshotError::NoSuchWebView);
+        }
+
+        // OpenGL has the origin at the bottom left.
+        let image = framebuffer.read_back_rgba_from_gpu(
+            rect.origin.x,
+            size.height - rect.max_y(),
+            FramebufferUintLength::new(rect.size.width as u32),
+            FramebufferUintLength::new(rect.size.height as u32),
+        );
+        let screenshot = &self.pending_screenshots[0];
+        screenshot::finish(
+            image,
+            device_pixel_ratio,
+            screenshot.scale_mode,
+            screenshot.format,
+        )
+    }
+
+    /// Render a webview into a framebuffer of the embedder when compositing to
+    /// [`CompositeTarget::WebViewTextures`], or stop rendering it if `target` is `None`.
+    pub fn set_webview_render_target(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        target: Option<WebViewRenderTarget>,
+    ) {
+        match target {
+            Some(target) => self.webview_render_targets.insert(webview_id, target),
+            None => self.webview_render_targets.remove(&webview_id),
+        };
+        let mut txn = Transaction::new();
+        self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+        self.generate_frame(&mut txn);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+    }
+
+    /// Bind the framebuffer that webviews are composited into before they are copied into
+    /// their render targets, creating it again if the webviews changed size.
+    fn bind_webview_texture_framebuffer(&mut self, size: DeviceIntSize) {
+        let is_current_size =
+            matches!(self.webview_texture_framebuffer, Some((old_size, _)) if old_size == size);
+        if !is_current_size {
+            let framebuffer = RenderTargetInfo::new(
+                self.webrender_gl.clone(),
+                FramebufferUintLength::new(size.width as u32),
+                FramebufferUintLength::new(size.height as u32),
+            );
+            self.webview_texture_framebuffer = Some((size, framebuffer));
+        }
+        if let Some((_, ref framebuffer)) = self.webview_texture_framebuffer {
+            framebuffer.bind();
+        }
+    }
+
+    /// Copy the webviews that have a render target out of the frame that was just
+    /// composited, telling the embedder about each of them.
+    fn copy_to_webview_render_targets(&mut self, frame_size: DeviceIntSize) {
+        let framebuffer_id = match self.webview_texture_framebuffer {
+            Some((_, ref framebuffer)) => framebuffer.framebuffer_id(),
+            None => return,
+        };
+        for webview_id in self.painted_webviews() {
+            let origin = self.webview_origin(webview_id);
+            let size = self.webview_viewport_size(webview_id);
+            // WebRender doesn't tell which parts of the frame changed, so all of the
+            // webview is damaged.
+            let damage = DeviceIntRect::from_size(size);
+            if let Some(target) = self.webview_render_targets.get(&webview_id).copied() {
+                self.copy_webview(
+                    framebuffer_id,
+                    target.framebuffer_id,
+                    origin,
+                    size,
+                    frame_size,
+                );
+                self.window.webview_frame_rendered(WebViewFrame {
+                    webview_id,
+                    target,
+                    damage,
+                    origin,
+                });
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(frame) = self.dmabuf_exporter.next_frame(webview_id, size) {
+                self.send_exported_buffers();
+                self.copy_webview(
+                    framebuffer_id,
+                    frame.framebuffer_id,
+                    origin,
+                    size,
+                    frame_size,
+                );
+                // The consumer of the buffer waits for the commands that were flushed.
+                self.webrender_gl.flush();
+                self.window
+                    .webview_frame_exported(webview_id, frame.buffer, damage);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        self.send_exported_buffers();
+        self.present_webview_surfaces(framebuffer_id, frame_size);
+        self.webrender_gl
+            .bind_framebuffer(gleam::gl::FRAMEBUFFER, 0);
+        self.assert_no_gl_error();
+    }
+
+    /// Present the webviews that have a native surface in it, and tell the embedder about
+    /// the surfaces of the webviews that were shown or hidden.
+    fn present_webview_surfaces(
+        &mut self,
+        framebuffer_id: gleam::gl::GLuint,
+        frame_size: DeviceIntSize,
+    ) {
+        let painted_webviews = self.painted_webviews();
+        let webview_ids: Vec<_> = self.webview_surfaces.keys().copied().collect();
+        for webview_id in webview_ids {
+            let visible = painted_webviews.contains(&webview_id);
+            if visible {
+                let origin = self.webview_origin(webview_id);
+                let size = self.webview_viewport_size(webview_id);
+                let surface = self
+                    .webview_surfaces
+                    .get_mut(&webview_id)
+                    .and_then(|surface| surface.surface.take());
+                if let Some(surface) = surface {
+                    let (surface, result) =
+                        self.rendering_context
+                            .draw_to_surface(surface, |target_framebuffer_id| {
+                                self.copy_webview(
+                                    framebuffer_id,
+                                    target_framebuffer_id,
+                                    origin,
+                                    size,
+                                    frame_size,
+                                )
+                            });
+                    if let Err(error) = result {
+                        warn!("Presenting {webview_id:?} in its surface failed ({error:?})");
+                    }
+                    if let Some(webview_surface) = self.webview_surfaces.get_mut(&webview_id) {
+                        webview_surface.surface = Some(surface);
+                    }
+                }
+            }
+            let changed = match self.webview_surfaces.get_mut(&webview_id) {
+                Some(webview_surface) if webview_surface.shown != visible => {
+                    webview_surface.shown = visible;
+                    true
+                },
+                _ => false,
+            };
+            if changed {
+                self.window
+                    .webview_surface_visibility_changed(webview_id, visible);
+            }
+        }
+    }
+
+    /// Present a webview in a native surface of its own when compositing to
+    /// [`CompositeTarget::WebViewTextures`], or stop presenting it if `native_widget` is
+    /// `None`.
+    #[allow(unsafe_code)]
+    #[allow(clippy::not_unsafe_ptr_arg_deref)] // It has an unsafe block inside
+    pub fn attach_webview_surface(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        native_widget: Option<(*mut c_void, DeviceIntSize)>,
+    ) {
+        if let Some(surface) = self
+            .webview_surfaces
+            .remove(&webview_id)
+            .and_then(|webview_surface| webview_surface.surface)
+        {
+            if let Err(error) = self.rendering_context.destroy_surface(surface) {
+                warn!("Destroying the surface of {webview_id:?} failed ({error:?})");
+            }
+        }
+        if let Some((native_widget, size)) = native_widget {
+            let connection = self.rendering_context.connection();
+            let native_widget = unsafe {
+                connection.create_native_widget_from_ptr(native_widget, size.to_untyped())
+            };
+            match self.rendering_context.create_widget_surface(native_widget) {
+                Ok(surface) => {
+                    self.webview_surfaces.insert(
+                        webview_id,
+                        WebViewSurface {
+                            surface: Some(surface),
+                            shown: false,
+                        },
+                    );
+                },
+                Err(error) => warn!("Creating a surface for {webview_id:?} failed ({error:?})"),
+            }
+        }
+
+        let mut txn = Transaction::new();
+        self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+        self.generate_frame(&mut txn);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+    }
+
+    /// Copy a webview of this size at `origin` in the frame that was just composited into
+    /// another framebuffer.
+    fn copy_webview(
+        &self,
+        framebuffer_id: gleam::gl::GLuint,
+        target_framebuffer_id: gleam::gl::GLuint,
+        origin: DeviceIntPoint,
+        size: DeviceIntSize,
+        frame_size: DeviceIntSize,
+    ) {
+        let gl = &self.webrender_gl;
+        // The frame is bottom up like any GL framebuffer, so the webviews are at its top.
+        let y = frame_size.height - origin.y - size.height;
+        gl.bind_framebuffer(gleam::gl::READ_FRAMEBUFFER, framebuffer_id);
+        gl.bind_framebuffer(gleam::gl::DRAW_FRAMEBUFFER, target_framebuffer_id);
+        gl.blit_framebuffer(
+            origin.x,
+            y,
+            origin.x + size.width,
+            y + size.height,
+            0,
+            0,
+            size.width,
+            size.height,
+            gleam::gl::COLOR_BUFFER_BIT,
+            gleam::gl::NEAREST,
+        );
+    }
+
+    /// Export the frames of a webview as DMA-BUFs when compositing to
+    /// [`CompositeTarget::WebViewTextures`], or stop exporting them if `formats` is `None`.
+    pub fn export_webview_frames(
+        &mut self,
+        webview_id: TopLevelBrowsingContextId,
+        formats: Option<Vec<DmabufFormat>>,
+    ) {
+        #[cfg(target_os = "linux")]
+        {
+            self.dmabuf_exporter.set_formats(webview_id, formats);
+            self.send_exported_buffers();
+        }
+        #[cfg(not(target_os = "linux"))]
+        warn!("Can't export the frames of {webview_id:?} in {formats:?} on this platform");
+
+        let mut txn = Transaction::new();
+        self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+        self.generate_frame(&mut txn);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+    }
+
+    /// Start or stop reading back the frames of a webview into memory.
+    pub fn read_back_webview_frames(&mut self, webview_id: TopLevelBrowsingContextId, read: bool) {
+        self.frame_reader.set_reading(webview_id, read);
+
+        let mut txn = Transaction::new();
+        self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+        self.generate_frame(&mut txn);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+    }
+
+    /// Start reading back the webviews whose frames are read back out of the frame that
+    /// was just composited into the bound framebuffer.
+    fn start_frame_readbacks(&mut self, frame_size: DeviceIntSize) {
+        let document_origin = self.document_view().origin.to_vector();
+        let frame_rect = DeviceIntRect::from_size(frame_size);
+        for webview_id in self.painted_webviews() {
+            if !self.frame_reader.is_read(webview_id) {
+                continue;
+            }
+            let origin = self.webview_origin(webview_id) + document_origin;
+            let rect = DeviceIntRect::new(origin, self.webview_viewport_size(webview_id))
+                .intersection(&frame_rect)
+                .unwrap_or_else(DeviceIntRect::zero);
+            self.frame_reader.read(webview_id, rect, frame_size.height);
+        }
+    }
+
+    /// Hand the frames whose readback finished to the embedder.
+    fn send_read_back_frames(&mut self) {
+        for frame in self.frame_reader.take_finished_frames() {
+            self.window.webview_frame_read_back(frame);
+        }
+        if self.frame_reader.has_frames_in_flight() {
+            self.event_loop_waker.wake();
+        }
+    }
+
+    /// Tell the embedder about the buffers that webviews started exporting their frames in.
+    #[cfg(target_os = "linux")]
+    fn send_exported_buffers(&mut self) {
+        for (webview_id, buffers) in self.dmabuf_exporter.take_results() {
+            self.window.webview_buffers_exported(webview_id, buffers);
+        }
+    }
+
+    /// Whether a webview is rendered to a texture when compositing to
+    /// [`CompositeTarget::WebViewTextures`].
+    fn is_rendered_to_texture(&self, webview_id: TopLevelBrowsingContextId) -> bool {
+        #[cfg(target_os = "linux")]
+        if self.dmabuf_exporter.is_exported(webview_id) {
+            return true;
+        }
+        self.webview_render_targets.contains_key(&webview_id) ||
+            self.webview_surfaces.contains_key(&webview_id) ||
+            self.frame_reader.is_read(webview_id)
+    }
+
     pub fn present(&mut self) {
         if let Err(err) = self.rendering_context.present() {
             warn!("Failed to present surface: {:?}", err);
@@ -1976,7 +3256,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.assert_gl_framebuffer_complete();
 
         // Set the viewport background based on prefs.
-        let viewport = self.embedder_coordinates.get_flipped_viewport();
+        let viewport = match self.composite_target {
+            CompositeTarget::WebViewTextures => self.document_view(),
+            _ => self.embedder_coordinates.get_flipped_viewport(),
+        };
         gl.scissor(
             viewport.origin.x,
             viewport.origin.y,
@@ -2023,7 +3306,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 CompositorMsg::NewWebRenderFrameReady(_) if found_recomposite_msg => {
                     // Only take one of duplicate NewWebRendeFrameReady messages, but do subtract
                     // one frame from the pending frames.
-                    self.pending_frames -= 1;
+                    self.count_ready_frame();
                 },
                 CompositorMsg::NewWebRenderFrameReady(_) => {
                     found_recomposite_msg = true;
@@ -2065,6 +3348,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         // The WebXR thread may make a different context current
         let _ = self.rendering_context.make_gl_context_current();
 
+        self.send_read_back_frames();
+        self.step_fling();
+
         if !self.pending_scroll_zoom_events.is_empty() {
             self.process_pending_scroll_events()
         }
@@ -2093,20 +3379,32 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// The pinch zoom of the root webview.
     pub fn pinch_zoom_level(&self) -> f32 {
-        self.viewport_zoom.get()
-    }
-
-    fn set_pinch_zoom_level(&mut self, mut zoom: f32) -> bool {
-        if let Some(min) = self.min_viewport_zoom {
-            zoom = f32::max(min.get(), zoom);
-        }
-        if let Some(max) = self.max_viewport_zoom {
-            zoom = f32::min(max.get(), zoom);
+        self.pinch_zoom(self.root_content_pipeline.top_level_browsing_context_id)
+            .factor
+    }
+
+    /// Pan the webview that is flung by how far the fling went since the last update, and
+    /// keep the event loop running until it stops.
+    fn step_fling(&mut self) {
+        let event = match self.fling.as_mut() {
+            Some((webview_id, fling)) => fling.step().map(|pan| PinchZoomEvent {
+                webview_id: *webview_id,
+                magnification: 1.0,
+                focus: fling.point,
+                pan,
+            }),
+            None => return,
+        };
+        match event {
+            Some(event) => {
+                self.pending_scroll_zoom_events
+                    .push(ScrollZoomEvent::PinchZoom(event));
+                self.event_loop_waker.wake();
+            },
+            None => self.fling = None,
         }
-
-        let old_zoom = std::mem::replace(&mut self.viewport_zoom, PinchZoomFactor::new(zoom));
-        old_zoom != self.viewport_zoom
     }
 
     pub fn toggle_webrender_debug(&mut self, option: WebRenderDebugOption) {
@@ -2129,6 +3427,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             .send_transaction(self.webrender_document, txn);
     }
 
+    /// Ask WebRender to free the textures and other resources it keeps around in case
+    /// they are needed again.
+    pub fn notify_memory_pressure(&self) {
+        self.webrender_api.notify_memory_pressure();
+    }
+
     pub fn capture_webrender(&mut self) {
         let capture_id = SystemTime::now()
             .duration_since(UNIX_EPOCH)
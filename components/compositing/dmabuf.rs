@@ -0,0 +1,381 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Export of the frames of webviews as DMA-BUFs, so that another process or a Wayland
+//! compositor can use them without copying. Each exported webview is copied into textures
+//! of its own, which are turned into EGL images whose memory is exported with
+//! `EGL_MESA_image_dma_buf_export`. Vulkan consumers can import these buffers as external
+//! memory with `VK_EXT_external_memory_dma_buf`.
+//!
+//! The driver chooses the format and modifier of the buffers, so the negotiation with the
+//! consumer consists of checking that it is one of those that the consumer can import.
+
+use std::collections::HashMap;
+use std::mem;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::rc::Rc;
+
+use gfx::rendering_context::RenderingContext;
+use gleam::gl::{self, Gl};
+use libc::c_void;
+use log::warn;
+use msg::constellation_msg::WebViewId;
+use webrender_api::units::DeviceIntSize;
+
+use crate::windowing::{DmabufBuffer, DmabufExportError, DmabufFormat, DmabufPlane};
+
+/// How many buffers the frames of a webview are exported in, so that the consumer can
+/// read a frame while the next one is copied.
+const BUFFER_COUNT: usize = 2;
+
+/// The most planes that a buffer can have.
+const MAX_PLANES: usize = 4;
+
+type EGLDisplay = *mut c_void;
+type EGLContext = *mut c_void;
+type EGLImage = *mut c_void;
+type EGLBoolean = u32;
+type EGLenum = u32;
+type EGLint = i32;
+
+const EGL_GL_TEXTURE_2D_KHR: EGLenum = 0x30B1;
+const EGL_NONE: EGLint = 0x3038;
+
+/// The EGL functions used to export buffers, which are all looked up at runtime since only
+/// some EGL implementations have the extensions that they belong to.
+struct Egl {
+    get_current_display: unsafe extern "C" fn() -> EGLDisplay,
+    get_current_context: unsafe extern "C" fn() -> EGLContext,
+    create_image: unsafe extern "C" fn(
+        EGLDisplay,
+        EGLContext,
+        EGLenum,
+        *mut c_void,
+        *const EGLint,
+    ) -> EGLImage,
+    destroy_image: unsafe extern "C" fn(EGLDisplay, EGLImage) -> EGLBoolean,
+    export_query:
+        unsafe extern "C" fn(EGLDisplay, EGLImage, *mut i32, *mut i32, *mut u64) -> EGLBoolean,
+    export: unsafe extern "C" fn(
+        EGLDisplay,
+        EGLImage,
+        *mut i32,
+        *mut EGLint,
+        *mut EGLint,
+    ) -> EGLBoolean,
+}
+
+impl Egl {
+    fn load(rendering_context: &RenderingContext) -> Option<Self> {
+        let function = |name: &str| {
+            let address = rendering_context.get_proc_address(name);
+            if address.is_null() {
+                None
+            } else {
+                Some(address)
+            }
+        };
+        unsafe {
+            Some(Self {
+                get_current_display: mem::transmute(function("eglGetCurrentDisplay")?),
+                get_current_context: mem::transmute(function("eglGetCurrentContext")?),
+                create_image: mem::transmute(function("eglCreateImageKHR")?),
+                destroy_image: mem::transmute(function("eglDestroyImageKHR")?),
+                export_query: mem::transmute(function("eglExportDMABUFImageQueryMESA")?),
+                export: mem::transmute(function("eglExportDMABUFImageMESA")?),
+            })
+        }
+    }
+}
+
+/// A texture that frames of a webview are copied into, whose memory is exported.
+struct ExportedTexture {
+    texture_id: gl::GLuint,
+    framebuffer_id: gl::GLuint,
+    image: EGLImage,
+}
+
+struct ExportedWebView {
+    /// The formats that the consumer can import.
+    formats: Vec<DmabufFormat>,
+    size: DeviceIntSize,
+    textures: Vec<ExportedTexture>,
+    /// The index of the texture that the next frame is copied into.
+    next_texture: usize,
+    /// Whether exporting failed, in which case it isn't tried again until the formats or
+    /// the size of the webview change.
+    failed: bool,
+}
+
+/// A buffer that the next frame of a webview is to be copied into.
+pub struct ExportedFrame {
+    pub framebuffer_id: gl::GLuint,
+    /// The index of the buffer among those announced to the embedder.
+    pub buffer: usize,
+}
+
+pub struct DmabufExporter {
+    gl: Rc<dyn Gl>,
+    rendering_context: RenderingContext,
+    egl: Option<Egl>,
+    webviews: HashMap<WebViewId, ExportedWebView>,
+    /// The buffers that were exported since they were last taken, or why they couldn't be.
+    results: Vec<(WebViewId, Result<Vec<DmabufBuffer>, DmabufExportError>)>,
+}
+
+impl DmabufExporter {
+    pub fn new(gl: Rc<dyn Gl>, rendering_context: RenderingContext) -> Self {
+        let egl = Egl::load(&rendering_context);
+        Self {
+            gl,
+            rendering_context,
+            egl,
+            webviews: HashMap::new(),
+            results: vec![],
+        }
+    }
+
+    /// Start exporting the frames of a webview in one of `formats`, or stop exporting them.
+    pub fn set_formats(&mut self, webview_id: WebViewId, formats: Option<Vec<DmabufFormat>>) {
+        if let Some(webview) = self.webviews.remove(&webview_id) {
+            self.destroy_textures(webview.textures);
+        }
+        let formats = match formats {
+            Some(formats) => formats,
+            None => return,
+        };
+        if self.egl.is_none() {
+            self.results
+                .push((webview_id, Err(DmabufExportError::Unsupported)));
+            return;
+        }
+        self.webviews.insert(
+            webview_id,
+            ExportedWebView {
+                formats,
+                size: DeviceIntSize::zero(),
+                textures: vec![],
+                next_texture: 0,
+                failed: false,
+            },
+        );
+    }
+
+    pub fn is_exported(&self, webview_id: WebViewId) -> bool {
+        self.webviews.contains_key(&webview_id)
+    }
+
+    /// The buffer that the next frame of a webview of this size is to be copied into, after
+    /// exporting new buffers if the webview changed size.
+    pub fn next_frame(
+        &mut self,
+        webview_id: WebViewId,
+        size: DeviceIntSize,
+    ) -> Option<ExportedFrame> {
+        let webview = self.webviews.get_mut(&webview_id)?;
+        if webview.size != size {
+            let old_textures = mem::take(&mut webview.textures);
+            webview.size = size;
+            webview.failed = false;
+            self.destroy_textures(old_textures);
+            self.export_textures(webview_id);
+        }
+        let webview = self.webviews.get_mut(&webview_id)?;
+        if webview.failed || webview.textures.is_empty() {
+            return None;
+        }
+        let buffer = webview.next_texture;
+        webview.next_texture = (buffer + 1) % webview.textures.len();
+        Some(ExportedFrame {
+            framebuffer_id: webview.textures[buffer].framebuffer_id,
+            buffer,
+        })
+    }
+
+    /// The buffers that were exported since the last call, or why they couldn't be, to be
+    /// announced to the embedder before the frames in them.
+    pub fn take_results(
+        &mut self,
+    ) -> Vec<(WebViewId, Result<Vec<DmabufBuffer>, DmabufExportError>)> {
+        mem::take(&mut self.results)
+    }
+
+    fn export_textures(&mut self, webview_id: WebViewId) {
+        let (size, formats) = match self.webviews.get(&webview_id) {
+            Some(webview) => (webview.size, webview.formats.clone()),
+            None => return,
+        };
+        let mut textures = vec![];
+        let mut buffers = vec![];
+        let mut result = Ok(());
+        for _ in 0..BUFFER_COUNT {
+            let mut texture = self.create_texture(size);
+            let exported = self.export_texture(&mut texture, size, &formats);
+            textures.push(texture);
+            match exported {
+                Ok(buffer) => buffers.push(buffer),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                },
+            }
+        }
+
+        let failed = result.is_err();
+        if let Err(ref error) = result {
+            warn!("Could not export the frames of {webview_id:?}: {error:?}");
+            self.destroy_textures(mem::take(&mut textures));
+        }
+        if let Some(webview) = self.webviews.get_mut(&webview_id) {
+            webview.textures = textures;
+            webview.next_texture = 0;
+            webview.failed = failed;
+        }
+        self.results.push((webview_id, result.map(|()| buffers)));
+    }
+
+    fn create_texture(&self, size: DeviceIntSize) -> ExportedTexture {
+        let gl = &self.gl;
+        let texture_id = gl.gen_textures(1)[0];
+        gl.bind_texture(gl::TEXTURE_2D, texture_id);
+        gl.tex_image_2d(
+            gl::TEXTURE_2D,
+            0,
+            gl::RGBA8 as gl::GLint,
+            size.width,
+            size.height,
+            0,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            None,
+        );
+        gl.tex_parameter_i(
+            gl::TEXTURE_2D,
+            gl::TEXTURE_MIN_FILTER,
+            gl::LINEAR as gl::GLint,
+        );
+        gl.tex_parameter_i(
+            gl::TEXTURE_2D,
+            gl::TEXTURE_MAG_FILTER,
+            gl::LINEAR as gl::GLint,
+        );
+        gl.bind_texture(gl::TEXTURE_2D, 0);
+
+        let framebuffer_id = gl.gen_framebuffers(1)[0];
+        gl.bind_framebuffer(gl::FRAMEBUFFER, framebuffer_id);
+        gl.framebuffer_texture_2d(
+            gl::FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            texture_id,
+            0,
+        );
+        gl.bind_framebuffer(gl::FRAMEBUFFER, 0);
+
+        ExportedTexture {
+            texture_id,
+            framebuffer_id,
+            image: std::ptr::null_mut(),
+        }
+    }
+
+    fn export_texture(
+        &self,
+        texture: &mut ExportedTexture,
+        size: DeviceIntSize,
+        formats: &[DmabufFormat],
+    ) -> Result<DmabufBuffer, DmabufExportError> {
+        let egl = self.egl.as_ref().ok_or(DmabufExportError::Unsupported)?;
+        if self.rendering_context.make_gl_context_current().is_err() {
+            return Err(DmabufExportError::Failed);
+        }
+        unsafe {
+            let display = (egl.get_current_display)();
+            let context = (egl.get_current_context)();
+            let attributes = [EGL_NONE];
+            let image = (egl.create_image)(
+                display,
+                context,
+                EGL_GL_TEXTURE_2D_KHR,
+                texture.texture_id as usize as *mut c_void,
+                attributes.as_ptr(),
+            );
+            if image.is_null() {
+                return Err(DmabufExportError::Failed);
+            }
+            // The image is destroyed along with the texture, even if it can't be exported.
+            texture.image = image;
+
+            let mut fourcc = 0;
+            let mut plane_count = 0;
+            let mut modifiers = [0u64; MAX_PLANES];
+            if (egl.export_query)(
+                display,
+                image,
+                &mut fourcc,
+                &mut plane_count,
+                modifiers.as_mut_ptr(),
+            ) == 0 ||
+                plane_count < 1 ||
+                plane_count as usize > MAX_PLANES
+            {
+                return Err(DmabufExportError::Failed);
+            }
+            let (fourcc, modifier) = (fourcc as u32, modifiers[0]);
+            let is_accepted = formats.iter().any(|format| {
+                format.fourcc == fourcc &&
+                    (format.modifiers.is_empty() || format.modifiers.contains(&modifier))
+            });
+            if !is_accepted {
+                return Err(DmabufExportError::UnacceptedFormat(fourcc, modifier));
+            }
+
+            let mut fds = [-1; MAX_PLANES];
+            let mut strides = [0; MAX_PLANES];
+            let mut offsets = [0; MAX_PLANES];
+            if (egl.export)(
+                display,
+                image,
+                fds.as_mut_ptr(),
+                strides.as_mut_ptr(),
+                offsets.as_mut_ptr(),
+            ) == 0
+            {
+                return Err(DmabufExportError::Failed);
+            }
+            let planes = (0..plane_count as usize)
+                .map(|plane| {
+                    // Planes that share the file descriptor of the first one have none.
+                    let fd = match fds[plane] {
+                        -1 => libc::dup(fds[0]),
+                        fd => fd,
+                    };
+                    DmabufPlane {
+                        fd: OwnedFd::from_raw_fd(fd),
+                        offset: offsets[plane] as u32,
+                        stride: strides[plane] as u32,
+                    }
+                })
+                .collect();
+            Ok(DmabufBuffer {
+                size,
+                fourcc,
+                modifier,
+                planes,
+            })
+        }
+    }
+
+    fn destroy_textures(&self, textures: Vec<ExportedTexture>) {
+        for texture in textures {
+            if let (Some(egl), false) = (self.egl.as_ref(), texture.image.is_null()) {
+                unsafe {
+                    (egl.destroy_image)((egl.get_current_display)(), texture.image);
+                }
+            }
+            self.gl.delete_framebuffers(&[texture.framebuffer_id]);
+            self.gl.delete_textures(&[texture.texture_id]);
+        }
+    }
+}
@@ -5,7 +5,7 @@
 use std::rc::Rc;
 
 use gleam::gl::{self, Gl};
-use image::RgbImage;
+use image::{RgbImage, RgbaImage};
 use log::trace;
 use servo_geometry::FramebufferUintLength;
 
@@ -21,6 +21,25 @@ impl RenderTargetInfo {
         gl: Rc<dyn Gl>,
         width: FramebufferUintLength,
         height: FramebufferUintLength,
+    ) -> Self {
+        Self::with_format(gl, width, height, gl::RGB)
+    }
+
+    /// A render target whose texture has an alpha channel, for reading back transparent
+    /// pixels.
+    pub fn new_rgba(
+        gl: Rc<dyn Gl>,
+        width: FramebufferUintLength,
+        height: FramebufferUintLength,
+    ) -> Self {
+        Self::with_format(gl, width, height, gl::RGBA)
+    }
+
+    fn with_format(
+        gl: Rc<dyn Gl>,
+        width: FramebufferUintLength,
+        height: FramebufferUintLength,
+        format: gl::GLenum,
     ) -> Self {
         let framebuffer_ids = gl.gen_framebuffers(1);
         gl.bind_framebuffer(gl::FRAMEBUFFER, framebuffer_ids[0]);
@@ -31,11 +50,11 @@ impl RenderTargetInfo {
         gl.tex_image_2d(
             gl::TEXTURE_2D,
             0,
-            gl::RGB as gl::GLint,
+            format as gl::GLint,
             width.get() as gl::GLsizei,
             height.get() as gl::GLsizei,
             0,
-            gl::RGB,
+            format,
             gl::UNSIGNED_BYTE,
             None,
         );
@@ -106,6 +125,31 @@ impl RenderTargetInfo {
         width: FramebufferUintLength,
         height: FramebufferUintLength,
     ) -> RgbImage {
+        let pixels = self.read_pixels(x, y, width, height, gl::RGB, 3);
+        RgbImage::from_raw(width.get(), height.get(), pixels).expect("Flipping image failed!")
+    }
+
+    /// Like [`Self::read_back_from_gpu`], but with an alpha channel.
+    pub fn read_back_rgba_from_gpu(
+        self,
+        x: i32,
+        y: i32,
+        width: FramebufferUintLength,
+        height: FramebufferUintLength,
+    ) -> RgbaImage {
+        let pixels = self.read_pixels(x, y, width, height, gl::RGBA, 4);
+        RgbaImage::from_raw(width.get(), height.get(), pixels).expect("Flipping image failed!")
+    }
+
+    fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: FramebufferUintLength,
+        height: FramebufferUintLength,
+        format: gl::GLenum,
+        bytes_per_pixel: usize,
+    ) -> Vec<u8> {
         let width = width.get() as usize;
         let height = height.get() as usize;
         // For some reason, OSMesa fails to render on the 3rd
@@ -121,21 +165,20 @@ impl RenderTargetInfo {
             y,
             width as gl::GLsizei,
             height as gl::GLsizei,
-            gl::RGB,
+            format,
             gl::UNSIGNED_BYTE,
         );
 
         // flip image vertically (texture is upside down)
         let orig_pixels = pixels.clone();
-        let stride = width * 3;
+        let stride = width * bytes_per_pixel;
         for y in 0..height {
             let dst_start = y * stride;
             let src_start = (height - y - 1) * stride;
             let src_slice = &orig_pixels[src_start..src_start + stride];
             (&mut pixels[dst_start..dst_start + stride]).clone_from_slice(&src_slice[..stride]);
         }
-
-        RgbImage::from_raw(width as u32, height as u32, pixels).expect("Flipping image failed!")
+        pixels
     }
 }
 
@@ -0,0 +1,142 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The choice of the webview that receives a pointer event from the embedder. Pointer
+//! events go to the topmost webview in painting order whose viewport contains them, even
+//! where that webview paints nothing, so that they never reach the webviews it covers.
+//!
+//! A webview that receives a mouse button press keeps receiving the mouse events until
+//! every button is released, and one that receives a touch keeps receiving its events
+//! until it ends, like with implicit pointer capture. Keyboard events aren't routed here,
+//! since they follow the focused webview of the constellation.
+
+use std::collections::HashMap;
+
+use msg::constellation_msg::TopLevelBrowsingContextId;
+use script_traits::{MouseButton, MouseEventType, TouchEventType, TouchId};
+use webrender_api::units::{DevicePoint, DeviceRect};
+
+/// A webview that pointer events can be routed to.
+#[derive(Clone, Copy, Debug)]
+pub struct InputRoute {
+    pub webview_id: TopLevelBrowsingContextId,
+    /// The viewport of the webview in the window, in device pixels.
+    pub rect: DeviceRect,
+}
+
+#[derive(Default)]
+pub struct InputRouter {
+    /// The webview that received the mouse buttons that are pressed, with those buttons.
+    mouse_capture: Option<(TopLevelBrowsingContextId, u16)>,
+    /// The webview that received the last mouse button release, where the click that
+    /// follows it goes.
+    click_target: Option<TopLevelBrowsingContextId>,
+    /// The webviews that received the touches that haven't ended.
+    touch_targets: HashMap<TouchId, TopLevelBrowsingContextId>,
+}
+
+impl InputRouter {
+    /// The route of the mouse button event at `point`, given the routes of the webviews
+    /// topmost first.
+    pub fn route_mouse_button(
+        &mut self,
+        routes: &[InputRoute],
+        event_type: MouseEventType,
+        button: MouseButton,
+        point: DevicePoint,
+    ) -> Option<InputRoute> {
+        let button = 1u16 << button as u16;
+        match event_type {
+            MouseEventType::MouseDown => {
+                let route = self.captured_mouse_route(routes, point)?;
+                let buttons = self.mouse_capture.map_or(0, |(_, buttons)| buttons);
+                self.mouse_capture = Some((route.webview_id, buttons | button));
+                Some(route)
+            },
+            MouseEventType::MouseUp => {
+                let route = self.captured_mouse_route(routes, point);
+                if let Some((webview_id, buttons)) = self.mouse_capture.take() {
+                    if buttons & !button != 0 {
+                        self.mouse_capture = Some((webview_id, buttons & !button));
+                    }
+                }
+                self.click_target = route.map(|route| route.webview_id);
+                route
+            },
+            MouseEventType::Click => match self.click_target.take() {
+                Some(webview_id) => route_for_webview(routes, webview_id),
+                None => route_at_point(routes, point),
+            },
+        }
+    }
+
+    /// The route of the mouse move event at `point`.
+    pub fn route_mouse_move(
+        &mut self,
+        routes: &[InputRoute],
+        point: DevicePoint,
+    ) -> Option<InputRoute> {
+        self.captured_mouse_route(routes, point)
+    }
+
+    /// The route of the webview that captured the mouse, or of the webview at `point` if
+    /// there is none. The capture ends early if that webview is no longer painted.
+    fn captured_mouse_route(
+        &mut self,
+        routes: &[InputRoute],
+        point: DevicePoint,
+    ) -> Option<InputRoute> {
+        if let Some((webview_id, _)) = self.mouse_capture {
+            match route_for_webview(routes, webview_id) {
+                Some(route) => return Some(route),
+                None => self.mouse_capture = None,
+            }
+        }
+        route_at_point(routes, point)
+    }
+
+    /// The route of the event of the touch `identifier` at `point`.
+    pub fn route_touch(
+        &mut self,
+        routes: &[InputRoute],
+        event_type: TouchEventType,
+        identifier: TouchId,
+        point: DevicePoint,
+    ) -> Option<InputRoute> {
+        match event_type {
+            TouchEventType::Down => {
+                let route = route_at_point(routes, point)?;
+                self.touch_targets.insert(identifier, route.webview_id);
+                Some(route)
+            },
+            TouchEventType::Move => {
+                let webview_id = *self.touch_targets.get(&identifier)?;
+                route_for_webview(routes, webview_id)
+            },
+            TouchEventType::Up | TouchEventType::Cancel => {
+                let webview_id = self.touch_targets.remove(&identifier)?;
+                route_for_webview(routes, webview_id)
+            },
+        }
+    }
+}
+
+/// The route of the topmost webview whose viewport contains `point`, for the events that
+/// aren't captured, like wheel events.
+pub fn route_at_point(routes: &[InputRoute], point: DevicePoint) -> Option<InputRoute> {
+    routes
+        .iter()
+        .find(|route| route.rect.contains(point))
+        .copied()
+}
+
+fn route_for_webview(
+    routes: &[InputRoute],
+    webview_id: TopLevelBrowsingContextId,
+) -> Option<InputRoute> {
+    routes
+        .iter()
+        .find(|route| route.webview_id == webview_id)
+        .copied()
+}
@@ -16,7 +16,14 @@ use webrender_api::DocumentId;
 pub use crate::compositor::{CompositeTarget, IOCompositor, ShutdownState};
 
 mod compositor;
+#[cfg(target_os = "linux")]
+#[allow(unsafe_code)]
+mod dmabuf;
 mod gl;
+mod input_routing;
+#[allow(unsafe_code)]
+mod readback;
+mod screenshot;
 mod touch;
 pub mod windowing;
 
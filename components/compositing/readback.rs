@@ -0,0 +1,201 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Readback of the frames of webviews into memory without waiting for the GPU. After a
+//! frame is composited, the pixels of each webview that is read back are copied into a
+//! pixel buffer object, and a fence is inserted after that copy. The buffer is only
+//! mapped once the fence is signalled, which the compositor checks whenever it updates,
+//! so the copy goes on while the next frames are rendered.
+//!
+//! A webview has a few frames in flight at most. The frames that are composited while
+//! it has as many are skipped, which the embedder sees as gaps in their ids.
+
+use std::collections::{HashMap, VecDeque};
+use std::ptr;
+use std::rc::Rc;
+use std::time::Instant;
+
+use gleam::gl::{self, Gl};
+use msg::constellation_msg::WebViewId;
+use webrender_api::units::{DeviceIntRect, DeviceIntSize};
+
+use crate::windowing::ReadBackFrame;
+
+/// How many frames of a webview can be read back at once.
+const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+struct FrameInFlight {
+    webview_id: WebViewId,
+    frame_id: u64,
+    timestamp: Instant,
+    size: DeviceIntSize,
+    buffer: gl::GLuint,
+    sync: gl::GLsync,
+}
+
+pub struct FrameReader {
+    gl: Rc<dyn Gl>,
+    /// The webviews whose frames are read back, with the id of their next frame.
+    webviews: HashMap<WebViewId, u64>,
+    /// The frames being read back, oldest first.
+    in_flight: VecDeque<FrameInFlight>,
+    /// The pixel buffers that aren't used, with their lengths in bytes.
+    free_buffers: Vec<(gl::GLuint, usize)>,
+}
+
+impl FrameReader {
+    pub fn new(gl: Rc<dyn Gl>) -> Self {
+        Self {
+            gl,
+            webviews: HashMap::new(),
+            in_flight: VecDeque::new(),
+            free_buffers: vec![],
+        }
+    }
+
+    /// Start or stop reading back the frames of a webview. The frames of a webview that
+    /// are in flight when it stops are dropped.
+    pub fn set_reading(&mut self, webview_id: WebViewId, read: bool) {
+        if read {
+            self.webviews.entry(webview_id).or_insert(0);
+        } else {
+            self.webviews.remove(&webview_id);
+        }
+    }
+
+    pub fn is_read(&self, webview_id: WebViewId) -> bool {
+        self.webviews.contains_key(&webview_id)
+    }
+
+    pub fn has_frames_in_flight(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+
+    /// Start reading back the frame of a webview that was just composited at `rect` of
+    /// the bound framebuffer, which has a height of `framebuffer_height`.
+    pub fn read(&mut self, webview_id: WebViewId, rect: DeviceIntRect, framebuffer_height: i32) {
+        let frame_id = match self.webviews.get_mut(&webview_id) {
+            Some(next_frame_id) => {
+                *next_frame_id += 1;
+                *next_frame_id - 1
+            },
+            None => return,
+        };
+        let frames_in_flight = self
+            .in_flight
+            .iter()
+            .filter(|frame| frame.webview_id == webview_id)
+            .count();
+        if rect.is_empty() || frames_in_flight >= MAX_FRAMES_IN_FLIGHT {
+            return;
+        }
+
+        let length = rect.area() as usize * 4;
+        let buffer = match self
+            .free_buffers
+            .iter()
+            .position(|&(_, free_length)| free_length == length)
+        {
+            Some(index) => {
+                let (buffer, _) = self.free_buffers.swap_remove(index);
+                self.gl.bind_buffer(gl::PIXEL_PACK_BUFFER, buffer);
+                buffer
+            },
+            None => {
+                let buffer = self.gl.gen_buffers(1)[0];
+                self.gl.bind_buffer(gl::PIXEL_PACK_BUFFER, buffer);
+                self.gl.buffer_data_untyped(
+                    gl::PIXEL_PACK_BUFFER,
+                    length as gl::GLsizeiptr,
+                    ptr::null(),
+                    gl::STREAM_READ,
+                );
+                buffer
+            },
+        };
+        // OpenGL counts rows from the bottom of the framebuffer.
+        self.gl.read_pixels_into_pbo(
+            rect.origin.x,
+            framebuffer_height - rect.max_y(),
+            rect.size.width,
+            rect.size.height,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        );
+        self.gl.bind_buffer(gl::PIXEL_PACK_BUFFER, 0);
+        let sync = self.gl.fence_sync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+        // Without a flush, the fence wouldn't be signalled before the next frame.
+        self.gl.flush();
+
+        self.in_flight.push_back(FrameInFlight {
+            webview_id,
+            frame_id,
+            timestamp: Instant::now(),
+            size: rect.size,
+            buffer,
+            sync,
+        });
+    }
+
+    /// The frames whose pixels reached their buffers, oldest first.
+    pub fn take_finished_frames(&mut self) -> Vec<ReadBackFrame> {
+        let mut frames = vec![];
+        while let Some(frame) = self.in_flight.front() {
+            // Fences are signalled in the order they were inserted in.
+            match self.gl.client_wait_sync(frame.sync, 0, 0) {
+                gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => {},
+                _ => break,
+            }
+            let frame = match self.in_flight.pop_front() {
+                Some(frame) => frame,
+                None => break,
+            };
+            self.gl.delete_sync(frame.sync);
+
+            let stride = frame.size.width as usize * 4;
+            let length = stride * frame.size.height as usize;
+            if self.webviews.contains_key(&frame.webview_id) {
+                self.gl.bind_buffer(gl::PIXEL_PACK_BUFFER, frame.buffer);
+                let mapped = self.gl.map_buffer_range(
+                    gl::PIXEL_PACK_BUFFER,
+                    0,
+                    length as gl::GLsizeiptr,
+                    gl::MAP_READ_BIT,
+                );
+                if !mapped.is_null() {
+                    let rows = unsafe { std::slice::from_raw_parts(mapped as *const u8, length) };
+                    let pixels = rows.chunks_exact(stride).rev().flatten().copied().collect();
+                    frames.push(ReadBackFrame {
+                        webview_id: frame.webview_id,
+                        frame_id: frame.frame_id,
+                        timestamp: frame.timestamp,
+                        size: frame.size,
+                        pixels,
+                    });
+                    self.gl.unmap_buffer(gl::PIXEL_PACK_BUFFER);
+                }
+                self.gl.bind_buffer(gl::PIXEL_PACK_BUFFER, 0);
+            }
+
+            self.free_buffers.push((frame.buffer, length));
+            if self.free_buffers.len() > MAX_FRAMES_IN_FLIGHT {
+                let (buffer, _) = self.free_buffers.remove(0);
+                self.gl.delete_buffers(&[buffer]);
+            }
+        }
+        frames
+    }
+}
+
+impl Drop for FrameReader {
+    fn drop(&mut self) {
+        for frame in self.in_flight.drain(..) {
+            self.gl.delete_sync(frame.sync);
+            self.gl.delete_buffers(&[frame.buffer]);
+        }
+        for (buffer, _) in self.free_buffers.drain(..) {
+            self.gl.delete_buffers(&[buffer]);
+        }
+    }
+}
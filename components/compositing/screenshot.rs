@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Screenshots of single webviews, which the embedder can take of webviews that aren't
+//! painted. Screenshots are taken one at a time: the compositor builds a scene with only
+//! the webview of the oldest one, waits for WebRender to produce a frame of it, renders
+//! that frame into a framebuffer of the size of the webview and reads it back, then
+//! paints the other webviews again. Nothing is composited to the window meanwhile.
+//!
+//! Documents that are printed are laid out with the size of their pages, and their
+//! screenshots wait for the display list of that layout before painting it at that size.
+
+use std::io::Cursor;
+
+use compositing_traits::ScreenshotLayout;
+use embedder_traits::{ScaleMode, Screenshot, ScreenshotError, ScreenshotFormat};
+use euclid::{Rect, Scale};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
+use style_traits::{CSSPixel, DevicePixel};
+use webrender_api::units::{DeviceIntRect, DeviceIntSize};
+
+pub struct PendingScreenshot {
+    pub webview_id: TopLevelBrowsingContextId,
+    /// The root pipeline of the webview when the screenshot was asked for.
+    pub pipeline_id: PipelineId,
+    /// The layout to take the screenshot of, if not the one the webview is shown with.
+    pub layout: Option<ScreenshotLayout>,
+    pub rect: Option<Rect<f32, CSSPixel>>,
+    pub scale_mode: ScaleMode,
+    pub format: ScreenshotFormat,
+    pub sender: IpcSender<Result<Screenshot, ScreenshotError>>,
+    /// The number of frames that WebRender has yet to produce before the one with only
+    /// this webview, or `None` if the scene with only this webview wasn't sent yet, such
+    /// as while the display list of its layout hasn't arrived.
+    pub frames_until_ready: Option<usize>,
+}
+
+/// The part of the viewport of a webview of `size` that `rect`, in CSS pixels, covers
+/// in device pixels, or all of it. `None` if that is empty.
+pub fn device_rect(
+    rect: Option<Rect<f32, CSSPixel>>,
+    device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+    size: DeviceIntSize,
+) -> Option<DeviceIntRect> {
+    let viewport = DeviceIntRect::from_size(size);
+    let rect = match rect {
+        Some(rect) => device_pixel_ratio
+            .transform_rect(&rect)
+            .round_out()
+            .to_i32()
+            .intersection(&viewport)?,
+        None => viewport,
+    };
+    Some(rect).filter(|rect| !rect.is_empty())
+}
+
+/// A screenshot of the premultiplied pixels read back from a webview, scaled for
+/// `scale_mode` and encoded in `format`.
+pub fn finish(
+    mut image: RgbaImage,
+    device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+    scale_mode: ScaleMode,
+    format: ScreenshotFormat,
+) -> Result<Screenshot, ScreenshotError> {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        if alpha != 0 && alpha != 255 {
+            for channel in &mut pixel.0[..3] {
+                *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+            }
+        }
+    }
+
+    let (width, height) = image.dimensions();
+    let factor = match scale_mode {
+        ScaleMode::DevicePixels => 1.,
+        ScaleMode::CssPixels => 1. / device_pixel_ratio.get(),
+        ScaleMode::Fit {
+            width: max_width,
+            height: max_height,
+        } => (max_width as f32 / width as f32).min(max_height as f32 / height as f32),
+    };
+    let scaled_width = ((width as f32 * factor).round() as u32).max(1);
+    let scaled_height = ((height as f32 * factor).round() as u32).max(1);
+    if (scaled_width, scaled_height) != (width, height) {
+        image = imageops::resize(&image, scaled_width, scaled_height, FilterType::Triangle);
+    }
+
+    let data = match format {
+        ScreenshotFormat::Rgba8 => image.into_raw(),
+        ScreenshotFormat::Png => {
+            let mut data = Cursor::new(vec![]);
+            DynamicImage::ImageRgba8(image)
+                .write_to(&mut data, ImageFormat::Png)
+                .map_err(|_| ScreenshotError::Failed)?;
+            data.into_inner()
+        },
+    };
+    Ok(Screenshot {
+        width: scaled_width,
+        height: scaled_height,
+        format,
+        data,
+    })
+}
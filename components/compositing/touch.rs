@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use euclid::{Point2D, Scale, Vector2D};
+use std::time::{Duration, Instant};
+
+use euclid::{Point2D, Vector2D};
 use log::warn;
 use script_traits::{EventResult, TouchId};
 use style_traits::DevicePixel;
@@ -12,9 +14,31 @@ use self::TouchState::*;
 /// Minimum number of `DeviceIndependentPixel` to begin touch scrolling.
 const TOUCH_PAN_MIN_SCREEN_PX: f32 = 20.0;
 
+/// Longest time between two taps of a double tap.
+const DOUBLE_TAP_MAX_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Maximum number of `DevicePixel` between two taps of a double tap.
+const DOUBLE_TAP_MAX_DISTANCE: f32 = 40.0;
+
+/// Slowest pan, in `DevicePixel` per second, that keeps scrolling after it is released,
+/// and the speed at which flings stop.
+const FLING_MIN_VELOCITY: f32 = 100.0;
+
+/// Longest time that a pan can stand still before it is released and still be flung.
+const FLING_MAX_REST: Duration = Duration::from_millis(50);
+
+/// The part of its velocity that a fling keeps after a second.
+const FLING_DECELERATION: f32 = 0.05;
+
 pub struct TouchHandler {
     pub state: TouchState,
     pub active_touch_points: Vec<TouchPoint>,
+    /// When and where the last tap was, if it could be the first of a double tap.
+    last_tap: Option<(Instant, Point2D<f32, DevicePixel>)>,
+    /// The velocity of the current pan, in `DevicePixel` per second, and when the touch
+    /// point last moved.
+    pan_velocity: Vector2D<f32, DevicePixel>,
+    last_pan_move: Option<Instant>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -33,8 +57,6 @@ impl TouchPoint {
 }
 
 /// The states of the touch input state machine.
-///
-/// TODO: Add support for "flinging" (scrolling inertia)
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TouchState {
     /// Not tracking any touch point
@@ -60,10 +82,16 @@ pub enum TouchState {
 pub enum TouchAction {
     /// Simulate a mouse click.
     Click,
+    /// Zoom in or out about the point of the second tap of a double tap.
+    DoubleTap(Point2D<f32, DevicePixel>),
     /// Scroll by the provided offset.
     Scroll(Vector2D<f32, DevicePixel>),
-    /// Zoom by a magnification factor and scroll by the provided offset.
-    Zoom(f32, Vector2D<f32, DevicePixel>),
+    /// Keep scrolling at the provided velocity, in `DevicePixel` per second, after a pan
+    /// was released.
+    Fling(Vector2D<f32, DevicePixel>),
+    /// Zoom by a magnification factor about a focal point and scroll by the provided
+    /// offset.
+    Zoom(f32, Point2D<f32, DevicePixel>, Vector2D<f32, DevicePixel>),
     /// Send a JavaScript event to content.
     DispatchEvent,
     /// Don't do anything.
@@ -75,6 +103,9 @@ impl TouchHandler {
         TouchHandler {
             state: Nothing,
             active_touch_points: Vec::new(),
+            last_tap: None,
+            pan_velocity: Vector2D::zero(),
+            last_pan_move: None,
         }
     }
 
@@ -109,6 +140,8 @@ impl TouchHandler {
                     delta.y.abs() > TOUCH_PAN_MIN_SCREEN_PX
                 {
                     self.state = Panning;
+                    self.pan_velocity = Vector2D::zero();
+                    self.last_pan_move = Some(Instant::now());
                     TouchAction::Scroll(delta)
                 } else {
                     TouchAction::NoAction
@@ -116,6 +149,7 @@ impl TouchHandler {
             },
             Panning => {
                 let delta = point - old_point;
+                self.track_pan_velocity(delta);
                 TouchAction::Scroll(delta)
             },
             DefaultPrevented => TouchAction::DispatchEvent,
@@ -125,9 +159,9 @@ impl TouchHandler {
                 let (d1, c1) = self.pinch_distance_and_center();
 
                 let magnification = d1 / d0;
-                let scroll_delta = c1 - c0 * Scale::new(magnification);
+                let scroll_delta = c1 - c0;
 
-                TouchAction::Zoom(magnification, scroll_delta)
+                TouchAction::Zoom(magnification, c1, scroll_delta)
             },
             WaitingForScript => TouchAction::NoAction,
             MultiTouch => TouchAction::NoAction,
@@ -142,7 +176,7 @@ impl TouchHandler {
         action
     }
 
-    pub fn on_touch_up(&mut self, id: TouchId, _point: Point2D<f32, DevicePixel>) -> TouchAction {
+    pub fn on_touch_up(&mut self, id: TouchId, point: Point2D<f32, DevicePixel>) -> TouchAction {
         match self.active_touch_points.iter().position(|t| t.id == id) {
             Some(i) => {
                 self.active_touch_points.swap_remove(i);
@@ -156,12 +190,32 @@ impl TouchHandler {
                 // FIXME: If the duration exceeds some threshold, send a contextmenu event instead.
                 // FIXME: Don't send a click if preventDefault is called on the touchend event.
                 self.state = Nothing;
-                TouchAction::Click
+                let now = Instant::now();
+                match self.last_tap.take() {
+                    Some((time, last_point))
+                        if now - time <= DOUBLE_TAP_MAX_INTERVAL &&
+                            (point - last_point).length() <= DOUBLE_TAP_MAX_DISTANCE =>
+                    {
+                        TouchAction::DoubleTap(point)
+                    },
+                    _ => {
+                        self.last_tap = Some((now, point));
+                        TouchAction::Click
+                    },
+                }
             },
-            Nothing | Panning => {
+            Panning => {
                 self.state = Nothing;
-                TouchAction::NoAction
+                let resting = self
+                    .last_pan_move
+                    .map_or(true, |time| time.elapsed() > FLING_MAX_REST);
+                if !resting && self.pan_velocity.length() >= FLING_MIN_VELOCITY {
+                    TouchAction::Fling(self.pan_velocity)
+                } else {
+                    TouchAction::NoAction
+                }
             },
+            Nothing => TouchAction::NoAction,
             Pinching => {
                 self.state = Panning;
                 TouchAction::NoAction
@@ -214,6 +268,19 @@ impl TouchHandler {
         }
     }
 
+    /// Smooth the velocity of the pan with the one of its latest move, which is noisy
+    /// since touch points move by whole pixels at irregular times.
+    fn track_pan_velocity(&mut self, delta: Vector2D<f32, DevicePixel>) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_pan_move
+            .replace(now)
+            .map_or(0.0, |time| (now - time).as_secs_f32());
+        if elapsed > 0.0 {
+            self.pan_velocity = self.pan_velocity * 0.2 + delta / elapsed * 0.8;
+        }
+    }
+
     fn touch_count(&self) -> usize {
         self.active_touch_points.len()
     }
@@ -228,3 +295,34 @@ impl TouchHandler {
         (distance, center)
     }
 }
+
+/// A scroll that goes on after a pan is released, slowing down until it stops.
+pub struct Fling {
+    /// Where the pan was released.
+    pub point: Point2D<f32, DevicePixel>,
+    velocity: Vector2D<f32, DevicePixel>,
+    last_step: Instant,
+}
+
+impl Fling {
+    pub fn new(point: Point2D<f32, DevicePixel>, velocity: Vector2D<f32, DevicePixel>) -> Self {
+        Fling {
+            point,
+            velocity,
+            last_step: Instant::now(),
+        }
+    }
+
+    /// How far the fling scrolled since its last step, or `None` once it stopped.
+    pub fn step(&mut self) -> Option<Vector2D<f32, DevicePixel>> {
+        let now = Instant::now();
+        let elapsed = (now - self.last_step).as_secs_f32();
+        self.last_step = now;
+        if self.velocity.length() < FLING_MIN_VELOCITY {
+            return None;
+        }
+        let delta = self.velocity * elapsed;
+        self.velocity = self.velocity * FLING_DECELERATION.powf(elapsed);
+        Some(delta)
+    }
+}
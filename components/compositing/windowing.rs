@@ -5,22 +5,34 @@
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
 use std::fmt::{Debug, Error, Formatter};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use embedder_traits::{EmbedderProxy, EventLoopWaker};
-use euclid::Scale;
+use embedder_traits::{
+    ContextMenuAction, CookieScope, EmbedderProxy, EventLoopWaker, FindOptions, FocusReason,
+    ImeEvent, NavigationPolicy, NewWebViewRequest, PaintingOrderChange, PermissionDecision,
+    PermissionScope, PrintError, PrintSettings, ScaleMode, Screenshot, ScreenshotError,
+    ScreenshotFormat, SessionState, StoredCookie, SurroundingText, WebViewLifecycleState,
+    WebViewPriority,
+};
+use euclid::{Rect, Scale};
 use gfx::rendering_context::RenderingContext;
-use keyboard_types::KeyboardEvent;
+use ipc_channel::ipc::IpcSender;
+use keyboard_types::{KeyboardEvent, Modifiers};
 use libc::c_void;
-use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
+use msg::constellation_msg::{
+    BrowsingContextId, PipelineId, TopLevelBrowsingContextId, TraversalDirection, WebViewGroupId,
+    WebViewId,
+};
+use net_traits::protocols::ProtocolRegistry;
 use script_traits::{
-    GamepadEvent, MediaSessionActionType, MouseButton, TouchEventType, TouchId, WheelDelta,
+    EditingCommand, GamepadEvent, JsGcSettings, MediaSessionActionType, MouseButton,
+    MouseEventType, PictureInPictureAction, SensorReading, TouchEventType, TouchId, WheelDelta,
 };
 use servo_geometry::DeviceIndependentPixel;
-use servo_url::ServoUrl;
-use style_traits::DevicePixel;
+use servo_url::{ImmutableOrigin, ServoUrl};
+use style_traits::{CSSPixel, DevicePixel};
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePoint};
-use webrender_api::ScrollLocation;
+use webrender_api::{ColorF, ScrollLocation};
 
 #[derive(Clone)]
 pub enum MouseWindowEvent {
@@ -29,6 +41,20 @@ pub enum MouseWindowEvent {
     MouseUp(MouseButton, DevicePoint),
 }
 
+/// An input event that the embedder injects into a webview, whether it is focused or not.
+/// Points are in device pixels from the top left corner of the viewport of the webview.
+#[derive(Clone, Debug)]
+pub enum InputEvent {
+    Keyboard(KeyboardEvent),
+    MouseButton(MouseEventType, MouseButton, DevicePoint, Modifiers),
+    MouseMove(DevicePoint),
+    /// A touch, which is dispatched to the document without being turned into the panning
+    /// and zooming gestures of touches from the window.
+    Touch(TouchEventType, TouchId, DevicePoint),
+    Wheel(WheelDelta, DevicePoint),
+    Scroll(ScrollLocation, DevicePoint),
+}
+
 /// Various debug and profiling flags that WebRender supports.
 #[derive(Clone)]
 pub enum WebRenderDebugOption {
@@ -52,8 +78,9 @@ pub enum EmbedderEvent {
     Refresh,
     /// Sent when the window is resized.
     Resize,
-    /// Sent when a navigation request from script is allowed/refused.
-    AllowNavigationResponse(PipelineId, bool),
+    /// Sent when a navigation request from script is allowed, refused or diverted to a new
+    /// webview.
+    AllowNavigationResponse(PipelineId, NavigationPolicy),
     /// Sent when a new URL is to be loaded.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Sent when a mouse hit test is to be performed.
@@ -75,36 +102,88 @@ pub enum EmbedderEvent {
     ResetZoom,
     /// Sent when the user uses chrome navigation (i.e. backspace or shift-backspace).
     Navigation(TopLevelBrowsingContextId, TraversalDirection),
+    /// Traverse the session history of a webview to the entry at the given index of the
+    /// entries of `EmbedderMsg::HistoryChanged`.
+    GoToHistoryIndex(TopLevelBrowsingContextId, usize),
     /// Sent when the user quits the application
     Quit,
-    /// Sent when the user exits from fullscreen mode
+    /// Sent when the user exits from fullscreen mode, for the fullscreen element of the
+    /// webview to leave fullscreen.
     ExitFullScreen(TopLevelBrowsingContextId),
+    /// Answer `EmbedderMsg::EnterFullscreen` of a webview with whether its element is shown
+    /// fullscreen. When the embedder resizes the webview for it, sending this afterwards
+    /// makes the documents see the resize before the element matches `:fullscreen`.
+    FullscreenEntered(TopLevelBrowsingContextId, bool),
     /// Sent when a key input state changes
     Keyboard(KeyboardEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload(TopLevelBrowsingContextId),
     /// Create a new top level browsing context
     NewWebView(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context in a webview group, with its own cookies,
+    /// caches and storage. Webviews it opens are in the same group.
+    NewWebViewInGroup(ServoUrl, TopLevelBrowsingContextId, WebViewGroupId),
     /// Close a top level browsing context
     CloseWebView(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
     SendError(Option<TopLevelBrowsingContextId>, String),
     /// Make a top level browsing context visible, hiding the previous
     /// visible one.
-    FocusWebView(TopLevelBrowsingContextId),
+    FocusWebView(TopLevelBrowsingContextId, FocusReason),
+    /// Take focus away from the focused webview. Its documents get blur events, and
+    /// unless the reason is `FocusReason::NativeWindow`, it is no longer focused.
+    BlurWebView(FocusReason),
+    /// Focus the webview that was focused before the focused one. Repeating this cycles
+    /// through the webviews from the most to the least recently focused.
+    FocusPreviousWebView,
+    /// Go back the other way through the webviews cycled through by `FocusPreviousWebView`.
+    FocusNextWebView,
     /// Toggles a debug flag in WebRender
     ToggleWebRenderDebug(WebRenderDebugOption),
     /// Capture current WebRender
     CaptureWebRender,
     /// Clear the network cache.
     ClearCache,
+    /// Store a decision for the webviews of a scope, replacing any previous one for the
+    /// same scope, origin and permission.
+    SetPermission(PermissionScope, PermissionDecision),
+    /// Ask for the decisions in the permission stores, which are sent back with
+    /// `EmbedderMsg::PermissionDecisions`.
+    GetPermissionDecisions,
+    /// Forget the permission decisions for an origin, or for every origin if none is given,
+    /// in every scope.
+    ClearPermissionDecisions(Option<ImmutableOrigin>),
+    /// Ask for the cookies in the jars of a scope that are sent to a URL, or for all of
+    /// them if none is given, which are sent back with `EmbedderMsg::Cookies`.
+    GetCookies(CookieScope, Option<ServoUrl>),
+    /// Store a cookie in the jars of a scope, replacing the one with the same name, domain
+    /// and path. It is stored as it is given, without the checks that the cookies set by
+    /// documents go through.
+    SetCookie(CookieScope, StoredCookie),
+    /// Delete the cookie with the same name, domain and path as the given one from the
+    /// jars of a scope.
+    DeleteCookie(CookieScope, StoredCookie),
+    /// Start or stop sending `EmbedderMsg::CookieChanged` for each change to the cookie
+    /// jars of every webview group.
+    ObserveCookieChanges(bool),
     /// Toggle sampling profiler with the given sampling rate and max duration.
     ToggleSamplingProfiler(Duration, Duration),
     /// Sent when the user triggers a media action through the UA exposed media UI
     /// (play, pause, seek, etc.).
     MediaSessionAction(MediaSessionActionType),
+    /// Sent when the user activates one of the controls of the picture-in-picture
+    /// surface, or resizes or closes it.
+    PictureInPictureAction(PictureInPictureAction),
+    /// Ask for the WebRender external image of the first canvas in the webview's
+    /// top-level document that matches a CSS selector. The answer comes back as
+    /// `EmbedderMsg::CanvasTextureShared`.
+    ShareCanvasTexture(TopLevelBrowsingContextId, String),
     /// The visibility of the webview has changed.
     WebViewVisibilityChanged(TopLevelBrowsingContextId, bool),
+    /// Show every webview, such as when the window is restored after being minimized.
+    MarkAllWebViewsShown,
+    /// Hide every webview, such as when the window is minimized.
+    MarkAllWebViewsNotShown,
     /// Virtual keyboard was dismissed
     IMEDismissed,
     /// Sent on platforms like Android where the native widget surface can be
@@ -117,6 +196,170 @@ pub enum EmbedderEvent {
     ReplaceNativeSurface(*mut c_void, DeviceIntSize),
     /// Sent when new Gamepad information is available.
     Gamepad(GamepadEvent),
+    /// Sent when a device sensor requested with `EmbedderMsg::StartSensor` has a
+    /// new reading.
+    SensorReading(SensorReading),
+    /// Run an editing command in the focused document of a webview.
+    EditingCommand(TopLevelBrowsingContextId, EditingCommand),
+    /// Request what is selected in the focused document of a webview, which is
+    /// sent back with `EmbedderMsg::Selection`.
+    GetSelection(TopLevelBrowsingContextId),
+    /// Turn caret browsing on or off in a webview. While it is on, the arrow keys move
+    /// a caret through the content, and `EmbedderMsg::CaretMoved` reports where it is.
+    SetCaretBrowsing(TopLevelBrowsingContextId, bool),
+    /// Change the JavaScript heap limit and garbage collection scheduling of a webview.
+    /// Webviews opened by a page share the script threads, and so the settings, of the
+    /// page that opened them.
+    SetJsGcSettings(TopLevelBrowsingContextId, JsGcSettings),
+    /// Move a webview up or down in the painting order. Only has an effect with the
+    /// `multiview` feature, which paints every webview instead of only the focused one.
+    ChangePaintingOrder(TopLevelBrowsingContextId, PaintingOrderChange),
+    /// Freeze or discard a background webview to free its resources, or make it active
+    /// again. `EmbedderMsg::WebViewLifecycleStateChanged` reports the new state.
+    SetWebViewLifecycleState(TopLevelBrowsingContextId, WebViewLifecycleState),
+    /// Send the state of every webview to the embedder with `EmbedderMsg::SessionState`.
+    GetSessionState,
+    /// Open the webviews of a saved session. Only the focused one loads its page
+    /// straight away, the others are discarded until they are focused.
+    RestoreSession(SessionState),
+    /// Give a webview its own device pixel ratio, for embedders that paint webviews onto
+    /// surfaces of different scales, or follow the window again with `None`. The page
+    /// zoom of the window doesn't apply to webviews with their own ratio.
+    SetWebViewDevicePixelRatio(
+        TopLevelBrowsingContextId,
+        Option<Scale<f32, CSSPixel, DevicePixel>>,
+    ),
+    /// Give a webview a viewport of its own size in device pixels, instead of the size of
+    /// the window, so that webviews of different sizes can be tiled in the window.
+    ResizeWebView(WebViewId, DeviceIntSize),
+    /// Send what is known about every webview to the embedder with
+    /// `EmbedderMsg::WebViewMetadata`.
+    GetWebViewMetadata,
+    /// Open a new webview with a copy of the session history of the given one, which
+    /// loads the current page again.
+    DuplicateWebView(WebViewId),
+    /// Load the page of a webview whose content crashed again, replacing the crash page.
+    ReloadCrashedWebView(WebViewId),
+    /// Mute or unmute every media element and AudioContext of a webview, without
+    /// changing what content sees of their state.
+    SetWebViewMuted(WebViewId, bool),
+    /// Get the browsing contexts of a webview and their documents, which are sent back
+    /// with `EmbedderMsg::FrameTree`.
+    GetFrameTree(WebViewId),
+    /// Limit the number of webviews that are running or frozen, or remove the limit with
+    /// `None`. Over the limit, the least recently focused webviews that are neither shown
+    /// nor focused are discarded, and `EmbedderMsg::WebViewsDiscarded` lists them.
+    SetWebViewLimit(Option<usize>),
+    /// Enforce a Content-Security-Policy in every document of a webview, in addition to the
+    /// policies the documents are delivered with, or stop enforcing it with `None`.
+    SetWebViewCsp(WebViewId, Option<String>),
+    /// Set the languages of a webview, most preferred first, which its documents see in
+    /// `navigator.languages` and send in the Accept-Language header. An empty list goes
+    /// back to the default languages.
+    SetWebViewLanguages(WebViewId, Vec<String>),
+    /// Create a hidden webview that loads a page before it is needed, and isn't painted
+    /// until `ActivatePrerender` puts it in place of the focused webview.
+    NewPrerenderWebView(ServoUrl, TopLevelBrowsingContextId),
+    /// Replace the focused webview with a prerendered one at once, hiding the focused webview
+    /// and giving its place in the painting order to the prerendered one.
+    /// `EmbedderMsg::PrerenderActivated` reports which webview it replaced.
+    ActivatePrerender(WebViewId),
+    /// Set how promptly the tasks of the documents of a webview are run, such as lowering
+    /// the priority of webviews in the background.
+    SetWebViewPriority(WebViewId, WebViewPriority),
+    /// Start recording the latest messages the constellation handles and the changes to its
+    /// webviews, keeping at most the given number of events, or stop recording with `None`.
+    SetConstellationTracing(Option<usize>),
+    /// Get the events recorded since `SetConstellationTracing` started tracing, which are
+    /// sent with `EmbedderMsg::ConstellationTrace`.
+    GetConstellationTrace,
+    /// Measure the memory that the documents of every webview use, which is sent with
+    /// `EmbedderMsg::WebViewMemoryUsage`.
+    GetWebViewMemoryUsage,
+    /// Suspend a webview, like a mobile app in the background: its documents get a `freeze`
+    /// event, their timers and media are suspended, and the caches they use are purged.
+    /// Suspending a webview does not hide it.
+    SuspendWebView(WebViewId),
+    /// Resume a suspended webview, whose documents get a `resume` event. Focusing a
+    /// suspended webview also resumes it.
+    ResumeWebView(WebViewId),
+    /// Set the color painted behind the documents of a webview, which shows before they
+    /// paint and through transparent ones, or `None` to paint nothing behind them.
+    SetWebViewBackgroundColor(WebViewId, Option<ColorF>),
+    /// Set whether a webview is transparent, so that the parts of it that its documents
+    /// paint no background on show what the embedder painted under Servo.
+    SetWebViewTransparent(WebViewId, bool),
+    /// Search the text of every document of a webview, or go to the next or previous match
+    /// when searching again for the same query. Results are sent with `EmbedderMsg::FindResult`.
+    Find(WebViewId, String, FindOptions),
+    /// Go to the next match of the last search of a webview, once every document of the
+    /// webview was searched.
+    FindNext(WebViewId),
+    /// Go to the previous match of the last search of a webview, once every document of the
+    /// webview was searched.
+    FindPrevious(WebViewId),
+    /// Stop the search of a webview, like when its find bar is closed: forget its matches
+    /// and remove their highlights, keeping its active match selected.
+    StopFinding(WebViewId),
+    /// Create a new private webview in a webview group. The cookies, caches and storage of
+    /// the private webviews of a group are kept in memory, apart from those of its other
+    /// webviews, and forgotten when the last of them is closed. Webviews it opens are private too.
+    NewPrivateWebView(ServoUrl, TopLevelBrowsingContextId, WebViewGroupId),
+    /// Open a popup that was blocked, as if the user had activated the webview that
+    /// tried to open it, such as from a "popup blocked" notification.
+    OpenBlockedPopup(NewWebViewRequest),
+    /// Ask for the navigations of a webview that haven't replaced their documents yet,
+    /// which are sent back with `EmbedderMsg::PendingNavigations`.
+    GetPendingNavigations(WebViewId),
+    /// Cancel the navigations of a webview and stop loading its documents, along with their
+    /// fetches, like the stop button of a browser.
+    CancelPendingNavigation(WebViewId),
+    /// Render a webview into a framebuffer of the embedder when compositing to
+    /// `CompositeTarget::WebViewTextures`, or stop rendering it with `None`.
+    SetWebViewRenderTarget(WebViewId, Option<WebViewRenderTarget>),
+    /// Export the frames of a webview as DMA-BUFs when compositing to
+    /// `CompositeTarget::WebViewTextures`, in one of the formats that their consumer can
+    /// import, or stop exporting them with `None`. Only supported on Linux, with EGL.
+    ExportWebViewFrames(WebViewId, Option<Vec<DmabufFormat>>),
+    /// Start reading back every frame of a webview that is composited into memory, which
+    /// is given to `WindowMethods::webview_frame_read_back` once the GPU finished copying
+    /// it, or stop with `false`.
+    ReadBackWebViewFrames(WebViewId, bool),
+    /// Present a webview in a native surface of its own, such as a `wl_surface`, a `HWND`
+    /// or a `CALayer`, when compositing to `CompositeTarget::WebViewTextures`. Carries the
+    /// pointer to the native widget and its size, or `None` to stop presenting the webview.
+    AttachWebViewSurface(WebViewId, Option<(*mut c_void, DeviceIntSize)>),
+    /// Take a screenshot of a webview, or of a rectangle of its viewport in CSS pixels. The
+    /// webview is painted for it even if it is hidden or was never painted, but without the
+    /// pinch zoom.
+    CaptureScreenshot(
+        WebViewId,
+        Option<Rect<f32, CSSPixel>>,
+        ScaleMode,
+        ScreenshotFormat,
+        IpcSender<Result<Screenshot, ScreenshotError>>,
+    ),
+    /// Print the document of a webview to a PDF file, which is sent back as bytes. The
+    /// document is laid out again with print media and the size of the pages meanwhile.
+    PrintToPdf(
+        WebViewId,
+        PrintSettings,
+        IpcSender<Result<Vec<u8>, PrintError>>,
+    ),
+    /// Inject input events into a webview, in order, whether it is focused or under the point of
+    /// the events or not. The events that have a point need the webview to be painted.
+    InjectInputEvents(WebViewId, Vec<InputEvent>),
+    /// Change the touch gestures that a webview recognizes and how far they zoom it.
+    SetWebViewGestureSettings(WebViewId, GestureSettings),
+    /// Sent when the input method of the embedder edits the focused editable element of a
+    /// webview, for example while composing text.
+    Ime(WebViewId, ImeEvent),
+    /// Get the value of the focused editable element of a webview, for its input method, or
+    /// `None` if it has none.
+    GetImeSurroundingText(WebViewId, IpcSender<Option<SurroundingText>>),
+    /// Run a standard action on what the last context menu of a frame of a webview was
+    /// opened on, as described by `EmbedderMsg::ContextMenu`.
+    ContextMenuAction(WebViewId, BrowsingContextId, ContextMenuAction),
 }
 
 impl Debug for EmbedderEvent {
@@ -137,23 +380,92 @@ impl Debug for EmbedderEvent {
             EmbedderEvent::PinchZoom(..) => write!(f, "PinchZoom"),
             EmbedderEvent::ResetZoom => write!(f, "ResetZoom"),
             EmbedderEvent::Navigation(..) => write!(f, "Navigation"),
+            EmbedderEvent::GoToHistoryIndex(..) => write!(f, "GoToHistoryIndex"),
             EmbedderEvent::Quit => write!(f, "Quit"),
             EmbedderEvent::Reload(..) => write!(f, "Reload"),
             EmbedderEvent::NewWebView(..) => write!(f, "NewWebView"),
+            EmbedderEvent::NewWebViewInGroup(..) => write!(f, "NewWebViewInGroup"),
             EmbedderEvent::SendError(..) => write!(f, "SendError"),
             EmbedderEvent::CloseWebView(..) => write!(f, "CloseWebView"),
             EmbedderEvent::FocusWebView(..) => write!(f, "FocusWebView"),
+            EmbedderEvent::BlurWebView(..) => write!(f, "BlurWebView"),
+            EmbedderEvent::FocusPreviousWebView => write!(f, "FocusPreviousWebView"),
+            EmbedderEvent::FocusNextWebView => write!(f, "FocusNextWebView"),
             EmbedderEvent::ToggleWebRenderDebug(..) => write!(f, "ToggleWebRenderDebug"),
             EmbedderEvent::CaptureWebRender => write!(f, "CaptureWebRender"),
             EmbedderEvent::ToggleSamplingProfiler(..) => write!(f, "ToggleSamplingProfiler"),
             EmbedderEvent::ExitFullScreen(..) => write!(f, "ExitFullScreen"),
+            EmbedderEvent::FullscreenEntered(..) => write!(f, "FullscreenEntered"),
             EmbedderEvent::MediaSessionAction(..) => write!(f, "MediaSessionAction"),
+            EmbedderEvent::PictureInPictureAction(..) => write!(f, "PictureInPictureAction"),
+            EmbedderEvent::ShareCanvasTexture(..) => write!(f, "ShareCanvasTexture"),
             EmbedderEvent::WebViewVisibilityChanged(..) => write!(f, "WebViewVisibilityChanged"),
+            EmbedderEvent::MarkAllWebViewsShown => write!(f, "MarkAllWebViewsShown"),
+            EmbedderEvent::MarkAllWebViewsNotShown => write!(f, "MarkAllWebViewsNotShown"),
             EmbedderEvent::IMEDismissed => write!(f, "IMEDismissed"),
             EmbedderEvent::ClearCache => write!(f, "ClearCache"),
+            EmbedderEvent::SetPermission(..) => write!(f, "SetPermission"),
+            EmbedderEvent::GetPermissionDecisions => write!(f, "GetPermissionDecisions"),
+            EmbedderEvent::ClearPermissionDecisions(..) => write!(f, "ClearPermissionDecisions"),
+            EmbedderEvent::GetCookies(..) => write!(f, "GetCookies"),
+            EmbedderEvent::SetCookie(..) => write!(f, "SetCookie"),
+            EmbedderEvent::DeleteCookie(..) => write!(f, "DeleteCookie"),
+            EmbedderEvent::ObserveCookieChanges(..) => write!(f, "ObserveCookieChanges"),
             EmbedderEvent::InvalidateNativeSurface => write!(f, "InvalidateNativeSurface"),
             EmbedderEvent::ReplaceNativeSurface(..) => write!(f, "ReplaceNativeSurface"),
             EmbedderEvent::Gamepad(..) => write!(f, "Gamepad"),
+            EmbedderEvent::SensorReading(..) => write!(f, "SensorReading"),
+            EmbedderEvent::EditingCommand(..) => write!(f, "EditingCommand"),
+            EmbedderEvent::GetSelection(..) => write!(f, "GetSelection"),
+            EmbedderEvent::SetCaretBrowsing(..) => write!(f, "SetCaretBrowsing"),
+            EmbedderEvent::SetJsGcSettings(..) => write!(f, "SetJsGcSettings"),
+            EmbedderEvent::ChangePaintingOrder(..) => write!(f, "ChangePaintingOrder"),
+            EmbedderEvent::SetWebViewLifecycleState(..) => write!(f, "SetWebViewLifecycleState"),
+            EmbedderEvent::GetSessionState => write!(f, "GetSessionState"),
+            EmbedderEvent::RestoreSession(..) => write!(f, "RestoreSession"),
+            EmbedderEvent::SetWebViewDevicePixelRatio(..) => {
+                write!(f, "SetWebViewDevicePixelRatio")
+            },
+            EmbedderEvent::ResizeWebView(..) => write!(f, "ResizeWebView"),
+            EmbedderEvent::GetWebViewMetadata => write!(f, "GetWebViewMetadata"),
+            EmbedderEvent::DuplicateWebView(..) => write!(f, "DuplicateWebView"),
+            EmbedderEvent::ReloadCrashedWebView(..) => write!(f, "ReloadCrashedWebView"),
+            EmbedderEvent::SetWebViewMuted(..) => write!(f, "SetWebViewMuted"),
+            EmbedderEvent::GetFrameTree(..) => write!(f, "GetFrameTree"),
+            EmbedderEvent::SetWebViewLimit(..) => write!(f, "SetWebViewLimit"),
+            EmbedderEvent::SetWebViewCsp(..) => write!(f, "SetWebViewCsp"),
+            EmbedderEvent::SetWebViewLanguages(..) => write!(f, "SetWebViewLanguages"),
+            EmbedderEvent::NewPrerenderWebView(..) => write!(f, "NewPrerenderWebView"),
+            EmbedderEvent::ActivatePrerender(..) => write!(f, "ActivatePrerender"),
+            EmbedderEvent::SetWebViewPriority(..) => write!(f, "SetWebViewPriority"),
+            EmbedderEvent::SetConstellationTracing(..) => write!(f, "SetConstellationTracing"),
+            EmbedderEvent::GetConstellationTrace => write!(f, "GetConstellationTrace"),
+            EmbedderEvent::GetWebViewMemoryUsage => write!(f, "GetWebViewMemoryUsage"),
+            EmbedderEvent::SuspendWebView(..) => write!(f, "SuspendWebView"),
+            EmbedderEvent::ResumeWebView(..) => write!(f, "ResumeWebView"),
+            EmbedderEvent::SetWebViewBackgroundColor(..) => write!(f, "SetWebViewBackgroundColor"),
+            EmbedderEvent::SetWebViewTransparent(..) => write!(f, "SetWebViewTransparent"),
+            EmbedderEvent::Find(..) => write!(f, "Find"),
+            EmbedderEvent::FindNext(..) => write!(f, "FindNext"),
+            EmbedderEvent::FindPrevious(..) => write!(f, "FindPrevious"),
+            EmbedderEvent::StopFinding(..) => write!(f, "StopFinding"),
+            EmbedderEvent::NewPrivateWebView(..) => write!(f, "NewPrivateWebView"),
+            EmbedderEvent::OpenBlockedPopup(..) => write!(f, "OpenBlockedPopup"),
+            EmbedderEvent::GetPendingNavigations(..) => write!(f, "GetPendingNavigations"),
+            EmbedderEvent::CancelPendingNavigation(..) => write!(f, "CancelPendingNavigation"),
+            EmbedderEvent::SetWebViewRenderTarget(..) => write!(f, "SetWebViewRenderTarget"),
+            EmbedderEvent::ExportWebViewFrames(..) => write!(f, "ExportWebViewFrames"),
+            EmbedderEvent::ReadBackWebViewFrames(..) => write!(f, "ReadBackWebViewFrames"),
+            EmbedderEvent::AttachWebViewSurface(..) => write!(f, "AttachWebViewSurface"),
+            EmbedderEvent::CaptureScreenshot(..) => write!(f, "CaptureScreenshot"),
+            EmbedderEvent::PrintToPdf(..) => write!(f, "PrintToPdf"),
+            EmbedderEvent::InjectInputEvents(..) => write!(f, "InjectInputEvents"),
+            EmbedderEvent::SetWebViewGestureSettings(..) => {
+                write!(f, "SetWebViewGestureSettings")
+            },
+            EmbedderEvent::Ime(..) => write!(f, "Ime"),
+            EmbedderEvent::GetImeSurroundingText(..) => write!(f, "GetImeSurroundingText"),
+            EmbedderEvent::ContextMenuAction(..) => write!(f, "ContextMenuAction"),
         }
     }
 }
@@ -177,6 +489,130 @@ pub trait WindowMethods {
     fn set_animation_state(&self, _state: AnimationState);
     /// Get the [`RenderingContext`] of this Window.
     fn rendering_context(&self) -> RenderingContext;
+    /// Called for each webview with a render target after a frame was composited to
+    /// `CompositeTarget::WebViewTextures`, once the webview was copied into its target.
+    fn webview_frame_rendered(&self, _frame: WebViewFrame) {}
+    /// Called with the buffers that the frames of a webview are exported in, once they
+    /// were allocated in a format from `EmbedderEvent::ExportWebViewFrames`, or with why
+    /// they couldn't be. New buffers are exported whenever the webview changes size.
+    #[cfg(target_os = "linux")]
+    fn webview_buffers_exported(
+        &self,
+        _webview_id: WebViewId,
+        _buffers: Result<Vec<DmabufBuffer>, DmabufExportError>,
+    ) {
+    }
+    /// Called when a webview with a native surface of its own is shown or hidden, after its
+    /// first frame was presented in the surface or when it stopped being painted, so that the
+    /// embedder can map or unmap the surface.
+    fn webview_surface_visibility_changed(&self, _webview_id: WebViewId, _visible: bool) {}
+    /// Called when a frame of a webview was exported in the buffer with this index among
+    /// those it was last given, with the part of the buffer that changed.
+    fn webview_frame_exported(
+        &self,
+        _webview_id: WebViewId,
+        _buffer: usize,
+        _damage: DeviceIntRect,
+    ) {
+    }
+    /// Called with the frames of the webviews that are read back, in the order they
+    /// were composited, a few frames after they were.
+    fn webview_frame_read_back(&self, _frame: ReadBackFrame) {}
+}
+
+/// A framebuffer of the embedder that a webview is rendered into, which must belong
+/// to the GL context of the [`RenderingContext`] or one that shares its objects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WebViewRenderTarget {
+    pub framebuffer_id: gleam::gl::GLuint,
+    /// The texture attached to the framebuffer, which is handed back with each frame.
+    pub texture_id: gleam::gl::GLuint,
+}
+
+/// A format that the consumer of the exported frames of a webview can import, as a DRM
+/// fourcc code with the modifiers it can import it with, or none for any modifier.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DmabufFormat {
+    pub fourcc: u32,
+    pub modifiers: Vec<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DmabufExportError {
+    /// The platform or the EGL implementation can't export buffers.
+    Unsupported,
+    /// The buffers would have this fourcc code and modifier, which the consumer can't import.
+    UnacceptedFormat(u32, u64),
+    Failed,
+}
+
+/// A buffer that the frames of a webview are exported in.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct DmabufBuffer {
+    pub size: DeviceIntSize,
+    pub fourcc: u32,
+    pub modifier: u64,
+    pub planes: Vec<DmabufPlane>,
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct DmabufPlane {
+    pub fd: std::os::fd::OwnedFd,
+    pub offset: u32,
+    pub stride: u32,
+}
+
+/// A frame of a webview that was rendered into its [`WebViewRenderTarget`].
+#[derive(Clone, Copy, Debug)]
+pub struct WebViewFrame {
+    pub webview_id: WebViewId,
+    pub target: WebViewRenderTarget,
+    /// The part of the target that changed, in device pixels from its bottom left corner.
+    pub damage: DeviceIntRect,
+    /// Where the webview is in the frame that Servo composited, whose coordinates are
+    /// those of the pointer events that the embedder sends for it.
+    pub origin: DeviceIntPoint,
+}
+
+/// A frame of a webview that was read back into memory.
+#[derive(Clone, Debug)]
+pub struct ReadBackFrame {
+    pub webview_id: WebViewId,
+    /// The number of frames of the webview that were composited before this one since
+    /// it started being read back. Frames are skipped while the GPU is behind.
+    pub frame_id: u64,
+    /// When the frame was composited.
+    pub timestamp: Instant,
+    pub size: DeviceIntSize,
+    /// The premultiplied RGBA pixels of the frame, top row first.
+    pub pixels: Vec<u8>,
+}
+
+/// How a webview responds to the touch gestures that aren't consumed by its content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureSettings {
+    /// The smallest pinch zoom of the webview, where 1 is its actual size.
+    pub min_zoom: f32,
+    /// The largest pinch zoom of the webview.
+    pub max_zoom: f32,
+    /// The pinch zoom that a double tap zooms the webview to, and back from. The second
+    /// tap of a double tap is a click when this is `None`.
+    pub double_tap_zoom: Option<f32>,
+    /// Whether a pan keeps scrolling for a while after it is released quickly.
+    pub fling: bool,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        GestureSettings {
+            min_zoom: 1.0,
+            max_zoom: 10.0,
+            double_tap_zoom: Some(2.0),
+            fling: true,
+        }
+    }
 }
 
 pub trait EmbedderMethods {
@@ -190,6 +626,11 @@ pub trait EmbedderMethods {
     fn get_user_agent_string(&self) -> Option<String> {
         None
     }
+
+    /// Returns the handlers of the custom URL schemes that the embedder loads itself.
+    fn get_protocol_handlers(&self) -> ProtocolRegistry {
+        ProtocolRegistry::default()
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
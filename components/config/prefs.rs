@@ -165,6 +165,10 @@ mod gen {
         std::cmp::max(num_cpus::get() * 3 / 4, 1) as i64
     }
 
+    fn default_permission_prompt_timeout() -> i64 {
+        60_000
+    }
+
     fn black() -> i64 {
         0x000000
     }
@@ -200,6 +204,23 @@ mod gen {
                 server: {
                     enabled: bool,
                     port: i64,
+                    websocket: {
+                        /// Also accept clients over an authenticated WebSocket. Clients on
+                        /// other hosts can only connect when TLS is configured.
+                        #[serde(default)]
+                        enabled: bool,
+                        #[serde(default)]
+                        port: i64,
+                        /// PEM certificate chain used to serve the WebSocket over TLS.
+                        #[serde(default)]
+                        tls_cert_path: String,
+                        /// PEM PKCS#8 private key used to serve the WebSocket over TLS.
+                        #[serde(default)]
+                        tls_key_path: String,
+                        /// The token clients must present. Defaults to the embedder token.
+                        #[serde(default)]
+                        token: String,
+                    },
                 },
             },
             dom: {
@@ -262,13 +283,36 @@ mod gen {
                 },
                 permissions: {
                     enabled: bool,
+                    /// How long a permission prompt waits for the embedder to answer, in
+                    /// milliseconds, before the request is denied. The denial isn't
+                    /// remembered, so the next request prompts again.
+                    #[serde(default = "default_permission_prompt_timeout")]
+                    prompt_timeout: i64,
                     testing: {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                picture_in_picture: {
+                    /// Enable the Picture-in-Picture API. Embedders need to handle
+                    /// `EmbedderMsg::PictureInPictureEvent` for it to have any effect.
+                    #[serde(default)]
+                    enabled: bool,
+                },
+                popups: {
+                    /// Let script open popups without transient user activation, as
+                    /// automated tests need to.
+                    #[serde(default)]
+                    allow_without_activation: bool,
+                },
                 script: {
                     asynch: bool,
                 },
+                sensors: {
+                    /// Enable the Generic Sensor and DeviceOrientation APIs. Embedders need
+                    /// to handle `EmbedderMsg::StartSensor` for them to have any effect.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 serviceworker: {
                     enabled: bool,
                     timeout_seconds: i64,
@@ -284,6 +328,26 @@ mod gen {
                 shadowdom: {
                     enabled: bool,
                 },
+                spellcheck: {
+                    /// Check the spelling of text controls with the built-in checker.
+                    /// Embedders with a spelling checker of their own leave this off.
+                    #[serde(default)]
+                    enabled: bool,
+                    /// The directory holding the Hunspell dictionaries, named like
+                    /// `en_US.aff` and `en_US.dic`.
+                    #[serde(default)]
+                    dictionaries: String,
+                },
+                storage: {
+                    /// The number of bytes each origin may keep in localStorage and
+                    /// sessionStorage together.
+                    #[serde(default)]
+                    origin_quota: i64,
+                    /// The number of bytes all origins may keep in localStorage before
+                    /// the least recently used ones are evicted.
+                    #[serde(default)]
+                    global_quota: i64,
+                },
                 svg: {
                     enabled: bool,
                 },
@@ -333,6 +397,11 @@ mod gen {
                     #[serde(default)]
                     enabled: bool,
                 },
+                video_frame_callback: {
+                    /// Enable `HTMLVideoElement.requestVideoFrameCallback`.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 webgl2: {
                     /// Enable WebGL2 APIs.
                     enabled: bool,
@@ -366,6 +435,10 @@ mod gen {
                     hands: {
                         #[serde(default)]
                         enabled: bool,
+                        /// Fire select events when the thumb and index finger of a
+                        /// tracked hand pinch, for runtimes that don't report them.
+                        #[serde(default)]
+                        pinch_select: bool,
                     },
                     layers: {
                         enabled: bool,
@@ -531,6 +604,11 @@ mod gen {
                     /// Enable hardware acceleration for video playback.
                     enabled: bool,
                 },
+                hardware_decode: {
+                    /// Prefer platform hardware video decoders (VA-API, V4L2, D3D11, VideoToolbox).
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 testing: {
                     /// Enable a non-standard event handler for verifying behavior of media elements during tests.
                     enabled: bool,
@@ -87,13 +87,15 @@
 #![allow(clippy::too_many_arguments)]
 
 use std::borrow::{Cow, ToOwned};
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
 use std::mem::replace;
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
-use std::{process, thread};
+use std::time::{Instant, SystemTime};
+use std::{fmt, process, thread};
 
 use background_hang_monitor::HangMonitorRegister;
 use bluetooth_traits::BluetoothRequest;
@@ -102,7 +104,7 @@ use canvas_traits::webgl::WebGLThreads;
 use canvas_traits::ConstellationCanvasMsg;
 use compositing_traits::{
     CompositorMsg, CompositorProxy, ConstellationMsg as FromCompositorMsg,
-    ForwardedToCompositorMsg, SendableFrameTree,
+    ForwardedToCompositorMsg, ScreenshotLayout, SendableFrameTree,
 };
 use crossbeam_channel::{after, never, select, unbounded, Receiver, Sender};
 use devtools_traits::{
@@ -110,10 +112,18 @@ use devtools_traits::{
     ScriptToDevtoolsControlMsg,
 };
 use embedder_traits::{
-    Cursor, EmbedderMsg, EmbedderProxy, MediaSessionEvent, MediaSessionPlaybackState,
+    ContextMenuAction, CookieScope, Cursor, EmbedderMsg, EmbedderProxy, FindOptions, FindResult,
+    FocusReason, FrameSelector, FrameTreeNode, HistoryEntry, JavaScriptEvaluationError,
+    MediaSessionEvent, MediaSessionPlaybackState, NavigationError, NavigationEvent,
+    NavigationPolicy, NavigationRequest, NavigationStep, NewWebViewRequest, PaintingOrderChange,
+    PendingNavigation, PendingNavigationState, PermissionDecision, PermissionName, PermissionScope,
+    PermissionSetting, PictureInPictureEvent, PrintError, PrintSettings, ScaleMode, Screenshot,
+    ScreenshotError, ScreenshotFormat, SessionState, StoredCookie, UserScript, UserScriptId,
+    WebViewLifecycleState, WebViewLoadState, WebViewMemoryUsage, WebViewMetadata, WebViewPriority,
+    WebViewSessionState, SESSION_STATE_VERSION,
 };
 use euclid::default::Size2D as UntypedSize2D;
-use euclid::Size2D;
+use euclid::{Rect, Scale, Size2D, Vector2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
@@ -127,34 +137,43 @@ use msg::constellation_msg::{
     BackgroundHangMonitorControlMsg, BackgroundHangMonitorRegister, BroadcastChannelRouterId,
     BrowsingContextGroupId, BrowsingContextId, HangMonitorAlert, HistoryStateId, MessagePortId,
     MessagePortRouterId, PipelineId, PipelineNamespace, PipelineNamespaceId,
-    PipelineNamespaceRequest, TopLevelBrowsingContextId, TraversalDirection,
+    PipelineNamespaceRequest, TopLevelBrowsingContextId, TraversalDirection, WebViewGroupId,
+    WebViewId,
 };
+use net::http_loader::set_accept_language;
+use net::permission_store::PermissionStore;
 use net_traits::pub_domains::reg_host;
 use net_traits::request::{Referrer, RequestBuilder};
+use net_traits::request_interceptor::RequestInterceptors;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
+use net_traits::{
+    self, ClearDataScope, ClearDataTypes, FetchMetadata, FetchResponseMsg, IpcSend, NetworkError,
+    ResourceThreads,
+};
 use profile_traits::{mem, time};
 use script_layout_interface::{LayoutFactory, ScriptThreadFactory};
 use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
 use script_traits::{
     webdriver_msg, AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo,
     BroadcastMsg, CompositorEvent, ConstellationControlMsg, DiscardBrowsingContext,
-    DocumentActivity, DocumentState, GamepadEvent, HistoryEntryReplacement, IFrameLoadInfo,
-    IFrameLoadInfoWithData, IFrameSandboxState, IFrameSizeMsg, Job, LayoutMsg as FromLayoutMsg,
-    LoadData, LoadOrigin, LogEntry, MediaSessionActionType, MessagePortMsg, MouseEventType,
-    PortMessageTask, SWManagerMsg, SWManagerSenders, ScriptMsg as FromScriptMsg,
-    ScriptToConstellationChan, ServiceWorkerManagerFactory, ServiceWorkerMsg,
-    StructuredSerializedData, TimerSchedulerMsg, UpdatePipelineIdReason, WebDriverCommandMsg,
-    WindowSizeData, WindowSizeType,
+    DocumentActivity, DocumentState, FindHighlights, GamepadEvent, HistoryEntryReplacement,
+    IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, IFrameSizeMsg, Job, JobError,
+    JobResult, JsGcSettings, LayoutMsg as FromLayoutMsg, LoadData, LoadOrigin, LogEntry,
+    MediaSessionActionType, MemoryPressureLevel, MessagePortMsg, MouseEventType,
+    PictureInPictureAction, PortMessageTask, SWManagerMsg, SWManagerSenders,
+    ScriptMsg as FromScriptMsg, ScriptToConstellationChan, SensorReading,
+    ServiceWorkerManagerFactory, ServiceWorkerMsg, StructuredSerializedData, TimerSchedulerMsg,
+    UpdatePipelineIdReason, WebDriverCommandMsg, WindowSizeData, WindowSizeType,
 };
 use serde::{Deserialize, Serialize};
 use servo_config::{opts, pref};
 use servo_rand::{random, Rng, ServoRng, SliceRandom};
 use servo_url::{Host, ImmutableOrigin, ServoUrl};
-use style_traits::CSSPixel;
+use style_traits::{CSSPixel, DevicePixel};
 use webgpu::{self, WebGPU, WebGPURequest};
 use webrender::{RenderApi, RenderApiSender};
-use webrender_api::DocumentId;
+use webrender_api::units::{DeviceIntRect, DeviceIntSize, LayoutPixel};
+use webrender_api::{ColorF, DocumentId};
 use webrender_traits::WebrenderExternalImageRegistry;
 
 use crate::browsingcontext::{
@@ -162,13 +181,15 @@ use crate::browsingcontext::{
     NewBrowsingContextInfo,
 };
 use crate::event_loop::EventLoop;
-use crate::network_listener::NetworkListener;
+use crate::network_listener::{NavigationFetchMsg, NetworkListener};
 use crate::pipeline::{InitialPipelineState, Pipeline};
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
 use crate::session_history::{
     JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff,
 };
 use crate::timer_scheduler::TimerScheduler;
+use crate::tracer::Tracer;
+use crate::user_activation::{is_activation_triggering, UserActivation};
 use crate::webview::WebViewManager;
 
 type PendingApprovalNavigations = HashMap<PipelineId, (LoadData, HistoryEntryReplacement)>;
@@ -231,6 +252,179 @@ struct WebView {
 
     /// The joint session history for this webview.
     session_history: JointSessionHistory,
+
+    /// Whether a caret can be moved through the content of this webview with the keyboard.
+    caret_browsing: bool,
+
+    /// The JavaScript heap limit and garbage collection settings of the script threads
+    /// of this webview.
+    js_gc_settings: JsGcSettings,
+
+    /// Whether this webview is running, frozen or discarded.
+    lifecycle_state: WebViewLifecycleState,
+
+    /// What to load when this webview is activated again after being discarded.
+    discarded_load: Option<DiscardedLoad>,
+
+    /// Where to scroll the current document once it has loaded, when this webview was
+    /// restored from a saved session.
+    scroll_offset_to_restore: Option<Vector2D<f32, LayoutPixel>>,
+
+    /// The device pixel ratio of this webview, if the embedder gave it one instead of
+    /// the ratio of the window.
+    device_pixel_ratio: Option<Scale<f32, CSSPixel, DevicePixel>>,
+
+    /// The size of the viewport of this webview, if the embedder gave it one instead of
+    /// the size of the window.
+    size: Option<DeviceIntSize>,
+
+    /// The color painted behind the documents of this webview, if the embedder set one.
+    background_color: Option<ColorF>,
+
+    /// Whether the parts of this webview without a background show what the embedder
+    /// painted under Servo, instead of the background color of the shell.
+    transparent: bool,
+
+    /// The title, URL, favicon and load state of this webview.
+    metadata: WebViewMetadata,
+
+    /// Whether the embedder asked to close this webview, which waits for its documents
+    /// to handle beforeunload.
+    closing: bool,
+
+    /// Whether the embedder muted the media elements and AudioContexts of this webview.
+    audio_muted: bool,
+
+    /// The Content-Security-Policy that the embedder enforces in every document of this
+    /// webview.
+    csp: Option<String>,
+
+    /// The languages of this webview, most preferred first, or none for the default ones.
+    languages: Vec<String>,
+
+    /// How promptly the tasks of the documents of this webview are run.
+    priority: WebViewPriority,
+
+    /// Whether the embedder suspended this webview, so that its documents are told when
+    /// they are resumed.
+    suspended: bool,
+
+    /// Whether a document in this webview is playing audio, muted or not, as last
+    /// reported to the embedder.
+    audible: bool,
+
+    /// The last search of the text of this webview, until the embedder stops it.
+    find: Option<FindState>,
+
+    /// The pipeline that asked the embedder to show one of its elements fullscreen, until
+    /// the embedder answers.
+    fullscreen_request: Option<PipelineId>,
+
+    /// Whether this webview is private, so that its documents use the in-memory cookies,
+    /// caches and storage of its group.
+    private: bool,
+
+    /// When the user last activated this webview with a click, a key press or a touch,
+    /// which lets its documents open popups for a while.
+    activation: UserActivation,
+}
+
+/// The fetch of the document of a pipeline that hasn't loaded yet.
+struct NavigationFetch {
+    /// The URL being fetched, which changes as redirects are followed.
+    url: ServoUrl,
+    redirects: usize,
+    is_form_submission: bool,
+    is_history_traversal: bool,
+}
+
+/// A search of the text of a webview, whose matches are ranked in the order of the
+/// documents in the frame tree and then in the order of the text in each document.
+struct FindState {
+    query: String,
+    options: FindOptions,
+    /// The pipelines searched, with their number of matches once they reported it.
+    match_counts: Vec<(PipelineId, Option<usize>)>,
+    /// The index of the active match among all of them, once every pipeline reported.
+    active_match: Option<usize>,
+    /// The pipeline that scrolls the active match into view next, which is the one it is
+    /// in and then each of the pipelines of the iframes that one is in.
+    scrolling_pipeline: Option<PipelineId>,
+    /// Where the matches and iframes of the pipelines searched are, as they last told.
+    highlights: HashMap<PipelineId, FindHighlights>,
+}
+
+impl FindState {
+    fn match_count(&self) -> usize {
+        self.match_counts
+            .iter()
+            .filter_map(|(_, count)| *count)
+            .sum()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.match_counts.iter().all(|(_, count)| count.is_some())
+    }
+
+    /// The pipeline that the active match is in, with its index among the matches of
+    /// that pipeline.
+    fn active_pipeline_match(&self) -> Option<(PipelineId, usize)> {
+        let mut index = self.active_match?;
+        for &(pipeline_id, count) in &self.match_counts {
+            let count = count.unwrap_or(0);
+            if index < count {
+                return Some((pipeline_id, index));
+            }
+            index -= count;
+        }
+        None
+    }
+}
+
+/// The page of a discarded webview, which is loaded again when it is activated.
+struct DiscardedLoad {
+    /// The pipeline to load the page in, which the session history may refer to.
+    pipeline_id: PipelineId,
+    url: ServoUrl,
+    new_browsing_context_info: NewBrowsingContextInfo,
+}
+
+/// A function creating the public and private resource threads of a webview group.
+pub type NewGroupResourceThreads =
+    Box<dyn Fn(WebViewGroupId) -> (ResourceThreads, ResourceThreads) + Send>;
+
+/// The resource threads of a webview group, which keep its cookies, caches and storage
+/// apart from those of other groups.
+struct WebViewGroup {
+    public_resource_threads: ResourceThreads,
+    private_resource_threads: ResourceThreads,
+}
+
+/// The unit of process isolation: a scheme together with a registrable domain.
+/// Documents of different sites can never become same-origin, so they never
+/// need to share an event loop.
+///
+/// <https://html.spec.whatwg.org/multipage/#site>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Site {
+    scheme: String,
+    host: Host,
+}
+
+impl Site {
+    /// The site of `url`, if it has a registrable domain.
+    fn from_url(url: &ServoUrl) -> Option<Site> {
+        reg_host(url).map(|host| Site {
+            scheme: url.scheme().to_owned(),
+            host,
+        })
+    }
+}
+
+impl fmt::Display for Site {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}://{}", self.scheme, self.host)
+    }
 }
 
 /// A browsing context group.
@@ -242,16 +436,18 @@ struct BrowsingContextGroup {
     top_level_browsing_context_set: HashSet<TopLevelBrowsingContextId>,
 
     /// The set of all event loops in this BrowsingContextGroup.
-    /// We store the event loops in a map
-    /// indexed by registered domain name (as a `Host`) to event loops.
-    /// It is important that scripts with the same eTLD+1,
+    /// We store the event loops in a map indexed by site.
+    /// It is important that scripts of the same site,
     /// who are part of the same browsing-context group
     /// share an event loop, since they can use `document.domain`
     /// to become same-origin, at which point they can share DOM objects.
-    event_loops: HashMap<Host, Weak<EventLoop>>,
+    /// Scripts of different sites always get different event loops, which
+    /// run in different processes when multiprocess is enabled.
+    event_loops: HashMap<Site, Weak<EventLoop>>,
 
-    /// The set of all WebGPU channels in this BrowsingContextGroup.
-    webgpus: HashMap<Host, WebGPU>,
+    /// The set of all WebGPU channels in this BrowsingContextGroup, one per
+    /// event loop.
+    webgpus: HashMap<Site, WebGPU>,
 }
 
 /// The `Constellation` itself. In the servo browser, there is one
@@ -313,10 +509,10 @@ pub struct Constellation<STF, SWF> {
     layout_receiver: Receiver<Result<FromLayoutMsg, IpcError>>,
 
     /// A channel for network listener to send messages to the constellation.
-    network_listener_sender: Sender<(PipelineId, FetchResponseMsg)>,
+    network_listener_sender: Sender<(PipelineId, NavigationFetchMsg)>,
 
     /// A channel for the constellation to receive messages from network listener.
-    network_listener_receiver: Receiver<(PipelineId, FetchResponseMsg)>,
+    network_listener_receiver: Receiver<(PipelineId, NavigationFetchMsg)>,
 
     /// A channel for the constellation to receive messages from the compositor thread.
     compositor_receiver: Receiver<FromCompositorMsg>,
@@ -331,6 +527,17 @@ pub struct Constellation<STF, SWF> {
     /// Bookkeeping data for all webviews in the constellation.
     webviews: WebViewManager<WebView>,
 
+    /// The webview whose documents were last told that they have system focus.
+    system_focus: Option<TopLevelBrowsingContextId>,
+
+    /// The number of webviews that may be running or frozen before the least recently
+    /// focused hidden ones are discarded, if the embedder set one.
+    webview_limit: Option<usize>,
+
+    /// The latest messages handled and changes to the webviews, while the embedder traces
+    /// the constellation.
+    tracer: Option<Tracer>,
+
     /// Channels for the constellation to send messages to the public
     /// resource-related threads. There are two groups of resource threads: one
     /// for public browsing, and one for private browsing.
@@ -342,6 +549,20 @@ pub struct Constellation<STF, SWF> {
     /// browsing.
     private_resource_threads: ResourceThreads,
 
+    /// Creates the public and private resource threads of a new webview group.
+    new_group_resource_threads: NewGroupResourceThreads,
+
+    /// The resource threads of each webview group other than the default one, which
+    /// uses `public_resource_threads` and `private_resource_threads`.
+    webview_groups: HashMap<WebViewGroupId, WebViewGroup>,
+
+    /// The permission decisions for webview groups and single webviews. Those for every
+    /// webview are kept by the resource threads.
+    permission_stores: HashMap<PermissionScope, PermissionStore>,
+
+    /// Whether the embedder observes the changes to the cookie jars of the webview groups.
+    observe_cookie_changes: bool,
+
     /// A channel for the constellation to send messages to the font
     /// cache thread.
     font_cache_thread: FontCacheThread,
@@ -473,6 +694,13 @@ pub struct Constellation<STF, SWF> {
     /// Navigation requests from script awaiting approval from the embedder.
     pending_approval_navigations: PendingApprovalNavigations,
 
+    /// The fetches of the documents of pipelines that are waiting for a response.
+    navigation_fetches: HashMap<PipelineId, NavigationFetch>,
+
+    /// The pipelines that load the documents of session history entries again, while the
+    /// history is traversed, until they start fetching them.
+    history_traversal_pipelines: HashSet<PipelineId>,
+
     /// Bitmask which indicates which combination of mouse buttons are
     /// currently being pressed.
     pressed_mouse_buttons: u16,
@@ -495,8 +723,18 @@ pub struct Constellation<STF, SWF> {
     /// Pipeline ID of the active media session.
     active_media_session: Option<PipelineId>,
 
+    /// Pipeline ID of the document whose video is shown in picture-in-picture.
+    picture_in_picture_pipeline: Option<PipelineId>,
+
     /// User agent string to report in network requests.
     user_agent: Cow<'static, str>,
+
+    /// The request interceptors of the embedder, which are told the webviews of pipelines.
+    request_interceptors: RequestInterceptors,
+
+    /// The scripts that the embedder injects into documents, in the order they were added
+    /// in, with the webview they are injected into or none for every webview.
+    user_scripts: Vec<(UserScriptId, Option<WebViewId>, UserScript)>,
 }
 
 /// State needed to construct a constellation.
@@ -522,6 +760,9 @@ pub struct InitialConstellationState {
     /// A channel to the resource thread.
     pub private_resource_threads: ResourceThreads,
 
+    /// Creates the public and private resource threads of a new webview group.
+    pub new_group_resource_threads: NewGroupResourceThreads,
+
     /// A channel to the time profiler thread.
     pub time_profiler_chan: time::ProfilerChan,
 
@@ -551,6 +792,9 @@ pub struct InitialConstellationState {
     /// User agent string to report in network requests.
     pub user_agent: Cow<'static, str>,
 
+    /// The request interceptors of the embedder.
+    pub request_interceptors: RequestInterceptors,
+
     pub wgpu_image_map: Arc<Mutex<HashMap<u64, webgpu::PresentationData>>>,
 }
 
@@ -594,6 +838,29 @@ enum ExitPipelineMode {
 /// The number of warnings to include in each crash report.
 const WARNINGS_BUFFER_SIZE: usize = 32;
 
+/// Move a box relative to the viewport of the document of an iframe, whose box relative to
+/// the viewport of the webview is `frame`, to the viewport of the webview, clipping it to
+/// the iframe. The box of the top-level document isn't moved.
+fn place_in_frame(rect: DeviceIntRect, frame: Option<DeviceIntRect>) -> Option<DeviceIntRect> {
+    match frame {
+        Some(frame) => rect
+            .translate(frame.origin.to_vector())
+            .intersection(&frame),
+        None => Some(rect),
+    }
+}
+
+/// The error that the embedder is told a navigation failed with.
+fn navigation_error(error: &NetworkError) -> NavigationError {
+    match error {
+        NetworkError::Internal(message) | NetworkError::Crash(message) => {
+            NavigationError::Network(message.clone())
+        },
+        NetworkError::SslValidation(message, _) => NavigationError::Certificate(message.clone()),
+        NetworkError::LoadCancelled => NavigationError::Cancelled,
+    }
+}
+
 /// Route an ipc receiver to an crossbeam receiver, preserving any errors.
 fn route_ipc_receiver_to_new_crossbeam_receiver_preserving_errors<T>(
     ipc_receiver: IpcReceiver<T>,
@@ -754,10 +1021,17 @@ where
                     embedder_proxy: state.embedder_proxy,
                     compositor_proxy: state.compositor_proxy,
                     webviews: WebViewManager::default(),
+                    system_focus: None,
+                    webview_limit: None,
+                    tracer: None,
                     devtools_sender: state.devtools_sender,
                     bluetooth_ipc_sender: state.bluetooth_thread,
                     public_resource_threads: state.public_resource_threads,
                     private_resource_threads: state.private_resource_threads,
+                    new_group_resource_threads: state.new_group_resource_threads,
+                    webview_groups: HashMap::new(),
+                    permission_stores: HashMap::new(),
+                    observe_cookie_changes: false,
                     font_cache_thread: state.font_cache_thread,
                     sw_managers: Default::default(),
                     swmanager_receiver,
@@ -805,13 +1079,18 @@ where
                     canvas_sender: canvas_create_sender,
                     canvas_ipc_sender,
                     pending_approval_navigations: HashMap::new(),
+                    navigation_fetches: HashMap::new(),
+                    history_traversal_pipelines: HashSet::new(),
                     pressed_mouse_buttons: 0,
                     hard_fail,
                     enable_canvas_antialiasing,
                     glplayer_threads: state.glplayer_threads,
                     player_context: state.player_context,
                     active_media_session: None,
+                    picture_in_picture_pipeline: None,
                     user_agent: state.user_agent,
+                    request_interceptors: state.request_interceptors,
+                    user_scripts: vec![],
                 };
 
                 constellation.run();
@@ -848,7 +1127,7 @@ where
 
     fn get_event_loop(
         &mut self,
-        host: &Host,
+        site: &Site,
         top_level_browsing_context_id: &TopLevelBrowsingContextId,
         opener: &Option<BrowsingContextId>,
     ) -> Result<Weak<EventLoop>, &'static str> {
@@ -882,7 +1161,7 @@ where
         };
         bc_group
             .event_loops
-            .get(host)
+            .get(site)
             .ok_or("Trying to get an event-loop from an unknown browsing context group")
             .map(|event_loop| event_loop.clone())
     }
@@ -890,7 +1169,7 @@ where
     fn set_event_loop(
         &mut self,
         event_loop: Weak<EventLoop>,
-        host: Host,
+        site: Site,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
         opener: Option<BrowsingContextId>,
     ) {
@@ -929,12 +1208,12 @@ where
         if let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) {
             if bc_group
                 .event_loops
-                .insert(host.clone(), event_loop)
+                .insert(site.clone(), event_loop)
                 .is_some()
             {
                 warn!(
-                    "Double-setting an event-loop for {:?} at {:?}",
-                    host, relevant_top_level
+                    "Double-setting an event-loop for {} at {:?}",
+                    site, relevant_top_level
                 );
             }
         }
@@ -966,7 +1245,7 @@ where
             pipeline_id, browsing_context_id
         );
 
-        let (event_loop, host) = match sandbox {
+        let (event_loop, site) = match sandbox {
             IFrameSandboxState::IFrameSandboxed => (None, None),
             IFrameSandboxState::IFrameUnsandboxed => {
                 // If this is an about:blank or about:srcdoc load, it must share the creator's
@@ -975,23 +1254,23 @@ where
                 if load_data.url.as_str() != "about:blank" &&
                     load_data.url.as_str() != "about:srcdoc"
                 {
-                    match reg_host(&load_data.url) {
+                    match Site::from_url(&load_data.url) {
                         None => (None, None),
-                        Some(host) => {
+                        Some(site) => {
                             match self.get_event_loop(
-                                &host,
+                                &site,
                                 &top_level_browsing_context_id,
                                 &opener,
                             ) {
                                 Err(err) => {
                                     warn!("{}", err);
-                                    (None, Some(host))
+                                    (None, Some(site))
                                 },
                                 Ok(event_loop) => {
                                     if let Some(event_loop) = event_loop.upgrade() {
                                         (Some(event_loop), None)
                                     } else {
-                                        (None, Some(host))
+                                        (None, Some(site))
                                     }
                                 },
                             }
@@ -1012,11 +1291,9 @@ where
             },
         };
 
-        let resource_threads = if is_private {
-            self.private_resource_threads.clone()
-        } else {
-            self.public_resource_threads.clone()
-        };
+        let resource_threads = self
+            .resource_threads_for_webview(top_level_browsing_context_id, is_private)
+            .clone();
 
         let result = Pipeline::spawn::<STF>(InitialPipelineState {
             id: pipeline_id,
@@ -1047,7 +1324,9 @@ where
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             window_size: WindowSizeData {
                 initial_viewport: initial_window_size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                device_pixel_ratio: self
+                    .window_size_for_webview(top_level_browsing_context_id)
+                    .device_pixel_ratio,
             },
             event_loop,
             load_data,
@@ -1065,28 +1344,38 @@ where
             user_agent: self.user_agent.clone(),
         });
 
-        let pipeline = match result {
+        let mut pipeline = match result {
             Ok(result) => result,
             Err(e) => return self.handle_send_error(pipeline_id, e),
         };
+        pipeline.pipeline.is_sandboxed = sandbox == IFrameSandboxState::IFrameSandboxed;
 
         if let Some(chan) = pipeline.bhm_control_chan {
             self.background_monitor_control_senders.push(chan);
         }
 
-        if let Some(host) = host {
+        if let Some(site) = site {
             debug!(
-                "{}: Adding new host entry {}",
-                top_level_browsing_context_id, host,
+                "{}: Adding new site entry {}",
+                top_level_browsing_context_id, site,
             );
             self.set_event_loop(
                 Rc::downgrade(&pipeline.pipeline.event_loop),
-                host,
+                site,
                 top_level_browsing_context_id,
                 opener,
             );
         }
 
+        self.inherit_caret_browsing(&pipeline.pipeline);
+        self.inherit_audio_muted(&pipeline.pipeline);
+        self.inherit_csp(&pipeline.pipeline);
+        self.inherit_user_scripts(&pipeline.pipeline);
+        self.inherit_languages(&pipeline.pipeline);
+        self.inherit_priority(&pipeline.pipeline);
+        self.inherit_js_gc_settings(&pipeline.pipeline);
+        self.request_interceptors
+            .set_pipeline_webview(pipeline_id, Some(top_level_browsing_context_id));
         assert!(!self.pipelines.contains_key(&pipeline_id));
         self.pipelines.insert(pipeline_id, pipeline.pipeline);
     }
@@ -1202,7 +1491,7 @@ where
             BackgroundHangMonitor(HangMonitorAlert),
             Compositor(FromCompositorMsg),
             Layout(FromLayoutMsg),
-            NetworkListener((PipelineId, FetchResponseMsg)),
+            NetworkListener((PipelineId, NavigationFetchMsg)),
             FromSWManager(SWManagerMsg),
             Timer(TimerSchedulerMsg),
         }
@@ -1264,6 +1553,20 @@ where
             Err(err) => return error!("Deserialization failed ({}).", err),
         };
 
+        if let Some(tracer) = self.tracer.as_mut() {
+            match request {
+                Request::Compositor(ref message) => tracer.record_compositor_msg(message),
+                Request::Script((pipeline_id, ref message)) => {
+                    let webview_id = self
+                        .pipelines
+                        .get(&pipeline_id)
+                        .map(|pipeline| pipeline.top_level_browsing_context_id);
+                    tracer.record_script_msg(format!("{:?}", message), webview_id, pipeline_id);
+                },
+                _ => {},
+            }
+        }
+
         match request {
             Request::PipelineNamespace(message) => {
                 self.handle_request_for_pipeline_namespace(message)
@@ -1288,6 +1591,10 @@ where
                 self.timer_scheduler.handle_timer_request(message);
             },
         }
+
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record_transitions(self.webviews.take_transitions());
+        }
     }
 
     fn handle_request_for_pipeline_namespace(&mut self, request: PipelineNamespaceRequest) {
@@ -1308,8 +1615,39 @@ where
         }
     }
 
-    fn handle_request_from_network_listener(&mut self, message: (PipelineId, FetchResponseMsg)) {
+    fn handle_request_from_network_listener(&mut self, message: (PipelineId, NavigationFetchMsg)) {
         let (id, message_) = message;
+        let message_ = match message_ {
+            NavigationFetchMsg::Redirected(url) => {
+                let fetch = match self.navigation_fetches.get_mut(&id) {
+                    Some(fetch) => fetch,
+                    None => return,
+                };
+                fetch.url = url.clone();
+                fetch.redirects += 1;
+                let step = NavigationStep::Started {
+                    is_redirect: true,
+                    is_form_submission: fetch.is_form_submission,
+                    is_history_traversal: fetch.is_history_traversal,
+                };
+                return self.send_navigation_event(id, url, step);
+            },
+            NavigationFetchMsg::Response(message_) => message_,
+        };
+        if let FetchResponseMsg::ProcessResponse(ref response) = message_ {
+            if let Some(fetch) = self.navigation_fetches.remove(&id) {
+                let step = match response {
+                    Ok(FetchMetadata::Unfiltered(metadata)) |
+                    Ok(FetchMetadata::Filtered {
+                        unsafe_: metadata, ..
+                    }) => NavigationStep::ResponseReceived {
+                        status: metadata.status.as_ref().map(|(code, _)| *code),
+                    },
+                    Err(error) => NavigationStep::Failed(navigation_error(error)),
+                };
+                self.send_navigation_event(id, fetch.url, step);
+            }
+        }
         let result = match self.pipelines.get(&id) {
             Some(pipeline) => {
                 let msg = ConstellationControlMsg::NavigationResponse(id, message_);
@@ -1351,13 +1689,16 @@ where
             FromCompositorMsg::Keyboard(key_event) => {
                 self.handle_key_msg(key_event);
             },
+            FromCompositorMsg::WebViewKeyboard(top_level_browsing_context_id, key_event) => {
+                self.handle_webview_key_msg(top_level_browsing_context_id, key_event);
+            },
             FromCompositorMsg::IMEDismissed => {
                 self.handle_ime_dismissed();
             },
             // Perform a navigation previously requested by script, if approved by the embedder.
             // If there is already a pending page (self.pending_changes), it will not be overridden;
             // However, if the id is not encompassed by another change, it will be.
-            FromCompositorMsg::AllowNavigationResponse(pipeline_id, allowed) => {
+            FromCompositorMsg::AllowNavigationResponse(pipeline_id, policy) => {
                 let pending = self.pending_approval_navigations.remove(&pipeline_id);
 
                 let top_level_browsing_context_id = match self.pipelines.get(&pipeline_id) {
@@ -1366,41 +1707,25 @@ where
                 };
 
                 match pending {
-                    Some((load_data, replace)) => {
-                        if allowed {
+                    Some((load_data, replace)) => match policy {
+                        NavigationPolicy::Allow => {
                             self.load_url(
                                 top_level_browsing_context_id,
                                 pipeline_id,
                                 load_data,
                                 replace,
                             );
-                        } else {
-                            let pipeline_is_top_level_pipeline = self
-                                .browsing_contexts
-                                .get(&BrowsingContextId::from(top_level_browsing_context_id))
-                                .map(|ctx| ctx.pipeline_id == pipeline_id)
-                                .unwrap_or(false);
-                            // If the navigation is refused, and this concerns an iframe,
-                            // we need to take it out of it's "delaying-load-events-mode".
-                            // https://html.spec.whatwg.org/multipage/#delaying-load-events-mode
-                            if !pipeline_is_top_level_pipeline {
-                                let msg = ConstellationControlMsg::StopDelayingLoadEventsMode(
-                                    pipeline_id,
-                                );
-                                let result = match self.pipelines.get(&pipeline_id) {
-                                    Some(pipeline) => pipeline.event_loop.send(msg),
-                                    None => {
-                                        return warn!(
-                                            "{}: Attempted to navigate after closure",
-                                            pipeline_id
-                                        );
-                                    },
-                                };
-                                if let Err(e) = result {
-                                    self.handle_send_error(pipeline_id, e);
-                                }
-                            }
-                        }
+                        },
+                        NavigationPolicy::Deny => {
+                            self.refuse_navigation(top_level_browsing_context_id, pipeline_id);
+                        },
+                        NavigationPolicy::OpenInNewWebView => {
+                            self.refuse_navigation(top_level_browsing_context_id, pipeline_id);
+                            self.open_navigation_in_new_webview(
+                                top_level_browsing_context_id,
+                                load_data,
+                            );
+                        },
                     },
                     None => {
                         warn!(
@@ -1411,8 +1736,71 @@ where
                 }
             },
             FromCompositorMsg::ClearCache => {
-                self.public_resource_threads.clear_cache();
-                self.private_resource_threads.clear_cache();
+                for (public_resource_threads, private_resource_threads) in
+                    self.all_resource_threads()
+                {
+                    public_resource_threads.clear_cache();
+                    private_resource_threads.clear_cache();
+                }
+            },
+            FromCompositorMsg::SetPermission(scope, decision) => {
+                self.handle_set_permission_msg(scope, decision);
+            },
+            FromCompositorMsg::GetPermissionDecisions => {
+                self.handle_get_permission_decisions_msg();
+            },
+            FromCompositorMsg::ClearPermissionDecisions(origin) => {
+                for resource_threads in self
+                    .all_resource_threads()
+                    .flat_map(|(public, private)| [public, private])
+                {
+                    let msg = net_traits::CoreResourceMsg::ClearPermissionDecisions(origin.clone());
+                    if let Err(e) = resource_threads.send(msg) {
+                        warn!(
+                            "Sending ClearPermissionDecisions to resource thread failed ({:?}).",
+                            e
+                        );
+                    }
+                }
+                for store in self.permission_stores.values_mut() {
+                    store.clear(origin.as_ref());
+                }
+            },
+            FromCompositorMsg::ClearData(scope, data_types, since) => {
+                self.handle_clear_data_msg(scope, data_types, since);
+            },
+            FromCompositorMsg::GetCookies(scope, url) => {
+                self.handle_get_cookies_msg(scope, url);
+            },
+            FromCompositorMsg::SetCookie(scope, cookie) => {
+                for (_, resource_threads) in self.cookie_resource_threads(scope) {
+                    let msg = net_traits::CoreResourceMsg::SetStoredCookie(cookie.clone());
+                    if let Err(e) = resource_threads.send(msg) {
+                        warn!(
+                            "Sending SetStoredCookie to resource thread failed ({:?}).",
+                            e
+                        );
+                    }
+                }
+            },
+            FromCompositorMsg::DeleteCookie(scope, cookie) => {
+                for (_, resource_threads) in self.cookie_resource_threads(scope) {
+                    let msg = net_traits::CoreResourceMsg::DeleteStoredCookie(cookie.clone());
+                    if let Err(e) = resource_threads.send(msg) {
+                        warn!(
+                            "Sending DeleteStoredCookie to resource thread failed ({:?}).",
+                            e
+                        );
+                    }
+                }
+            },
+            FromCompositorMsg::ObserveCookieChanges(observe) => {
+                self.observe_cookie_changes = observe;
+                for (group_id, resource_threads) in
+                    self.cookie_resource_threads(CookieScope::Global)
+                {
+                    self.send_cookie_observer(group_id, resource_threads);
+                }
             },
             // Load a new page from a typed url
             // If there is already a pending page (self.pending_changes), it will not be overridden;
@@ -1455,12 +1843,33 @@ where
             },
             // Create a new top level browsing context. Will use response_chan to return
             // the browsing context id.
-            FromCompositorMsg::NewWebView(url, top_level_browsing_context_id) => {
-                self.handle_new_top_level_browsing_context(url, top_level_browsing_context_id);
+            FromCompositorMsg::NewWebView(url, top_level_browsing_context_id, group_id) => {
+                self.handle_new_top_level_browsing_context(
+                    url,
+                    top_level_browsing_context_id,
+                    group_id,
+                    true,
+                    false,
+                );
+            },
+            FromCompositorMsg::NewPrivateWebView(url, top_level_browsing_context_id, group_id) => {
+                self.handle_new_top_level_browsing_context(
+                    url,
+                    top_level_browsing_context_id,
+                    group_id,
+                    true,
+                    true,
+                );
+            },
+            FromCompositorMsg::NewPrerenderWebView(url, top_level_browsing_context_id) => {
+                self.handle_new_prerender_webview_msg(url, top_level_browsing_context_id);
+            },
+            FromCompositorMsg::ActivatePrerender(top_level_browsing_context_id) => {
+                self.handle_activate_prerender_msg(top_level_browsing_context_id);
             },
             // Close a top level browsing context.
             FromCompositorMsg::CloseWebView(top_level_browsing_context_id) => {
-                self.handle_close_top_level_browsing_context(top_level_browsing_context_id);
+                self.handle_close_webview_msg(top_level_browsing_context_id);
             },
             // Panic a top level browsing context.
             FromCompositorMsg::SendError(top_level_browsing_context_id, error) => {
@@ -1470,28 +1879,47 @@ where
                 }
                 self.handle_panic(top_level_browsing_context_id, error, None);
             },
-            FromCompositorMsg::FocusWebView(top_level_browsing_context_id) => {
+            FromCompositorMsg::FocusWebView(top_level_browsing_context_id, reason) => {
                 if self.webviews.get(top_level_browsing_context_id).is_none() {
                     return warn!("{top_level_browsing_context_id}: FocusWebView on unknown top-level browsing context");
                 }
-                self.webviews.focus(top_level_browsing_context_id);
-                self.embedder_proxy.send((
-                    Some(top_level_browsing_context_id),
-                    EmbedderMsg::WebViewFocused(top_level_browsing_context_id),
-                ));
-                if !cfg!(feature = "multiview") {
-                    self.update_frame_tree_if_focused(top_level_browsing_context_id);
+                // A webview whose native window lost focus is still the focused one,
+                // and only its documents need to know that they have focus again.
+                let is_focused = self.webviews.focused_webview().map(|(id, _)| id) ==
+                    Some(top_level_browsing_context_id);
+                if !(reason == FocusReason::NativeWindow && is_focused) {
+                    self.webviews.focus(top_level_browsing_context_id);
+                    self.notify_webview_focused(top_level_browsing_context_id);
                 }
+                self.set_system_focus(Some(top_level_browsing_context_id));
             },
-            FromCompositorMsg::BlurWebView => {
-                self.webviews.unfocus();
-                self.embedder_proxy
-                    .send((None, EmbedderMsg::WebViewBlurred));
+            FromCompositorMsg::FocusPreviousWebView => {
+                if let Some(top_level_browsing_context_id) = self.webviews.focus_previous() {
+                    self.notify_webview_focused(top_level_browsing_context_id);
+                    self.set_system_focus(Some(top_level_browsing_context_id));
+                }
+            },
+            FromCompositorMsg::FocusNextWebView => {
+                if let Some(top_level_browsing_context_id) = self.webviews.focus_next() {
+                    self.notify_webview_focused(top_level_browsing_context_id);
+                    self.set_system_focus(Some(top_level_browsing_context_id));
+                }
+            },
+            FromCompositorMsg::BlurWebView(reason) => {
+                self.set_system_focus(None);
+                if reason != FocusReason::NativeWindow {
+                    self.webviews.unfocus();
+                    self.embedder_proxy
+                        .send((None, EmbedderMsg::WebViewBlurred));
+                }
             },
             // Handle a forward or back request
             FromCompositorMsg::TraverseHistory(top_level_browsing_context_id, direction) => {
                 self.handle_traverse_history_msg(top_level_browsing_context_id, direction);
             },
+            FromCompositorMsg::GoToHistoryIndex(top_level_browsing_context_id, index) => {
+                self.handle_go_to_history_index_msg(top_level_browsing_context_id, index);
+            },
             FromCompositorMsg::WindowSize(top_level_browsing_context_id, new_size, size_type) => {
                 self.handle_window_size_msg(top_level_browsing_context_id, new_size, size_type);
             },
@@ -1510,6 +1938,9 @@ where
             FromCompositorMsg::ForwardEvent(destination_pipeline_id, event) => {
                 self.forward_event(destination_pipeline_id, event);
             },
+            FromCompositorMsg::OpenBlockedPopup(popup) => {
+                self.handle_open_blocked_popup_msg(popup);
+            },
             FromCompositorMsg::SetCursor(cursor) => self.handle_set_cursor_msg(cursor),
             FromCompositorMsg::EnableProfiler(rate, max_duration) => {
                 for background_monitor_control_sender in &self.background_monitor_control_senders {
@@ -1532,11 +1963,41 @@ where
             FromCompositorMsg::ExitFullScreen(top_level_browsing_context_id) => {
                 self.handle_exit_fullscreen_msg(top_level_browsing_context_id);
             },
+            FromCompositorMsg::FullscreenEntered(top_level_browsing_context_id, entered) => {
+                self.handle_fullscreen_entered_msg(top_level_browsing_context_id, entered);
+            },
             FromCompositorMsg::MediaSessionAction(action) => {
                 self.handle_media_session_action_msg(action);
             },
+            FromCompositorMsg::PictureInPictureAction(action) => {
+                self.handle_picture_in_picture_action_msg(action);
+            },
+            FromCompositorMsg::ShareCanvasTexture(top_level_browsing_context_id, selector) => {
+                self.handle_share_canvas_texture_msg(top_level_browsing_context_id, selector);
+            },
             FromCompositorMsg::WebViewVisibilityChanged(webview_id, visible) => {
-                self.notify_webview_visibility(webview_id, visible);
+                let changed = if visible {
+                    self.webviews.mark_webview_shown(webview_id)
+                } else {
+                    self.webviews.mark_webview_not_shown(webview_id)
+                };
+                if changed {
+                    self.notify_webview_visibility(webview_id, visible);
+                }
+                if !visible {
+                    self.discard_webviews_over_limit(None);
+                }
+            },
+            FromCompositorMsg::MarkAllWebViewsShown => {
+                for webview_id in self.webviews.mark_all_webviews_shown() {
+                    self.notify_webview_visibility(webview_id, true);
+                }
+            },
+            FromCompositorMsg::MarkAllWebViewsNotShown => {
+                for webview_id in self.webviews.mark_all_webviews_not_shown() {
+                    self.notify_webview_visibility(webview_id, false);
+                }
+                self.discard_webviews_over_limit(None);
             },
             FromCompositorMsg::ReadyToPresent(top_level_browsing_context_id) => {
                 self.embedder_proxy.send((
@@ -1547,6 +2008,188 @@ where
             FromCompositorMsg::Gamepad(gamepad_event) => {
                 self.handle_gamepad_msg(gamepad_event);
             },
+            FromCompositorMsg::SensorReading(reading) => {
+                self.handle_sensor_reading_msg(reading);
+            },
+            FromCompositorMsg::EditingCommand(top_level_browsing_context_id, command) => {
+                self.send_to_focused_document(top_level_browsing_context_id, |pipeline_id| {
+                    ConstellationControlMsg::EditingCommand(pipeline_id, command)
+                });
+            },
+            FromCompositorMsg::GetSelection(top_level_browsing_context_id) => {
+                self.send_to_focused_document(
+                    top_level_browsing_context_id,
+                    ConstellationControlMsg::GetSelection,
+                );
+            },
+            FromCompositorMsg::Ime(top_level_browsing_context_id, event) => {
+                self.send_to_focused_document(top_level_browsing_context_id, |pipeline_id| {
+                    ConstellationControlMsg::SendEvent(
+                        pipeline_id,
+                        CompositorEvent::ImeEvent(event),
+                    )
+                });
+            },
+            FromCompositorMsg::GetImeSurroundingText(top_level_browsing_context_id, sender) => {
+                self.send_to_focused_document(top_level_browsing_context_id, |pipeline_id| {
+                    ConstellationControlMsg::GetImeSurroundingText(pipeline_id, sender)
+                });
+            },
+            FromCompositorMsg::ContextMenuAction(webview_id, browsing_context_id, action) => {
+                self.handle_context_menu_action_msg(webview_id, browsing_context_id, action);
+            },
+            FromCompositorMsg::AddUserScript(id, webview_id, script) => {
+                self.user_scripts.push((id, webview_id, script));
+                self.update_user_scripts();
+            },
+            FromCompositorMsg::EvaluateJavaScript(webview_id, frame, source, sender) => {
+                self.handle_evaluate_javascript_msg(webview_id, frame, source, sender);
+            },
+            FromCompositorMsg::RemoveUserScript(id) => {
+                self.user_scripts.retain(|&(other_id, _, _)| other_id != id);
+                self.update_user_scripts();
+            },
+            FromCompositorMsg::SetCaretBrowsing(top_level_browsing_context_id, enabled) => {
+                self.handle_set_caret_browsing_msg(top_level_browsing_context_id, enabled);
+            },
+            FromCompositorMsg::Find(top_level_browsing_context_id, query, options) => {
+                self.handle_find_msg(top_level_browsing_context_id, query, options);
+            },
+            FromCompositorMsg::FindNext(top_level_browsing_context_id) => {
+                self.handle_find_next_msg(top_level_browsing_context_id, false);
+            },
+            FromCompositorMsg::FindPrevious(top_level_browsing_context_id) => {
+                self.handle_find_next_msg(top_level_browsing_context_id, true);
+            },
+            FromCompositorMsg::StopFinding(top_level_browsing_context_id) => {
+                self.handle_stop_finding_msg(top_level_browsing_context_id);
+            },
+            FromCompositorMsg::MemoryPressure(level) => {
+                self.handle_memory_pressure_msg(level);
+            },
+            FromCompositorMsg::SetJsGcSettings(top_level_browsing_context_id, settings) => {
+                self.handle_set_js_gc_settings_msg(top_level_browsing_context_id, settings);
+            },
+            FromCompositorMsg::CompositorIdle => {
+                self.handle_compositor_idle_msg();
+            },
+            FromCompositorMsg::ChangePaintingOrder(top_level_browsing_context_id, change) => {
+                self.handle_change_painting_order_msg(top_level_browsing_context_id, change);
+            },
+            FromCompositorMsg::SetWebViewLifecycleState(top_level_browsing_context_id, state) => {
+                self.handle_set_webview_lifecycle_state_msg(top_level_browsing_context_id, state);
+            },
+            FromCompositorMsg::GetSessionState => {
+                self.handle_get_session_state_msg();
+            },
+            FromCompositorMsg::RestoreSession(session_state) => {
+                self.handle_restore_session_msg(session_state);
+            },
+            FromCompositorMsg::SetWebViewDevicePixelRatio(top_level_browsing_context_id, ratio) => {
+                self.handle_set_webview_device_pixel_ratio_msg(
+                    top_level_browsing_context_id,
+                    ratio,
+                );
+            },
+            FromCompositorMsg::ResizeWebView(webview_id, size) => {
+                self.handle_resize_webview_msg(webview_id, size);
+            },
+            FromCompositorMsg::GetWebViewMetadata => {
+                self.handle_get_webview_metadata_msg();
+            },
+            FromCompositorMsg::GetWebViewMemoryUsage => {
+                self.handle_get_webview_memory_usage_msg();
+            },
+            FromCompositorMsg::DuplicateWebView(webview_id) => {
+                self.handle_duplicate_webview_msg(webview_id);
+            },
+            FromCompositorMsg::ReloadCrashedWebView(webview_id) => {
+                self.handle_reload_crashed_webview_msg(webview_id);
+            },
+            FromCompositorMsg::SetWebViewMuted(webview_id, muted) => {
+                self.handle_set_webview_muted_msg(webview_id, muted);
+            },
+            FromCompositorMsg::GetPendingNavigations(webview_id) => {
+                self.handle_get_pending_navigations_msg(webview_id);
+            },
+            FromCompositorMsg::CancelPendingNavigation(webview_id) => {
+                self.handle_cancel_pending_navigation_msg(webview_id);
+            },
+            FromCompositorMsg::CaptureScreenshot(webview_id, rect, scale_mode, format, sender) => {
+                self.handle_capture_screenshot_msg(webview_id, rect, scale_mode, format, sender);
+            },
+            FromCompositorMsg::PrintToPdf(webview_id, settings, sender) => {
+                self.handle_print_to_pdf_msg(webview_id, settings, sender);
+            },
+            FromCompositorMsg::GetFrameTree(webview_id) => {
+                self.handle_get_frame_tree_msg(webview_id);
+            },
+            FromCompositorMsg::SetWebViewCsp(webview_id, policy) => {
+                self.handle_set_webview_csp_msg(webview_id, policy);
+            },
+            FromCompositorMsg::SetWebViewLanguages(webview_id, languages) => {
+                self.handle_set_webview_languages_msg(webview_id, languages);
+            },
+            FromCompositorMsg::SuspendWebView(webview_id) => {
+                self.handle_suspend_webview_msg(webview_id);
+            },
+            FromCompositorMsg::ResumeWebView(webview_id) => {
+                let is_suspended = self
+                    .webviews
+                    .get(webview_id)
+                    .map_or(false, |webview| webview.suspended);
+                if !is_suspended {
+                    return warn!(
+                        "{}: Tried to resume a webview that isn't suspended",
+                        webview_id
+                    );
+                }
+                self.activate_webview(webview_id);
+            },
+            FromCompositorMsg::SetWebViewBackgroundColor(webview_id, color) => {
+                match self.webviews.get_mut(webview_id) {
+                    Some(webview) => webview.background_color = color,
+                    None => {
+                        return warn!(
+                            "{}: Tried to set the background color of nonexistent webview",
+                            webview_id,
+                        );
+                    },
+                }
+                self.compositor_proxy
+                    .send(CompositorMsg::SetWebViewBackgroundColor(webview_id, color));
+            },
+            FromCompositorMsg::SetWebViewTransparent(webview_id, transparent) => {
+                match self.webviews.get_mut(webview_id) {
+                    Some(webview) => webview.transparent = transparent,
+                    None => {
+                        return warn!(
+                            "{}: Tried to set the transparency of nonexistent webview",
+                            webview_id,
+                        );
+                    },
+                }
+                self.compositor_proxy
+                    .send(CompositorMsg::SetWebViewTransparent(
+                        webview_id,
+                        transparent,
+                    ));
+            },
+            FromCompositorMsg::SetWebViewPriority(webview_id, priority) => {
+                self.handle_set_webview_priority_msg(webview_id, priority);
+            },
+            FromCompositorMsg::SetConstellationTracing(capacity) => {
+                self.handle_set_constellation_tracing_msg(capacity);
+            },
+            FromCompositorMsg::GetConstellationTrace => {
+                let trace = self.tracer.as_ref().map(|tracer| tracer.trace().to_json());
+                self.embedder_proxy
+                    .send((None, EmbedderMsg::ConstellationTrace(trace)));
+            },
+            FromCompositorMsg::SetWebViewLimit(limit) => {
+                self.webview_limit = limit;
+                self.discard_webviews_over_limit(None);
+            },
         }
     }
 
@@ -1627,6 +2270,7 @@ where
                 self.handle_schedule_broadcast(source_pipeline_id, router_id, message);
             },
             FromScriptMsg::ForwardToEmbedder(embedder_msg) => {
+                self.update_webview_metadata(source_top_ctx_id, &embedder_msg);
                 self.embedder_proxy
                     .send((Some(source_top_ctx_id), embedder_msg));
             },
@@ -1652,6 +2296,34 @@ where
             FromScriptMsg::ScriptNewAuxiliary(load_info) => {
                 self.handle_script_new_auxiliary(load_info);
             },
+            FromScriptMsg::AllowOpeningWebView(
+                url,
+                target_name,
+                noopener,
+                features,
+                response_sender,
+            ) => {
+                let popup = NewWebViewRequest {
+                    opener: source_top_ctx_id,
+                    opener_pipeline: source_pipeline_id,
+                    url,
+                    target_name,
+                    noopener,
+                    features,
+                };
+                self.handle_allow_opening_webview_msg(popup, response_sender);
+            },
+            FromScriptMsg::HasTransientActivation(response_sender) => {
+                let has_transient_activation = self
+                    .webviews
+                    .get(source_top_ctx_id)
+                    .map_or(false, |webview| {
+                        webview.activation.is_active(Instant::now())
+                    });
+                if let Err(e) = response_sender.send(has_transient_activation) {
+                    warn!("Failed to send HasTransientActivation response ({:?}).", e);
+                }
+            },
             FromScriptMsg::ChangeRunningAnimationsState(animation_state) => {
                 self.handle_change_running_animations_state(source_pipeline_id, animation_state)
             },
@@ -1802,6 +2474,9 @@ where
             FromScriptMsg::ScheduleJob(job) => {
                 self.handle_schedule_serviceworker_job(source_pipeline_id, job);
             },
+            FromScriptMsg::ClearSiteData(data_types) => {
+                self.handle_clear_site_data_msg(source_pipeline_id, data_types);
+            },
             FromScriptMsg::ForwardDOMMessage(msg_vec, scope_url) => {
                 if let Some(mgr) = self.sw_managers.get(&scope_url.origin()) {
                     let _ = mgr.send(ServiceWorkerMsg::ForwardDOMMessage(msg_vec, scope_url));
@@ -1842,6 +2517,18 @@ where
                     EmbedderMsg::MediaSessionEvent(event),
                 ));
             },
+            FromScriptMsg::PictureInPictureEvent(pipeline_id, event) => {
+                self.handle_picture_in_picture_event(source_top_ctx_id, pipeline_id, event);
+            },
+            FromScriptMsg::RequestFullscreen(rect) => {
+                self.handle_request_fullscreen_msg(source_top_ctx_id, source_pipeline_id, rect);
+            },
+            FromScriptMsg::CanvasTextureShared(selector, image) => {
+                self.embedder_proxy.send((
+                    Some(source_top_ctx_id),
+                    EmbedderMsg::CanvasTextureShared(selector, image),
+                ));
+            },
             FromScriptMsg::RequestAdapter(response_sender, options, ids) => self
                 .handle_wgpu_request(
                     source_pipeline_id,
@@ -1853,24 +2540,89 @@ where
                 BrowsingContextId::from(source_top_ctx_id),
                 FromScriptMsg::GetWebGPUChan(response_sender),
             ),
-            FromScriptMsg::TitleChanged(pipeline, title) => {
-                if let Some(pipeline) = self.pipelines.get_mut(&pipeline) {
-                    pipeline.title = title;
+            FromScriptMsg::TitleChanged(pipeline_id, title) => {
+                self.handle_title_changed_msg(pipeline_id, title);
+            },
+            FromScriptMsg::PromptToUnloadResult(can_unload) => {
+                self.handle_prompt_to_unload_result(source_top_ctx_id, can_unload);
+            },
+            FromScriptMsg::AudioStateChanged(audible) => {
+                if let Some(pipeline) = self.pipelines.get_mut(&source_pipeline_id) {
+                    pipeline.is_audible = audible;
                 }
+                self.update_webview_audio_state(source_top_ctx_id);
             },
-        }
-    }
-
-    /// Check the origin of a message against that of the pipeline it came from.
-    /// Note: this is still limited as a security check,
-    /// see <https://github.com/servo/servo/issues/11722>
-    fn check_origin_against_pipeline(
-        &self,
-        pipeline_id: &PipelineId,
-        origin: &ImmutableOrigin,
-    ) -> Result<(), ()> {
-        let pipeline_origin = match self.pipelines.get(pipeline_id) {
-            Some(pipeline) => pipeline.load_data.url.origin(),
+            FromScriptMsg::GetPermission(origin, name, sender) => {
+                if self
+                    .check_origin_against_pipeline(&source_pipeline_id, &origin)
+                    .is_err()
+                {
+                    warn!(
+                        "{}: Permission asked for an origin other than the pipeline's",
+                        source_pipeline_id
+                    );
+                    let _ = sender.send(None);
+                    return;
+                }
+                self.handle_get_permission_msg(source_pipeline_id, origin, name, sender);
+            },
+            FromScriptMsg::SetPermission(decision) => {
+                if !decision.origin.is_tuple() {
+                    return;
+                }
+                if self
+                    .check_origin_against_pipeline(&source_pipeline_id, &decision.origin)
+                    .is_err()
+                {
+                    return warn!(
+                        "{}: Permission set for an origin other than the pipeline's",
+                        source_pipeline_id
+                    );
+                }
+                let scope = PermissionScope::WebView(source_top_ctx_id);
+                self.handle_set_permission_msg(scope, decision.clone());
+                self.embedder_proxy.send((
+                    Some(source_top_ctx_id),
+                    EmbedderMsg::PermissionDecisionChanged(scope, decision),
+                ));
+            },
+            FromScriptMsg::FindMatches(query, count) => {
+                self.handle_find_matches_msg(source_top_ctx_id, source_pipeline_id, query, count);
+            },
+            FromScriptMsg::ActiveFindMatch(rect) => {
+                self.handle_active_find_match_msg(source_top_ctx_id, source_pipeline_id, rect);
+            },
+            FromScriptMsg::FindHighlights(highlights) => {
+                self.handle_find_highlights_msg(source_top_ctx_id, source_pipeline_id, highlights);
+            },
+            FromScriptMsg::CaptureForPrint(viewport_size, epoch, sender) => {
+                let layout = ScreenshotLayout {
+                    viewport_size,
+                    epoch,
+                };
+                self.compositor_proxy.send(CompositorMsg::CaptureScreenshot(
+                    source_top_ctx_id,
+                    source_pipeline_id,
+                    Some(layout),
+                    None,
+                    ScaleMode::DevicePixels,
+                    ScreenshotFormat::Rgba8,
+                    sender,
+                ));
+            },
+        }
+    }
+
+    /// Check the origin of a message against that of the pipeline it came from.
+    /// Note: this is still limited as a security check,
+    /// see <https://github.com/servo/servo/issues/11722>
+    fn check_origin_against_pipeline(
+        &self,
+        pipeline_id: &PipelineId,
+        origin: &ImmutableOrigin,
+    ) -> Result<(), ()> {
+        let pipeline_origin = match self.pipelines.get(pipeline_id) {
+            Some(pipeline) => pipeline.load_data.url.origin(),
             None => {
                 warn!("Received message from closed or unknown pipeline.");
                 return Err(());
@@ -2036,8 +2788,8 @@ where
             Some(pipeline) => pipeline,
             None => return warn!("{}: ScriptMsg from closed pipeline", source_pipeline_id),
         };
-        let host = match reg_host(&source_pipeline.url) {
-            Some(host) => host,
+        let site = match Site::from_url(&source_pipeline.url) {
+            Some(site) => site,
             None => return warn!("Invalid host url"),
         };
         let browsing_context_group = if let Some(bcg) = self
@@ -2048,7 +2800,7 @@ where
         } else {
             return warn!("Browsing context group not found");
         };
-        let webgpu_chan = match browsing_context_group.webgpus.entry(host) {
+        let webgpu_chan = match browsing_context_group.webgpus.entry(site) {
             Entry::Vacant(v) => WebGPU::new(
                 self.webrender_wgpu.webrender_api.create_sender(),
                 self.webrender_document,
@@ -2498,6 +3250,20 @@ where
             );
         }
 
+        // Registrations outlive the documents that make them, so like other engines
+        // private webviews can't register service workers.
+        let is_private = self
+            .pipelines
+            .get(&pipeline_id)
+            .and_then(|pipeline| self.webviews.get(pipeline.top_level_browsing_context_id))
+            .map_or(false, |webview| webview.private);
+        if is_private {
+            let _ = job
+                .client
+                .send(JobResult::RejectPromise(JobError::SecurityError));
+            return;
+        }
+
         // This match is equivalent to Entry.or_insert_with but allows for early return.
         let sw_manager = match self.sw_managers.entry(origin.clone()) {
             Entry::Occupied(entry) => entry.into_mut(),
@@ -2535,8 +3301,26 @@ where
         new_value: Option<String>,
     ) {
         let origin = url.origin();
+        // Each group has its own storage, and so do its private webviews.
+        let storage_of = |top_level_browsing_context_id| {
+            let is_private = self
+                .webviews
+                .get(top_level_browsing_context_id)
+                .map_or(false, |webview| webview.private);
+            (
+                self.webviews.group(top_level_browsing_context_id),
+                is_private,
+            )
+        };
+        let source_storage = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => storage_of(pipeline.top_level_browsing_context_id),
+            None => return warn!("{}: Storage event from closed pipeline", pipeline_id),
+        };
         for pipeline in self.pipelines.values() {
-            if (pipeline.id != pipeline_id) && (pipeline.url.origin() == origin) {
+            if (pipeline.id != pipeline_id) &&
+                (pipeline.url.origin() == origin) &&
+                storage_of(pipeline.top_level_browsing_context_id) == source_storage
+            {
                 let msg = ConstellationControlMsg::DispatchStorageEvent(
                     pipeline.id,
                     storage,
@@ -2645,6 +3429,8 @@ where
             ipc::channel().expect("Failed to create IPC channel!");
         let (storage_ipc_sender, storage_ipc_receiver) =
             ipc::channel().expect("Failed to create IPC channel!");
+        let (private_storage_ipc_sender, private_storage_ipc_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
 
         debug!("Exiting core resource threads.");
         if let Err(e) = self
@@ -2669,6 +3455,39 @@ where
         {
             warn!("Exit storage thread failed ({})", e);
         }
+        if let Err(e) = self
+            .private_resource_threads
+            .send(StorageThreadMsg::Exit(private_storage_ipc_sender))
+        {
+            warn!("Exit private storage thread failed ({})", e);
+        }
+
+        debug!("Exiting resource threads of webview groups.");
+        let mut group_exit_receivers = vec![];
+        for (group_id, group) in self.webview_groups.iter() {
+            let (core_sender, core_receiver) =
+                ipc::channel().expect("Failed to create IPC channel!");
+            let (storage_sender, storage_receiver) =
+                ipc::channel().expect("Failed to create IPC channel!");
+            let resource_threads = &group.public_resource_threads;
+            match resource_threads.send(net_traits::CoreResourceMsg::Exit(core_sender)) {
+                Ok(()) => group_exit_receivers.push(core_receiver),
+                Err(e) => warn!("Exit resource thread of {} failed ({})", group_id, e),
+            }
+            match resource_threads.send(StorageThreadMsg::Exit(storage_sender)) {
+                Ok(()) => group_exit_receivers.push(storage_receiver),
+                Err(e) => warn!("Exit storage thread of {} failed ({})", group_id, e),
+            }
+            let (private_storage_sender, private_storage_receiver) =
+                ipc::channel().expect("Failed to create IPC channel!");
+            match group
+                .private_resource_threads
+                .send(StorageThreadMsg::Exit(private_storage_sender))
+            {
+                Ok(()) => group_exit_receivers.push(private_storage_receiver),
+                Err(e) => warn!("Exit private storage thread of {} failed ({})", group_id, e),
+            }
+        }
 
         debug!("Exiting bluetooth thread.");
         if let Err(e) = self.bluetooth_ipc_sender.send(BluetoothRequest::Exit) {
@@ -2734,6 +3553,14 @@ where
         if let Err(e) = storage_ipc_receiver.recv() {
             warn!("Exit storage thread failed ({:?})", e);
         }
+        if let Err(e) = private_storage_ipc_receiver.recv() {
+            warn!("Exit private storage thread failed ({:?})", e);
+        }
+        for receiver in group_exit_receivers {
+            if let Err(e) = receiver.recv() {
+                warn!("Exit resource thread of webview group failed ({:?})", e);
+            }
+        }
 
         debug!("Asking compositor to complete shutdown.");
         self.compositor_proxy.send(CompositorMsg::ShutdownComplete);
@@ -2744,7 +3571,34 @@ where
 
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("{}: Exited", pipeline_id);
-        self.pipelines.remove(&pipeline_id);
+        let pipeline = self.pipelines.remove(&pipeline_id);
+        self.navigation_fetches.remove(&pipeline_id);
+        self.history_traversal_pipelines.remove(&pipeline_id);
+        self.request_interceptors
+            .set_pipeline_webview(pipeline_id, None);
+
+        if let Some(ref pipeline) = pipeline {
+            if pipeline.is_audible {
+                self.update_webview_audio_state(pipeline.top_level_browsing_context_id);
+            }
+            // The webview of the pipeline is gone if it is closing.
+            let had_languages = self
+                .webviews
+                .get(pipeline.top_level_browsing_context_id)
+                .map_or(true, |webview| !webview.languages.is_empty());
+            if had_languages {
+                self.send_pipeline_languages_to_resource_threads(pipeline_id, vec![]);
+            }
+        }
+
+        // Don't leave the embedder showing a surface nobody will update anymore.
+        if self.picture_in_picture_pipeline == Some(pipeline_id) {
+            self.picture_in_picture_pipeline = None;
+            self.embedder_proxy.send((
+                pipeline.map(|pipeline| pipeline.top_level_browsing_context_id),
+                EmbedderMsg::PictureInPictureEvent(PictureInPictureEvent::Leave),
+            ));
+        }
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
@@ -2788,6 +3642,16 @@ where
             EmbedderMsg::Panic(reason.clone(), backtrace.clone()),
         ));
 
+        if self
+            .webviews
+            .mark_webview_crashed(top_level_browsing_context_id)
+        {
+            self.embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::WebViewCrashed,
+            ));
+        }
+
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
             Some(context) => context,
             None => return warn!("failed browsing context is missing"),
@@ -2855,6 +3719,60 @@ where
         });
     }
 
+    /// Replace the crash page of a webview with a new pipeline for the page that crashed.
+    fn handle_reload_crashed_webview_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        if !self.webviews.is_crashed(top_level_browsing_context_id) {
+            return warn!(
+                "{}: Tried to reload a webview that didn't crash",
+                top_level_browsing_context_id
+            );
+        }
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(context) => context,
+            None => return warn!("crashed browsing context is missing"),
+        };
+        let window_size = browsing_context.size;
+        let is_private = browsing_context.is_private;
+        let is_visible = browsing_context.is_visible;
+        let crash_pipeline_id = browsing_context.pipeline_id;
+        let (opener, load_data) = match self.pipelines.get(&crash_pipeline_id) {
+            Some(pipeline) => (
+                pipeline.opener,
+                LoadData {
+                    crash: None,
+                    ..pipeline.load_data.clone()
+                },
+            ),
+            None => return warn!("crash page pipeline is missing"),
+        };
+
+        let new_pipeline_id = PipelineId::new();
+        self.new_pipeline(
+            new_pipeline_id,
+            browsing_context_id,
+            top_level_browsing_context_id,
+            None,
+            opener,
+            window_size,
+            load_data,
+            IFrameSandboxState::IFrameUnsandboxed,
+            is_private,
+            is_visible,
+        );
+        self.add_pending_change(SessionHistoryChange {
+            top_level_browsing_context_id,
+            browsing_context_id,
+            new_pipeline_id,
+            replace: Some(NeedsToReload::No(crash_pipeline_id)),
+            new_browsing_context_info: None,
+            window_size,
+        });
+    }
+
     fn handle_log_entry(
         &mut self,
         top_level_browsing_context_id: Option<TopLevelBrowsingContextId>,
@@ -2893,16 +3811,23 @@ where
         }
 
         let event = match event {
-            MouseButtonEvent(event_type, button, point, node_address, point_in_node, _) => {
-                MouseButtonEvent(
-                    event_type,
-                    button,
-                    point,
-                    node_address,
-                    point_in_node,
-                    self.pressed_mouse_buttons,
-                )
-            },
+            MouseButtonEvent(
+                event_type,
+                button,
+                point,
+                node_address,
+                point_in_node,
+                _,
+                modifiers,
+            ) => MouseButtonEvent(
+                event_type,
+                button,
+                point,
+                node_address,
+                point_in_node,
+                self.pressed_mouse_buttons,
+                modifiers,
+            ),
             MouseMoveEvent(point, node_address, _) => {
                 MouseMoveEvent(point, node_address, self.pressed_mouse_buttons)
             },
@@ -2921,6 +3846,15 @@ where
             Some(pipeline) => pipeline,
         };
 
+        if is_activation_triggering(&event) {
+            if let Some(webview) = self
+                .webviews
+                .get_mut(pipeline.top_level_browsing_context_id)
+            {
+                webview.activation.activate(Instant::now());
+            }
+        }
+
         self.embedder_proxy.send((
             Some(pipeline.top_level_browsing_context_id),
             EmbedderMsg::EventDelivered((&event).into()),
@@ -2938,15 +3872,10 @@ where
         &mut self,
         url: ServoUrl,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
+        group_id: WebViewGroupId,
+        is_visible: bool,
+        is_private: bool,
     ) {
-        let window_size = self.window_size.initial_viewport;
-        let pipeline_id = PipelineId::new();
-        let msg = (
-            Some(top_level_browsing_context_id),
-            EmbedderMsg::WebViewOpened(top_level_browsing_context_id),
-        );
-        self.embedder_proxy.send(msg);
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         let load_data = LoadData::new(
             LoadOrigin::Constellation,
             url,
@@ -2955,28 +3884,29 @@ where
             None,
             None,
         );
-        let sandbox = IFrameSandboxState::IFrameUnsandboxed;
-        let is_private = false;
-        let is_visible = true;
-
-        // Register this new top-level browsing context id as a webview and set
-        // its focused browsing context to be itself.
-        self.webviews.add(
+        self.new_webview_with_load_data(
+            load_data,
             top_level_browsing_context_id,
-            WebView {
-                focused_browsing_context_id: browsing_context_id,
-                session_history: JointSessionHistory::new(),
-            },
+            group_id,
+            is_visible,
+            is_private,
         );
+    }
 
-        // https://html.spec.whatwg.org/multipage/#creating-a-new-browsing-context-group
-        let mut new_bc_group: BrowsingContextGroup = Default::default();
-        let new_bc_group_id = self.next_browsing_context_group_id();
-        new_bc_group
-            .top_level_browsing_context_set
-            .insert(top_level_browsing_context_id);
-        self.browsing_context_group_set
-            .insert(new_bc_group_id, new_bc_group);
+    fn new_webview_with_load_data(
+        &mut self,
+        load_data: LoadData,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        group_id: WebViewGroupId,
+        is_visible: bool,
+        is_private: bool,
+    ) {
+        let window_size = self.window_size.initial_viewport;
+        let pipeline_id = PipelineId::new();
+        self.add_webview(top_level_browsing_context_id, group_id, is_private);
+        self.discard_webviews_over_limit(Some(top_level_browsing_context_id));
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let sandbox = IFrameSandboxState::IFrameUnsandboxed;
 
         self.new_pipeline(
             pipeline_id,
@@ -3005,69 +3935,378 @@ where
         });
     }
 
-    fn handle_close_top_level_browsing_context(
+    /// Create a hidden webview that isn't painted, and whose documents are throttled like
+    /// those of other hidden webviews while they load.
+    fn handle_new_prerender_webview_msg(
         &mut self,
+        url: ServoUrl,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
-        debug!("{top_level_browsing_context_id}: Closing");
+        self.handle_new_top_level_browsing_context(
+            url,
+            top_level_browsing_context_id,
+            WebViewGroupId::default(),
+            false,
+            false,
+        );
+        self.webviews
+            .mark_webview_not_shown(top_level_browsing_context_id);
+        self.webviews
+            .mark_webview_prerendering(top_level_browsing_context_id);
+    }
+
+    /// Put a prerendered webview in place of the focused one, which is hidden, in a single
+    /// change of the painting order.
+    fn handle_activate_prerender_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        // Its visibility can't change before the browsing context exists.
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let browsing_context =
-            self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
-        if self.webviews.focused_webview().map(|(id, _)| id) == Some(top_level_browsing_context_id)
+        if !self.browsing_contexts.contains_key(&browsing_context_id) {
+            return warn!(
+                "{}: Tried to activate a prerender before it loaded a page",
+                top_level_browsing_context_id,
+            );
+        }
+        if !self
+            .webviews
+            .mark_webview_prerendered(top_level_browsing_context_id)
         {
-            self.embedder_proxy
-                .send((None, EmbedderMsg::WebViewBlurred));
+            return warn!(
+                "{}: Tried to activate a webview that isn't a prerender",
+                top_level_browsing_context_id,
+            );
         }
-        self.webviews.remove(top_level_browsing_context_id);
-        // TODO Send the compositor a RemoveWebView event.
+
+        let replaced = self.webviews.focused_webview().map(|(id, _)| id);
+        let replaced_index = replaced
+            .and_then(|replaced| self.webviews.painting_order().position(|id| id == replaced));
+        match replaced_index {
+            Some(index) => self
+                .webviews
+                .set_index(top_level_browsing_context_id, index),
+            None => self.webviews.raise_to_top(top_level_browsing_context_id),
+        }
+        if let Some(replaced) = replaced {
+            if self.webviews.mark_webview_not_shown(replaced) {
+                self.notify_webview_visibility(replaced, false);
+            }
+        }
+        if self
+            .webviews
+            .mark_webview_shown(top_level_browsing_context_id)
+        {
+            self.notify_webview_visibility(top_level_browsing_context_id, true);
+        }
+
+        self.webviews.focus(top_level_browsing_context_id);
+        self.notify_webview_focused(top_level_browsing_context_id);
+        if self.system_focus.is_some() {
+            self.set_system_focus(Some(top_level_browsing_context_id));
+        }
+        self.send_painting_order();
         self.embedder_proxy.send((
             Some(top_level_browsing_context_id),
-            EmbedderMsg::WebViewClosed(top_level_browsing_context_id),
+            EmbedderMsg::PrerenderActivated(replaced),
         ));
+    }
 
-        let Some(browsing_context) = browsing_context else {
+    /// Forget the cookies, caches and web storage of the private webviews of a group once
+    /// none of them is left, so that the next private webview starts without them.
+    fn clear_private_data_if_unused(&mut self, group_id: WebViewGroupId) {
+        let in_use = self
+            .webviews
+            .iter()
+            .any(|(top_level_browsing_context_id, webview)| {
+                webview.private && self.webviews.group(top_level_browsing_context_id) == group_id
+            });
+        if in_use {
             return;
+        }
+        debug!("Clearing the private data of {}", group_id);
+        let private_resource_threads = match self.webview_groups.get(&group_id) {
+            Some(group) => &group.private_resource_threads,
+            None => &self.private_resource_threads,
         };
-        // https://html.spec.whatwg.org/multipage/#bcg-remove
-        let bc_group_id = browsing_context.bc_group_id;
-        let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) else {
-            warn!("{}: Browsing context group not found!", bc_group_id);
-            return;
+        let data_types = ClearDataTypes {
+            cache: true,
+            cookies: true,
+            hsts: true,
+            ..ClearDataTypes::default()
         };
-        if !bc_group
-            .top_level_browsing_context_set
-            .remove(&top_level_browsing_context_id)
-        {
-            warn!(
-                "{top_level_browsing_context_id}: Top-level browsing context not found in {bc_group_id}",
-            );
+        let msg = net_traits::CoreResourceMsg::ClearData(None, data_types, None);
+        if let Err(e) = private_resource_threads.send(msg) {
+            warn!("Sending ClearData to resource thread failed ({:?}).", e);
         }
-        if bc_group.top_level_browsing_context_set.is_empty() {
-            self.browsing_context_group_set
-                .remove(&browsing_context.bc_group_id);
+        if let Err(e) = private_resource_threads.send(StorageThreadMsg::ClearData(None)) {
+            warn!("Sending ClearData to storage thread failed ({:?}).", e);
         }
-
-        debug!("{top_level_browsing_context_id}: Closed");
     }
 
-    fn handle_iframe_size_msg(&mut self, iframe_sizes: Vec<IFrameSizeMsg>) {
-        for IFrameSizeMsg {
-            browsing_context_id,
-            size,
-            type_,
-        } in iframe_sizes
-        {
-            let window_size = WindowSizeData {
-                initial_viewport: size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
-            };
+    /// Register a new top-level browsing context as a webview in its own browsing context
+    /// group, without loading anything in it yet.
+    fn add_webview(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        group_id: WebViewGroupId,
+        is_private: bool,
+    ) {
+        let msg = (
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewOpened(top_level_browsing_context_id),
+        );
+        self.embedder_proxy.send(msg);
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
 
-            self.resize_browsing_context(window_size, type_, browsing_context_id);
+        // Register this new top-level browsing context id as a webview and set
+        // its focused browsing context to be itself.
+        self.webviews.add_to_group(
+            top_level_browsing_context_id,
+            WebView {
+                focused_browsing_context_id: browsing_context_id,
+                session_history: JointSessionHistory::new(),
+                caret_browsing: false,
+                js_gc_settings: JsGcSettings::default(),
+                lifecycle_state: WebViewLifecycleState::Active,
+                discarded_load: None,
+                scroll_offset_to_restore: None,
+                device_pixel_ratio: None,
+                size: None,
+                metadata: WebViewMetadata::default(),
+                closing: false,
+                audio_muted: false,
+                audible: false,
+                csp: None,
+                languages: vec![],
+                priority: WebViewPriority::default(),
+                suspended: false,
+                background_color: None,
+                transparent: false,
+                find: None,
+                fullscreen_request: None,
+                private: is_private,
+                activation: UserActivation::default(),
+            },
+            group_id,
+        );
+        if group_id != WebViewGroupId::default() && !self.webview_groups.contains_key(&group_id) {
+            debug!("Creating resource threads for {}", group_id);
+            let (public_resource_threads, private_resource_threads) =
+                (self.new_group_resource_threads)(group_id);
+            if self.observe_cookie_changes {
+                self.send_cookie_observer(group_id, &public_resource_threads);
+            }
+            self.webview_groups.insert(
+                group_id,
+                WebViewGroup {
+                    public_resource_threads,
+                    private_resource_threads,
+                },
+            );
         }
-    }
 
-    fn handle_subframe_loaded(&mut self, pipeline_id: PipelineId) {
-        let browsing_context_id = match self.pipelines.get(&pipeline_id) {
+        // https://html.spec.whatwg.org/multipage/#creating-a-new-browsing-context-group
+        let mut new_bc_group: BrowsingContextGroup = Default::default();
+        let new_bc_group_id = self.next_browsing_context_group_id();
+        new_bc_group
+            .top_level_browsing_context_set
+            .insert(top_level_browsing_context_id);
+        self.browsing_context_group_set
+            .insert(new_bc_group_id, new_bc_group);
+    }
+
+    /// Close a webview for the embedder, once its documents have handled beforeunload.
+    fn handle_close_webview_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) if webview.closing => return,
+            Some(webview) => webview.closing = true,
+            None => {
+                return warn!(
+                    "{}: Tried to close nonexistent webview",
+                    top_level_browsing_context_id
+                )
+            },
+        }
+
+        // A discarded webview has no documents to ask.
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return self.handle_prompt_to_unload_result(top_level_browsing_context_id, true)
+            },
+        };
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline
+                .event_loop
+                .send(ConstellationControlMsg::PromptToUnload(pipeline_id)),
+            None => {
+                return self.handle_prompt_to_unload_result(top_level_browsing_context_id, true)
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+            self.handle_prompt_to_unload_result(top_level_browsing_context_id, true);
+        }
+    }
+
+    fn handle_prompt_to_unload_result(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        can_unload: bool,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) if webview.closing => webview.closing = false,
+            _ => return,
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewCloseResult(can_unload),
+        ));
+        if can_unload {
+            self.handle_close_top_level_browsing_context(top_level_browsing_context_id);
+        }
+    }
+
+    fn handle_close_top_level_browsing_context(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        debug!("{top_level_browsing_context_id}: Closing");
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let browsing_context =
+            self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
+        if self.webviews.focused_webview().map(|(id, _)| id) == Some(top_level_browsing_context_id)
+        {
+            self.embedder_proxy
+                .send((None, EmbedderMsg::WebViewBlurred));
+        }
+        self.permission_stores
+            .remove(&PermissionScope::WebView(top_level_browsing_context_id));
+        let group_id = self.webviews.group(top_level_browsing_context_id);
+        if let Some(webview) = self.webviews.remove(top_level_browsing_context_id) {
+            if webview.private {
+                self.clear_private_data_if_unused(group_id);
+            }
+            if webview.device_pixel_ratio.is_some() {
+                self.compositor_proxy
+                    .send(CompositorMsg::SetWebViewDevicePixelRatio(
+                        top_level_browsing_context_id,
+                        None,
+                    ));
+            }
+            if webview.size.is_some() {
+                self.compositor_proxy.send(CompositorMsg::ResizeWebView(
+                    top_level_browsing_context_id,
+                    None,
+                ));
+            }
+            if webview.background_color.is_some() {
+                self.compositor_proxy
+                    .send(CompositorMsg::SetWebViewBackgroundColor(
+                        top_level_browsing_context_id,
+                        None,
+                    ));
+            }
+            if webview.transparent {
+                self.compositor_proxy
+                    .send(CompositorMsg::SetWebViewTransparent(
+                        top_level_browsing_context_id,
+                        false,
+                    ));
+            }
+            if webview.find.is_some() {
+                self.compositor_proxy.send(CompositorMsg::SetFindHighlights(
+                    top_level_browsing_context_id,
+                    vec![],
+                    vec![],
+                ));
+            }
+        }
+        self.send_painting_order();
+        // TODO Send the compositor a RemoveWebView event.
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewClosed(top_level_browsing_context_id),
+        ));
+
+        // A discarded webview has no browsing context, but is still in its group.
+        let bc_group_id = match browsing_context {
+            Some(browsing_context) => browsing_context.bc_group_id,
+            None => match self
+                .browsing_context_group_set
+                .iter()
+                .find(|(_, bc_group)| {
+                    bc_group
+                        .top_level_browsing_context_set
+                        .contains(&top_level_browsing_context_id)
+                })
+                .map(|(bc_group_id, _)| *bc_group_id)
+            {
+                Some(bc_group_id) => bc_group_id,
+                None => return,
+            },
+        };
+        // https://html.spec.whatwg.org/multipage/#bcg-remove
+        let Some(bc_group) = self.browsing_context_group_set.get_mut(&bc_group_id) else {
+            warn!("{}: Browsing context group not found!", bc_group_id);
+            return;
+        };
+        if !bc_group
+            .top_level_browsing_context_set
+            .remove(&top_level_browsing_context_id)
+        {
+            warn!(
+                "{top_level_browsing_context_id}: Top-level browsing context not found in {bc_group_id}",
+            );
+        }
+        if bc_group.top_level_browsing_context_set.is_empty() {
+            self.browsing_context_group_set.remove(&bc_group_id);
+        }
+
+        debug!("{top_level_browsing_context_id}: Closed");
+    }
+
+    fn handle_iframe_size_msg(&mut self, iframe_sizes: Vec<IFrameSizeMsg>) {
+        for IFrameSizeMsg {
+            browsing_context_id,
+            size,
+            type_,
+        } in iframe_sizes
+        {
+            // The browsing context of a new iframe may not have been created yet.
+            let top_level_browsing_context_id = self
+                .browsing_contexts
+                .get(&browsing_context_id)
+                .map(|browsing_context| browsing_context.top_level_id)
+                .or_else(|| {
+                    self.pending_changes
+                        .iter()
+                        .find(|change| change.browsing_context_id == browsing_context_id)
+                        .map(|change| change.top_level_browsing_context_id)
+                });
+            let device_pixel_ratio = match top_level_browsing_context_id {
+                Some(top_level_browsing_context_id) => {
+                    self.window_size_for_webview(top_level_browsing_context_id)
+                        .device_pixel_ratio
+                },
+                None => self.window_size.device_pixel_ratio,
+            };
+            let window_size = WindowSizeData {
+                initial_viewport: size,
+                device_pixel_ratio,
+            };
+
+            self.resize_browsing_context(window_size, type_, browsing_context_id);
+        }
+    }
+
+    fn handle_subframe_loaded(&mut self, pipeline_id: PipelineId) {
+        let browsing_context_id = match self.pipelines.get(&pipeline_id) {
             Some(pipeline) => pipeline.browsing_context_id,
             None => return warn!("{}: Subframe loaded after closure", pipeline_id),
         };
@@ -3107,15 +4346,49 @@ where
     }
 
     fn handle_navigate_request(
-        &self,
+        &mut self,
         id: PipelineId,
         request_builder: RequestBuilder,
         cancel_chan: IpcReceiver<()>,
     ) {
+        let top_level_browsing_context_id = match self.pipelines.get(&id) {
+            Some(pipeline) => pipeline.top_level_browsing_context_id,
+            None => return warn!("{}: Navigate request for closed pipeline", id),
+        };
+        // The pipeline that will show the response doesn't exist yet.
+        let mut request_builder = request_builder;
+        let mut is_private = false;
+        if let Some(webview) = self.webviews.get(top_level_browsing_context_id) {
+            if !webview.languages.is_empty() {
+                set_accept_language(&mut request_builder.headers, &webview.languages);
+            }
+            is_private = webview.private;
+        }
+        let is_form_submission = self
+            .pipelines
+            .get(&id)
+            .map_or(false, |pipeline| pipeline.load_data.is_form_submission);
+        let is_history_traversal = self.history_traversal_pipelines.remove(&id);
+        self.navigation_fetches.insert(
+            id,
+            NavigationFetch {
+                url: request_builder.url.clone(),
+                redirects: 0,
+                is_form_submission,
+                is_history_traversal,
+            },
+        );
+        let step = NavigationStep::Started {
+            is_redirect: false,
+            is_form_submission,
+            is_history_traversal,
+        };
+        self.send_navigation_event(id, request_builder.url.clone(), step);
         let listener = NetworkListener::new(
             request_builder,
             id,
-            self.public_resource_threads.clone(),
+            self.resource_threads_for_webview(top_level_browsing_context_id, is_private)
+                .clone(),
             self.network_listener_sender.clone(),
         );
 
@@ -3263,7 +4536,7 @@ where
                 },
             };
         let is_private = is_private || is_parent_private;
-        let pipeline = Pipeline::new(
+        let mut pipeline = Pipeline::new(
             new_pipeline_id,
             browsing_context_id,
             top_level_browsing_context_id,
@@ -3273,7 +4546,16 @@ where
             is_parent_visible,
             load_info.load_data,
         );
-
+        pipeline.is_sandboxed = load_info.sandbox == IFrameSandboxState::IFrameSandboxed;
+
+        self.inherit_caret_browsing(&pipeline);
+        self.inherit_audio_muted(&pipeline);
+        self.inherit_csp(&pipeline);
+        self.inherit_user_scripts(&pipeline);
+        self.inherit_languages(&pipeline);
+        self.inherit_priority(&pipeline);
+        self.request_interceptors
+            .set_pipeline_webview(new_pipeline_id, Some(top_level_browsing_context_id));
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
         self.add_pending_change(SessionHistoryChange {
@@ -3311,9 +4593,14 @@ where
                     );
                 },
             };
-        let (is_opener_private, is_opener_visible, is_opener_secure) =
+        let (is_opener_private, is_opener_visible, is_opener_secure, opener_webview_id) =
             match self.browsing_contexts.get(&opener_browsing_context_id) {
-                Some(ctx) => (ctx.is_private, ctx.is_visible, ctx.inherited_secure_context),
+                Some(ctx) => (
+                    ctx.is_private,
+                    ctx.is_visible,
+                    ctx.inherited_secure_context,
+                    ctx.top_level_id,
+                ),
                 None => {
                     return warn!(
                         "{}: New auxiliary {} loaded in closed opener browsing context",
@@ -3334,13 +4621,40 @@ where
 
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
-        self.webviews.add(
+        // The auxiliary runs in the event loop of its opener, so it is in the same group.
+        let group_id = self.webviews.group(opener_webview_id);
+        self.webviews.add_to_group(
             new_top_level_browsing_context_id,
             WebView {
                 focused_browsing_context_id: new_browsing_context_id,
                 session_history: JointSessionHistory::new(),
-            },
+                caret_browsing: false,
+                js_gc_settings: JsGcSettings::default(),
+                lifecycle_state: WebViewLifecycleState::Active,
+                discarded_load: None,
+                scroll_offset_to_restore: None,
+                device_pixel_ratio: None,
+                size: None,
+                metadata: WebViewMetadata::default(),
+                closing: false,
+                audio_muted: false,
+                audible: false,
+                csp: None,
+                languages: vec![],
+                priority: WebViewPriority::default(),
+                suspended: false,
+                background_color: None,
+                transparent: false,
+                find: None,
+                fullscreen_request: None,
+                private: is_opener_private,
+                activation: UserActivation::default(),
+            },
+            group_id,
         );
+        self.webviews
+            .set_opener(new_top_level_browsing_context_id, opener_webview_id);
+        self.discard_webviews_over_limit(Some(new_top_level_browsing_context_id));
 
         // https://html.spec.whatwg.org/multipage/#bcg-append
         let opener = match self.browsing_contexts.get(&opener_browsing_context_id) {
@@ -3377,6 +4691,61 @@ where
         });
     }
 
+    /// Block a popup unless its webview has transient user activation, which it consumes,
+    /// so that each click, key press or touch opens at most one popup. The embedder is
+    /// asked about the popups that aren't blocked, and answers script with the id of the
+    /// webview to create, which `handle_script_new_auxiliary` then adds. The embedder is
+    /// told about the popups that are blocked.
+    /// <https://html.spec.whatwg.org/multipage/#consume-user-activation>
+    fn handle_allow_opening_webview_msg(
+        &mut self,
+        popup: NewWebViewRequest,
+        response_sender: IpcSender<Option<TopLevelBrowsingContextId>>,
+    ) {
+        let has_transient_activation = self
+            .webviews
+            .get_mut(popup.opener)
+            .map_or(false, |webview| webview.activation.consume(Instant::now()));
+        if has_transient_activation || pref!(dom.popups.allow_without_activation) {
+            let opener = popup.opener;
+            let msg = EmbedderMsg::AllowOpeningWebView(popup, response_sender);
+            return self.embedder_proxy.send((Some(opener), msg));
+        }
+
+        debug!("{}: Blocked popup {:?}", popup.opener_pipeline, popup.url);
+        if let Err(e) = response_sender.send(None) {
+            warn!("Failed to send AllowOpeningWebView response ({:?}).", e);
+        }
+        let opener = popup.opener;
+        self.embedder_proxy
+            .send((Some(opener), EmbedderMsg::PopupBlocked(popup)));
+    }
+
+    /// Open a popup that was blocked, activating its webview as if the user had clicked
+    /// in it so that the popup isn't blocked again.
+    fn handle_open_blocked_popup_msg(&mut self, popup: NewWebViewRequest) {
+        let is_current = self
+            .pipelines
+            .get(&popup.opener_pipeline)
+            .map_or(false, |pipeline| {
+                pipeline.top_level_browsing_context_id == popup.opener
+            });
+        if !is_current {
+            return warn!(
+                "{}: Blocked popup opened after closure",
+                popup.opener_pipeline
+            );
+        }
+        if let Some(webview) = self.webviews.get_mut(popup.opener) {
+            webview.activation.activate(Instant::now());
+        }
+        let pipeline_id = popup.opener_pipeline;
+        self.send_to_pipeline(
+            pipeline_id,
+            ConstellationControlMsg::OpenBlockedPopup(popup),
+        );
+    }
+
     fn handle_pending_paint_metric(&self, pipeline_id: PipelineId, epoch: Epoch) {
         self.compositor_proxy
             .send(CompositorMsg::PendingPaintMetric(pipeline_id, epoch))
@@ -3438,13 +4807,89 @@ where
             },
         };
         // Allow the embedder to handle the url itself
+        let browsing_context_id = match self.pipelines.get(&source_id) {
+            Some(pipeline) => pipeline.browsing_context_id,
+            None => return warn!("{}: Navigated after closure", source_id),
+        };
+        let initiator = load_data
+            .initiator_pipeline_id
+            .and_then(|pipeline_id| self.pipelines.get(&pipeline_id))
+            .map(|pipeline| pipeline.browsing_context_id);
+        let has_user_gesture = self
+            .webviews
+            .get(top_level_browsing_context_id)
+            .map_or(false, |webview| {
+                webview.activation.is_active(Instant::now())
+            });
+        let request = NavigationRequest {
+            pipeline_id: source_id,
+            browsing_context_id,
+            is_top_level: browsing_context_id == top_level_browsing_context_id,
+            url: load_data.url.clone(),
+            initiator,
+            has_user_gesture,
+            is_form_submission: load_data.is_form_submission,
+        };
         let msg = (
             Some(top_level_browsing_context_id),
-            EmbedderMsg::AllowNavigationRequest(source_id, load_data.url.clone()),
+            EmbedderMsg::AllowNavigationRequest(request),
         );
         self.embedder_proxy.send(msg);
     }
 
+    /// Load the URL of a navigation that the embedder diverted in a new webview, in the
+    /// group of the webview that it was denied in.
+    fn open_navigation_in_new_webview(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        load_data: LoadData,
+    ) {
+        let is_private = self
+            .webviews
+            .get(top_level_browsing_context_id)
+            .map_or(false, |webview| webview.private);
+        let group_id = self.webviews.group(top_level_browsing_context_id);
+        let new_top_level_browsing_context_id = TopLevelBrowsingContextId::new();
+        debug!(
+            "{}: Opening navigation to {} in {}",
+            top_level_browsing_context_id, load_data.url, new_top_level_browsing_context_id
+        );
+        self.new_webview_with_load_data(
+            load_data,
+            new_top_level_browsing_context_id,
+            group_id,
+            true,
+            is_private,
+        );
+    }
+
+    fn refuse_navigation(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+    ) {
+        let pipeline_is_top_level_pipeline = self
+            .browsing_contexts
+            .get(&BrowsingContextId::from(top_level_browsing_context_id))
+            .map(|ctx| ctx.pipeline_id == pipeline_id)
+            .unwrap_or(false);
+        // If the navigation is refused, and this concerns an iframe,
+        // we need to take it out of it's "delaying-load-events-mode".
+        // https://html.spec.whatwg.org/multipage/#delaying-load-events-mode
+        if !pipeline_is_top_level_pipeline {
+            let msg = ConstellationControlMsg::StopDelayingLoadEventsMode(pipeline_id);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => {
+                    return warn!("{}: Attempted to navigate after closure", pipeline_id);
+                },
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
     fn load_url(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
@@ -3611,6 +5056,7 @@ where
             debug!("{}: Marking as loaded", pipeline_id);
             pipeline.completely_loaded = true;
         }
+        self.restore_scroll_offset(top_level_browsing_context_id, pipeline_id);
 
         // Notify the embedder that the TopLevelBrowsingContext current document
         // has finished loading.
@@ -3669,6 +5115,37 @@ where
         }
     }
 
+    /// Traverse the session history of a webview to the entry at an index of the entries
+    /// that `notify_history_changed` sends, with the current one at the number of past ones.
+    fn handle_go_to_history_index_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        index: usize,
+    ) {
+        let session_history = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => &webview.session_history,
+            None => {
+                return warn!(
+                    "{}: GoToHistoryIndex after closure",
+                    top_level_browsing_context_id
+                )
+            },
+        };
+        if index >= session_history.history_length() {
+            return warn!(
+                "{}: No session history entry at index {}",
+                top_level_browsing_context_id, index
+            );
+        }
+        let current_index = session_history.past.len();
+        let direction = match index.cmp(&current_index) {
+            Ordering::Less => TraversalDirection::Back(current_index - index),
+            Ordering::Greater => TraversalDirection::Forward(index - current_index),
+            Ordering::Equal => return,
+        };
+        self.handle_traverse_history_msg(top_level_browsing_context_id, direction);
+    }
+
     fn handle_traverse_history_msg(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
@@ -3860,6 +5337,7 @@ where
                     is_private,
                     is_visible,
                 );
+                self.history_traversal_pipelines.insert(new_pipeline_id);
                 self.add_pending_change(SessionHistoryChange {
                     top_level_browsing_context_id: top_level_id,
                     browsing_context_id,
@@ -3908,6 +5386,16 @@ where
                 ));
             }
 
+            // The document was loaded already.
+            let step = NavigationStep::Started {
+                is_redirect: false,
+                is_form_submission: false,
+                is_history_traversal: true,
+            };
+            self.send_navigation_event(new_pipeline_id, new_pipeline.url.clone(), step);
+            let step = NavigationStep::LoadComplete;
+            self.send_navigation_event(new_pipeline_id, new_pipeline.url.clone(), step);
+
             new_pipeline.notify_visibility(true);
         }
 
@@ -4069,32 +5557,13 @@ where
     fn handle_key_msg(&mut self, event: KeyboardEvent) {
         // Send to the focused browsing contexts' current pipeline.  If it
         // doesn't exist, fall back to sending to the compositor.
-        let focused_browsing_context_id = self
+        let focused_webview = self
             .webviews
             .focused_webview()
-            .map(|(_, webview)| webview.focused_browsing_context_id);
-        match focused_browsing_context_id {
-            Some(browsing_context_id) => {
-                let event = CompositorEvent::KeyboardEvent(event);
-                let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-                    Some(ctx) => ctx.pipeline_id,
-                    None => {
-                        return warn!(
-                            "{}: Got key event for nonexistent browsing context",
-                            browsing_context_id,
-                        );
-                    },
-                };
-                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
-                let result = match self.pipelines.get(&pipeline_id) {
-                    Some(pipeline) => pipeline.event_loop.send(msg),
-                    None => {
-                        return debug!("{}: Got key event after closure", pipeline_id);
-                    },
-                };
-                if let Err(e) = result {
-                    self.handle_send_error(pipeline_id, e);
-                }
+            .map(|(id, webview)| (id, webview.focused_browsing_context_id));
+        match focused_webview {
+            Some((top_level_browsing_context_id, browsing_context_id)) => {
+                self.send_key_event(top_level_browsing_context_id, browsing_context_id, event);
             },
             None => {
                 warn!("No focused browsing context! Falling back to sending key to compositor");
@@ -4104,9 +5573,91 @@ where
         }
     }
 
-    fn handle_reload_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+    /// Send a key event to the focused browsing context of a webview, which doesn't need
+    /// to be the focused webview, for the input events that the embedder injects into it.
+    fn handle_webview_key_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        event: KeyboardEvent,
+    ) {
+        let browsing_context_id = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => webview.focused_browsing_context_id,
+            None => {
+                return warn!(
+                    "{}: Got key event for nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        self.send_key_event(top_level_browsing_context_id, browsing_context_id, event);
+    }
+
+    fn send_key_event(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        browsing_context_id: BrowsingContextId,
+        event: KeyboardEvent,
+    ) {
+        let event = CompositorEvent::KeyboardEvent(event);
+        if is_activation_triggering(&event) {
+            if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+                webview.activation.activate(Instant::now());
+            }
+        }
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                return warn!(
+                    "{}: Got key event for nonexistent browsing context",
+                    browsing_context_id,
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => {
+                return debug!("{}: Got key event after closure", pipeline_id);
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_share_canvas_texture_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        selector: String,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "{}: Got share canvas texture event after closure",
+                    browsing_context_id
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::ShareCanvasTexture(pipeline_id, selector);
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => {
+                return warn!(
+                    "{}: Got share canvas texture event after closure",
+                    pipeline_id
+                )
+            },
+            Some(pipeline) => pipeline.event_loop.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_reload_msg(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
             Some(browsing_context) => browsing_context.pipeline_id,
             None => {
                 return warn!("{}: Got reload event after closure", browsing_context_id);
@@ -4205,12 +5756,16 @@ where
                 None => return warn!("{}: Focus parent after closure", pipeline_id),
             };
 
-        // Focus the top-level browsing context.
+        // Focus the top-level browsing context. The documents of another webview that
+        // had system focus lose it, unless focus is outside of every webview.
         self.webviews.focus(top_level_browsing_context_id);
         self.embedder_proxy.send((
             Some(top_level_browsing_context_id),
             EmbedderMsg::WebViewFocused(top_level_browsing_context_id),
         ));
+        if self.system_focus.is_some() {
+            self.set_system_focus(Some(top_level_browsing_context_id));
+        }
 
         // Update the webview’s focused browsing context.
         match self.webviews.get_mut(top_level_browsing_context_id) {
@@ -4229,6 +5784,58 @@ where
         self.focus_parent_pipeline(browsing_context_id);
     }
 
+    /// Move system focus to the given webview, firing blur events in the documents of
+    /// the one that had it and focus events in those of the new one.
+    fn set_system_focus(
+        &mut self,
+        top_level_browsing_context_id: Option<TopLevelBrowsingContextId>,
+    ) {
+        if self.system_focus == top_level_browsing_context_id {
+            return;
+        }
+        if let Some(previous) = self.system_focus.take() {
+            self.send_system_focus(previous, false);
+        }
+        self.system_focus = top_level_browsing_context_id;
+        if let Some(top_level_browsing_context_id) = top_level_browsing_context_id {
+            self.send_system_focus(top_level_browsing_context_id, true);
+        }
+    }
+
+    /// Tell the documents in the focus chain of a webview, from its focused browsing
+    /// context up to the top-level one, whether they have system focus.
+    fn send_system_focus(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        has_focus: bool,
+    ) {
+        let mut browsing_context_id = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => webview.focused_browsing_context_id,
+            None => return,
+        };
+        let mut pipeline_ids = vec![];
+        while let Some(browsing_context) = self.browsing_contexts.get(&browsing_context_id) {
+            pipeline_ids.push(browsing_context.pipeline_id);
+            match browsing_context
+                .parent_pipeline_id
+                .and_then(|parent_pipeline_id| self.pipelines.get(&parent_pipeline_id))
+            {
+                Some(parent) => browsing_context_id = parent.browsing_context_id,
+                None => break,
+            }
+        }
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::SetSystemFocus(pipeline_id, has_focus);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
     fn focus_parent_pipeline(&mut self, browsing_context_id: BrowsingContextId) {
         let parent_pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
             Some(ctx) => ctx.parent_pipeline_id,
@@ -4455,6 +6062,17 @@ where
                 self.compositor_proxy
                     .send(CompositorMsg::CreatePng(rect, response_sender));
             },
+            WebDriverCommandMsg::Print(
+                top_level_browsing_context_id,
+                settings,
+                response_sender,
+            ) => {
+                self.handle_print_to_pdf_msg(
+                    top_level_browsing_context_id,
+                    settings,
+                    response_sender,
+                );
+            },
         }
     }
 
@@ -4464,132 +6082,235 @@ where
         visible: bool,
     ) {
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-            Some(browsing_context) => browsing_context.pipeline_id,
-            None => {
-                return warn!("{browsing_context_id}: Tried to notify visibility after closure");
-            },
+        if !self.browsing_contexts.contains_key(&browsing_context_id) {
+            return warn!("{browsing_context_id}: Tried to notify visibility after closure");
+        }
+
+        // Every document of the webview is hidden with it, so that the script threads
+        // throttle their timers and animation frames, and so are the documents that it
+        // loads while hidden.
+        let mut pipeline_ids = vec![];
+        for browsing_context in self.browsing_contexts.values_mut() {
+            if browsing_context.top_level_id == top_level_browsing_context_id {
+                browsing_context.is_visible = visible;
+                pipeline_ids.push(browsing_context.pipeline_id);
+            }
+        }
+        for pipeline_id in pipeline_ids {
+            match self.pipelines.get(&pipeline_id) {
+                None => warn!("{pipeline_id}: Tried to notify visibility after closure"),
+                Some(pipeline) => pipeline.notify_visibility(visible),
+            }
+        }
+    }
+
+    /// Remember the title of a document, and tell the embedder about the new title of the
+    /// entry of a top-level one.
+    fn handle_title_changed_msg(&mut self, pipeline_id: PipelineId, title: String) {
+        let pipeline = match self.pipelines.get_mut(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return warn!("{}: TitleChanged after closure", pipeline_id),
         };
-        match self.pipelines.get(&pipeline_id) {
-            None => warn!("{pipeline_id}: Tried to notify visibility after closure"),
-            Some(pipeline) => pipeline.notify_visibility(visible),
+        pipeline.title = title.clone();
+        let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+        if pipeline.browsing_context_id != BrowsingContextId::from(top_level_browsing_context_id) {
+            return;
         }
+        let webview = match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview,
+            None => return,
+        };
+        webview.session_history.titles.insert(pipeline_id, title);
+        self.notify_history_changed(top_level_browsing_context_id);
     }
 
-    fn notify_history_changed(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+    fn notify_history_changed(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
         // Send a flat projection of the history to embedder.
-        // The final vector is a concatenation of the LoadData of the past
-        // entries, the current entry and the future entries.
-        // LoadData of inner frames are ignored and replaced with the LoadData
-        // of the parent.
+        let (entries, current_index) =
+            match self.flat_history_entries(top_level_browsing_context_id) {
+                Some(history) => history,
+                None => return,
+            };
+        let webview = match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview,
+            None => return,
+        };
+        webview.metadata.url = Some(entries[current_index].1.url.clone());
 
-        let session_history = match self.webviews.get(top_level_browsing_context_id) {
-            Some(webview) => &webview.session_history,
+        // Forget the titles of the documents of the entries that were removed.
+        let titles = &mut webview.session_history.titles;
+        titles.retain(|pipeline_id, _| {
+            entries
+                .iter()
+                .any(|(entry_pipeline_id, _)| *entry_pipeline_id == Some(*pipeline_id))
+        });
+        let entries = entries
+            .into_iter()
+            .map(|(pipeline_id, load_data)| HistoryEntry {
+                url: load_data.url,
+                title: pipeline_id
+                    .and_then(|pipeline_id| titles.get(&pipeline_id))
+                    .filter(|title| !title.is_empty())
+                    .cloned(),
+            })
+            .collect();
+        let msg = (
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::HistoryChanged(entries, current_index),
+        );
+        self.embedder_proxy.send(msg);
+    }
+
+    /// The LoadData of the past entries, the current entry and the future entries of
+    /// the session history of a webview, with the index of the current entry.
+    /// LoadData of inner frames are ignored and replaced with the LoadData
+    /// of the parent.
+    fn flat_history(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Option<(Vec<LoadData>, usize)> {
+        let (entries, current_index) = self.flat_history_entries(top_level_browsing_context_id)?;
+        let entries = entries
+            .into_iter()
+            .map(|(_, load_data)| load_data)
+            .collect();
+        Some((entries, current_index))
+    }
+
+    /// Like `flat_history`, with the top-level pipeline of each entry, which is unknown for
+    /// the current entry of a discarded webview.
+    fn flat_history_entries(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Option<(Vec<(Option<PipelineId>, LoadData)>, usize)> {
+        let webview = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => webview,
             None => {
-                return warn!(
+                warn!(
                     "{}: Session history does not exist for browsing context",
                     top_level_browsing_context_id
                 );
+                return None;
             },
         };
-
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
-            Some(browsing_context) => browsing_context,
-            None => {
-                return warn!(
-                    "notify_history_changed error after top-level browsing context closed."
-                );
-            },
-        };
-
-        let current_load_data = match self.pipelines.get(&browsing_context.pipeline_id) {
-            Some(pipeline) => pipeline.load_data.clone(),
-            None => {
-                return warn!("{}: Refresh after closure", browsing_context.pipeline_id);
-            },
+        let session_history = &webview.session_history;
+
+        // A discarded webview has no pipeline, but knows which page it will load.
+        let current_entry = if let Some(ref discarded_load) = webview.discarded_load {
+            let load_data = LoadData::new(
+                LoadOrigin::Constellation,
+                discarded_load.url.clone(),
+                None,
+                Referrer::NoReferrer,
+                None,
+                None,
+            );
+            (None, load_data)
+        } else {
+            let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+            let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
+                Some(browsing_context) => browsing_context,
+                None => {
+                    warn!("notify_history_changed error after top-level browsing context closed.");
+                    return None;
+                },
+            };
+            match self.pipelines.get(&browsing_context.pipeline_id) {
+                Some(pipeline) => (Some(pipeline.id), pipeline.load_data.clone()),
+                None => {
+                    warn!("{}: Refresh after closure", browsing_context.pipeline_id);
+                    return None;
+                },
+            }
         };
 
         // If LoadData was ignored, use the LoadData of the previous SessionHistoryEntry, which
         // is the LoadData of the parent browsing context.
         let resolve_load_data_future =
-            |previous_load_data: &mut LoadData, diff: &SessionHistoryDiff| match *diff {
-                SessionHistoryDiff::BrowsingContext {
-                    browsing_context_id,
-                    ref new_reloader,
-                    ..
-                } => {
-                    if browsing_context_id == top_level_browsing_context_id {
-                        let load_data = match *new_reloader {
-                            NeedsToReload::No(pipeline_id) => {
-                                match self.pipelines.get(&pipeline_id) {
-                                    Some(pipeline) => pipeline.load_data.clone(),
-                                    None => previous_load_data.clone(),
-                                }
-                            },
-                            NeedsToReload::Yes(_, ref load_data) => load_data.clone(),
-                        };
-                        *previous_load_data = load_data.clone();
-                        Some(load_data)
-                    } else {
-                        Some(previous_load_data.clone())
-                    }
-                },
-                _ => Some(previous_load_data.clone()),
+            |previous_entry: &mut (Option<PipelineId>, LoadData), diff: &SessionHistoryDiff| {
+                match *diff {
+                    SessionHistoryDiff::BrowsingContext {
+                        browsing_context_id,
+                        ref new_reloader,
+                        ..
+                    } => {
+                        if browsing_context_id == top_level_browsing_context_id {
+                            let entry = match *new_reloader {
+                                NeedsToReload::No(pipeline_id) => {
+                                    match self.pipelines.get(&pipeline_id) {
+                                        Some(pipeline) => {
+                                            (Some(pipeline_id), pipeline.load_data.clone())
+                                        },
+                                        None => previous_entry.clone(),
+                                    }
+                                },
+                                NeedsToReload::Yes(pipeline_id, ref load_data) => {
+                                    (Some(pipeline_id), load_data.clone())
+                                },
+                            };
+                            *previous_entry = entry.clone();
+                            Some(entry)
+                        } else {
+                            Some(previous_entry.clone())
+                        }
+                    },
+                    _ => Some(previous_entry.clone()),
+                }
             };
 
         let resolve_load_data_past =
-            |previous_load_data: &mut LoadData, diff: &SessionHistoryDiff| match *diff {
-                SessionHistoryDiff::BrowsingContext {
-                    browsing_context_id,
-                    ref old_reloader,
-                    ..
-                } => {
-                    if browsing_context_id == top_level_browsing_context_id {
-                        let load_data = match *old_reloader {
-                            NeedsToReload::No(pipeline_id) => {
-                                match self.pipelines.get(&pipeline_id) {
-                                    Some(pipeline) => pipeline.load_data.clone(),
-                                    None => previous_load_data.clone(),
-                                }
-                            },
-                            NeedsToReload::Yes(_, ref load_data) => load_data.clone(),
-                        };
-                        *previous_load_data = load_data.clone();
-                        Some(load_data)
-                    } else {
-                        Some(previous_load_data.clone())
-                    }
-                },
-                _ => Some(previous_load_data.clone()),
+            |previous_entry: &mut (Option<PipelineId>, LoadData), diff: &SessionHistoryDiff| {
+                match *diff {
+                    SessionHistoryDiff::BrowsingContext {
+                        browsing_context_id,
+                        ref old_reloader,
+                        ..
+                    } => {
+                        if browsing_context_id == top_level_browsing_context_id {
+                            let entry = match *old_reloader {
+                                NeedsToReload::No(pipeline_id) => {
+                                    match self.pipelines.get(&pipeline_id) {
+                                        Some(pipeline) => {
+                                            (Some(pipeline_id), pipeline.load_data.clone())
+                                        },
+                                        None => previous_entry.clone(),
+                                    }
+                                },
+                                NeedsToReload::Yes(pipeline_id, ref load_data) => {
+                                    (Some(pipeline_id), load_data.clone())
+                                },
+                            };
+                            *previous_entry = entry.clone();
+                            Some(entry)
+                        } else {
+                            Some(previous_entry.clone())
+                        }
+                    },
+                    _ => Some(previous_entry.clone()),
+                }
             };
 
-        let mut entries: Vec<LoadData> = session_history
+        let mut entries: Vec<(Option<PipelineId>, LoadData)> = session_history
             .past
             .iter()
             .rev()
-            .scan(current_load_data.clone(), &resolve_load_data_past)
+            .scan(current_entry.clone(), &resolve_load_data_past)
             .collect();
 
         entries.reverse();
 
         let current_index = entries.len();
 
-        entries.push(current_load_data.clone());
+        entries.push(current_entry.clone());
 
         entries.extend(
             session_history
                 .future
                 .iter()
                 .rev()
-                .scan(current_load_data, &resolve_load_data_future),
-        );
-        let urls = entries.iter().map(|entry| entry.url.clone()).collect();
-        let msg = (
-            Some(top_level_browsing_context_id),
-            EmbedderMsg::HistoryChanged(urls, current_index),
+                .scan(current_entry, &resolve_load_data_future),
         );
-        self.embedder_proxy.send(msg);
+        Some((entries, current_index))
     }
 
     fn load_url_for_webdriver(
@@ -4625,6 +6346,18 @@ where
             change.browsing_context_id, change.new_pipeline_id
         );
 
+        // A webview that loads anything but a crash page in its top-level browsing
+        // context has recovered from a crash.
+        if change.browsing_context_id ==
+            BrowsingContextId::from(change.top_level_browsing_context_id) &&
+            self.pipelines
+                .get(&change.new_pipeline_id)
+                .map_or(false, |pipeline| pipeline.load_data.crash.is_none())
+        {
+            self.webviews
+                .mark_webview_recovered(change.top_level_browsing_context_id);
+        }
+
         // If the currently focused browsing context is a child of the browsing
         // context in which the page is being loaded, then update the focused
         // browsing context to be the one where the page is being loaded.
@@ -4789,11 +6522,21 @@ where
     }
 
     fn trim_history(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let history_length = pref!(session_history.max_length) as usize;
+        self.trim_history_to(top_level_browsing_context_id, history_length);
+    }
+
+    /// Close the pipelines of the session history entries that are more than
+    /// `history_length` entries away from the current one, which are reloaded if they
+    /// are traversed to again.
+    fn trim_history_to(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        history_length: usize,
+    ) {
         let pipelines_to_evict = {
             let session_history = self.get_joint_session_history(top_level_browsing_context_id);
 
-            let history_length = pref!(session_history.max_length) as usize;
-
             // The past is stored with older entries at the front.
             // We reverse the iter so that newer entries are at the front and then
             // skip _n_ entries and evict the remaining entries.
@@ -4903,14 +6646,105 @@ where
             new_size.initial_viewport.to_untyped()
         );
 
+        self.window_size = new_size;
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        self.resize_browsing_context(new_size, size_type, browsing_context_id);
+        self.resize_browsing_context(
+            self.window_size_for_webview(top_level_browsing_context_id),
+            size_type,
+            browsing_context_id,
+        );
 
         if let Some(response_sender) = self.webdriver.resize_channel.take() {
             let _ = response_sender.send(new_size);
         }
+    }
 
-        self.window_size = new_size;
+    /// The size of the viewport and device pixel ratio of a webview, which are those of the
+    /// window unless the embedder gave the webview its own size or device pixel ratio.
+    fn window_size_for_webview(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> WindowSizeData {
+        let (device_pixel_ratio, size) = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => (webview.device_pixel_ratio, webview.size),
+            None => (None, None),
+        };
+        if device_pixel_ratio.is_none() && size.is_none() {
+            return self.window_size;
+        }
+
+        // Without a size of its own, the webview covers as many device pixels as the
+        // window, even if it fits a different number of CSS pixels in them.
+        let device_size = match size {
+            Some(size) => size.to_f32(),
+            None => self.window_size.initial_viewport * self.window_size.device_pixel_ratio,
+        };
+        let device_pixel_ratio = device_pixel_ratio.unwrap_or(self.window_size.device_pixel_ratio);
+        WindowSizeData {
+            initial_viewport: device_size / device_pixel_ratio,
+            device_pixel_ratio,
+        }
+    }
+
+    fn handle_resize_webview_msg(&mut self, webview_id: WebViewId, size: DeviceIntSize) {
+        match self.webviews.get_mut(webview_id) {
+            Some(webview) => webview.size = Some(size),
+            None => {
+                return warn!("{}: Tried to resize nonexistent webview", webview_id);
+            },
+        }
+        self.compositor_proxy
+            .send(CompositorMsg::ResizeWebView(webview_id, Some(size)));
+
+        let window_size = self.window_size_for_webview(webview_id);
+        let browsing_context_id = BrowsingContextId::from(webview_id);
+        self.resize_browsing_context(window_size, WindowSizeType::Resize, browsing_context_id);
+    }
+
+    fn handle_set_webview_device_pixel_ratio_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        device_pixel_ratio: Option<Scale<f32, CSSPixel, DevicePixel>>,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.device_pixel_ratio = device_pixel_ratio,
+            None => {
+                return warn!(
+                    "{}: Tried to set the device pixel ratio of nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        self.compositor_proxy
+            .send(CompositorMsg::SetWebViewDevicePixelRatio(
+                top_level_browsing_context_id,
+                device_pixel_ratio,
+            ));
+
+        // Every document of the webview has to lay out again, the nested ones keeping
+        // their size in CSS pixels.
+        let window_size = self.window_size_for_webview(top_level_browsing_context_id);
+        let browsing_contexts: Vec<(BrowsingContextId, Size2D<f32, CSSPixel>)> = self
+            .browsing_contexts
+            .values()
+            .filter(|browsing_context| {
+                browsing_context.top_level_id == top_level_browsing_context_id
+            })
+            .map(|browsing_context| (browsing_context.id, browsing_context.size))
+            .collect();
+        for (browsing_context_id, size) in browsing_contexts {
+            let initial_viewport =
+                if browsing_context_id == BrowsingContextId::from(top_level_browsing_context_id) {
+                    window_size.initial_viewport
+                } else {
+                    size
+                };
+            let new_size = WindowSizeData {
+                initial_viewport,
+                device_pixel_ratio: window_size.device_pixel_ratio,
+            };
+            self.resize_browsing_context(new_size, WindowSizeType::Resize, browsing_context_id);
+        }
     }
 
     /// Called when the window exits from fullscreen mode
@@ -4918,8 +6752,64 @@ where
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
-        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        self.switch_fullscreen_mode(browsing_context_id);
+        // The fullscreen element may be in the document of an iframe.
+        let browsing_context_ids: Vec<BrowsingContextId> = self
+            .fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+            .map(|browsing_context| browsing_context.id)
+            .collect();
+        for browsing_context_id in browsing_context_ids {
+            self.switch_fullscreen_mode(browsing_context_id);
+        }
+    }
+
+    /// Ask the embedder to show an element of a document fullscreen, denying the request of
+    /// another document of the webview that it didn't answer yet.
+    fn handle_request_fullscreen_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        rect: DeviceIntRect,
+    ) {
+        let previous_request = match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.fullscreen_request.replace(pipeline_id),
+            None => {
+                return warn!(
+                    "{}: Fullscreen requested in nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        if let Some(previous_pipeline_id) = previous_request.filter(|id| *id != pipeline_id) {
+            let msg = ConstellationControlMsg::FullscreenEntered(previous_pipeline_id, false);
+            self.send_to_pipeline(previous_pipeline_id, msg);
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::EnterFullscreen(rect),
+        ));
+    }
+
+    /// Tell the document that asked to show an element fullscreen whether the embedder does.
+    fn handle_fullscreen_entered_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        entered: bool,
+    ) {
+        let pipeline_id = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.fullscreen_request.take())
+        {
+            Some(pipeline_id) => pipeline_id,
+            None => {
+                return warn!(
+                    "{}: Fullscreen entered without a request",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::FullscreenEntered(pipeline_id, entered);
+        self.send_to_pipeline(pipeline_id, msg);
     }
 
     /// Checks the state of all script and layout pipelines to see if they are idle
@@ -5021,7 +6911,7 @@ where
                         if let Some(parent_pipeline_id) = browsing_context.parent_pipeline_id {
                             ancestor_id = parent_pipeline_id;
                             continue;
-                        } else {
+                        } else if !self.is_webview_frozen(ancestor.top_level_browsing_context_id) {
                             return DocumentActivity::FullyActive;
                         }
                     }
@@ -5382,11 +7272,273 @@ where
             })
     }
 
+    fn handle_get_frame_tree_msg(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let is_active = !self.is_webview_frozen(top_level_browsing_context_id);
+        match self.frame_tree_node(
+            BrowsingContextId::from(top_level_browsing_context_id),
+            is_active,
+        ) {
+            Some(frame_tree) => self.embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::FrameTree(frame_tree),
+            )),
+            None => warn!(
+                "{}: Tried to get the frame tree of a webview without a browsing context",
+                top_level_browsing_context_id
+            ),
+        }
+    }
+
+    /// The navigations of a webview that haven't replaced their documents yet: those
+    /// waiting for the embedder to allow them, then those that load a new pipeline.
+    fn pending_navigations(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Vec<PendingNavigation> {
+        let awaiting_approval =
+            self.pending_approval_navigations
+                .iter()
+                .filter_map(|(pipeline_id, (load_data, _))| {
+                    let pipeline = self.pipelines.get(pipeline_id)?;
+                    if pipeline.top_level_browsing_context_id != top_level_browsing_context_id {
+                        return None;
+                    }
+                    Some(PendingNavigation {
+                        browsing_context_id: pipeline.browsing_context_id,
+                        url: load_data.url.clone(),
+                        state: PendingNavigationState::AwaitingApproval,
+                    })
+                });
+        let loading = self
+            .pending_changes
+            .iter()
+            .filter(|change| change.top_level_browsing_context_id == top_level_browsing_context_id)
+            .filter_map(|change| {
+                let pipeline = self.pipelines.get(&change.new_pipeline_id)?;
+                let (url, state) = match self.navigation_fetches.get(&change.new_pipeline_id) {
+                    Some(fetch) => (
+                        fetch.url.clone(),
+                        PendingNavigationState::Fetching {
+                            redirects: fetch.redirects,
+                        },
+                    ),
+                    None => (pipeline.url.clone(), PendingNavigationState::Loading),
+                };
+                Some(PendingNavigation {
+                    browsing_context_id: change.browsing_context_id,
+                    url,
+                    state,
+                })
+            });
+        awaiting_approval.chain(loading).collect()
+    }
+
+    fn handle_get_pending_navigations_msg(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let navigations = self.pending_navigations(top_level_browsing_context_id);
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::PendingNavigations(navigations),
+        ));
+    }
+
+    /// Cancel the navigations of a webview, then stop loading its documents. The
+    /// navigations of new browsing contexts aren't cancelled, since those would be left
+    /// without a document.
+    fn handle_cancel_pending_navigation_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let awaiting_approval: Vec<PipelineId> = self
+            .pending_approval_navigations
+            .keys()
+            .filter(|pipeline_id| {
+                self.pipelines.get(pipeline_id).map_or(false, |pipeline| {
+                    pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+                })
+            })
+            .cloned()
+            .collect();
+        for pipeline_id in awaiting_approval {
+            debug!("{}: Cancelling navigation awaiting approval", pipeline_id);
+            self.pending_approval_navigations.remove(&pipeline_id);
+            self.refuse_navigation(top_level_browsing_context_id, pipeline_id);
+        }
+
+        // Closing the pipelines drops their incomplete loads, which cancels their fetches.
+        let loading: Vec<PipelineId> = self
+            .pending_changes
+            .iter()
+            .filter(|change| {
+                change.top_level_browsing_context_id == top_level_browsing_context_id &&
+                    change.new_browsing_context_info.is_none()
+            })
+            .map(|change| change.new_pipeline_id)
+            .collect();
+        for &pipeline_id in &loading {
+            debug!("{}: Cancelling navigation", pipeline_id);
+            self.close_pipeline(
+                pipeline_id,
+                DiscardBrowsingContext::No,
+                ExitPipelineMode::Normal,
+            );
+        }
+
+        let current: Vec<(PipelineId, bool)> = self
+            .fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+            .filter_map(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id))
+            .map(|pipeline| (pipeline.id, pipeline.completely_loaded))
+            .collect();
+        for &(pipeline_id, completely_loaded) in &current {
+            if !completely_loaded {
+                self.send_to_pipeline(
+                    pipeline_id,
+                    ConstellationControlMsg::StopLoading(pipeline_id),
+                );
+            }
+        }
+
+        // A new top-level document that was cancelled told the embedder that it started
+        // loading, and won't tell it that it finished, unlike a current one that stops.
+        let top_level_is_loaded = current
+            .first()
+            .map_or(false, |&(_, completely_loaded)| completely_loaded);
+        if !loading.is_empty() && top_level_is_loaded {
+            let msg = EmbedderMsg::LoadComplete;
+            self.update_webview_metadata(top_level_browsing_context_id, &msg);
+            self.embedder_proxy
+                .send((Some(top_level_browsing_context_id), msg));
+        }
+    }
+
+    /// Ask the compositor for a screenshot of the current document of a webview, which it
+    /// paints on its own, so the webview needn't be visible or in the painting order.
+    fn handle_capture_screenshot_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        rect: Option<Rect<f32, CSSPixel>>,
+        scale_mode: ScaleMode,
+        format: ScreenshotFormat,
+        sender: IpcSender<Result<Screenshot, ScreenshotError>>,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                let _ = sender.send(Err(ScreenshotError::NoSuchWebView));
+                return;
+            },
+        };
+        self.compositor_proxy.send(CompositorMsg::CaptureScreenshot(
+            top_level_browsing_context_id,
+            pipeline_id,
+            None,
+            rect,
+            scale_mode,
+            format,
+            sender,
+        ));
+    }
+
+    /// Ask the current document of a webview to print itself. The documents of its
+    /// iframes are printed as they are painted, without print media.
+    fn handle_print_to_pdf_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        settings: PrintSettings,
+        sender: IpcSender<Result<Vec<u8>, PrintError>>,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                let _ = sender.send(Err(PrintError::NoSuchWebView));
+                return;
+            },
+        };
+        self.send_to_pipeline(
+            pipeline_id,
+            ConstellationControlMsg::PrintToPdf(pipeline_id, settings, sender),
+        );
+    }
+
+    /// Run a script of the embedder in the document of a frame of a webview.
+    fn handle_evaluate_javascript_msg(
+        &mut self,
+        webview_id: WebViewId,
+        frame: FrameSelector,
+        source: String,
+        sender: IpcSender<Result<String, JavaScriptEvaluationError>>,
+    ) {
+        let browsing_context_id = match frame {
+            FrameSelector::TopLevel => Some(BrowsingContextId::from(webview_id)),
+            FrameSelector::Focused => self
+                .webviews
+                .get(webview_id)
+                .map(|webview| webview.focused_browsing_context_id),
+            FrameSelector::Frame(browsing_context_id) => Some(browsing_context_id),
+        };
+        let pipeline_id = match browsing_context_id
+            .and_then(|id| self.browsing_contexts.get(&id))
+            .filter(|browsing_context| browsing_context.top_level_id == webview_id)
+        {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                let _ = sender.send(Err(JavaScriptEvaluationError::FrameNotFound));
+                return;
+            },
+        };
+        self.send_to_pipeline(
+            pipeline_id,
+            ConstellationControlMsg::EvaluateJavaScript(pipeline_id, source, sender),
+        );
+    }
+
+    fn frame_tree_node(
+        &self,
+        browsing_context_id: BrowsingContextId,
+        is_active: bool,
+    ) -> Option<FrameTreeNode> {
+        let browsing_context = self.browsing_contexts.get(&browsing_context_id)?;
+        let pipeline = self.pipelines.get(&browsing_context.pipeline_id)?;
+        Some(FrameTreeNode {
+            browsing_context_id,
+            pipeline_id: pipeline.id,
+            url: pipeline.url.clone(),
+            is_sandboxed: pipeline.is_sandboxed,
+            is_active,
+            is_visible: browsing_context.is_visible,
+            children: pipeline
+                .children
+                .iter()
+                .filter_map(|child| self.frame_tree_node(*child, is_active))
+                .collect(),
+        })
+    }
+
+    /// Tell the embedder that a webview was focused, and show it unless the embedder
+    /// chooses which webviews are shown itself.
+    fn notify_webview_focused(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        self.activate_webview(top_level_browsing_context_id);
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewFocused(top_level_browsing_context_id),
+        ));
+        if !cfg!(feature = "multiview") {
+            self.update_frame_tree_if_focused(top_level_browsing_context_id);
+        }
+    }
+
     /// Send the frame tree for the given webview to the compositor.
     fn update_frame_tree_if_focused(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
+        // The root pipeline of the webview may have changed.
+        self.send_painting_order();
+
         // Only send the frame tree if the given webview is focused.
         if let Some(focused_webview_id) = self.webviews.focused_webview().map(|(id, _)| id) {
             if top_level_browsing_context_id != focused_webview_id {
@@ -5404,64 +7556,2120 @@ where
         }
     }
 
-    fn handle_media_session_action_msg(&mut self, action: MediaSessionActionType) {
-        if let Some(media_session_pipeline_id) = self.active_media_session {
-            let result = match self.pipelines.get(&media_session_pipeline_id) {
-                None => {
-                    return warn!(
-                        "{}: Got media session action request after closure",
-                        media_session_pipeline_id,
-                    )
-                },
-                Some(pipeline) => {
-                    let msg = ConstellationControlMsg::MediaSessionAction(
-                        media_session_pipeline_id,
-                        action,
-                    );
-                    pipeline.event_loop.send(msg)
-                },
-            };
-            if let Err(e) = result {
-                self.handle_send_error(media_session_pipeline_id, e);
-            }
-        } else {
-            error!("Got a media session action but no active media session is registered");
+    fn handle_change_painting_order_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        change: PaintingOrderChange,
+    ) {
+        if self.webviews.get(top_level_browsing_context_id).is_none() {
+            return warn!(
+                "{}: Tried to change the painting order of nonexistent webview",
+                top_level_browsing_context_id,
+            );
+        }
+        match change {
+            PaintingOrderChange::RaiseToTop => {
+                self.webviews.raise_to_top(top_level_browsing_context_id)
+            },
+            PaintingOrderChange::LowerToBottom => {
+                self.webviews.lower_to_bottom(top_level_browsing_context_id)
+            },
+            PaintingOrderChange::SetIndex(index) => self
+                .webviews
+                .set_index(top_level_browsing_context_id, index),
         }
+        self.send_painting_order();
     }
 
-    /// Handle GamepadEvents from the embedder and forward them to the script thread
-    fn handle_gamepad_msg(&mut self, event: GamepadEvent) {
-        // Send to the focused browsing contexts' current pipeline.
-        let focused_browsing_context_id = self
-            .webviews
-            .focused_webview()
-            .map(|(_, webview)| webview.focused_browsing_context_id);
-        match focused_browsing_context_id {
-            Some(browsing_context_id) => {
-                let event = CompositorEvent::GamepadEvent(event);
-                let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-                    Some(ctx) => ctx.pipeline_id,
-                    None => {
-                        return warn!(
-                            "{}: Got gamepad event for nonexistent browsing context",
-                            browsing_context_id,
-                        );
-                    },
-                };
-                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
-                let result = match self.pipelines.get(&pipeline_id) {
-                    Some(pipeline) => pipeline.event_loop.send(msg),
-                    None => {
-                        return debug!("{}: Got gamepad event after closure", pipeline_id);
-                    },
-                };
-                if let Err(e) = result {
-                    self.handle_send_error(pipeline_id, e);
+    fn handle_set_webview_lifecycle_state_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        state: WebViewLifecycleState,
+    ) {
+        let current_state = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => webview.lifecycle_state,
+            None => {
+                return warn!(
+                    "{}: Tried to change the lifecycle state of nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        if state == current_state {
+            return;
+        }
+        match state {
+            WebViewLifecycleState::Active => self.activate_webview(top_level_browsing_context_id),
+            WebViewLifecycleState::Frozen => {
+                if current_state == WebViewLifecycleState::Discarded {
+                    return warn!(
+                        "{}: Tried to freeze a discarded webview",
+                        top_level_browsing_context_id,
+                    );
+                }
+                self.freeze_webview(top_level_browsing_context_id);
+            },
+            WebViewLifecycleState::Discarded => {
+                if self.webviews.focused_webview().map(|(id, _)| id) ==
+                    Some(top_level_browsing_context_id)
+                {
+                    return warn!(
+                        "{}: Tried to discard the focused webview",
+                        top_level_browsing_context_id,
+                    );
                 }
+                self.discard_webview(top_level_browsing_context_id);
             },
+        }
+    }
+
+    fn is_webview_frozen(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) -> bool {
+        self.webviews
+            .get(top_level_browsing_context_id)
+            .map_or(false, |webview| {
+                webview.lifecycle_state == WebViewLifecycleState::Frozen
+            })
+    }
+
+    fn set_webview_lifecycle_state(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        state: WebViewLifecycleState,
+    ) {
+        debug!(
+            "{}: Lifecycle state is now {:?}",
+            top_level_browsing_context_id, state
+        );
+        if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+            webview.lifecycle_state = state;
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewLifecycleStateChanged(state),
+        ));
+    }
+
+    /// Suspend the timers, animations and media of every document in the webview, by
+    /// making them inactive like documents in the session history.
+    fn freeze_webview(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
             None => {
-                warn!("No focused webview to handle gamepad event");
+                return warn!(
+                    "{}: Tried to freeze a webview without a browsing context",
+                    top_level_browsing_context_id,
+                );
             },
+        };
+        self.set_webview_lifecycle_state(
+            top_level_browsing_context_id,
+            WebViewLifecycleState::Frozen,
+        );
+        self.set_activity(pipeline_id, DocumentActivity::Inactive);
+    }
+
+    /// Freeze the webview like [`Self::freeze_webview`], after its documents handle the
+    /// `freeze` event.
+    fn handle_suspend_webview_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) if webview.lifecycle_state == WebViewLifecycleState::Active => {
+                webview.suspended = true;
+            },
+            Some(_) => {
+                return warn!(
+                    "{}: Tried to suspend a webview that isn't running",
+                    top_level_browsing_context_id,
+                );
+            },
+            None => {
+                return warn!(
+                    "{}: Tried to suspend nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        self.send_suspended(top_level_browsing_context_id, true);
+        self.freeze_webview(top_level_browsing_context_id);
+        self.compositor_proxy.send(CompositorMsg::WebViewSuspended(
+            top_level_browsing_context_id,
+        ));
+    }
+
+    /// Tell every document of the webview that it was suspended or resumed.
+    fn send_suspended(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        suspended: bool,
+    ) {
+        let pipeline_ids: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::SetSuspended(pipeline_id, suspended);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Close every pipeline of the webview, remembering the current page so that it can
+    /// be loaded again. The session history of the webview is lost.
+    fn discard_webview(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let discarded_load = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => match self.pipelines.get(&browsing_context.pipeline_id) {
+                Some(pipeline) => DiscardedLoad {
+                    pipeline_id: PipelineId::new(),
+                    url: pipeline.url.clone(),
+                    new_browsing_context_info: NewBrowsingContextInfo {
+                        parent_pipeline_id: None,
+                        is_private: browsing_context.is_private,
+                        inherited_secure_context: browsing_context.inherited_secure_context,
+                        is_visible: browsing_context.is_visible,
+                    },
+                },
+                None => {
+                    return warn!(
+                        "{}: Tried to discard a webview while its page is closing",
+                        top_level_browsing_context_id,
+                    );
+                },
+            },
+            None => {
+                return warn!(
+                    "{}: Tried to discard a webview without a browsing context",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+
+        self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
+        if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+            webview.session_history = JointSessionHistory::new();
+            webview.focused_browsing_context_id = browsing_context_id;
+            webview.discarded_load = Some(discarded_load);
+            webview.suspended = false;
+        }
+        self.set_webview_lifecycle_state(
+            top_level_browsing_context_id,
+            WebViewLifecycleState::Discarded,
+        );
+        self.send_painting_order();
+    }
+
+    /// Resume a frozen webview, or load the page of a discarded one again.
+    fn activate_webview(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let (state, discarded_load, was_suspended) =
+            match self.webviews.get_mut(top_level_browsing_context_id) {
+                Some(webview) => (
+                    webview.lifecycle_state,
+                    webview.discarded_load.take(),
+                    std::mem::take(&mut webview.suspended),
+                ),
+                None => return,
+            };
+        if state == WebViewLifecycleState::Active {
+            return;
+        }
+        self.set_webview_lifecycle_state(
+            top_level_browsing_context_id,
+            WebViewLifecycleState::Active,
+        );
+
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        if let Some(DiscardedLoad {
+            pipeline_id,
+            url,
+            new_browsing_context_info,
+        }) = discarded_load
+        {
+            let window_size = self
+                .window_size_for_webview(top_level_browsing_context_id)
+                .initial_viewport;
+            let load_data = LoadData::new(
+                LoadOrigin::Constellation,
+                url,
+                None,
+                Referrer::NoReferrer,
+                None,
+                None,
+            );
+            self.new_pipeline(
+                pipeline_id,
+                browsing_context_id,
+                top_level_browsing_context_id,
+                None,
+                None,
+                window_size,
+                load_data,
+                IFrameSandboxState::IFrameUnsandboxed,
+                new_browsing_context_info.is_private,
+                new_browsing_context_info.is_visible,
+            );
+            self.add_pending_change(SessionHistoryChange {
+                top_level_browsing_context_id,
+                browsing_context_id,
+                new_pipeline_id: pipeline_id,
+                replace: None,
+                new_browsing_context_info: Some(new_browsing_context_info),
+                window_size,
+            });
+            self.discard_webviews_over_limit(Some(top_level_browsing_context_id));
+        } else if let Some(browsing_context) = self.browsing_contexts.get(&browsing_context_id) {
+            self.update_activity(browsing_context.pipeline_id);
+            if was_suspended {
+                self.send_suspended(top_level_browsing_context_id, false);
+            }
+        }
+    }
+
+    /// Discard the least recently focused webviews that are neither shown, focused nor
+    /// prerendering, other than `keep`, until no more than the embedder's limit are
+    /// running or frozen.
+    fn discard_webviews_over_limit(&mut self, keep: Option<TopLevelBrowsingContextId>) {
+        let limit = match self.webview_limit {
+            Some(limit) => limit,
+            None => return,
+        };
+        let is_live = |webview: &WebView| {
+            webview.lifecycle_state != WebViewLifecycleState::Discarded && !webview.closing
+        };
+        let mut live_count = self
+            .webviews
+            .iter()
+            .filter(|(_, webview)| is_live(webview))
+            .count();
+        if live_count <= limit {
+            return;
+        }
+
+        let focused = self.webviews.focused_webview().map(|(id, _)| id);
+        let mut discarded = vec![];
+        for webview_id in self.webviews.least_recently_focused() {
+            if live_count <= limit {
+                break;
+            }
+            if Some(webview_id) == keep ||
+                Some(webview_id) == focused ||
+                self.webviews.is_shown(webview_id) ||
+                self.webviews.is_prerendering(webview_id) ||
+                !self.webviews.get(webview_id).map_or(false, is_live) ||
+                !self
+                    .browsing_contexts
+                    .contains_key(&BrowsingContextId::from(webview_id))
+            {
+                continue;
+            }
+            self.discard_webview(webview_id);
+            discarded.push(webview_id);
+            live_count -= 1;
+        }
+
+        if !discarded.is_empty() {
+            debug!(
+                "Discarded {:?} to stay within {} webviews",
+                discarded, limit
+            );
+            self.embedder_proxy
+                .send((None, EmbedderMsg::WebViewsDiscarded(discarded)));
+        }
+    }
+
+    /// Tell the embedder about a step of the navigation that loads the document of a
+    /// pipeline.
+    fn send_navigation_event(&self, pipeline_id: PipelineId, url: ServoUrl, step: NavigationStep) {
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return warn!("{}: Navigated after closure", pipeline_id),
+        };
+        let event = NavigationEvent {
+            browsing_context_id: pipeline.browsing_context_id,
+            pipeline_id,
+            url,
+            step,
+        };
+        self.embedder_proxy.send((
+            Some(pipeline.top_level_browsing_context_id),
+            EmbedderMsg::NavigationEvent(event),
+        ));
+    }
+
+    /// Keep track of what the top-level document of a webview reports to the embedder
+    /// about itself.
+    fn update_webview_metadata(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        embedder_msg: &EmbedderMsg,
+    ) {
+        let metadata = match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => &mut webview.metadata,
+            None => return,
+        };
+        match embedder_msg {
+            EmbedderMsg::ChangePageTitle(title) => metadata.title = title.clone(),
+            EmbedderMsg::NewFavicon(url) => metadata.favicon_url = Some(url.clone()),
+            EmbedderMsg::FaviconChanged(None) => metadata.favicon_url = None,
+            // A new document reports its own title and favicon.
+            EmbedderMsg::LoadStart => {
+                metadata.title = None;
+                metadata.favicon_url = None;
+                metadata.load_state = WebViewLoadState::Loading;
+            },
+            EmbedderMsg::LoadComplete => metadata.load_state = WebViewLoadState::Idle,
+            _ => {},
+        }
+    }
+
+    fn handle_get_webview_metadata_msg(&self) {
+        let metadata = self
+            .webviews
+            .painting_order()
+            .filter_map(|id| {
+                let mut metadata = self.webviews.get(id)?.metadata.clone();
+                metadata.opener = self.webviews.opener(id);
+                Some((id, metadata))
+            })
+            .collect();
+        self.embedder_proxy
+            .send((None, EmbedderMsg::WebViewMetadata(metadata)));
+    }
+
+    /// Send the memory that the documents of every webview use to the embedder, once every
+    /// script thread has measured its documents.
+    fn handle_get_webview_memory_usage_msg(&self) {
+        let mut receivers = vec![];
+        for event_loop in self.event_loops_of(|_| true) {
+            let (sender, receiver) = match ipc::channel() {
+                Ok(channel) => channel,
+                Err(e) => return warn!("Failed to create IPC channel ({:?}).", e),
+            };
+            match event_loop.send(ConstellationControlMsg::CollectMemoryUsage(sender)) {
+                Ok(()) => receivers.push(receiver),
+                Err(e) => warn!("Failed to ask a script thread for memory usage ({:?})", e),
+            }
+        }
+
+        let webview_of_pipeline: HashMap<PipelineId, TopLevelBrowsingContextId> = self
+            .pipelines
+            .values()
+            .map(|pipeline| (pipeline.id, pipeline.top_level_browsing_context_id))
+            .collect();
+        let webview_ids: Vec<TopLevelBrowsingContextId> = self.webviews.painting_order().collect();
+        let embedder_proxy = self.embedder_proxy.clone();
+        thread::Builder::new()
+            .name("WebViewMemoryUsage".to_owned())
+            .spawn(move || {
+                let mut usage: HashMap<TopLevelBrowsingContextId, WebViewMemoryUsage> =
+                    HashMap::new();
+                for receiver in receivers {
+                    let pipeline_usage = match receiver.recv() {
+                        Ok(pipeline_usage) => pipeline_usage,
+                        Err(e) => {
+                            warn!("Failed to receive memory usage ({:?}).", e);
+                            continue;
+                        },
+                    };
+                    for (pipeline_id, pipeline_usage) in pipeline_usage {
+                        if let Some(webview_id) = webview_of_pipeline.get(&pipeline_id) {
+                            *usage.entry(*webview_id).or_default() += pipeline_usage;
+                        }
+                    }
+                }
+                let usage = webview_ids
+                    .into_iter()
+                    .map(|id| (id, usage.remove(&id).unwrap_or_default()))
+                    .collect();
+                embedder_proxy.send((None, EmbedderMsg::WebViewMemoryUsage(usage)));
+            })
+            .expect("Thread spawning failed");
+    }
+
+    /// Send the state of every webview to the embedder, once the script threads have
+    /// said how far their documents are scrolled.
+    fn handle_get_session_state_msg(&mut self) {
+        let webview_ids: Vec<TopLevelBrowsingContextId> = self.webviews.painting_order().collect();
+        let focus_order = self
+            .webviews
+            .focus_order()
+            .filter_map(|id| webview_ids.iter().position(|webview_id| *webview_id == id))
+            .collect();
+        let mut session_state = SessionState {
+            version: SESSION_STATE_VERSION,
+            webviews: vec![],
+            focus_order,
+            is_focused: self.webviews.focused_webview().is_some(),
+        };
+
+        let mut scroll_offset_receivers = vec![];
+        for top_level_browsing_context_id in webview_ids {
+            let (history, current_index) = self
+                .flat_history(top_level_browsing_context_id)
+                .map(|(entries, current_index)| {
+                    let urls = entries.into_iter().map(|entry| entry.url).collect();
+                    (urls, current_index)
+                })
+                .unwrap_or_default();
+            let webview = match self.webviews.get(top_level_browsing_context_id) {
+                Some(webview) => webview,
+                None => continue,
+            };
+            let scroll_offset = webview
+                .scroll_offset_to_restore
+                .unwrap_or_else(Vector2D::zero);
+
+            let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+            let pipeline = self
+                .browsing_contexts
+                .get(&browsing_context_id)
+                .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id));
+            if let Some(pipeline) = pipeline {
+                let (sender, receiver) = match ipc::channel() {
+                    Ok(channel) => channel,
+                    Err(e) => return warn!("Failed to create IPC channel ({:?}).", e),
+                };
+                let msg = ConstellationControlMsg::GetScrollOffset(pipeline.id, sender);
+                match pipeline.event_loop.send(msg) {
+                    Ok(()) => {
+                        scroll_offset_receivers.push((session_state.webviews.len(), receiver))
+                    },
+                    Err(e) => warn!(
+                        "{}: Failed to ask for scroll offset ({:?}).",
+                        pipeline.id, e
+                    ),
+                }
+            }
+
+            session_state.webviews.push(WebViewSessionState {
+                history,
+                current_index,
+                scroll_offset,
+                group_id: self.webviews.group(top_level_browsing_context_id),
+            });
+        }
+
+        let embedder_proxy = self.embedder_proxy.clone();
+        thread::Builder::new()
+            .name("SessionState".to_owned())
+            .spawn(move || {
+                for (index, receiver) in scroll_offset_receivers {
+                    match receiver.recv() {
+                        Ok(offset) => session_state.webviews[index].scroll_offset = offset,
+                        Err(e) => warn!("Failed to receive scroll offset ({:?}).", e),
+                    }
+                }
+                embedder_proxy.send((None, EmbedderMsg::SessionState(session_state)));
+            })
+            .expect("Thread spawning failed");
+    }
+
+    /// Open the webviews of a saved session as discarded webviews, with their session
+    /// histories, and load the page of the focused one.
+    fn handle_restore_session_msg(&mut self, session_state: SessionState) {
+        if session_state.version != SESSION_STATE_VERSION {
+            return warn!(
+                "Can't restore a session saved with version {} instead of {}",
+                session_state.version, SESSION_STATE_VERSION
+            );
+        }
+
+        let mut webview_ids = vec![];
+        for webview_state in session_state.webviews {
+            if webview_state.current_index >= webview_state.history.len() {
+                warn!("Not restoring a webview without a current session history entry");
+                webview_ids.push(None);
+                continue;
+            }
+            let history = webview_state
+                .history
+                .into_iter()
+                .map(|url| {
+                    LoadData::new(
+                        LoadOrigin::Constellation,
+                        url,
+                        None,
+                        Referrer::NoReferrer,
+                        None,
+                        None,
+                    )
+                })
+                .collect();
+            let top_level_browsing_context_id = TopLevelBrowsingContextId::new();
+            self.add_discarded_webview(
+                top_level_browsing_context_id,
+                webview_state.group_id,
+                history,
+                webview_state.current_index,
+                false,
+            );
+            webview_ids.push(Some(top_level_browsing_context_id));
+            if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+                webview.scroll_offset_to_restore = Some(webview_state.scroll_offset);
+            }
+            self.set_webview_lifecycle_state(
+                top_level_browsing_context_id,
+                WebViewLifecycleState::Discarded,
+            );
+        }
+
+        for index in session_state.focus_order {
+            if let Some(Some(top_level_browsing_context_id)) = webview_ids.get(index) {
+                self.webviews.focus(*top_level_browsing_context_id);
+            }
+        }
+        let latest_focused = self.webviews.focus_order().last();
+        match latest_focused {
+            Some(top_level_browsing_context_id) if session_state.is_focused => {
+                self.notify_webview_focused(top_level_browsing_context_id);
+            },
+            _ => self.webviews.unfocus(),
+        }
+        self.send_painting_order();
+    }
+
+    /// Add a discarded webview with the given session history, whose current entry is
+    /// loaded when the webview is activated. The other entries are loaded again if they
+    /// are traversed to, like documents discarded from the session history.
+    fn add_discarded_webview(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        group_id: WebViewGroupId,
+        history: Vec<LoadData>,
+        current_index: usize,
+        is_private: bool,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        self.add_webview(top_level_browsing_context_id, group_id, is_private);
+
+        let pipeline_id = PipelineId::new();
+        let current_url = history[current_index].url.clone();
+        let reloaders: Vec<NeedsToReload> = history
+            .into_iter()
+            .enumerate()
+            .map(|(index, load_data)| {
+                if index == current_index {
+                    return NeedsToReload::No(pipeline_id);
+                }
+                NeedsToReload::Yes(PipelineId::new(), load_data)
+            })
+            .collect();
+        let diff = |index: usize| SessionHistoryDiff::BrowsingContext {
+            browsing_context_id,
+            old_reloader: reloaders[index].clone(),
+            new_reloader: reloaders[index + 1].clone(),
+        };
+        let session_history = JointSessionHistory {
+            past: (0..current_index).map(diff).collect(),
+            future: (current_index..reloaders.len() - 1)
+                .rev()
+                .map(diff)
+                .collect(),
+        };
+
+        if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+            webview.session_history = session_history;
+            webview.lifecycle_state = WebViewLifecycleState::Discarded;
+            webview.discarded_load = Some(DiscardedLoad {
+                pipeline_id,
+                url: current_url,
+                new_browsing_context_info: NewBrowsingContextInfo {
+                    parent_pipeline_id: None,
+                    is_private,
+                    inherited_secure_context: None,
+                    is_visible: true,
+                },
+            });
+        }
+    }
+
+    /// Open a new webview in the group of the given one, with a copy of its session
+    /// history, and load its current page again.
+    fn handle_duplicate_webview_msg(&mut self, webview_id: WebViewId) {
+        let (history, current_index) = match self.flat_history(webview_id) {
+            Some(history) => history,
+            None => return,
+        };
+        let discarded_is_private = self.webviews.get(webview_id).and_then(|webview| {
+            let discarded_load = webview.discarded_load.as_ref()?;
+            Some(discarded_load.new_browsing_context_info.is_private)
+        });
+        let is_private = discarded_is_private.unwrap_or_else(|| {
+            self.browsing_contexts
+                .get(&BrowsingContextId::from(webview_id))
+                .map_or(false, |browsing_context| browsing_context.is_private)
+        });
+        let new_webview_id = TopLevelBrowsingContextId::new();
+        self.add_discarded_webview(
+            new_webview_id,
+            self.webviews.group(webview_id),
+            history,
+            current_index,
+            is_private,
+        );
+        self.activate_webview(new_webview_id);
+        self.send_painting_order();
+    }
+
+    /// Scroll the current document of a restored webview to where it was when the
+    /// session was saved, once it has loaded.
+    fn restore_scroll_offset(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let is_current = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .map_or(false, |browsing_context| {
+                browsing_context.pipeline_id == pipeline_id
+            });
+        if !is_current {
+            return;
+        }
+        let offset = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.scroll_offset_to_restore.take())
+        {
+            Some(offset) => offset,
+            None => return,
+        };
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            let msg = ConstellationControlMsg::ScrollTo(pipeline_id, offset);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "{}: Failed to restore scroll offset ({:?}).",
+                    pipeline_id, e
+                );
+            }
+        }
+    }
+
+    /// Send the root pipelines of the webviews in painting order to the compositor, which
+    /// only paints more than the focused webview with the `multiview` feature.
+    fn send_painting_order(&self) {
+        if !cfg!(feature = "multiview") {
+            return;
+        }
+        // Prerendered webviews aren't painted until they are activated.
+        let pipeline_ids = self
+            .webviews
+            .painting_order()
+            .filter(|id| !self.webviews.is_prerendering(*id))
+            .filter_map(|top_level_browsing_context_id| {
+                let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+                self.browsing_contexts.get(&browsing_context_id)
+            })
+            .map(|browsing_context| browsing_context.pipeline_id)
+            .collect();
+        self.compositor_proxy
+            .send(CompositorMsg::SetPaintingOrder(pipeline_ids));
+    }
+
+    fn handle_media_session_action_msg(&mut self, action: MediaSessionActionType) {
+        if let Some(media_session_pipeline_id) = self.active_media_session {
+            let result = match self.pipelines.get(&media_session_pipeline_id) {
+                None => {
+                    return warn!(
+                        "{}: Got media session action request after closure",
+                        media_session_pipeline_id,
+                    )
+                },
+                Some(pipeline) => {
+                    let msg = ConstellationControlMsg::MediaSessionAction(
+                        media_session_pipeline_id,
+                        action,
+                    );
+                    pipeline.event_loop.send(msg)
+                },
+            };
+            if let Err(e) = result {
+                self.handle_send_error(media_session_pipeline_id, e);
+            }
+        } else {
+            error!("Got a media session action but no active media session is registered");
+        }
+    }
+
+    fn handle_picture_in_picture_event(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        event: PictureInPictureEvent,
+    ) {
+        // There is a single picture-in-picture surface. When another document's
+        // video enters picture-in-picture, the previous one is asked to leave, and
+        // the events it sends while doing so are discarded.
+        if let PictureInPictureEvent::Enter(..) = event {
+            if let Some(previous) = self.picture_in_picture_pipeline {
+                if previous != pipeline_id {
+                    self.send_picture_in_picture_action(previous, PictureInPictureAction::Close);
+                }
+            }
+            self.picture_in_picture_pipeline = Some(pipeline_id);
+        } else if self.picture_in_picture_pipeline != Some(pipeline_id) {
+            return;
+        }
+        if let PictureInPictureEvent::Leave = event {
+            self.picture_in_picture_pipeline = None;
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::PictureInPictureEvent(event),
+        ));
+    }
+
+    fn handle_picture_in_picture_action_msg(&mut self, action: PictureInPictureAction) {
+        match self.picture_in_picture_pipeline {
+            Some(pipeline_id) => self.send_picture_in_picture_action(pipeline_id, action),
+            None => warn!("Got a picture-in-picture action but no video is in picture-in-picture"),
+        }
+    }
+
+    fn send_picture_in_picture_action(
+        &mut self,
+        pipeline_id: PipelineId,
+        action: PictureInPictureAction,
+    ) {
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => {
+                return warn!(
+                    "{}: Got picture-in-picture action request after closure",
+                    pipeline_id,
+                )
+            },
+            Some(pipeline) => {
+                let msg = ConstellationControlMsg::PictureInPictureAction(pipeline_id, action);
+                pipeline.event_loop.send(msg)
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    /// Handle GamepadEvents from the embedder and forward them to the script thread
+    fn handle_gamepad_msg(&mut self, event: GamepadEvent) {
+        // Send to the focused browsing contexts' current pipeline.
+        let focused_browsing_context_id = self
+            .webviews
+            .focused_webview()
+            .map(|(_, webview)| webview.focused_browsing_context_id);
+        match focused_browsing_context_id {
+            Some(browsing_context_id) => {
+                let event = CompositorEvent::GamepadEvent(event);
+                let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+                    Some(ctx) => ctx.pipeline_id,
+                    None => {
+                        return warn!(
+                            "{}: Got gamepad event for nonexistent browsing context",
+                            browsing_context_id,
+                        );
+                    },
+                };
+                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+                let result = match self.pipelines.get(&pipeline_id) {
+                    Some(pipeline) => pipeline.event_loop.send(msg),
+                    None => {
+                        return debug!("{}: Got gamepad event after closure", pipeline_id);
+                    },
+                };
+                if let Err(e) = result {
+                    self.handle_send_error(pipeline_id, e);
+                }
+            },
+            None => {
+                warn!("No focused webview to handle gamepad event");
+            },
+        }
+    }
+
+    /// Store a permission decision for the webviews of `scope`. Decisions for every webview
+    /// go to the resource threads of every group, which save them in the config directory.
+    fn handle_set_permission_msg(&mut self, scope: PermissionScope, decision: PermissionDecision) {
+        if scope != PermissionScope::Global {
+            return self
+                .permission_stores
+                .entry(scope)
+                .or_default()
+                .set(decision);
+        }
+        for resource_threads in self
+            .all_resource_threads()
+            .flat_map(|(public, private)| [public, private])
+        {
+            let msg = net_traits::CoreResourceMsg::SetPermission(decision.clone());
+            if let Err(e) = resource_threads.send(msg) {
+                warn!("Sending SetPermission to resource thread failed ({:?}).", e);
+            }
+        }
+    }
+
+    /// Answer a document asking for the permission setting of an origin with the decision
+    /// for its webview, or else for its group, or else ask the resource threads of the
+    /// webview for the decision that applies to every webview.
+    fn handle_get_permission_msg(
+        &mut self,
+        pipeline_id: PipelineId,
+        origin: ImmutableOrigin,
+        name: PermissionName,
+        sender: IpcSender<Option<PermissionSetting>>,
+    ) {
+        let (top_level_browsing_context_id, browsing_context_id) =
+            match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => (
+                    pipeline.top_level_browsing_context_id,
+                    pipeline.browsing_context_id,
+                ),
+                None => return warn!("{}: Permission asked for by closed pipeline", pipeline_id),
+            };
+        let group_id = self.webviews.group(top_level_browsing_context_id);
+        let scopes = [
+            PermissionScope::WebView(top_level_browsing_context_id),
+            PermissionScope::Group(group_id),
+        ];
+        for scope in scopes {
+            let setting = self
+                .permission_stores
+                .get_mut(&scope)
+                .and_then(|store| store.get(&origin, name));
+            if setting.is_some() {
+                let _ = sender.send(setting);
+                return;
+            }
+        }
+        let is_private = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .map_or(false, |browsing_context| browsing_context.is_private);
+        let msg = net_traits::CoreResourceMsg::GetPermission(origin, name, sender);
+        let resource_threads =
+            self.resource_threads_for_webview(top_level_browsing_context_id, is_private);
+        if let Err(e) = resource_threads.send(msg) {
+            warn!("Sending GetPermission to resource thread failed ({:?}).", e);
+        }
+    }
+
+    /// Ask the resource thread for the permission decisions for every webview, and pass
+    /// them on to the embedder with those for groups and single webviews once they arrive.
+    fn handle_get_permission_decisions_msg(&mut self) {
+        let (sender, receiver) = match ipc::channel() {
+            Ok(channel) => channel,
+            Err(e) => return warn!("Failed to create IPC channel ({:?}).", e),
+        };
+        let msg = net_traits::CoreResourceMsg::GetPermissionDecisions(sender);
+        if let Err(e) = self.public_resource_threads.send(msg) {
+            return warn!(
+                "Sending GetPermissionDecisions to resource thread failed ({:?}).",
+                e
+            );
+        }
+        let mut scoped_decisions: Vec<(PermissionScope, PermissionDecision)> = self
+            .permission_stores
+            .iter_mut()
+            .flat_map(|(scope, store)| {
+                let scope = *scope;
+                store
+                    .decisions()
+                    .into_iter()
+                    .map(move |decision| (scope, decision))
+            })
+            .collect();
+        let embedder_proxy = self.embedder_proxy.clone();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| match message.to() {
+                Ok(decisions) => {
+                    let decisions: Vec<PermissionDecision> = decisions;
+                    let decisions = decisions
+                        .into_iter()
+                        .map(|decision| (PermissionScope::Global, decision))
+                        .chain(scoped_decisions.drain(..))
+                        .collect();
+                    embedder_proxy.send((None, EmbedderMsg::PermissionDecisions(decisions)))
+                },
+                Err(e) => warn!("Failed to receive permission decisions ({:?}).", e),
+            }),
+        );
+    }
+
+    /// The public resource threads of the webview groups of a scope, whose cookie jars
+    /// the embedder manages.
+    fn cookie_resource_threads(
+        &self,
+        scope: CookieScope,
+    ) -> Vec<(WebViewGroupId, &ResourceThreads)> {
+        let default = (WebViewGroupId::default(), &self.public_resource_threads);
+        let groups = self
+            .webview_groups
+            .iter()
+            .map(|(group_id, group)| (*group_id, &group.public_resource_threads));
+        match scope {
+            CookieScope::Global => std::iter::once(default).chain(groups).collect(),
+            CookieScope::Group(group_id) if group_id == WebViewGroupId::default() => {
+                vec![default]
+            },
+            CookieScope::Group(group_id) => groups.filter(|(id, _)| *id == group_id).collect(),
+        }
+    }
+
+    /// Ask the resource threads of a scope for their cookies, and pass them on to the
+    /// embedder together once every one answered.
+    fn handle_get_cookies_msg(&mut self, scope: CookieScope, url: Option<ServoUrl>) {
+        let resource_threads = self.cookie_resource_threads(scope);
+        if resource_threads.is_empty() {
+            return self
+                .embedder_proxy
+                .send((None, EmbedderMsg::Cookies(vec![])));
+        }
+        // The number of jars that didn't answer yet, and the cookies of those that did.
+        let answers = Arc::new(Mutex::new((resource_threads.len(), vec![])));
+        for (group_id, resource_threads) in resource_threads {
+            let (sender, receiver) = match ipc::channel() {
+                Ok(channel) => channel,
+                Err(e) => return warn!("Failed to create IPC channel ({:?}).", e),
+            };
+            let msg = net_traits::CoreResourceMsg::GetStoredCookies(url.clone(), sender);
+            if let Err(e) = resource_threads.send(msg) {
+                return warn!(
+                    "Sending GetStoredCookies to resource thread failed ({:?}).",
+                    e
+                );
+            }
+            let answers = answers.clone();
+            let embedder_proxy = self.embedder_proxy.clone();
+            ROUTER.add_route(
+                receiver.to_opaque(),
+                Box::new(move |message| {
+                    let cookies: Vec<StoredCookie> = match message.to() {
+                        Ok(cookies) => cookies,
+                        Err(e) => return warn!("Failed to receive cookies ({:?}).", e),
+                    };
+                    let mut answers = answers.lock().unwrap();
+                    let (remaining, all_cookies) = &mut *answers;
+                    all_cookies.extend(cookies.into_iter().map(|cookie| (group_id, cookie)));
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        let cookies = std::mem::take(all_cookies);
+                        embedder_proxy.send((None, EmbedderMsg::Cookies(cookies)));
+                    }
+                }),
+            );
+        }
+    }
+
+    /// Give the resource threads of a webview group a sender for the changes to their
+    /// cookie jar that passes them on to the embedder, or take it back, depending on
+    /// whether the embedder observes them.
+    fn send_cookie_observer(&self, group_id: WebViewGroupId, resource_threads: &ResourceThreads) {
+        let observer = if self.observe_cookie_changes {
+            let (sender, receiver) = match ipc::channel() {
+                Ok(channel) => channel,
+                Err(e) => return warn!("Failed to create IPC channel ({:?}).", e),
+            };
+            let embedder_proxy = self.embedder_proxy.clone();
+            ROUTER.add_route(
+                receiver.to_opaque(),
+                Box::new(move |message| match message.to() {
+                    Ok(change) => {
+                        embedder_proxy.send((None, EmbedderMsg::CookieChanged(group_id, change)))
+                    },
+                    Err(e) => warn!("Failed to receive cookie change ({:?}).", e),
+                }),
+            );
+            Some(sender)
+        } else {
+            None
+        };
+        let msg = net_traits::CoreResourceMsg::ObserveCookieChanges(observer);
+        if let Err(e) = resource_threads.send(msg) {
+            warn!(
+                "Sending ObserveCookieChanges to resource thread failed ({:?}).",
+                e
+            );
+        }
+    }
+
+    /// Clear the given kinds of data of a scope for the embedder.
+    fn handle_clear_data_msg(
+        &mut self,
+        scope: ClearDataScope,
+        data_types: ClearDataTypes,
+        since: Option<SystemTime>,
+    ) {
+        match scope {
+            ClearDataScope::Everything => self.clear_data(None, None, data_types, since),
+            ClearDataScope::Origins(origins) => {
+                self.clear_data(None, Some(origins), data_types, since)
+            },
+            ClearDataScope::Group(group_id) => {
+                self.clear_data(Some(group_id), None, data_types, since)
+            },
+        }
+    }
+
+    /// Clear the given kinds of data for `origins`, or for every origin if it is `None`,
+    /// from each of the components of a webview group, or of every group, that store some.
+    fn clear_data(
+        &mut self,
+        group_id: Option<WebViewGroupId>,
+        origins: Option<Vec<ImmutableOrigin>>,
+        data_types: ClearDataTypes,
+        since: Option<SystemTime>,
+    ) {
+        let matches_origin = |origin: &ImmutableOrigin| {
+            origins
+                .as_ref()
+                .map_or(true, |origins| origins.contains(origin))
+        };
+        let resource_threads: Vec<&ResourceThreads> = match group_id {
+            Some(group_id) if group_id != WebViewGroupId::default() => {
+                match self.webview_groups.get(&group_id) {
+                    Some(group) => vec![
+                        &group.public_resource_threads,
+                        &group.private_resource_threads,
+                    ],
+                    None => vec![],
+                }
+            },
+            Some(_) => vec![
+                &self.public_resource_threads,
+                &self.private_resource_threads,
+            ],
+            None => self
+                .all_resource_threads()
+                .flat_map(|(public, private)| [public, private])
+                .collect(),
+        };
+
+        if data_types.cache || data_types.cookies || data_types.hsts {
+            for resource_threads in resource_threads.iter() {
+                let msg =
+                    net_traits::CoreResourceMsg::ClearData(origins.clone(), data_types, since);
+                if let Err(e) = resource_threads.send(msg) {
+                    warn!("Sending ClearData to resource thread failed ({:?}).", e);
+                }
+            }
+        }
+
+        if data_types.web_storage {
+            for resource_threads in resource_threads.iter() {
+                let msg = StorageThreadMsg::ClearData(origins.clone());
+                if let Err(e) = resource_threads.send(msg) {
+                    warn!("Sending ClearData to storage thread failed ({:?}).", e);
+                }
+            }
+        }
+
+        if data_types.service_workers {
+            // Service worker registrations are only kept by the manager of their
+            // origin, so shutting it down unregisters them.
+            let sw_origins: Vec<ImmutableOrigin> = self
+                .sw_managers
+                .keys()
+                .filter(|origin| matches_origin(origin))
+                .cloned()
+                .collect();
+            for origin in sw_origins {
+                if let Some(mgr) = self.sw_managers.remove(&origin) {
+                    if let Err(e) = mgr.send(ServiceWorkerMsg::Exit) {
+                        warn!("Exit service worker manager failed ({})", e);
+                    }
+                }
+            }
+        }
+
+        if data_types.execution_contexts {
+            let top_level_ids: HashSet<TopLevelBrowsingContextId> = self
+                .browsing_contexts
+                .values()
+                .filter(|browsing_context| {
+                    group_id.map_or(true, |group_id| {
+                        self.webviews.group(browsing_context.top_level_id) == group_id
+                    })
+                })
+                .filter(|browsing_context| {
+                    self.pipelines
+                        .get(&browsing_context.pipeline_id)
+                        .map_or(false, |pipeline| matches_origin(&pipeline.url.origin()))
+                })
+                .map(|browsing_context| browsing_context.top_level_id)
+                .collect();
+            for top_level_id in top_level_ids {
+                self.handle_reload_msg(top_level_id);
+            }
+        }
+    }
+
+    /// <https://w3c.github.io/webappsec-clear-site-data/#clear-response>
+    fn handle_clear_site_data_msg(
+        &mut self,
+        source_pipeline_id: PipelineId,
+        mut data_types: ClearDataTypes,
+    ) {
+        let (origin, group_id) = match self.pipelines.get(&source_pipeline_id) {
+            Some(pipeline) => (
+                pipeline.url.origin(),
+                self.webviews.group(pipeline.top_level_browsing_context_id),
+            ),
+            None => return warn!("{}: ClearSiteData after closure", source_pipeline_id),
+        };
+        if !origin.is_tuple() {
+            return;
+        }
+        // Reloading the documents of the origin would load the response asking
+        // for their removal again.
+        data_types.execution_contexts = false;
+        // The other webview groups are kept apart from the one of the response, and
+        // their data isn't observable from there.
+        self.clear_data(Some(group_id), Some(vec![origin]), data_types, None);
+    }
+
+    /// Handle a sensor reading from the embedder and forward it to the script thread
+    /// of the focused browsing context, which is the only one allowed to see it, as long
+    /// as its document is same origin with the top-level one. Readings reveal how the
+    /// device moves, so cross-origin iframes never get them.
+    /// <https://w3c.github.io/deviceorientation/#security-and-privacy>
+    fn handle_sensor_reading_msg(&mut self, reading: SensorReading) {
+        let (top_level_browsing_context_id, focused_browsing_context_id) = match self
+            .webviews
+            .focused_webview()
+            .map(|(id, webview)| (id, webview.focused_browsing_context_id))
+        {
+            Some(ids) => ids,
+            None => return debug!("No focused webview to handle sensor reading"),
+        };
+        let top_level_origin = match self
+            .browsing_contexts
+            .get(&BrowsingContextId::from(top_level_browsing_context_id))
+            .and_then(|ctx| self.pipelines.get(&ctx.pipeline_id))
+        {
+            Some(pipeline) => pipeline.load_data.url.origin(),
+            None => {
+                return warn!(
+                    "{}: Got sensor reading for nonexistent top-level browsing context",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        let pipeline_id = match self.browsing_contexts.get(&focused_browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                return warn!(
+                    "{}: Got sensor reading for nonexistent browsing context",
+                    focused_browsing_context_id,
+                );
+            },
+        };
+        if self
+            .check_origin_against_pipeline(&pipeline_id, &top_level_origin)
+            .is_err()
+        {
+            return debug!(
+                "{}: Dropped sensor reading for cross-origin document",
+                pipeline_id
+            );
+        }
+        let msg = ConstellationControlMsg::SensorReading(pipeline_id, reading);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return debug!("{}: Got sensor reading after closure", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    /// Send a message built by `make_msg` to the script thread of the focused browsing
+    /// context of a webview, which is where editing happens.
+    fn send_to_focused_document(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        make_msg: impl FnOnce(PipelineId) -> ConstellationControlMsg,
+    ) {
+        let focused_browsing_context_id = match self.webviews.get(top_level_browsing_context_id) {
+            Some(webview) => webview.focused_browsing_context_id,
+            None => {
+                return warn!(
+                    "{}: Got editing message for nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        let pipeline_id = match self.browsing_contexts.get(&focused_browsing_context_id) {
+            Some(ctx) => ctx.pipeline_id,
+            None => {
+                return warn!(
+                    "{}: Got editing message for nonexistent browsing context",
+                    focused_browsing_context_id,
+                );
+            },
+        };
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(make_msg(pipeline_id)),
+            None => return debug!("{}: Got editing message after closure", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    /// Send a context menu action to the document of a frame of a webview.
+    fn handle_context_menu_action_msg(
+        &mut self,
+        webview_id: WebViewId,
+        browsing_context_id: BrowsingContextId,
+        action: ContextMenuAction,
+    ) {
+        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(ctx) if ctx.top_level_id == webview_id => ctx.pipeline_id,
+            _ => {
+                return warn!(
+                    "{}: Got context menu action for a browsing context not in {}",
+                    browsing_context_id, webview_id,
+                );
+            },
+        };
+        let msg = ConstellationControlMsg::ContextMenuAction(pipeline_id, action);
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return debug!("{}: Got context menu action after closure", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    /// Turn caret browsing on or off in every document of a webview.
+    fn handle_set_caret_browsing_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        enabled: bool,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.caret_browsing = enabled,
+            None => {
+                return warn!(
+                    "{}: Tried to set caret browsing for nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        let pipeline_ids: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::SetCaretBrowsing(pipeline_id, enabled);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Search the text of every document of a webview, or go to the next or previous match
+    /// if every document was searched for the same query already.
+    fn handle_find_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        query: String,
+        options: FindOptions,
+    ) {
+        let webview = match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview,
+            None => {
+                return warn!(
+                    "{}: Tried to search nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        };
+        if let Some(find) = webview.find.as_mut() {
+            if find.query == query &&
+                find.options.case_sensitive == options.case_sensitive &&
+                find.is_complete() &&
+                find.match_count() > 0
+            {
+                find.options = options;
+                return self.handle_find_next_msg(top_level_browsing_context_id, options.backwards);
+            }
+        }
+
+        let pipeline_ids: Vec<PipelineId> = self
+            .fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+            .map(|browsing_context| browsing_context.pipeline_id)
+            .collect();
+        if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+            webview.find = Some(FindState {
+                query: query.clone(),
+                options,
+                match_counts: pipeline_ids.iter().map(|id| (*id, None)).collect(),
+                active_match: None,
+                scrolling_pipeline: None,
+                highlights: HashMap::new(),
+            });
+        }
+        if pipeline_ids.is_empty() {
+            self.send_find_highlights(top_level_browsing_context_id);
+            return self.send_find_result(top_level_browsing_context_id, None);
+        }
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::Find(pipeline_id, query.clone(), options);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+    }
+
+    /// Go to the next or previous match of the search of a webview, once every document of
+    /// the webview was searched.
+    fn handle_find_next_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        backwards: bool,
+    ) {
+        let find = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_mut())
+        {
+            Some(find) if find.is_complete() && find.match_count() > 0 => find,
+            _ => return,
+        };
+        let count = find.match_count();
+        let previous = find.active_pipeline_match();
+        let active_match = find.active_match.unwrap_or(0);
+        find.active_match = Some(if backwards {
+            (active_match + count - 1) % count
+        } else {
+            (active_match + 1) % count
+        });
+        self.activate_find_match(
+            top_level_browsing_context_id,
+            previous.map(|(pipeline_id, _)| pipeline_id),
+        );
+    }
+
+    /// Record the number of matches of a search in a document, and once every document
+    /// of the webview was searched, activate the first match, or the last one when the
+    /// search goes backwards.
+    fn handle_find_matches_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        query: String,
+        count: usize,
+    ) {
+        let find = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_mut())
+        {
+            Some(find) if find.query == query => find,
+            // The answer to a search that was stopped or replaced.
+            _ => return,
+        };
+        match find
+            .match_counts
+            .iter_mut()
+            .find(|(id, _)| *id == pipeline_id)
+        {
+            Some((_, match_count)) => *match_count = Some(count),
+            None => return,
+        }
+        if find.is_complete() {
+            let count = find.match_count();
+            if count > 0 {
+                find.active_match = Some(if find.options.backwards { count - 1 } else { 0 });
+                return self.activate_find_match(top_level_browsing_context_id, None);
+            }
+        }
+        self.send_find_result(top_level_browsing_context_id, None);
+    }
+
+    /// Ask the document with the active match of the search of a webview to select it,
+    /// and the document that had the previous one to forget it.
+    fn activate_find_match(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        previous_pipeline_id: Option<PipelineId>,
+    ) {
+        let active_match = self
+            .webviews
+            .get(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_ref())
+            .and_then(FindState::active_pipeline_match);
+        let (pipeline_id, index) = match active_match {
+            Some(active_match) => active_match,
+            None => return,
+        };
+        if let Some(find) = self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_mut())
+        {
+            find.scrolling_pipeline = Some(pipeline_id);
+        }
+        if let Some(previous_pipeline_id) = previous_pipeline_id.filter(|id| *id != pipeline_id) {
+            let msg = ConstellationControlMsg::ActivateFindMatch(previous_pipeline_id, None);
+            self.send_to_pipeline(previous_pipeline_id, msg);
+        }
+        let msg = ConstellationControlMsg::ActivateFindMatch(pipeline_id, Some(index));
+        self.send_to_pipeline(pipeline_id, msg);
+    }
+
+    fn handle_stop_finding_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let find = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.take())
+        {
+            Some(find) => find,
+            None => return,
+        };
+        self.compositor_proxy.send(CompositorMsg::SetFindHighlights(
+            top_level_browsing_context_id,
+            vec![],
+            vec![],
+        ));
+        for (pipeline_id, _) in find.match_counts {
+            self.send_to_pipeline(
+                pipeline_id,
+                ConstellationControlMsg::StopFinding(pipeline_id),
+            );
+        }
+    }
+
+    /// Ask the pipeline of the iframe that the active match of the search of a webview was
+    /// scrolled to in to scroll the iframe into view as well, or once the top-level pipeline
+    /// did, tell the embedder where the match is.
+    fn handle_active_find_match_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        rect: Option<DeviceIntRect>,
+    ) {
+        let parent = self.pipelines.get(&pipeline_id).and_then(|pipeline| {
+            let browsing_context = self.browsing_contexts.get(&pipeline.browsing_context_id)?;
+            Some((
+                pipeline.browsing_context_id,
+                browsing_context.parent_pipeline_id?,
+            ))
+        });
+        let find = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_mut())
+        {
+            Some(find) if find.scrolling_pipeline == Some(pipeline_id) => find,
+            // The answer to a match that isn't active anymore.
+            _ => return,
+        };
+        match (parent, rect) {
+            (Some((browsing_context_id, parent_pipeline_id)), Some(rect)) => {
+                find.scrolling_pipeline = Some(parent_pipeline_id);
+                let msg = ConstellationControlMsg::ScrollFindMatchIntoView(
+                    parent_pipeline_id,
+                    browsing_context_id,
+                    rect,
+                );
+                self.send_to_pipeline(parent_pipeline_id, msg);
+            },
+            // The match isn't rendered, or it is in view in the webview.
+            _ => {
+                find.scrolling_pipeline = None;
+                self.send_find_result(top_level_browsing_context_id, rect);
+            },
+        }
+    }
+
+    /// Record where the matches and iframes of a document searched are, and highlight the
+    /// matches of its webview again.
+    fn handle_find_highlights_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        highlights: FindHighlights,
+    ) {
+        let find = match self
+            .webviews
+            .get_mut(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_mut())
+        {
+            Some(find) => find,
+            None => return,
+        };
+        if !find.match_counts.iter().any(|(id, _)| *id == pipeline_id) {
+            return;
+        }
+        find.highlights.insert(pipeline_id, highlights);
+        self.send_find_highlights(top_level_browsing_context_id);
+    }
+
+    /// Tell the compositor where to highlight the matches of the search of a webview, moving
+    /// those of the documents of iframes to where their iframes are.
+    fn send_find_highlights(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let find = match self
+            .webviews
+            .get(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_ref())
+        {
+            Some(find) => find,
+            None => return,
+        };
+        let mut matches = vec![];
+        let mut active_match = vec![];
+        for (pipeline_id, highlights) in &find.highlights {
+            let frame = match self.find_frame_rect(find, *pipeline_id) {
+                Some(frame) => frame,
+                // The iframe isn't in view, or its document didn't tell where it is yet.
+                None => continue,
+            };
+            let place = |rect: &DeviceIntRect| place_in_frame(*rect, frame);
+            matches.extend(highlights.matches.iter().filter_map(place));
+            active_match.extend(highlights.active_match.iter().filter_map(place));
+        }
+        self.compositor_proxy.send(CompositorMsg::SetFindHighlights(
+            top_level_browsing_context_id,
+            matches,
+            active_match,
+        ));
+    }
+
+    /// The box of the iframe that a pipeline searched is in, relative to the viewport of its
+    /// webview, or `Some(None)` if it is the top-level pipeline. This is `None` if the iframe
+    /// isn't in view or where it is isn't known.
+    fn find_frame_rect(
+        &self,
+        find: &FindState,
+        pipeline_id: PipelineId,
+    ) -> Option<Option<DeviceIntRect>> {
+        let browsing_context_id = self.pipelines.get(&pipeline_id)?.browsing_context_id;
+        let parent_pipeline_id = match self
+            .browsing_contexts
+            .get(&browsing_context_id)?
+            .parent_pipeline_id
+        {
+            Some(parent_pipeline_id) => parent_pipeline_id,
+            None => return Some(None),
+        };
+        let (_, rect) = find
+            .highlights
+            .get(&parent_pipeline_id)?
+            .frames
+            .iter()
+            .find(|(id, _)| *id == browsing_context_id)?;
+        let parent_frame = self.find_frame_rect(find, parent_pipeline_id)?;
+        place_in_frame(*rect, parent_frame).map(Some)
+    }
+
+    fn send_find_result(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        active_match_rect: Option<DeviceIntRect>,
+    ) {
+        let find = match self
+            .webviews
+            .get(top_level_browsing_context_id)
+            .and_then(|webview| webview.find.as_ref())
+        {
+            Some(find) => find,
+            None => return,
+        };
+        let result = FindResult {
+            query: find.query.clone(),
+            match_count: find.match_count(),
+            active_match: find.active_match,
+            active_match_rect,
+            final_update: find.is_complete(),
+        };
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::FindResult(result),
+        ));
+    }
+
+    /// Send a message to the event loop of a pipeline, if it still exists.
+    fn send_to_pipeline(&mut self, pipeline_id: PipelineId, msg: ConstellationControlMsg) {
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    /// Enforce a Content-Security-Policy in every document of a webview, including the
+    /// ones it loads later.
+    fn handle_set_webview_csp_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        policy: Option<String>,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.csp = policy.clone(),
+            None => {
+                return warn!(
+                    "{}: Tried to set the policy of nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        let pipeline_ids: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::SetEmbedderCsp(pipeline_id, policy.clone());
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Set the languages of every document of a webview, including the ones it loads
+    /// later, and of their requests.
+    fn handle_set_webview_languages_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        languages: Vec<String>,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.languages = languages.clone(),
+            None => {
+                return warn!(
+                    "{}: Tried to set the languages of nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        let pipeline_ids: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            self.send_pipeline_languages_to_resource_threads(pipeline_id, languages.clone());
+            let msg = ConstellationControlMsg::SetLanguages(pipeline_id, languages.clone());
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Tell every resource thread which languages to prefer in the requests of a pipeline,
+    /// as they don't know the webview a request comes from.
+    fn send_pipeline_languages_to_resource_threads(
+        &self,
+        pipeline_id: PipelineId,
+        languages: Vec<String>,
+    ) {
+        for resource_threads in self
+            .all_resource_threads()
+            .flat_map(|(public, private)| [public, private])
+        {
+            let msg =
+                net_traits::CoreResourceMsg::SetPipelineLanguages(pipeline_id, languages.clone());
+            if let Err(e) = resource_threads.send(msg) {
+                warn!(
+                    "Sending SetPipelineLanguages to resource thread failed ({:?}).",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Start tracing with the given capacity, starting with the current state of the
+    /// webviews so that the trace can be replayed, or stop tracing.
+    fn handle_set_constellation_tracing_msg(&mut self, capacity: Option<usize>) {
+        self.tracer = capacity.map(Tracer::new);
+        self.webviews.record_transitions(false);
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record_transitions(self.webviews.snapshot());
+            self.webviews.record_transitions(true);
+        }
+    }
+
+    /// Set how promptly the tasks of every document of a webview are run, including the
+    /// ones it loads later.
+    fn handle_set_webview_priority_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        priority: WebViewPriority,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.priority = priority,
+            None => {
+                return warn!(
+                    "{}: Tried to set the priority of nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        let pipeline_ids: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::SetPriority(pipeline_id, priority);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Mute or unmute every document of a webview.
+    fn handle_set_webview_muted_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        muted: bool,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.audio_muted = muted,
+            None => {
+                return warn!(
+                    "{}: Tried to mute nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        let pipeline_ids: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationControlMsg::SetAudioMuted(pipeline_id, muted);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Tell the embedder whether a webview is playing audio, if any of its pipelines
+    /// started or stopped doing so.
+    fn update_webview_audio_state(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let audible = self.pipelines.values().any(|pipeline| {
+            pipeline.top_level_browsing_context_id == top_level_browsing_context_id &&
+                pipeline.is_audible
+        });
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) if webview.audible != audible => webview.audible = audible,
+            _ => return,
+        }
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewAudioStateChanged(audible),
+        ));
+    }
+
+    /// Ask every script thread to free memory. At the critical level, the documents
+    /// in session history are closed first, so that the script threads can also
+    /// collect what they held on to.
+    fn handle_memory_pressure_msg(&mut self, level: MemoryPressureLevel) {
+        if level == MemoryPressureLevel::Critical {
+            let webview_ids: Vec<TopLevelBrowsingContextId> = self.webviews.webview_ids().collect();
+            for top_level_browsing_context_id in webview_ids {
+                self.trim_history_to(top_level_browsing_context_id, 0);
+            }
+        }
+
+        for event_loop in self.event_loops_of(|_| true) {
+            if let Err(e) = event_loop.send(ConstellationControlMsg::MemoryPressure(level)) {
+                warn!(
+                    "Failed to send memory pressure to a script thread ({:?})",
+                    e
+                );
+            }
+        }
+    }
+
+    /// The resource threads keeping the cookies, caches and storage of the given webview.
+    fn resource_threads_for_webview(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+    ) -> &ResourceThreads {
+        let group_id = self.webviews.group(top_level_browsing_context_id);
+        let (public, private) = match self.webview_groups.get(&group_id) {
+            Some(group) => (
+                &group.public_resource_threads,
+                &group.private_resource_threads,
+            ),
+            None => (
+                &self.public_resource_threads,
+                &self.private_resource_threads,
+            ),
+        };
+        if is_private {
+            private
+        } else {
+            public
+        }
+    }
+
+    /// The public and private resource threads of every webview group, starting with
+    /// those of the default group.
+    fn all_resource_threads(&self) -> impl Iterator<Item = (&ResourceThreads, &ResourceThreads)> {
+        let default = (
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        );
+        std::iter::once(default).chain(self.webview_groups.values().map(|group| {
+            (
+                &group.public_resource_threads,
+                &group.private_resource_threads,
+            )
+        }))
+    }
+
+    /// The event loops running the pipelines for which `predicate` is true, each once.
+    fn event_loops_of(&self, predicate: impl Fn(&Pipeline) -> bool) -> Vec<Rc<EventLoop>> {
+        let mut event_loops: Vec<Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            let seen = event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop));
+            if !seen && predicate(pipeline) {
+                event_loops.push(pipeline.event_loop.clone());
+            }
+        }
+        event_loops
+    }
+
+    fn handle_set_js_gc_settings_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        settings: JsGcSettings,
+    ) {
+        match self.webviews.get_mut(top_level_browsing_context_id) {
+            Some(webview) => webview.js_gc_settings = settings,
+            None => {
+                return warn!(
+                    "{}: Tried to set garbage collection settings for nonexistent webview",
+                    top_level_browsing_context_id,
+                );
+            },
+        }
+        let event_loops = self.event_loops_of(|pipeline| {
+            pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+        });
+        for event_loop in event_loops {
+            if let Err(e) = event_loop.send(ConstellationControlMsg::SetJsGcSettings(settings)) {
+                warn!(
+                    "{}: Failed to send garbage collection settings ({:?})",
+                    top_level_browsing_context_id, e
+                );
+            }
+        }
+    }
+
+    /// Give the script threads of the webviews that asked for it a chance to collect
+    /// garbage while the compositor is idle.
+    fn handle_compositor_idle_msg(&self) {
+        let event_loops = self.event_loops_of(|pipeline| {
+            self.webviews
+                .get(pipeline.top_level_browsing_context_id)
+                .map_or(false, |webview| webview.js_gc_settings.idle_gc)
+        });
+        for event_loop in event_loops {
+            if let Err(e) = event_loop.send(ConstellationControlMsg::IdleGc) {
+                warn!("Failed to send idle garbage collection ({:?})", e);
+            }
+        }
+    }
+
+    /// Apply the garbage collection settings of the webview of a new pipeline to its
+    /// script thread, which may have just been started.
+    fn inherit_js_gc_settings(&self, pipeline: &Pipeline) {
+        let settings = match self.webviews.get(pipeline.top_level_browsing_context_id) {
+            Some(webview) => webview.js_gc_settings,
+            None => return,
+        };
+        if settings == JsGcSettings::default() {
+            return;
+        }
+        let msg = ConstellationControlMsg::SetJsGcSettings(settings);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!(
+                "{}: Failed to send garbage collection settings ({:?})",
+                pipeline.id, e
+            );
+        }
+    }
+
+    /// Mute a new pipeline if its webview is muted.
+    fn inherit_audio_muted(&self, pipeline: &Pipeline) {
+        let muted = self
+            .webviews
+            .get(pipeline.top_level_browsing_context_id)
+            .map_or(false, |webview| webview.audio_muted);
+        if !muted {
+            return;
+        }
+        let msg = ConstellationControlMsg::SetAudioMuted(pipeline.id, true);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!("{}: Failed to mute pipeline ({:?})", pipeline.id, e);
+        }
+    }
+
+    /// Lower the priority of a new pipeline if the priority of its webview is low.
+    fn inherit_priority(&self, pipeline: &Pipeline) {
+        let priority = self
+            .webviews
+            .get(pipeline.top_level_browsing_context_id)
+            .map_or(WebViewPriority::default(), |webview| webview.priority);
+        if priority == WebViewPriority::default() {
+            return;
+        }
+        let msg = ConstellationControlMsg::SetPriority(pipeline.id, priority);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!("{}: Failed to set the priority ({:?})", pipeline.id, e);
+        }
+    }
+
+    /// Set the languages of a new pipeline, if the embedder set them for its webview.
+    fn inherit_languages(&self, pipeline: &Pipeline) {
+        let languages = match self.webviews.get(pipeline.top_level_browsing_context_id) {
+            Some(webview) if !webview.languages.is_empty() => webview.languages.clone(),
+            _ => return,
+        };
+        self.send_pipeline_languages_to_resource_threads(pipeline.id, languages.clone());
+        let msg = ConstellationControlMsg::SetLanguages(pipeline.id, languages);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!("{}: Failed to set the languages ({:?})", pipeline.id, e);
+        }
+    }
+
+    /// The scripts that the embedder injects into the documents of a pipeline.
+    fn user_scripts_of_pipeline(&self, pipeline: &Pipeline) -> Vec<UserScript> {
+        let webview_id = pipeline.top_level_browsing_context_id;
+        let is_top_level = pipeline.browsing_context_id == webview_id;
+        self.user_scripts
+            .iter()
+            .filter(|(_, script_webview_id, script)| {
+                script_webview_id.map_or(true, |id| id == webview_id) &&
+                    (is_top_level || script.all_frames)
+            })
+            .map(|(_, _, script)| script.clone())
+            .collect()
+    }
+
+    /// Send the scripts that the embedder injects to every pipeline, after they changed.
+    fn update_user_scripts(&mut self) {
+        let pipeline_scripts: Vec<(PipelineId, Vec<UserScript>)> = self
+            .pipelines
+            .values()
+            .map(|pipeline| (pipeline.id, self.user_scripts_of_pipeline(pipeline)))
+            .collect();
+        for (pipeline_id, scripts) in pipeline_scripts {
+            let msg = ConstellationControlMsg::SetUserScripts(pipeline_id, scripts);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.event_loop.send(msg),
+                None => continue,
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Give a new pipeline the scripts that the embedder injects into its documents.
+    fn inherit_user_scripts(&self, pipeline: &Pipeline) {
+        let scripts = self.user_scripts_of_pipeline(pipeline);
+        if scripts.is_empty() {
+            return;
+        }
+        let msg = ConstellationControlMsg::SetUserScripts(pipeline.id, scripts);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!("{}: Failed to set the user scripts ({:?})", pipeline.id, e);
+        }
+    }
+
+    /// Enforce the policy of the webview of a new pipeline, if the embedder set one.
+    fn inherit_csp(&self, pipeline: &Pipeline) {
+        let policy = match self
+            .webviews
+            .get(pipeline.top_level_browsing_context_id)
+            .and_then(|webview| webview.csp.clone())
+        {
+            Some(policy) => policy,
+            None => return,
+        };
+        let msg = ConstellationControlMsg::SetEmbedderCsp(pipeline.id, Some(policy));
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!("{}: Failed to set the policy ({:?})", pipeline.id, e);
+        }
+    }
+
+    /// Enable caret browsing in a new pipeline if its webview has it enabled.
+    fn inherit_caret_browsing(&self, pipeline: &Pipeline) {
+        let enabled = self
+            .webviews
+            .get(pipeline.top_level_browsing_context_id)
+            .map_or(false, |webview| webview.caret_browsing);
+        if !enabled {
+            return;
+        }
+        let msg = ConstellationControlMsg::SetCaretBrowsing(pipeline.id, true);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            warn!("{}: Failed to enable caret browsing ({:?})", pipeline.id, e);
         }
     }
 }
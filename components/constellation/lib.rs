@@ -14,9 +14,13 @@ mod sandboxing;
 mod serviceworker;
 mod session_history;
 mod timer_scheduler;
+mod tracer;
+mod user_activation;
 mod webview;
 
-pub use crate::constellation::{Constellation, InitialConstellationState};
+pub use crate::constellation::{Constellation, InitialConstellationState, NewGroupResourceThreads};
 pub use crate::logging::{FromCompositorLogger, FromScriptLogger};
 pub use crate::pipeline::UnprivilegedPipelineContent;
 pub use crate::sandboxing::{content_process_sandbox_profile, UnprivilegedContent};
+pub use crate::tracer::{ConstellationTrace, MessageSource, TraceEntry, TraceEvent};
+pub use crate::webview::{WebViewManager, WebViewTransition};
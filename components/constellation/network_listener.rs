@@ -6,9 +6,11 @@
 //! Any redirects that are encountered are followed. Whenever a non-redirect
 //! response is received, it is forwarded to the appropriate script thread.
 
+use std::sync::{Arc, Mutex};
+
 use crossbeam_channel::Sender;
 use http::HeaderMap;
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use log::warn;
 use msg::constellation_msg::PipelineId;
@@ -19,14 +21,27 @@ use net_traits::{
     CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseMsg, IpcSend, NetworkError,
     ResourceThreads,
 };
+use servo_url::ServoUrl;
+
+/// A message from a [`NetworkListener`] to the constellation about the fetch of a
+/// navigation.
+pub enum NavigationFetchMsg {
+    /// The fetch followed a redirect to this URL.
+    Redirected(ServoUrl),
+    /// A message to forward to the script thread of the pipeline.
+    Response(FetchResponseMsg),
+}
 
 pub struct NetworkListener {
     res_init: Option<ResponseInit>,
     request_builder: RequestBuilder,
     pipeline_id: PipelineId,
     resource_threads: ResourceThreads,
-    sender: Sender<(PipelineId, FetchResponseMsg)>,
+    sender: Sender<(PipelineId, NavigationFetchMsg)>,
     should_send: bool,
+    /// Cancels the fetch of the latest redirect, so that cancelling the navigation also
+    /// cancels the fetches that follow its redirects.
+    canceller: Arc<Mutex<Option<IpcSender<()>>>>,
 }
 
 impl NetworkListener {
@@ -34,7 +49,7 @@ impl NetworkListener {
         request_builder: RequestBuilder,
         pipeline_id: PipelineId,
         resource_threads: ResourceThreads,
-        sender: Sender<(PipelineId, FetchResponseMsg)>,
+        sender: Sender<(PipelineId, NavigationFetchMsg)>,
     ) -> NetworkListener {
         NetworkListener {
             res_init: None,
@@ -43,12 +58,29 @@ impl NetworkListener {
             resource_threads,
             sender,
             should_send: false,
+            canceller: Arc::new(Mutex::new(None)),
         }
     }
 
     pub fn initiate_fetch(&self, cancel_chan: Option<ipc::IpcReceiver<()>>) {
         let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
 
+        // Each fetch gets a cancel channel of its own, which `cancel_chan` cancels.
+        let (cancel_sender, cancel_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
+        *self.canceller.lock().unwrap() = Some(cancel_sender);
+        if let Some(cancel_chan) = cancel_chan {
+            let canceller = self.canceller.clone();
+            ROUTER.add_route(
+                cancel_chan.to_opaque(),
+                Box::new(move |_| {
+                    if let Some(cancel_sender) = canceller.lock().unwrap().take() {
+                        let _ = cancel_sender.send(());
+                    }
+                }),
+            );
+        }
+
         let mut listener = NetworkListener {
             res_init: self.res_init.clone(),
             request_builder: self.request_builder.clone(),
@@ -56,6 +88,7 @@ impl NetworkListener {
             sender: self.sender.clone(),
             pipeline_id: self.pipeline_id,
             should_send: false,
+            canceller: self.canceller.clone(),
         };
 
         let msg = match self.res_init {
@@ -63,7 +96,7 @@ impl NetworkListener {
                 self.request_builder.clone(),
                 res_init_.clone(),
                 ipc_sender,
-                None,
+                Some(cancel_receiver),
             ),
             None => {
                 set_default_accept(Destination::Document, &mut listener.request_builder.headers);
@@ -71,7 +104,7 @@ impl NetworkListener {
 
                 CoreResourceMsg::Fetch(
                     listener.request_builder.clone(),
-                    FetchChannels::ResponseMsg(ipc_sender, cancel_chan),
+                    FetchChannels::ResponseMsg(ipc_sender, Some(cancel_receiver)),
                 )
             },
         };
@@ -141,9 +174,15 @@ impl NetworkListener {
                                 .unwrap_or(200),
                         });
 
-                        // XXXManishearth we don't have the cancel_chan anymore and
-                        // can't use it here.
-                        //
+                        let msg = NavigationFetchMsg::Redirected(location_url.clone());
+                        if let Err(e) = self.sender.send((self.pipeline_id, msg)) {
+                            warn!(
+                                "Failed to report redirect of pipeline {:?}: {:?}",
+                                self.pipeline_id, e
+                            );
+                        }
+
+                        // The cancel channel of the first fetch cancels this one too.
                         // Ideally the Fetch code would handle manual redirects on its own
                         self.initiate_fetch(None);
                     },
@@ -163,6 +202,7 @@ impl NetworkListener {
 
     fn send(&mut self, msg: FetchResponseMsg) {
         if self.should_send {
+            let msg = NavigationFetchMsg::Response(msg);
             if let Err(e) = self.sender.send((self.pipeline_id, msg)) {
                 warn!(
                     "Failed to forward network message to pipeline {:?}: {:?}",
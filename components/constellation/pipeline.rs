@@ -95,6 +95,12 @@ pub struct Pipeline {
     /// The title of this pipeline's document.
     pub title: String,
 
+    /// Whether this pipeline's document is playing audio.
+    pub is_audible: bool,
+
+    /// Whether this pipeline was loaded in an iframe with the sandbox attribute.
+    pub is_sandboxed: bool,
+
     /// The last compositor [`Epoch`] that was laid out in this pipeline if "exit after load" is
     /// enabled.
     pub layout_epoch: Epoch,
@@ -296,6 +302,7 @@ impl Pipeline {
                     script_port,
                     opts: (*opts::get()).clone(),
                     prefs: prefs::pref_map().iter().collect(),
+                    secure_schemes: servo_url::secure_schemes(),
                     pipeline_namespace_id: state.pipeline_namespace_id,
                     webrender_api_sender: state.webrender_api_sender,
                     webrender_image_api_sender: state.webrender_image_api_sender,
@@ -374,6 +381,8 @@ impl Pipeline {
             history_states: HashSet::new(),
             completely_loaded: false,
             title: String::new(),
+            is_audible: false,
+            is_sandboxed: false,
             layout_epoch: Epoch(0),
         };
 
@@ -499,6 +508,7 @@ pub struct UnprivilegedPipelineContent {
     script_port: IpcReceiver<ConstellationControlMsg>,
     opts: Opts,
     prefs: HashMap<String, PrefValue>,
+    secure_schemes: Vec<String>,
     pipeline_namespace_id: PipelineNamespaceId,
     webrender_api_sender: script_traits::WebrenderIpcSender,
     webrender_image_api_sender: net_traits::WebrenderIpcSender,
@@ -590,4 +600,8 @@ impl UnprivilegedPipelineContent {
     pub fn prefs(&self) -> HashMap<String, PrefValue> {
         self.prefs.clone()
     }
+
+    pub fn secure_schemes(&self) -> Vec<String> {
+        self.secure_schemes.clone()
+    }
 }
@@ -47,6 +47,13 @@ impl UnprivilegedContent {
             UnprivilegedContent::ServiceWorker(content) => content.prefs(),
         }
     }
+
+    pub fn secure_schemes(&self) -> Vec<String> {
+        match self {
+            UnprivilegedContent::Pipeline(content) => content.secure_schemes(),
+            UnprivilegedContent::ServiceWorker(content) => content.secure_schemes(),
+        }
+    }
 }
 
 /// Our content process sandbox profile on Mac. As restrictive as possible.
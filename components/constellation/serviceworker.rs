@@ -19,6 +19,7 @@ use crate::sandboxing::{spawn_multiprocess, UnprivilegedContent};
 pub struct ServiceWorkerUnprivilegedContent {
     opts: Opts,
     prefs: HashMap<String, PrefValue>,
+    secure_schemes: Vec<String>,
     senders: SWManagerSenders,
     origin: ImmutableOrigin,
 }
@@ -31,6 +32,7 @@ impl ServiceWorkerUnprivilegedContent {
         ServiceWorkerUnprivilegedContent {
             opts: (*opts::get()).clone(),
             prefs: prefs::pref_map().iter().collect(),
+            secure_schemes: servo_url::secure_schemes(),
             senders,
             origin,
         }
@@ -56,4 +58,8 @@ impl ServiceWorkerUnprivilegedContent {
     pub fn prefs(&self) -> HashMap<String, PrefValue> {
         self.prefs.clone()
     }
+
+    pub fn secure_schemes(&self) -> Vec<String> {
+        self.secure_schemes.clone()
+    }
 }
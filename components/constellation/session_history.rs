@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cmp::PartialEq;
+use std::collections::HashMap;
 use std::fmt;
 
 use euclid::Size2D;
@@ -27,6 +28,10 @@ pub struct JointSessionHistory {
     /// Diffs used to traverse to future entries. Oldest entries are at the back,
     /// the most recent entries are at the front.
     pub future: Vec<SessionHistoryDiff>,
+
+    /// The titles of the top-level documents of the entries, which are still known once
+    /// the documents of past and future entries are discarded.
+    pub titles: HashMap<PipelineId, String>,
 }
 
 impl JointSessionHistory {
@@ -34,6 +39,7 @@ impl JointSessionHistory {
         JointSessionHistory {
             past: Vec::new(),
             future: Vec::new(),
+            titles: HashMap::new(),
         }
     }
 
@@ -0,0 +1,246 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An opt-in record of the most recent messages that the constellation handled and of
+//! the changes they made to its webviews, to find out how it got into a buggy state.
+//!
+//! Messages are recorded by name with the webview and pipeline they target, since most
+//! of them carry channels that can't be sent again. The webview transitions are recorded
+//! in full, so replaying a trace gives a [`WebViewManager`] the state the constellation's
+//! one was in, which reproduces bugs in focus, visibility and painting order.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use compositing_traits::ConstellationMsg as FromCompositorMsg;
+use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
+use serde::{Deserialize, Serialize};
+
+use crate::webview::{WebViewManager, WebViewTransition};
+
+/// Where a traced message came from.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MessageSource {
+    Compositor,
+    Script,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum TraceEntry {
+    /// A message the constellation started handling.
+    Message {
+        source: MessageSource,
+        /// The name of the message, such as `ConstellationMsg::LoadUrl`.
+        name: String,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        pipeline_id: Option<PipelineId>,
+    },
+    /// A change to the webviews while handling the latest message.
+    WebView(WebViewTransition),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TraceEvent {
+    /// The time since tracing started, in microseconds.
+    pub time: u64,
+    pub entry: TraceEntry,
+}
+
+/// The events that a [`Tracer`] kept, oldest first, as exported to the embedder.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ConstellationTrace {
+    pub events: Vec<TraceEvent>,
+    /// Whether older events were dropped to stay within the capacity of the tracer,
+    /// in which case the webviews that existed before the first event can't be replayed.
+    pub truncated: bool,
+}
+
+impl ConstellationTrace {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// A webview manager in the state that the traced webview transitions leave an
+    /// empty one in, with default webviews.
+    pub fn replay<WebView: Default>(&self) -> WebViewManager<WebView> {
+        WebViewManager::replay(self.events.iter().filter_map(|event| match event.entry {
+            TraceEntry::WebView(ref transition) => Some(transition),
+            TraceEntry::Message { .. } => None,
+        }))
+    }
+}
+
+/// A ring buffer of the latest trace events.
+pub struct Tracer {
+    start: Instant,
+    capacity: usize,
+    events: VecDeque<TraceEvent>,
+    truncated: bool,
+}
+
+impl Tracer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            start: Instant::now(),
+            capacity: capacity.max(1),
+            events: VecDeque::new(),
+            truncated: false,
+        }
+    }
+
+    pub fn record_compositor_msg(&mut self, message: &FromCompositorMsg) {
+        let (webview_id, pipeline_id) = compositor_msg_target(message);
+        self.record(TraceEntry::Message {
+            source: MessageSource::Compositor,
+            name: format!("{:?}", message),
+            webview_id,
+            pipeline_id,
+        });
+    }
+
+    pub fn record_script_msg(
+        &mut self,
+        name: String,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        pipeline_id: PipelineId,
+    ) {
+        self.record(TraceEntry::Message {
+            source: MessageSource::Script,
+            name,
+            webview_id,
+            pipeline_id: Some(pipeline_id),
+        });
+    }
+
+    pub fn record_transitions(&mut self, transitions: Vec<WebViewTransition>) {
+        for transition in transitions {
+            self.record(TraceEntry::WebView(transition));
+        }
+    }
+
+    fn record(&mut self, entry: TraceEntry) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.truncated = true;
+        }
+        self.events.push_back(TraceEvent {
+            time: self.start.elapsed().as_micros() as u64,
+            entry,
+        });
+    }
+
+    pub fn trace(&self) -> ConstellationTrace {
+        ConstellationTrace {
+            events: self.events.iter().cloned().collect(),
+            truncated: self.truncated,
+        }
+    }
+}
+
+/// The webview and pipeline that a message from the compositor is about, if any.
+fn compositor_msg_target(
+    message: &FromCompositorMsg,
+) -> (Option<TopLevelBrowsingContextId>, Option<PipelineId>) {
+    use FromCompositorMsg::*;
+    match *message {
+        LoadUrl(webview_id, _) |
+        TraverseHistory(webview_id, _) |
+        GoToHistoryIndex(webview_id, _) |
+        WindowSize(webview_id, ..) |
+        Reload(webview_id) |
+        NewWebView(_, webview_id, _) |
+        NewPrivateWebView(_, webview_id, _) |
+        CloseWebView(webview_id) |
+        FocusWebView(webview_id, _) |
+        ExitFullScreen(webview_id) |
+        FullscreenEntered(webview_id, _) |
+        ShareCanvasTexture(webview_id, _) |
+        WebViewVisibilityChanged(webview_id, _) |
+        ReadyToPresent(webview_id) |
+        EditingCommand(webview_id, _) |
+        GetSelection(webview_id) |
+        Ime(webview_id, _) |
+        GetImeSurroundingText(webview_id, _) |
+        ContextMenuAction(webview_id, ..) |
+        AddUserScript(_, Some(webview_id), _) |
+        EvaluateJavaScript(webview_id, ..) |
+        SetCaretBrowsing(webview_id, _) |
+        SetJsGcSettings(webview_id, _) |
+        ChangePaintingOrder(webview_id, _) |
+        SetWebViewLifecycleState(webview_id, _) |
+        SetWebViewDevicePixelRatio(webview_id, _) |
+        ResizeWebView(webview_id, _) |
+        DuplicateWebView(webview_id) |
+        ReloadCrashedWebView(webview_id) |
+        SetWebViewMuted(webview_id, _) |
+        GetFrameTree(webview_id) |
+        SetWebViewCsp(webview_id, _) |
+        SetWebViewLanguages(webview_id, _) |
+        NewPrerenderWebView(_, webview_id) |
+        ActivatePrerender(webview_id) |
+        SetWebViewPriority(webview_id, _) |
+        SuspendWebView(webview_id) |
+        ResumeWebView(webview_id) |
+        SetWebViewBackgroundColor(webview_id, _) |
+        SetWebViewTransparent(webview_id, _) |
+        Find(webview_id, ..) |
+        FindNext(webview_id) |
+        FindPrevious(webview_id) |
+        StopFinding(webview_id) |
+        GetPendingNavigations(webview_id) |
+        CancelPendingNavigation(webview_id) |
+        CaptureScreenshot(webview_id, ..) |
+        PrintToPdf(webview_id, ..) |
+        WebViewKeyboard(webview_id, _) => (Some(webview_id), None),
+        OpenBlockedPopup(ref popup) => (Some(popup.opener), Some(popup.opener_pipeline)),
+        LogEntry(webview_id, ..) | SendError(webview_id, _) => (webview_id, None),
+        GetBrowsingContext(pipeline_id, _) |
+        AllowNavigationResponse(pipeline_id, _) |
+        TickAnimation(pipeline_id, _) |
+        ForwardEvent(pipeline_id, _) => (None, Some(pipeline_id)),
+        Exit |
+        GetPipeline(..) |
+        GetFocusTopLevelBrowsingContext(_) |
+        IsReadyToSaveImage(_) |
+        Keyboard(_) |
+        ClearCache |
+        SetPermission(..) |
+        GetPermissionDecisions |
+        ClearPermissionDecisions(_) |
+        GetCookies(..) |
+        SetCookie(..) |
+        DeleteCookie(..) |
+        ObserveCookieChanges(_) |
+        ClearData(..) |
+        WebDriverCommand(_) |
+        BlurWebView(_) |
+        FocusPreviousWebView |
+        FocusNextWebView |
+        SetCursor(_) |
+        EnableProfiler(..) |
+        DisableProfiler |
+        MediaSessionAction(_) |
+        PictureInPictureAction(_) |
+        MarkAllWebViewsShown |
+        MarkAllWebViewsNotShown |
+        IMEDismissed |
+        Gamepad(_) |
+        SensorReading(_) |
+        MemoryPressure(_) |
+        CompositorIdle |
+        GetSessionState |
+        RestoreSession(_) |
+        GetWebViewMetadata |
+        SetWebViewLimit(_) |
+        SetConstellationTracing(_) |
+        GetConstellationTrace |
+        GetWebViewMemoryUsage |
+        AddUserScript(_, None, _) |
+        RemoveUserScript(_) => (None, None),
+    }
+}
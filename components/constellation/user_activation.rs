@@ -0,0 +1,168 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The transient user activation of webviews, which lets their documents open a popup
+//! shortly after the user clicked, pressed a key or touched them.
+//!
+//! <https://html.spec.whatwg.org/multipage/#tracking-user-activation>
+
+use std::time::{Duration, Instant};
+
+use keyboard_types::{Key, KeyState};
+use script_traits::{CompositorEvent, MouseEventType, TouchEventType};
+
+/// How long a user activation lets the documents of a webview open a popup, like the
+/// transient activation duration of other engines.
+/// <https://html.spec.whatwg.org/multipage/#transient-activation-duration>
+pub const TRANSIENT_ACTIVATION_DURATION: Duration = Duration::from_secs(5);
+
+/// Whether an input event gives its webview transient user activation.
+/// <https://html.spec.whatwg.org/multipage/#activation-triggering-input-event>
+pub fn is_activation_triggering(event: &CompositorEvent) -> bool {
+    match event {
+        CompositorEvent::MouseButtonEvent(MouseEventType::MouseDown, ..) => true,
+        CompositorEvent::TouchEvent(TouchEventType::Up, ..) => true,
+        CompositorEvent::KeyboardEvent(event) => {
+            event.state == KeyState::Down && event.key != Key::Escape
+        },
+        _ => false,
+    }
+}
+
+/// When the user last activated a webview, if they did since it last opened a popup.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UserActivation {
+    last_activation: Option<Instant>,
+}
+
+impl UserActivation {
+    /// Record an activation triggering input event that happened at `now`.
+    pub fn activate(&mut self, now: Instant) {
+        self.last_activation = Some(now);
+    }
+
+    /// Whether the webview has transient activation at `now`.
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.last_activation.map_or(false, |activation| {
+            now.saturating_duration_since(activation) < TRANSIENT_ACTIVATION_DURATION
+        })
+    }
+
+    /// Consume the activation, so that each activation opens at most one popup, and
+    /// return whether the webview had transient activation at `now`.
+    /// <https://html.spec.whatwg.org/multipage/#consume-user-activation>
+    pub fn consume(&mut self, now: Instant) -> bool {
+        let was_active = self.is_active(now);
+        self.last_activation = None;
+        was_active
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use euclid::Point2D;
+    use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+    use script_traits::{CompositorEvent, MouseButton, MouseEventType, TouchEventType, TouchId};
+
+    use crate::user_activation::{
+        is_activation_triggering, UserActivation, TRANSIENT_ACTIVATION_DURATION,
+    };
+
+    fn mouse_button_event(event_type: MouseEventType) -> CompositorEvent {
+        CompositorEvent::MouseButtonEvent(
+            event_type,
+            MouseButton::Left,
+            Point2D::zero(),
+            None,
+            None,
+            0,
+            Modifiers::empty(),
+        )
+    }
+
+    fn keyboard_event(state: KeyState, key: Key) -> CompositorEvent {
+        CompositorEvent::KeyboardEvent(KeyboardEvent {
+            state,
+            key,
+            code: Code::Unidentified,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_activation_triggering_events() {
+        assert!(is_activation_triggering(&mouse_button_event(
+            MouseEventType::MouseDown
+        )));
+        assert!(!is_activation_triggering(&mouse_button_event(
+            MouseEventType::MouseUp
+        )));
+        assert!(!is_activation_triggering(&mouse_button_event(
+            MouseEventType::Click
+        )));
+
+        let touch_event =
+            |event_type| CompositorEvent::TouchEvent(event_type, TouchId(0), Point2D::zero(), None);
+        assert!(is_activation_triggering(&touch_event(TouchEventType::Up)));
+        assert!(!is_activation_triggering(&touch_event(
+            TouchEventType::Down
+        )));
+
+        let key = Key::Character("a".to_owned());
+        assert!(is_activation_triggering(&keyboard_event(
+            KeyState::Down,
+            key.clone()
+        )));
+        assert!(!is_activation_triggering(&keyboard_event(
+            KeyState::Up,
+            key
+        )));
+        assert!(!is_activation_triggering(&keyboard_event(
+            KeyState::Down,
+            Key::Escape
+        )));
+
+        assert!(!is_activation_triggering(&CompositorEvent::MouseMoveEvent(
+            Point2D::zero(),
+            None,
+            0
+        )));
+    }
+
+    #[test]
+    fn test_activation_is_consumed() {
+        let now = Instant::now();
+        let mut activation = UserActivation::default();
+        assert!(!activation.consume(now));
+
+        activation.activate(now);
+        assert!(activation.is_active(now));
+        assert!(activation.consume(now + Duration::from_secs(1)));
+
+        // One activation opens a single popup.
+        assert!(!activation.is_active(now + Duration::from_secs(1)));
+        assert!(!activation.consume(now + Duration::from_secs(1)));
+
+        activation.activate(now + Duration::from_secs(2));
+        assert!(activation.consume(now + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_activation_expires() {
+        let now = Instant::now();
+        let mut activation = UserActivation::default();
+        activation.activate(now);
+        assert!(
+            activation.is_active(now + TRANSIENT_ACTIVATION_DURATION - Duration::from_millis(1))
+        );
+        assert!(!activation.is_active(now + TRANSIENT_ACTIVATION_DURATION));
+
+        // Consuming an expired activation doesn't open a popup, and clears it anyway.
+        assert!(!activation.consume(now + TRANSIENT_ACTIVATION_DURATION));
+        assert!(!activation.is_active(now));
+    }
+}
@@ -2,9 +2,32 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use msg::constellation_msg::TopLevelBrowsingContextId;
+use msg::constellation_msg::{TopLevelBrowsingContextId, WebViewGroupId};
+use serde::{Deserialize, Serialize};
+
+/// A change to the state of a [`WebViewManager`], which applied again in the same order
+/// to an empty manager gives it the same state.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum WebViewTransition {
+    Add(TopLevelBrowsingContextId, WebViewGroupId),
+    Remove(TopLevelBrowsingContextId),
+    SetOpener(TopLevelBrowsingContextId, TopLevelBrowsingContextId),
+    MarkCrashed(TopLevelBrowsingContextId),
+    MarkRecovered(TopLevelBrowsingContextId),
+    MarkPrerendering(TopLevelBrowsingContextId),
+    MarkPrerendered(TopLevelBrowsingContextId),
+    MarkShown(TopLevelBrowsingContextId),
+    MarkNotShown(TopLevelBrowsingContextId),
+    MarkAllShown,
+    MarkAllNotShown,
+    Focus(TopLevelBrowsingContextId),
+    Unfocus,
+    FocusPrevious,
+    FocusNext,
+    SetIndex(TopLevelBrowsingContextId, usize),
+}
 
 #[derive(Debug)]
 pub struct WebViewManager<WebView> {
@@ -15,8 +38,30 @@ pub struct WebViewManager<WebView> {
     /// The order in which they were focused, latest last.
     focus_order: Vec<TopLevelBrowsingContextId>,
 
+    /// The order in which they are painted, bottom first. Webviews are added on top.
+    painting_order: Vec<TopLevelBrowsingContextId>,
+
+    /// The group of each webview, which decides the cookies, caches and storage it uses.
+    groups: HashMap<TopLevelBrowsingContextId, WebViewGroupId>,
+
+    /// The webviews that the embedder has hidden. Webviews are shown when they are added.
+    not_shown: HashSet<TopLevelBrowsingContextId>,
+
+    /// The webview whose document opened each popup with `window.open`, while both exist.
+    openers: HashMap<TopLevelBrowsingContextId, TopLevelBrowsingContextId>,
+
+    /// The webviews whose content crashed, and which show a crash page until they load
+    /// another page.
+    crashed: HashSet<TopLevelBrowsingContextId>,
+
+    /// The hidden webviews that load a page before it is needed, until they are activated.
+    prerendering: HashSet<TopLevelBrowsingContextId>,
+
     /// Whether the latest webview in focus order is currently focused.
     is_focused: bool,
+
+    /// The transitions since they were last taken, while they are being recorded.
+    transitions: Option<Vec<WebViewTransition>>,
 }
 
 impl<WebView> Default for WebViewManager<WebView> {
@@ -24,29 +69,191 @@ impl<WebView> Default for WebViewManager<WebView> {
         Self {
             webviews: HashMap::default(),
             focus_order: Vec::default(),
+            painting_order: Vec::default(),
+            groups: HashMap::default(),
+            not_shown: HashSet::default(),
+            openers: HashMap::default(),
+            crashed: HashSet::default(),
+            prerendering: HashSet::default(),
             is_focused: false,
+            transitions: None,
+        }
+    }
+}
+
+impl<WebView: Default> WebViewManager<WebView> {
+    /// A manager in the state that `transitions` give an empty one, with default webviews.
+    pub fn replay<'a>(transitions: impl IntoIterator<Item = &'a WebViewTransition>) -> Self {
+        let mut webviews = Self::default();
+        for transition in transitions {
+            webviews.apply(transition);
+        }
+        webviews
+    }
+
+    /// Make the change that `transition` records, adding a default webview if it adds one.
+    pub fn apply(&mut self, transition: &WebViewTransition) {
+        match *transition {
+            WebViewTransition::Add(id, group_id) => {
+                self.add_to_group(id, WebView::default(), group_id)
+            },
+            WebViewTransition::Remove(id) => {
+                self.remove(id);
+            },
+            WebViewTransition::SetOpener(id, opener) => self.set_opener(id, opener),
+            WebViewTransition::MarkCrashed(id) => {
+                self.mark_webview_crashed(id);
+            },
+            WebViewTransition::MarkRecovered(id) => {
+                self.mark_webview_recovered(id);
+            },
+            WebViewTransition::MarkPrerendering(id) => {
+                self.mark_webview_prerendering(id);
+            },
+            WebViewTransition::MarkPrerendered(id) => {
+                self.mark_webview_prerendered(id);
+            },
+            WebViewTransition::MarkShown(id) => {
+                self.mark_webview_shown(id);
+            },
+            WebViewTransition::MarkNotShown(id) => {
+                self.mark_webview_not_shown(id);
+            },
+            WebViewTransition::MarkAllShown => {
+                self.mark_all_webviews_shown();
+            },
+            WebViewTransition::MarkAllNotShown => {
+                self.mark_all_webviews_not_shown();
+            },
+            WebViewTransition::Focus(id) => self.focus(id),
+            WebViewTransition::Unfocus => self.unfocus(),
+            WebViewTransition::FocusPrevious => {
+                self.focus_previous();
+            },
+            WebViewTransition::FocusNext => {
+                self.focus_next();
+            },
+            WebViewTransition::SetIndex(id, index) => self.set_index(id, index),
         }
     }
 }
 
 impl<WebView> WebViewManager<WebView> {
+    /// Start or stop recording the transitions of this manager, dropping any that
+    /// weren't taken when stopping.
+    pub fn record_transitions(&mut self, record: bool) {
+        match (record, self.transitions.is_some()) {
+            (true, false) => self.transitions = Some(vec![]),
+            (false, true) => self.transitions = None,
+            _ => {},
+        }
+    }
+
+    /// The transitions recorded since the last call, oldest first.
+    pub fn take_transitions(&mut self) -> Vec<WebViewTransition> {
+        self.transitions
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Transitions that give an empty manager the current state of this one, such as
+    /// to start a recording with.
+    pub fn snapshot(&self) -> Vec<WebViewTransition> {
+        let mut transitions: Vec<WebViewTransition> = self
+            .painting_order
+            .iter()
+            .map(|id| WebViewTransition::Add(*id, self.group(*id)))
+            .collect();
+        transitions.extend(
+            self.focus_order
+                .iter()
+                .copied()
+                .map(WebViewTransition::Focus),
+        );
+        if !self.is_focused {
+            transitions.push(WebViewTransition::Unfocus);
+        }
+        transitions.extend(
+            self.not_shown
+                .iter()
+                .copied()
+                .map(WebViewTransition::MarkNotShown),
+        );
+        transitions.extend(
+            self.openers
+                .iter()
+                .map(|(id, opener)| WebViewTransition::SetOpener(*id, *opener)),
+        );
+        transitions.extend(
+            self.crashed
+                .iter()
+                .copied()
+                .map(WebViewTransition::MarkCrashed),
+        );
+        transitions.extend(
+            self.prerendering
+                .iter()
+                .copied()
+                .map(WebViewTransition::MarkPrerendering),
+        );
+        transitions
+    }
+
+    fn record(&mut self, transition: WebViewTransition) {
+        if let Some(transitions) = self.transitions.as_mut() {
+            transitions.push(transition);
+        }
+    }
+
     pub fn add(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
         webview: WebView,
     ) {
+        self.add_to_group(
+            top_level_browsing_context_id,
+            webview,
+            WebViewGroupId::default(),
+        );
+    }
+
+    pub fn add_to_group(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        webview: WebView,
+        group_id: WebViewGroupId,
+    ) {
+        self.record(WebViewTransition::Add(
+            top_level_browsing_context_id,
+            group_id,
+        ));
+        if !self.webviews.contains_key(&top_level_browsing_context_id) {
+            self.painting_order.push(top_level_browsing_context_id);
+        }
         self.webviews.insert(top_level_browsing_context_id, webview);
+        self.groups.insert(top_level_browsing_context_id, group_id);
     }
 
     pub fn remove(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) -> Option<WebView> {
+        self.record(WebViewTransition::Remove(top_level_browsing_context_id));
         if self.focus_order.last() == Some(&top_level_browsing_context_id) {
             self.is_focused = false;
         }
         self.focus_order
             .retain(|b| *b != top_level_browsing_context_id);
+        self.painting_order
+            .retain(|b| *b != top_level_browsing_context_id);
+        self.groups.remove(&top_level_browsing_context_id);
+        self.not_shown.remove(&top_level_browsing_context_id);
+        self.openers.remove(&top_level_browsing_context_id);
+        self.openers
+            .retain(|_, opener| *opener != top_level_browsing_context_id);
+        self.crashed.remove(&top_level_browsing_context_id);
+        self.prerendering.remove(&top_level_browsing_context_id);
         self.webviews.remove(&top_level_browsing_context_id)
     }
 
@@ -64,6 +271,214 @@ impl<WebView> WebViewManager<WebView> {
         self.webviews.get_mut(&top_level_browsing_context_id)
     }
 
+    /// The number of webviews.
+    pub fn len(&self) -> usize {
+        self.webviews.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.webviews.is_empty()
+    }
+
+    /// All webviews with their ids, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (TopLevelBrowsingContextId, &WebView)> + '_ {
+        self.webviews.iter().map(|(id, webview)| (*id, webview))
+    }
+
+    /// All webviews with their ids, in no particular order.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (TopLevelBrowsingContextId, &mut WebView)> + '_ {
+        self.webviews.iter_mut().map(|(id, webview)| (*id, webview))
+    }
+
+    /// The ids of all webviews, in no particular order.
+    pub fn webview_ids(&self) -> impl Iterator<Item = TopLevelBrowsingContextId> + '_ {
+        self.webviews.keys().copied()
+    }
+
+    /// The group of the given webview, or the default group if there is no such webview.
+    pub fn group(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> WebViewGroupId {
+        self.groups
+            .get(&top_level_browsing_context_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The ids of the webviews in the given group, in no particular order.
+    pub fn ids_in_group(
+        &self,
+        group_id: WebViewGroupId,
+    ) -> impl Iterator<Item = TopLevelBrowsingContextId> + '_ {
+        self.groups
+            .iter()
+            .filter(move |(_, group)| **group == group_id)
+            .map(|(id, _)| *id)
+    }
+
+    /// Record that the given webview is a popup opened by a document in `opener`.
+    pub fn set_opener(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        opener: TopLevelBrowsingContextId,
+    ) {
+        debug_assert!(self.webviews.contains_key(&top_level_browsing_context_id));
+        debug_assert!(self.webviews.contains_key(&opener));
+        self.record(WebViewTransition::SetOpener(
+            top_level_browsing_context_id,
+            opener,
+        ));
+        self.openers.insert(top_level_browsing_context_id, opener);
+    }
+
+    /// The webview that opened the given popup, if it is one and its opener still exists.
+    pub fn opener(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> Option<TopLevelBrowsingContextId> {
+        self.openers.get(&top_level_browsing_context_id).copied()
+    }
+
+    pub fn is_popup(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) -> bool {
+        self.openers.contains_key(&top_level_browsing_context_id)
+    }
+
+    /// The popups opened by the given webview, in no particular order.
+    pub fn popups(
+        &self,
+        opener: TopLevelBrowsingContextId,
+    ) -> impl Iterator<Item = TopLevelBrowsingContextId> + '_ {
+        self.openers
+            .iter()
+            .filter(move |(_, id)| **id == opener)
+            .map(|(popup, _)| *popup)
+    }
+
+    pub fn is_crashed(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) -> bool {
+        self.crashed.contains(&top_level_browsing_context_id)
+    }
+
+    /// Record that the content of the given webview crashed, returning whether it hadn't
+    /// already.
+    pub fn mark_webview_crashed(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.record(WebViewTransition::MarkCrashed(
+            top_level_browsing_context_id,
+        ));
+        self.webviews.contains_key(&top_level_browsing_context_id) &&
+            self.crashed.insert(top_level_browsing_context_id)
+    }
+
+    /// Record that the given webview loaded a page after crashing, returning whether it
+    /// had crashed.
+    pub fn mark_webview_recovered(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.record(WebViewTransition::MarkRecovered(
+            top_level_browsing_context_id,
+        ));
+        self.crashed.remove(&top_level_browsing_context_id)
+    }
+
+    pub fn is_prerendering(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.prerendering.contains(&top_level_browsing_context_id)
+    }
+
+    /// Record that the given webview is a prerender, returning whether it wasn't already.
+    pub fn mark_webview_prerendering(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.record(WebViewTransition::MarkPrerendering(
+            top_level_browsing_context_id,
+        ));
+        self.webviews.contains_key(&top_level_browsing_context_id) &&
+            self.prerendering.insert(top_level_browsing_context_id)
+    }
+
+    /// Record that the given webview was activated, returning whether it was a prerender.
+    pub fn mark_webview_prerendered(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.record(WebViewTransition::MarkPrerendered(
+            top_level_browsing_context_id,
+        ));
+        self.prerendering.remove(&top_level_browsing_context_id)
+    }
+
+    /// The ids of the webviews that have been focused, from the least to the most
+    /// recently focused.
+    pub fn focus_order(&self) -> impl Iterator<Item = TopLevelBrowsingContextId> + '_ {
+        self.focus_order.iter().copied()
+    }
+
+    /// The ids of all webviews from the least to the most recently focused, starting
+    /// with the ones that were never focused, from the bottom of the painting order.
+    pub fn least_recently_focused(&self) -> Vec<TopLevelBrowsingContextId> {
+        let mut ids: Vec<TopLevelBrowsingContextId> = self
+            .painting_order
+            .iter()
+            .filter(|id| !self.focus_order.contains(id))
+            .copied()
+            .collect();
+        ids.extend(self.focus_order.iter().copied());
+        ids
+    }
+
+    pub fn is_shown(&self, top_level_browsing_context_id: TopLevelBrowsingContextId) -> bool {
+        !self.not_shown.contains(&top_level_browsing_context_id)
+    }
+
+    /// Show the given webview, returning whether it was hidden.
+    pub fn mark_webview_shown(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.record(WebViewTransition::MarkShown(top_level_browsing_context_id));
+        self.not_shown.remove(&top_level_browsing_context_id)
+    }
+
+    /// Hide the given webview, returning whether it was shown.
+    pub fn mark_webview_not_shown(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.record(WebViewTransition::MarkNotShown(
+            top_level_browsing_context_id,
+        ));
+        self.webviews.contains_key(&top_level_browsing_context_id) &&
+            self.not_shown.insert(top_level_browsing_context_id)
+    }
+
+    /// Show every webview, returning the ones that were hidden, in no particular order.
+    pub fn mark_all_webviews_shown(&mut self) -> Vec<TopLevelBrowsingContextId> {
+        self.record(WebViewTransition::MarkAllShown);
+        self.not_shown.drain().collect()
+    }
+
+    /// Hide every webview, returning the ones that were shown, in no particular order.
+    pub fn mark_all_webviews_not_shown(&mut self) -> Vec<TopLevelBrowsingContextId> {
+        self.record(WebViewTransition::MarkAllNotShown);
+        let shown: Vec<TopLevelBrowsingContextId> = self
+            .webviews
+            .keys()
+            .filter(|id| !self.not_shown.contains(id))
+            .copied()
+            .collect();
+        self.not_shown.extend(shown.iter().copied());
+        shown
+    }
+
     pub fn focused_webview(&self) -> Option<(TopLevelBrowsingContextId, &WebView)> {
         if !self.is_focused {
             return None;
@@ -84,6 +499,7 @@ impl<WebView> WebViewManager<WebView> {
 
     pub fn focus(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
         debug_assert!(self.webviews.contains_key(&top_level_browsing_context_id));
+        self.record(WebViewTransition::Focus(top_level_browsing_context_id));
         self.focus_order
             .retain(|b| *b != top_level_browsing_context_id);
         self.focus_order.push(top_level_browsing_context_id);
@@ -91,8 +507,71 @@ impl<WebView> WebViewManager<WebView> {
     }
 
     pub fn unfocus(&mut self) {
+        self.record(WebViewTransition::Unfocus);
         self.is_focused = false;
     }
+
+    /// Focus the webview that was focused before the focused one, which moves to the
+    /// start of the focus order, so that repeated calls cycle through the webviews from
+    /// the most to the least recently focused. If none is focused, the one that was
+    /// focused last is focused again.
+    pub fn focus_previous(&mut self) -> Option<TopLevelBrowsingContextId> {
+        self.record(WebViewTransition::FocusPrevious);
+        if self.is_focused {
+            self.focus_order.rotate_right(1);
+        }
+        self.refocus_latest()
+    }
+
+    /// Undo [`Self::focus_previous`], focusing the webview at the start of the focus
+    /// order. If none is focused, the one that was focused last is focused again.
+    pub fn focus_next(&mut self) -> Option<TopLevelBrowsingContextId> {
+        self.record(WebViewTransition::FocusNext);
+        if self.is_focused {
+            self.focus_order.rotate_left(1);
+        }
+        self.refocus_latest()
+    }
+
+    fn refocus_latest(&mut self) -> Option<TopLevelBrowsingContextId> {
+        let latest = self.focus_order.last().copied();
+        self.is_focused = latest.is_some();
+        latest
+    }
+
+    /// The ids of all webviews from the bottom to the top of the painting order.
+    pub fn painting_order(&self) -> impl Iterator<Item = TopLevelBrowsingContextId> + '_ {
+        self.painting_order.iter().copied()
+    }
+
+    /// Move the given webview to `index` in the painting order, counting from the bottom.
+    /// An index past the top moves it to the top.
+    pub fn set_index(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        index: usize,
+    ) {
+        debug_assert!(self.webviews.contains_key(&top_level_browsing_context_id));
+        self.record(WebViewTransition::SetIndex(
+            top_level_browsing_context_id,
+            index,
+        ));
+        self.painting_order
+            .retain(|b| *b != top_level_browsing_context_id);
+        let index = index.min(self.painting_order.len());
+        self.painting_order
+            .insert(index, top_level_browsing_context_id);
+    }
+
+    /// Paint the given webview above all others.
+    pub fn raise_to_top(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        self.set_index(top_level_browsing_context_id, usize::MAX);
+    }
+
+    /// Paint the given webview below all others.
+    pub fn lower_to_bottom(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        self.set_index(top_level_browsing_context_id, 0);
+    }
 }
 
 #[cfg(test)]
@@ -101,10 +580,10 @@ mod test {
 
     use msg::constellation_msg::{
         BrowsingContextId, BrowsingContextIndex, PipelineNamespace, PipelineNamespaceId,
-        TopLevelBrowsingContextId,
+        TopLevelBrowsingContextId, WebViewGroupId,
     };
 
-    use crate::webview::WebViewManager;
+    use crate::webview::{WebViewManager, WebViewTransition};
 
     fn top_level_id(namespace_id: u32, index: u32) -> TopLevelBrowsingContextId {
         TopLevelBrowsingContextId(BrowsingContextId {
@@ -116,20 +595,12 @@ mod test {
     fn webviews_sorted<WebView: Clone>(
         webviews: &WebViewManager<WebView>,
     ) -> Vec<(TopLevelBrowsingContextId, WebView)> {
-        let mut keys = webviews.webviews.keys().collect::<Vec<_>>();
-        keys.sort();
-        keys.iter()
-            .map(|&id| {
-                (
-                    *id,
-                    webviews
-                        .webviews
-                        .get(id)
-                        .cloned()
-                        .expect("Incorrect test case"),
-                )
-            })
-            .collect()
+        let mut webviews = webviews
+            .iter()
+            .map(|(id, webview)| (id, webview.clone()))
+            .collect::<Vec<_>>();
+        webviews.sort_by_key(|(id, _)| *id);
+        webviews
     }
 
     #[test]
@@ -196,5 +667,352 @@ mod test {
         // remove() removes the given webview from both the map and the focus order.
         assert!(webviews_sorted(&webviews).is_empty());
         assert!(webviews.focus_order.is_empty());
+        assert!(webviews.painting_order.is_empty());
+    }
+
+    #[test]
+    fn test_painting_order() {
+        PipelineNamespace::install(PipelineNamespaceId(1));
+        let mut webviews = WebViewManager::default();
+
+        // add() puts the webview on top of the painting order.
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        webviews.add(TopLevelBrowsingContextId::new(), 'b');
+        webviews.add(TopLevelBrowsingContextId::new(), 'c');
+        let (a, b, c) = (top_level_id(1, 1), top_level_id(1, 2), top_level_id(1, 3));
+        assert_eq!(webviews.painting_order, vec![a, b, c]);
+
+        webviews.raise_to_top(a);
+        assert_eq!(webviews.painting_order, vec![b, c, a]);
+        webviews.lower_to_bottom(c);
+        assert_eq!(webviews.painting_order, vec![c, b, a]);
+        webviews.set_index(c, 1);
+        assert_eq!(webviews.painting_order, vec![b, c, a]);
+
+        // Indices past the top are clamped.
+        webviews.set_index(b, 10);
+        assert_eq!(webviews.painting_order, vec![c, a, b]);
+
+        // Focusing doesn't change the painting order.
+        webviews.focus(c);
+        assert_eq!(webviews.painting_order, vec![c, a, b]);
+
+        webviews.remove(a);
+        assert_eq!(webviews.painting_order, vec![c, b]);
+    }
+
+    #[test]
+    fn test_focus_traversal() {
+        PipelineNamespace::install(PipelineNamespaceId(2));
+        let mut webviews = WebViewManager::default();
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        webviews.add(TopLevelBrowsingContextId::new(), 'b');
+        webviews.add(TopLevelBrowsingContextId::new(), 'c');
+        let (a, b, c) = (top_level_id(2, 1), top_level_id(2, 2), top_level_id(2, 3));
+
+        // Nothing has been focused yet, so there is nothing to traverse.
+        assert_eq!(webviews.focus_previous(), None);
+        assert_eq!(webviews.is_focused, false);
+
+        webviews.focus(a);
+        webviews.focus(b);
+        webviews.focus(c);
+
+        // focus_previous() goes from the most to the least recently focused, and wraps.
+        assert_eq!(webviews.focus_previous(), Some(b));
+        assert_eq!(webviews.focus_previous(), Some(a));
+        assert_eq!(webviews.focus_previous(), Some(c));
+        assert_eq!(webviews.focus_order, vec![a, b, c]);
+
+        // focus_next() goes the other way.
+        assert_eq!(webviews.focus_next(), Some(a));
+        assert_eq!(webviews.focus_next(), Some(b));
+        assert_eq!(webviews.focused_webview().map(|(id, _)| id), Some(b));
+
+        // Without a focused webview, the latest one is focused again.
+        webviews.unfocus();
+        assert_eq!(webviews.focus_next(), Some(b));
+        assert_eq!(webviews.is_focused, true);
+    }
+
+    #[test]
+    fn test_groups() {
+        PipelineNamespace::install(PipelineNamespaceId(3));
+        let mut webviews = WebViewManager::default();
+        let profile = WebViewGroupId(1);
+
+        // add() puts the webview in the default group, add_to_group() in the given one.
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        webviews.add_to_group(TopLevelBrowsingContextId::new(), 'b', profile);
+        webviews.add_to_group(TopLevelBrowsingContextId::new(), 'c', profile);
+        let (a, b, c) = (top_level_id(3, 1), top_level_id(3, 2), top_level_id(3, 3));
+        assert_eq!(webviews.group(a), WebViewGroupId::default());
+        assert_eq!(webviews.group(b), profile);
+
+        let mut members = webviews.ids_in_group(profile).collect::<Vec<_>>();
+        members.sort();
+        assert_eq!(members, vec![b, c]);
+
+        // remove() takes the webview out of its group.
+        webviews.remove(b);
+        assert_eq!(webviews.ids_in_group(profile).collect::<Vec<_>>(), vec![c]);
+        assert_eq!(
+            webviews
+                .ids_in_group(WebViewGroupId::default())
+                .collect::<Vec<_>>(),
+            vec![a]
+        );
+    }
+
+    #[test]
+    fn test_iteration() {
+        PipelineNamespace::install(PipelineNamespaceId(4));
+        let mut webviews = WebViewManager::default();
+        assert!(webviews.is_empty());
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        webviews.add(TopLevelBrowsingContextId::new(), 'b');
+        let (a, b) = (top_level_id(4, 1), top_level_id(4, 2));
+        assert_eq!(webviews.len(), 2);
+
+        let mut ids = webviews.webview_ids().collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec![a, b]);
+
+        // iter_mut() changes the webviews in place.
+        for (_, webview) in webviews.iter_mut() {
+            *webview = webview.to_ascii_uppercase();
+        }
+        assert_eq!(webviews_sorted(&webviews), vec![(a, 'A'), (b, 'B')]);
+
+        // focus_order() lists the focused webviews, latest last.
+        webviews.focus(b);
+        webviews.focus(a);
+        assert_eq!(webviews.focus_order().collect::<Vec<_>>(), vec![b, a]);
+    }
+
+    #[test]
+    fn test_visibility() {
+        PipelineNamespace::install(PipelineNamespaceId(5));
+        let mut webviews = WebViewManager::default();
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        webviews.add(TopLevelBrowsingContextId::new(), 'b');
+        let (a, b) = (top_level_id(5, 1), top_level_id(5, 2));
+
+        // Webviews are shown when they are added, and marking them only reports changes.
+        assert!(webviews.is_shown(a));
+        assert!(!webviews.mark_webview_shown(a));
+        assert!(webviews.mark_webview_not_shown(a));
+        assert!(!webviews.mark_webview_not_shown(a));
+        assert!(!webviews.is_shown(a));
+
+        // The bulk methods return the webviews whose visibility changed.
+        let mut hidden = webviews.mark_all_webviews_not_shown();
+        hidden.sort();
+        assert_eq!(hidden, vec![b]);
+        let mut shown = webviews.mark_all_webviews_shown();
+        shown.sort();
+        assert_eq!(shown, vec![a, b]);
+        assert!(webviews.mark_all_webviews_shown().is_empty());
+
+        // remove() forgets that a webview was hidden.
+        webviews.mark_webview_not_shown(b);
+        webviews.remove(b);
+        assert!(webviews.mark_all_webviews_shown().is_empty());
+    }
+
+    #[test]
+    fn test_openers() {
+        PipelineNamespace::install(PipelineNamespaceId(6));
+        let mut webviews = WebViewManager::default();
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        webviews.add(TopLevelBrowsingContextId::new(), 'b');
+        webviews.add(TopLevelBrowsingContextId::new(), 'c');
+        let (a, b, c) = (top_level_id(6, 1), top_level_id(6, 2), top_level_id(6, 3));
+
+        webviews.set_opener(b, a);
+        webviews.set_opener(c, a);
+        assert_eq!(webviews.opener(b), Some(a));
+        assert_eq!(webviews.opener(a), None);
+        assert!(webviews.is_popup(c));
+        assert!(!webviews.is_popup(a));
+        let mut popups: Vec<_> = webviews.popups(a).collect();
+        popups.sort();
+        assert_eq!(popups, vec![b, c]);
+
+        // remove() forgets the popups of the removed webview, and whether it was one.
+        webviews.remove(c);
+        assert_eq!(webviews.popups(a).collect::<Vec<_>>(), vec![b]);
+        webviews.remove(a);
+        assert_eq!(webviews.opener(b), None);
+        assert!(!webviews.is_popup(b));
+    }
+
+    #[test]
+    fn test_least_recently_focused() {
+        PipelineNamespace::install(PipelineNamespaceId(8));
+        let mut webviews = WebViewManager::default();
+        let a = top_level_id(8, 1);
+        let b = top_level_id(8, 2);
+        let c = top_level_id(8, 3);
+        let d = top_level_id(8, 4);
+        webviews.add(a, 'a');
+        webviews.add(b, 'b');
+        webviews.add(c, 'c');
+        webviews.add(d, 'd');
+
+        // Webviews that were never focused come first, in painting order.
+        webviews.focus(c);
+        webviews.focus(a);
+        assert_eq!(webviews.least_recently_focused(), vec![b, d, c, a]);
+        webviews.lower_to_bottom(d);
+        assert_eq!(webviews.least_recently_focused(), vec![d, b, c, a]);
+
+        // Focusing a webview again makes it the most recently focused.
+        webviews.focus(c);
+        assert_eq!(webviews.least_recently_focused(), vec![d, b, a, c]);
+        webviews.remove(b);
+        assert_eq!(webviews.least_recently_focused(), vec![d, a, c]);
+    }
+
+    #[test]
+    fn test_crashed() {
+        PipelineNamespace::install(PipelineNamespaceId(7));
+        let mut webviews = WebViewManager::default();
+        webviews.add(TopLevelBrowsingContextId::new(), 'a');
+        let (a, b) = (top_level_id(7, 1), top_level_id(7, 2));
+
+        assert!(!webviews.is_crashed(a));
+        assert!(webviews.mark_webview_crashed(a));
+        assert!(!webviews.mark_webview_crashed(a));
+        assert!(webviews.is_crashed(a));
+
+        // Only webviews that exist can crash.
+        assert!(!webviews.mark_webview_crashed(b));
+        assert!(!webviews.is_crashed(b));
+
+        assert!(webviews.mark_webview_recovered(a));
+        assert!(!webviews.mark_webview_recovered(a));
+        assert!(!webviews.is_crashed(a));
+
+        // remove() forgets that the webview crashed.
+        webviews.mark_webview_crashed(a);
+        webviews.remove(a);
+        assert!(!webviews.is_crashed(a));
+    }
+
+    #[test]
+    fn test_prerendering() {
+        PipelineNamespace::install(PipelineNamespaceId(9));
+        let mut webviews = WebViewManager::default();
+        let a = top_level_id(9, 1);
+        let b = top_level_id(9, 2);
+        webviews.add(a, 'a');
+
+        // Only existing webviews can be prerenders.
+        assert!(!webviews.mark_webview_prerendering(b));
+        assert!(webviews.mark_webview_prerendering(a));
+        assert!(!webviews.mark_webview_prerendering(a));
+        assert!(webviews.is_prerendering(a));
+
+        assert!(webviews.mark_webview_prerendered(a));
+        assert!(!webviews.mark_webview_prerendered(a));
+        assert!(!webviews.is_prerendering(a));
+
+        // Removing a webview forgets that it is a prerender.
+        webviews.add(b, 'b');
+        webviews.mark_webview_prerendering(b);
+        webviews.remove(b);
+        webviews.add(b, 'b');
+        assert!(!webviews.is_prerendering(b));
+    }
+
+    #[test]
+    fn test_replay() {
+        PipelineNamespace::install(PipelineNamespaceId(10));
+        let mut webviews = WebViewManager::default();
+        let (a, b, c) = (
+            top_level_id(10, 1),
+            top_level_id(10, 2),
+            top_level_id(10, 3),
+        );
+
+        // Nothing is recorded until recording starts.
+        webviews.add(a, 'a');
+        assert!(webviews.take_transitions().is_empty());
+
+        webviews.record_transitions(true);
+        webviews.add_to_group(b, 'b', WebViewGroupId(1));
+        webviews.add(c, 'c');
+        webviews.set_opener(c, b);
+        webviews.focus(b);
+        webviews.focus(c);
+        webviews.focus_previous();
+        webviews.mark_webview_not_shown(a);
+        webviews.raise_to_top(a);
+        webviews.mark_webview_crashed(c);
+        webviews.remove(b);
+        let transitions = webviews.take_transitions();
+        assert_eq!(transitions[0], WebViewTransition::Add(b, WebViewGroupId(1)));
+        assert_eq!(transitions.last(), Some(&WebViewTransition::Remove(b)));
+        assert!(webviews.take_transitions().is_empty());
+
+        // Replaying the transitions after adding the webview that was added before they
+        // were recorded gives the same state.
+        let add_a = WebViewTransition::Add(a, WebViewGroupId(0));
+        let replayed: WebViewManager<char> =
+            WebViewManager::replay(std::iter::once(&add_a).chain(&transitions));
+        assert_eq!(replayed.focus_order, webviews.focus_order);
+        assert_eq!(replayed.painting_order, webviews.painting_order);
+        assert_eq!(replayed.groups, webviews.groups);
+        assert_eq!(replayed.not_shown, webviews.not_shown);
+        assert_eq!(replayed.openers, webviews.openers);
+        assert_eq!(replayed.crashed, webviews.crashed);
+        assert_eq!(replayed.is_focused, webviews.is_focused);
+        assert_eq!(replayed.len(), webviews.len());
+
+        // So does replaying a snapshot.
+        let replayed: WebViewManager<char> = WebViewManager::replay(&webviews.snapshot());
+        assert_eq!(replayed.focus_order, webviews.focus_order);
+        assert_eq!(replayed.painting_order, webviews.painting_order);
+        assert_eq!(replayed.not_shown, webviews.not_shown);
+        assert_eq!(replayed.crashed, webviews.crashed);
+        assert_eq!(replayed.is_focused, webviews.is_focused);
+
+        // Stopping drops the transitions that weren't taken.
+        webviews.unfocus();
+        webviews.record_transitions(false);
+        webviews.record_transitions(true);
+        assert!(webviews.take_transitions().is_empty());
+    }
+
+    #[test]
+    fn test_focused_webview() {
+        // Only the latest webview to be focused is, until the embedder unfocuses the
+        // webviews or closes that one.
+        PipelineNamespace::install(PipelineNamespaceId(11));
+        let mut webviews = WebViewManager::default();
+        let (a, b) = (top_level_id(11, 1), top_level_id(11, 2));
+        webviews.add(a, 'a');
+        webviews.add(b, 'b');
+
+        // Adding a webview doesn't focus it.
+        assert_eq!(webviews.focused_webview(), None);
+
+        webviews.focus(a);
+        assert_eq!(webviews.focused_webview(), Some((a, &'a')));
+        webviews.focus(b);
+        assert_eq!(webviews.focused_webview(), Some((b, &'b')));
+
+        // Once the embedder unfocuses the webviews, none of them is focused, even the one
+        // that was focused last.
+        webviews.unfocus();
+        assert_eq!(webviews.focused_webview(), None);
+
+        // Closing the focused webview doesn't pass the focus on to another one.
+        webviews.focus(a);
+        webviews.remove(a);
+        assert_eq!(webviews.focused_webview(), None);
+        webviews.focus(b);
+        assert_eq!(webviews.focused_webview(), Some((b, &'b')));
     }
 }
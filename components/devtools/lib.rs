@@ -32,6 +32,7 @@ use ipc_channel::ipc::{self, IpcSender};
 use log::{debug, warn};
 use msg::constellation_msg::{BrowsingContextId, PipelineId};
 use serde::Serialize;
+use servo_config::pref;
 use servo_rand::RngCore;
 
 use crate::actor::{Actor, ActorRegistry};
@@ -72,6 +73,7 @@ mod actors {
     pub mod worker;
 }
 mod protocol;
+mod websocket;
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 enum UniqueId {
@@ -149,6 +151,11 @@ fn run_server(
     let port = bound.as_ref().map(|(_, port)| *port).ok_or(());
     embedder.send((None, EmbedderMsg::OnDevtoolsStarted(port, token.clone())));
 
+    if pref!(devtools.server.websocket.enabled) {
+        let port = start_websocket_listener(&sender, &token);
+        embedder.send((None, EmbedderMsg::OnDevtoolsWebSocketStarted(port)));
+    }
+
     let listener = match bound {
         Some((l, _)) => l,
         None => return,
@@ -716,6 +723,32 @@ fn run_server(
     }
 }
 
+/// Start the WebSocket listener for remote clients, configured from the
+/// `devtools.server.websocket.*` preferences. Remote clients authenticate with
+/// the configured token, or with the embedder token if none is set. Without a
+/// certificate and key only local clients can connect.
+fn start_websocket_listener(sender: &Sender<DevtoolsControlMsg>, token: &str) -> Result<u16, ()> {
+    let configured_token = pref!(devtools.server.websocket.token);
+    let cert_path = pref!(devtools.server.websocket.tls_cert_path);
+    let key_path = pref!(devtools.server.websocket.tls_key_path);
+    let options = websocket::WebSocketOptions {
+        port: pref!(devtools.server.websocket.port) as u16,
+        token: if configured_token.is_empty() {
+            token.to_owned()
+        } else {
+            configured_token
+        },
+        tls: if cert_path.is_empty() || key_path.is_empty() {
+            None
+        } else {
+            Some((cert_path, key_path))
+        },
+    };
+    websocket::start_listener(options, sender.clone()).map_err(|e| {
+        warn!("Failed to start devtools WebSocket server: {}", e);
+    })
+}
+
 fn allow_devtools_client(stream: &mut TcpStream, embedder: &EmbedderProxy, token: &str) -> bool {
     // By-pass prompt if we receive a valid token.
     let token = format!("25:{{\"auth_token\":\"{}\"}}", token);
@@ -0,0 +1,355 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A WebSocket transport for the devtools server, for debugging devices that can't expose the
+//! plain TCP port. Each WebSocket text message carries one JSON packet. Clients must present the
+//! server token, either as an `Authorization: Bearer <token>` header or as a `token` query
+//! parameter, and the connection can optionally be wrapped in TLS. Without TLS the token would
+//! cross the network in the clear, so the listener then only accepts connections from loopback.
+//!
+//! Authenticated connections are bridged onto a loopback [`TcpStream`], so that the actors keep
+//! speaking the regular [stream transport] and don't need to know about WebSockets at all.
+//!
+//! [stream transport]: https://wiki.mozilla.org/Remote_Debugging_Protocol_Stream_Transport
+
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
+use http::StatusCode;
+use log::{debug, info, warn};
+use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection, StreamOwned};
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::{Error as WebSocketError, Message, WebSocket};
+
+use crate::protocol::JsonPacketStream;
+
+/// How long a relay thread holds the WebSocket before giving the other direction a turn.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Settings for the WebSocket devtools listener.
+pub struct WebSocketOptions {
+    /// The port to listen on.
+    pub port: u16,
+    /// The token that clients must present to be allowed to connect.
+    pub token: String,
+    /// PEM-encoded certificate chain and private key, if TLS should be used.
+    pub tls: Option<(String, String)>,
+}
+
+/// A client connection, with or without TLS.
+enum MaybeTlsStream {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ServerConnection, TcpStream>>),
+}
+
+impl MaybeTlsStream {
+    fn tcp_stream(&self) -> &TcpStream {
+        match self {
+            MaybeTlsStream::Plain(stream) => stream,
+            MaybeTlsStream::Tls(stream) => stream.get_ref(),
+        }
+    }
+}
+
+impl Read for MaybeTlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            MaybeTlsStream::Plain(stream) => stream.read(buf),
+            MaybeTlsStream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for MaybeTlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            MaybeTlsStream::Plain(stream) => stream.write(buf),
+            MaybeTlsStream::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            MaybeTlsStream::Plain(stream) => stream.flush(),
+            MaybeTlsStream::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+fn load_tls_config(cert_path: &str, key_path: &str) -> Result<Arc<ServerConfig>, String> {
+    let mut cert_reader = BufReader::new(File::open(cert_path).map_err(|e| e.to_string())?);
+    let certs = rustls_pemfile::certs(&mut cert_reader)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    let mut key_reader = BufReader::new(File::open(key_path).map_err(|e| e.to_string())?);
+    let key = rustls_pemfile::pkcs8_private_keys(&mut key_reader)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .map(PrivateKey)
+        .ok_or_else(|| format!("No PKCS#8 private key found in {}", key_path))?;
+
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map(Arc::new)
+        .map_err(|e| e.to_string())
+}
+
+/// Compare two tokens without leaking how long the matching prefix is.
+fn tokens_match(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len() &&
+        expected
+            .bytes()
+            .zip(actual.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b)) ==
+            0
+}
+
+fn request_token(request: &Request) -> Option<String> {
+    let header_token = request
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_owned());
+    header_token.or_else(|| {
+        request.uri().query().and_then(|query| {
+            query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "token")
+                .map(|(_, value)| value.to_owned())
+        })
+    })
+}
+
+/// Start accepting WebSocket devtools clients. Authenticated clients are handed to the devtools
+/// server as regular clients through `sender`.
+pub fn start_listener(
+    options: WebSocketOptions,
+    sender: Sender<DevtoolsControlMsg>,
+) -> Result<u16, String> {
+    let tls_config = match options.tls {
+        Some((ref cert_path, ref key_path)) => Some(load_tls_config(cert_path, key_path)?),
+        None => None,
+    };
+    let uses_tls = tls_config.is_some();
+    let address = if uses_tls { "0.0.0.0" } else { "127.0.0.1" };
+    let listener = TcpListener::bind((address, options.port)).map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let token = Arc::new(options.token);
+
+    thread::Builder::new()
+        .name("DevtWsAcceptor".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("Error accepting devtools WebSocket connection: {}", e);
+                        continue;
+                    },
+                };
+                let tls_config = tls_config.clone();
+                let token = token.clone();
+                let sender = sender.clone();
+                let _ = thread::Builder::new()
+                    .name("DevtWsHandshake".to_owned())
+                    .spawn(move || {
+                        if let Err(e) = accept_client(stream, tls_config, &token, sender) {
+                            debug!("Rejected devtools WebSocket connection: {}", e);
+                        }
+                    });
+            }
+        })
+        .expect("Thread spawning failed");
+
+    info!(
+        "Devtools WebSocket server listening on {}:{} ({})",
+        address,
+        port,
+        if uses_tls { "TLS" } else { "no TLS" }
+    );
+    Ok(port)
+}
+
+fn accept_client(
+    stream: TcpStream,
+    tls_config: Option<Arc<ServerConfig>>,
+    token: &str,
+    sender: Sender<DevtoolsControlMsg>,
+) -> Result<(), String> {
+    let stream = match tls_config {
+        Some(config) => {
+            let connection = ServerConnection::new(config).map_err(|e| e.to_string())?;
+            MaybeTlsStream::Tls(Box::new(StreamOwned::new(connection, stream)))
+        },
+        None => MaybeTlsStream::Plain(stream),
+    };
+
+    let callback = |request: &Request, response: Response| match request_token(request) {
+        Some(ref presented) if tokens_match(token, presented) => Ok(response),
+        _ => {
+            let mut error = ErrorResponse::new(Some("Invalid devtools token".to_owned()));
+            *error.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(error)
+        },
+    };
+    let websocket = tungstenite::accept_hdr(stream, callback).map_err(|e| e.to_string())?;
+
+    // Bridge the WebSocket onto a loopback stream that the actors can use as usual.
+    let loopback = TcpListener::bind(("127.0.0.1", 0)).map_err(|e| e.to_string())?;
+    let relay_stream = TcpStream::connect(loopback.local_addr().map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    let (client_stream, _) = loopback.accept().map_err(|e| e.to_string())?;
+
+    websocket
+        .get_ref()
+        .tcp_stream()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|e| e.to_string())?;
+    let websocket = Arc::new(Mutex::new(websocket));
+
+    sender
+        .send(DevtoolsControlMsg::FromChrome(
+            ChromeToDevtoolsControlMsg::AddClient(client_stream),
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let outgoing = relay_stream.try_clone().map_err(|e| e.to_string())?;
+    let outgoing_websocket = websocket.clone();
+    thread::Builder::new()
+        .name("DevtWsOutgoing".to_owned())
+        .spawn(move || relay_outgoing(outgoing, outgoing_websocket))
+        .map_err(|e| e.to_string())?;
+    relay_incoming(relay_stream, websocket);
+    Ok(())
+}
+
+/// Forward packets written by the actors to the WebSocket client.
+fn relay_outgoing(mut stream: TcpStream, websocket: Arc<Mutex<WebSocket<MaybeTlsStream>>>) {
+    loop {
+        let packet = match stream.read_json_packet() {
+            Ok(Some(packet)) => packet,
+            Ok(None) => break,
+            Err(e) => {
+                debug!("Error reading devtools packet: {}", e);
+                break;
+            },
+        };
+        let mut websocket = websocket.lock().unwrap();
+        if let Err(e) = websocket.send(Message::Text(packet.to_string())) {
+            debug!("Error sending devtools WebSocket message: {}", e);
+            break;
+        }
+    }
+    let _ = websocket.lock().unwrap().close(None);
+}
+
+/// Forward packets from the WebSocket client to the actors.
+fn relay_incoming(mut stream: TcpStream, websocket: Arc<Mutex<WebSocket<MaybeTlsStream>>>) {
+    loop {
+        let message = {
+            let mut websocket = websocket.lock().unwrap();
+            websocket.read()
+        };
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Binary(bytes)) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => {
+                    debug!("Ignoring non-UTF8 devtools WebSocket message");
+                    continue;
+                },
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(WebSocketError::Io(ref e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut =>
+            {
+                // Let the outgoing relay use the socket in the meantime.
+                thread::yield_now();
+                continue;
+            },
+            Err(e) => {
+                debug!("Error reading devtools WebSocket message: {}", e);
+                break;
+            },
+        };
+        let packet = match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(packet) => packet,
+            Err(e) => {
+                debug!("Ignoring malformed devtools packet: {}", e);
+                continue;
+            },
+        };
+        if let Err(e) = stream.write_json_packet(&packet) {
+            debug!("Error forwarding devtools packet: {}", e);
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+#[cfg(test)]
+mod test {
+    use tungstenite::handshake::server::Request;
+
+    use super::{request_token, tokens_match};
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(authorization) = authorization {
+            builder = builder.header("Authorization", authorization);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("1A2B3C", "1A2B3C"));
+        assert!(!tokens_match("1A2B3C", "1A2B3D"));
+        assert!(!tokens_match("1A2B3C", "1A2B3"));
+        assert!(!tokens_match("1A2B3C", "1A2B3CD"));
+        assert!(!tokens_match("1A2B3C", ""));
+    }
+
+    #[test]
+    fn test_request_token_from_header() {
+        let request = request("ws://localhost/", Some("Bearer 1A2B3C"));
+        assert_eq!(request_token(&request).as_deref(), Some("1A2B3C"));
+
+        let request = request("ws://localhost/", Some("Basic 1A2B3C"));
+        assert_eq!(request_token(&request), None);
+    }
+
+    #[test]
+    fn test_request_token_from_query() {
+        let request = request("ws://localhost/?client=1&token=1A2B3C", None);
+        assert_eq!(request_token(&request).as_deref(), Some("1A2B3C"));
+
+        let request = request("ws://localhost/?tokens=1A2B3C", None);
+        assert_eq!(request_token(&request), None);
+
+        let request = request("ws://localhost/", None);
+        assert_eq!(request_token(&request), None);
+    }
+
+    #[test]
+    fn test_request_token_prefers_header() {
+        let request = request("ws://localhost/?token=query", Some("Bearer header"));
+        assert_eq!(request_token(&request).as_deref(), Some("header"));
+    }
+}
@@ -32,6 +32,10 @@ pub use crate::platform::font_list::fallback_font_families;
 use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::{ByteIndex, GlyphData, GlyphId, GlyphStore};
 use crate::text::shaping::ShaperMethods;
+use crate::text::util::{
+    has_emoji_presentation, EMOJI_PRESENTATION_SELECTOR, TEXT_PRESENTATION_SELECTOR,
+    ZERO_WIDTH_JOINER,
+};
 use crate::text::Shaper;
 
 #[macro_export]
@@ -44,6 +48,11 @@ macro_rules! ot_tag {
 pub const GPOS: u32 = ot_tag!('G', 'P', 'O', 'S');
 pub const GSUB: u32 = ot_tag!('G', 'S', 'U', 'B');
 pub const KERN: u32 = ot_tag!('k', 'e', 'r', 'n');
+pub const COLR: u32 = ot_tag!('C', 'O', 'L', 'R');
+pub const CBDT: u32 = ot_tag!('C', 'B', 'D', 'T');
+pub const SBIX: u32 = ot_tag!('s', 'b', 'i', 'x');
+pub const SVG: u32 = ot_tag!('S', 'V', 'G', ' ');
+pub const COLOR_GLYPH_TABLES: [u32; 4] = [COLR, CBDT, SBIX, SVG];
 pub const LAST_RESORT_GLYPH_ADVANCE: FractionalPixel = 10.0;
 
 static TEXT_SHAPING_PERFORMANCE_COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -77,6 +86,13 @@ pub trait FontHandleMethods: Sized {
     fn metrics(&self) -> FontMetrics;
     fn table_for_tag(&self, _: FontTableTag) -> Option<FontTable>;
 
+    /// Whether this font has color glyphs, in any of the formats used by emoji fonts.
+    fn has_color_glyphs(&self) -> bool {
+        COLOR_GLYPH_TABLES
+            .iter()
+            .any(|tag| self.table_for_tag(*tag).is_some())
+    }
+
     /// A unique identifier for the font, allowing comparison.
     fn identifier(&self) -> Atom;
 }
@@ -173,6 +189,8 @@ pub struct Font {
     shape_cache: RefCell<HashMap<ShapeCacheEntry, Arc<GlyphStore>>>,
     glyph_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
     pub font_key: FontInstanceKey,
+    /// Whether the font has color glyphs, which is how an emoji font is recognized.
+    pub has_color_glyphs: bool,
 
     /// If this is a synthesized small caps font, then this font reference is for
     /// the version of the font used to replace lowercase ASCII letters. It's up
@@ -188,6 +206,7 @@ impl Font {
         synthesized_small_caps: Option<FontRef>,
     ) -> Font {
         let metrics = handle.metrics();
+        let has_color_glyphs = handle.has_color_glyphs();
 
         Font {
             handle,
@@ -197,6 +216,7 @@ impl Font {
             shape_cache: RefCell::new(HashMap::new()),
             glyph_advance_cache: RefCell::new(HashMap::new()),
             font_key,
+            has_color_glyphs,
             synthesized_small_caps,
         }
     }
@@ -365,6 +385,25 @@ impl Font {
         self.glyph_index(codepoint).is_some()
     }
 
+    /// Whether this font, used for `previous_codepoint`, should also be used for `codepoint`
+    /// because they are part of the same emoji sequence, which is drawn as a single glyph
+    /// only when the whole sequence is shaped with one font.
+    /// <https://www.unicode.org/reports/tr51/#Emoji_Sequences>
+    pub fn continues_emoji_sequence(
+        &self,
+        codepoint: char,
+        previous_codepoint: Option<char>,
+    ) -> bool {
+        match codepoint {
+            ZERO_WIDTH_JOINER |
+            TEXT_PRESENTATION_SELECTOR |
+            EMOJI_PRESENTATION_SELECTOR |
+            '\u{E0020}'..='\u{E007F}' => true,
+            '\u{20E3}' | '\u{1F3FB}'..='\u{1F3FF}' => self.has_glyph_for(codepoint),
+            _ => previous_codepoint == Some(ZERO_WIDTH_JOINER) && self.has_glyph_for(codepoint),
+        }
+    }
+
     pub fn glyph_h_kerning(&self, first_glyph: GlyphId, second_glyph: GlyphId) -> FractionalPixel {
         self.handle.glyph_h_kerning(first_glyph, second_glyph)
     }
@@ -416,11 +455,13 @@ impl FontGroup {
     /// Finds the first font, or else the first fallback font, which contains a glyph for
     /// `codepoint`. If no such font is found, returns the first available font or fallback font
     /// (which will cause a "glyph not found" character to be rendered). If no font at all can be
-    /// found, returns None.
+    /// found, returns None. When `codepoint`, followed by `next_codepoint`, has emoji
+    /// presentation, a font with a color glyph for it is preferred.
     pub fn find_by_codepoint<S: FontSource>(
         &mut self,
         font_context: &mut FontContext<S>,
         codepoint: char,
+        next_codepoint: Option<char>,
     ) -> Option<FontRef> {
         let should_look_for_small_caps = self.descriptor.variant == font_variant_caps::T::SmallCaps &&
             codepoint.is_ascii_lowercase();
@@ -434,24 +475,52 @@ impl FontGroup {
             Some(font)
         };
 
-        let has_glyph = |font: &FontRef| font.borrow().has_glyph_for(codepoint);
+        if has_emoji_presentation(codepoint, next_codepoint) {
+            let has_color_glyph = |font: &FontRef| {
+                let font = font.borrow();
+                font.has_color_glyphs && font.has_glyph_for(codepoint)
+            };
+            if let Some(font) = self.find_with_fallback(font_context, codepoint, has_color_glyph) {
+                return Some(font);
+            }
+        }
 
-        if let Some(font) = self.find(font_context, has_glyph) {
+        let has_glyph = |font: &FontRef| font.borrow().has_glyph_for(codepoint);
+        if let Some(font) = self.find_with_fallback(font_context, codepoint, has_glyph) {
             return font_or_synthesized_small_caps(font);
         }
 
+        self.first(font_context)
+    }
+
+    /// Find a font which returns true for `predicate`, looking at the fonts of the group, then
+    /// the last fallback font that was used, then the fallback fonts for `codepoint`.
+    fn find_with_fallback<S, P>(
+        &mut self,
+        font_context: &mut FontContext<S>,
+        codepoint: char,
+        predicate: P,
+    ) -> Option<FontRef>
+    where
+        S: FontSource,
+        P: Fn(&FontRef) -> bool + Copy,
+    {
+        if let Some(font) = self.find(font_context, predicate) {
+            return Some(font);
+        }
+
         if let Some(ref last_matching_fallback) = self.last_matching_fallback {
-            if has_glyph(last_matching_fallback) {
-                return font_or_synthesized_small_caps(last_matching_fallback.clone());
+            if predicate(last_matching_fallback) {
+                return Some(last_matching_fallback.clone());
             }
         }
 
-        if let Some(font) = self.find_fallback(font_context, Some(codepoint), has_glyph) {
+        if let Some(font) = self.find_fallback(font_context, Some(codepoint), predicate) {
             self.last_matching_fallback = Some(font.clone());
-            return font_or_synthesized_small_caps(font);
+            return Some(font);
         }
 
-        self.first(font_context)
+        None
     }
 
     /// Find the first available font in the group, or the first available fallback font.
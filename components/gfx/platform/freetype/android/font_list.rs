@@ -8,7 +8,7 @@ use log::warn;
 use ucd::{Codepoint, UnicodeBlock};
 
 use super::xml::{Attribute, Node};
-use crate::text::util::is_cjk;
+use crate::text::util::{is_cjk, is_emoji};
 
 lazy_static::lazy_static! {
     static ref FONT_LIST: FontList = FontList::new();
@@ -467,6 +467,10 @@ pub fn system_default_family(generic_name: &str) -> Option<String> {
 pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec![];
 
+    if codepoint.map_or(false, is_emoji) {
+        families.push("Noto Color Emoji");
+    }
+
     if let Some(block) = codepoint.and_then(|c| c.block()) {
         match block {
             UnicodeBlock::Armenian => {
@@ -5,14 +5,15 @@
 use std::ffi::CString;
 use std::os::raw::{c_char, c_long};
 use std::sync::Arc;
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 
 use app_units::Au;
 use freetype::freetype::{
     FT_Done_Face, FT_F26Dot6, FT_Face, FT_FaceRec, FT_Get_Char_Index, FT_Get_Kerning,
-    FT_Get_Postscript_Name, FT_Get_Sfnt_Table, FT_GlyphSlot, FT_Int32, FT_Kerning_Mode, FT_Library,
-    FT_Load_Glyph, FT_Load_Sfnt_Table, FT_Long, FT_New_Face, FT_New_Memory_Face, FT_Set_Char_Size,
-    FT_Sfnt_Tag, FT_SizeRec, FT_Size_Metrics, FT_UInt, FT_ULong, FT_Vector, FT_STYLE_FLAG_ITALIC,
+    FT_Get_Postscript_Name, FT_Get_Sfnt_Table, FT_GlyphSlot, FT_Int, FT_Int32, FT_Kerning_Mode,
+    FT_Library, FT_Load_Glyph, FT_Load_Sfnt_Table, FT_Long, FT_New_Face, FT_New_Memory_Face,
+    FT_Select_Size, FT_Set_Char_Size, FT_Sfnt_Tag, FT_SizeRec, FT_Size_Metrics, FT_UInt, FT_ULong,
+    FT_Vector, FT_FACE_FLAG_SCALABLE, FT_STYLE_FLAG_ITALIC,
 };
 use freetype::succeeded;
 use freetype::tt_os2::TT_OS2;
@@ -24,8 +25,8 @@ use style::values::computed::font::FontStyle;
 
 use super::c_str_to_string;
 use crate::font::{
-    FontHandleMethods, FontMetrics, FontTableMethods, FontTableTag, FractionalPixel, GPOS, GSUB,
-    KERN,
+    FontHandleMethods, FontMetrics, FontTableMethods, FontTableTag, FractionalPixel,
+    COLOR_GLYPH_TABLES, GPOS, GSUB, KERN,
 };
 use crate::platform::font_context::FontContextHandle;
 use crate::platform::font_template::FontTemplateData;
@@ -80,6 +81,9 @@ pub struct FontHandle {
     // FreeTypeLibraryHandle is not dropped.
     context_handle: FontContextHandle,
     can_do_fast_shaping: bool,
+    /// How much the glyphs of a bitmap font are scaled from the size of the strike that was
+    /// selected to the requested size. This is 1 for scalable fonts.
+    bitmap_scale: f64,
 }
 
 impl Drop for FontHandle {
@@ -151,7 +155,11 @@ impl FontHandleMethods for FontHandle {
             font_data: template,
             context_handle: fctx.clone(),
             can_do_fast_shaping: false,
+            bitmap_scale: 1.0,
         };
+        if let Some(pt_size) = pt_size {
+            handle.bitmap_scale = handle.bitmap_scale(pt_size);
+        }
         // TODO (#11310): Implement basic support for GPOS and GSUB.
         handle.can_do_fast_shaping =
             handle.has_table(KERN) && !handle.has_table(GPOS) && !handle.has_table(GSUB);
@@ -251,7 +259,7 @@ impl FontHandleMethods for FontHandle {
                 &mut delta,
             );
         }
-        fixed_to_float_ft(delta.x as i32)
+        fixed_to_float_ft(delta.x as i32) * self.bitmap_scale
     }
 
     fn can_do_fast_shaping(&self) -> bool {
@@ -269,7 +277,7 @@ impl FontHandleMethods for FontHandle {
                 let advance = (*slot).metrics.horiAdvance;
                 debug!("h_advance for {} is {}", glyph, advance);
                 let advance = advance as i32;
-                Some(fixed_to_float_ft(advance) as FractionalPixel)
+                Some(fixed_to_float_ft(advance) * self.bitmap_scale as FractionalPixel)
             } else {
                 debug!("Unable to load glyph {}. reason: {:?}", glyph, res);
                 None
@@ -361,6 +369,11 @@ impl FontHandleMethods for FontHandle {
         }
     }
 
+    fn has_color_glyphs(&self) -> bool {
+        // Color glyph tables are large, so only check that they exist instead of loading them.
+        COLOR_GLYPH_TABLES.iter().any(|tag| self.has_table(*tag))
+    }
+
     fn identifier(&self) -> Atom {
         self.font_data.identifier.clone()
     }
@@ -371,6 +384,31 @@ impl<'a> FontHandle {
         let char_size = pt_size.to_f64_px() * 64.0 + 0.5;
 
         unsafe {
+            // Bitmap fonts, like most color emoji fonts, only come in the sizes of their
+            // strikes, so use the smallest strike that is at least as large as the requested
+            // size, or else the largest one. The glyphs are scaled from it to the right size.
+            if !Self::is_scalable(face) && (*face).num_fixed_sizes > 0 {
+                let sizes = slice::from_raw_parts(
+                    (*face).available_sizes,
+                    (*face).num_fixed_sizes as usize,
+                );
+                let mut selected = 0;
+                for (index, size) in sizes.iter().enumerate() {
+                    let selected_size = sizes[selected].y_ppem as f64;
+                    let size = size.y_ppem as f64;
+                    if (selected_size < char_size && size > selected_size) ||
+                        (size >= char_size && size < selected_size)
+                    {
+                        selected = index;
+                    }
+                }
+                return if succeeded(FT_Select_Size(face, selected as FT_Int)) {
+                    Ok(())
+                } else {
+                    Err(())
+                };
+            }
+
             let result = FT_Set_Char_Size(face, char_size as FT_F26Dot6, 0, 0, 0);
             if succeeded(result) {
                 Ok(())
@@ -380,6 +418,23 @@ impl<'a> FontHandle {
         }
     }
 
+    fn is_scalable(face: FT_Face) -> bool {
+        unsafe { (*face).face_flags & FT_FACE_FLAG_SCALABLE as c_long != 0 }
+    }
+
+    /// The scale from the strike selected by `set_char_size` to `pt_size`, for bitmap fonts.
+    fn bitmap_scale(&self, pt_size: Au) -> f64 {
+        if Self::is_scalable(self.face) {
+            return 1.0;
+        }
+        let face = self.face_rec_mut();
+        let size: &FT_SizeRec = unsafe { mem::transmute(&(*face.size)) };
+        match size.metrics.y_ppem {
+            0 => 1.0,
+            y_ppem => pt_size.to_f64_px() / y_ppem as f64,
+        }
+    }
+
     fn has_table(&self, tag: FontTableTag) -> bool {
         unsafe {
             succeeded(FT_Load_Sfnt_Table(
@@ -406,7 +461,7 @@ impl<'a> FontHandle {
         let metrics: &FT_Size_Metrics = &(size).metrics;
 
         let em_size = face.units_per_EM as f64;
-        let x_scale = (metrics.x_ppem as f64) / em_size;
+        let x_scale = (metrics.x_ppem as f64) / em_size * self.bitmap_scale;
 
         // If this isn't true then we're scaling one of the axes wrong
         assert_eq!(metrics.x_ppem, metrics.y_ppem);
@@ -15,7 +15,7 @@ use libc::{c_char, c_int};
 use log::debug;
 
 use super::c_str_to_string;
-use crate::text::util::is_cjk;
+use crate::text::util::{is_cjk, is_emoji};
 
 static FC_FAMILY: &[u8] = b"family\0";
 static FC_FILE: &[u8] = b"file\0";
@@ -156,6 +156,10 @@ pub static SANS_SERIF_FONT_FAMILY: &str = "DejaVu Sans";
 pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec!["DejaVu Serif", "FreeSerif", "DejaVu Sans", "FreeSans"];
 
+    if codepoint.map_or(false, is_emoji) {
+        families.push("Noto Color Emoji");
+    }
+
     if let Some(codepoint) = codepoint {
         if is_cjk(codepoint) {
             families.push("TakaoPGothic");
@@ -5,7 +5,7 @@
 use log::debug;
 use ucd::{Codepoint, UnicodeBlock};
 
-use crate::text::util::unicode_plane;
+use crate::text::util::{is_emoji, unicode_plane};
 
 pub fn for_each_available_family<F>(mut callback: F)
 where
@@ -41,6 +41,10 @@ pub fn system_default_family(_generic_name: &str) -> Option<String> {
 pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec!["Lucida Grande"];
 
+    if codepoint.map_or(false, is_emoji) {
+        families.push("Apple Color Emoji");
+    }
+
     if let Some(codepoint) = codepoint {
         match unicode_plane(codepoint) {
             // https://en.wikipedia.org/wiki/Plane_(Unicode)#Basic_Multilingual_Plane
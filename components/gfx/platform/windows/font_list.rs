@@ -11,7 +11,7 @@ use lazy_static::lazy_static;
 use servo_atoms::Atom;
 use ucd::{Codepoint, UnicodeBlock};
 
-use crate::text::util::unicode_plane;
+use crate::text::util::{is_emoji, unicode_plane};
 
 lazy_static! {
     static ref FONT_ATOM_COUNTER: AtomicUsize = AtomicUsize::new(1);
@@ -78,6 +78,10 @@ pub fn font_from_atom(ident: &Atom) -> Font {
 pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec!["Arial"];
 
+    if codepoint.map_or(false, is_emoji) {
+        families.push("Segoe UI Emoji");
+    }
+
     if let Some(codepoint) = codepoint {
         match unicode_plane(codepoint) {
             // https://en.wikipedia.org/wiki/Plane_(Unicode)#Basic_Multilingual_Plane
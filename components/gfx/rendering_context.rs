@@ -217,6 +217,65 @@ impl RenderingContext {
         device.get_proc_address(context, name)
     }
 
+    /// Create a surface for a native widget other than the one of the context, such as the
+    /// widget of a single webview, to draw into with [`Self::draw_to_surface`].
+    pub fn create_widget_surface(&self, native_widget: NativeWidget) -> Result<Surface, Error> {
+        let mut device = self.0.device.borrow_mut();
+        let context = self.0.context.borrow();
+        let surface_type = SurfaceType::Widget { native_widget };
+        device.create_surface(&context, SurfaceAccess::GPUOnly, surface_type)
+    }
+
+    pub fn destroy_surface(&self, mut surface: Surface) -> Result<(), Error> {
+        let device = self.0.device.borrow();
+        let mut context = self.0.context.borrow_mut();
+        device.destroy_surface(&mut context, &mut surface)
+    }
+
+    /// Bind `surface` to the context in place of its own surface while `draw` draws into
+    /// the framebuffer object of `surface`, then present it. The surface is given back even
+    /// if it couldn't be drawn into.
+    pub fn draw_to_surface<F: FnOnce(u32)>(
+        &self,
+        surface: Surface,
+        draw: F,
+    ) -> (Surface, Result<(), Error>) {
+        let device = &mut self.0.device.borrow_mut();
+        let context = &mut self.0.context.borrow_mut();
+        let context_surface = match device.unbind_surface_from_context(context) {
+            Ok(context_surface) => context_surface,
+            Err(error) => return (surface, Err(error)),
+        };
+        let (mut surface, mut result) = match device.bind_surface_to_context(context, surface) {
+            Ok(()) => {
+                let result = device
+                    .make_context_current(context)
+                    .and_then(|()| device.context_surface_info(context))
+                    .map(|info| draw(info.map_or(0, |info| info.framebuffer_object)));
+                let surface = device
+                    .unbind_surface_from_context(context)
+                    .ok()
+                    .flatten()
+                    .expect("The surface was just bound");
+                (surface, result)
+            },
+            Err((error, surface)) => (surface, Err(error)),
+        };
+        if result.is_ok() {
+            result = device.present_surface(context, &mut surface);
+        }
+        if let Some(context_surface) = context_surface {
+            if let Err((error, mut context_surface)) =
+                device.bind_surface_to_context(context, context_surface)
+            {
+                let _ = device.destroy_surface(context, &mut context_surface);
+                return (surface, Err(error));
+            }
+        }
+        let _ = device.make_context_current(context);
+        (surface, result)
+    }
+
     pub fn unbind_native_surface_from_context(&self) -> Result<(), Error> {
         let device = self.0.device.borrow_mut();
         let mut context = self.0.context.borrow_mut();
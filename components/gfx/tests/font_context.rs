@@ -163,7 +163,7 @@ fn test_font_group_find_by_codepoint() {
 
     let font = group
         .borrow_mut()
-        .find_by_codepoint(&mut context, 'a')
+        .find_by_codepoint(&mut context, 'a', None)
         .unwrap();
     assert_eq!(&*font.borrow().identifier(), "csstest-ascii");
     assert_eq!(
@@ -174,7 +174,7 @@ fn test_font_group_find_by_codepoint() {
 
     let font = group
         .borrow_mut()
-        .find_by_codepoint(&mut context, 'a')
+        .find_by_codepoint(&mut context, 'a', None)
         .unwrap();
     assert_eq!(&*font.borrow().identifier(), "csstest-ascii");
     assert_eq!(
@@ -185,7 +185,7 @@ fn test_font_group_find_by_codepoint() {
 
     let font = group
         .borrow_mut()
-        .find_by_codepoint(&mut context, 'á')
+        .find_by_codepoint(&mut context, 'á', None)
         .unwrap();
     assert_eq!(&*font.borrow().identifier(), "csstest-basic-regular");
     assert_eq!(count.get(), 2, "both fonts should now have been loaded");
@@ -203,7 +203,7 @@ fn test_font_fallback() {
 
     let font = group
         .borrow_mut()
-        .find_by_codepoint(&mut context, 'a')
+        .find_by_codepoint(&mut context, 'a', None)
         .unwrap();
     assert_eq!(
         &*font.borrow().identifier(),
@@ -213,7 +213,7 @@ fn test_font_fallback() {
 
     let font = group
         .borrow_mut()
-        .find_by_codepoint(&mut context, 'á')
+        .find_by_codepoint(&mut context, 'á', None)
         .unwrap();
     assert_eq!(
         &*font.borrow().identifier(),
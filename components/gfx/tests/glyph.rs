@@ -0,0 +1,32 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use gfx::text::glyph::{ByteIndex, GlyphData, GlyphStore};
+use range::Range;
+
+fn glyph(id: u32, advance: i32, ligature_start: bool) -> GlyphData {
+    GlyphData::new(id, Au(advance), None, true, ligature_start)
+}
+
+#[test]
+fn test_range_index_of_advance_counts_clusters() {
+    // "a", then a three byte cluster shaped into two glyphs, then "b".
+    let mut store = GlyphStore::new(5, false, false);
+    store.add_glyph_for_byte_index(ByteIndex(0), 'a', &glyph(1, 10, true));
+    store.add_glyphs_for_byte_index(ByteIndex(1), &[glyph(2, 10, true), glyph(3, 10, false)]);
+    store.add_glyph_for_byte_index(ByteIndex(4), 'b', &glyph(4, 10, true));
+    store.finalize_changes();
+
+    let range = Range::new(ByteIndex(0), ByteIndex(5));
+    assert_eq!(store.range_index_of_advance(&range, Au(5), Au(0)).0, 0);
+    assert_eq!(store.range_index_of_advance(&range, Au(15), Au(0)).0, 1);
+    // Within the second glyph of the cluster, which is still the second index.
+    assert_eq!(store.range_index_of_advance(&range, Au(25), Au(0)).0, 1);
+    assert_eq!(store.range_index_of_advance(&range, Au(35), Au(0)).0, 2);
+    assert_eq!(
+        store.range_index_of_advance(&range, Au(100), Au(0)),
+        (3, Au(40))
+    );
+}
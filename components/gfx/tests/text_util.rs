@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use gfx::text::util::{transform_text, CompressionMode};
+use gfx::text::util::{has_emoji_presentation, transform_text, CompressionMode};
 
 #[test]
 fn test_transform_compress_none() {
@@ -104,3 +104,28 @@ fn test_transform_compress_whitespace_newline_no_incoming() {
         assert_eq!(trimmed_str, oracle)
     }
 }
+
+#[test]
+fn test_emoji_presentation() {
+    let test_strs = [
+        ("\u{1F600}", true),
+        ("\u{1F600}\u{FE0E}", false),
+        ("\u{2764}", false),
+        ("\u{2764}\u{FE0F}", true),
+        ("\u{231A}", true),
+        ("1", false),
+        ("1\u{FE0F}", true),
+        ("a\u{FE0F}", false),
+    ];
+
+    for &(test, oracle) in test_strs.iter() {
+        let mut chars = test.chars();
+        let codepoint = chars.next().unwrap();
+        assert_eq!(
+            has_emoji_presentation(codepoint, chars.next()),
+            oracle,
+            "{:?}",
+            test
+        )
+    }
+}
@@ -396,6 +396,17 @@ impl<'a> GlyphInfo<'a> {
 
         store.char_is_word_separator(entry_i)
     }
+
+    /// Whether this is the first glyph of a cluster. When the shaper turns a cluster of
+    /// characters, such as an Indic conjunct or an Arabic letter and its marks, into
+    /// several glyphs, they are all stored at the byte index of the first character.
+    #[inline]
+    pub fn is_cluster_start(self) -> bool {
+        match self {
+            GlyphInfo::Simple(_, _) => true,
+            GlyphInfo::Detail(_, _, detail_j) => detail_j == 0,
+        }
+    }
 }
 
 /// Stores the glyph data belonging to a text run.
@@ -593,9 +604,10 @@ impl<'a> GlyphStore {
         }
     }
 
-    // Scan the glyphs for a given range until we reach a given advance. Returns the index
-    // and advance of the glyph in the range at the given advance, if reached. Otherwise, returns the
-    // the number of glyphs and the advance for the given range.
+    // Scan the clusters for a given range until we reach a given advance. Returns the index
+    // and advance of the cluster in the range at the given advance, if reached. Otherwise, returns
+    // the number of clusters and the advance for the given range. The glyphs of a cluster are
+    // measured together, so that the index never falls within a cluster.
     #[inline]
     pub fn range_index_of_advance(
         &self,
@@ -605,12 +617,16 @@ impl<'a> GlyphStore {
     ) -> (usize, Au) {
         let mut index = 0;
         let mut current_advance = Au(0);
-        for glyph in self.iter_glyphs_for_byte_range(range) {
+        let mut glyphs = self.iter_glyphs_for_byte_range(range).peekable();
+        while let Some(glyph) = glyphs.next() {
             if glyph.char_is_word_separator() {
                 current_advance += glyph.advance() + extra_word_spacing
             } else {
                 current_advance += glyph.advance()
             }
+            while let Some(glyph) = glyphs.next_if(|glyph| !glyph.is_cluster_start()) {
+                current_advance += glyph.advance()
+            }
             if current_advance > advance {
                 break;
             }
@@ -552,11 +552,20 @@ impl Shaper {
                 // collect all glyphs to be assigned to the first character.
                 let mut datas = vec![];
 
+                // A cluster is spaced like a single character, so letter and word spacing
+                // are added once, after its last glyph, rather than between the glyphs of
+                // an Indic conjunct or an Arabic letter and its marks.
+                let character = text[byte_range.clone()].chars().next().unwrap();
                 for glyph_i in glyph_span.clone() {
                     let shape = glyph_data.entry_for_glyph(glyph_i, &mut y_pos);
+                    let advance = if glyph_i + 1 == glyph_span.end {
+                        self.advance_for_shaped_glyph(shape.advance, character, options)
+                    } else {
+                        shape.advance
+                    };
                     datas.push(GlyphData::new(
                         shape.codepoint,
-                        shape.advance,
+                        advance,
                         shape.offset,
                         true, // treat as cluster start
                         glyph_i > glyph_span.start,
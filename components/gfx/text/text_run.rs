@@ -13,6 +13,7 @@ use range::Range;
 use serde::{Deserialize, Serialize};
 use style::str::char_is_whitespace;
 use unicode_bidi as bidi;
+use unicode_segmentation::UnicodeSegmentation;
 use webrender_api::FontInstanceKey;
 use xi_unicode::LineBreakLeafIter;
 
@@ -155,9 +156,16 @@ impl<'a> Iterator for CharacterSliceIterator<'a> {
         let glyph_run = self.glyph_run?;
 
         debug_assert!(!self.range.is_empty());
+        // Slices are whole grapheme clusters, so that breaking a word anywhere never
+        // separates an Indic conjunct or a letter and its combining marks. A slice can't
+        // go past the end of its glyph run.
         let byte_start = self.range.begin();
-        let byte_len = match self.text[byte_start.to_usize()..].chars().next() {
-            Some(ch) => ByteIndex(ch.len_utf8() as isize),
+        let byte_end = self.range.end().min(glyph_run.range.end());
+        let byte_len = match self.text[byte_start.to_usize()..byte_end.to_usize()]
+            .graphemes(true)
+            .next()
+        {
+            Some(grapheme) => ByteIndex(grapheme.len() as isize),
             None => unreachable!(), // XXX refactor?
         };
 
@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cmp::Ordering;
+
 use ucd::{Codepoint, UnicodeBlock};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -151,3 +153,150 @@ pub fn is_cjk(codepoint: char) -> bool {
     // https://en.wikipedia.org/wiki/Plane_(Unicode)#Supplementary_Ideographic_Plane
     unicode_plane(codepoint) == 2
 }
+
+/// The characters with the `Emoji_Presentation` property, which are drawn as color emoji
+/// unless followed by the text variation selector.
+/// <https://www.unicode.org/reports/tr51/#Emoji_Properties>
+const EMOJI_PRESENTATION: &[(u32, u32)] = &[
+    (0x231A, 0x231B),
+    (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267F, 0x267F),
+    (0x2693, 0x2693),
+    (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26CE, 0x26CE),
+    (0x26D4, 0x26D4),
+    (0x26EA, 0x26EA),
+    (0x26F2, 0x26F3),
+    (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA),
+    (0x26FD, 0x26FD),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2728, 0x2728),
+    (0x274C, 0x274C),
+    (0x274E, 0x274E),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55),
+    (0x1F004, 0x1F004),
+    (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A),
+    (0x1F1E6, 0x1F1FF),
+    (0x1F201, 0x1F201),
+    (0x1F21A, 0x1F21A),
+    (0x1F22F, 0x1F22F),
+    (0x1F232, 0x1F236),
+    (0x1F238, 0x1F23A),
+    (0x1F250, 0x1F251),
+    (0x1F300, 0x1F320),
+    (0x1F32D, 0x1F335),
+    (0x1F337, 0x1F37C),
+    (0x1F37E, 0x1F393),
+    (0x1F3A0, 0x1F3CA),
+    (0x1F3CF, 0x1F3D3),
+    (0x1F3E0, 0x1F3F0),
+    (0x1F3F4, 0x1F3F4),
+    (0x1F3F8, 0x1F43E),
+    (0x1F440, 0x1F440),
+    (0x1F442, 0x1F4FC),
+    (0x1F4FF, 0x1F53D),
+    (0x1F54B, 0x1F54E),
+    (0x1F550, 0x1F567),
+    (0x1F57A, 0x1F57A),
+    (0x1F595, 0x1F596),
+    (0x1F5A4, 0x1F5A4),
+    (0x1F5FB, 0x1F64F),
+    (0x1F680, 0x1F6C5),
+    (0x1F6CC, 0x1F6CC),
+    (0x1F6D0, 0x1F6D2),
+    (0x1F6D5, 0x1F6D7),
+    (0x1F6DC, 0x1F6DF),
+    (0x1F6EB, 0x1F6EC),
+    (0x1F6F4, 0x1F6FC),
+    (0x1F7E0, 0x1F7EB),
+    (0x1F7F0, 0x1F7F0),
+    (0x1F90C, 0x1F93A),
+    (0x1F93C, 0x1F945),
+    (0x1F947, 0x1F9FF),
+    (0x1FA70, 0x1FA7C),
+    (0x1FA80, 0x1FA88),
+    (0x1FA90, 0x1FABD),
+    (0x1FABF, 0x1FAC5),
+    (0x1FACE, 0x1FADB),
+    (0x1FAE0, 0x1FAE8),
+    (0x1FAF0, 0x1FAF8),
+];
+
+pub const ZERO_WIDTH_JOINER: char = '\u{200D}';
+pub const TEXT_PRESENTATION_SELECTOR: char = '\u{FE0E}';
+pub const EMOJI_PRESENTATION_SELECTOR: char = '\u{FE0F}';
+
+/// Whether `codepoint` may be drawn as an emoji, which is the case for the characters that
+/// only are emoji, and for the symbols that some emoji fonts have a colored form of. This
+/// roughly follows the `Emoji` property, except for the digits and `#` and `*`, which are
+/// only emoji as part of a keycap sequence.
+pub fn is_emoji(codepoint: char) -> bool {
+    matches!(
+        codepoint,
+        '\u{A9}' |
+            '\u{AE}' |
+            '\u{203C}' |
+            '\u{2049}' |
+            '\u{2122}' |
+            '\u{2139}' |
+            '\u{2194}'..='\u{21AA}' |
+            '\u{2300}'..='\u{23FF}' |
+            '\u{24C2}' |
+            '\u{25AA}'..='\u{25FE}' |
+            '\u{2600}'..='\u{27BF}' |
+            '\u{2934}'..='\u{2935}' |
+            '\u{2B05}'..='\u{2B55}' |
+            '\u{3030}' |
+            '\u{303D}' |
+            '\u{3297}' |
+            '\u{3299}' |
+            '\u{1F000}'..='\u{1FAFF}'
+    ) || has_default_emoji_presentation(codepoint)
+}
+
+fn has_default_emoji_presentation(codepoint: char) -> bool {
+    let codepoint = codepoint as u32;
+    EMOJI_PRESENTATION
+        .binary_search_by(|&(start, end)| {
+            if end < codepoint {
+                Ordering::Less
+            } else if start > codepoint {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Whether `codepoint`, followed by `next_codepoint`, should be drawn as a color emoji rather
+/// than as text, according to its default presentation and any variation selector after it.
+/// <https://www.unicode.org/reports/tr51/#Presentation_Style>
+pub fn has_emoji_presentation(codepoint: char, next_codepoint: Option<char>) -> bool {
+    match next_codepoint {
+        Some(TEXT_PRESENTATION_SELECTOR) => false,
+        Some(EMOJI_PRESENTATION_SELECTOR) => {
+            is_emoji(codepoint) || codepoint.is_ascii_digit() || matches!(codepoint, '#' | '*')
+        },
+        _ => has_default_emoji_presentation(codepoint),
+    }
+}
@@ -207,7 +207,7 @@ impl TextRunScanner {
                     .unwrap_or_else(|| {
                         let space_width = font_group
                             .borrow_mut()
-                            .find_by_codepoint(font_context, ' ')
+                            .find_by_codepoint(font_context, ' ', None)
                             .and_then(|font| {
                                 let font = font.borrow();
                                 font.glyph_index(' ')
@@ -250,9 +250,25 @@ impl TextRunScanner {
                 let (mut start_position, mut end_position) = (0, 0);
                 for (byte_index, character) in text.char_indices() {
                     if !character.is_control() {
-                        let font = font_group
-                            .borrow_mut()
-                            .find_by_codepoint(font_context, character);
+                        // The characters of an emoji sequence keep the font of the emoji it
+                        // starts with, so that they are shaped into a single glyph.
+                        let previous_character = text[..byte_index].chars().next_back();
+                        let next_character =
+                            text[byte_index + character.len_utf8()..].chars().next();
+                        let font = match run_info.font {
+                            Some(ref font)
+                                if font
+                                    .borrow()
+                                    .continues_emoji_sequence(character, previous_character) =>
+                            {
+                                Some(font.clone())
+                            },
+                            _ => font_group.borrow_mut().find_by_codepoint(
+                                font_context,
+                                character,
+                                next_character,
+                            ),
+                        };
 
                         let bidi_level = match bidi_levels {
                             Some(levels) => levels[*paragraph_bytes_processed],
@@ -25,6 +25,10 @@ pub struct LayoutContext<'a> {
     pub use_rayon: bool,
     pub origin: ImmutableOrigin,
 
+    /// Whether the backgrounds of elements are painted, which they aren't when a document
+    /// is printed without them.
+    pub paint_backgrounds: bool,
+
     /// Bits shared by the layout and style system.
     pub style_context: SharedStyleContext<'a>,
 
@@ -583,6 +583,9 @@ impl<'a> BuilderForBoxFragment<'a> {
         builder: &mut DisplayListBuilder,
         painter: &BackgroundPainter,
     ) {
+        if !builder.context.paint_backgrounds {
+            return;
+        }
         let b = painter.style.get_background();
         let background_color = painter.style.resolve_color(b.background_color.clone());
         if background_color.alpha > 0.0 {
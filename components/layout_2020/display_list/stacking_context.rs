@@ -520,6 +520,9 @@ impl StackingContext {
         fragment_tree: &crate::FragmentTree,
         containing_block_rect: &PhysicalRect<Length>,
     ) {
+        if !builder.context.paint_backgrounds {
+            return;
+        }
         let style = if let Some(style) = &fragment_tree.canvas_background.style {
             style
         } else {
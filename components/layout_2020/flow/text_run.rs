@@ -318,10 +318,17 @@ impl TextRun {
             };
 
         let mut next_byte_index = 0;
-        let text = char_iterator
-            .map(|character| {
+        let mut previous_character = None;
+        let mut char_iterator = char_iterator.peekable();
+        let chars_and_next_chars = std::iter::from_fn(|| {
+            let character = char_iterator.next()?;
+            Some((character, char_iterator.peek().copied()))
+        });
+        let text = chars_and_next_chars
+            .map(|(character, next_character)| {
                 let current_byte_index = next_byte_index;
                 next_byte_index += character.len_utf8();
+                let previous_character = previous_character.replace(character);
 
                 *last_inline_box_ended_with_white_space = character.is_whitespace();
                 *on_word_boundary = *last_inline_box_ended_with_white_space;
@@ -337,10 +344,22 @@ impl TextRun {
                     return character;
                 }
 
-                let font = match font_group
-                    .borrow_mut()
-                    .find_by_codepoint(font_context, character)
-                {
+                // The characters of an emoji sequence keep the font of the emoji it starts
+                // with, so that they are shaped into a single glyph.
+                if let Some((_, font)) = current.as_ref() {
+                    if font
+                        .borrow()
+                        .continues_emoji_sequence(character, previous_character)
+                    {
+                        return character;
+                    }
+                }
+
+                let font = match font_group.borrow_mut().find_by_codepoint(
+                    font_context,
+                    character,
+                    next_character,
+                ) {
                     Some(font) => font,
                     None => return character,
                 };
@@ -959,8 +959,12 @@ impl LayoutThread {
         };
 
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
+        let media_type = match data.print_layout {
+            Some(_) => MediaType::print(),
+            None => MediaType::screen(),
+        };
         let device = Device::new(
-            MediaType::screen(),
+            media_type,
             self.stylist.quirks_mode(),
             initial_viewport,
             device_pixel_ratio,
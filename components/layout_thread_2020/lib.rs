@@ -396,6 +396,7 @@ impl LayoutThread {
         animations: &DocumentAnimationSet,
         stylesheets_changed: bool,
         use_rayon: bool,
+        paint_backgrounds: bool,
     ) -> LayoutContext<'a> {
         let traversal_flags = match stylesheets_changed {
             true => TraversalFlags::ForCSSRuleChanges,
@@ -421,6 +422,7 @@ impl LayoutThread {
             webrender_image_cache: self.webrender_image_cache.clone(),
             pending_images: Mutex::new(vec![]),
             use_rayon,
+            paint_backgrounds,
         }
     }
 
@@ -649,8 +651,12 @@ impl LayoutThread {
             ua_or_user: &ua_or_user_guard,
         };
 
+        let media_type = match data.print_layout {
+            Some(_) => MediaType::print(),
+            None => MediaType::screen(),
+        };
         let device = Device::new(
-            MediaType::screen(),
+            media_type,
             self.stylist.quirks_mode(),
             initial_viewport,
             device_pixel_ratio,
@@ -744,6 +750,8 @@ impl LayoutThread {
             &data.animations,
             data.stylesheets_changed,
             rayon_pool.is_some(),
+            data.print_layout
+                .map_or(true, |print_layout| print_layout.backgrounds),
         );
 
         let dirty_root = unsafe {
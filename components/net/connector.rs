@@ -6,6 +6,7 @@ use std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
 use std::sync::{Arc, Mutex};
 
+use embedder_traits::CertificateErrorKind;
 use futures::task::{Context, Poll};
 use futures::Future;
 use http::uri::{Authority, Uri as Destination};
@@ -16,7 +17,9 @@ use hyper::{Body, Client};
 use hyper_rustls::HttpsConnector as HyperRustlsHttpsConnector;
 use log::warn;
 use rustls::client::WebPkiVerifier;
-use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use rustls::{
+    Certificate, CertificateError, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName,
+};
 
 use crate::hosts::replace_host;
 use crate::http_loader::HANDLE;
@@ -76,20 +79,33 @@ impl Service<Destination> for ServoHttpConnector {
 pub type Connector = HyperRustlsHttpsConnector<ServoHttpConnector>;
 pub type TlsConfig = ClientConfig;
 
+/// The certificates that a server presented, which failed to verify.
+#[derive(Clone, Debug)]
+pub struct CertificateVerificationFailure {
+    /// The certificate of the server, followed by its intermediates.
+    pub chain: Vec<Certificate>,
+    pub kind: CertificateErrorKind,
+    pub message: String,
+}
+
 #[derive(Clone, Debug, Default)]
 struct CertificateErrorOverrideManagerInternal {
     /// A mapping of certificates and their hosts, which have seen certificate errors.
     /// This is used to later create an override in this [CertificateErrorOverrideManager].
-    certificates_failing_to_verify: HashMap<ServerName, Certificate>,
+    certificates_failing_to_verify: HashMap<ServerName, CertificateVerificationFailure>,
     /// A list of certificates that should be accepted despite encountering verification
     /// errors.
     overrides: Vec<Certificate>,
+    /// Whether the embedder accepted certificates with verification errors that hosts
+    /// presented, which is remembered for the session.
+    decisions: HashMap<(ServerName, Certificate), bool>,
 }
 
 /// This data structure is used to track certificate verification errors and overrides.
 /// It tracks:
 ///  - A list of [Certificate]s with verification errors mapped by their [ServerName]
 ///  - A list of [Certificate]s for which to ignore verification errors.
+///  - The decisions of the embedder about the [Certificate]s of a [ServerName].
 #[derive(Clone, Debug, Default)]
 pub struct CertificateErrorOverrideManager(Arc<Mutex<CertificateErrorOverrideManagerInternal>>);
 
@@ -110,20 +126,67 @@ impl CertificateErrorOverrideManager {
     pub(crate) fn remove_certificate_failing_verification(
         &self,
         host: &str,
-    ) -> Option<Certificate> {
-        let server_name = match ServerName::try_from(host) {
-            Ok(name) => name,
-            Err(error) => {
-                warn!("Could not convert host string into RustTLS ServerName: {error:?}");
-                return None;
-            },
-        };
+    ) -> Option<CertificateVerificationFailure> {
+        let server_name = server_name(host)?;
         self.0
             .lock()
             .unwrap()
             .certificates_failing_to_verify
             .remove(&server_name)
     }
+
+    /// The certificate with verification errors that a host presented, if any, which is
+    /// kept until it is removed.
+    pub(crate) fn certificate_failing_verification(&self, host: &str) -> Option<Certificate> {
+        let server_name = server_name(host)?;
+        self.0
+            .lock()
+            .unwrap()
+            .certificates_failing_to_verify
+            .get(&server_name)
+            .map(|failure| failure.chain[0].clone())
+    }
+
+    /// Whether the embedder accepted a certificate with verification errors that a host
+    /// presented, or `None` if it wasn't asked about it yet.
+    pub(crate) fn decision(&self, host: &str, certificate: &Certificate) -> Option<bool> {
+        let key = (server_name(host)?, certificate.clone());
+        self.0.lock().unwrap().decisions.get(&key).copied()
+    }
+
+    /// Remember whether the embedder accepted a certificate that a host presented.
+    pub(crate) fn set_decision(&self, host: &str, certificate: &Certificate, accepted: bool) {
+        if let Some(server_name) = server_name(host) {
+            let key = (server_name, certificate.clone());
+            self.0.lock().unwrap().decisions.insert(key, accepted);
+        }
+    }
+}
+
+fn server_name(host: &str) -> Option<ServerName> {
+    match ServerName::try_from(host) {
+        Ok(name) => Some(name),
+        Err(error) => {
+            warn!("Could not convert host string into RustTLS ServerName: {error:?}");
+            None
+        },
+    }
+}
+
+fn certificate_error_kind(error: &rustls::Error) -> CertificateErrorKind {
+    let error = match error {
+        rustls::Error::InvalidCertificate(error) => error,
+        _ => return CertificateErrorKind::Other,
+    };
+    match error {
+        CertificateError::Expired => CertificateErrorKind::Expired,
+        CertificateError::NotValidYet => CertificateErrorKind::NotValidYet,
+        CertificateError::Revoked => CertificateErrorKind::Revoked,
+        CertificateError::UnknownIssuer => CertificateErrorKind::UnknownIssuer,
+        CertificateError::BadSignature => CertificateErrorKind::BadSignature,
+        CertificateError::NotValidForName => CertificateErrorKind::NameMismatch,
+        _ => CertificateErrorKind::Other,
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -232,17 +295,28 @@ impl rustls::client::ServerCertVerifier for CertificateVerificationOverrideVerif
         }
 
         // If there's an override for this certificate, just accept it.
-        for cert_with_exception in &*self.override_manager.0.lock().unwrap().overrides {
+        let mut internal = self.override_manager.0.lock().unwrap();
+        for cert_with_exception in &internal.overrides {
             if *end_entity == *cert_with_exception {
                 return Ok(rustls::client::ServerCertVerified::assertion());
             }
         }
-        self.override_manager
-            .0
-            .lock()
-            .unwrap()
+        let key = (server_name.clone(), end_entity.clone());
+        if internal.decisions.get(&key) == Some(&true) {
+            return Ok(rustls::client::ServerCertVerified::assertion());
+        }
+        let chain = std::iter::once(end_entity)
+            .chain(intermediates)
+            .cloned()
+            .collect();
+        let failure = CertificateVerificationFailure {
+            chain,
+            kind: certificate_error_kind(&error),
+            message: error.to_string(),
+        };
+        internal
             .certificates_failing_to_verify
-            .insert(server_name.clone(), end_entity.clone());
+            .insert(server_name.clone(), failure);
         Err(error)
     }
 }
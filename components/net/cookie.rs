@@ -7,13 +7,16 @@
 
 use std::borrow::ToOwned;
 use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::UNIX_EPOCH;
 
+use cookie_rs::SameSite;
+use embedder_traits::{CookieSameSite, StoredCookie};
 use hyper_serde::Serde;
 use net_traits::pub_domains::is_pub_domain;
 use net_traits::CookieSource;
 use serde::{Deserialize, Serialize};
 use servo_url::ServoUrl;
-use time::{at, now, Duration, Tm};
+use time::{at, at_utc, now, Duration, Timespec, Tm};
 
 /// A stored cookie that wraps the definition in cookie-rs. This is used to implement
 /// various behaviours defined in the spec that rely on an associated request URL,
@@ -141,6 +144,68 @@ impl Cookie {
         })
     }
 
+    /// A cookie that the embedder sets, which is stored as it is rather than checked
+    /// against the URL of a request like those of documents are.
+    pub fn from_stored(stored: StoredCookie) -> Cookie {
+        let mut cookie = cookie_rs::Cookie::new(stored.name, stored.value);
+        cookie.set_domain(stored.domain);
+        cookie.set_path(stored.path);
+        cookie.set_secure(stored.secure);
+        cookie.set_http_only(stored.http_only);
+        if let Some(same_site) = stored.same_site {
+            cookie.set_same_site(match same_site {
+                CookieSameSite::Strict => SameSite::Strict,
+                CookieSameSite::Lax => SameSite::Lax,
+                CookieSameSite::None => SameSite::None,
+            });
+        }
+        let expiry_time = stored.expires.map(|expires| {
+            let since_epoch = expires.duration_since(UNIX_EPOCH).unwrap_or_default();
+            at_utc(Timespec::new(
+                since_epoch.as_secs() as i64,
+                since_epoch.subsec_nanos() as i32,
+            ))
+        });
+        if let Some(expiry_time) = expiry_time {
+            cookie.set_expires(expiry_time);
+        }
+
+        Cookie {
+            cookie,
+            host_only: stored.host_only,
+            persistent: expiry_time.is_some(),
+            creation_time: now(),
+            last_access: now(),
+            expiry_time: expiry_time.map(Serde),
+        }
+    }
+
+    /// The cookie as the embedder sees it.
+    pub fn to_stored(&self) -> StoredCookie {
+        let expires = self.expiry_time.as_ref().map(|expiry_time| {
+            let time = expiry_time.to_timespec();
+            match u64::try_from(time.sec) {
+                Ok(sec) => UNIX_EPOCH + std::time::Duration::new(sec, time.nsec as u32),
+                Err(_) => UNIX_EPOCH,
+            }
+        });
+        StoredCookie {
+            name: self.cookie.name().to_owned(),
+            value: self.cookie.value().to_owned(),
+            domain: self.cookie.domain().unwrap_or("").to_owned(),
+            host_only: self.host_only,
+            path: self.cookie.path().unwrap_or("/").to_owned(),
+            expires,
+            secure: self.cookie.secure().unwrap_or(false),
+            http_only: self.cookie.http_only().unwrap_or(false),
+            same_site: self.cookie.same_site().map(|same_site| match same_site {
+                SameSite::Strict => CookieSameSite::Strict,
+                SameSite::Lax => CookieSameSite::Lax,
+                SameSite::None => CookieSameSite::None,
+            }),
+        }
+    }
+
     pub fn touch(&mut self) {
         self.last_access = now();
     }
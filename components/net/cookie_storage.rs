@@ -9,12 +9,14 @@ use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
-use log::{debug, info};
+use embedder_traits::{CookieChange, StoredCookie};
+use ipc_channel::ipc::IpcSender;
+use log::{debug, info, warn};
 use net_traits::pub_domains::reg_suffix;
 use net_traits::CookieSource;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
-use time::{self, Tm};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use time::{self, Timespec, Tm};
 
 use crate::cookie::Cookie;
 
@@ -23,6 +25,9 @@ pub struct CookieStorage {
     version: u32,
     cookies_map: HashMap<String, Vec<Cookie>>,
     max_per_host: usize,
+    /// Where the changes to the cookies are sent, while the embedder observes them.
+    #[serde(skip)]
+    observer: Option<IpcSender<CookieChange>>,
 }
 
 impl CookieStorage {
@@ -31,9 +36,14 @@ impl CookieStorage {
             version: 1,
             cookies_map: HashMap::new(),
             max_per_host: max_cookies,
+            observer: None,
         }
     }
 
+    pub fn set_observer(&mut self, observer: Option<IpcSender<CookieChange>>) {
+        self.observer = observer;
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.3
     pub fn remove(
         &mut self,
@@ -88,14 +98,95 @@ impl CookieStorage {
             Ok(None)
         }
     }
+
+    /// Remove the cookies of the registrable domains of `origins`, or every cookie if no
+    /// origins are given. Cookies created before `since` are kept.
+    /// <https://w3c.github.io/webappsec-clear-site-data/#clear-cookies>
+    pub fn clear_data(&mut self, origins: Option<&[ImmutableOrigin]>, since: Option<Timespec>) {
+        let observer = &self.observer;
+        let keep = |cookie: &Cookie| {
+            let created_since =
+                since.map_or(true, |since| cookie.creation_time.to_timespec() >= since);
+            if created_since {
+                notify(observer, || CookieChange::Removed(cookie.to_stored()));
+            }
+            !created_since
+        };
+        match origins {
+            Some(origins) => {
+                for origin in origins {
+                    let host = match origin {
+                        ImmutableOrigin::Tuple(_, host, _) => host.to_string(),
+                        ImmutableOrigin::Opaque(_) => continue,
+                    };
+                    if let Some(cookies) = self.cookies_map.get_mut(&reg_host(&host)) {
+                        cookies.retain(keep);
+                    }
+                }
+            },
+            None => {
+                for cookies in self.cookies_map.values_mut() {
+                    cookies.retain(keep);
+                }
+            },
+        }
+    }
+
     pub fn clear_storage(&mut self, url: &ServoUrl) {
         let domain = reg_host(url.host_str().unwrap_or(""));
         let cookies = self.cookies_map.entry(domain).or_default();
         for cookie in cookies.iter_mut() {
+            notify(&self.observer, || CookieChange::Removed(cookie.to_stored()));
             cookie.set_expiry_time_negative();
         }
     }
 
+    /// The cookies that are sent to `url`, in the order they are sent in, or every cookie
+    /// if no URL is given.
+    pub fn stored_cookies(&self, url: Option<&ServoUrl>) -> Vec<StoredCookie> {
+        let mut cookies: Vec<&Cookie> = match url {
+            Some(url) => self
+                .cookies_map
+                .get(&reg_host(url.host_str().unwrap_or("")))
+                .into_iter()
+                .flatten()
+                .filter(|cookie| cookie.appropriate_for_url(url, CookieSource::HTTP))
+                .collect(),
+            None => self.cookies_map.values().flatten().collect(),
+        };
+        cookies.retain(|cookie| !is_cookie_expired(cookie));
+        cookies.sort_by(|a, b| CookieStorage::cookie_comparator(a, b));
+        cookies.into_iter().map(Cookie::to_stored).collect()
+    }
+
+    /// Store a cookie that the embedder sets, replacing the one with the same name, domain
+    /// and path.
+    pub fn set_stored_cookie(&mut self, stored: StoredCookie) {
+        let url = match ServoUrl::parse(&format!("https://{}{}", stored.domain, stored.path)) {
+            Ok(url) => url,
+            Err(e) => return warn!("Invalid domain or path of cookie ({:?}).", e),
+        };
+        self.push(Cookie::from_stored(stored), &url, CookieSource::HTTP);
+    }
+
+    /// Remove the cookie with the same name, domain and path as the given one, if there
+    /// is one.
+    pub fn delete_stored_cookie(&mut self, stored: &StoredCookie) {
+        let cookies = match self.cookies_map.get_mut(&reg_host(&stored.domain)) {
+            Some(cookies) => cookies,
+            None => return,
+        };
+        let position = cookies.iter().position(|c| {
+            c.cookie.name() == stored.name &&
+                c.cookie.domain() == Some(&*stored.domain) &&
+                c.cookie.path() == Some(&*stored.path)
+        });
+        if let Some(index) = position {
+            let cookie = cookies.remove(index);
+            notify(&self.observer, || CookieChange::Removed(cookie.to_stored()));
+        }
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.3
     pub fn push(&mut self, mut cookie: Cookie, url: &ServoUrl, source: CookieSource) {
         // https://www.ietf.org/id/draft-ietf-httpbis-cookie-alone-01.txt Step 1
@@ -131,6 +222,7 @@ impl CookieStorage {
                 return;
             }
         }
+        notify(&self.observer, || CookieChange::Set(cookie.to_stored()));
         cookies.push(cookie);
     }
 
@@ -223,6 +315,14 @@ impl CookieStorage {
     }
 }
 
+fn notify(observer: &Option<IpcSender<CookieChange>>, change: impl FnOnce() -> CookieChange) {
+    if let Some(observer) = observer {
+        if let Err(e) = observer.send(change()) {
+            warn!("Failed to send cookie change ({:?}).", e);
+        }
+    }
+}
+
 fn reg_host(url: &str) -> String {
     reg_suffix(url).to_lowercase()
 }
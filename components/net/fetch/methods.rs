@@ -4,7 +4,7 @@
 
 use std::borrow::Cow;
 use std::fs::File;
-use std::io::{self, BufReader, Seek, SeekFrom};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
 use std::ops::Bound;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
@@ -15,6 +15,7 @@ use base64::Engine as _;
 use content_security_policy as csp;
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
+use embedder_traits::EmbedderProxy;
 use headers::{AccessControlExposeHeaders, ContentType, HeaderMapExt, Range};
 use http::header::{self, HeaderMap, HeaderName};
 use http::{Method, StatusCode};
@@ -24,11 +25,13 @@ use log::{debug, warn};
 use mime::{self, Mime};
 use net_traits::blob_url_store::{parse_blob_url, BlobURLStoreError};
 use net_traits::filemanager_thread::{FileTokenCheck, RelativePos};
+use net_traits::protocols::{ProtocolCors, ProtocolHandler, ProtocolRegistry, ProtocolResponse};
 use net_traits::request::{
     is_cors_safelisted_method, is_cors_safelisted_request_header, BodyChunkRequest,
-    BodyChunkResponse, CredentialsMode, Destination, Origin, Referrer, Request, RequestMode,
-    ResponseTainting, Window,
+    BodyChunkResponse, CredentialsMode, Destination, Origin, ParserMetadata, Referrer, Request,
+    RequestMode, ResponseTainting, Window,
 };
+use net_traits::request_interceptor::{InterceptedRequest, Interception, RequestInterceptors};
 use net_traits::response::{Response, ResponseBody, ResponseType};
 use net_traits::{
     FetchTaskTarget, NetworkError, ReferrerPolicy, ResourceAttribute, ResourceFetchTiming,
@@ -47,8 +50,8 @@ use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::headers::determine_nosniff;
 use crate::filemanager_thread::{FileManager, FILE_CHUNK_SIZE};
 use crate::http_loader::{
-    determine_requests_referrer, http_fetch, set_default_accept, set_default_accept_language,
-    HttpState,
+    cors_check, determine_requests_referrer, http_fetch, http_redirect_fetch, set_accept_language,
+    set_default_accept, HttpState, HANDLE,
 };
 use crate::subresource_integrity::is_response_integrity_valid;
 
@@ -69,7 +72,10 @@ pub enum Data {
 pub struct FetchContext {
     pub state: Arc<HttpState>,
     pub user_agent: Cow<'static, str>,
+    pub protocols: Arc<ProtocolRegistry>,
+    pub request_interceptors: RequestInterceptors,
     pub devtools_chan: Option<Arc<Mutex<Sender<DevtoolsControlMsg>>>>,
+    pub embedder_proxy: EmbedderProxy,
     pub filemanager: Arc<Mutex<FileManager>>,
     pub file_token: FileTokenCheck,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
@@ -147,7 +153,16 @@ pub async fn fetch_with_cors_cache(
     set_default_accept(request.destination, &mut request.headers);
 
     // Step 4.
-    set_default_accept_language(&mut request.headers);
+    let languages = request.pipeline_id.and_then(|pipeline_id| {
+        context
+            .state
+            .pipeline_languages
+            .read()
+            .unwrap()
+            .get(&pipeline_id)
+            .cloned()
+    });
+    set_accept_language(&mut request.headers, &languages.unwrap_or_default());
 
     // Step 5.
     // TODO: figure out what a Priority object is.
@@ -176,9 +191,13 @@ pub fn should_request_be_blocked_by_csp(request: &Request) -> csp::CheckResult {
         redirect_count: request.redirect_count,
         destination: request.destination,
         initiator: csp::Initiator::None,
-        nonce: String::new(),
+        nonce: request.cryptographic_nonce_metadata.clone(),
         integrity_metadata: request.integrity_metadata.clone(),
-        parser_metadata: csp::ParserMetadata::None,
+        parser_metadata: match request.parser_metadata {
+            ParserMetadata::ParserInserted => csp::ParserMetadata::ParserInserted,
+            ParserMetadata::NotParserInserted => csp::ParserMetadata::NotParserInserted,
+            ParserMetadata::Default => csp::ParserMetadata::None,
+        },
     };
     // TODO: Instead of ignoring violations, report them.
     request
@@ -280,6 +299,11 @@ pub async fn main_fetch(
         .unwrap()
         .apply_hsts_rules(request.current_url_mut());
 
+    // Servo-specific: the interceptors of the embedder see the request before it's loaded.
+    if response.is_none() {
+        response = intercept_request(request, cache, cors_flag, target, done_chan, context).await;
+    }
+
     // Step 11.
     // Not applicable: see fetch_async.
 
@@ -297,7 +321,8 @@ pub async fn main_fetch(
 
             if (same_origin && !cors_flag) ||
                 current_url.scheme() == "data" ||
-                current_url.scheme() == "chrome"
+                current_url.scheme() == "chrome" ||
+                context.protocols.cors(current_url.scheme()) == ProtocolCors::Allowed
             {
                 // Substep 1.
                 request.response_tainting = ResponseTainting::Basic;
@@ -312,6 +337,16 @@ pub async fn main_fetch(
 
                 // Substep 2.
                 scheme_fetch(request, cache, target, done_chan, context).await
+            } else if context.protocols.cors(current_url.scheme()) == ProtocolCors::Checked {
+                // Servo-specific: custom schemes can't be preflighted, but their responses
+                // are checked like HTTP ones.
+                request.response_tainting = ResponseTainting::CorsTainting;
+                let response = scheme_fetch(request, cache, target, done_chan, context).await;
+                if !response.is_network_error() && cors_check(request, &response).is_err() {
+                    Response::network_error(NetworkError::Internal("CORS check failed".into()))
+                } else {
+                    response
+                }
             } else if !matches!(current_url.scheme(), "http" | "https") {
                 Response::network_error(NetworkError::Internal("Non-http scheme".into()))
             } else if request.use_cors_preflight ||
@@ -683,6 +718,71 @@ fn handle_allowcert_request(request: &mut Request, context: &FetchContext) -> io
     Ok(())
 }
 
+/// Let the interceptors of the embedder that see a request decide what happens to it, in
+/// order. This returns the response of the first one that doesn't let it continue.
+async fn intercept_request(
+    request: &mut Request,
+    cache: &mut CorsCache,
+    cors_flag: bool,
+    target: Target<'_>,
+    done_chan: &mut DoneChannel,
+    context: &FetchContext,
+) -> Option<Response> {
+    if context.request_interceptors.is_empty() {
+        return None;
+    }
+    let webview_id = request.pipeline_id.and_then(|pipeline_id| {
+        context
+            .request_interceptors
+            .webview_of_pipeline(pipeline_id)
+    });
+    let url = request.current_url();
+    for interceptor in context.request_interceptors.matching(webview_id) {
+        let interception = interceptor.intercept(InterceptedRequest {
+            webview_id,
+            url: &url,
+            method: &request.method,
+            destination: request.destination,
+            headers: &mut request.headers,
+        });
+        match interception {
+            Interception::Continue => {},
+            Interception::Block => {
+                return Some(Response::network_error(NetworkError::Internal(
+                    "Blocked by an interceptor".into(),
+                )));
+            },
+            Interception::Fulfill(protocol_response) => {
+                return Some(response_from_protocol_response(
+                    protocol_response,
+                    request,
+                    done_chan,
+                    context,
+                ));
+            },
+            Interception::Redirect(location_url) => {
+                let mut response =
+                    Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+                response.status = Some((
+                    StatusCode::TEMPORARY_REDIRECT,
+                    "Temporary Redirect".to_string(),
+                ));
+                response.raw_status = Some((
+                    StatusCode::TEMPORARY_REDIRECT.as_u16(),
+                    b"Temporary Redirect".to_vec(),
+                ));
+                response.location_url = Some(Ok(location_url));
+                let response = http_redirect_fetch(
+                    request, cache, response, cors_flag, target, done_chan, context,
+                )
+                .await;
+                return Some(response);
+            },
+        }
+    }
+    None
+}
+
 /// [Scheme fetch](https://fetch.spec.whatwg.org#scheme-fetch)
 async fn scheme_fetch(
     request: &mut Request,
@@ -867,8 +967,103 @@ async fn scheme_fetch(
             Response::network_error(NetworkError::Internal("Unexpected scheme".into()))
         },
 
-        _ => Response::network_error(NetworkError::Internal("Unexpected scheme".into())),
+        scheme => match context.protocols.get(scheme) {
+            Some(handler) => protocol_fetch(handler, request, done_chan, context),
+            None => Response::network_error(NetworkError::Internal("Unexpected scheme".into())),
+        },
+    }
+}
+
+/// Load the URL of a custom scheme with the handler that the embedder registered for it.
+fn protocol_fetch(
+    handler: &dyn ProtocolHandler,
+    request: &Request,
+    done_chan: &mut DoneChannel,
+    context: &FetchContext,
+) -> Response {
+    match handler.load(request) {
+        Ok(protocol_response) => {
+            response_from_protocol_response(protocol_response, request, done_chan, context)
+        },
+        Err(error) => Response::network_error(NetworkError::Internal(format!(
+            "Loading {} URL failed ({error})",
+            request.current_url().scheme()
+        ))),
+    }
+}
+
+/// The response to a request that a handler or an interceptor of the embedder answered.
+fn response_from_protocol_response(
+    protocol_response: ProtocolResponse,
+    request: &Request,
+    done_chan: &mut DoneChannel,
+    context: &FetchContext,
+) -> Response {
+    let url = request.current_url();
+    let mut response = Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+    let status = protocol_response.status;
+    let reason = status.canonical_reason().unwrap_or("");
+    response.status = Some((status, reason.to_string()));
+    response.raw_status = Some((status.as_u16(), reason.as_bytes().to_vec()));
+    response.headers = protocol_response.headers;
+
+    // The body is read on a blocking thread, as the handler may produce it slowly.
+    let (done_sender, done_receiver) = unbounded_channel();
+    *done_chan = Some((done_sender.clone(), done_receiver));
+    *response.body.lock().unwrap() = ResponseBody::Receiving(vec![]);
+
+    let res_body = response.body.clone();
+    let cancellation_listener = context.cancellation_listener.clone();
+    let reader = protocol_response.body;
+    HANDLE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .spawn_blocking(move || {
+            read_protocol_body(reader, res_body, done_sender, cancellation_listener)
+        });
+
+    response
+}
+
+fn read_protocol_body(
+    mut reader: Box<dyn Read + Send>,
+    res_body: ServoArc<Mutex<ResponseBody>>,
+    done_sender: TokioSender<Data>,
+    cancellation_listener: Arc<Mutex<CancellationListener>>,
+) {
+    let mut buffer = vec![0; FILE_CHUNK_SIZE];
+    loop {
+        if cancellation_listener.lock().unwrap().cancelled() {
+            *res_body.lock().unwrap() = ResponseBody::Done(vec![]);
+            let _ = done_sender.send(Data::Cancelled);
+            return;
+        }
+        let length = match reader.read(&mut buffer) {
+            Ok(length) => length,
+            Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => {
+                warn!("Could not read the body of a custom scheme response: {error}");
+                0
+            },
+        };
+        if length == 0 {
+            break;
+        }
+        let chunk = &buffer[..length];
+        if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
+            body.extend_from_slice(chunk);
+        }
+        let _ = done_sender.send(Data::Payload(chunk.to_vec()));
     }
+    let mut body = res_body.lock().unwrap();
+    let completed_body = match *body {
+        ResponseBody::Receiving(ref mut body) => mem::take(body),
+        _ => vec![],
+    };
+    *body = ResponseBody::Done(completed_body);
+    let _ = done_sender.send(Data::Done);
 }
 
 fn is_null_body_status(status: &Option<(StatusCode, String)>) -> bool {
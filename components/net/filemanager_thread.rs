@@ -3,19 +3,20 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::ops::Index;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock, Weak};
 
-use embedder_traits::{EmbedderMsg, EmbedderProxy, FilterPattern};
+use embedder_traits::{EmbedderMsg, EmbedderProxy, FilePickerRequest, FilterPattern};
 use headers::{ContentLength, ContentType, HeaderMap, HeaderMapExt};
 use http::header::{self, HeaderValue};
 use ipc_channel::ipc::{self, IpcSender};
 use log::warn;
 use mime::{self, Mime};
+use msg::constellation_msg::WebViewId;
 use net_traits::blob_url_store::{BlobBuf, BlobURLStoreError};
 use net_traits::filemanager_thread::{
     FileManagerResult, FileManagerThreadError, FileManagerThreadMsg, FileOrigin, FileTokenCheck,
@@ -153,14 +154,21 @@ impl FileManager {
     /// Message handler
     pub fn handle(&self, msg: FileManagerThreadMsg) {
         match msg {
-            FileManagerThreadMsg::SelectFile(filter, sender, origin, opt_test_path) => {
+            FileManagerThreadMsg::SelectFile(webview_id, filter, sender, origin, opt_test_path) => {
                 let store = self.store.clone();
                 let embedder = self.embedder_proxy.clone();
                 self.thread_pool
                     .upgrade()
                     .map(|pool| {
                         pool.spawn(move || {
-                            store.select_file(filter, sender, origin, opt_test_path, embedder);
+                            store.select_file(
+                                webview_id,
+                                filter,
+                                sender,
+                                origin,
+                                opt_test_path,
+                                embedder,
+                            );
                         });
                     })
                     .unwrap_or_else(|| {
@@ -169,14 +177,27 @@ impl FileManager {
                         );
                     });
             },
-            FileManagerThreadMsg::SelectFiles(filter, sender, origin, opt_test_paths) => {
+            FileManagerThreadMsg::SelectFiles(
+                webview_id,
+                request,
+                sender,
+                origin,
+                opt_test_paths,
+            ) => {
                 let store = self.store.clone();
                 let embedder = self.embedder_proxy.clone();
                 self.thread_pool
                     .upgrade()
                     .map(|pool| {
                         pool.spawn(move || {
-                            store.select_files(filter, sender, origin, opt_test_paths, embedder);
+                            store.select_files(
+                                webview_id,
+                                request,
+                                sender,
+                                origin,
+                                opt_test_paths,
+                                embedder,
+                            );
                         });
                     })
                     .unwrap_or_else(|| {
@@ -534,14 +555,14 @@ impl FileManagerStore {
 
     fn query_files_from_embedder(
         &self,
-        patterns: Vec<FilterPattern>,
-        multiple_files: bool,
+        webview_id: WebViewId,
+        request: FilePickerRequest,
         embedder_proxy: EmbedderProxy,
     ) -> Option<Vec<String>> {
         let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
         let msg = (
-            None,
-            EmbedderMsg::SelectFiles(patterns, multiple_files, ipc_sender),
+            Some(webview_id),
+            EmbedderMsg::SelectFiles(request, ipc_sender),
         );
 
         embedder_proxy.send(msg);
@@ -556,6 +577,7 @@ impl FileManagerStore {
 
     fn select_file(
         &self,
+        webview_id: WebViewId,
         patterns: Vec<FilterPattern>,
         sender: IpcSender<FileManagerResult<SelectedFile>>,
         origin: FileOrigin,
@@ -568,7 +590,12 @@ impl FileManagerStore {
         let opt_s = if pref!(dom.testing.html_input_element.select_files.enabled) {
             opt_test_path
         } else {
-            self.query_files_from_embedder(patterns, false, embedder_proxy)
+            let request = FilePickerRequest {
+                filter_patterns: patterns,
+                multiple: false,
+                directory: false,
+            };
+            self.query_files_from_embedder(webview_id, request, embedder_proxy)
                 .and_then(|mut x| x.pop())
         };
 
@@ -586,19 +613,21 @@ impl FileManagerStore {
 
     fn select_files(
         &self,
-        patterns: Vec<FilterPattern>,
+        webview_id: WebViewId,
+        request: FilePickerRequest,
         sender: IpcSender<FileManagerResult<Vec<SelectedFile>>>,
         origin: FileOrigin,
         opt_test_paths: Option<Vec<String>>,
         embedder_proxy: EmbedderProxy,
     ) {
+        let directory = request.directory;
         // Check if the select_files preference is enabled
         // to ensure process-level security against compromised script;
         // Then try applying opt_test_paths directly for testing convenience
         let opt_v = if pref!(dom.testing.html_input_element.select_files.enabled) {
             opt_test_paths
         } else {
-            self.query_files_from_embedder(patterns, true, embedder_proxy)
+            self.query_files_from_embedder(webview_id, request, embedder_proxy)
         };
 
         match opt_v {
@@ -612,13 +641,16 @@ impl FileManagerStore {
                 let mut replies = vec![];
 
                 for path in selected_paths {
-                    match self.create_entry(path, &origin) {
-                        Ok(triple) => replies.push(triple),
-                        Err(e) => {
-                            let _ = sender.send(Err(e));
-                            return;
-                        },
+                    let result = if directory {
+                        self.create_directory_entries(path, &origin, &mut replies)
+                    } else {
+                        self.create_entry(path, &origin)
+                            .map(|triple| replies.push(triple))
                     };
+                    if let Err(e) = result {
+                        let _ = sender.send(Err(e));
+                        return;
+                    }
                 }
 
                 let _ = sender.send(Ok(replies));
@@ -685,9 +717,56 @@ impl FileManagerStore {
             modified: modified_epoch,
             size: file_size,
             type_string,
+            relative_path: String::new(),
         })
     }
 
+    /// Create an entry for every file in a directory and its subdirectories, in the order
+    /// of their paths.
+    fn create_directory_entries(
+        &self,
+        directory: &Path,
+        origin: &str,
+        files: &mut Vec<SelectedFile>,
+    ) -> Result<(), FileManagerThreadError> {
+        use net_traits::filemanager_thread::FileManagerThreadError::FileSystemError;
+
+        let mut directories = vec![directory.to_path_buf()];
+        let mut paths = vec![];
+        while let Some(directory) = directories.pop() {
+            let entries = fs::read_dir(&directory).map_err(|e| FileSystemError(e.to_string()))?;
+            for entry in entries {
+                let entry = entry.map_err(|e| FileSystemError(e.to_string()))?;
+                // Symbolic links aren't followed, so that a link to a parent directory
+                // can't make this loop forever.
+                let file_type = entry
+                    .file_type()
+                    .map_err(|e| FileSystemError(e.to_string()))?;
+                if file_type.is_dir() {
+                    directories.push(entry.path());
+                } else if file_type.is_file() {
+                    paths.push(entry.path());
+                }
+            }
+        }
+        paths.sort();
+
+        // Relative paths start with the name of the picked directory.
+        let root = directory.parent().unwrap_or(directory);
+        for path in paths {
+            let mut file = self.create_entry(&path, origin)?;
+            if let Ok(relative_path) = path.strip_prefix(root) {
+                file.relative_path = relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+            }
+            files.push(file);
+        }
+        Ok(())
+    }
+
     fn get_blob_buf(
         &self,
         sender: &IpcSender<FileManagerResult<ReadFileProgress>>,
@@ -135,6 +135,20 @@ impl HstsList {
         }
     }
 
+    /// Remove the entries that responses added for `hosts`, or for every host, at `since`
+    /// or later. The entries of the preload list, which have no timestamp, are kept.
+    pub fn clear_data(&mut self, hosts: Option<&[String]>, since: Option<u64>) {
+        let cleared = |entry: &HstsEntry| {
+            entry.timestamp.map_or(false, |timestamp| {
+                since.map_or(true, |since| timestamp >= since)
+            }) && hosts.map_or(true, |hosts| hosts.contains(&entry.host))
+        };
+        for entries in self.entries_map.values_mut() {
+            entries.retain(|entry| !cleared(entry));
+        }
+        self.entries_map.retain(|_, entries| !entries.is_empty());
+    }
+
     /// Step 2.9 of <https://fetch.spec.whatwg.org/#concept-main-fetch>.
     pub fn apply_hsts_rules(&self, url: &mut ServoUrl) {
         if url.scheme() != "http" && url.scheme() != "ws" {
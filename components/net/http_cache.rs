@@ -29,7 +29,7 @@ use net_traits::response::{HttpsState, Response, ResponseBody};
 use net_traits::{FetchMetadata, Metadata, ResourceFetchTiming};
 use servo_arc::Arc;
 use servo_config::pref;
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use time::{Duration, Timespec, Tm};
 use tokio::sync::mpsc::{unbounded_channel as unbounded, UnboundedSender as TokioSender};
 
@@ -899,4 +899,10 @@ impl HttpCache {
     pub fn clear(&mut self) {
         self.entries.clear();
     }
+
+    /// Remove the cached responses whose URL belongs to one of `origins`.
+    pub fn clear_origins(&mut self, origins: &[ImmutableOrigin]) {
+        self.entries
+            .retain(|key, _| !origins.contains(&key.url.origin()));
+    }
 }
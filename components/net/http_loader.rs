@@ -14,13 +14,17 @@ use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
     HttpResponse as DevtoolsHttpResponse, NetworkEvent,
 };
+use embedder_traits::{
+    AuthenticationRequest, AuthenticationResponse, CertificateError, EmbedderMsg,
+};
 use futures::{future, StreamExt, TryFutureExt, TryStreamExt};
 use headers::authorization::Basic;
 use headers::{
     AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
     AccessControlAllowOrigin, AccessControlMaxAge, AccessControlRequestHeaders,
     AccessControlRequestMethod, Authorization, CacheControl, ContentLength, HeaderMapExt,
-    IfModifiedSince, LastModified, Origin as HyperOrigin, Pragma, Referer, UserAgent,
+    IfModifiedSince, LastModified, Origin as HyperOrigin, Pragma, ProxyAuthorization, Referer,
+    UserAgent,
 };
 use http::header::{
     self, HeaderValue, ACCEPT, CONTENT_ENCODING, CONTENT_LANGUAGE, CONTENT_LOCATION, CONTENT_TYPE,
@@ -33,7 +37,7 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use lazy_static::lazy_static;
 use log::{debug, error, info, log_enabled, warn};
-use msg::constellation_msg::{HistoryStateId, PipelineId};
+use msg::constellation_msg::{HistoryStateId, PipelineId, WebViewId};
 use net_traits::pub_domains::reg_suffix;
 use net_traits::quality::{quality_to_value, Quality, QualityItem};
 use net_traits::request::Origin::Origin as SpecificOrigin;
@@ -68,7 +72,8 @@ use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{main_fetch, Data, DoneChannel, FetchContext, Target};
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
-use crate::resource_thread::AuthCache;
+use crate::permission_store::PermissionStore;
+use crate::resource_thread::{AuthCache, AuthCacheEntry};
 
 lazy_static! {
     pub static ref HANDLE: Mutex<Option<Runtime>> = Mutex::new(Some(Runtime::new().unwrap()));
@@ -95,8 +100,12 @@ pub struct HttpState {
     pub http_cache_state: Mutex<HashMap<CacheKey, Arc<(Mutex<HttpCacheEntryState>, Condvar)>>>,
     pub auth_cache: RwLock<AuthCache>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    pub permission_store: RwLock<PermissionStore>,
     pub client: Client<Connector, Body>,
     pub override_manager: CertificateErrorOverrideManager,
+    /// The languages that the embedder chose for the webviews of pipelines, most
+    /// preferred first, which replace the default Accept-Language header of their requests.
+    pub pipeline_languages: RwLock<HashMap<PipelineId, Vec<String>>>,
 }
 
 impl Default for HttpState {
@@ -107,6 +116,7 @@ impl Default for HttpState {
             cookie_jar: RwLock::new(CookieStorage::new(150)),
             auth_cache: RwLock::new(AuthCache::default()),
             history_states: RwLock::new(HashMap::new()),
+            permission_store: RwLock::new(PermissionStore::default()),
             http_cache: RwLock::new(HttpCache::default()),
             http_cache_state: Mutex::new(HashMap::new()),
             client: create_http_client(create_tls_config(
@@ -115,6 +125,7 @@ impl Default for HttpState {
                 override_manager.clone(),
             )),
             override_manager,
+            pipeline_languages: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -184,6 +195,31 @@ pub fn set_default_accept_language(headers: &mut HeaderMap) {
     );
 }
 
+/// Set an Accept-Language header preferring `languages` in order, or the default one if
+/// there are none.
+pub fn set_accept_language(headers: &mut HeaderMap, languages: &[String]) {
+    if headers.contains_key(header::ACCEPT_LANGUAGE) {
+        return;
+    }
+
+    let value = languages
+        .iter()
+        .enumerate()
+        .map(|(index, language)| match index {
+            0 => language.clone(),
+            // Each language is preferred a little less than the previous one.
+            _ => format!("{}; q={:.1}", language, (10 - index.min(9)) as f32 / 10.),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    match HeaderValue::from_str(&value) {
+        Ok(value) if !languages.is_empty() => {
+            headers.insert(header::ACCEPT_LANGUAGE, value);
+        },
+        _ => set_default_accept_language(headers),
+    }
+}
+
 /// <https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-state-no-referrer-when-downgrade>
 fn no_referrer_when_downgrade(referrer_url: ServoUrl, current_url: ServoUrl) -> Option<ServoUrl> {
     // Step 1
@@ -496,10 +532,13 @@ async fn obtain_response(
 
         let devtools_bytes = StdArc::new(Mutex::new(vec![]));
 
+        // The credentials of the URL are only ever sent in the `Authorization` header.
+        let mut url_without_credentials = url.clone().into_url();
+        let _ = url_without_credentials.set_username("");
+        let _ = url_without_credentials.set_password(None);
+
         // https://url.spec.whatwg.org/#percent-encoded-bytes
-        let encoded_url = url
-            .clone()
-            .into_url()
+        let encoded_url = url_without_credentials
             .as_ref()
             .replace('|', "%7C")
             .replace('{', "%7B")
@@ -694,7 +733,7 @@ async fn obtain_response(
             .map_err(move |error| {
                 NetworkError::from_hyper_error(
                     &error,
-                    override_manager.remove_certificate_failing_verification(host.as_str()),
+                    override_manager.certificate_failing_verification(host.as_str()),
                 )
             })
             .await
@@ -1273,7 +1312,18 @@ async fn http_network_or_cache_fetch(
     }
 
     // Step 5.18
-    // TODO If there’s a proxy-authentication entry, use it as appropriate.
+    if !http_request
+        .headers
+        .contains_key(header::PROXY_AUTHORIZATION)
+    {
+        let proxy_entry = context.state.auth_cache.read().unwrap().proxy_entry.clone();
+        if let Some(entry) = proxy_entry {
+            let basic = Authorization::basic(&entry.user_name, &entry.password);
+            http_request
+                .headers
+                .typed_insert(ProxyAuthorization(basic.0));
+        }
+    }
 
     // If the cache is not ready to construct a response, wait.
     //
@@ -1445,8 +1495,12 @@ async fn http_network_or_cache_fetch(
     // More Step 7
     if response.is_none() {
         // Substep 2
-        let forward_response =
+        let mut forward_response =
             http_network_fetch(http_request, credentials_flag, done_chan, context).await;
+        if accept_certificate_error(http_request, &forward_response, context).await {
+            forward_response =
+                http_network_fetch(http_request, credentials_flag, done_chan, context).await;
+        }
         // Substep 3
         if let Some((200..=399, _)) = forward_response.raw_status {
             if !http_request.method.is_safe() {
@@ -1576,34 +1630,56 @@ async fn http_network_or_cache_fetch(
         }
 
         // Substep 3
-        if !http_request.use_url_credentials || authentication_fetch_flag {
-            // FIXME: Prompt the user for username and password from the window
-
-            // Wrong, but will have to do until we are able to prompt the user
-            // otherwise this creates an infinite loop
-            // We basically pretend that the user declined to enter credentials
+        // The authentication fetch returns the responses that refuse its credentials, for
+        // the embedder to be asked for others here.
+        if authentication_fetch_flag {
             return response;
         }
 
-        // Make sure this is set to None,
-        // since we're about to start a new `http_network_or_cache_fetch`.
-        *done_chan = None;
+        // The credentials of the URL are tried first, when there are some.
+        let mut retry_count = 0;
+        let mut use_url_credentials =
+            http_request.use_url_credentials && has_credentials(&http_request.current_url());
+        loop {
+            // Whatever credentials were sent were refused.
+            http_request.headers.remove(header::AUTHORIZATION);
+            if !use_url_credentials {
+                let credentials =
+                    match request_credentials(http_request, false, retry_count, &response, context)
+                        .await
+                    {
+                        Some(credentials) => credentials,
+                        None => return response,
+                    };
+                let basic = Authorization::basic(&credentials.username, &credentials.password);
+                http_request.headers.typed_insert(basic);
+            }
 
-        // Substep 4
-        response = http_network_or_cache_fetch(
-            http_request,
-            true, /* authentication flag */
-            cors_flag,
-            done_chan,
-            context,
-        )
-        .await;
+            // Make sure this is set to None,
+            // since we're about to start a new `http_network_or_cache_fetch`.
+            *done_chan = None;
+
+            // Substep 4
+            response = http_network_or_cache_fetch(
+                http_request,
+                true, /* authentication flag */
+                cors_flag,
+                done_chan,
+                context,
+            )
+            .await;
+            if !matches!(response.status, Some((StatusCode::UNAUTHORIZED, _))) {
+                break;
+            }
+            use_url_credentials = false;
+            retry_count += 1;
+        }
     }
 
     // Step 11
     if let Some((StatusCode::PROXY_AUTHENTICATION_REQUIRED, _)) = response.status.as_ref() {
         // Step 1
-        if request_has_no_window {
+        if webview_of_request(http_request, context).is_none() {
             return Response::network_error(NetworkError::Internal(
                 "Can't find Window object".into(),
             ));
@@ -1611,23 +1687,53 @@ async fn http_network_or_cache_fetch(
 
         // Step 2
         // TODO: Spec says requires testing on Proxy-Authenticate headers
+        if authentication_fetch_flag {
+            return response;
+        }
 
-        // Step 3
-        // FIXME: Prompt the user for proxy authentication credentials
-
-        // Wrong, but will have to do until we are able to prompt the user
-        // otherwise this creates an infinite loop
-        // We basically pretend that the user declined to enter credentials
-        return response;
-
-        // Step 4
-        // return http_network_or_cache_fetch(request, authentication_fetch_flag,
-        //                                    cors_flag, done_chan, context);
+        let mut retry_count = 0;
+        loop {
+            // Step 3
+            let credentials = match request_credentials(
+                http_request,
+                true,
+                retry_count,
+                &response,
+                context,
+            )
+            .await
+            {
+                Some(credentials) => credentials,
+                None => return response,
+            };
+            let basic = Authorization::basic(&credentials.username, &credentials.password);
+            http_request
+                .headers
+                .typed_insert(ProxyAuthorization(basic.0));
+
+            // Step 4
+            *done_chan = None;
+            response = http_network_or_cache_fetch(
+                http_request,
+                true, /* authentication flag */
+                cors_flag,
+                done_chan,
+                context,
+            )
+            .await;
+            if !matches!(
+                response.status,
+                Some((StatusCode::PROXY_AUTHENTICATION_REQUIRED, _))
+            ) {
+                break;
+            }
+            retry_count += 1;
+        }
     }
 
     // Step 12
     if authentication_fetch_flag {
-        // TODO Create the authentication entry for request and the given realm
+        create_authentication_entries(http_request, &response, context);
     }
 
     // Step 13
@@ -1655,6 +1761,167 @@ impl Drop for ResponseEndTimer {
     }
 }
 
+/// The webview of the document that a request comes from, if it comes from one.
+fn webview_of_request(request: &Request, context: &FetchContext) -> Option<WebViewId> {
+    request.pipeline_id.and_then(|pipeline_id| {
+        context
+            .request_interceptors
+            .webview_of_pipeline(pipeline_id)
+    })
+}
+
+/// The realm of the `WWW-Authenticate` or `Proxy-Authenticate` challenge of a response.
+fn authentication_realm(response: &Response, for_proxy: bool) -> Option<String> {
+    let name = if for_proxy {
+        header::PROXY_AUTHENTICATE
+    } else {
+        header::WWW_AUTHENTICATE
+    };
+    let challenge = response.headers.get(name)?.to_str().ok()?;
+    let start = challenge.to_ascii_lowercase().find("realm=")? + "realm=".len();
+    let value = &challenge[start..];
+    let realm = match value.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .next()?,
+    };
+    Some(realm.to_owned())
+}
+
+/// Ask the embedder for credentials to answer the challenge of a response of a server, or
+/// of the proxy, which `None` is returned for when the request doesn't come from a webview.
+async fn request_credentials(
+    request: &Request,
+    for_proxy: bool,
+    retry_count: u32,
+    response: &Response,
+    context: &FetchContext,
+) -> Option<AuthenticationResponse> {
+    let webview_id = webview_of_request(request, context)?;
+    let url = request.current_url();
+    let authentication_request = AuthenticationRequest {
+        origin: url.origin(),
+        url,
+        realm: authentication_realm(response, for_proxy),
+        for_proxy,
+        retry_count,
+    };
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = EmbedderMsg::RequestAuthentication(authentication_request, sender);
+    context.embedder_proxy.send((Some(webview_id), msg));
+    // The embedder may ask the user, so the answer is waited for on a blocking thread.
+    let answer = HANDLE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .spawn_blocking(move || receiver.recv());
+    match answer.await {
+        Ok(Ok(credentials)) => credentials,
+        _ => {
+            warn!("Failed to receive the credentials of an authentication request.");
+            None
+        },
+    }
+}
+
+/// Remember the credentials that an authentication fetch sent, for the origin of the URL
+/// or the proxy, when the response didn't refuse them.
+/// <https://fetch.spec.whatwg.org/#create-an-authentication-entry>
+fn create_authentication_entries(request: &Request, response: &Response, context: &FetchContext) {
+    let entry = |basic: &Basic| AuthCacheEntry {
+        user_name: basic.username().to_owned(),
+        password: basic.password().to_owned(),
+    };
+    let mut auth_cache = context.state.auth_cache.write().unwrap();
+    if !matches!(response.status, Some((StatusCode::UNAUTHORIZED, _))) {
+        if let Some(Authorization(basic)) = request.headers.typed_get::<Authorization<Basic>>() {
+            let origin = request.current_url().origin().ascii_serialization();
+            auth_cache.entries.insert(origin, entry(&basic));
+        }
+    }
+    if !matches!(
+        response.status,
+        Some((StatusCode::PROXY_AUTHENTICATION_REQUIRED, _))
+    ) {
+        if let Some(ProxyAuthorization(basic)) =
+            request.headers.typed_get::<ProxyAuthorization<Basic>>()
+        {
+            auth_cache.proxy_entry = Some(entry(&basic));
+        }
+    }
+}
+
+/// Ask the embedder whether to accept the certificate of the server of a document that
+/// failed to verify, unless it was asked about it already, and return whether it did, in
+/// which case the document is fetched again.
+async fn accept_certificate_error(
+    request: &Request,
+    response: &Response,
+    context: &FetchContext,
+) -> bool {
+    if !matches!(
+        response.get_network_error(),
+        Some(NetworkError::SslValidation(..))
+    ) {
+        return false;
+    }
+    let url = request.current_url();
+    let host = match url.host_str() {
+        Some(host) => host.to_owned(),
+        None => return false,
+    };
+    let override_manager = &context.state.override_manager;
+    let failure = match override_manager.remove_certificate_failing_verification(&host) {
+        Some(failure) => failure,
+        None => return false,
+    };
+    // Only documents are fetched again, which don't have a body to send again.
+    if request.destination != Destination::Document || request.body.is_some() {
+        return false;
+    }
+    let webview_id = match webview_of_request(request, context) {
+        Some(webview_id) => webview_id,
+        None => return false,
+    };
+    let certificate = failure.chain[0].clone();
+    if override_manager.decision(&host, &certificate).is_some() {
+        return false;
+    }
+
+    let error = CertificateError {
+        url: url.clone(),
+        origin: url.origin(),
+        kind: failure.kind,
+        message: failure.message,
+        chain: failure
+            .chain
+            .into_iter()
+            .map(|certificate| certificate.0)
+            .collect(),
+    };
+    let (sender, receiver) = ipc::channel().unwrap();
+    let msg = EmbedderMsg::CertificateError(error, sender);
+    context.embedder_proxy.send((Some(webview_id), msg));
+    // The embedder may ask the user, so the answer is waited for on a blocking thread.
+    let answer = HANDLE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .spawn_blocking(move || receiver.recv());
+    let accepted = match answer.await {
+        Ok(Ok(accepted)) => accepted,
+        _ => {
+            warn!("Failed to receive the answer to a certificate error.");
+            false
+        },
+    };
+    override_manager.set_decision(&host, &certificate, accepted);
+    accepted
+}
+
 /// [HTTP network fetch](https://fetch.spec.whatwg.org/#http-network-fetch)
 async fn http_network_fetch(
     request: &mut Request,
@@ -2121,7 +2388,7 @@ async fn cors_preflight_fetch(
 }
 
 /// [CORS check](https://fetch.spec.whatwg.org#concept-cors-check)
-fn cors_check(request: &Request, response: &Response) -> Result<(), ()> {
+pub fn cors_check(request: &Request, response: &Response) -> Result<(), ()> {
     // Step 1
     let origin = response.headers.typed_get::<AccessControlAllowOrigin>();
 
@@ -399,6 +399,29 @@ impl ImageCacheStore {
             )
     }
 
+    /// Remove the completed loads whose image is only held by the cache, along with
+    /// those that failed, and release the WebRender images of the removed ones.
+    fn purge_unused_images(&mut self) {
+        let mut unused_keys = vec![];
+        self.completed_loads
+            .retain(|_, completed_load| match completed_load.image_response {
+                ImageResponse::Loaded(ref image, _) => {
+                    if Arc::strong_count(image) > 1 {
+                        return true;
+                    }
+                    unused_keys.extend(image.id);
+                    false
+                },
+                ImageResponse::PlaceholderLoaded(..) |
+                ImageResponse::MetadataLoaded(_) |
+                ImageResponse::None => false,
+            });
+        debug!("Purging {} unused images", unused_keys.len());
+        if !unused_keys.is_empty() {
+            self.webrender_api.delete_images(unused_keys);
+        }
+    }
+
     /// Handle a message from one of the decoder worker threads or from a sync
     /// decoding operation.
     fn handle_decoder(&mut self, msg: DecoderMsg) {
@@ -657,6 +680,10 @@ impl ImageCache for ImageCacheImpl {
             },
         }
     }
+
+    fn purge_unused_images(&self) {
+        self.store.lock().unwrap().purge_unused_images();
+    }
 }
 
 impl ImageCacheImpl {
@@ -16,6 +16,8 @@ pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
 pub mod mime_classifier;
+pub mod permission_store;
+pub mod quota_manager;
 pub mod resource_thread;
 mod storage_thread;
 pub mod subresource_integrity;
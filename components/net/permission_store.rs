@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The decisions made about permission-gated features, per origin.
+//! <https://w3c.github.io/permissions/#permission-store>
+
+use std::time::SystemTime;
+
+use embedder_traits::{PermissionDecision, PermissionName, PermissionSetting};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PermissionStore {
+    decisions: Vec<PermissionDecision>,
+}
+
+impl PermissionStore {
+    /// The setting stored for `name` on `origin`, if an unexpired one exists.
+    pub fn get(
+        &mut self,
+        origin: &ImmutableOrigin,
+        name: PermissionName,
+    ) -> Option<PermissionSetting> {
+        self.remove_expired();
+        self.decisions
+            .iter()
+            .find(|decision| decision.origin == *origin && decision.name == name)
+            .map(|decision| decision.setting)
+    }
+
+    /// Store `decision`, replacing any previous one for the same origin and
+    /// permission. Decisions about opaque origins are not kept, as they can
+    /// never be queried again.
+    pub fn set(&mut self, decision: PermissionDecision) {
+        if !decision.origin.is_tuple() {
+            debug!("Ignoring permission decision for an opaque origin");
+            return;
+        }
+        self.decisions.retain(|existing| {
+            existing.origin != decision.origin || existing.name != decision.name
+        });
+        self.decisions.push(decision);
+    }
+
+    /// All the unexpired decisions in the store.
+    pub fn decisions(&mut self) -> Vec<PermissionDecision> {
+        self.remove_expired();
+        self.decisions.clone()
+    }
+
+    /// Forget the decisions made for `origin`, or every decision if it is `None`.
+    pub fn clear(&mut self, origin: Option<&ImmutableOrigin>) {
+        match origin {
+            Some(origin) => self.decisions.retain(|decision| decision.origin != *origin),
+            None => self.decisions.clear(),
+        }
+    }
+
+    fn remove_expired(&mut self) {
+        let now = SystemTime::now();
+        self.decisions.retain(|decision| match decision.expires {
+            Some(expires) => expires > now,
+            None => true,
+        });
+    }
+}
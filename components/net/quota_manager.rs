@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Accounting of the storage used by each origin, and the choice of which
+//! origins to evict when all of them together use too much.
+//! <https://storage.spec.whatwg.org/#usage-and-quota>
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use servo_config::pref;
+
+/// How many bytes an origin may store when `dom.storage.origin_quota` isn't set.
+const DEFAULT_ORIGIN_QUOTA: usize = 5 * 1024 * 1024;
+
+/// How many bytes all origins may store when `dom.storage.global_quota` isn't set.
+const DEFAULT_GLOBAL_QUOTA: usize = 50 * 1024 * 1024;
+
+/// The number of bytes a single origin may store.
+pub fn origin_quota() -> usize {
+    match pref!(dom.storage.origin_quota) {
+        quota if quota > 0 => quota as usize,
+        _ => DEFAULT_ORIGIN_QUOTA,
+    }
+}
+
+/// The number of bytes all origins may store before some of them are evicted.
+pub fn global_quota() -> usize {
+    match pref!(dom.storage.global_quota) {
+        quota if quota > 0 => quota as usize,
+        _ => DEFAULT_GLOBAL_QUOTA,
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct OriginUsage {
+    bytes: usize,
+    /// The value of the access clock when the origin's storage was last used.
+    last_access: u64,
+    /// <https://storage.spec.whatwg.org/#persistence>
+    persisted: bool,
+}
+
+/// The usage of every origin that stores something, keyed by the ASCII
+/// serialization of the origin.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct QuotaManager {
+    origins: HashMap<String, OriginUsage>,
+    /// A counter giving the order in which origins were used, which unlike the
+    /// wall clock never goes backwards or ties.
+    access_clock: u64,
+}
+
+impl QuotaManager {
+    /// The number of bytes `origin` currently stores.
+    pub fn usage(&self, origin: &str) -> usize {
+        self.origins.get(origin).map_or(0, |usage| usage.bytes)
+    }
+
+    /// The number of bytes all origins currently store.
+    pub fn total_usage(&self) -> usize {
+        self.origins.values().map(|usage| usage.bytes).sum()
+    }
+
+    /// Record that the storage of `origin` was used.
+    pub fn touch(&mut self, origin: &str) {
+        if let Some(usage) = self.origins.get_mut(origin) {
+            self.access_clock += 1;
+            usage.last_access = self.access_clock;
+        }
+    }
+
+    /// Record that `origin` now stores `bytes`, which also counts as a use.
+    pub fn set_usage(&mut self, origin: &str, bytes: usize) {
+        self.access_clock += 1;
+        let usage = self.origins.entry(origin.to_owned()).or_default();
+        usage.bytes = bytes;
+        usage.last_access = self.access_clock;
+        if bytes == 0 && !usage.persisted {
+            self.origins.remove(origin);
+        }
+    }
+
+    /// <https://storage.spec.whatwg.org/#persist>
+    pub fn set_persisted(&mut self, origin: &str, persisted: bool) {
+        let usage = self.origins.entry(origin.to_owned()).or_default();
+        usage.persisted = persisted;
+        if usage.bytes == 0 && !persisted {
+            self.origins.remove(origin);
+        }
+    }
+
+    pub fn is_persisted(&self, origin: &str) -> bool {
+        self.origins
+            .get(origin)
+            .map_or(false, |usage| usage.persisted)
+    }
+
+    /// Make the recorded usage match `stored`, the size of the data each origin has
+    /// actually stored, keeping what is known about the origins that are in both.
+    pub fn reconcile<'a>(&mut self, stored: impl Iterator<Item = (&'a str, usize)>) {
+        let stored: HashMap<&str, usize> = stored.collect();
+        self.origins
+            .retain(|origin, usage| usage.persisted || stored.contains_key(origin.as_str()));
+        for (origin, usage) in self.origins.iter_mut() {
+            usage.bytes = stored.get(origin.as_str()).copied().unwrap_or(0);
+        }
+        for (origin, bytes) in stored {
+            self.origins.entry(origin.to_owned()).or_default().bytes = bytes;
+        }
+    }
+
+    /// Forget everything about `origin`, once its data has been removed.
+    pub fn remove(&mut self, origin: &str) {
+        self.origins.remove(origin);
+    }
+
+    /// Forget every origin, once all data has been removed.
+    pub fn clear(&mut self) {
+        self.origins.clear();
+    }
+
+    /// The origins whose data should be evicted, least recently used first, for the
+    /// total usage to fit in `global_quota`. Persisted origins and `keep`, the origin
+    /// that is being written to, are never chosen.
+    /// <https://storage.spec.whatwg.org/#storage-pressure>
+    pub fn origins_to_evict(&self, keep: &str, global_quota: usize) -> Vec<String> {
+        let mut total = self.total_usage();
+        if total <= global_quota {
+            return vec![];
+        }
+        let mut candidates: Vec<(&String, &OriginUsage)> = self
+            .origins
+            .iter()
+            .filter(|(origin, usage)| !usage.persisted && origin.as_str() != keep)
+            .collect();
+        candidates.sort_by_key(|(_, usage)| usage.last_access);
+
+        let mut evicted = vec![];
+        for (origin, usage) in candidates {
+            if total <= global_quota {
+                break;
+            }
+            total -= usage.bytes;
+            evicted.push(origin.clone());
+        }
+        evicted
+    }
+}
@@ -12,7 +12,7 @@ use std::io::{self, BufReader};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
@@ -23,13 +23,15 @@ use log::{debug, warn};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use net_traits::blob_url_store::parse_blob_url;
 use net_traits::filemanager_thread::FileTokenCheck;
+use net_traits::protocols::ProtocolRegistry;
 use net_traits::request::{Destination, RequestBuilder};
+use net_traits::request_interceptor::RequestInterceptors;
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::{
-    CookieSource, CoreResourceMsg, CoreResourceThread, CustomResponseMediator, DiscardFetch,
-    FetchChannels, FetchTaskTarget, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
-    WebSocketDomAction, WebSocketNetworkEvent,
+    ClearDataTypes, CookieSource, CoreResourceMsg, CoreResourceThread, CustomResponseMediator,
+    DiscardFetch, FetchChannels, FetchTaskTarget, ResourceFetchTiming, ResourceThreads,
+    ResourceTimingType, WebSocketDomAction, WebSocketNetworkEvent,
 };
 use profile_traits::mem::{ProfilerChan as MemProfilerChan, Report, ReportKind, ReportsChan};
 use profile_traits::path;
@@ -49,6 +51,7 @@ use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::permission_store::PermissionStore;
 use crate::storage_thread::StorageThreadFactory;
 use crate::{cookie, websocket_loader};
 
@@ -65,6 +68,8 @@ fn load_root_cert_store_from_file(file_path: String) -> io::Result<RootCertStore
 /// Returns a tuple of (public, private) senders to the new threads.
 pub fn new_resource_threads(
     user_agent: Cow<'static, str>,
+    protocols: Arc<ProtocolRegistry>,
+    request_interceptors: RequestInterceptors,
     devtools_sender: Option<Sender<DevtoolsControlMsg>>,
     time_profiler_chan: ProfilerChan,
     mem_profiler_chan: MemProfilerChan,
@@ -86,6 +91,8 @@ pub fn new_resource_threads(
 
     let (public_core, private_core) = new_core_resource_thread(
         user_agent,
+        protocols,
+        request_interceptors,
         devtools_sender,
         time_profiler_chan,
         mem_profiler_chan,
@@ -95,15 +102,19 @@ pub fn new_resource_threads(
         ignore_certificate_errors,
     );
     let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
+    // Private browsing keeps its web storage in memory, apart from the public one.
+    let private_storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(None);
     (
-        ResourceThreads::new(public_core, storage.clone()),
-        ResourceThreads::new(private_core, storage),
+        ResourceThreads::new(public_core, storage),
+        ResourceThreads::new(private_core, private_storage),
     )
 }
 
 /// Create a CoreResourceThread
 pub fn new_core_resource_thread(
     user_agent: Cow<'static, str>,
+    protocols: Arc<ProtocolRegistry>,
+    request_interceptors: RequestInterceptors,
     devtools_sender: Option<Sender<DevtoolsControlMsg>>,
     time_profiler_chan: ProfilerChan,
     mem_profiler_chan: MemProfilerChan,
@@ -121,6 +132,8 @@ pub fn new_core_resource_thread(
         .spawn(move || {
             let resource_manager = CoreResourceManager::new(
                 user_agent,
+                protocols,
+                request_interceptors,
                 devtools_sender,
                 time_profiler_chan,
                 embedder_proxy,
@@ -162,10 +175,12 @@ fn create_http_states(
     let mut auth_cache = AuthCache::default();
     let http_cache = HttpCache::default();
     let mut cookie_jar = CookieStorage::new(150);
+    let mut permission_store = PermissionStore::default();
     if let Some(config_dir) = config_dir {
         read_json_from_file(&mut auth_cache, config_dir, "auth_cache.json");
         read_json_from_file(&mut hsts_list, config_dir, "hsts_list.json");
         read_json_from_file(&mut cookie_jar, config_dir, "cookie_jar.json");
+        read_json_from_file(&mut permission_store, config_dir, "permission_store.json");
     }
 
     let override_manager = CertificateErrorOverrideManager::new();
@@ -174,6 +189,7 @@ fn create_http_states(
         cookie_jar: RwLock::new(cookie_jar),
         auth_cache: RwLock::new(auth_cache),
         history_states: RwLock::new(HashMap::new()),
+        permission_store: RwLock::new(permission_store),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
         client: create_http_client(create_tls_config(
@@ -182,6 +198,7 @@ fn create_http_states(
             override_manager.clone(),
         )),
         override_manager,
+        pipeline_languages: RwLock::new(HashMap::new()),
     };
 
     let override_manager = CertificateErrorOverrideManager::new();
@@ -190,6 +207,7 @@ fn create_http_states(
         cookie_jar: RwLock::new(CookieStorage::new(150)),
         auth_cache: RwLock::new(AuthCache::default()),
         history_states: RwLock::new(HashMap::new()),
+        permission_store: RwLock::new(PermissionStore::default()),
         http_cache: RwLock::new(HttpCache::default()),
         http_cache_state: Mutex::new(HashMap::new()),
         client: create_http_client(create_tls_config(
@@ -198,6 +216,7 @@ fn create_http_states(
             override_manager.clone(),
         )),
         override_manager,
+        pipeline_languages: RwLock::new(HashMap::new()),
     };
 
     (Arc::new(http_state), Arc::new(private_http_state))
@@ -308,6 +327,31 @@ impl ResourceChannelManager {
                     .clear_storage(&request);
                 return true;
             },
+            CoreResourceMsg::GetStoredCookies(url, consumer) => {
+                let cookie_jar = http_state.cookie_jar.read().unwrap();
+                let _ = consumer.send(cookie_jar.stored_cookies(url.as_ref()));
+            },
+            CoreResourceMsg::SetStoredCookie(cookie) => {
+                http_state
+                    .cookie_jar
+                    .write()
+                    .unwrap()
+                    .set_stored_cookie(cookie);
+            },
+            CoreResourceMsg::DeleteStoredCookie(cookie) => {
+                http_state
+                    .cookie_jar
+                    .write()
+                    .unwrap()
+                    .delete_stored_cookie(&cookie);
+            },
+            CoreResourceMsg::ObserveCookieChanges(observer) => {
+                http_state
+                    .cookie_jar
+                    .write()
+                    .unwrap()
+                    .set_observer(observer);
+            },
             CoreResourceMsg::FetchRedirect(req_init, res_init, sender, cancel_chan) => self
                 .resource_manager
                 .fetch(req_init, Some(res_init), sender, http_state, cancel_chan),
@@ -367,6 +411,35 @@ impl ResourceChannelManager {
             CoreResourceMsg::ClearCache => {
                 http_state.http_cache.write().unwrap().clear();
             },
+            CoreResourceMsg::GetPermission(origin, name, consumer) => {
+                let mut permission_store = http_state.permission_store.write().unwrap();
+                let _ = consumer.send(permission_store.get(&origin, name));
+            },
+            CoreResourceMsg::SetPermission(decision) => {
+                http_state.permission_store.write().unwrap().set(decision);
+            },
+            CoreResourceMsg::GetPermissionDecisions(consumer) => {
+                let mut permission_store = http_state.permission_store.write().unwrap();
+                let _ = consumer.send(permission_store.decisions());
+            },
+            CoreResourceMsg::ClearPermissionDecisions(origin) => {
+                http_state
+                    .permission_store
+                    .write()
+                    .unwrap()
+                    .clear(origin.as_ref());
+            },
+            CoreResourceMsg::ClearData(origins, types, since) => {
+                clear_data(http_state, origins.as_deref(), types, since);
+            },
+            CoreResourceMsg::SetPipelineLanguages(pipeline_id, languages) => {
+                let mut pipeline_languages = http_state.pipeline_languages.write().unwrap();
+                if languages.is_empty() {
+                    pipeline_languages.remove(&pipeline_id);
+                } else {
+                    pipeline_languages.insert(pipeline_id, languages);
+                }
+            },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
@@ -384,6 +457,14 @@ impl ResourceChannelManager {
                         Ok(hsts) => write_json_to_file(&*hsts, config_dir, "hsts_list.json"),
                         Err(_) => warn!("Error writing hsts list to disk"),
                     }
+                    match http_state.permission_store.read() {
+                        Ok(permission_store) => write_json_to_file(
+                            &*permission_store,
+                            config_dir,
+                            "permission_store.json",
+                        ),
+                        Err(_) => warn!("Error writing permission store to disk"),
+                    }
                 }
                 self.resource_manager.exit();
                 let _ = sender.send(());
@@ -394,6 +475,62 @@ impl ResourceChannelManager {
     }
 }
 
+/// Remove the cached responses, cookies, credentials and HSTS hosts of `origins`, or of
+/// every origin.
+/// <https://w3c.github.io/webappsec-clear-site-data/#clear-response>
+fn clear_data(
+    http_state: &HttpState,
+    origins: Option<&[ImmutableOrigin]>,
+    types: ClearDataTypes,
+    since: Option<SystemTime>,
+) {
+    if types.cache {
+        let mut http_cache = http_state.http_cache.write().unwrap();
+        match origins {
+            Some(origins) => http_cache.clear_origins(origins),
+            None => http_cache.clear(),
+        }
+    }
+    if types.cookies {
+        let since = since
+            .and_then(|since| since.duration_since(UNIX_EPOCH).ok())
+            .map(|since| time::Timespec::new(since.as_secs() as i64, since.subsec_nanos() as i32));
+        http_state
+            .cookie_jar
+            .write()
+            .unwrap()
+            .clear_data(origins, since);
+        let mut auth_cache = http_state.auth_cache.write().unwrap();
+        match origins {
+            Some(origins) => {
+                for origin in origins {
+                    auth_cache.entries.remove(&origin.ascii_serialization());
+                }
+            },
+            None => auth_cache.entries.clear(),
+        }
+    }
+    if types.hsts {
+        let hosts: Option<Vec<String>> = origins.map(|origins| {
+            origins
+                .iter()
+                .filter_map(|origin| match origin {
+                    ImmutableOrigin::Tuple(_, host, _) => Some(host.to_string()),
+                    ImmutableOrigin::Opaque(_) => None,
+                })
+                .collect()
+        });
+        let since = since
+            .and_then(|since| since.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs());
+        http_state
+            .hsts_list
+            .write()
+            .unwrap()
+            .clear_data(hosts.as_deref(), since);
+    }
+}
+
 pub fn read_json_from_file<T>(data: &mut T, config_dir: &Path, filename: &str)
 where
     T: for<'de> Deserialize<'de>,
@@ -454,6 +591,7 @@ impl Default for AuthCache {
         Self {
             version: 1,
             entries: HashMap::new(),
+            proxy_entry: None,
         }
     }
 }
@@ -462,11 +600,17 @@ impl Default for AuthCache {
 pub struct AuthCache {
     pub version: u32,
     pub entries: HashMap<String, AuthCacheEntry>,
+    /// The credentials that the proxy accepted, which are sent with every request.
+    #[serde(default)]
+    pub proxy_entry: Option<AuthCacheEntry>,
 }
 
 pub struct CoreResourceManager {
     user_agent: Cow<'static, str>,
+    protocols: Arc<ProtocolRegistry>,
+    request_interceptors: RequestInterceptors,
     devtools_sender: Option<Sender<DevtoolsControlMsg>>,
+    embedder_proxy: EmbedderProxy,
     sw_managers: HashMap<ImmutableOrigin, IpcSender<CustomResponseMediator>>,
     filemanager: FileManager,
     thread_pool: Arc<CoreResourceThreadPool>,
@@ -602,6 +746,8 @@ impl CoreResourceThreadPool {
 impl CoreResourceManager {
     pub fn new(
         user_agent: Cow<'static, str>,
+        protocols: Arc<ProtocolRegistry>,
+        request_interceptors: RequestInterceptors,
         devtools_sender: Option<Sender<DevtoolsControlMsg>>,
         _profiler_chan: ProfilerChan,
         embedder_proxy: EmbedderProxy,
@@ -612,7 +758,10 @@ impl CoreResourceManager {
         let pool_handle = Arc::new(pool);
         CoreResourceManager {
             user_agent,
+            protocols,
+            request_interceptors,
             devtools_sender,
+            embedder_proxy: embedder_proxy.clone(),
             sw_managers: Default::default(),
             filemanager: FileManager::new(embedder_proxy, Arc::downgrade(&pool_handle)),
             thread_pool: pool_handle,
@@ -654,7 +803,10 @@ impl CoreResourceManager {
     ) {
         let http_state = http_state.clone();
         let ua = self.user_agent.clone();
+        let protocols = self.protocols.clone();
+        let request_interceptors = self.request_interceptors.clone();
         let dc = self.devtools_sender.clone();
+        let embedder_proxy = self.embedder_proxy.clone();
         let filemanager = self.filemanager.clone();
 
         let timing_type = match request_builder.destination {
@@ -694,7 +846,10 @@ impl CoreResourceManager {
             let context = FetchContext {
                 state: http_state,
                 user_agent: ua,
+                protocols,
+                request_interceptors,
                 devtools_chan: dc.map(|dc| Arc::new(Mutex::new(dc))),
+                embedder_proxy,
                 filemanager: Arc::new(Mutex::new(filemanager)),
                 file_token,
                 cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(cancel_chan))),
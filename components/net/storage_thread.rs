@@ -8,13 +8,13 @@ use std::path::PathBuf;
 use std::thread;
 
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use log::debug;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 
+use crate::quota_manager::{self, QuotaManager};
 use crate::resource_thread;
 
-const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
-
 pub trait StorageThreadFactory {
     fn new(config_dir: Option<PathBuf>) -> Self;
 }
@@ -33,23 +33,36 @@ impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
     }
 }
 
+/// Every script thread's storage requests arrive on the one port of this thread, so
+/// the writes to the storage of an origin are applied in the order they were sent.
 struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
     session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
+    /// The usage of local storage, which is the one kept on disk.
+    quota: QuotaManager,
     config_dir: Option<PathBuf>,
 }
 
 impl StorageManager {
     fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
         let mut local_data = HashMap::new();
+        let mut quota = QuotaManager::default();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
+            resource_thread::read_json_from_file(&mut quota, config_dir, "storage_quota.json");
         }
+        // The stored data is what to trust if the two files disagree.
+        quota.reconcile(
+            local_data
+                .iter()
+                .map(|(origin, (total, _))| (origin.as_str(), *total)),
+        );
         StorageManager {
             port,
             session_data: HashMap::new(),
             local_data,
+            quota,
             config_dir,
         }
     }
@@ -60,12 +73,15 @@ impl StorageManager {
         loop {
             match self.port.recv().unwrap() {
                 StorageThreadMsg::Length(sender, url, storage_type) => {
+                    self.touch(&url, storage_type);
                     self.length(sender, url, storage_type)
                 },
                 StorageThreadMsg::Key(sender, url, storage_type, index) => {
+                    self.touch(&url, storage_type);
                     self.key(sender, url, storage_type, index)
                 },
                 StorageThreadMsg::Keys(sender, url, storage_type) => {
+                    self.touch(&url, storage_type);
                     self.keys(sender, url, storage_type)
                 },
                 StorageThreadMsg::SetItem(sender, url, storage_type, name, value) => {
@@ -73,6 +89,7 @@ impl StorageManager {
                     self.save_state()
                 },
                 StorageThreadMsg::GetItem(sender, url, storage_type, name) => {
+                    self.touch(&url, storage_type);
                     self.request_item(sender, url, storage_type, name)
                 },
                 StorageThreadMsg::RemoveItem(sender, url, storage_type, name) => {
@@ -83,6 +100,10 @@ impl StorageManager {
                     self.clear(sender, url, storage_type);
                     self.save_state()
                 },
+                StorageThreadMsg::ClearData(origins) => {
+                    self.clear_data(origins);
+                    self.save_state()
+                },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
                     let _ = sender.send(());
@@ -92,9 +113,56 @@ impl StorageManager {
         }
     }
 
+    /// <https://w3c.github.io/webappsec-clear-site-data/#clear-dom-accessible-storage>
+    fn clear_data(&mut self, origins: Option<Vec<ImmutableOrigin>>) {
+        let origins = match origins {
+            Some(origins) => origins,
+            None => {
+                self.session_data.clear();
+                self.local_data.clear();
+                self.quota.clear();
+                return;
+            },
+        };
+        for origin in origins {
+            let origin = origin.ascii_serialization();
+            self.session_data.remove(&origin);
+            self.local_data.remove(&origin);
+            self.quota.remove(&origin);
+        }
+    }
+
     fn save_state(&self) {
         if let Some(ref config_dir) = self.config_dir {
             resource_thread::write_json_to_file(&self.local_data, config_dir, "local_data.json");
+            resource_thread::write_json_to_file(&self.quota, config_dir, "storage_quota.json");
+        }
+    }
+
+    /// Count a use of the storage of the origin of `url`, which makes its local
+    /// storage less likely to be evicted.
+    fn touch(&mut self, url: &ServoUrl, storage_type: StorageType) {
+        if let StorageType::Local = storage_type {
+            self.quota.touch(&url.origin().ascii_serialization());
+        }
+    }
+
+    /// Record the size of the local storage of `origin` after it changed.
+    fn update_usage(&mut self, origin: &str) {
+        let total = self.local_data.get(origin).map_or(0, |&(total, _)| total);
+        self.quota.set_usage(origin, total);
+    }
+
+    /// Evict the local storage of the least recently used origins other than
+    /// `origin`, until all of them together fit in the global quota.
+    fn evict(&mut self, origin: &str) {
+        for evicted in self
+            .quota
+            .origins_to_evict(origin, quota_manager::global_quota())
+        {
+            debug!("Evicting the local storage of {}", evicted);
+            self.local_data.remove(&evicted);
+            self.quota.remove(&evicted);
         }
     }
 
@@ -165,6 +233,7 @@ impl StorageManager {
         value: String,
     ) {
         let origin = self.origin_as_string(url);
+        let origin_quota = quota_manager::origin_quota();
 
         let (this_storage_size, other_storage_size) = {
             let local_data = self.select_data(StorageType::Local);
@@ -192,7 +261,7 @@ impl StorageManager {
                     new_total_size += name.as_bytes().len();
                 }
 
-                if (new_total_size + other_storage_size) > QUOTA_SIZE_LIMIT {
+                if (new_total_size + other_storage_size) > origin_quota {
                     return Err(());
                 }
 
@@ -210,6 +279,10 @@ impl StorageManager {
                 message
             })
             .unwrap();
+        if let (StorageType::Local, Ok(_)) = (storage_type, &message) {
+            self.update_usage(&origin);
+            self.evict(&origin);
+        }
         sender.send(message).unwrap();
     }
 
@@ -249,26 +322,30 @@ impl StorageManager {
                     old
                 })
             });
+        if let StorageType::Local = storage_type {
+            self.update_usage(&origin);
+        }
         sender.send(old_value).unwrap();
     }
 
     fn clear(&mut self, sender: IpcSender<bool>, url: ServoUrl, storage_type: StorageType) {
         let origin = self.origin_as_string(url);
         let data = self.select_data_mut(storage_type);
-        sender
-            .send(
-                data.get_mut(&origin)
-                    .map_or(false, |&mut (ref mut total, ref mut entry)| {
-                        if !entry.is_empty() {
-                            entry.clear();
-                            *total = 0;
-                            true
-                        } else {
-                            false
-                        }
-                    }),
-            )
-            .unwrap();
+        let cleared = data
+            .get_mut(&origin)
+            .map_or(false, |&mut (ref mut total, ref mut entry)| {
+                if !entry.is_empty() {
+                    entry.clear();
+                    *total = 0;
+                    true
+                } else {
+                    false
+                }
+            });
+        if let StorageType::Local = storage_type {
+            self.update_usage(&origin);
+        }
+        sender.send(cleared).unwrap();
     }
 
     fn origin_as_string(&self, url: ServoUrl) -> String {
@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use embedder_traits::{CookieChange, CookieSameSite, StoredCookie};
+use ipc_channel::ipc;
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
 use net_traits::CookieSource;
@@ -475,3 +477,70 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+fn stored_cookie(name: &str, domain: &str, path: &str) -> StoredCookie {
+    StoredCookie {
+        name: name.to_owned(),
+        value: "bar".to_owned(),
+        domain: domain.to_owned(),
+        host_only: false,
+        path: path.to_owned(),
+        expires: None,
+        secure: false,
+        http_only: true,
+        same_site: Some(CookieSameSite::Lax),
+    }
+}
+
+#[test]
+fn test_stored_cookies_set_and_deleted_by_embedder() {
+    let mut storage = CookieStorage::new(150);
+    storage.set_stored_cookie(stored_cookie("foo", "example.org", "/"));
+    storage.set_stored_cookie(stored_cookie("baz", "example.org", "/private"));
+    let url = ServoUrl::parse("http://www.example.org/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "qux=quux");
+
+    let cookies = storage.stored_cookies(Some(&url));
+    let names: Vec<&str> = cookies.iter().map(|cookie| &*cookie.name).collect();
+    assert_eq!(names, ["foo", "qux"]);
+    assert_eq!(cookies[0], stored_cookie("foo", "example.org", "/"));
+    assert_eq!(storage.stored_cookies(None).len(), 3);
+    // The HTTP only cookies that the embedder set are sent with requests.
+    assert_eq!(
+        storage.cookies_for_url(&url, CookieSource::HTTP).unwrap(),
+        "foo=bar; qux=quux"
+    );
+
+    storage.delete_stored_cookie(&stored_cookie("foo", "example.org", "/"));
+    let names: Vec<String> = storage
+        .stored_cookies(None)
+        .into_iter()
+        .map(|cookie| cookie.name)
+        .collect();
+    assert_eq!(names.len(), 2);
+    assert!(!names.contains(&"foo".to_owned()));
+}
+
+#[test]
+fn test_cookie_changes_are_observed() {
+    let mut storage = CookieStorage::new(150);
+    let (sender, receiver) = ipc::channel().unwrap();
+    storage.set_observer(Some(sender));
+
+    let url = ServoUrl::parse("https://example.org/").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    let cookie = match receiver.recv().unwrap() {
+        CookieChange::Set(cookie) => cookie,
+        change => panic!("Unexpected cookie change {:?}", change),
+    };
+    assert_eq!(cookie.name, "foo");
+    assert_eq!(cookie.domain, "example.org");
+    assert!(cookie.host_only);
+
+    storage.delete_stored_cookie(&cookie);
+    assert_eq!(receiver.recv().unwrap(), CookieChange::Removed(cookie));
+
+    storage.set_observer(None);
+    add_cookie_to_storage(&mut storage, &url, "baz=qux");
+    assert!(receiver.try_recv().is_err());
+}
@@ -13,6 +13,7 @@ use std::time::{Duration, SystemTime};
 
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse};
+use embedder_traits::EmbedderMsg;
 use headers::{
     AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
     AccessControlAllowOrigin, AccessControlMaxAge, CacheControl, ContentLength, ContentType,
@@ -22,7 +23,9 @@ use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use http::{Method, StatusCode};
 use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
 use mime::{self, Mime};
-use msg::constellation_msg::TEST_PIPELINE_ID;
+use msg::constellation_msg::{
+    TopLevelBrowsingContextId, WebViewId, TEST_BROWSING_CONTEXT_ID, TEST_PIPELINE_ID,
+};
 use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
@@ -30,9 +33,15 @@ use net::hsts::HstsEntry;
 use net::resource_thread::CoreResourceThreadPool;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
+use net_traits::protocols::{
+    ProtocolCors, ProtocolHandler, ProtocolOptions, ProtocolRegistry, ProtocolResponse,
+};
 use net_traits::request::{
     Destination, Origin, RedirectMode, Referrer, Request, RequestBuilder, RequestMode,
 };
+use net_traits::request_interceptor::{
+    InterceptedRequest, Interception, InterceptorScope, RequestInterceptor, RequestInterceptors,
+};
 use net_traits::response::{CacheState, HttpsState, Response, ResponseBody, ResponseType};
 use net_traits::{
     FetchTaskTarget, IncludeSubdomains, NetworkError, ReferrerPolicy, ResourceFetchTiming,
@@ -319,6 +328,148 @@ fn test_fetch_bogus_scheme() {
     assert!(fetch_response.is_network_error());
 }
 
+struct TestProtocolHandler;
+
+impl ProtocolHandler for TestProtocolHandler {
+    fn load(&self, request: &Request) -> Result<ProtocolResponse, String> {
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(ContentType::text());
+        Ok(ProtocolResponse::from_bytes(
+            headers,
+            request.current_url().path().as_bytes().to_vec(),
+        ))
+    }
+}
+
+fn fetch_custom_scheme(cors: ProtocolCors, mode: RequestMode) -> Response {
+    let mut protocols = ProtocolRegistry::default();
+    protocols
+        .register(
+            "app",
+            TestProtocolHandler,
+            ProtocolOptions {
+                secure: false,
+                cors,
+            },
+        )
+        .unwrap();
+    let url = ServoUrl::parse("app://bundle/index.txt").unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    request.mode = mode;
+    let mut context = new_fetch_context(None, None, None);
+    context.protocols = Arc::new(protocols);
+    fetch_with_context(&mut request, &mut context)
+}
+
+#[test]
+fn test_fetch_custom_scheme() {
+    let fetch_response = fetch_custom_scheme(ProtocolCors::Blocked, RequestMode::NoCors);
+    assert_eq!(fetch_response.response_type, ResponseType::Opaque);
+
+    let actual_response = fetch_response.actual_response();
+    assert!(!actual_response.is_network_error());
+    let content_type: Mime = actual_response
+        .headers
+        .typed_get::<ContentType>()
+        .unwrap()
+        .into();
+    assert_eq!(content_type, mime::TEXT_PLAIN);
+    match *actual_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(body, b"/index.txt"),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_fetch_custom_scheme_cors() {
+    let fetch_response = fetch_custom_scheme(ProtocolCors::Blocked, RequestMode::CorsMode);
+    assert!(fetch_response.is_network_error());
+
+    // Without an `Access-Control-Allow-Origin` header, the check fails.
+    let fetch_response = fetch_custom_scheme(ProtocolCors::Checked, RequestMode::CorsMode);
+    assert!(fetch_response.is_network_error());
+
+    let fetch_response = fetch_custom_scheme(ProtocolCors::Allowed, RequestMode::SameOrigin);
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(fetch_response.response_type, ResponseType::Basic);
+}
+
+struct TestRequestInterceptor;
+
+impl RequestInterceptor for TestRequestInterceptor {
+    fn intercept(&self, request: InterceptedRequest) -> Interception {
+        match request.url.as_str() {
+            "data:,blocked" => Interception::Block,
+            "data:,fulfilled" => Interception::Fulfill(ProtocolResponse::from_bytes(
+                HeaderMap::new(),
+                b"intercepted".to_vec(),
+            )),
+            _ => Interception::Continue,
+        }
+    }
+}
+
+fn fetch_intercepted(
+    url: &str,
+    scope: InterceptorScope,
+    webview_id: Option<WebViewId>,
+) -> Response {
+    let url = ServoUrl::parse(url).unwrap();
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        Some(TEST_PIPELINE_ID),
+        HttpsState::None,
+    );
+    let mut context = new_fetch_context(None, None, None);
+    context
+        .request_interceptors
+        .set_pipeline_webview(TEST_PIPELINE_ID, webview_id);
+    context
+        .request_interceptors
+        .add(scope, TestRequestInterceptor);
+    fetch_with_context(&mut request, &mut context)
+}
+
+#[test]
+fn test_fetch_intercepted() {
+    let webview_id = TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID);
+    for scope in [
+        InterceptorScope::Global,
+        InterceptorScope::WebView(webview_id),
+    ] {
+        let fetch_response = fetch_intercepted("data:,blocked", scope, Some(webview_id));
+        assert!(fetch_response.is_network_error());
+
+        let fetch_response = fetch_intercepted("data:,fulfilled", scope, Some(webview_id));
+        assert!(!fetch_response.is_network_error());
+        match *fetch_response.body.lock().unwrap() {
+            ResponseBody::Done(ref body) => assert_eq!(body, b"intercepted"),
+            _ => panic!(),
+        }
+
+        let fetch_response = fetch_intercepted("data:,loaded", scope, Some(webview_id));
+        match *fetch_response.body.lock().unwrap() {
+            ResponseBody::Done(ref body) => assert_eq!(body, b"loaded"),
+            _ => panic!(),
+        }
+    }
+
+    // The interceptors of webviews don't see the requests that don't come from them.
+    let scope = InterceptorScope::WebView(webview_id);
+    let fetch_response = fetch_intercepted("data:,blocked", scope, None);
+    assert!(!fetch_response.is_network_error());
+}
+
 #[test]
 fn test_cors_preflight_fetch() {
     static ACK: &'static [u8] = b"ACK";
@@ -759,7 +910,10 @@ fn test_fetch_with_hsts() {
     let mut context = FetchContext {
         state: Arc::new(HttpState::default()),
         user_agent: DEFAULT_USER_AGENT.into(),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        request_interceptors: RequestInterceptors::default(),
         devtools_chan: None,
+        embedder_proxy: create_embedder_proxy(),
         filemanager: Arc::new(Mutex::new(FileManager::new(
             create_embedder_proxy(),
             Weak::new(),
@@ -818,7 +972,10 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
     let mut context = FetchContext {
         state: Arc::new(HttpState::default()),
         user_agent: DEFAULT_USER_AGENT.into(),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        request_interceptors: RequestInterceptors::default(),
         devtools_chan: None,
+        embedder_proxy: create_embedder_proxy(),
         filemanager: Arc::new(Mutex::new(FileManager::new(
             create_embedder_proxy(),
             Weak::new(),
@@ -875,7 +1032,10 @@ fn test_fetch_self_signed() {
     let mut context = FetchContext {
         state: Arc::new(HttpState::default()),
         user_agent: DEFAULT_USER_AGENT.into(),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        request_interceptors: RequestInterceptors::default(),
         devtools_chan: None,
+        embedder_proxy: create_embedder_proxy(),
         filemanager: Arc::new(Mutex::new(FileManager::new(
             create_embedder_proxy(),
             Weak::new(),
@@ -923,6 +1083,153 @@ fn test_fetch_self_signed() {
     let _ = server.close();
 }
 
+#[test]
+fn test_fetch_self_signed_accepted_by_embedder() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+
+    let (server, mut url) = make_ssl_server(handler);
+    url.as_mut_url().set_scheme("https").unwrap();
+
+    let (embedder_sender, embedder_receiver) = unbounded();
+    let mut embedder_proxy = create_embedder_proxy();
+    embedder_proxy.sender = embedder_sender;
+    let mut context = new_fetch_context(None, Some(embedder_proxy), None);
+    let webview_id = TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID);
+    context
+        .request_interceptors
+        .set_pipeline_webview(TEST_PIPELINE_ID, Some(webview_id));
+
+    let expected_origin = url.origin();
+    let expected_certificate = server.certificates.as_ref().unwrap()[0].0.clone();
+    let embedder = std::thread::spawn(move || {
+        let (id, msg) = embedder_receiver.recv().unwrap();
+        assert_eq!(id, Some(webview_id));
+        match msg {
+            EmbedderMsg::CertificateError(error, sender) => {
+                assert_eq!(error.origin, expected_origin);
+                assert_eq!(error.chain[0], expected_certificate);
+                sender.send(true).unwrap();
+            },
+            msg => panic!("Unexpected embedder message {:?}", msg),
+        }
+        embedder_receiver
+    });
+
+    let fetch_document = |context: &mut FetchContext| {
+        let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+            .method(Method::GET)
+            .body(None)
+            .destination(Destination::Document)
+            .origin(url.clone().origin())
+            .pipeline_id(Some(TEST_PIPELINE_ID))
+            .build();
+        fetch_with_context(&mut request, context)
+    };
+
+    let response = fetch_document(&mut context);
+    assert!(response.status.unwrap().0.is_success());
+    let embedder_receiver = embedder.join().unwrap();
+
+    // The certificate stays accepted, without asking the embedder again.
+    let response = fetch_document(&mut context);
+    assert!(response.status.unwrap().0.is_success());
+    assert!(embedder_receiver.try_recv().is_err());
+
+    let _ = server.close();
+}
+
+#[test]
+fn test_fetch_self_signed_rejected_by_embedder() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+
+    let (server, mut url) = make_ssl_server(handler);
+    url.as_mut_url().set_scheme("https").unwrap();
+
+    let (embedder_sender, embedder_receiver) = unbounded();
+    let mut embedder_proxy = create_embedder_proxy();
+    embedder_proxy.sender = embedder_sender;
+    let mut context = new_fetch_context(None, Some(embedder_proxy), None);
+    let webview_id = TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID);
+    context
+        .request_interceptors
+        .set_pipeline_webview(TEST_PIPELINE_ID, Some(webview_id));
+
+    let embedder = std::thread::spawn(move || {
+        match embedder_receiver.recv().unwrap() {
+            (_, EmbedderMsg::CertificateError(_, sender)) => sender.send(false).unwrap(),
+            (_, msg) => panic!("Unexpected embedder message {:?}", msg),
+        }
+        embedder_receiver
+    });
+
+    let fetch_document = |context: &mut FetchContext| {
+        let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+            .method(Method::GET)
+            .body(None)
+            .destination(Destination::Document)
+            .origin(url.clone().origin())
+            .pipeline_id(Some(TEST_PIPELINE_ID))
+            .build();
+        fetch_with_context(&mut request, context)
+    };
+
+    let response = fetch_document(&mut context);
+    assert!(matches!(
+        response.get_network_error(),
+        Some(NetworkError::SslValidation(..))
+    ));
+    let embedder_receiver = embedder.join().unwrap();
+
+    // The certificate stays rejected, without asking the embedder again.
+    let response = fetch_document(&mut context);
+    assert!(matches!(
+        response.get_network_error(),
+        Some(NetworkError::SslValidation(..))
+    ));
+    assert!(embedder_receiver.try_recv().is_err());
+
+    let _ = server.close();
+}
+
+#[test]
+fn test_fetch_self_signed_subresource_does_not_ask_embedder() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+
+    let (server, mut url) = make_ssl_server(handler);
+    url.as_mut_url().set_scheme("https").unwrap();
+
+    let (embedder_sender, embedder_receiver) = unbounded();
+    let mut embedder_proxy = create_embedder_proxy();
+    embedder_proxy.sender = embedder_sender;
+    let mut context = new_fetch_context(None, Some(embedder_proxy), None);
+    context.request_interceptors.set_pipeline_webview(
+        TEST_PIPELINE_ID,
+        Some(TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID)),
+    );
+
+    let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Script)
+        .origin(url.clone().origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let response = fetch_with_context(&mut request, &mut context);
+    assert!(matches!(
+        response.get_network_error(),
+        Some(NetworkError::SslValidation(..))
+    ));
+    assert!(embedder_receiver.try_recv().is_err());
+
+    let _ = server.close();
+}
+
 #[test]
 fn test_fetch_with_sri_network_error() {
     static MESSAGE: &'static [u8] = b"alert('Hello, Network Error');";
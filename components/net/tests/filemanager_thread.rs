@@ -2,13 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use embedder_traits::FilterPattern;
+use embedder_traits::{FilePickerRequest, FilterPattern};
 use ipc_channel::ipc;
+use msg::constellation_msg::{TopLevelBrowsingContextId, TEST_BROWSING_CONTEXT_ID};
 use net::filemanager_thread::FileManager;
 use net::resource_thread::CoreResourceThreadPool;
 use net_traits::blob_url_store::BlobURLStoreError;
@@ -16,6 +17,7 @@ use net_traits::filemanager_thread::{
     FileManagerThreadError, FileManagerThreadMsg, ReadFileProgress,
 };
 use servo_config::set_pref;
+use uuid::Uuid;
 
 use crate::create_embedder_proxy;
 
@@ -41,6 +43,7 @@ fn test_filemanager() {
         // Try to select a dummy file "components/net/tests/test.jpeg"
         let (tx, rx) = ipc::channel().unwrap();
         filemanager.handle(FileManagerThreadMsg::SelectFile(
+            TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID),
             patterns.clone(),
             tx,
             origin.clone(),
@@ -134,3 +137,51 @@ fn test_filemanager() {
         }
     }
 }
+
+#[test]
+fn test_filemanager_select_directory() {
+    let pool = CoreResourceThreadPool::new(1);
+    let pool_handle = Arc::new(pool);
+    let filemanager = FileManager::new(create_embedder_proxy(), Arc::downgrade(&pool_handle));
+    set_pref!(dom.testing.html_input_element.select_files.enabled, true);
+
+    let directory = std::env::temp_dir().join(format!("servo-upload-{}", Uuid::new_v4()));
+    fs::create_dir_all(directory.join("photos")).unwrap();
+    fs::write(directory.join("notes.txt"), "notes").unwrap();
+    fs::write(directory.join("photos").join("cat.jpeg"), "cat").unwrap();
+    let directory_name = directory.file_name().unwrap().to_str().unwrap().to_owned();
+
+    let request = FilePickerRequest {
+        filter_patterns: vec![],
+        multiple: false,
+        directory: true,
+    };
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::SelectFiles(
+        TopLevelBrowsingContextId(TEST_BROWSING_CONTEXT_ID),
+        request,
+        tx,
+        "test.com".to_string(),
+        Some(vec![directory.to_str().unwrap().to_owned()]),
+    ));
+    let selected = rx
+        .recv()
+        .expect("Broken channel")
+        .expect("The file manager failed to list the directory");
+    fs::remove_dir_all(&directory).unwrap();
+
+    // Every file of the directory is selected, with its path from the directory.
+    let relative_paths: Vec<_> = selected
+        .iter()
+        .map(|file| file.relative_path.clone())
+        .collect();
+    assert_eq!(
+        relative_paths,
+        vec![
+            format!("{}/notes.txt", directory_name),
+            format!("{}/photos/cat.jpeg", directory_name),
+        ]
+    );
+    assert_eq!(selected[1].filename, PathBuf::from("cat.jpeg"));
+    assert_eq!(selected[1].type_string, "image/jpeg".to_string());
+}
@@ -383,3 +383,38 @@ fn test_preload_hsts_domains_well_formed() {
     let hsts_list = HstsList::from_servo_preload();
     assert!(!hsts_list.entries_map.is_empty());
 }
+
+#[test]
+fn test_hsts_list_clear_data_keeps_preloaded_entries() {
+    let mut list = HstsList {
+        entries_map: HashMap::new(),
+    };
+    list.push(HstsEntry {
+        host: "mozilla.org".to_owned(),
+        include_subdomains: false,
+        max_age: None,
+        timestamp: None,
+    });
+    list.push(
+        HstsEntry::new("servo.org".to_owned(), IncludeSubdomains::NotIncluded, None).unwrap(),
+    );
+    list.push(
+        HstsEntry::new(
+            "example.com".to_owned(),
+            IncludeSubdomains::NotIncluded,
+            None,
+        )
+        .unwrap(),
+    );
+
+    list.clear_data(Some(&["servo.org".to_owned()]), None);
+    assert!(!list.is_host_secure("servo.org"));
+    assert!(list.is_host_secure("example.com"));
+
+    list.clear_data(None, Some(time::get_time().sec as u64 + 10));
+    assert!(list.is_host_secure("example.com"));
+
+    list.clear_data(None, None);
+    assert!(!list.is_host_secure("example.com"));
+    assert!(list.is_host_secure("mozilla.org"));
+}
@@ -15,6 +15,8 @@ mod hsts;
 mod http_cache;
 mod http_loader;
 mod mime_classifier;
+mod permission_store;
+mod quota_manager;
 mod resource_thread;
 mod subresource_integrity;
 
@@ -41,7 +43,9 @@ use net::filemanager_thread::FileManager;
 use net::resource_thread::CoreResourceThreadPool;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
+use net_traits::protocols::ProtocolRegistry;
 use net_traits::request::Request;
+use net_traits::request_interceptor::RequestInterceptors;
 use net_traits::response::Response;
 use net_traits::{FetchTaskTarget, ResourceFetchTiming, ResourceTimingType};
 use rustls::{self, Certificate, PrivateKey};
@@ -105,7 +109,10 @@ fn new_fetch_context(
     FetchContext {
         state: Arc::new(HttpState::default()),
         user_agent: DEFAULT_USER_AGENT.into(),
+        protocols: Arc::new(ProtocolRegistry::default()),
+        request_interceptors: RequestInterceptors::default(),
         devtools_chan: dc.map(|dc| Arc::new(Mutex::new(dc))),
+        embedder_proxy: sender.clone(),
         filemanager: Arc::new(Mutex::new(FileManager::new(
             sender,
             pool_handle.unwrap_or_else(|| Weak::new()),
@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::time::{Duration, SystemTime};
+
+use embedder_traits::{PermissionDecision, PermissionName, PermissionSetting};
+use net::permission_store::PermissionStore;
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+fn origin(url: &str) -> ImmutableOrigin {
+    ServoUrl::parse(url).unwrap().origin()
+}
+
+fn decision(
+    origin: ImmutableOrigin,
+    setting: PermissionSetting,
+    expires: Option<SystemTime>,
+) -> PermissionDecision {
+    PermissionDecision {
+        origin,
+        name: PermissionName::Geolocation,
+        setting,
+        expires,
+    }
+}
+
+#[test]
+fn test_permission_store_replaces_previous_decision() {
+    let mut store = PermissionStore::default();
+    let mozilla = origin("https://mozilla.org");
+    store.set(decision(mozilla.clone(), PermissionSetting::Granted, None));
+    store.set(decision(mozilla.clone(), PermissionSetting::Denied, None));
+
+    assert_eq!(
+        store.get(&mozilla, PermissionName::Geolocation),
+        Some(PermissionSetting::Denied)
+    );
+    assert_eq!(store.get(&mozilla, PermissionName::Camera), None);
+    assert_eq!(store.decisions().len(), 1);
+}
+
+#[test]
+fn test_permission_store_forgets_expired_decisions() {
+    let mut store = PermissionStore::default();
+    let mozilla = origin("https://mozilla.org");
+    let expired = SystemTime::now() - Duration::from_secs(10);
+    store.set(decision(
+        mozilla.clone(),
+        PermissionSetting::Granted,
+        Some(expired),
+    ));
+
+    assert_eq!(store.get(&mozilla, PermissionName::Geolocation), None);
+    assert!(store.decisions().is_empty());
+}
+
+#[test]
+fn test_permission_store_ignores_opaque_origins() {
+    let mut store = PermissionStore::default();
+    store.set(decision(
+        ImmutableOrigin::new_opaque(),
+        PermissionSetting::Granted,
+        None,
+    ));
+
+    assert!(store.decisions().is_empty());
+}
+
+#[test]
+fn test_permission_store_clears_decisions_for_origin() {
+    let mut store = PermissionStore::default();
+    let mozilla = origin("https://mozilla.org");
+    let servo = origin("https://servo.org");
+    store.set(decision(mozilla.clone(), PermissionSetting::Granted, None));
+    store.set(decision(servo.clone(), PermissionSetting::Denied, None));
+
+    store.clear(Some(&mozilla));
+    assert_eq!(store.get(&mozilla, PermissionName::Geolocation), None);
+    assert_eq!(
+        store.get(&servo, PermissionName::Geolocation),
+        Some(PermissionSetting::Denied)
+    );
+
+    store.clear(None);
+    assert!(store.decisions().is_empty());
+}
@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::quota_manager::QuotaManager;
+
+#[test]
+fn test_quota_manager_evicts_least_recently_used_origins() {
+    let mut quota = QuotaManager::default();
+    quota.set_usage("https://a.org", 40);
+    quota.set_usage("https://b.org", 40);
+    quota.set_usage("https://c.org", 40);
+    quota.touch("https://a.org");
+
+    assert_eq!(quota.total_usage(), 120);
+    assert!(quota.origins_to_evict("https://c.org", 120).is_empty());
+    assert_eq!(
+        quota.origins_to_evict("https://c.org", 80),
+        vec!["https://b.org".to_owned()]
+    );
+    assert_eq!(
+        quota.origins_to_evict("https://c.org", 40),
+        vec!["https://b.org".to_owned(), "https://a.org".to_owned()]
+    );
+}
+
+#[test]
+fn test_quota_manager_never_evicts_persisted_or_current_origins() {
+    let mut quota = QuotaManager::default();
+    quota.set_usage("https://a.org", 40);
+    quota.set_persisted("https://a.org", true);
+    quota.set_usage("https://b.org", 40);
+
+    assert!(quota.origins_to_evict("https://b.org", 0).is_empty());
+}
+
+#[test]
+fn test_quota_manager_forgets_empty_origins() {
+    let mut quota = QuotaManager::default();
+    quota.set_usage("https://a.org", 40);
+    quota.set_usage("https://a.org", 0);
+    assert_eq!(quota.usage("https://a.org"), 0);
+
+    quota.set_persisted("https://b.org", true);
+    quota.set_usage("https://b.org", 0);
+    assert!(quota.is_persisted("https://b.org"));
+}
+
+#[test]
+fn test_quota_manager_reconcile_uses_stored_sizes() {
+    let mut quota = QuotaManager::default();
+    quota.set_usage("https://a.org", 40);
+    quota.set_usage("https://b.org", 40);
+    quota.reconcile(vec![("https://a.org", 10), ("https://c.org", 20)].into_iter());
+
+    assert_eq!(quota.usage("https://a.org"), 10);
+    assert_eq!(quota.usage("https://b.org"), 0);
+    assert_eq!(quota.usage("https://c.org"), 20);
+    assert_eq!(quota.total_usage(), 30);
+}
@@ -3,11 +3,14 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::net::IpAddr;
+use std::sync::Arc;
 
 use ipc_channel::ipc;
 use net::connector::CACertificates;
 use net::resource_thread::new_core_resource_thread;
 use net::test::parse_hostsfile;
+use net_traits::protocols::ProtocolRegistry;
+use net_traits::request_interceptor::RequestInterceptors;
 use net_traits::CoreResourceMsg;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan;
@@ -25,6 +28,8 @@ fn test_exit() {
     let (sender, receiver) = ipc::channel().unwrap();
     let (resource_thread, _private_resource_thread) = new_core_resource_thread(
         "".into(),
+        Arc::new(ProtocolRegistry::default()),
+        RequestInterceptors::default(),
         None,
         ProfilerChan(tx),
         MemProfilerChan(mtx),
@@ -0,0 +1,288 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Caret browsing, where the keyboard moves a caret through the text of a document
+//! as if it could be edited, and holding shift extends the selection from the caret.
+//!
+//! Layout doesn't know where the characters of a run of text are, so moving up and
+//! down goes to the previous and next run of text instead of the previous and next
+//! line, and the position reported to the embedder is the box of the whole run.
+
+use embedder_traits::{CaretPosition, EmbedderMsg};
+use html5ever::local_name;
+use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::selection::Selection;
+use crate::dom::text::Text;
+use crate::textinput::{Direction, CMD_OR_CONTROL};
+
+#[derive(Clone, Copy)]
+enum Movement {
+    /// To the next or previous grapheme cluster.
+    Character(Direction),
+    /// Past the next word, or to the start of the previous one.
+    Word(Direction),
+    /// To the start of the next or previous run of text.
+    Run(Direction),
+    /// To the end or start of the current run of text.
+    RunBoundary(Direction),
+    /// To the end or start of the document.
+    DocumentBoundary(Direction),
+}
+
+/// A point in a run of text, with the offset in UTF-16 code units like the offsets
+/// of the selection API.
+struct Caret {
+    text: DomRoot<Text>,
+    offset: u32,
+}
+
+/// Move the caret in `document` if `event` is a caret browsing key, returning whether
+/// it was. Only called while caret browsing and when no text control is focused.
+pub(crate) fn handle_key(document: &Document, event: &KeyboardEvent) -> bool {
+    let mut mods = event.modifiers;
+    let extend = mods.contains(Modifiers::SHIFT);
+    mods.remove(Modifiers::SHIFT);
+    let movement = ShortcutMatcher::new(event.state, event.key.clone(), mods)
+        .shortcut(Modifiers::empty(), Key::ArrowLeft, || {
+            Some(Movement::Character(Direction::Backward))
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowRight, || {
+            Some(Movement::Character(Direction::Forward))
+        })
+        .shortcut(Modifiers::ALT, Key::ArrowLeft, || {
+            Some(Movement::Word(Direction::Backward))
+        })
+        .shortcut(Modifiers::ALT, Key::ArrowRight, || {
+            Some(Movement::Word(Direction::Forward))
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowUp, || {
+            Some(Movement::Run(Direction::Backward))
+        })
+        .shortcut(Modifiers::empty(), Key::ArrowDown, || {
+            Some(Movement::Run(Direction::Forward))
+        })
+        .shortcut(Modifiers::empty(), Key::Home, || {
+            Some(Movement::RunBoundary(Direction::Backward))
+        })
+        .shortcut(Modifiers::empty(), Key::End, || {
+            Some(Movement::RunBoundary(Direction::Forward))
+        })
+        .shortcut(CMD_OR_CONTROL, Key::Home, || {
+            Some(Movement::DocumentBoundary(Direction::Backward))
+        })
+        .shortcut(CMD_OR_CONTROL, Key::End, || {
+            Some(Movement::DocumentBoundary(Direction::Forward))
+        })
+        .otherwise(|| None)
+        .flatten();
+    let movement = match movement {
+        Some(movement) => movement,
+        None => return false,
+    };
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+
+    // Without a caret in a run of text, the first key press puts it at the start
+    // of the document.
+    let caret = match current_caret(&selection) {
+        Some(caret) => move_caret(document, caret, movement),
+        None => first_run(document).map(|text| Caret { text, offset: 0 }),
+    };
+    let caret = match caret {
+        Some(caret) => caret,
+        None => return false,
+    };
+
+    let node = caret.text.upcast::<Node>();
+    if extend && selection.RangeCount() > 0 {
+        let _ = selection.Extend(node, caret.offset);
+    } else {
+        let _ = selection.Collapse(Some(node), caret.offset);
+    }
+    send_caret_position(document, &selection, &caret);
+    true
+}
+
+fn current_caret(selection: &Selection) -> Option<Caret> {
+    let node = selection.GetFocusNode()?;
+    let text = DomRoot::downcast::<Text>(node)?;
+    if !is_browsable(&text) {
+        return None;
+    }
+    Some(Caret {
+        text,
+        offset: selection.FocusOffset(),
+    })
+}
+
+fn move_caret(document: &Document, caret: Caret, movement: Movement) -> Option<Caret> {
+    let data = String::from(caret.text.upcast::<CharacterData>().Data());
+    let index = utf16_to_utf8(&data, caret.offset);
+    let new_index = match movement {
+        Movement::Character(Direction::Forward) => data[index..]
+            .graphemes(true)
+            .next()
+            .map(|g| index + g.len()),
+        Movement::Character(Direction::Backward) => data[..index]
+            .graphemes(true)
+            .next_back()
+            .map(|g| index - g.len()),
+        Movement::Word(Direction::Forward) => data[index..]
+            .unicode_word_indices()
+            .next()
+            .map(|(start, word)| index + start + word.len()),
+        Movement::Word(Direction::Backward) => data[..index]
+            .unicode_word_indices()
+            .next_back()
+            .map(|(start, _)| start),
+        Movement::RunBoundary(Direction::Forward) => Some(data.len()),
+        Movement::RunBoundary(Direction::Backward) => Some(0),
+        Movement::Run(direction) => {
+            return adjacent_run(document, &caret.text, direction)
+                .map(|text| Caret { text, offset: 0 });
+        },
+        Movement::DocumentBoundary(Direction::Forward) => {
+            return last_run(document).map(|text| {
+                let offset = text.upcast::<CharacterData>().Length();
+                Caret { text, offset }
+            });
+        },
+        Movement::DocumentBoundary(Direction::Backward) => {
+            return first_run(document).map(|text| Caret { text, offset: 0 });
+        },
+    };
+
+    match new_index {
+        Some(new_index) => Some(Caret {
+            offset: utf8_to_utf16(&data, new_index),
+            text: caret.text,
+        }),
+        // Moving past the end of a run of text continues in the next one, as its
+        // start is at the same place.
+        None => {
+            let direction = match movement {
+                Movement::Character(direction) | Movement::Word(direction) => direction,
+                _ => return Some(caret),
+            };
+            let text = match adjacent_run(document, &caret.text, direction) {
+                Some(text) => text,
+                None => return Some(caret),
+            };
+            let offset = match direction {
+                Direction::Forward => 0,
+                Direction::Backward => text.upcast::<CharacterData>().Length(),
+            };
+            Some(move_caret(document, Caret { text, offset }, movement).unwrap_or(caret))
+        },
+    }
+}
+
+/// Whether the caret can be put in `text`, which must be visible text in the body
+/// of the document rather than white space between elements or the source of a
+/// script or style sheet.
+pub(crate) fn is_browsable(text: &Text) -> bool {
+    if text.upcast::<CharacterData>().Data().trim().is_empty() {
+        return false;
+    }
+    !text
+        .upcast::<Node>()
+        .ancestors()
+        .filter_map(DomRoot::downcast::<Element>)
+        .any(|element| {
+            matches!(
+                *element.local_name(),
+                local_name!("head") |
+                    local_name!("script") |
+                    local_name!("style") |
+                    local_name!("template") |
+                    local_name!("noscript") |
+                    local_name!("textarea") |
+                    local_name!("select")
+            )
+        })
+}
+
+fn first_run(document: &Document) -> Option<DomRoot<Text>> {
+    let body = document.GetBody()?;
+    let body = body.upcast::<Node>();
+    body.traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Text>)
+        .find(|text| is_browsable(text))
+}
+
+fn last_run(document: &Document) -> Option<DomRoot<Text>> {
+    let body = document.GetBody()?;
+    let body = body.upcast::<Node>();
+    body.traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Text>)
+        .filter(|text| is_browsable(text))
+        .last()
+}
+
+fn adjacent_run(document: &Document, text: &Text, direction: Direction) -> Option<DomRoot<Text>> {
+    let body = document.GetBody()?;
+    let body = body.upcast::<Node>();
+    let node = text.upcast::<Node>();
+    match direction {
+        Direction::Forward => node
+            .following_nodes(body)
+            .filter_map(DomRoot::downcast::<Text>)
+            .find(|text| is_browsable(text)),
+        Direction::Backward => node
+            .preceding_nodes(body)
+            .filter_map(DomRoot::downcast::<Text>)
+            .find(|text| is_browsable(text)),
+    }
+}
+
+fn send_caret_position(document: &Document, selection: &Selection, caret: &Caret) {
+    let node = caret.text.upcast::<Node>();
+    let rect = node.content_boxes().into_iter().next().or_else(|| {
+        node.GetParentElement()
+            .and_then(|parent| parent.upcast::<Node>().bounding_content_box())
+    });
+    let selected_text = if selection.IsCollapsed() {
+        String::new()
+    } else {
+        String::from(selection.Stringifier())
+    };
+    let position = CaretPosition {
+        text: String::from(caret.text.upcast::<CharacterData>().Data()),
+        offset: caret.offset as usize,
+        selected_text,
+        rect: rect.map(|rect| document.viewport_device_rect(rect)),
+    };
+    document
+        .window()
+        .send_to_embedder(EmbedderMsg::CaretMoved(position));
+}
+
+fn utf16_to_utf8(text: &str, offset: u32) -> usize {
+    let mut utf16_len = 0;
+    for (index, c) in text.char_indices() {
+        if utf16_len >= offset as usize {
+            return index;
+        }
+        utf16_len += c.len_utf16();
+    }
+    text.len()
+}
+
+pub(crate) fn utf8_to_utf16(text: &str, index: usize) -> u32 {
+    text[..index].chars().map(char::len_utf16).sum::<usize>() as u32
+}
@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::SensorType;
+use js::rust::HandleObject;
+use script_traits::SensorReading;
+
+use crate::dom::bindings::codegen::Bindings::AccelerometerBinding::AccelerometerMethods;
+use crate::dom::bindings::codegen::Bindings::SensorBinding::SensorOptions;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::sensor::Sensor;
+use crate::dom::window::Window;
+
+#[dom_struct]
+pub struct Accelerometer {
+    sensor: Sensor,
+}
+
+impl Accelerometer {
+    fn new_inherited(options: &SensorOptions) -> Accelerometer {
+        Accelerometer {
+            sensor: Sensor::new_inherited(SensorType::Accelerometer, options),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &SensorOptions,
+    ) -> DomRoot<Accelerometer> {
+        reflect_dom_object_with_proto(
+            Box::new(Accelerometer::new_inherited(options)),
+            window,
+            proto,
+        )
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &SensorOptions,
+    ) -> DomRoot<Accelerometer> {
+        Accelerometer::new(window, proto, options)
+    }
+}
+
+impl AccelerometerMethods for Accelerometer {
+    /// <https://w3c.github.io/accelerometer/#dom-accelerometer-x>
+    fn GetX(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::Accelerometer { x, .. }) => Some(Finite::wrap(x)),
+            _ => None,
+        }
+    }
+
+    /// <https://w3c.github.io/accelerometer/#dom-accelerometer-y>
+    fn GetY(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::Accelerometer { y, .. }) => Some(Finite::wrap(y)),
+            _ => None,
+        }
+    }
+
+    /// <https://w3c.github.io/accelerometer/#dom-accelerometer-z>
+    fn GetZ(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::Accelerometer { z, .. }) => Some(Finite::wrap(z)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::SensorType;
+use js::rust::HandleObject;
+use script_traits::SensorReading;
+
+use crate::dom::bindings::codegen::Bindings::AmbientLightSensorBinding::AmbientLightSensorMethods;
+use crate::dom::bindings::codegen::Bindings::SensorBinding::SensorOptions;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::sensor::Sensor;
+use crate::dom::window::Window;
+
+#[dom_struct]
+pub struct AmbientLightSensor {
+    sensor: Sensor,
+}
+
+impl AmbientLightSensor {
+    fn new_inherited(options: &SensorOptions) -> AmbientLightSensor {
+        AmbientLightSensor {
+            sensor: Sensor::new_inherited(SensorType::AmbientLight, options),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &SensorOptions,
+    ) -> DomRoot<AmbientLightSensor> {
+        reflect_dom_object_with_proto(
+            Box::new(AmbientLightSensor::new_inherited(options)),
+            window,
+            proto,
+        )
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &SensorOptions,
+    ) -> DomRoot<AmbientLightSensor> {
+        AmbientLightSensor::new(window, proto, options)
+    }
+}
+
+impl AmbientLightSensorMethods for AmbientLightSensor {
+    /// <https://w3c.github.io/ambient-light/#dom-ambientlightsensor-illuminance>
+    fn GetIlluminance(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::AmbientLight { illuminance }) => Some(Finite::wrap(illuminance)),
+            _ => None,
+        }
+    }
+}
@@ -87,6 +87,7 @@ impl AudioContext {
         let pipeline_id = window.pipeline_id();
         let context = AudioContext::new_inherited(options, pipeline_id);
         let context = reflect_dom_object_with_proto(Box::new(context), window, proto);
+        window.track_audio_context(&context);
         context.resume();
         context
     }
@@ -43,9 +43,11 @@ use crate::dom::bindings::codegen::Bindings::ChannelMergerNodeBinding::ChannelMe
 use crate::dom::bindings::codegen::Bindings::ChannelSplitterNodeBinding::ChannelSplitterOptions;
 use crate::dom::bindings::codegen::Bindings::ConstantSourceNodeBinding::ConstantSourceOptions;
 use crate::dom::bindings::codegen::Bindings::GainNodeBinding::GainOptions;
+use crate::dom::bindings::codegen::Bindings::IIRFilterNodeBinding::IIRFilterOptions;
 use crate::dom::bindings::codegen::Bindings::OscillatorNodeBinding::OscillatorOptions;
 use crate::dom::bindings::codegen::Bindings::PannerNodeBinding::PannerOptions;
 use crate::dom::bindings::codegen::Bindings::StereoPannerNodeBinding::StereoPannerOptions;
+use crate::dom::bindings::codegen::Bindings::WaveShaperNodeBinding::WaveShaperOptions;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
@@ -59,10 +61,12 @@ use crate::dom::constantsourcenode::ConstantSourceNode;
 use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::gainnode::GainNode;
+use crate::dom::iirfilternode::IIRFilterNode;
 use crate::dom::oscillatornode::OscillatorNode;
 use crate::dom::pannernode::PannerNode;
 use crate::dom::promise::Promise;
 use crate::dom::stereopannernode::StereoPannerNode;
+use crate::dom::waveshapernode::WaveShaperNode;
 use crate::dom::window::Window;
 use crate::realms::InRealm;
 use crate::task_source::TaskSource;
@@ -222,6 +226,21 @@ impl BaseAudioContext {
     /// Set audio context state
     pub fn set_state_attribute(&self, state: AudioContextState) {
         self.state.set(state);
+        self.global().as_window().update_audio_state();
+    }
+
+    /// Stop or restart rendering while the webview is muted. The state attribute is not
+    /// changed, so muting is invisible to content apart from the current time stopping.
+    pub fn set_muted(&self, muted: bool) {
+        if self.state.get() != AudioContextState::Running {
+            return;
+        }
+        let audio_context = self.audio_context_impl.lock().unwrap();
+        let _ = if muted {
+            audio_context.suspend()
+        } else {
+            audio_context.resume()
+        };
     }
 
     pub fn resume(&self) {
@@ -230,8 +249,14 @@ impl BaseAudioContext {
         let task_source = window.task_manager().dom_manipulation_task_source();
         let this = Trusted::new(self);
         // Set the rendering thread state to 'running' and start
-        // rendering the audio graph.
-        match self.audio_context_impl.lock().unwrap().resume() {
+        // rendering the audio graph, unless the webview is muted, in which case
+        // rendering starts once it is unmuted.
+        let result = if window.audio_muted() {
+            Ok(())
+        } else {
+            self.audio_context_impl.lock().unwrap().resume()
+        };
+        match result {
             Ok(()) => {
                 self.take_pending_resume_promises(Ok(()));
                 let _ = task_source.queue(
@@ -239,7 +264,7 @@ impl BaseAudioContext {
                         let this = this.root();
                         this.fulfill_in_flight_resume_promises(|| {
                             if this.state.get() != AudioContextState::Running {
-                                this.state.set(AudioContextState::Running);
+                                this.set_state_attribute(AudioContextState::Running);
                                 let window = DomRoot::downcast::<Window>(this.global()).unwrap();
                                 window.task_manager().dom_manipulation_task_source().queue_simple_event(
                                     this.upcast(),
@@ -370,6 +395,29 @@ impl BaseAudioContextMethods for BaseAudioContext {
         )
     }
 
+    /// <https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createiirfilter>
+    fn CreateIIRFilter(
+        &self,
+        feedforward: Vec<Finite<f64>>,
+        feedback: Vec<Finite<f64>>,
+    ) -> Fallible<DomRoot<IIRFilterNode>> {
+        let opts = IIRFilterOptions {
+            parent: AudioNodeOptions::empty(),
+            feedforward,
+            feedback,
+        };
+        IIRFilterNode::new(&self.global().as_window(), &self, &opts)
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createwaveshaper>
+    fn CreateWaveShaper(&self) -> Fallible<DomRoot<WaveShaperNode>> {
+        WaveShaperNode::new(
+            &self.global().as_window(),
+            &self,
+            &WaveShaperOptions::empty(),
+        )
+    }
+
     /// <https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createstereopanner>
     fn CreateStereoPanner(&self) -> Fallible<DomRoot<StereoPannerNode>> {
         StereoPannerNode::new(
@@ -174,7 +174,7 @@ pub unsafe fn create_global_object(
 }
 
 /// Choose the compartment to create a new global object in.
-fn select_compartment(cx: SafeJSContext, options: &mut RealmOptions) {
+pub fn select_compartment(cx: SafeJSContext, options: &mut RealmOptions) {
     type Data = *mut Compartment;
     unsafe extern "C" fn callback(
         _cx: *mut JSContext,
@@ -0,0 +1,160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::{
+    DeviceMotionEventInit, DeviceMotionEventMethods,
+};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::devicemotioneventacceleration::DeviceMotionEventAcceleration;
+use crate::dom::devicemotioneventrotationrate::DeviceMotionEventRotationRate;
+use crate::dom::event::Event;
+use crate::dom::window::Window;
+
+// https://w3c.github.io/deviceorientation/#devicemotion
+#[dom_struct]
+pub struct DeviceMotionEvent {
+    event: Event,
+    acceleration: Option<Dom<DeviceMotionEventAcceleration>>,
+    acceleration_including_gravity: Option<Dom<DeviceMotionEventAcceleration>>,
+    rotation_rate: Option<Dom<DeviceMotionEventRotationRate>>,
+    interval: f64,
+}
+
+impl DeviceMotionEvent {
+    fn new_inherited(
+        acceleration: Option<&DeviceMotionEventAcceleration>,
+        acceleration_including_gravity: Option<&DeviceMotionEventAcceleration>,
+        rotation_rate: Option<&DeviceMotionEventRotationRate>,
+        interval: f64,
+    ) -> DeviceMotionEvent {
+        DeviceMotionEvent {
+            event: Event::new_inherited(),
+            acceleration: acceleration.map(Dom::from_ref),
+            acceleration_including_gravity: acceleration_including_gravity.map(Dom::from_ref),
+            rotation_rate: rotation_rate.map(Dom::from_ref),
+            interval,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        acceleration: Option<&DeviceMotionEventAcceleration>,
+        acceleration_including_gravity: Option<&DeviceMotionEventAcceleration>,
+        rotation_rate: Option<&DeviceMotionEventRotationRate>,
+        interval: f64,
+    ) -> DomRoot<DeviceMotionEvent> {
+        Self::new_with_proto(
+            window,
+            None,
+            type_,
+            bubbles,
+            cancelable,
+            acceleration,
+            acceleration_including_gravity,
+            rotation_rate,
+            interval,
+        )
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        acceleration: Option<&DeviceMotionEventAcceleration>,
+        acceleration_including_gravity: Option<&DeviceMotionEventAcceleration>,
+        rotation_rate: Option<&DeviceMotionEventRotationRate>,
+        interval: f64,
+    ) -> DomRoot<DeviceMotionEvent> {
+        let ev = reflect_dom_object_with_proto(
+            Box::new(DeviceMotionEvent::new_inherited(
+                acceleration,
+                acceleration_including_gravity,
+                rotation_rate,
+                interval,
+            )),
+            window,
+            proto,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &DeviceMotionEventInit,
+    ) -> DomRoot<DeviceMotionEvent> {
+        let acceleration = init
+            .acceleration
+            .as_ref()
+            .map(|init| DeviceMotionEventAcceleration::new_from_init(window, init));
+        let acceleration_including_gravity = init
+            .accelerationIncludingGravity
+            .as_ref()
+            .map(|init| DeviceMotionEventAcceleration::new_from_init(window, init));
+        let rotation_rate = init
+            .rotationRate
+            .as_ref()
+            .map(|init| DeviceMotionEventRotationRate::new_from_init(window, init));
+        DeviceMotionEvent::new_with_proto(
+            window,
+            proto,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            acceleration.as_deref(),
+            acceleration_including_gravity.as_deref(),
+            rotation_rate.as_deref(),
+            *init.interval,
+        )
+    }
+}
+
+impl DeviceMotionEventMethods for DeviceMotionEvent {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-acceleration
+    fn GetAcceleration(&self) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+        self.acceleration.as_deref().map(DomRoot::from_ref)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-accelerationincludinggravity
+    fn GetAccelerationIncludingGravity(&self) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+        self.acceleration_including_gravity
+            .as_deref()
+            .map(DomRoot::from_ref)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-rotationrate
+    fn GetRotationRate(&self) -> Option<DomRoot<DeviceMotionEventRotationRate>> {
+        self.rotation_rate.as_deref().map(DomRoot::from_ref)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-interval
+    fn Interval(&self) -> Finite<f64> {
+        Finite::wrap(self.interval)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::{
+    DeviceMotionEventAccelerationInit, DeviceMotionEventAccelerationMethods,
+};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+
+// https://w3c.github.io/deviceorientation/#devicemotioneventacceleration
+#[dom_struct]
+pub struct DeviceMotionEventAcceleration {
+    reflector_: Reflector,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+}
+
+impl DeviceMotionEventAcceleration {
+    fn new_inherited(
+        x: Option<f64>,
+        y: Option<f64>,
+        z: Option<f64>,
+    ) -> DeviceMotionEventAcceleration {
+        DeviceMotionEventAcceleration {
+            reflector_: Reflector::new(),
+            x,
+            y,
+            z,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        x: Option<f64>,
+        y: Option<f64>,
+        z: Option<f64>,
+    ) -> DomRoot<DeviceMotionEventAcceleration> {
+        reflect_dom_object(
+            Box::new(DeviceMotionEventAcceleration::new_inherited(x, y, z)),
+            window,
+        )
+    }
+
+    pub fn new_from_init(
+        window: &Window,
+        init: &DeviceMotionEventAccelerationInit,
+    ) -> DomRoot<DeviceMotionEventAcceleration> {
+        DeviceMotionEventAcceleration::new(
+            window,
+            init.x.map(|x| *x),
+            init.y.map(|y| *y),
+            init.z.map(|z| *z),
+        )
+    }
+}
+
+impl DeviceMotionEventAccelerationMethods for DeviceMotionEventAcceleration {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventacceleration-x
+    fn GetX(&self) -> Option<Finite<f64>> {
+        self.x.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventacceleration-y
+    fn GetY(&self) -> Option<Finite<f64>> {
+        self.y.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventacceleration-z
+    fn GetZ(&self) -> Option<Finite<f64>> {
+        self.z.map(Finite::wrap)
+    }
+}
@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::{
+    DeviceMotionEventRotationRateInit, DeviceMotionEventRotationRateMethods,
+};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+
+// https://w3c.github.io/deviceorientation/#devicemotioneventrotationrate
+#[dom_struct]
+pub struct DeviceMotionEventRotationRate {
+    reflector_: Reflector,
+    alpha: Option<f64>,
+    beta: Option<f64>,
+    gamma: Option<f64>,
+}
+
+impl DeviceMotionEventRotationRate {
+    fn new_inherited(
+        alpha: Option<f64>,
+        beta: Option<f64>,
+        gamma: Option<f64>,
+    ) -> DeviceMotionEventRotationRate {
+        DeviceMotionEventRotationRate {
+            reflector_: Reflector::new(),
+            alpha,
+            beta,
+            gamma,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        alpha: Option<f64>,
+        beta: Option<f64>,
+        gamma: Option<f64>,
+    ) -> DomRoot<DeviceMotionEventRotationRate> {
+        reflect_dom_object(
+            Box::new(DeviceMotionEventRotationRate::new_inherited(
+                alpha, beta, gamma,
+            )),
+            window,
+        )
+    }
+
+    pub fn new_from_init(
+        window: &Window,
+        init: &DeviceMotionEventRotationRateInit,
+    ) -> DomRoot<DeviceMotionEventRotationRate> {
+        DeviceMotionEventRotationRate::new(
+            window,
+            init.alpha.map(|alpha| *alpha),
+            init.beta.map(|beta| *beta),
+            init.gamma.map(|gamma| *gamma),
+        )
+    }
+}
+
+impl DeviceMotionEventRotationRateMethods for DeviceMotionEventRotationRate {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventrotationrate-alpha
+    fn GetAlpha(&self) -> Option<Finite<f64>> {
+        self.alpha.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventrotationrate-beta
+    fn GetBeta(&self) -> Option<Finite<f64>> {
+        self.beta.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotioneventrotationrate-gamma
+    fn GetGamma(&self) -> Option<Finite<f64>> {
+        self.gamma.map(Finite::wrap)
+    }
+}
@@ -0,0 +1,124 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::DeviceOrientationEventBinding::{
+    DeviceOrientationEventInit, DeviceOrientationEventMethods,
+};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::window::Window;
+
+/// The orientation of the device, as Euler angles in degrees.
+#[derive(Clone, Copy, Default, JSTraceable, MallocSizeOf)]
+pub struct DeviceOrientation {
+    pub alpha: Option<f64>,
+    pub beta: Option<f64>,
+    pub gamma: Option<f64>,
+    pub absolute: bool,
+}
+
+// https://w3c.github.io/deviceorientation/#deviceorientation
+#[dom_struct]
+pub struct DeviceOrientationEvent {
+    event: Event,
+    orientation: DeviceOrientation,
+}
+
+impl DeviceOrientationEvent {
+    fn new_inherited(orientation: DeviceOrientation) -> DeviceOrientationEvent {
+        DeviceOrientationEvent {
+            event: Event::new_inherited(),
+            orientation,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        orientation: DeviceOrientation,
+    ) -> DomRoot<DeviceOrientationEvent> {
+        Self::new_with_proto(window, None, type_, bubbles, cancelable, orientation)
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        orientation: DeviceOrientation,
+    ) -> DomRoot<DeviceOrientationEvent> {
+        let ev = reflect_dom_object_with_proto(
+            Box::new(DeviceOrientationEvent::new_inherited(orientation)),
+            window,
+            proto,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &DeviceOrientationEventInit,
+    ) -> DomRoot<DeviceOrientationEvent> {
+        let orientation = DeviceOrientation {
+            alpha: init.alpha.map(|alpha| *alpha),
+            beta: init.beta.map(|beta| *beta),
+            gamma: init.gamma.map(|gamma| *gamma),
+            absolute: init.absolute,
+        };
+        DeviceOrientationEvent::new_with_proto(
+            window,
+            proto,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            orientation,
+        )
+    }
+}
+
+impl DeviceOrientationEventMethods for DeviceOrientationEvent {
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-alpha
+    fn GetAlpha(&self) -> Option<Finite<f64>> {
+        self.orientation.alpha.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-beta
+    fn GetBeta(&self) -> Option<Finite<f64>> {
+        self.orientation.beta.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-gamma
+    fn GetGamma(&self) -> Option<Finite<f64>> {
+        self.orientation.gamma.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-absolute
+    fn Absolute(&self) -> bool {
+        self.orientation.absolute
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -14,21 +14,26 @@ use std::rc::Rc;
 use std::slice::from_ref;
 use std::time::{Duration, Instant};
 
+use app_units::Au;
 use canvas_traits::webgl::{self, WebGLContextId, WebGLMsg};
 use content_security_policy::{self as csp, CspList};
 use cookie::Cookie;
 use cssparser::match_ignore_ascii_case;
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{
+    ContextMenuAction, ContextMenuData, EmbedderMsg, ImeEvent, NavigationEvent, NavigationStep,
+    PictureInPictureEvent as EmbedderPictureInPictureEvent, SavedImage, SelectionInfo,
+    SurroundingText, UserScript, UserScriptInjectionTime,
+};
 use encoding_rs::{Encoding, UTF_8};
 use euclid::default::{Point2D, Rect, Size2D};
 use html5ever::{local_name, namespace_url, ns, LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
-use js::jsapi::JSObject;
+use js::jsapi::{Heap, JSObject};
 use js::rust::HandleObject;
-use keyboard_types::{Code, Key, KeyState};
+use keyboard_types::{Code, Key, KeyState, Modifiers};
 use lazy_static::lazy_static;
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
@@ -44,13 +49,14 @@ use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
 use net_traits::{FetchResponseMsg, IpcSend, ReferrerPolicy};
 use num_traits::ToPrimitive;
 use percent_encoding::percent_decode;
+use pixels::PixelFormat;
 use profile_traits::ipc as profile_ipc;
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType};
 use script_layout_interface::message::{Msg, PendingRestyle, ReflowGoal};
 use script_layout_interface::TrustedNodeAddress;
 use script_traits::{
-    AnimationState, DocumentActivity, MouseButton, MouseEventType, MsDuration, ScriptMsg,
-    TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
+    AnimationState, DocumentActivity, EditingCommand, MouseButton, MouseEventType, MsDuration,
+    PictureInPictureAction, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
 };
 use servo_arc::Arc;
 use servo_atoms::Atom;
@@ -68,16 +74,17 @@ use style::stylesheet_set::DocumentStylesheetSet;
 use style::stylesheets::{Origin, OriginSet, Stylesheet};
 use url::Host;
 use uuid::Uuid;
-use webrender_api::units::DeviceIntRect;
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 
 use super::bindings::trace::{HashMapTracedValues, NoTrace};
 use crate::animation_timeline::AnimationTimeline;
 use crate::animations::Animations;
+use crate::caret_browsing;
 use crate::document_loader::{DocumentLoader, LoadType};
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
-use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref, RefMut};
+use crate::dom::bindings::cell::{DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEvent_Binding::BeforeUnloadEventMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
@@ -85,11 +92,15 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
 use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElement_Binding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::HTMLVideoElementMethods;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::Navigator_Binding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -140,10 +151,12 @@ use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmlmediaelement::{HTMLMediaElement, ReadyState};
 use crate::dom::htmlmetaelement::RefreshRedirectDue;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::htmltitleelement::HTMLTitleElement;
+use crate::dom::htmlvideoelement::HTMLVideoElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::location::Location;
 use crate::dom::messageevent::MessageEvent;
@@ -155,6 +168,8 @@ use crate::dom::node::{
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::pictureinpictureevent::PictureInPictureEvent;
+use crate::dom::pictureinpicturewindow::PictureInPictureWindow;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
@@ -169,13 +184,16 @@ use crate::dom::touchevent::TouchEvent;
 use crate::dom::touchlist::TouchList;
 use crate::dom::treewalker::TreeWalker;
 use crate::dom::uievent::UIEvent;
+use crate::dom::userscripts;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
+use crate::favicon;
 use crate::fetch::FetchCanceller;
-use crate::realms::{AlreadyInRealm, InRealm};
+use crate::find_in_page::FindMatch;
+use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
 use crate::script_runtime::{CommonScriptMsg, JSContext, ScriptThreadEventCategory};
 use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::stylesheet_set::StylesheetSetRef;
@@ -192,6 +210,9 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// The number of seconds the seek controls of the picture-in-picture surface skip.
+const PICTURE_IN_PICTURE_SEEK_OFFSET: f64 = 10.;
+
 pub enum TouchEventResult {
     Processed(bool),
     Forwarded,
@@ -295,6 +316,12 @@ pub struct Document {
     focus_transaction: DomRefCell<FocusTransaction>,
     /// The element that currently has the document focus context.
     focused: MutNullableDom<Element>,
+    /// Whether this document is in the focus chain of the webview with system focus.
+    has_system_focus: Cell<bool>,
+    /// Whether the input method of the embedder is composing text in the focused element.
+    ime_composing: Cell<bool>,
+    /// The element the last context menu of this document was opened on.
+    context_menu_target: MutNullableDom<Element>,
     /// The script element that is currently executing.
     current_script: MutNullableDom<HTMLScriptElement>,
     /// <https://html.spec.whatwg.org/multipage/#pending-parsing-blocking-script>
@@ -386,6 +413,15 @@ pub struct Document {
     dom_count: Cell<u32>,
     /// Entry node for fullscreen.
     fullscreen_element: MutNullableDom<Element>,
+    /// The element that the embedder was asked to show fullscreen, until it answers.
+    pending_fullscreen_element: MutNullableDom<Element>,
+    /// The promise of the request to show `pending_fullscreen_element` fullscreen.
+    #[ignore_malloc_size_of = "promises are hard"]
+    pending_fullscreen_promise: DomRefCell<Option<Rc<Promise>>>,
+    /// <https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement>
+    picture_in_picture_element: MutNullableDom<HTMLVideoElement>,
+    /// The window of the picture-in-picture video, if any.
+    picture_in_picture_window: MutNullableDom<PictureInPictureWindow>,
     /// Map from ID to set of form control elements that have that ID as
     /// their 'form' content attribute. Used to reset form controls
     /// whenever any element with the same ID as the form attribute
@@ -438,6 +474,20 @@ pub struct Document {
     #[ignore_malloc_size_of = "Defined in rust-content-security-policy"]
     #[no_trace]
     csp_list: DomRefCell<Option<CspList>>,
+    /// The policy that the embedder enforces in every document of the webview, in addition
+    /// to the ones in `csp_list`.
+    #[ignore_malloc_size_of = "Defined in rust-content-security-policy"]
+    #[no_trace]
+    embedder_csp_list: DomRefCell<Option<CspList>>,
+    /// The scripts and stylesheets that the embedder injects into this document, if it
+    /// matches them.
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    #[no_trace]
+    user_scripts: DomRefCell<Vec<UserScript>>,
+    /// The global of the isolated world of the scripts that the embedder injects, once
+    /// one of them ran.
+    #[ignore_malloc_size_of = "mozjs"]
+    isolated_world: Heap<*mut JSObject>,
     /// <https://w3c.github.io/slection-api/#dfn-selection>
     selection: MutNullableDom<Selection>,
     /// A timeline for animations which is used for synchronizing animations.
@@ -449,6 +499,17 @@ pub struct Document {
     dirty_root: MutNullableDom<Element>,
     /// <https://html.spec.whatwg.org/multipage/#will-declaratively-refresh>
     declarative_refresh: DomRefCell<Option<DeclarativeRefresh>>,
+    /// Whether the embedder enabled caret browsing in the webview of this document.
+    caret_browsing: Cell<bool>,
+    /// The matches of the last search of the embedder in this document, in tree order,
+    /// until the search is stopped.
+    find_matches: DomRefCell<Option<Vec<FindMatch>>>,
+    /// The index of the active match of the last search, if it is in this document.
+    find_active_match: Cell<Option<usize>>,
+    /// The URL of the favicon that was last picked for the embedder, if this is a
+    /// top-level document.
+    #[no_trace]
+    favicon_url: DomRefCell<Option<ServoUrl>>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -1123,7 +1184,7 @@ impl Document {
             let node = elem.upcast::<Node>();
             elem.set_focus_state(false);
             // FIXME: pass appropriate relatedTarget
-            self.fire_focus_event(FocusEventType::Blur, node, None);
+            self.fire_focus_event(FocusEventType::Blur, node.upcast(), None);
 
             // Notify the embedder to hide the input method.
             if elem.input_method_type().is_some() {
@@ -1132,12 +1193,13 @@ impl Document {
         }
 
         self.focused.set(possibly_focused.as_ref().map(|e| &**e));
+        self.ime_composing.set(false);
 
         if let Some(ref elem) = self.focused.get() {
             elem.set_focus_state(true);
             let node = elem.upcast::<Node>();
             // FIXME: pass appropriate relatedTarget
-            self.fire_focus_event(FocusEventType::Focus, node, None);
+            self.fire_focus_event(FocusEventType::Focus, node.upcast(), None);
             // Update the focus state for all elements in the focus chain.
             // https://html.spec.whatwg.org/multipage/#focus-chain
             if focus_type == FocusType::Element {
@@ -1213,6 +1275,134 @@ impl Document {
         window.send_to_embedder(msg);
     }
 
+    /// Tell the embedder about a step of the navigation that loaded this document.
+    fn send_navigation_event(&self, step: NavigationStep) {
+        let browsing_context_id = match self.browsing_context() {
+            Some(window_proxy) => window_proxy.browsing_context_id(),
+            None => return,
+        };
+        self.send_to_embedder(EmbedderMsg::NavigationEvent(NavigationEvent {
+            browsing_context_id,
+            pipeline_id: self.window().pipeline_id(),
+            url: self.url(),
+            step,
+        }));
+    }
+
+    /// Run an editing command from the embedder on the focused text control, or on the
+    /// selection of this document when no text control is focused.
+    pub fn handle_editing_command(&self, command: EditingCommand) {
+        if let Some(elem) = self.get_focused_element() {
+            if let Some(input) = elem.downcast::<HTMLInputElement>() {
+                return input.handle_editing_command(command);
+            }
+            if let Some(textarea) = elem.downcast::<HTMLTextAreaElement>() {
+                return textarea.handle_editing_command(command);
+            }
+        }
+
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        match command {
+            EditingCommand::SelectAll => {
+                if let Some(body) = self.GetBody() {
+                    let _ = selection.SelectAllChildren(body.upcast());
+                }
+            },
+            EditingCommand::Copy => {
+                let text = selection.Stringifier();
+                if !text.is_empty() {
+                    self.send_to_embedder(EmbedderMsg::SetClipboardContents(text.into()));
+                }
+            },
+            // Outside of text controls the document can't be edited.
+            EditingCommand::Cut | EditingCommand::Paste | EditingCommand::Undo => {},
+        }
+    }
+
+    /// Send the selection of the focused text control, or of this document when no text
+    /// control is focused, to the embedder.
+    pub fn send_selection_to_embedder(&self) {
+        let focused_text_control = self
+            .get_focused_element()
+            .filter(|elem| elem.is::<HTMLInputElement>() || elem.is::<HTMLTextAreaElement>());
+        let (text, boxes) = match focused_text_control {
+            Some(elem) => {
+                let text = match elem.downcast::<HTMLInputElement>() {
+                    Some(input) => input.selection_text(),
+                    None => elem
+                        .downcast::<HTMLTextAreaElement>()
+                        .and_then(|textarea| textarea.selection_text()),
+                };
+                // Layout can't tell where the characters of a text control are, so the
+                // whole control is used.
+                let boxes = match text {
+                    Some(_) => elem
+                        .upcast::<Node>()
+                        .bounding_content_box()
+                        .into_iter()
+                        .collect(),
+                    None => vec![],
+                };
+                (text.unwrap_or_default(), boxes)
+            },
+            None => match self
+                .GetSelection()
+                .and_then(|selection| selection.GetRangeAt(0).ok())
+            {
+                Some(range) if !range.Collapsed() => {
+                    let boxes = range
+                        .text_nodes()
+                        .iter()
+                        .flat_map(|text_node| text_node.upcast::<Node>().content_boxes())
+                        .collect();
+                    (String::from(range.Stringifier()), boxes)
+                },
+                _ => (String::new(), vec![]),
+            },
+        };
+
+        let rects = boxes
+            .into_iter()
+            .map(|rect| self.viewport_device_rect(rect))
+            .collect();
+        self.send_to_embedder(EmbedderMsg::Selection(SelectionInfo { text, rects }));
+    }
+
+    /// Convert a box from layout, relative to the initial containing block, to device
+    /// pixels relative to the viewport, which is what the embedder draws in.
+    pub(crate) fn viewport_device_rect(&self, rect: Rect<Au>) -> DeviceIntRect {
+        let viewport_origin = self.window.current_viewport().origin;
+        let device_pixel_ratio = self.window.device_pixel_ratio().get();
+        let rect = Rect::new(
+            Point2D::new(
+                (rect.origin.x - viewport_origin.x).to_f32_px(),
+                (rect.origin.y - viewport_origin.y).to_f32_px(),
+            ),
+            Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+        );
+        let rect = rect.scale(device_pixel_ratio, device_pixel_ratio);
+        DeviceIntRect::from_untyped(&rect.round_out().to_i32())
+    }
+
+    pub fn set_caret_browsing(&self, enabled: bool) {
+        self.caret_browsing.set(enabled);
+    }
+
+    pub(crate) fn find_matches(&self) -> &DomRefCell<Option<Vec<FindMatch>>> {
+        &self.find_matches
+    }
+
+    pub(crate) fn find_active_match(&self) -> &Cell<Option<usize>> {
+        &self.find_active_match
+    }
+
+    pub(crate) fn favicon_url(&self) -> &DomRefCell<Option<ServoUrl>> {
+        &self.favicon_url
+    }
+
     pub fn dirty_all_nodes(&self) {
         let root = match self.GetDocumentElement() {
             Some(root) => root,
@@ -1235,6 +1425,7 @@ impl Document {
         node_address: Option<UntrustedNodeAddress>,
         point_in_node: Option<Point2D<f32>>,
         pressed_mouse_buttons: u16,
+        modifiers: Modifiers,
     ) {
         let mouse_event_type_string = match mouse_event_type {
             MouseEventType::Click => "click".to_owned(),
@@ -1281,10 +1472,10 @@ impl Document {
             client_y,
             client_x,
             client_y, // TODO: Get real screen coordinates?
-            false,
-            false,
-            false,
-            false,
+            modifiers.contains(Modifiers::CONTROL),
+            modifiers.contains(Modifiers::ALT),
+            modifiers.contains(Modifiers::SHIFT),
+            modifiers.contains(Modifiers::META),
             match &button {
                 MouseButton::Left => 0i16,
                 MouseButton::Middle => 1i16,
@@ -1313,6 +1504,15 @@ impl Document {
 
                 let target = node.upcast();
                 event.fire(target);
+
+                if button == MouseButton::Right {
+                    self.fire_contextmenu_event(
+                        &el,
+                        client_point,
+                        pressed_mouse_buttons,
+                        modifiers,
+                    );
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
@@ -1326,7 +1526,7 @@ impl Document {
 
         if let MouseEventType::Click = mouse_event_type {
             self.commit_focus_transaction(FocusType::Element);
-            self.maybe_fire_dblclick(client_point, node, pressed_mouse_buttons);
+            self.maybe_fire_dblclick(client_point, node, pressed_mouse_buttons, modifiers);
         }
 
         self.window
@@ -1338,6 +1538,7 @@ impl Document {
         click_pos: Point2D<f32>,
         target: &Node,
         pressed_mouse_buttons: u16,
+        modifiers: Modifiers,
     ) {
         // https://w3c.github.io/uievents/#event-type-dblclick
         let now = Instant::now();
@@ -1372,10 +1573,10 @@ impl Document {
                     client_y,
                     client_x,
                     client_y,
-                    false,
-                    false,
-                    false,
-                    false,
+                    modifiers.contains(Modifiers::CONTROL),
+                    modifiers.contains(Modifiers::ALT),
+                    modifiers.contains(Modifiers::SHIFT),
+                    modifiers.contains(Modifiers::META),
                     0i16,
                     pressed_mouse_buttons,
                     None,
@@ -1393,6 +1594,129 @@ impl Document {
         *self.last_click_info.borrow_mut() = Some((now, click_pos));
     }
 
+    /// <https://w3c.github.io/uievents/#event-type-contextmenu>
+    fn fire_contextmenu_event(
+        &self,
+        el: &Element,
+        client_point: Point2D<f32>,
+        pressed_mouse_buttons: u16,
+        modifiers: Modifiers,
+    ) {
+        let client_x = client_point.x as i32;
+        let client_y = client_point.y as i32;
+        let event = MouseEvent::new(
+            &self.window,
+            DOMString::from("contextmenu"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            Some(&self.window),
+            0,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            modifiers.contains(Modifiers::CONTROL),
+            modifiers.contains(Modifiers::ALT),
+            modifiers.contains(Modifiers::SHIFT),
+            modifiers.contains(Modifiers::META),
+            2i16,
+            pressed_mouse_buttons,
+            None,
+            None,
+        );
+        // Content cancels the event to show its own menu instead.
+        if event.upcast::<Event>().fire(el.upcast()) == EventStatus::Canceled {
+            return;
+        }
+
+        let image_url = el
+            .downcast::<HTMLImageElement>()
+            .and_then(|image| image.get_url());
+        let device_pixel_ratio = self.window.device_pixel_ratio().get();
+        let point = (client_point * device_pixel_ratio).round().to_i32();
+        let data = ContextMenuData {
+            browsing_context_id: self.window.window_proxy().browsing_context_id(),
+            point: DeviceIntPoint::from_untyped(point),
+            link_url: self.link_url_of(el),
+            image_url,
+            selected_text: self.selected_text(),
+            editable: el.input_method_type().is_some(),
+        };
+        self.context_menu_target.set(Some(el));
+        self.send_to_embedder(EmbedderMsg::ContextMenu(data));
+    }
+
+    /// The URL of the link an element is in, if any.
+    fn link_url_of(&self, el: &Element) -> Option<ServoUrl> {
+        el.upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .filter(|elem| elem.is::<HTMLAnchorElement>() || elem.is::<HTMLAreaElement>())
+            .find_map(|elem| elem.get_attribute(&ns!(), &local_name!("href")))
+            .and_then(|href| self.base_url().join(&href.value()).ok());
+    }
+
+    /// The text selected in the focused text control, or in this document when no text
+    /// control is focused.
+    fn selected_text(&self) -> String {
+        if let Some(elem) = self.get_focused_element() {
+            if let Some(input) = elem.downcast::<HTMLInputElement>() {
+                return input.selection_text().unwrap_or_default();
+            }
+            if let Some(textarea) = elem.downcast::<HTMLTextAreaElement>() {
+                return textarea.selection_text().unwrap_or_default();
+            }
+        }
+        self.GetSelection()
+            .map(|selection| selection.Stringifier().into())
+            .unwrap_or_default()
+    }
+
+    /// Run an action of the last context menu of this document on what it was opened on.
+    pub fn handle_context_menu_action(&self, action: ContextMenuAction) {
+        let target = match self.context_menu_target.get() {
+            Some(target) => target,
+            None => return warn!("Context menu action {:?} without a context menu", action),
+        };
+        match action {
+            ContextMenuAction::CopyLink => {
+                if let Some(link_url) = self.link_url_of(&target) {
+                    let contents = link_url.into_string();
+                    self.send_to_embedder(EmbedderMsg::SetClipboardContents(contents));
+                }
+            },
+            ContextMenuAction::SaveImage => {
+                let image = match target.downcast::<HTMLImageElement>() {
+                    Some(image) => image,
+                    None => return,
+                };
+                let (url, decoded) = match (image.get_url(), image.current_image()) {
+                    (Some(url), Some(decoded)) => (url, decoded),
+                    _ => return warn!("Tried to save an image that isn't loaded"),
+                };
+                let mut pixels = decoded.bytes.to_vec();
+                match decoded.format {
+                    PixelFormat::RGBA8 => {},
+                    PixelFormat::BGRA8 => pixels::rgba8_byte_swap_colors_inplace(&mut pixels),
+                    PixelFormat::K8 | PixelFormat::KA8 => {
+                        return warn!("Saving grayscale images is not supported");
+                    },
+                }
+                self.send_to_embedder(EmbedderMsg::SaveImage(SavedImage {
+                    url,
+                    size: DeviceIntSize::new(decoded.width as i32, decoded.height as i32),
+                    pixels,
+                }));
+            },
+            ContextMenuAction::InspectElement => {
+                self.send_to_embedder(EmbedderMsg::InspectElement(
+                    self.window.pipeline_id(),
+                    target.upcast::<Node>().unique_id(),
+                ));
+            },
+        }
+    }
+
     pub fn fire_mouse_event(
         &self,
         client_point: Point2D<f32>,
@@ -1815,7 +2139,16 @@ impl Document {
             cancel_state = ev.get_cancel_state();
         }
 
-        if cancel_state == EventDefault::Allowed {
+        // While caret browsing, keys that move the caret outside of text controls
+        // aren't passed on to the embedder, which would scroll with them.
+        let is_caret_key = cancel_state == EventDefault::Allowed &&
+            self.caret_browsing.get() &&
+            !focused.as_ref().map_or(false, |elem| {
+                elem.is::<HTMLInputElement>() || elem.is::<HTMLTextAreaElement>()
+            }) &&
+            caret_browsing::handle_key(self, &keyboard_event);
+
+        if cancel_state == EventDefault::Allowed && !is_caret_key {
             let msg = EmbedderMsg::Keyboard(keyboard_event.clone());
             self.send_to_embedder(msg);
 
@@ -1875,6 +2208,63 @@ impl Document {
         event.fire(target);
     }
 
+    /// Dispatch an edit of the input method of the embedder to the focused element, with
+    /// the composition events of the text it composes.
+    pub fn dispatch_ime_event(&self, event: ImeEvent) {
+        let fire = |state, data: &str| {
+            self.dispatch_composition_event(keyboard_types::CompositionEvent {
+                state,
+                data: data.to_owned(),
+            })
+        };
+        match event {
+            ImeEvent::Preedit(ref text, _) => {
+                if !self.ime_composing.replace(true) {
+                    fire(keyboard_types::CompositionState::Start, "");
+                }
+                fire(keyboard_types::CompositionState::Update, text);
+                if text.is_empty() {
+                    self.ime_composing.set(false);
+                    fire(keyboard_types::CompositionState::End, "");
+                }
+            },
+            ImeEvent::Commit(ref text) => {
+                if !self.ime_composing.replace(false) {
+                    fire(keyboard_types::CompositionState::Start, "");
+                }
+                fire(keyboard_types::CompositionState::Update, text);
+                fire(keyboard_types::CompositionState::End, text);
+            },
+            ImeEvent::SetSelection(..) | ImeEvent::DeleteSurroundingText(..) => {},
+        }
+
+        // Composition events may have moved the focus.
+        let focused = match self.get_focused_element() {
+            Some(focused) if focused.input_method_type().is_some() => focused,
+            _ => return,
+        };
+        if let Some(input) = focused.downcast::<HTMLInputElement>() {
+            input.selection().handle_ime_event(&event);
+        } else if let Some(textarea) = focused.downcast::<HTMLTextAreaElement>() {
+            textarea.selection().handle_ime_event(&event);
+        }
+    }
+
+    /// The value of the focused text control, for the input method of the embedder.
+    pub fn ime_surrounding_text(&self) -> Option<SurroundingText> {
+        let focused = self.get_focused_element()?;
+        if focused.input_method_type().is_none() {
+            return None;
+        }
+        if let Some(input) = focused.downcast::<HTMLInputElement>() {
+            Some(input.selection().surrounding_text())
+        } else {
+            focused
+                .downcast::<HTMLTextAreaElement>()
+                .map(|textarea| textarea.selection().surrounding_text())
+        }
+    }
+
     // https://dom.spec.whatwg.org/#converting-nodes-into-a-node
     pub fn node_from_nodes_and_strings(
         &self,
@@ -1990,6 +2380,16 @@ impl Document {
         ident
     }
 
+    /// Ask for animation frames again once the document is no longer hidden, as the
+    /// callbacks that are still waiting weren't run while it was.
+    pub fn resume_animation_frames(&self) {
+        if !self.animation_frame_list.borrow().is_empty() {
+            let event =
+                ScriptMsg::ChangeRunningAnimationsState(AnimationState::AnimationCallbacksPresent);
+            self.window().send_to_constellation(event);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
     pub fn cancel_animation_frame(&self, ident: u32) {
         let mut list = self.animation_frame_list.borrow_mut();
@@ -2081,7 +2481,7 @@ impl Document {
         mut request: RequestBuilder,
         fetch_target: IpcSender<FetchResponseMsg>,
     ) {
-        request.csp_list = self.get_csp_list().map(|x| x.clone());
+        request.csp_list = self.get_csp_list();
         request.https_state = self.https_state.get();
         let mut loader = self.loader.borrow_mut();
         loader.fetch_async(load, request, fetch_target);
@@ -2334,6 +2734,7 @@ impl Document {
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
                     update_with_current_time_ms(&document.load_event_end);
+                    document.send_navigation_event(NavigationStep::LoadComplete);
 
                     window.reflow(ReflowGoal::Full, ReflowReason::DocumentLoaded);
 
@@ -2413,6 +2814,7 @@ impl Document {
                     task!(completely_loaded: move || {
                         let document = document.root();
                         document.completely_loaded.set(true);
+                        favicon::update_favicon(&document);
                         if let Some(DeclarativeRefresh::PendingLoad {
                             url,
                             time
@@ -2584,8 +2986,10 @@ impl Document {
             .queue(
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
+                userscripts::inject_user_scripts(&document, UserScriptInjectionTime::DocumentEnd);
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"));
                 update_with_current_time_ms(&document.dom_content_loaded_event_end);
+                document.send_navigation_event(NavigationStep::DOMContentLoaded);
                 }),
                 window.upcast(),
             )
@@ -2762,7 +3166,7 @@ impl Document {
     fn fire_focus_event(
         &self,
         focus_event_type: FocusEventType,
-        node: &Node,
+        target: &EventTarget,
         related_target: Option<&EventTarget>,
     ) {
         let (event_name, does_bubble) = match focus_event_type {
@@ -2780,10 +3184,32 @@ impl Document {
         );
         let event = event.upcast::<Event>();
         event.set_trusted(true);
-        let target = node.upcast();
         event.fire(target);
     }
 
+    /// Fire the events for the window of this document gaining or losing system focus.
+    /// The focused element stays focused, but is blurred along with the window.
+    /// <https://html.spec.whatwg.org/multipage/#focus-update-steps>
+    pub(crate) fn set_system_focus(&self, has_focus: bool) {
+        if self.has_system_focus.replace(has_focus) == has_focus {
+            return;
+        }
+        let focused = self.focused.get();
+        if has_focus {
+            self.fire_focus_event(FocusEventType::Focus, self.window.upcast(), None);
+            if let Some(ref elem) = focused {
+                elem.set_focus_state(true);
+                self.fire_focus_event(FocusEventType::Focus, elem.upcast(), None);
+            }
+        } else {
+            if let Some(ref elem) = focused {
+                elem.set_focus_state(false);
+                self.fire_focus_event(FocusEventType::Blur, elem.upcast(), None);
+            }
+            self.fire_focus_event(FocusEventType::Blur, self.window.upcast(), None);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#cookie-averse-document-object>
     pub fn is_cookie_averse(&self) -> bool {
         !self.has_browsing_context || !url_has_network_scheme(&self.url())
@@ -3159,6 +3585,9 @@ impl Document {
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             focus_transaction: DomRefCell::new(FocusTransaction::NotInTransaction),
             focused: Default::default(),
+            has_system_focus: Cell::new(true),
+            ime_composing: Cell::new(false),
+            context_menu_target: Default::default(),
             current_script: Default::default(),
             pending_parsing_blocking_script: Default::default(),
             script_blocking_stylesheets_count: Cell::new(0u32),
@@ -3198,6 +3627,10 @@ impl Document {
             spurious_animation_frames: Cell::new(0),
             dom_count: Cell::new(1),
             fullscreen_element: MutNullableDom::new(None),
+            pending_fullscreen_element: MutNullableDom::new(None),
+            pending_fullscreen_promise: DomRefCell::new(None),
+            picture_in_picture_element: MutNullableDom::new(None),
+            picture_in_picture_window: MutNullableDom::new(None),
             form_id_listener_map: Default::default(),
             interactive_time: DomRefCell::new(interactive_time),
             tti_window: DomRefCell::new(InteractiveWindow::new()),
@@ -3218,6 +3651,9 @@ impl Document {
             dirty_webgl_contexts: DomRefCell::new(HashMapTracedValues::new()),
             dirty_webgpu_contexts: DomRefCell::new(HashMap::new()),
             csp_list: DomRefCell::new(None),
+            embedder_csp_list: DomRefCell::new(None),
+            user_scripts: DomRefCell::new(vec![]),
+            isolated_world: Heap::default(),
             selection: MutNullableDom::new(None),
             animation_timeline: if pref!(layout.animations.test.enabled) {
                 DomRefCell::new(AnimationTimeline::new_for_testing())
@@ -3227,6 +3663,10 @@ impl Document {
             animations: DomRefCell::new(Animations::new()),
             dirty_root: Default::default(),
             declarative_refresh: Default::default(),
+            caret_browsing: Cell::new(false),
+            find_matches: DomRefCell::new(None),
+            find_active_match: Cell::new(None),
+            favicon_url: DomRefCell::new(None),
         }
     }
 
@@ -3234,8 +3674,47 @@ impl Document {
         *self.csp_list.borrow_mut() = csp_list;
     }
 
-    pub fn get_csp_list(&self) -> Option<Ref<CspList>> {
-        ref_filter_map(self.csp_list.borrow(), Option::as_ref)
+    pub fn set_embedder_csp(&self, policy: Option<&str>) {
+        *self.embedder_csp_list.borrow_mut() = policy.map(|policy| {
+            CspList::parse(
+                policy,
+                csp::PolicySource::Header,
+                csp::PolicyDisposition::Enforce,
+            )
+        });
+    }
+
+    pub fn set_user_scripts(&self, scripts: Vec<UserScript>) {
+        *self.user_scripts.borrow_mut() = scripts;
+    }
+
+    /// The scripts and stylesheets that the embedder injects into this document at the
+    /// given time.
+    pub fn user_scripts(&self, injection_time: UserScriptInjectionTime) -> Vec<UserScript> {
+        let url = self.url();
+        self.user_scripts
+            .borrow()
+            .iter()
+            .filter(|script| script.injection_time == injection_time && script.matches_url(&url))
+            .cloned()
+            .collect()
+    }
+
+    pub fn isolated_world(&self) -> &Heap<*mut JSObject> {
+        &self.isolated_world
+    }
+
+    /// The policies of this document, followed by the one the embedder enforces.
+    pub fn get_csp_list(&self) -> Option<CspList> {
+        let csp_list = self.csp_list.borrow().clone();
+        let embedder_csp_list = self.embedder_csp_list.borrow().clone();
+        match (csp_list, embedder_csp_list) {
+            (Some(mut csp_list), Some(embedder_csp_list)) => {
+                csp_list.append(embedder_csp_list);
+                Some(csp_list)
+            },
+            (csp_list, embedder_csp_list) => csp_list.or(embedder_csp_list),
+        }
     }
 
     /// <https://www.w3.org/TR/CSP/#should-block-inline>
@@ -3250,13 +3729,16 @@ impl Document {
                 .get_attribute(&ns!(), &local_name!("nonce"))
                 .map(|attr| Cow::Owned(attr.value().to_string())),
         };
-        // TODO: Instead of ignoring violations, report them.
-        self.get_csp_list()
-            .map(|c| {
-                c.should_elements_inline_type_behavior_be_blocked(&element, type_, source)
-                    .0
-            })
-            .unwrap_or(csp::CheckResult::Allowed)
+        let (result, violations) = match self.get_csp_list() {
+            Some(csp_list) => {
+                csp_list.should_elements_inline_type_behavior_be_blocked(&element, type_, source)
+            },
+            None => return csp::CheckResult::Allowed,
+        };
+        self.window
+            .upcast::<GlobalScope>()
+            .report_csp_violations(violations, Some(el));
+        result
     }
 
     /// Prevent any JS or layout from running until the corresponding call to
@@ -3691,28 +4173,44 @@ impl Document {
             error = true;
         }
 
+        // Step 5 Parallel start
+
         if pref!(dom.fullscreen.test) {
             // For reftests we just take over the current window,
             // and don't try to really enter fullscreen.
             info!("Tests don't really enter fullscreen.");
-        } else {
-            // TODO fullscreen is supported
-            // TODO This algorithm is allowed to request fullscreen.
-            warn!("Fullscreen not supported yet");
+        } else if !error {
+            // Step 6
+            // The embedder decides whether the element is shown fullscreen, and answers
+            // after it resized the webview for it, so the element only matches `:fullscreen`
+            // once the document has its new size.
+            // An earlier request that the embedder didn't answer yet fails.
+            self.handle_fullscreen_entered(false);
+            self.pending_fullscreen_element.set(Some(pending));
+            *self.pending_fullscreen_promise.borrow_mut() = Some(promise.clone());
+            let rect = pending.upcast::<Node>().bounding_content_box_or_zero();
+            let rect = self.viewport_device_rect(rect);
+            self.window()
+                .send_to_constellation(ScriptMsg::RequestFullscreen(rect));
+            return promise;
         }
 
-        // Step 5 Parallel start
+        // Step 7
+        self.queue_fullscreen_enter(pending, &promise, error);
+        promise
+    }
 
-        let window = self.window();
-        // Step 6
-        if !error {
-            let event = EmbedderMsg::SetFullscreenState(true);
-            self.send_to_embedder(event);
+    /// Answer the request of this document to show an element fullscreen, if any, with
+    /// whether the embedder shows it.
+    pub fn handle_fullscreen_entered(&self, entered: bool) {
+        let element = self.pending_fullscreen_element.take();
+        let promise = self.pending_fullscreen_promise.borrow_mut().take();
+        if let (Some(element), Some(promise)) = (element, promise) {
+            self.queue_fullscreen_enter(&element, &promise, !entered);
         }
+    }
 
-        let pipeline_id = self.window().pipeline_id();
-
-        // Step 7
+    fn queue_fullscreen_enter(&self, pending: &Element, promise: &Rc<Promise>, error: bool) {
         let trusted_pending = Trusted::new(pending);
         let trusted_promise = TrustedPromise::new(promise.clone());
         let handler = ElementPerformFullscreenEnter::new(trusted_pending, trusted_promise, error);
@@ -3721,13 +4219,11 @@ impl Document {
         let script_msg = CommonScriptMsg::Task(
             ScriptThreadEventCategory::EnterFullscreen,
             handler,
-            Some(pipeline_id),
+            Some(self.window().pipeline_id()),
             TaskSourceName::DOMManipulation,
         );
         let msg = MainThreadScriptMsg::Common(script_msg);
-        window.main_thread_script_chan().send(msg).unwrap();
-
-        promise
+        self.window().main_thread_script_chan().send(msg).unwrap();
     }
 
     // https://fullscreen.spec.whatwg.org/#exit-fullscreen
@@ -3748,8 +4244,7 @@ impl Document {
 
         let window = self.window();
         // Step 8
-        let event = EmbedderMsg::SetFullscreenState(false);
-        self.send_to_embedder(event);
+        self.send_to_embedder(EmbedderMsg::ExitFullscreen);
 
         // Step 9
         let trusted_element = Trusted::new(&*element);
@@ -3770,6 +4265,174 @@ impl Document {
         promise
     }
 
+    /// <https://w3c.github.io/picture-in-picture/#request-pip>
+    pub fn request_picture_in_picture(&self, video: &HTMLVideoElement) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+
+        // Steps 1-2.
+        if !self.PictureInPictureEnabled() {
+            promise.reject_error(Error::NotSupported);
+            return promise;
+        }
+
+        // Step 3.
+        if video.upcast::<HTMLMediaElement>().get_ready_state() == ReadyState::HaveNothing {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 5.
+        if video.DisablePictureInPicture() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 6.
+        if self.picture_in_picture_element.get().is_none() && !self.has_transient_activation() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 7.
+        if self.picture_in_picture_element.get().as_deref() == Some(video) {
+            if let Some(picture_in_picture_window) = self.picture_in_picture_window.get() {
+                promise.resolve_native(&picture_in_picture_window);
+                return promise;
+            }
+        }
+
+        // Step 8.
+        self.leave_picture_in_picture();
+
+        // Steps 9-11.
+        let width = video.get_video_width() as i32;
+        let height = video.get_video_height() as i32;
+        let picture_in_picture_window = PictureInPictureWindow::new(self.window(), width, height);
+        self.picture_in_picture_element.set(Some(video));
+        self.picture_in_picture_window
+            .set(Some(&picture_in_picture_window));
+        self.send_picture_in_picture_event(EmbedderPictureInPictureEvent::Enter(
+            DeviceIntSize::new(width, height),
+        ));
+        video
+            .upcast::<HTMLMediaElement>()
+            .send_picture_in_picture_frame();
+
+        // Step 12.
+        self.queue_picture_in_picture_event(
+            video,
+            "enterpictureinpicture",
+            &picture_in_picture_window,
+        );
+
+        // Step 13.
+        promise.resolve_native(&picture_in_picture_window);
+        promise
+    }
+
+    /// Whether the webview of this document has transient user activation, which the
+    /// constellation tracks from the input events it forwards.
+    /// <https://html.spec.whatwg.org/multipage/#transient-activation>
+    fn has_transient_activation(&self) -> bool {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        self.window
+            .send_to_constellation(ScriptMsg::HasTransientActivation(sender));
+        receiver.recv().unwrap_or(false)
+    }
+
+    /// <https://w3c.github.io/picture-in-picture/#exit-pip>
+    pub fn leave_picture_in_picture(&self) {
+        let video = match self.picture_in_picture_element.take() {
+            Some(video) => video,
+            None => return,
+        };
+
+        // Step 4.
+        self.send_picture_in_picture_event(EmbedderPictureInPictureEvent::Leave);
+
+        // Steps 5-6.
+        if let Some(picture_in_picture_window) = self.picture_in_picture_window.take() {
+            picture_in_picture_window.close();
+            self.queue_picture_in_picture_event(
+                &video,
+                "leavepictureinpicture",
+                &picture_in_picture_window,
+            );
+        }
+    }
+
+    pub fn picture_in_picture_element(&self) -> Option<DomRoot<HTMLVideoElement>> {
+        self.picture_in_picture_element.get()
+    }
+
+    pub fn send_picture_in_picture_event(&self, event: EmbedderPictureInPictureEvent) {
+        let window = self.window();
+        window.send_to_constellation(ScriptMsg::PictureInPictureEvent(
+            window.pipeline_id(),
+            event,
+        ));
+    }
+
+    fn queue_picture_in_picture_event(
+        &self,
+        video: &HTMLVideoElement,
+        type_: &'static str,
+        picture_in_picture_window: &PictureInPictureWindow,
+    ) {
+        let video = Trusted::new(video);
+        let picture_in_picture_window = Trusted::new(picture_in_picture_window);
+        let (task_source, canceller) = self
+            .window()
+            .task_manager()
+            .media_element_task_source_with_canceller();
+        let _ = task_source.queue_with_canceller(
+            task!(fire_picture_in_picture_event: move || {
+                let video = video.root();
+                let picture_in_picture_window = picture_in_picture_window.root();
+                let event = PictureInPictureEvent::new(
+                    &video.global(),
+                    Atom::from(type_),
+                    true,
+                    false,
+                    &picture_in_picture_window,
+                );
+                event.upcast::<Event>().fire(video.upcast());
+            }),
+            &canceller,
+        );
+    }
+
+    /// Handle a control of the embedder's picture-in-picture surface.
+    pub fn handle_picture_in_picture_action(&self, action: PictureInPictureAction) {
+        let video = match self.picture_in_picture_element.get() {
+            Some(video) => video,
+            None => return warn!("No picture-in-picture video for this document"),
+        };
+        let media = video.upcast::<HTMLMediaElement>();
+        match action {
+            PictureInPictureAction::Play => {
+                let realm = enter_realm(self);
+                media.Play(InRealm::Entered(&realm));
+            },
+            PictureInPictureAction::Pause => media.Pause(),
+            PictureInPictureAction::SeekBackward => {
+                let time = (*media.CurrentTime() - PICTURE_IN_PICTURE_SEEK_OFFSET).max(0.);
+                media.SetCurrentTime(Finite::wrap(time));
+            },
+            PictureInPictureAction::SeekForward => {
+                let time = *media.CurrentTime() + PICTURE_IN_PICTURE_SEEK_OFFSET;
+                media.SetCurrentTime(Finite::wrap(time));
+            },
+            PictureInPictureAction::Resize(width, height) => {
+                if let Some(picture_in_picture_window) = self.picture_in_picture_window.get() {
+                    picture_in_picture_window.resize(self.window(), width as i32, height as i32);
+                }
+            },
+            PictureInPictureAction::Close => self.leave_picture_in_picture(),
+        }
+    }
+
     pub fn set_fullscreen_element(&self, element: Option<&Element>) {
         self.fullscreen_element.set(element);
     }
@@ -4054,7 +4717,7 @@ impl DocumentMethods for Document {
     fn HasFocus(&self) -> bool {
         // Step 1-2.
         if self.window().parent_info().is_none() && self.is_fully_active() {
-            return true;
+            return self.has_system_focus.get();
         }
         // TODO Step 3.
         false
@@ -5284,6 +5947,36 @@ impl DocumentMethods for Document {
         self.exit_fullscreen()
     }
 
+    // https://w3c.github.io/picture-in-picture/#dom-document-pictureinpictureenabled
+    fn PictureInPictureEnabled(&self) -> bool {
+        pref!(dom.picture_in_picture.enabled) && self.browsing_context().is_some()
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-document-exitpictureinpicture
+    fn ExitPictureInPicture(&self) -> Rc<Promise> {
+        let in_realm_proof = AlreadyInRealm::assert();
+        let promise = Promise::new_in_current_realm(InRealm::Already(&in_realm_proof));
+
+        // Step 1.
+        if self.picture_in_picture_element.get().is_none() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        // Step 2.
+        self.leave_picture_in_picture();
+        promise.resolve_native(&());
+        promise
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-documentorshadowroot-pictureinpictureelement
+    fn GetPictureInPictureElement(&self) -> Option<DomRoot<Element>> {
+        // TODO ShadowRoot
+        self.picture_in_picture_element
+            .get()
+            .map(|video| DomRoot::from_ref(video.upcast::<Element>()))
+    }
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -5370,6 +6063,12 @@ pub enum AnimationFrameCallback {
         #[ignore_malloc_size_of = "Rc is hard"]
         callback: Rc<FrameRequestCallback>,
     },
+    /// Runs the video frame request callbacks of a video element that
+    /// presented a new frame.
+    VideoFrameCallbacks {
+        #[ignore_malloc_size_of = "non-owning"]
+        video: Trusted<HTMLVideoElement>,
+    },
 }
 
 impl AnimationFrameCallback {
@@ -5389,6 +6088,9 @@ impl AnimationFrameCallback {
                 // https://github.com/servo/servo/issues/6928
                 let _ = callback.Call__(Finite::wrap(now), ExceptionHandling::Report);
             },
+            AnimationFrameCallback::VideoFrameCallbacks { ref video } => {
+                video.root().run_video_frame_callbacks(now);
+            },
         }
     }
 }
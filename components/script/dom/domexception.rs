@@ -43,6 +43,7 @@ pub enum DOMErrorName {
     DataCloneError = DOMExceptionConstants::DATA_CLONE_ERR,
     NotReadableError,
     OperationError,
+    NotAllowedError,
 }
 
 impl DOMErrorName {
@@ -72,6 +73,7 @@ impl DOMErrorName {
             "DataCloneError" => Some(DOMErrorName::DataCloneError),
             "NotReadableError" => Some(DOMErrorName::NotReadableError),
             "OperationError" => Some(DOMErrorName::OperationError),
+            "NotAllowedError" => Some(DOMErrorName::NotAllowedError),
             _ => None,
         }
     }
@@ -119,6 +121,9 @@ impl DOMException {
             DOMErrorName::OperationError => {
                 "The operation failed for an operation-specific reason."
             },
+            DOMErrorName::NotAllowedError => {
+                "The request is not allowed by the user agent or the platform."
+            },
         };
 
         (
@@ -12,9 +12,11 @@ use std::rc::Rc;
 use std::str::FromStr;
 use std::{fmt, mem};
 
+use content_security_policy as csp;
 use cssparser::match_ignore_ascii_case;
 use devtools_traits::AttrInfo;
 use dom_struct::dom_struct;
+use embedder_traits::EmbedderMsg;
 use euclid::default::{Rect, Size2D};
 use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
 use html5ever::serialize::{SerializeOpts, TraversalScope};
@@ -469,7 +471,7 @@ impl Element {
     }
 
     // https://drafts.csswg.org/cssom-view/#scrolling-box
-    fn has_scrolling_box(&self) -> bool {
+    pub(crate) fn has_scrolling_box(&self) -> bool {
         // TODO: scrolling mechanism, such as scrollbar (We don't have scrollbar yet)
         //       self.has_scrolling_mechanism()
         self.style().map_or(false, |style| {
@@ -478,7 +480,7 @@ impl Element {
         })
     }
 
-    fn has_overflow(&self) -> bool {
+    pub(crate) fn has_overflow(&self) -> bool {
         self.ScrollHeight() > self.ClientHeight() || self.ScrollWidth() > self.ClientWidth()
     }
 
@@ -2935,7 +2937,7 @@ impl VirtualMethods for Element {
                             _ => false,
                         };
 
-                        let block = if is_declaration {
+                        if is_declaration {
                             let mut value = AttrValue::String(String::new());
                             attr.swap_value(&mut value);
                             let (serialization, block) = match value {
@@ -2944,19 +2946,28 @@ impl VirtualMethods for Element {
                             };
                             let mut value = AttrValue::String(serialization);
                             attr.swap_value(&mut value);
-                            block
+                            Some(block)
+                        } else if doc.should_elements_inline_type_behavior_be_blocked(
+                            self,
+                            csp::InlineCheckType::StyleAttribute,
+                            &attr.value(),
+                        ) == csp::CheckResult::Blocked
+                        {
+                            // Only markup is subject to CSP, CSSOM changes take the
+                            // declaration fast path above.
+                            None
                         } else {
                             let win = window_from_node(self);
-                            Arc::new(doc.style_shared_lock().wrap(parse_style_attribute(
-                                &attr.value(),
-                                &UrlExtraData(doc.base_url().get_arc()),
-                                win.css_error_reporter(),
-                                doc.quirks_mode(),
-                                CssRuleType::Style,
+                            Some(Arc::new(doc.style_shared_lock().wrap(
+                                parse_style_attribute(
+                                    &attr.value(),
+                                    &UrlExtraData(doc.base_url().get_arc()),
+                                    win.css_error_reporter(),
+                                    doc.quirks_mode(),
+                                    CssRuleType::Style,
+                                ),
                             )))
-                        };
-
-                        Some(block)
+                        }
                     },
                     AttributeMutation::Removed => None,
                 };
@@ -3827,6 +3838,10 @@ impl TaskOnce for ElementPerformFullscreenEnter {
 
         // Step 7.1
         if self.error || !element.fullscreen_element_ready_check() {
+            // The embedder shows an element that can't be fullscreen anymore.
+            if !self.error {
+                document.send_to_embedder(EmbedderMsg::ExitFullscreen);
+            }
             document
                 .upcast::<EventTarget>()
                 .fire_event(atom!("fullscreenerror"));
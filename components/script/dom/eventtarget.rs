@@ -410,7 +410,7 @@ impl EventTarget {
     /// <https://html.spec.whatwg.org/multipage/#event-handler-attributes:event-handlers-11>
     fn set_inline_event_listener(&self, ty: Atom, listener: Option<InlineEventListener>) {
         let mut handlers = self.handlers.borrow_mut();
-        let entries = match handlers.entry(ty) {
+        let entries = match handlers.entry(ty.clone()) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -441,6 +441,16 @@ impl EventTarget {
                 }
             },
         }
+        drop(handlers);
+        self.listeners_changed(&ty);
+    }
+
+    /// Some listeners on a window need the embedder to start producing the events
+    /// they listen for, so let it know whenever they come and go.
+    fn listeners_changed(&self, ty: &Atom) {
+        if let Some(window) = self.downcast::<Window>() {
+            window.event_listeners_changed(ty);
+        }
     }
 
     pub fn remove_listener_if_once(&self, ty: &Atom, listener: &Rc<EventListener>) {
@@ -710,8 +720,9 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        let ty = Atom::from(ty);
         let mut handlers = self.handlers.borrow_mut();
-        let entry = match handlers.entry(Atom::from(ty)) {
+        let entry = match handlers.entry(ty.clone()) {
             Occupied(entry) => entry.into_mut(),
             Vacant(entry) => entry.insert(EventListeners(vec![])),
         };
@@ -729,6 +740,8 @@ impl EventTarget {
         if !entry.contains(&new_entry) {
             entry.push(new_entry);
         }
+        drop(handlers);
+        self.listeners_changed(&ty);
     }
 
     // https://dom.spec.whatwg.org/#dom-eventtarget-removeeventlistener
@@ -742,8 +755,9 @@ impl EventTarget {
             Some(l) => l,
             None => return,
         };
+        let ty = Atom::from(ty);
         let mut handlers = self.handlers.borrow_mut();
-        let entry = handlers.get_mut(&Atom::from(ty));
+        let entry = handlers.get_mut(&ty);
         if let Some(entry) = entry {
             let phase = if options.capture {
                 ListenerPhase::Capturing
@@ -759,6 +773,8 @@ impl EventTarget {
                 entry.remove(position);
             }
         }
+        drop(handlers);
+        self.listeners_changed(&ty);
     }
 }
 
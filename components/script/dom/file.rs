@@ -7,6 +7,7 @@ use js::rust::HandleObject;
 use net_traits::filemanager_thread::SelectedFile;
 use script_traits::serializable::BlobImpl;
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::FileBinding;
 use crate::dom::bindings::codegen::Bindings::FileBinding::FileMethods;
 use crate::dom::bindings::codegen::UnionTypes::ArrayBufferOrArrayBufferViewOrBlobOrString;
@@ -14,7 +15,7 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::blob::{blob_parts_to_bytes, normalize_type_string, Blob};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::window::Window;
@@ -24,6 +25,8 @@ pub struct File {
     blob: Blob,
     name: DOMString,
     modified: i64,
+    /// <https://wicg.github.io/entries-api/#file-webkitrelativepath>
+    relative_path: DomRefCell<DOMString>,
 }
 
 impl File {
@@ -40,6 +43,7 @@ impl File {
                     time.sec * 1000 + (time.nsec / 1000000) as i64
                 },
             },
+            relative_path: DomRefCell::new(DOMString::new()),
         }
     }
 
@@ -78,7 +82,7 @@ impl File {
                 .expect("File name encoding error"),
         );
 
-        File::new(
+        let file = File::new(
             window.upcast(),
             BlobImpl::new_from_file(
                 selected.id,
@@ -88,7 +92,9 @@ impl File {
             ),
             name,
             Some(selected.modified as i64),
-        )
+        );
+        *file.relative_path.borrow_mut() = DOMString::from(selected.relative_path);
+        file
     }
 
     // https://w3c.github.io/FileAPI/#file-constructor
@@ -136,4 +142,9 @@ impl FileMethods for File {
     fn LastModified(&self) -> i64 {
         self.modified
     }
+
+    // https://wicg.github.io/entries-api/#dom-file-webkitrelativepath
+    fn WebkitRelativePath(&self) -> USVString {
+        USVString(self.relative_path.borrow().to_string())
+    }
 }
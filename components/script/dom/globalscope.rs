@@ -14,7 +14,7 @@ use std::thread::JoinHandle;
 use std::time::Instant;
 use std::{mem, ptr};
 
-use content_security_policy::CspList;
+use content_security_policy::{self as csp, CspList};
 use crossbeam_channel::Sender;
 use devtools_traits::{PageError, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
@@ -54,6 +54,7 @@ use script_traits::{
     BroadcastMsg, GamepadEvent, GamepadUpdateType, MessagePortMsg, MsDuration, PortMessageTask,
     ScriptMsg, ScriptToConstellationChan, TimerEvent, TimerEventId, TimerSchedulerMsg, TimerSource,
 };
+use servo_atoms::Atom;
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use uuid::Uuid;
 use webgpu::identity::WebGPUOpResult;
@@ -62,6 +63,7 @@ use webgpu::{ErrorScopeId, WebGPUDevice};
 use super::bindings::trace::HashMapTracedValues;
 use crate::dom::bindings::cell::{DomRefCell, RefMut};
 use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSource_Binding::EventSourceMethods;
 use crate::dom::bindings::codegen::Bindings::GamepadListBinding::GamepadList_Binding::GamepadListMethods;
 use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
@@ -89,6 +91,7 @@ use crate::dom::crypto::Crypto;
 use crate::dom::dedicatedworkerglobalscope::{
     DedicatedWorkerControlMsg, DedicatedWorkerGlobalScope,
 };
+use crate::dom::element::Element;
 use crate::dom::errorevent::ErrorEvent;
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::eventsource::EventSource;
@@ -102,13 +105,16 @@ use crate::dom::identityhub::Identities;
 use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::messageport::MessagePort;
+use crate::dom::node::Node;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
 use crate::dom::performanceobserver::VALID_ENTRY_TYPES;
 use crate::dom::promise::Promise;
 use crate::dom::readablestream::{ExternalUnderlyingSource, ReadableStream};
+use crate::dom::securitypolicyviolationevent::{CSPViolationReport, SecurityPolicyViolationEvent};
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
+use crate::dom::userscripts::window_of_isolated_world;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
@@ -3073,12 +3079,59 @@ impl GlobalScope {
     /// <https://www.w3.org/TR/CSP/#get-csp-of-object>
     pub fn get_csp_list(&self) -> Option<CspList> {
         if let Some(window) = self.downcast::<Window>() {
-            return window.Document().get_csp_list().map(|c| c.clone());
+            return window.Document().get_csp_list();
         }
         // TODO: Worker and Worklet global scopes.
         None
     }
 
+    /// <https://w3c.github.io/webappsec-csp/#report-violation>
+    pub fn report_csp_violations(
+        &self,
+        violations: Vec<csp::Violation>,
+        element: Option<&Element>,
+    ) {
+        let referrer = match self.downcast::<Window>() {
+            Some(window) => window.Document().Referrer().to_string(),
+            None => String::new(),
+        };
+        for violation in violations {
+            let report =
+                CSPViolationReport::new(violation, self.get_url().to_string(), referrer.clone());
+            warn!(
+                "Content-Security-Policy violation of {:?} by {}",
+                report.effective_directive, report.blocked_url
+            );
+
+            // Step 3: Fire at the element if it is still connected,
+            // otherwise at the global's document (or the global itself).
+            let target = match (element, self.downcast::<Window>()) {
+                (Some(element), _) if element.upcast::<Node>().is_connected() => {
+                    Trusted::new(element.upcast::<EventTarget>())
+                },
+                (_, Some(window)) => Trusted::new(window.Document().upcast::<EventTarget>()),
+                (_, None) => Trusted::new(self.upcast::<EventTarget>()),
+            };
+            let _ = self.dom_manipulation_task_source().queue(
+                task!(fire_security_policy_violation_event: move || {
+                    let target = target.root();
+                    let event = SecurityPolicyViolationEvent::new(
+                        &target.global(),
+                        Atom::from("securitypolicyviolation"),
+                        true,
+                        false,
+                        report,
+                    );
+                    event.upcast::<Event>().fire(&target);
+                }),
+                self,
+            );
+
+            // TODO: Step 4: Send the report to the policy's reporting endpoints
+            // through the Reporting API.
+        }
+    }
+
     pub fn wgpu_id_hub(&self) -> Arc<Mutex<Identities>> {
         self.gpu_id_hub.clone()
     }
@@ -3260,13 +3313,15 @@ impl GlobalScope {
     }
 }
 
-/// Returns the Rust global scope from a JS global object.
+/// Returns the Rust global scope from a JS global object, which is that of its window for
+/// the global of an isolated world.
 #[allow(unsafe_code)]
 unsafe fn global_scope_from_global(
     global: *mut JSObject,
     cx: *mut JSContext,
 ) -> DomRoot<GlobalScope> {
     assert!(!global.is_null());
+    let global = window_of_isolated_world(global).unwrap_or(global);
     let clasp = get_object_class(global);
     assert_ne!(
         ((*clasp).flags & (JSCLASS_IS_DOMJSCLASS | JSCLASS_IS_GLOBAL)),
@@ -3279,6 +3334,7 @@ unsafe fn global_scope_from_global(
 #[allow(unsafe_code)]
 unsafe fn global_scope_from_global_static(global: *mut JSObject) -> DomRoot<GlobalScope> {
     assert!(!global.is_null());
+    let global = window_of_isolated_world(global).unwrap_or(global);
     let clasp = get_object_class(global);
     assert_ne!(
         ((*clasp).flags & (JSCLASS_IS_DOMJSCLASS | JSCLASS_IS_GLOBAL)),
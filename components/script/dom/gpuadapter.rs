@@ -93,6 +93,14 @@ impl GPUAdapterMethods for GPUAdapter {
         let sender = response_async(&promise, self);
         let mut features = wgt::Features::empty();
         for &ext in descriptor.requiredFeatures.iter() {
+            // Step 3
+            if !self.features.contains(ext) {
+                promise.reject_error(Error::Type(format!(
+                    "{} is not supported by this adapter",
+                    ext.as_str()
+                )));
+                return promise;
+            }
             if let Some(feature) = gpu_to_wgt_feature(ext) {
                 features.insert(feature);
             } else {
@@ -171,13 +179,28 @@ impl GPUAdapterMethods for GPUAdapter {
                     },
                     _ => {
                         error!("Unknown required limit: {limit} with value {value}");
-                        // we should reject but spec is still evolving
-                        // promise.reject_error(Error::Operation);
-                        // return promise;
+                        promise.reject_error(Error::Operation);
+                        return promise;
                     },
                 }
             }
         }
+        // Step 4
+        let alignments = [
+            desc.limits.min_uniform_buffer_offset_alignment,
+            desc.limits.min_storage_buffer_offset_alignment,
+        ];
+        if alignments
+            .iter()
+            .any(|alignment| !alignment.is_power_of_two())
+        {
+            promise.reject_error(Error::Operation);
+            return promise;
+        }
+        if !desc.limits.check_limits(self.limits.limits()) {
+            promise.reject_error(Error::Operation);
+            return promise;
+        }
         let id = self
             .global()
             .wgpu_id_hub()
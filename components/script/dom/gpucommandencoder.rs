@@ -29,6 +29,7 @@ use crate::dom::gpuconvert::{
     convert_texture_size_to_dict, convert_texture_size_to_wgt,
 };
 use crate::dom::gpudevice::GPUDevice;
+use crate::dom::gpuqueryset::GPUQuerySet;
 use crate::dom::gpurenderpassencoder::GPURenderPassEncoder;
 
 // TODO(sagudev): this is different now
@@ -130,6 +131,13 @@ impl GPUCommandEncoderMethods for GPUCommandEncoder {
         let compute_pass = if !self.valid.get() {
             None
         } else {
+            let timestamp_writes = descriptor.timestampWrites.as_ref().map(|writes| {
+                wgpu_com::ComputePassTimestampWrites {
+                    query_set: writes.querySet.id().0,
+                    beginning_of_pass_write_index: writes.beginningOfPassWriteIndex,
+                    end_of_pass_write_index: writes.endOfPassWriteIndex,
+                }
+            });
             Some(wgpu_com::ComputePass::new(
                 self.encoder.0,
                 &wgpu_com::ComputePassDescriptor {
@@ -138,7 +146,7 @@ impl GPUCommandEncoderMethods for GPUCommandEncoder {
                         .label
                         .as_ref()
                         .map(|l| Cow::Borrowed(&**l)),
-                    timestamp_writes: None,
+                    timestamp_writes: timestamp_writes.as_ref(),
                 },
             ))
         };
@@ -183,6 +191,14 @@ impl GPUCommandEncoderMethods for GPUCommandEncoder {
                 }
             });
 
+            let timestamp_writes = descriptor.timestampWrites.as_ref().map(|writes| {
+                wgpu_com::RenderPassTimestampWrites {
+                    query_set: writes.querySet.id().0,
+                    beginning_of_pass_write_index: writes.beginningOfPassWriteIndex,
+                    end_of_pass_write_index: writes.endOfPassWriteIndex,
+                }
+            });
+
             let desc = wgpu_com::RenderPassDescriptor {
                 color_attachments: Cow::Owned(
                     descriptor
@@ -235,8 +251,8 @@ impl GPUCommandEncoderMethods for GPUCommandEncoder {
                     .label
                     .as_ref()
                     .map(|l| Cow::Borrowed(&**l)),
-                timestamp_writes: None,
-                occlusion_query_set: None,
+                timestamp_writes: timestamp_writes.as_ref(),
+                occlusion_query_set: descriptor.occlusionQuerySet.as_ref().map(|q| q.id().0),
             };
             Some(wgpu_com::RenderPass::new(self.encoder.0, &desc))
         };
@@ -284,6 +300,39 @@ impl GPUCommandEncoderMethods for GPUCommandEncoder {
             .expect("Failed to send CopyBufferToBuffer");
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-resolvequeryset>
+    fn ResolveQuerySet(
+        &self,
+        query_set: &GPUQuerySet,
+        first_query: u32,
+        query_count: u32,
+        destination: &GPUBuffer,
+        destination_offset: GPUSize64,
+    ) {
+        if !(*self.state.borrow() == GPUCommandEncoderState::Open) {
+            self.valid.set(false);
+            return;
+        }
+
+        self.buffers
+            .borrow_mut()
+            .insert(DomRoot::from_ref(destination));
+        self.channel
+            .0
+            .send((
+                None,
+                WebGPURequest::ResolveQuerySet {
+                    command_encoder_id: self.encoder.0,
+                    query_set_id: query_set.id().0,
+                    first_query,
+                    query_count,
+                    destination_id: destination.id().0,
+                    destination_offset,
+                },
+            ))
+            .expect("Failed to send ResolveQuerySet");
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpucommandencoder-copybuffertotexture>
     fn CopyBufferToTexture(
         &self,
@@ -11,8 +11,9 @@ use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
     GPUAddressMode, GPUBlendComponent, GPUBlendFactor, GPUBlendOperation, GPUCompareFunction,
     GPUCullMode, GPUExtent3D, GPUExtent3DDict, GPUFilterMode, GPUFrontFace, GPUImageCopyBuffer,
     GPUImageCopyTexture, GPUImageDataLayout, GPUIndexFormat, GPULoadOp, GPUObjectDescriptorBase,
-    GPUOrigin3D, GPUPrimitiveState, GPUPrimitiveTopology, GPUStencilOperation, GPUStoreOp,
-    GPUTextureAspect, GPUTextureFormat, GPUTextureViewDimension, GPUVertexFormat,
+    GPUOrigin3D, GPUPipelineStatisticName, GPUPrimitiveState, GPUPrimitiveTopology, GPUQueryType,
+    GPUStencilOperation, GPUStoreOp, GPUTextureAspect, GPUTextureFormat, GPUTextureViewDimension,
+    GPUVertexFormat,
 };
 
 pub fn convert_texture_format(format: GPUTextureFormat) -> wgt::TextureFormat {
@@ -331,3 +332,36 @@ pub fn convert_ic_texture(ic_texture: &GPUImageCopyTexture) -> wgpu_com::ImageCo
 pub fn convert_label(parent: &GPUObjectDescriptorBase) -> Option<Cow<'static, str>> {
     parent.label.as_ref().map(|s| Cow::Owned(s.to_string()))
 }
+
+pub fn convert_query_type(
+    type_: GPUQueryType,
+    pipeline_statistics: &[GPUPipelineStatisticName],
+) -> wgt::QueryType {
+    match type_ {
+        GPUQueryType::Occlusion => wgt::QueryType::Occlusion,
+        GPUQueryType::Pipeline_statistics => {
+            let mut types = wgt::PipelineStatisticsTypes::empty();
+            for statistic in pipeline_statistics {
+                types.insert(match statistic {
+                    GPUPipelineStatisticName::Vertex_shader_invocations => {
+                        wgt::PipelineStatisticsTypes::VERTEX_SHADER_INVOCATIONS
+                    },
+                    GPUPipelineStatisticName::Clipper_invocations => {
+                        wgt::PipelineStatisticsTypes::CLIPPER_INVOCATIONS
+                    },
+                    GPUPipelineStatisticName::Clipper_primitives_out => {
+                        wgt::PipelineStatisticsTypes::CLIPPER_PRIMITIVES_OUT
+                    },
+                    GPUPipelineStatisticName::Fragment_shader_invocations => {
+                        wgt::PipelineStatisticsTypes::FRAGMENT_SHADER_INVOCATIONS
+                    },
+                    GPUPipelineStatisticName::Compute_shader_invocations => {
+                        wgt::PipelineStatisticsTypes::COMPUTE_SHADER_INVOCATIONS
+                    },
+                });
+            }
+            wgt::QueryType::PipelineStatistics(types)
+        },
+        GPUQueryType::Timestamp => wgt::QueryType::Timestamp,
+    }
+}
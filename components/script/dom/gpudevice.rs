@@ -30,11 +30,12 @@ use crate::dom::bindings::codegen::Bindings::EventTargetBinding::EventTargetMeth
 use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
     GPUBindGroupDescriptor, GPUBindGroupLayoutDescriptor, GPUBindingResource, GPUBufferBindingType,
     GPUBufferDescriptor, GPUCommandEncoderDescriptor, GPUComputePipelineDescriptor,
-    GPUDeviceLostReason, GPUDeviceMethods, GPUError, GPUErrorFilter, GPUPipelineLayoutDescriptor,
-    GPURenderBundleEncoderDescriptor, GPURenderPipelineDescriptor, GPUSamplerBindingType,
-    GPUSamplerDescriptor, GPUShaderModuleDescriptor, GPUStorageTextureAccess,
-    GPUSupportedLimitsMethods, GPUTextureDescriptor, GPUTextureDimension, GPUTextureSampleType,
-    GPUUncapturedErrorEventInit, GPUVertexStepMode,
+    GPUDeviceLostReason, GPUDeviceMethods, GPUError, GPUErrorFilter, GPUExternalTextureDescriptor,
+    GPUPipelineLayoutDescriptor, GPUQuerySetDescriptor, GPURenderBundleEncoderDescriptor,
+    GPURenderPipelineDescriptor, GPUSamplerBindingType, GPUSamplerDescriptor,
+    GPUShaderModuleDescriptor, GPUStorageTextureAccess, GPUSupportedLimitsMethods,
+    GPUTextureDescriptor, GPUTextureDimension, GPUTextureSampleType, GPUUncapturedErrorEventInit,
+    GPUVertexStepMode,
 };
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
@@ -51,12 +52,14 @@ use crate::dom::gpucommandencoder::GPUCommandEncoder;
 use crate::dom::gpucomputepipeline::GPUComputePipeline;
 use crate::dom::gpuconvert::{
     convert_address_mode, convert_blend_component, convert_compare_function, convert_filter_mode,
-    convert_label, convert_primitive_state, convert_stencil_op, convert_texture_format,
-    convert_texture_size_to_dict, convert_texture_size_to_wgt, convert_vertex_format,
-    convert_view_dimension,
+    convert_label, convert_primitive_state, convert_query_type, convert_stencil_op,
+    convert_texture_format, convert_texture_size_to_dict, convert_texture_size_to_wgt,
+    convert_vertex_format, convert_view_dimension,
 };
+use crate::dom::gpuexternaltexture::GPUExternalTexture;
 use crate::dom::gpuoutofmemoryerror::GPUOutOfMemoryError;
 use crate::dom::gpupipelinelayout::GPUPipelineLayout;
+use crate::dom::gpuqueryset::GPUQuerySet;
 use crate::dom::gpuqueue::GPUQueue;
 use crate::dom::gpurenderbundleencoder::GPURenderBundleEncoder;
 use crate::dom::gpurenderpipeline::GPURenderPipeline;
@@ -476,6 +479,14 @@ impl GPUDeviceMethods for GPUDevice {
                         format: convert_texture_format(storage.format),
                         view_dimension: convert_view_dimension(storage.viewDimension),
                     }
+                } else if bind.externalTexture.is_some() {
+                    // External textures are imported as plain 2D textures, see
+                    // `ImportExternalTexture`.
+                    wgt::BindingType::Texture {
+                        sample_type: wgt::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgt::TextureViewDimension::D2,
+                        multisampled: false,
+                    }
                 } else if let Some(texture) = &bind.texture {
                     wgt::BindingType::Texture {
                         sample_type: match texture.sampleType {
@@ -611,6 +622,9 @@ impl GPUDeviceMethods for GPUDevice {
                     GPUBindingResource::GPUTextureView(ref t) => {
                         wgpu_bind::BindingResource::TextureView(t.id().0)
                     },
+                    GPUBindingResource::GPUExternalTexture(ref t) => {
+                        wgpu_bind::BindingResource::TextureView(t.view_id().0)
+                    },
                     GPUBindingResource::GPUBufferBinding(ref b) => {
                         wgpu_bind::BindingResource::Buffer(wgpu_bind::BufferBinding {
                             buffer_id: b.buffer.id().0,
@@ -886,6 +900,58 @@ impl GPUDeviceMethods for GPUDevice {
         )
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-importexternaltexture>
+    fn ImportExternalTexture(
+        &self,
+        descriptor: &GPUExternalTextureDescriptor,
+    ) -> Fallible<DomRoot<GPUExternalTexture>> {
+        // The decoder's frames live in shared memory unless the player renders
+        // to GL textures, which cannot be shared with a wgpu device.
+        let (data, size) = match descriptor.source.get_current_frame_data() {
+            Some((Some(data), size)) => (data, size),
+            Some((None, _)) => return Err(Error::NotSupported),
+            None => return Err(Error::InvalidState),
+        };
+
+        let texture_id = self
+            .global()
+            .wgpu_id_hub()
+            .lock()
+            .create_texture_id(self.device.0.backend());
+        let texture_view_id = self
+            .global()
+            .wgpu_id_hub()
+            .lock()
+            .create_texture_view_id(self.device.0.backend());
+
+        let scope_id = self.use_current_scope();
+        self.channel
+            .0
+            .send((
+                scope_id,
+                WebGPURequest::ImportExternalTexture {
+                    device_id: self.device.0,
+                    texture_id,
+                    texture_view_id,
+                    size: wgt::Extent3d {
+                        width: size.width,
+                        height: size.height,
+                        depth_or_array_layers: 1,
+                    },
+                    data,
+                },
+            ))
+            .expect("Failed to import WebGPU external texture");
+
+        Ok(GPUExternalTexture::new(
+            &self.global(),
+            self.channel.clone(),
+            webgpu::WebGPUTexture(texture_id),
+            webgpu::WebGPUTextureView(texture_view_id),
+            descriptor.parent.label.clone().unwrap_or_default(),
+        ))
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createrenderpipeline>
     fn CreateRenderPipeline(
         &self,
@@ -1079,6 +1145,44 @@ impl GPUDeviceMethods for GPUDevice {
         )
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+    fn CreateQuerySet(&self, descriptor: &GPUQuerySetDescriptor) -> DomRoot<GPUQuerySet> {
+        let query_set_id = self
+            .global()
+            .wgpu_id_hub()
+            .lock()
+            .create_query_set_id(self.device.0.backend());
+        let desc = wgt::QuerySetDescriptor {
+            label: convert_label(&descriptor.parent),
+            ty: convert_query_type(descriptor.type_, &descriptor.pipelineStatistics),
+            count: descriptor.count,
+        };
+
+        let scope_id = self.use_current_scope();
+        self.channel
+            .0
+            .send((
+                scope_id,
+                WebGPURequest::CreateQuerySet {
+                    device_id: self.device.0,
+                    query_set_id,
+                    descriptor: desc,
+                },
+            ))
+            .expect("Failed to create WebGPU query set");
+
+        let query_set = webgpu::WebGPUQuerySet(query_set_id);
+
+        GPUQuerySet::new(
+            &self.global(),
+            self.channel.clone(),
+            query_set,
+            descriptor.type_,
+            descriptor.count,
+            descriptor.parent.label.clone().unwrap_or_default(),
+        )
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-pusherrorscope>
     fn PushErrorScope(&self, filter: GPUErrorFilter) {
         let mut context = self.scope_context.borrow_mut();
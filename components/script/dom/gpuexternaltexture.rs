@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use webgpu::{WebGPU, WebGPURequest, WebGPUTexture, WebGPUTextureView};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WebGPUBinding::GPUExternalTextureMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::globalscope::GlobalScope;
+
+/// <https://gpuweb.github.io/gpuweb/#gpuexternaltexture>
+#[dom_struct]
+pub struct GPUExternalTexture {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    #[no_trace]
+    channel: WebGPU,
+    label: DomRefCell<USVString>,
+    /// The texture holding the video frame this object was imported from.
+    #[no_trace]
+    texture: WebGPUTexture,
+    #[no_trace]
+    texture_view: WebGPUTextureView,
+}
+
+impl GPUExternalTexture {
+    fn new_inherited(
+        channel: WebGPU,
+        texture: WebGPUTexture,
+        texture_view: WebGPUTextureView,
+        label: USVString,
+    ) -> Self {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            label: DomRefCell::new(label),
+            texture,
+            texture_view,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        texture: WebGPUTexture,
+        texture_view: WebGPUTextureView,
+        label: USVString,
+    ) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(GPUExternalTexture::new_inherited(
+                channel,
+                texture,
+                texture_view,
+                label,
+            )),
+            global,
+        )
+    }
+}
+
+impl GPUExternalTexture {
+    /// The view bind groups sample the imported frame through.
+    pub fn view_id(&self) -> WebGPUTextureView {
+        self.texture_view
+    }
+}
+
+impl Drop for GPUExternalTexture {
+    fn drop(&mut self) {
+        if let Err(e) = self
+            .channel
+            .0
+            .send((None, WebGPURequest::DestroyTexture(self.texture.0)))
+        {
+            warn!(
+                "Failed to send WebGPURequest::DestroyTexture({:?}) ({})",
+                self.texture.0, e
+            );
+        };
+    }
+}
+
+impl GPUExternalTextureMethods for GPUExternalTexture {
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
+    fn Label(&self) -> USVString {
+        self.label.borrow().clone()
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
+    fn SetLabel(&self, value: USVString) {
+        *self.label.borrow_mut() = value;
+    }
+}
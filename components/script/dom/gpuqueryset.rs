@@ -2,34 +2,116 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-#![allow(dead_code)] // this file is stub
+use std::cell::Cell;
 
 use dom_struct::dom_struct;
+use webgpu::{WebGPU, WebGPUQuerySet, WebGPURequest};
 
-use super::bindings::codegen::Bindings::WebGPUBinding::GPUQuerySetMethods;
-use super::bindings::str::USVString;
-use crate::dom::bindings::reflector::Reflector;
+use super::bindings::codegen::Bindings::WebGPUBinding::{GPUQuerySetMethods, GPUQueryType};
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::globalscope::GlobalScope;
 
 #[dom_struct]
 pub struct GPUQuerySet {
     reflector_: Reflector,
-    // #[ignore_malloc_size_of = "defined in wgpu-types"]
+    #[ignore_malloc_size_of = "defined in webgpu"]
+    #[no_trace]
+    channel: WebGPU,
+    label: DomRefCell<USVString>,
+    #[no_trace]
+    query_set: WebGPUQuerySet,
+    type_: GPUQueryType,
+    count: u32,
+    destroyed: Cell<bool>,
 }
 
-// TODO: wgpu does not expose right fields right now
-impl GPUQuerySetMethods for GPUQuerySet {
-    /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-destroy>
-    fn Destroy(&self) {
-        todo!()
+impl GPUQuerySet {
+    fn new_inherited(
+        channel: WebGPU,
+        query_set: WebGPUQuerySet,
+        type_: GPUQueryType,
+        count: u32,
+        label: USVString,
+    ) -> Self {
+        Self {
+            reflector_: Reflector::new(),
+            channel,
+            label: DomRefCell::new(label),
+            query_set,
+            type_,
+            count,
+            destroyed: Cell::new(false),
+        }
     }
 
+    pub fn new(
+        global: &GlobalScope,
+        channel: WebGPU,
+        query_set: WebGPUQuerySet,
+        type_: GPUQueryType,
+        count: u32,
+        label: USVString,
+    ) -> DomRoot<Self> {
+        reflect_dom_object(
+            Box::new(GPUQuerySet::new_inherited(
+                channel, query_set, type_, count, label,
+            )),
+            global,
+        )
+    }
+}
+
+impl Drop for GPUQuerySet {
+    fn drop(&mut self) {
+        self.Destroy()
+    }
+}
+
+impl GPUQuerySet {
+    pub fn id(&self) -> WebGPUQuerySet {
+        self.query_set
+    }
+}
+
+impl GPUQuerySetMethods for GPUQuerySet {
     /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
     fn Label(&self) -> USVString {
-        todo!()
+        self.label.borrow().clone()
     }
 
     /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
-    fn SetLabel(&self, _value: USVString) {
-        todo!()
+    fn SetLabel(&self, value: USVString) {
+        *self.label.borrow_mut() = value;
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-destroy>
+    fn Destroy(&self) {
+        if self.destroyed.get() {
+            return;
+        }
+        if let Err(e) = self
+            .channel
+            .0
+            .send((None, WebGPURequest::DestroyQuerySet(self.query_set.0)))
+        {
+            warn!(
+                "Failed to send WebGPURequest::DestroyQuerySet({:?}) ({})",
+                self.query_set.0, e
+            );
+        };
+        self.destroyed.set(true);
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-type>
+    fn Type(&self) -> GPUQueryType {
+        self.type_
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpuqueryset-count>
+    fn Count(&self) -> u32 {
+        self.count
     }
 }
@@ -72,6 +72,18 @@ impl GPUSupportedFeatures {
         if features.contains(wgt::Features::INDIRECT_FIRST_INSTANCE) {
             set.insert(GPUFeatureName::Indirect_first_instance);
         }
+        if features.contains(wgt::Features::SHADER_F16) {
+            set.insert(GPUFeatureName::Shader_f16);
+        }
+        if features.contains(wgt::Features::RG11B10UFLOAT_RENDERABLE) {
+            set.insert(GPUFeatureName::Rg11b10ufloat_renderable);
+        }
+        if features.contains(wgt::Features::BGRA8UNORM_STORAGE) {
+            set.insert(GPUFeatureName::Bgra8unorm_storage);
+        }
+        if features.contains(wgt::Features::FLOAT32_FILTERABLE) {
+            set.insert(GPUFeatureName::Float32_filterable);
+        }
         reflect_dom_object_with_proto(
             Box::new(GPUSupportedFeatures {
                 reflector: Reflector::new(),
@@ -92,6 +104,12 @@ impl GPUSupportedFeatures {
     }
 }
 
+impl GPUSupportedFeatures {
+    pub fn contains(&self, feature: GPUFeatureName) -> bool {
+        self.internal.borrow().contains(&feature)
+    }
+}
+
 impl GPUSupportedFeaturesMethods for GPUSupportedFeatures {
     fn Size(&self) -> u32 {
         self.internal.size()
@@ -109,6 +127,10 @@ pub fn gpu_to_wgt_feature(feature: GPUFeatureName) -> Option<wgt::Features> {
         GPUFeatureName::Texture_compression_astc => Some(wgt::Features::TEXTURE_COMPRESSION_ASTC),
         GPUFeatureName::Timestamp_query => Some(wgt::Features::TIMESTAMP_QUERY),
         GPUFeatureName::Indirect_first_instance => Some(wgt::Features::INDIRECT_FIRST_INSTANCE),
+        GPUFeatureName::Shader_f16 => Some(wgt::Features::SHADER_F16),
+        GPUFeatureName::Rg11b10ufloat_renderable => Some(wgt::Features::RG11B10UFLOAT_RENDERABLE),
+        GPUFeatureName::Bgra8unorm_storage => Some(wgt::Features::BGRA8UNORM_STORAGE),
+        GPUFeatureName::Float32_filterable => Some(wgt::Features::FLOAT32_FILTERABLE),
     }
 }
 
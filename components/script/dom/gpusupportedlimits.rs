@@ -31,6 +31,10 @@ impl GPUSupportedLimits {
     pub fn new(global: &GlobalScope, limits: Limits) -> DomRoot<Self> {
         reflect_dom_object(Box::new(Self::new_inherited(limits)), global)
     }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
 }
 
 impl GPUSupportedLimitsMethods for GPUSupportedLimits {
@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::SensorType;
+use js::rust::HandleObject;
+use script_traits::SensorReading;
+
+use crate::dom::bindings::codegen::Bindings::GyroscopeBinding::GyroscopeMethods;
+use crate::dom::bindings::codegen::Bindings::SensorBinding::SensorOptions;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::sensor::Sensor;
+use crate::dom::window::Window;
+
+#[dom_struct]
+pub struct Gyroscope {
+    sensor: Sensor,
+}
+
+impl Gyroscope {
+    fn new_inherited(options: &SensorOptions) -> Gyroscope {
+        Gyroscope {
+            sensor: Sensor::new_inherited(SensorType::Gyroscope, options),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &SensorOptions,
+    ) -> DomRoot<Gyroscope> {
+        reflect_dom_object_with_proto(Box::new(Gyroscope::new_inherited(options)), window, proto)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &SensorOptions,
+    ) -> DomRoot<Gyroscope> {
+        Gyroscope::new(window, proto, options)
+    }
+}
+
+impl GyroscopeMethods for Gyroscope {
+    /// <https://w3c.github.io/gyroscope/#dom-gyroscope-x>
+    fn GetX(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::Gyroscope { x, .. }) => Some(Finite::wrap(x)),
+            _ => None,
+        }
+    }
+
+    /// <https://w3c.github.io/gyroscope/#dom-gyroscope-y>
+    fn GetY(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::Gyroscope { y, .. }) => Some(Finite::wrap(y)),
+            _ => None,
+        }
+    }
+
+    /// <https://w3c.github.io/gyroscope/#dom-gyroscope-z>
+    fn GetZ(&self) -> Option<Finite<f64>> {
+        match self.sensor.reading() {
+            Some(SensorReading::Gyroscope { z, .. }) => Some(Finite::wrap(z)),
+            _ => None,
+        }
+    }
+}
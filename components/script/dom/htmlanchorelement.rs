@@ -5,6 +5,7 @@
 use std::default::Default;
 
 use dom_struct::dom_struct;
+use embedder_traits::WindowFeatures;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::rust::HandleObject;
 use net_traits::request::Referrer;
@@ -654,10 +655,29 @@ pub fn follow_hyperlink(subject: &Element, hyperlink_suffix: Option<String>) {
     // Step 6.
     let noopener = get_element_noopener(subject, target_attribute_value.clone());
 
+    // Step 10, 11, parsed before choosing a browsing context so that a blocked popup can
+    // say what it would have loaded. TODO: if parsing the URL failed, navigate to error page.
+    let attribute = subject.get_attribute(&ns!(), &local_name!("href")).unwrap();
+    let mut href = attribute.Value();
+    // Step 11: append a hyperlink suffix.
+    // https://www.w3.org/Bugs/Public/show_bug.cgi?id=28925
+    if let Some(suffix) = hyperlink_suffix {
+        href.push_str(&suffix);
+    }
+    let url = match document.base_url().join(&href) {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+
     // Step 7.
     let (maybe_chosen, replace) = match target_attribute_value {
         Some(name) => {
-            let (maybe_chosen, new) = source.choose_browsing_context(name, noopener);
+            let (maybe_chosen, new) = source.choose_browsing_context(
+                name,
+                noopener,
+                Some(url.clone()),
+                WindowFeatures::default(),
+            );
             let replace = if new {
                 HistoryEntryReplacement::Enabled
             } else {
@@ -683,19 +703,6 @@ pub fn follow_hyperlink(subject: &Element, hyperlink_suffix: Option<String>) {
         // will have been done as part of Step 7 above
         // in choose_browsing_context/create_auxiliary_browsing_context.
 
-        // Step 10, 11. TODO: if parsing the URL failed, navigate to error page.
-        let attribute = subject.get_attribute(&ns!(), &local_name!("href")).unwrap();
-        let mut href = attribute.Value();
-        // Step 11: append a hyperlink suffix.
-        // https://www.w3.org/Bugs/Public/show_bug.cgi?id=28925
-        if let Some(suffix) = hyperlink_suffix {
-            href.push_str(&suffix);
-        }
-        let url = match document.base_url().join(&href) {
-            Ok(url) => url,
-            Err(_) => return,
-        };
-
         // Step 12.
         let referrer_policy = referrer_policy_for_element(subject);
 
@@ -710,7 +717,7 @@ pub fn follow_hyperlink(subject: &Element, hyperlink_suffix: Option<String>) {
         // Step 14
         let pipeline_id = target_window.upcast::<GlobalScope>().pipeline_id();
         let secure = target_window.upcast::<GlobalScope>().is_secure_context();
-        let load_data = LoadData::new(
+        let mut load_data = LoadData::new(
             LoadOrigin::Script(document.origin().immutable().clone()),
             url,
             Some(pipeline_id),
@@ -718,6 +725,7 @@ pub fn follow_hyperlink(subject: &Element, hyperlink_suffix: Option<String>) {
             referrer_policy,
             Some(secure),
         );
+        load_data.initiator_pipeline_id = Some(document.window().pipeline_id());
         let target = Trusted::new(target_window);
         let task = task!(navigate_follow_hyperlink: move || {
             debug!("following hyperlink to {}", load_data.url);
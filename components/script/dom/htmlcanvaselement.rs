@@ -18,6 +18,7 @@ use script_traits::ScriptMsg;
 use servo_media::streams::registry::MediaStreamId;
 use servo_media::streams::MediaStreamType;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
+use webrender_api::ExternalImageId;
 
 use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref};
@@ -201,6 +202,18 @@ impl HTMLCanvasElement {
         ref_filter_map(self.context.borrow(), |ctx| ctx.as_ref())
     }
 
+    /// The WebRender external image that the front buffer of this canvas is presented
+    /// through. Only WebGL contexts render into a texture that can be shared this way.
+    pub fn external_image_id(&self) -> Option<ExternalImageId> {
+        match *self.context()? {
+            CanvasContext::WebGL(ref context) => Some(ExternalImageId(context.context_id().0)),
+            CanvasContext::WebGL2(ref context) => {
+                Some(ExternalImageId(context.base_context().context_id().0))
+            },
+            CanvasContext::Context2d(_) | CanvasContext::WebGPU(_) => None,
+        }
+    }
+
     fn get_or_init_2d_context(&self) -> Option<DomRoot<CanvasRenderingContext2D>> {
         if let Some(ctx) = self.context() {
             return match *ctx {
@@ -129,6 +129,36 @@ impl HTMLElementMethods for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#the-dir-attribute
     make_setter!(SetDir, "dir");
 
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn Spellcheck(&self) -> bool {
+        let element = self.upcast::<Element>();
+        match element
+            .get_attribute(&ns!(), &local_name!("spellcheck"))
+            .map(|attr| attr.value().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("true") | Some("") => return true,
+            Some("false") => return false,
+            _ => {},
+        }
+        // Text controls are true-by-default, and every other element is
+        // inherit-by-default.
+        let node = self.upcast::<Node>();
+        if node.is::<HTMLInputElement>() || node.is::<HTMLTextAreaElement>() {
+            return true;
+        }
+        node.GetParentElement()
+            .and_then(DomRoot::downcast::<HTMLElement>)
+            .map_or(false, |parent| parent.Spellcheck())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-spellcheck
+    fn SetSpellcheck(&self, spellcheck: bool) {
+        let value = if spellcheck { "true" } else { "false" };
+        self.upcast::<Element>()
+            .set_string_attribute(&local_name!("spellcheck"), DOMString::from(value));
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-hidden
     make_bool_getter!(Hidden, "hidden");
     // https://html.spec.whatwg.org/multipage/#dom-hidden
@@ -7,6 +7,7 @@ use std::cell::Cell;
 use std::time::{Duration, Instant};
 
 use dom_struct::dom_struct;
+use embedder_traits::WindowFeatures;
 use encoding_rs::{Encoding, UTF_8};
 use headers::{ContentType, HeaderMapExt};
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
@@ -813,8 +814,12 @@ impl HTMLFormElement {
 
         // Step 19
         let source = doc.browsing_context().unwrap();
-        let (maybe_chosen, _new) = source
-            .choose_browsing_context(target_attribute_value.unwrap_or(DOMString::new()), noopener);
+        let (maybe_chosen, _new) = source.choose_browsing_context(
+            target_attribute_value.unwrap_or(DOMString::new()),
+            noopener,
+            Some(action_components.clone()),
+            WindowFeatures::default(),
+        );
 
         // Step 20
         let chosen = match maybe_chosen {
@@ -835,6 +840,8 @@ impl HTMLFormElement {
             target_document.get_referrer_policy(),
             Some(target_window.upcast::<GlobalScope>().is_secure_context()),
         );
+        load_data.is_form_submission = true;
+        load_data.initiator_pipeline_id = Some(doc.window().pipeline_id());
 
         // Step 22
         match (&*scheme, method) {
@@ -175,6 +175,11 @@ impl HTMLImageElement {
     pub fn get_url(&self) -> Option<ServoUrl> {
         self.current_request.borrow().parsed_url.clone()
     }
+
+    /// The image of the current request, once some of it is decoded.
+    pub fn current_image(&self) -> Option<Arc<Image>> {
+        self.current_request.borrow().image.clone()
+    }
     // https://html.spec.whatwg.org/multipage/#check-the-usability-of-the-image-argument
     pub fn is_usable(&self) -> Fallible<bool> {
         // If image has an intrinsic width or intrinsic height (or both) equal to zero, then return bad.
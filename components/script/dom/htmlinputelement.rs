@@ -11,7 +11,7 @@ use std::{f64, ptr};
 use chrono::naive::{NaiveDate, NaiveDateTime};
 use chrono::{DateTime, Datelike, Weekday};
 use dom_struct::dom_struct;
-use embedder_traits::FilterPattern;
+use embedder_traits::{FilePickerRequest, FilterPattern, Misspelling};
 use encoding_rs::Encoding;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::jsapi::{
@@ -27,7 +27,7 @@ use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
 use script_layout_interface::rpc::TextIndexResponse;
-use script_traits::ScriptToConstellationChan;
+use script_traits::{EditingCommand, ScriptToConstellationChan};
 use servo_atoms::Atom;
 use style::attr::AttrValue;
 use style::str::{split_commas, str_join};
@@ -77,6 +77,7 @@ use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::realms::enter_realm;
 use crate::script_runtime::JSContext as SafeJSContext;
+use crate::spellcheck;
 use crate::textinput::KeyReaction::{
     DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction,
 };
@@ -258,6 +259,10 @@ pub struct HTMLInputElement {
     #[ignore_malloc_size_of = "#7193"]
     #[no_trace]
     textinput: DomRefCell<TextInput<ScriptToConstellationChan>>,
+    /// The misspelled words last reported to the embedder.
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    #[no_trace]
+    misspellings: DomRefCell<Vec<Misspelling>>,
     // https://html.spec.whatwg.org/multipage/#concept-input-value-dirty-flag
     value_dirty: Cell<bool>,
     // not specified explicitly, but implied by the fact that sanitization can't
@@ -318,6 +323,7 @@ impl HTMLInputElement {
                 None,
                 SelectionDirection::None,
             )),
+            misspellings: DomRefCell::new(vec![]),
             value_dirty: Cell::new(false),
             sanitization_flag: Cell::new(true),
             filelist: MutNullableDom::new(None),
@@ -1471,6 +1477,18 @@ impl HTMLInputElementMethods for HTMLInputElement {
     // https://html.spec.whatwg.org/multipage/#dom-input-multiple
     make_bool_setter!(SetMultiple, "multiple");
 
+    // https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory
+    fn Webkitdirectory(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("webkitdirectory"))
+    }
+
+    // https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory
+    fn SetWebkitdirectory(&self, value: bool) {
+        self.upcast::<Element>()
+            .set_bool_attribute(&LocalName::from("webkitdirectory"), value)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-input-pattern
     make_getter!(Pattern, "pattern");
 
@@ -1824,6 +1842,63 @@ impl HTMLInputElement {
         !(self.upcast::<Element>().disabled_state() || self.ReadOnly())
     }
 
+    /// Run an editing command from the embedder on the value of this control. The
+    /// value of password fields can't be copied or cut.
+    pub(crate) fn handle_editing_command(&self, command: EditingCommand) {
+        let input_type = self.input_type();
+        if !input_type.is_textual_or_password() {
+            return;
+        }
+        let is_password = input_type == InputType::Password;
+        let changed = {
+            let mut textinput = self.textinput.borrow_mut();
+            let old_value = textinput.get_content();
+            match command {
+                EditingCommand::SelectAll => textinput.select_all(),
+                EditingCommand::Copy if !is_password => textinput.copy(),
+                EditingCommand::Cut if !is_password && self.is_mutable() => textinput.cut(),
+                EditingCommand::Paste if self.is_mutable() => textinput.paste(),
+                EditingCommand::Undo if self.is_mutable() => {
+                    textinput.undo();
+                },
+                _ => return,
+            }
+            textinput.get_content() != old_value
+        };
+
+        if changed {
+            self.value_dirty.set(true);
+            self.update_placeholder_shown_state();
+            if matches!(input_type, InputType::Text | InputType::Search) {
+                spellcheck::check_text_control(
+                    self.upcast(),
+                    &self.textinput.borrow().get_content(),
+                    &self.misspellings,
+                );
+            }
+            let window = window_from_node(self);
+            let _ = window
+                .task_manager()
+                .user_interaction_task_source()
+                .queue_event(
+                    &self.upcast(),
+                    atom!("input"),
+                    EventBubbles::Bubbles,
+                    EventCancelable::NotCancelable,
+                    &window,
+                );
+        }
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// The selected part of the value of this control, unless it is a password.
+    pub(crate) fn selection_text(&self) -> Option<String> {
+        if !self.input_type().is_textual() {
+            return None;
+        }
+        self.textinput.borrow().get_selection_text()
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-input-element:concept-form-reset-control
     pub fn reset(&self) {
         match self.input_type() {
@@ -1861,16 +1936,29 @@ impl HTMLInputElement {
         let mut files: Vec<DomRoot<File>> = vec![];
         let mut error = None;
 
+        let webview_id = window.window_proxy().top_level_browsing_context_id();
         let filter = filter_from_accept(&self.Accept());
         let target = self.upcast::<EventTarget>();
 
-        if self.Multiple() {
+        // The files of a directory are listed by the file manager, off the script thread.
+        if self.Multiple() || self.Webkitdirectory() {
             let opt_test_paths =
                 opt_test_paths.map(|paths| paths.iter().map(|p| p.to_string()).collect());
 
             let (chan, recv) = ipc::channel(self.global().time_profiler_chan().clone())
                 .expect("Error initializing channel");
-            let msg = FileManagerThreadMsg::SelectFiles(filter, chan, origin, opt_test_paths);
+            let request = FilePickerRequest {
+                filter_patterns: filter,
+                multiple: self.Multiple(),
+                directory: self.Webkitdirectory(),
+            };
+            let msg = FileManagerThreadMsg::SelectFiles(
+                webview_id,
+                request,
+                chan,
+                origin,
+                opt_test_paths,
+            );
             let _ = resource_threads
                 .send(CoreResourceMsg::ToFileManager(msg))
                 .unwrap();
@@ -1897,7 +1985,8 @@ impl HTMLInputElement {
 
             let (chan, recv) = ipc::channel(self.global().time_profiler_chan().clone())
                 .expect("Error initializing channel");
-            let msg = FileManagerThreadMsg::SelectFile(filter, chan, origin, opt_test_path);
+            let msg =
+                FileManagerThreadMsg::SelectFile(webview_id, filter, chan, origin, opt_test_path);
             let _ = resource_threads
                 .send(CoreResourceMsg::ToFileManager(msg))
                 .unwrap();
@@ -2067,7 +2156,7 @@ impl HTMLInputElement {
     }
 
     #[allow(crown::unrooted_must_root)]
-    fn selection(&self) -> TextControlSelection<Self> {
+    pub(crate) fn selection(&self) -> TextControlSelection<Self> {
         TextControlSelection::new(&self, &self.textinput)
     }
 
@@ -2587,6 +2676,13 @@ impl VirtualMethods for HTMLInputElement {
                     DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
+                        if matches!(self.input_type(), InputType::Text | InputType::Search) {
+                            spellcheck::check_text_control(
+                                self.upcast(),
+                                &self.textinput.borrow().get_content(),
+                                &self.misspellings,
+                            );
+                        }
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -2619,10 +2715,16 @@ impl VirtualMethods for HTMLInputElement {
             event.type_() == atom!("compositionend")) &&
             self.input_type().is_textual_or_password()
         {
-            // TODO: Update DOM on start and continue
-            // and generally do proper CompositionEvent handling.
+            // The text being composed is part of the value until the composition ends and
+            // it is replaced with the text that is committed.
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
+                if event.type_() == atom!("compositionupdate") {
+                    let _ = self
+                        .textinput
+                        .borrow_mut()
+                        .handle_compositionupdate(compositionevent);
+                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                } else if event.type_() == atom!("compositionend") {
                     let _ = self
                         .textinput
                         .borrow_mut()
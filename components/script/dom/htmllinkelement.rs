@@ -8,7 +8,6 @@ use std::default::Default;
 
 use cssparser::{Parser as CssParser, ParserInput};
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::rust::HandleObject;
 use net_traits::ReferrerPolicy;
@@ -42,6 +41,7 @@ use crate::dom::node::{
 };
 use crate::dom::stylesheet::StyleSheet as DOMStyleSheet;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::favicon::{self, IconLink};
 use crate::stylesheet_loader::{StylesheetContextSource, StylesheetLoader, StylesheetOwner};
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
@@ -209,21 +209,14 @@ impl VirtualMethods for HTMLLinkElement {
                 if string_is_stylesheet(&rel) {
                     self.handle_stylesheet_url(&attr.value());
                 } else if is_favicon(&rel) {
-                    let sizes = get_attr(self.upcast(), &local_name!("sizes"));
-                    self.handle_favicon_url(rel.as_ref().unwrap(), &attr.value(), &sizes);
+                    favicon::update_favicon(&document_from_node(self));
                 }
             },
-            &local_name!("sizes") => {
-                if is_favicon(&rel) {
-                    if let Some(ref href) = get_attr(self.upcast(), &local_name!("href")) {
-                        self.handle_favicon_url(
-                            rel.as_ref().unwrap(),
-                            href,
-                            &Some(attr.value().to_string()),
-                        );
-                    }
-                }
+            &local_name!("sizes") | &local_name!("type") if is_favicon(&rel) => {
+                favicon::update_favicon(&document_from_node(self));
             },
+            // This may have stopped being an icon.
+            &local_name!("rel") => favicon::update_favicon(&document_from_node(self)),
             _ => {},
         }
     }
@@ -248,14 +241,13 @@ impl VirtualMethods for HTMLLinkElement {
 
             let rel = get_attr(element, &local_name!("rel"));
             let href = get_attr(element, &local_name!("href"));
-            let sizes = get_attr(self.upcast(), &local_name!("sizes"));
 
             match href {
                 Some(ref href) if string_is_stylesheet(&rel) => {
                     self.handle_stylesheet_url(href);
                 },
-                Some(ref href) if is_favicon(&rel) => {
-                    self.handle_favicon_url(rel.as_ref().unwrap(), href, &sizes);
+                Some(_) if is_favicon(&rel) => {
+                    favicon::update_favicon(&document_from_node(self));
                 },
                 _ => {},
             }
@@ -271,6 +263,10 @@ impl VirtualMethods for HTMLLinkElement {
             self.clean_stylesheet_ownership();
             stylesheets_owner_from_node(self).remove_stylesheet(self.upcast(), &s);
         }
+
+        if is_favicon(&get_attr(self.upcast(), &local_name!("rel"))) {
+            favicon::update_favicon(&document_from_node(self));
+        }
     }
 }
 
@@ -348,18 +344,24 @@ impl HTMLLinkElement {
         );
     }
 
-    fn handle_favicon_url(&self, _rel: &str, href: &str, _sizes: &Option<String>) {
-        let document = document_from_node(self);
-        match document.base_url().join(href) {
-            Ok(url) => {
-                let window = document.window();
-                if window.is_top_level() {
-                    let msg = EmbedderMsg::NewFavicon(url.clone());
-                    window.send_to_embedder(msg);
-                }
-            },
-            Err(e) => debug!("Parsing url {} failed: {}", href, e),
+    /// The icon that this element declares, if it could be the favicon of its document.
+    /// Scalable icons aren't, as they can't be decoded.
+    pub(crate) fn icon(&self) -> Option<IconLink> {
+        let element = self.upcast::<Element>();
+        if !is_favicon(&get_attr(element, &local_name!("rel"))) {
+            return None;
+        }
+        let is_scalable = get_attr(element, &local_name!("type")).map_or(false, |icon_type| {
+            icon_type.eq_ignore_ascii_case("image/svg+xml")
+        });
+        if is_scalable {
+            return None;
         }
+        let href = get_attr(element, &local_name!("href")).filter(|href| !href.is_empty())?;
+        let url = document_from_node(self).base_url().join(&href).ok()?;
+        let size = get_attr(element, &local_name!("sizes"))
+            .map_or(0, |sizes| favicon::largest_size(&sizes));
+        Some(IconLink { url, size })
     }
 }
 
@@ -11,7 +11,9 @@ use std::{f64, mem};
 
 use dom_struct::dom_struct;
 use embedder_traits::resources::{self, Resource as EmbedderResource};
-use embedder_traits::{MediaPositionState, MediaSessionEvent, MediaSessionPlaybackState};
+use embedder_traits::{
+    MediaPositionState, MediaSessionEvent, MediaSessionPlaybackState, PictureInPictureEvent,
+};
 use euclid::default::Size2D;
 use headers::{ContentLength, ContentRange, HeaderMapExt};
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
@@ -34,6 +36,7 @@ use servo_media::player::video::{VideoFrame, VideoFrameRenderer};
 use servo_media::player::{PlaybackState, Player, PlayerError, PlayerEvent, SeekLock, StreamType};
 use servo_media::{ClientContextId, ServoMedia, SupportsMediaType};
 use servo_url::ServoUrl;
+use webrender_api::units::DeviceIntSize;
 use webrender_api::{
     ExternalImageData, ExternalImageId, ExternalImageType, ImageBufferKind, ImageData,
     ImageDescriptor, ImageDescriptorFlags, ImageFormat, ImageKey,
@@ -159,6 +162,8 @@ pub struct MediaFrameRenderer {
     old_frame: Option<ImageKey>,
     very_old_frame: Option<ImageKey>,
     current_frame_holder: Option<FrameHolder>,
+    /// How long it took to hand the last frame over to WebRender.
+    last_frame_processing_duration: Option<Duration>,
 }
 
 impl MediaFrameRenderer {
@@ -170,6 +175,7 @@ impl MediaFrameRenderer {
             old_frame: None,
             very_old_frame: None,
             current_frame_holder: None,
+            last_frame_processing_duration: None,
         }
     }
 
@@ -182,6 +188,7 @@ impl MediaFrameRenderer {
 
 impl VideoFrameRenderer for MediaFrameRenderer {
     fn render(&mut self, frame: VideoFrame) {
+        let start = Instant::now();
         let mut updates = vec![];
 
         if let Some(old_image_key) = mem::replace(&mut self.very_old_frame, self.old_frame.take()) {
@@ -279,6 +286,7 @@ impl VideoFrameRenderer for MediaFrameRenderer {
             },
         }
         self.api.update_images(updates);
+        self.last_frame_processing_duration = Some(start.elapsed());
     }
 }
 
@@ -1066,6 +1074,20 @@ impl HTMLMediaElement {
         !self.is_blocked_media_element()
     }
 
+    /// Whether this element is playing sound that the user would hear if the webview
+    /// weren't muted.
+    pub fn is_audible(&self) -> bool {
+        self.is_potentially_playing() && !self.muted.get() && self.volume.get() > 0.
+    }
+
+    /// Mute the player if either the element or its webview is muted.
+    pub fn apply_mute(&self) {
+        if let Some(ref player) = *self.player.borrow() {
+            let muted = self.muted.get() || window_from_node(self).audio_muted();
+            let _ = player.lock().unwrap().set_mute(muted);
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#blocked-media-element
     fn is_blocked_media_element(&self) -> bool {
         self.ready_state.get() <= ReadyState::HaveCurrentData ||
@@ -1374,6 +1396,7 @@ impl HTMLMediaElement {
         );
 
         *self.player.borrow_mut() = Some(player);
+        self.apply_mute();
 
         let trusted_node = Trusted::new(self);
         let (task_source, canceller) = window
@@ -1559,6 +1582,10 @@ impl HTMLMediaElement {
             },
             PlayerEvent::VideoFrameUpdated => {
                 self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                self.send_picture_in_picture_frame();
+                if let Some(video) = self.downcast::<HTMLVideoElement>() {
+                    video.video_frame_presented();
+                }
             },
             PlayerEvent::MetadataUpdated(ref metadata) => {
                 // https://html.spec.whatwg.org/multipage/#media-data-processing-steps-list
@@ -1848,9 +1875,17 @@ impl HTMLMediaElement {
                     "Sending media session event playback state changed to {:?}",
                     media_session_playback_state
                 );
+                if self.is_picture_in_picture_element() {
+                    document_from_node(self).send_picture_in_picture_event(
+                        PictureInPictureEvent::PlaybackStateChange(
+                            media_session_playback_state.clone(),
+                        ),
+                    );
+                }
                 self.send_media_session_event(MediaSessionEvent::PlaybackStateChange(
                     media_session_playback_state,
                 ));
+                window_from_node(self).update_audio_state();
             },
         }
     }
@@ -1927,6 +1962,15 @@ impl HTMLMediaElement {
         }
     }
 
+    /// How long the media pipeline spent preparing the current frame for
+    /// presentation, once decoded.
+    pub fn get_current_frame_processing_duration(&self) -> Option<Duration> {
+        self.video_renderer
+            .lock()
+            .unwrap()
+            .last_frame_processing_duration
+    }
+
     pub fn get_current_frame(&self) -> Option<VideoFrame> {
         match self.video_renderer.lock().unwrap().current_frame_holder {
             Some(ref holder) => Some(holder.get_frame()),
@@ -1948,6 +1992,30 @@ impl HTMLMediaElement {
         }
     }
 
+    /// Whether this is the video its document shows in picture-in-picture.
+    fn is_picture_in_picture_element(&self) -> bool {
+        self.downcast::<HTMLVideoElement>().map_or(false, |video| {
+            document_from_node(self)
+                .picture_in_picture_element()
+                .as_deref() ==
+                Some(video)
+        })
+    }
+
+    /// Hand the current frame to the embedder's picture-in-picture surface, if this
+    /// video is shown in it.
+    pub fn send_picture_in_picture_frame(&self) {
+        if !self.is_picture_in_picture_element() {
+            return;
+        }
+        let current_frame = self.video_renderer.lock().unwrap().current_frame;
+        if let Some((image_key, width, height)) = current_frame {
+            document_from_node(self).send_picture_in_picture_event(
+                PictureInPictureEvent::FrameUpdated(image_key, DeviceIntSize::new(width, height)),
+            );
+        }
+    }
+
     fn send_media_session_event(&self, event: MediaSessionEvent) {
         let global = self.global();
         let media_session = global.as_window().Navigator().MediaSession();
@@ -2058,12 +2126,10 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             return;
         }
 
-        if let Some(ref player) = *self.player.borrow() {
-            let _ = player.lock().unwrap().set_mute(value);
-        }
-
         self.muted.set(value);
+        self.apply_mute();
         let window = window_from_node(self);
+        window.update_audio_state();
         window
             .task_manager()
             .media_element_task_source()
@@ -2406,6 +2472,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             self.volume.set(*value);
 
             let window = window_from_node(self);
+            window.update_audio_state();
             window
                 .task_manager()
                 .media_element_task_source()
@@ -2454,6 +2521,11 @@ impl VirtualMethods for HTMLMediaElement {
 
         self.remove_controls();
 
+        // https://w3c.github.io/picture-in-picture/#remove-video
+        if self.is_picture_in_picture_element() {
+            document_from_node(self).leave_picture_in_picture();
+        }
+
         if context.tree_connected {
             let task = MediaElementMicrotask::PauseIfNotInDocumentTask {
                 elem: DomRoot::from_ref(self),
@@ -522,6 +522,7 @@ pub(crate) fn script_fetch_request(
     .pipeline_id(Some(pipeline_id))
     .parser_metadata(options.parser_metadata)
     .integrity_metadata(options.integrity_metadata.clone())
+    .cryptographic_nonce_metadata(options.cryptographic_nonce.clone())
     .referrer_policy(options.referrer_policy)
 }
 
@@ -698,7 +699,11 @@ impl HTMLScriptElement {
             ),
         };
 
-        // TODO: Step 20: Nonce.
+        // Step 20: Nonce.
+        let cryptographic_nonce = element
+            .get_attribute(&ns!(), &local_name!("nonce"))
+            .map(|attr| attr.value().to_string())
+            .unwrap_or_default();
 
         // Step 21: Integrity metadata.
         let im_attribute = element.get_attribute(&ns!(), &local_name!("integrity"));
@@ -719,7 +724,7 @@ impl HTMLScriptElement {
 
         // Step 24.
         let options = ScriptFetchOptions {
-            cryptographic_nonce: "".into(),
+            cryptographic_nonce,
             integrity_metadata: integrity_metadata.to_owned(),
             parser_metadata,
             referrer: self.global().get_referrer(),
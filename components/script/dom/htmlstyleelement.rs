@@ -4,6 +4,7 @@
 
 use std::cell::Cell;
 
+use content_security_policy as csp;
 use cssparser::{Parser as CssParser, ParserInput};
 use dom_struct::dom_struct;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
@@ -100,6 +101,17 @@ impl HTMLStyleElement {
         let data = node
             .GetTextContent()
             .expect("Element.textContent must be a string");
+
+        // https://html.spec.whatwg.org/multipage/#update-a-style-block Step 5
+        if doc.should_elements_inline_type_behavior_be_blocked(
+            element,
+            csp::InlineCheckType::Style,
+            &data,
+        ) == csp::CheckResult::Blocked
+        {
+            return;
+        }
+
         let url_data = UrlExtraData(window.get_url().get_arc());
         let css_error_reporter = window.css_error_reporter();
         let context = CssParserContext::new(
@@ -7,9 +7,10 @@ use std::default::Default;
 use std::ops::Range;
 
 use dom_struct::dom_struct;
+use embedder_traits::Misspelling;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::rust::HandleObject;
-use script_traits::ScriptToConstellationChan;
+use script_traits::{EditingCommand, ScriptToConstellationChan};
 use style::attr::AttrValue;
 use style_traits::dom::ElementState;
 
@@ -42,6 +43,7 @@ use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
 use crate::dom::validation::{is_barred_by_datalist_ancestor, Validatable};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::spellcheck;
 use crate::textinput::{
     Direction, KeyReaction, Lines, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes,
 };
@@ -53,6 +55,10 @@ pub struct HTMLTextAreaElement {
     #[no_trace]
     textinput: DomRefCell<TextInput<ScriptToConstellationChan>>,
     placeholder: DomRefCell<DOMString>,
+    /// The misspelled words last reported to the embedder.
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    #[no_trace]
+    misspellings: DomRefCell<Vec<Misspelling>>,
     // https://html.spec.whatwg.org/multipage/#concept-textarea-dirty
     value_dirty: Cell<bool>,
     form_owner: MutNullableDom<HTMLFormElement>,
@@ -165,6 +171,7 @@ impl HTMLTextAreaElement {
                 None,
                 SelectionDirection::None,
             )),
+            misspellings: DomRefCell::new(vec![]),
             value_dirty: Cell::new(false),
             form_owner: Default::default(),
             labels_node_list: Default::default(),
@@ -206,6 +213,52 @@ impl HTMLTextAreaElement {
         // https://html.spec.whatwg.org/multipage/#the-readonly-attribute:concept-fe-mutable
         !(self.upcast::<Element>().disabled_state() || self.ReadOnly())
     }
+
+    /// Run an editing command from the embedder on the value of this control.
+    pub(crate) fn handle_editing_command(&self, command: EditingCommand) {
+        let changed = {
+            let mut textinput = self.textinput.borrow_mut();
+            let old_value = textinput.get_content();
+            match command {
+                EditingCommand::SelectAll => textinput.select_all(),
+                EditingCommand::Copy => textinput.copy(),
+                EditingCommand::Cut if self.is_mutable() => textinput.cut(),
+                EditingCommand::Paste if self.is_mutable() => textinput.paste(),
+                EditingCommand::Undo if self.is_mutable() => {
+                    textinput.undo();
+                },
+                _ => return,
+            }
+            textinput.get_content() != old_value
+        };
+
+        if changed {
+            self.value_dirty.set(true);
+            self.update_placeholder_shown_state();
+            spellcheck::check_text_control(
+                self.upcast(),
+                &self.textinput.borrow().get_content(),
+                &self.misspellings,
+            );
+            let window = window_from_node(self);
+            let _ = window
+                .task_manager()
+                .user_interaction_task_source()
+                .queue_event(
+                    &self.upcast(),
+                    atom!("input"),
+                    EventBubbles::Bubbles,
+                    EventCancelable::NotCancelable,
+                    &window,
+                );
+        }
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// The selected part of the value of this control.
+    pub(crate) fn selection_text(&self) -> Option<String> {
+        self.textinput.borrow().get_selection_text()
+    }
 }
 
 impl TextControlElement for HTMLTextAreaElement {
@@ -455,7 +508,7 @@ impl HTMLTextAreaElement {
     }
 
     #[allow(crown::unrooted_must_root)]
-    fn selection(&self) -> TextControlSelection<Self> {
+    pub(crate) fn selection(&self) -> TextControlSelection<Self> {
         TextControlSelection::new(&self, &self.textinput)
     }
 }
@@ -639,6 +692,11 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
+                        spellcheck::check_text_control(
+                            self.upcast(),
+                            &self.textinput.borrow().get_content(),
+                            &self.misspellings,
+                        );
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -667,10 +725,16 @@ impl VirtualMethods for HTMLTextAreaElement {
             event.type_() == atom!("compositionupdate") ||
             event.type_() == atom!("compositionend")
         {
-            // TODO: Update DOM on start and continue
-            // and generally do proper CompositionEvent handling.
+            // The text being composed is part of the value until the composition ends and
+            // it is replaced with the text that is committed.
             if let Some(compositionevent) = event.downcast::<CompositionEvent>() {
-                if event.type_() == atom!("compositionend") {
+                if event.type_() == atom!("compositionupdate") {
+                    let _ = self
+                        .textinput
+                        .borrow_mut()
+                        .handle_compositionupdate(compositionevent);
+                    self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                } else if event.type_() == atom!("compositionend") {
                     let _ = self
                         .textinput
                         .borrow_mut()
@@ -3,6 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::mem;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 use dom_struct::dom_struct;
@@ -25,19 +27,26 @@ use servo_url::ServoUrl;
 
 use crate::document_loader::{LoadBlocker, LoadType};
 use crate::dom::attr::Attr;
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
-use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::HTMLVideoElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLVideoElementBinding::{
+    HTMLVideoElementMethods, VideoFrameCallbackMetadata, VideoFrameRequestCallback,
+};
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::Document;
+use crate::dom::document::{AnimationFrameCallback, Document};
 use crate::dom::element::{AttributeMutation, Element};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlmediaelement::{HTMLMediaElement, ReadyState};
 use crate::dom::node::{document_from_node, window_from_node, Node};
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::promise::Promise;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::fetch::FetchCanceller;
 use crate::image_listener::{generate_cache_listener_for_element, ImageCacheListener};
@@ -46,6 +55,9 @@ use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingLi
 const DEFAULT_WIDTH: u32 = 300;
 const DEFAULT_HEIGHT: u32 = 150;
 
+/// <https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture>
+const DISABLE_PICTURE_IN_PICTURE: &str = "disablepictureinpicture";
+
 #[dom_struct]
 pub struct HTMLVideoElement {
     htmlmediaelement: HTMLMediaElement,
@@ -64,6 +76,29 @@ pub struct HTMLVideoElement {
     #[ignore_malloc_size_of = "VideoFrame"]
     #[no_trace]
     last_frame: DomRefCell<Option<VideoFrame>>,
+    /// <https://wicg.github.io/video-rvfc/#list-of-video-frame-request-callbacks>
+    #[ignore_malloc_size_of = "Rc is hard"]
+    video_frame_callbacks: DomRefCell<Vec<(u32, Option<Rc<VideoFrameRequestCallback>>)>>,
+    /// The handle given out to the last video frame request callback.
+    video_frame_callback_ident: Cell<u32>,
+    /// Whether the document has been asked to run the video frame request
+    /// callbacks during its next animation frame.
+    video_frame_callbacks_scheduled: Cell<bool>,
+    /// <https://wicg.github.io/video-rvfc/#dom-videoframecallbackmetadata-presentedframes>
+    presented_frames: Cell<u32>,
+    /// The metadata of the most recently presented frame.
+    last_presented_frame: Cell<Option<PresentedFrameMetadata>>,
+}
+
+/// What is known about a video frame at the time the media pipeline
+/// presented it.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+struct PresentedFrameMetadata {
+    presentation_time: f64,
+    width: u32,
+    height: u32,
+    media_time: f64,
+    processing_duration: Option<f64>,
 }
 
 impl HTMLVideoElement {
@@ -80,6 +115,11 @@ impl HTMLVideoElement {
             poster_frame_canceller: DomRefCell::new(Default::default()),
             load_blocker: Default::default(),
             last_frame: Default::default(),
+            video_frame_callbacks: Default::default(),
+            video_frame_callback_ident: Cell::new(0),
+            video_frame_callbacks_scheduled: Cell::new(false),
+            presented_frames: Cell::new(0),
+            last_presented_frame: Cell::new(None),
         }
     }
 
@@ -136,6 +176,63 @@ impl HTMLVideoElement {
         }
     }
 
+    /// Record the metadata of a frame the media pipeline just presented, and
+    /// make sure any pending video frame request callbacks run on the next
+    /// animation frame.
+    pub(crate) fn video_frame_presented(&self) {
+        let media = &self.htmlmediaelement;
+        let (width, height) = match media.get_current_frame() {
+            Some(frame) => (frame.get_width() as u32, frame.get_height() as u32),
+            None => (self.video_width.get(), self.video_height.get()),
+        };
+        self.presented_frames
+            .set(self.presented_frames.get().wrapping_add(1));
+        self.last_presented_frame.set(Some(PresentedFrameMetadata {
+            presentation_time: *self.global().performance().Now(),
+            width,
+            height,
+            media_time: *media.CurrentTime(),
+            processing_duration: media
+                .get_current_frame_processing_duration()
+                .map(|duration| duration.as_secs_f64()),
+        }));
+
+        if self.video_frame_callbacks.borrow().is_empty() ||
+            self.video_frame_callbacks_scheduled.replace(true)
+        {
+            return;
+        }
+        document_from_node(self).request_animation_frame(
+            AnimationFrameCallback::VideoFrameCallbacks {
+                video: Trusted::new(self),
+            },
+        );
+    }
+
+    /// <https://wicg.github.io/video-rvfc/#video-rvfc-procedures>
+    pub(crate) fn run_video_frame_callbacks(&self, now: f64) {
+        self.video_frame_callbacks_scheduled.set(false);
+        let frame = match self.last_presented_frame.get() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        // Callbacks registered from within a callback wait for the next frame.
+        let callbacks = mem::take(&mut *self.video_frame_callbacks.borrow_mut());
+        let metadata = VideoFrameCallbackMetadata {
+            presentationTime: Finite::wrap(frame.presentation_time),
+            expectedDisplayTime: Finite::wrap(now),
+            width: frame.width,
+            height: frame.height,
+            mediaTime: Finite::wrap(frame.media_time),
+            presentedFrames: self.presented_frames.get(),
+            processingDuration: frame.processing_duration.map(Finite::wrap),
+        };
+        for callback in callbacks.into_iter().filter_map(|(_, callback)| callback) {
+            let _ = callback.Call__(Finite::wrap(now), &metadata, ExceptionHandling::Report);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#poster-frame>
     fn fetch_poster_frame(&self, poster_url: &str) {
         // Step 1.
@@ -265,6 +362,55 @@ impl HTMLVideoElementMethods for HTMLVideoElement {
     // https://html.spec.whatwg.org/multipage/#dom-video-poster
     make_setter!(SetPoster, "poster");
 
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-requestpictureinpicture
+    fn RequestPictureInPicture(&self) -> Rc<Promise> {
+        document_from_node(self).request_picture_in_picture(self)
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture
+    fn DisablePictureInPicture(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from(DISABLE_PICTURE_IN_PICTURE))
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-disablepictureinpicture
+    fn SetDisablePictureInPicture(&self, value: bool) {
+        self.upcast::<Element>()
+            .set_bool_attribute(&LocalName::from(DISABLE_PICTURE_IN_PICTURE), value)
+    }
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-onenterpictureinpicture
+    event_handler!(
+        enterpictureinpicture,
+        GetOnenterpictureinpicture,
+        SetOnenterpictureinpicture
+    );
+
+    // https://w3c.github.io/picture-in-picture/#dom-htmlvideoelement-onleavepictureinpicture
+    event_handler!(
+        leavepictureinpicture,
+        GetOnleavepictureinpicture,
+        SetOnleavepictureinpicture
+    );
+
+    // https://wicg.github.io/video-rvfc/#dom-htmlvideoelement-requestvideoframecallback
+    fn RequestVideoFrameCallback(&self, callback: Rc<VideoFrameRequestCallback>) -> u32 {
+        let ident = self.video_frame_callback_ident.get() + 1;
+        self.video_frame_callback_ident.set(ident);
+        self.video_frame_callbacks
+            .borrow_mut()
+            .push((ident, Some(callback)));
+        ident
+    }
+
+    // https://wicg.github.io/video-rvfc/#dom-htmlvideoelement-cancelvideoframecallback
+    fn CancelVideoFrameCallback(&self, handle: u32) {
+        let mut callbacks = self.video_frame_callbacks.borrow_mut();
+        if let Some(pair) = callbacks.iter_mut().find(|pair| pair.0 == handle) {
+            pair.1 = None;
+        }
+    }
+
     // For testing purposes only. This is not an event from
     // https://html.spec.whatwg.org/multipage/#dom-video-poster
     event_handler!(postershown, GetOnpostershown, SetOnpostershown);
@@ -283,6 +429,13 @@ impl VirtualMethods for HTMLVideoElement {
                 &local_name!("poster") => {
                     self.fetch_poster_frame(&new_value);
                 },
+                // https://w3c.github.io/picture-in-picture/#disable-pip
+                name if &**name == DISABLE_PICTURE_IN_PICTURE => {
+                    let document = document_from_node(self);
+                    if document.picture_in_picture_element().as_deref() == Some(self) {
+                        document.leave_picture_in_picture();
+                    }
+                },
                 _ => (),
             };
         }
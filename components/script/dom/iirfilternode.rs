@@ -0,0 +1,164 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use dom_struct::dom_struct;
+use js::rust::{CustomAutoRooterGuard, HandleObject};
+use js::typedarray::Float32Array;
+use servo_media::audio::iir_filter_node::IIRFilterNodeOptions;
+use servo_media::audio::node::AudioNodeInit;
+
+use crate::dom::audionode::AudioNode;
+use crate::dom::baseaudiocontext::BaseAudioContext;
+use crate::dom::bindings::codegen::Bindings::AudioNodeBinding::{
+    ChannelCountMode, ChannelInterpretation,
+};
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContext_Binding::BaseAudioContextMethods;
+use crate::dom::bindings::codegen::Bindings::IIRFilterNodeBinding::{
+    IIRFilterNodeMethods, IIRFilterOptions,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+
+/// The maximum number of coefficients in either direction.
+/// <https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createiirfilter>
+const MAX_COEFFICIENTS: usize = 20;
+
+#[dom_struct]
+pub struct IIRFilterNode {
+    node: AudioNode,
+    feedforward: Vec<f64>,
+    feedback: Vec<f64>,
+    sample_rate: f32,
+}
+
+impl IIRFilterNode {
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_inherited(
+        _window: &Window,
+        context: &BaseAudioContext,
+        options: &IIRFilterOptions,
+    ) -> Fallible<IIRFilterNode> {
+        let feedforward: Vec<f64> = options.feedforward.iter().map(|x| **x).collect();
+        let feedback: Vec<f64> = options.feedback.iter().map(|x| **x).collect();
+        if feedforward.is_empty() || feedforward.len() > MAX_COEFFICIENTS {
+            return Err(Error::NotSupported);
+        }
+        if feedforward.iter().all(|x| *x == 0.) {
+            return Err(Error::InvalidState);
+        }
+        if feedback.is_empty() || feedback.len() > MAX_COEFFICIENTS {
+            return Err(Error::NotSupported);
+        }
+        if feedback[0] == 0. {
+            return Err(Error::InvalidState);
+        }
+
+        let node_options =
+            options
+                .parent
+                .unwrap_or(2, ChannelCountMode::Max, ChannelInterpretation::Speakers);
+        let node = AudioNode::new_inherited(
+            AudioNodeInit::IIRFilterNode(IIRFilterNodeOptions {
+                feedforward: Arc::new(feedforward.clone()),
+                feedback: Arc::new(feedback.clone()),
+            }),
+            context,
+            node_options,
+            1, // inputs
+            1, // outputs
+        )?;
+        Ok(IIRFilterNode {
+            node,
+            feedforward,
+            feedback,
+            sample_rate: *context.SampleRate(),
+        })
+    }
+
+    pub fn new(
+        window: &Window,
+        context: &BaseAudioContext,
+        options: &IIRFilterOptions,
+    ) -> Fallible<DomRoot<IIRFilterNode>> {
+        Self::new_with_proto(window, None, context, options)
+    }
+
+    #[allow(crown::unrooted_must_root)]
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        context: &BaseAudioContext,
+        options: &IIRFilterOptions,
+    ) -> Fallible<DomRoot<IIRFilterNode>> {
+        let node = IIRFilterNode::new_inherited(window, context, options)?;
+        Ok(reflect_dom_object_with_proto(Box::new(node), window, proto))
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        context: &BaseAudioContext,
+        options: &IIRFilterOptions,
+    ) -> Fallible<DomRoot<IIRFilterNode>> {
+        IIRFilterNode::new_with_proto(window, proto, context, options)
+    }
+
+    /// Evaluate the filter's transfer function on the unit circle at `frequency`,
+    /// returning its magnitude and phase.
+    fn frequency_response(&self, frequency: f64) -> (f64, f64) {
+        let omega = 2. * PI * frequency / self.sample_rate as f64;
+        // Sum c[k] * e^(-i * omega * k) as (real, imaginary).
+        let evaluate = |coefficients: &[f64]| {
+            coefficients
+                .iter()
+                .enumerate()
+                .fold((0., 0.), |(re, im), (k, c)| {
+                    let angle = -omega * k as f64;
+                    (re + c * angle.cos(), im + c * angle.sin())
+                })
+        };
+        let (num_re, num_im) = evaluate(&self.feedforward);
+        let (den_re, den_im) = evaluate(&self.feedback);
+        let den = den_re * den_re + den_im * den_im;
+        let re = (num_re * den_re + num_im * den_im) / den;
+        let im = (num_im * den_re - num_re * den_im) / den;
+        (re.hypot(im), im.atan2(re))
+    }
+}
+
+impl IIRFilterNodeMethods for IIRFilterNode {
+    /// <https://webaudio.github.io/web-audio-api/#dom-iirfilternode-getfrequencyresponse>
+    #[allow(unsafe_code)]
+    fn GetFrequencyResponse(
+        &self,
+        frequency_hz: CustomAutoRooterGuard<Float32Array>,
+        mut mag_response: CustomAutoRooterGuard<Float32Array>,
+        mut phase_response: CustomAutoRooterGuard<Float32Array>,
+    ) -> Fallible<()> {
+        let frequencies = frequency_hz.to_vec();
+        if mag_response.len() != frequencies.len() || phase_response.len() != frequencies.len() {
+            return Err(Error::InvalidAccess);
+        }
+        let nyquist = self.sample_rate / 2.;
+        let mag_response = unsafe { mag_response.as_mut_slice() };
+        let phase_response = unsafe { phase_response.as_mut_slice() };
+        for (i, frequency) in frequencies.into_iter().enumerate() {
+            if frequency < 0. || frequency > nyquist || frequency.is_nan() {
+                mag_response[i] = f32::NAN;
+                phase_response[i] = f32::NAN;
+                continue;
+            }
+            let (magnitude, phase) = self.frequency_response(frequency as f64);
+            mag_response[i] = magnitude as f32;
+            phase_response[i] = phase as f32;
+        }
+        Ok(())
+    }
+}
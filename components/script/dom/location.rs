@@ -116,7 +116,7 @@ impl Location {
 
         // Initiate navigation
         // TODO: rethrow exceptions, set exceptions enabled flag.
-        let load_data = LoadData::new(
+        let mut load_data = LoadData::new(
             LoadOrigin::Script(load_origin),
             url,
             creator_pipeline_id,
@@ -124,6 +124,7 @@ impl Location {
             referrer_policy,
             None, // Top navigation doesn't inherit secure context
         );
+        load_data.initiator_pipeline_id = creator_pipeline_id;
         self.window
             .load_url(replacement_flag, reload_triggered, load_data);
     }
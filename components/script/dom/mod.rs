@@ -211,7 +211,9 @@ pub mod types {
 
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
+pub mod accelerometer;
 pub mod activation;
+pub mod ambientlightsensor;
 pub mod analysernode;
 pub mod animationevent;
 pub mod attr;
@@ -277,6 +279,10 @@ pub mod csssupportsrule;
 pub mod customelementregistry;
 pub mod customevent;
 pub mod dedicatedworkerglobalscope;
+pub mod devicemotionevent;
+pub mod devicemotioneventacceleration;
+pub mod devicemotioneventrotationrate;
+pub mod deviceorientationevent;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
 pub mod document;
@@ -339,6 +345,7 @@ pub mod gpucomputepipeline;
 pub mod gpuconvert;
 pub mod gpudevice;
 pub mod gpudevicelostinfo;
+pub mod gpuexternaltexture;
 pub mod gpumapmode;
 pub mod gpuoutofmemoryerror;
 pub mod gpupipelinelayout;
@@ -358,6 +365,7 @@ pub mod gputextureusage;
 pub mod gputextureview;
 pub mod gpuuncapturederrorevent;
 pub mod gpuvalidationerror;
+pub mod gyroscope;
 pub mod hashchangeevent;
 pub mod headers;
 pub mod history;
@@ -434,6 +442,7 @@ pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
 pub mod identityhub;
+pub mod iirfilternode;
 pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
@@ -491,6 +500,8 @@ pub mod performancepainttiming;
 pub mod performanceresourcetiming;
 pub mod permissions;
 pub mod permissionstatus;
+pub mod pictureinpictureevent;
+pub mod pictureinpicturewindow;
 pub mod plugin;
 pub mod pluginarray;
 pub mod popstateevent;
@@ -517,7 +528,9 @@ pub(crate) mod rtcrtptransceiver;
 pub mod rtcsessiondescription;
 pub mod rtctrackevent;
 pub mod screen;
+pub mod securitypolicyviolationevent;
 pub mod selection;
+pub mod sensor;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
@@ -574,6 +587,7 @@ pub mod videotracklist;
 pub mod virtualmethods;
 pub mod vttcue;
 pub mod vttregion;
+pub mod waveshapernode;
 pub mod webgl2renderingcontext;
 pub mod webgl_extensions;
 pub mod webgl_validations;
@@ -610,6 +624,8 @@ pub mod xmlhttprequest;
 pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
 pub mod xmlserializer;
+pub mod xranchor;
+pub mod xranchorset;
 pub mod xrcompositionlayer;
 pub mod xrcubelayer;
 pub mod xrcylinderlayer;
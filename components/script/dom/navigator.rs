@@ -155,13 +155,26 @@ impl NavigatorMethods for Navigator {
 
     // https://html.spec.whatwg.org/multipage/#navigatorlanguage
     fn Language(&self) -> DOMString {
-        navigatorinfo::Language()
+        match self.global().as_window().languages().first() {
+            Some(language) => DOMString::from(language.as_str()),
+            None => navigatorinfo::Language(),
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-languages
     #[allow(unsafe_code)]
     fn Languages(&self, cx: JSContext) -> JSVal {
-        to_frozen_array(&[self.Language()], cx)
+        let languages: Vec<DOMString> = self
+            .global()
+            .as_window()
+            .languages()
+            .iter()
+            .map(|language| DOMString::from(language.as_str()))
+            .collect();
+        if languages.is_empty() {
+            return to_frozen_array(&[self.Language()], cx);
+        }
+        to_frozen_array(&languages, cx)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-plugins
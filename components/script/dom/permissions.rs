@@ -3,13 +3,18 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
 
 use dom_struct::dom_struct;
-use embedder_traits::{self, EmbedderMsg, PermissionPrompt, PermissionRequest};
-use ipc_channel::ipc;
+use embedder_traits::{
+    self, EmbedderMsg, PermissionDecision, PermissionPrompt, PermissionRequest, PermissionSetting,
+};
+use ipc_channel::ipc::{self, IpcReceiver, TryRecvError};
 use js::conversions::ConversionResult;
 use js::jsapi::JSObject;
 use js::jsval::{ObjectValue, UndefinedValue};
+use script_traits::ScriptMsg;
 use servo_config::pref;
 
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
@@ -17,6 +22,7 @@ use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
 };
 use crate::dom::bindings::codegen::Bindings::PermissionsBinding::PermissionsMethods;
 use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bluetooth::Bluetooth;
@@ -26,6 +32,7 @@ use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
 use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
 
 pub trait PermissionAlgorithm {
     type Descriptor;
@@ -145,13 +152,8 @@ impl Permissions {
             _ => {
                 match &op {
                     &Operation::Request => {
-                        // (Request) Step 6.
+                        // (Request) Step 6 - 8, once the user answered if they are asked.
                         Permissions::permission_request(cx, &p, &root_desc, &status);
-
-                        // (Request) Step 7. The default algorithm always resolve
-
-                        // (Request) Step 8.
-                        p.resolve_native(&status);
                     },
                     &Operation::Query => {
                         // (Query) Step 6.
@@ -252,19 +254,56 @@ impl PermissionAlgorithm for Permissions {
 
                 // https://w3c.github.io/permissions/#request-permission-to-use (Step 3 - 4)
                 let globalscope = GlobalScope::current().expect("No current global object");
-                let state = prompt_user_from_embedder(prompt, &globalscope);
-                globalscope
-                    .permission_state_invocation_results()
-                    .borrow_mut()
-                    .insert(perm_name.to_string(), state);
+                let trusted_promise = TrustedPromise::new(promise.clone());
+                let trusted_status = Trusted::new(status);
+                let task_source = globalscope.dom_manipulation_task_source();
+                let canceller = globalscope.task_canceller(TaskSourceName::DOMManipulation);
+                let receiver = send_prompt_to_embedder(prompt, &globalscope);
+                let timeout = prompt_timeout();
+                thread::Builder::new()
+                    .name("PermissionPrompt".to_owned())
+                    .spawn(move || {
+                        let answer =
+                            permission_state_from_answer(receiver.try_recv_timeout(timeout));
+                        let _ = task_source.queue_with_canceller(
+                            task!(permission_prompt_answered: move || {
+                                let status = trusted_status.root();
+                                let globalscope = status.global();
+                                match answer {
+                                    Some(state) => {
+                                        remember_permission_decision(
+                                            perm_name,
+                                            state,
+                                            &globalscope,
+                                        );
+                                        globalscope
+                                            .permission_state_invocation_results()
+                                            .borrow_mut()
+                                            .insert(perm_name.to_string(), state);
+
+                                        // Step 4.
+                                        status.set_state(get_descriptor_permission_state(
+                                            perm_name,
+                                            Some(&*globalscope),
+                                        ));
+                                    },
+                                    // Without an answer, only this request is denied, and
+                                    // the next one prompts again.
+                                    None => status.set_state(PermissionState::Denied),
+                                }
+
+                                // (Request) Step 8 of the calling algorithm.
+                                trusted_promise.root().resolve_native(&status);
+                            }),
+                            &canceller,
+                        );
+                    })
+                    .expect("Thread spawning failed");
             },
 
             // Step 2.
-            _ => return,
+            _ => promise.resolve_native(status),
         }
-
-        // Step 4.
-        Permissions::permission_query(cx, promise, descriptor, status);
     }
 
     fn permission_revoke(_descriptor: &PermissionDescriptor, _status: &PermissionStatus) {}
@@ -281,6 +320,13 @@ pub fn get_descriptor_permission_state(
         None => GlobalScope::current().expect("No current global object"),
     };
 
+    // A decision the user made earlier for this origin outlives the document.
+    match stored_permission_setting(permission_name, &globalscope) {
+        Some(PermissionSetting::Granted) => return PermissionState::Granted,
+        Some(PermissionSetting::Denied) => return PermissionState::Denied,
+        Some(PermissionSetting::Prompt) | None => {},
+    }
+
     // Step 2.
     // TODO: The `is the environment settings object a non-secure context` check is missing.
     // The current solution is a workaround with a message box to warn about this,
@@ -351,23 +397,98 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
     }
 }
 
-fn prompt_user_from_embedder(prompt: PermissionPrompt, gs: &GlobalScope) -> PermissionState {
+/// <https://w3c.github.io/permissions/#dfn-get-a-permission-store-entry>
+fn stored_permission_setting(
+    permission_name: PermissionName,
+    gs: &GlobalScope,
+) -> Option<PermissionSetting> {
     let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
-    gs.send_to_embedder(EmbedderMsg::PromptPermission(prompt, sender));
+    let msg = ScriptMsg::GetPermission(
+        gs.origin().immutable().clone(),
+        embedder_traits::PermissionName::from(permission_name),
+        sender,
+    );
+    if gs.script_to_constellation_chan().send(msg).is_err() {
+        warn!("Failed to query the permission store.");
+        return None;
+    }
+    receiver.recv().ok().flatten()
+}
 
-    match receiver.recv() {
-        Ok(PermissionRequest::Granted) => PermissionState::Granted,
-        Ok(PermissionRequest::Denied) => PermissionState::Denied,
+/// <https://w3c.github.io/permissions/#dfn-set-a-permission-store-entry>
+///
+/// The answer to a prompt is kept, unless the store says the user wants to be
+/// asked every time.
+fn remember_permission_decision(
+    permission_name: PermissionName,
+    state: PermissionState,
+    gs: &GlobalScope,
+) {
+    let setting = match state {
+        PermissionState::Granted => PermissionSetting::Granted,
+        PermissionState::Denied => PermissionSetting::Denied,
+        PermissionState::Prompt => return,
+    };
+    if stored_permission_setting(permission_name, gs) == Some(PermissionSetting::Prompt) {
+        return;
+    }
+    let _ = gs
+        .script_to_constellation_chan()
+        .send(ScriptMsg::SetPermission(PermissionDecision {
+            origin: gs.origin().immutable().clone(),
+            name: embedder_traits::PermissionName::from(permission_name),
+            setting,
+            expires: None,
+        }));
+}
+
+/// How long the embedder has to answer a prompt before the request is denied.
+fn prompt_timeout() -> Duration {
+    Duration::from_millis(pref!(dom.permissions.prompt_timeout).max(0) as u64)
+}
+
+fn send_prompt_to_embedder(
+    prompt: PermissionPrompt,
+    gs: &GlobalScope,
+) -> IpcReceiver<PermissionRequest> {
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    let origin = gs.origin().immutable().clone();
+    gs.send_to_embedder(EmbedderMsg::PromptPermission(prompt, origin, sender));
+    receiver
+}
+
+/// The state that the answer of the embedder to a prompt gives the permission, or `None`
+/// if the embedder didn't answer, in which case the user didn't decide anything that
+/// could be remembered.
+fn permission_state_from_answer(
+    answer: Result<PermissionRequest, TryRecvError>,
+) -> Option<PermissionState> {
+    match answer {
+        Ok(PermissionRequest::Granted) => Some(PermissionState::Granted),
+        Ok(PermissionRequest::Denied) => Some(PermissionState::Denied),
+        Ok(PermissionRequest::Dismissed) => Some(PermissionState::Prompt),
+        Err(TryRecvError::Empty) => {
+            warn!("The embedder didn't answer a permission prompt in time.");
+            None
+        },
         Err(e) => {
             warn!(
                 "Failed to receive permission state from embedder ({:?}).",
                 e
             );
-            PermissionState::Denied
+            None
         },
     }
 }
 
+/// Prompt the user and wait for their answer, for the prompts that have to be answered
+/// before script can go on.
+fn prompt_user_from_embedder(prompt: PermissionPrompt, gs: &GlobalScope) -> PermissionState {
+    let receiver = send_prompt_to_embedder(prompt, gs);
+    permission_state_from_answer(receiver.try_recv_timeout(prompt_timeout()))
+        .unwrap_or(PermissionState::Denied)
+}
+
 impl From<PermissionName> for embedder_traits::PermissionName {
     fn from(permission_name: PermissionName) -> Self {
         match permission_name {
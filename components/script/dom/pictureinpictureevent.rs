@@ -0,0 +1,105 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PictureInPictureEventBinding::{
+    self, PictureInPictureEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::pictureinpicturewindow::PictureInPictureWindow;
+use crate::dom::window::Window;
+
+#[dom_struct]
+pub struct PictureInPictureEvent {
+    event: Event,
+    picture_in_picture_window: Dom<PictureInPictureWindow>,
+}
+
+impl PictureInPictureEvent {
+    #[allow(crown::unrooted_must_root)]
+    fn new_inherited(picture_in_picture_window: &PictureInPictureWindow) -> PictureInPictureEvent {
+        PictureInPictureEvent {
+            event: Event::new_inherited(),
+            picture_in_picture_window: Dom::from_ref(picture_in_picture_window),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        picture_in_picture_window: &PictureInPictureWindow,
+    ) -> DomRoot<PictureInPictureEvent> {
+        Self::new_with_proto(
+            global,
+            None,
+            type_,
+            bubbles,
+            cancelable,
+            picture_in_picture_window,
+        )
+    }
+
+    fn new_with_proto(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        picture_in_picture_window: &PictureInPictureWindow,
+    ) -> DomRoot<PictureInPictureEvent> {
+        let pipevent = reflect_dom_object_with_proto(
+            Box::new(PictureInPictureEvent::new_inherited(
+                picture_in_picture_window,
+            )),
+            global,
+            proto,
+        );
+        {
+            let event = pipevent.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        pipevent
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &PictureInPictureEventBinding::PictureInPictureEventInit,
+    ) -> Fallible<DomRoot<PictureInPictureEvent>> {
+        Ok(PictureInPictureEvent::new_with_proto(
+            &window.global(),
+            proto,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.pictureInPictureWindow,
+        ))
+    }
+}
+
+impl PictureInPictureEventMethods for PictureInPictureEvent {
+    // https://w3c.github.io/picture-in-picture/#dom-pictureinpictureevent-pictureinpicturewindow
+    fn PictureInPictureWindow(&self) -> DomRoot<PictureInPictureWindow> {
+        DomRoot::from_ref(&*self.picture_in_picture_window)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -142,6 +142,35 @@ impl Range {
                 .any(|n| &*n == node)
     }
 
+    /// The text nodes with some of their characters in this range, in tree order.
+    pub(crate) fn text_nodes(&self) -> Vec<DomRoot<Text>> {
+        let start_node = self.StartContainer();
+        let end_node = self.EndContainer();
+        let mut nodes = vec![];
+
+        if let Some(text_node) = start_node.downcast::<Text>() {
+            nodes.push(DomRoot::from_ref(text_node));
+            if start_node == end_node {
+                return nodes;
+            }
+        }
+
+        let ancestor = self.CommonAncestorContainer();
+        nodes.extend(
+            start_node
+                .following_nodes(&ancestor)
+                .filter_map(DomRoot::downcast::<Text>)
+                .filter(|text_node| self.contains(text_node.upcast())),
+        );
+
+        if let Some(text_node) = end_node.downcast::<Text>() {
+            if self.EndOffset() > 0 {
+                nodes.push(DomRoot::from_ref(text_node));
+            }
+        }
+        nodes
+    }
+
     // https://dom.spec.whatwg.org/#concept-range-clone
     fn contained_children(
         &self,
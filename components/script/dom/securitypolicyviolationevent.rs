@@ -0,0 +1,229 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use content_security_policy as csp;
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::{
+    SecurityPolicyViolationEventDisposition, SecurityPolicyViolationEventInit,
+    SecurityPolicyViolationEventMethods,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+
+/// The details of a single violation of a content security policy.
+/// <https://w3c.github.io/webappsec-csp/#violation>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct CSPViolationReport {
+    pub document_url: String,
+    pub referrer: String,
+    pub blocked_url: String,
+    pub effective_directive: String,
+    pub original_policy: String,
+    pub source_file: String,
+    pub sample: String,
+    pub disposition: SecurityPolicyViolationEventDisposition,
+    pub status_code: u16,
+    pub line_number: u32,
+    pub column_number: u32,
+}
+
+impl CSPViolationReport {
+    /// The report of `violation`, found in the document at `document_url` that was
+    /// navigated to from `referrer`.
+    pub fn new(violation: csp::Violation, document_url: String, referrer: String) -> Self {
+        // https://w3c.github.io/webappsec-csp/#obtain-violation-blocked-uri
+        let (blocked_url, sample) = match violation.resource {
+            csp::ViolationResource::Url(url) => (url.to_string(), None),
+            csp::ViolationResource::Inline { sample } => ("inline".to_owned(), sample),
+            _ => ("eval".to_owned(), None),
+        };
+        let original_policy = violation
+            .policy
+            .directive_set
+            .iter()
+            .map(|directive| {
+                if directive.value.is_empty() {
+                    directive.name.clone()
+                } else {
+                    format!("{} {}", directive.name, directive.value.join(" "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let disposition = match violation.policy.disposition {
+            csp::PolicyDisposition::Enforce => SecurityPolicyViolationEventDisposition::Enforce,
+            csp::PolicyDisposition::Report => SecurityPolicyViolationEventDisposition::Report,
+        };
+        CSPViolationReport {
+            document_url,
+            referrer,
+            blocked_url,
+            effective_directive: violation.directive.name,
+            original_policy,
+            // TODO: Fill in the location of the script that caused the violation.
+            source_file: String::new(),
+            sample: sample.unwrap_or_default(),
+            disposition,
+            status_code: 0,
+            line_number: 0,
+            column_number: 0,
+        }
+    }
+}
+
+impl From<&SecurityPolicyViolationEventInit> for CSPViolationReport {
+    fn from(init: &SecurityPolicyViolationEventInit) -> CSPViolationReport {
+        CSPViolationReport {
+            document_url: init.documentURI.0.clone(),
+            referrer: init.referrer.0.clone(),
+            blocked_url: init.blockedURI.0.clone(),
+            effective_directive: init.effectiveDirective.to_string(),
+            original_policy: init.originalPolicy.to_string(),
+            source_file: init.sourceFile.0.clone(),
+            sample: init.sample.to_string(),
+            disposition: init.disposition,
+            status_code: init.statusCode,
+            line_number: init.lineNumber,
+            column_number: init.columnNumber,
+        }
+    }
+}
+
+// https://w3c.github.io/webappsec-csp/#securitypolicyviolationevent
+#[dom_struct]
+pub struct SecurityPolicyViolationEvent {
+    event: Event,
+    report: CSPViolationReport,
+}
+
+impl SecurityPolicyViolationEvent {
+    fn new_inherited(report: CSPViolationReport) -> SecurityPolicyViolationEvent {
+        SecurityPolicyViolationEvent {
+            event: Event::new_inherited(),
+            report,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        report: CSPViolationReport,
+    ) -> DomRoot<SecurityPolicyViolationEvent> {
+        Self::new_with_proto(global, None, type_, bubbles, cancelable, report)
+    }
+
+    fn new_with_proto(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        report: CSPViolationReport,
+    ) -> DomRoot<SecurityPolicyViolationEvent> {
+        let ev = reflect_dom_object_with_proto(
+            Box::new(SecurityPolicyViolationEvent::new_inherited(report)),
+            global,
+            proto,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &SecurityPolicyViolationEventInit,
+    ) -> DomRoot<SecurityPolicyViolationEvent> {
+        SecurityPolicyViolationEvent::new_with_proto(
+            global,
+            proto,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            CSPViolationReport::from(init),
+        )
+    }
+}
+
+impl SecurityPolicyViolationEventMethods for SecurityPolicyViolationEvent {
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-documenturi
+    fn DocumentURI(&self) -> USVString {
+        USVString(self.report.document_url.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-referrer
+    fn Referrer(&self) -> USVString {
+        USVString(self.report.referrer.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-blockeduri
+    fn BlockedURI(&self) -> USVString {
+        USVString(self.report.blocked_url.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-effectivedirective
+    fn EffectiveDirective(&self) -> DOMString {
+        DOMString::from(self.report.effective_directive.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-violateddirective
+    fn ViolatedDirective(&self) -> DOMString {
+        DOMString::from(self.report.effective_directive.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-originalpolicy
+    fn OriginalPolicy(&self) -> DOMString {
+        DOMString::from(self.report.original_policy.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-sourcefile
+    fn SourceFile(&self) -> USVString {
+        USVString(self.report.source_file.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-sample
+    fn Sample(&self) -> DOMString {
+        DOMString::from(self.report.sample.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-disposition
+    fn Disposition(&self) -> SecurityPolicyViolationEventDisposition {
+        self.report.disposition
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-statuscode
+    fn StatusCode(&self) -> u16 {
+        self.report.status_code
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-linenumber
+    fn LineNumber(&self) -> u32 {
+        self.report.line_number
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-securitypolicyviolationevent-columnnumber
+    fn ColumnNumber(&self) -> u32 {
+        self.report.column_number
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
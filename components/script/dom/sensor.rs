@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use embedder_traits::SensorType;
+use script_traits::SensorReading;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::SensorBinding::{SensorMethods, SensorOptions};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::eventtarget::EventTarget;
+
+/// <https://w3c.github.io/sensors/#sensor-lifecycle>
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
+enum SensorState {
+    Idle,
+    Activating,
+    Activated,
+}
+
+#[dom_struct]
+pub struct Sensor {
+    eventtarget: EventTarget,
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    #[no_trace]
+    sensor_type: SensorType,
+    /// <https://w3c.github.io/sensors/#requested-sampling-frequency>
+    frequency: Option<f64>,
+    state: Cell<SensorState>,
+    /// The latest reading, which is only kept while the sensor is started.
+    #[ignore_malloc_size_of = "defined in script_traits"]
+    #[no_trace]
+    reading: Cell<Option<SensorReading>>,
+    /// <https://w3c.github.io/sensors/#dom-sensor-timestamp>
+    timestamp: Cell<Option<f64>>,
+}
+
+impl Sensor {
+    pub fn new_inherited(sensor_type: SensorType, options: &SensorOptions) -> Sensor {
+        Sensor {
+            eventtarget: EventTarget::new_inherited(),
+            sensor_type,
+            frequency: options
+                .frequency
+                .map(|frequency| *frequency)
+                .filter(|frequency| *frequency > 0.),
+            state: Cell::new(SensorState::Idle),
+            reading: Cell::new(None),
+            timestamp: Cell::new(None),
+        }
+    }
+
+    pub(crate) fn sensor_type(&self) -> SensorType {
+        self.sensor_type
+    }
+
+    /// The latest reading of this sensor, if it has one.
+    pub(crate) fn reading(&self) -> Option<SensorReading> {
+        self.reading.get()
+    }
+
+    /// <https://w3c.github.io/sensors/#update-latest-reading>
+    pub(crate) fn handle_reading(&self, reading: SensorReading, timestamp: f64) {
+        if self.state.get() == SensorState::Idle {
+            return;
+        }
+        if let (Some(frequency), Some(last_timestamp)) = (self.frequency, self.timestamp.get()) {
+            if timestamp - last_timestamp < 1000. / frequency {
+                return;
+            }
+        }
+        self.reading.set(Some(reading));
+        self.timestamp.set(Some(timestamp));
+
+        // The platform sensor is only known to be running once it reports.
+        let target = self.upcast::<EventTarget>();
+        if self.state.get() == SensorState::Activating {
+            self.state.set(SensorState::Activated);
+            target.fire_event(Atom::from("activate"));
+        }
+        target.fire_event(Atom::from("reading"));
+    }
+}
+
+impl SensorMethods for Sensor {
+    /// <https://w3c.github.io/sensors/#dom-sensor-activated>
+    fn Activated(&self) -> bool {
+        self.state.get() == SensorState::Activated
+    }
+
+    /// <https://w3c.github.io/sensors/#dom-sensor-hasreading>
+    fn HasReading(&self) -> bool {
+        self.timestamp.get().is_some()
+    }
+
+    /// <https://w3c.github.io/sensors/#dom-sensor-timestamp>
+    fn GetTimestamp(&self) -> Option<Finite<f64>> {
+        self.timestamp.get().map(Finite::wrap)
+    }
+
+    /// <https://w3c.github.io/sensors/#dom-sensor-start>
+    fn Start(&self) {
+        if self.state.get() != SensorState::Idle {
+            return;
+        }
+        self.state.set(SensorState::Activating);
+        self.global().as_window().add_sensor(self);
+    }
+
+    /// <https://w3c.github.io/sensors/#dom-sensor-stop>
+    fn Stop(&self) {
+        if self.state.get() == SensorState::Idle {
+            return;
+        }
+        self.state.set(SensorState::Idle);
+        self.reading.set(None);
+        self.timestamp.set(None);
+        self.global().as_window().remove_sensor(self);
+    }
+
+    // https://w3c.github.io/sensors/#dom-sensor-onreading
+    event_handler!(reading, GetOnreading, SetOnreading);
+
+    // https://w3c.github.io/sensors/#dom-sensor-onactivate
+    event_handler!(activate, GetOnactivate, SetOnactivate);
+}
@@ -22,13 +22,13 @@ use hyper_serde::Serde;
 use mime::{self, Mime};
 use msg::constellation_msg::PipelineId;
 use net_traits::{
-    FetchMetadata, FetchResponseListener, Metadata, NetworkError, ResourceFetchTiming,
-    ResourceTimingType,
+    ClearDataTypes, FetchMetadata, FetchResponseListener, Metadata, NetworkError,
+    ResourceFetchTiming, ResourceTimingType,
 };
 use profile_traits::time::{
     profile, ProfilerCategory, TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType,
 };
-use script_traits::DocumentActivity;
+use script_traits::{DocumentActivity, ScriptMsg};
 use servo_config::pref;
 use servo_url::ServoUrl;
 use style::context::QuirksMode as ServoQuirksMode;
@@ -833,6 +833,20 @@ impl FetchResponseListener for ParserContext {
             Some(csp_list)
         });
 
+        // https://w3c.github.io/webappsec-clear-site-data/#header
+        let clear_site_data = metadata
+            .as_ref()
+            .and_then(|m| m.headers.as_ref())
+            .map(|h| {
+                let values: Vec<&str> = h
+                    .get_all("clear-site-data")
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .collect();
+                ClearDataTypes::from_header_value(&values.join(","))
+            })
+            .unwrap_or_default();
+
         let parser = match ScriptThread::page_headers_available(&self.id, metadata) {
             Some(parser) => parser,
             None => return,
@@ -844,6 +858,15 @@ impl FetchResponseListener for ParserContext {
         let _realm = enter_realm(&*parser.document);
 
         parser.document.set_csp_list(csp_list);
+
+        // https://w3c.github.io/webappsec-clear-site-data/#clear-response
+        if !clear_site_data.is_empty() && parser.document.url().is_origin_trustworthy() {
+            parser
+                .document
+                .window()
+                .send_to_constellation(ScriptMsg::ClearSiteData(clear_site_data));
+        }
+
         self.parser = Some(Trusted::new(&*parser));
         self.submit_resource_timing();
 
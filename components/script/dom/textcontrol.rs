@@ -7,6 +7,9 @@
 //!
 //! <https://html.spec.whatwg.org/multipage/#textFieldSelection>
 
+use std::ops::Range;
+
+use embedder_traits::{ImeEvent, SurroundingText};
 use script_traits::ScriptToConstellationChan;
 
 use crate::dom::bindings::cell::DomRefCell;
@@ -17,7 +20,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::event::{EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::node::{window_from_node, Node, NodeDamage};
-use crate::textinput::{SelectionDirection, SelectionState, TextInput, UTF8Bytes};
+use crate::textinput::{SelectionDirection, SelectionState, TextInput, UTF16CodeUnits, UTF8Bytes};
 
 pub trait TextControlElement: DerivedFrom<EventTarget> + DerivedFrom<Node> {
     fn selection_api_applies(&self) -> bool;
@@ -263,6 +266,52 @@ impl<'a, E: TextControlElement> TextControlSelection<'a, E> {
         Ok(())
     }
 
+    /// Apply an edit of the input method of the embedder other than composing text, which
+    /// the default actions of composition events take care of.
+    pub fn handle_ime_event(&self, event: &ImeEvent) {
+        match *event {
+            ImeEvent::Preedit(_, Some(ref selection)) => {
+                self.textinput.borrow_mut().select_in_composition(
+                    UTF16CodeUnits(selection.start)..UTF16CodeUnits(selection.end),
+                );
+                self.element
+                    .upcast::<Node>()
+                    .dirty(NodeDamage::OtherNodeDamage);
+            },
+            ImeEvent::SetSelection(ref selection) => {
+                let (UTF8Bytes(start), UTF8Bytes(end)) = {
+                    let textinput = self.textinput.borrow();
+                    (
+                        textinput.utf8_offset(UTF16CodeUnits(selection.start)),
+                        textinput.utf8_offset(UTF16CodeUnits(selection.end)),
+                    )
+                };
+                self.set_range(Some(start as u32), Some(end as u32), None, None);
+            },
+            ImeEvent::DeleteSurroundingText(before, after) => {
+                self.element.set_dirty_value_flag(true);
+                self.textinput
+                    .borrow_mut()
+                    .delete_surrounding_text(UTF16CodeUnits(before), UTF16CodeUnits(after));
+                self.element
+                    .upcast::<Node>()
+                    .dirty(NodeDamage::OtherNodeDamage);
+            },
+            ImeEvent::Preedit(_, None) | ImeEvent::Commit(_) => {},
+        }
+    }
+
+    /// The value and the selection, for the input method of the embedder.
+    pub fn surrounding_text(&self) -> SurroundingText {
+        let textinput = self.textinput.borrow();
+        let to_usize = |range: Range<UTF16CodeUnits>| range.start.0..range.end.0;
+        SurroundingText {
+            text: textinput.get_content().into(),
+            selection: to_usize(textinput.selection_utf16_range()),
+            composition: textinput.composition_utf16_range().map(to_usize),
+        }
+    }
+
     fn start(&self) -> u32 {
         let UTF8Bytes(offset) = self.textinput.borrow().selection_start_offset();
         offset as u32
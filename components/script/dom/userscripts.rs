@@ -2,21 +2,61 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+//! The scripts of the `--userscripts` option, and the scripts and stylesheets that the
+//! embedder injects into documents.
+//!
+//! Injected scripts run either in the global of the document or in its isolated world: a
+//! global of its own in the compartment of the window, with its own builtins, whose
+//! `window`, `document` and `console` are those of the page. The globals that the scripts
+//! of either world define can't be seen or replaced by the other, but they share the DOM.
+//! When the DOM needs the global that a script runs in, that of an isolated world is the
+//! window it belongs to.
+
 use std::fs::{read_dir, File};
 use std::io::Read;
 use std::path::PathBuf;
+use std::ptr;
 use std::rc::Rc;
 
-use js::jsval::UndefinedValue;
+use embedder_traits::{UserScriptInjectionTime, UserScriptSource, UserScriptWorld};
+use js::glue::JS_GetReservedSlot;
+use js::jsapi::{
+    Compile1, HandleObject as RawHandleObject, JSAutoRealm, JSClass, JSClassOps, JSContext,
+    JSObject, JS_EnumerateStandardClasses, JS_GlobalObjectTraceHook, JS_NewGlobalObject,
+    JS_ResolveStandardClass, JS_SetReservedSlot, MutableHandleIdVector as RawMutableHandleIdVector,
+    OnNewGlobalHookOption, JSCLASS_RESERVED_SLOTS_SHIFT, JSPROP_ENUMERATE, JSPROP_PERMANENT,
+    JSPROP_READONLY,
+};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    JS_DefineProperty, JS_ExecuteScript, JS_FireOnNewGlobalObject, JS_GetProperty,
+};
+use js::rust::{
+    get_object_class, transform_str_to_source_text, CompileOptionsWrapper, RealmOptions,
+};
+use js::{JSCLASS_GLOBAL_SLOT_COUNT, JSCLASS_IS_GLOBAL, JSCLASS_RESERVED_SLOTS_MASK};
+use script_layout_interface::message::Msg;
+use servo_arc::Arc;
+use style::global_style_data::GLOBAL_STYLE_DATA;
+use style::media_queries::MediaList;
+use style::stylesheets::{AllowImportRules, Origin, Stylesheet, UrlExtraData};
 
+use crate::dom::bindings::error::report_pending_exception;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::interface::select_compartment;
+use crate::dom::bindings::principals::ServoJSPrincipals;
 use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::settings_stack::AutoEntryScript;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlheadelement::HTMLHeadElement;
 use crate::dom::htmlscriptelement::SourceCode;
 use crate::dom::node::document_from_node;
+use crate::realms::InRealm;
 use crate::script_module::ScriptFetchOptions;
+use crate::script_runtime::JSContext as SafeJSContext;
 
 pub fn load_script(head: &HTMLHeadElement) {
     let doc = document_from_node(head);
@@ -58,3 +98,192 @@ pub fn load_script(head: &HTMLHeadElement) {
         }
     }));
 }
+
+/// Inject the scripts and stylesheets of the embedder that match a document into it, in
+/// the order they were added in.
+pub fn inject_user_scripts(document: &Document, injection_time: UserScriptInjectionTime) {
+    for script in document.user_scripts(injection_time) {
+        match (script.source, script.world) {
+            (UserScriptSource::Stylesheet(css), _) => add_user_stylesheet(document, &css),
+            (UserScriptSource::Script(source), UserScriptWorld::Page) => {
+                let global = document.window().upcast::<GlobalScope>();
+                let cx = GlobalScope::get_cx();
+                rooted!(in(*cx) let mut rval = UndefinedValue());
+                global.evaluate_script_on_global_with_result(
+                    &SourceCode::Text(Rc::new(DOMString::from(source))),
+                    document.url().as_str(),
+                    rval.handle_mut(),
+                    1,
+                    ScriptFetchOptions::default_classic_script(global),
+                    global.api_base_url(),
+                );
+            },
+            (UserScriptSource::Script(source), UserScriptWorld::Isolated) => {
+                evaluate_in_isolated_world(document, &source)
+            },
+        }
+    }
+}
+
+/// Apply a stylesheet to a document at the user origin. It is only given to layout, so it
+/// isn't one of the `document.styleSheets` of the page.
+fn add_user_stylesheet(document: &Document, css: &str) {
+    // Layout reads the stylesheets of the user origin with the lock of the user agent ones.
+    let shared_lock = GLOBAL_STYLE_DATA.shared_lock.clone();
+    let media = Arc::new(shared_lock.wrap(MediaList::empty()));
+    let window = document.window();
+    let sheet = Arc::new(Stylesheet::from_str(
+        css,
+        UrlExtraData(document.url().get_arc()),
+        Origin::User,
+        media,
+        shared_lock,
+        None,
+        window.css_error_reporter(),
+        document.quirks_mode(),
+        0,
+        AllowImportRules::No,
+    ));
+    let _ = window.with_layout(move |layout| layout.process(Msg::AddStylesheet(sheet, None)));
+    document.invalidate_stylesheets();
+}
+
+static ISOLATED_WORLD_CLASS_OPS: JSClassOps = JSClassOps {
+    addProperty: None,
+    delProperty: None,
+    enumerate: None,
+    newEnumerate: Some(enumerate_isolated_world),
+    resolve: Some(JS_ResolveStandardClass),
+    mayResolve: None,
+    finalize: None,
+    call: None,
+    construct: None,
+    trace: Some(JS_GlobalObjectTraceHook),
+};
+
+struct IsolatedWorldClass(JSClass);
+
+#[allow(unsafe_code)]
+unsafe impl Sync for IsolatedWorldClass {}
+
+/// The class of the globals of isolated worlds, which only have the standard classes of
+/// JavaScript as lazy properties.
+static ISOLATED_WORLD_CLASS: IsolatedWorldClass = IsolatedWorldClass(JSClass {
+    name: b"IsolatedWorld\0" as *const u8 as *const libc::c_char,
+    flags: JSCLASS_IS_GLOBAL |
+        (((ISOLATED_WORLD_WINDOW_SLOT + 1) & JSCLASS_RESERVED_SLOTS_MASK) <<
+            JSCLASS_RESERVED_SLOTS_SHIFT),
+    cOps: &ISOLATED_WORLD_CLASS_OPS,
+    spec: ptr::null(),
+    ext: ptr::null(),
+    oOps: ptr::null(),
+});
+
+/// The reserved slot of the global of an isolated world that holds the global of its
+/// window.
+const ISOLATED_WORLD_WINDOW_SLOT: u32 = JSCLASS_GLOBAL_SLOT_COUNT;
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn enumerate_isolated_world(
+    cx: *mut JSContext,
+    obj: RawHandleObject,
+    _props: RawMutableHandleIdVector,
+    _enumerable_only: bool,
+) -> bool {
+    JS_EnumerateStandardClasses(cx, obj)
+}
+
+/// The global of the window that an isolated world belongs to, if `global` is the global of
+/// one.
+#[allow(unsafe_code)]
+pub unsafe fn window_of_isolated_world(global: *mut JSObject) -> Option<*mut JSObject> {
+    if get_object_class(global) != &ISOLATED_WORLD_CLASS.0 as *const JSClass {
+        return None;
+    }
+    let mut window = UndefinedValue();
+    JS_GetReservedSlot(global, ISOLATED_WORLD_WINDOW_SLOT, &mut window);
+    Some(window.to_object())
+}
+
+/// The global of the isolated world of a document, which is created the first time one of
+/// its scripts runs.
+#[allow(unsafe_code)]
+unsafe fn isolated_world(document: &Document, cx: SafeJSContext) -> *mut JSObject {
+    let world = document.isolated_world();
+    if !world.get().is_null() {
+        return world.get();
+    }
+
+    let window = document.window();
+    let mut options = RealmOptions::default();
+    options.creationOptions_.sharedMemoryAndAtomics_ = false;
+    // The compartment of the window, so that the DOM objects don't need wrappers.
+    select_compartment(cx, &mut options);
+    let principals = ServoJSPrincipals::new(document.origin());
+    rooted!(in(*cx) let global = JS_NewGlobalObject(
+        *cx,
+        &ISOLATED_WORLD_CLASS.0,
+        principals.as_raw(),
+        OnNewGlobalHookOption::DontFireOnNewGlobalHook,
+        &*options,
+    ));
+    assert!(!global.is_null());
+    let window_global = ObjectValue(window.reflector().get_jsobject().get());
+    JS_SetReservedSlot(global.get(), ISOLATED_WORLD_WINDOW_SLOT, &window_global);
+    world.set(global.get());
+
+    rooted!(in(*cx) let mut console = UndefinedValue());
+    {
+        let _ac = JSAutoRealm::new(*cx, window.reflector().get_jsobject().get());
+        JS_GetProperty(
+            *cx,
+            window.reflector().get_jsobject(),
+            b"console\0".as_ptr() as *const libc::c_char,
+            console.handle_mut(),
+        );
+    }
+
+    let _ac = JSAutoRealm::new(*cx, global.get());
+    JS_FireOnNewGlobalObject(*cx, global.handle());
+    let proxy = window.window_proxy();
+    rooted!(in(*cx) let window_proxy = ObjectValue(proxy.reflector().get_jsobject().get()));
+    rooted!(in(*cx) let document_object = ObjectValue(document.reflector().get_jsobject().get()));
+    let properties = [
+        ("window\0", window_proxy.handle()),
+        ("document\0", document_object.handle()),
+        ("console\0", console.handle()),
+    ];
+    for (name, value) in properties {
+        assert!(JS_DefineProperty(
+            *cx,
+            global.handle(),
+            name.as_ptr() as *const libc::c_char,
+            value,
+            (JSPROP_ENUMERATE | JSPROP_READONLY | JSPROP_PERMANENT) as u32,
+        ));
+    }
+    global.get()
+}
+
+/// Run a script in the isolated world of a document. Its exceptions are logged rather than
+/// reported to the page.
+#[allow(unsafe_code)]
+fn evaluate_in_isolated_world(document: &Document, source: &str) {
+    let window = document.window();
+    let cx = GlobalScope::get_cx();
+    let _aes = AutoEntryScript::new(window.upcast());
+    unsafe {
+        rooted!(in(*cx) let world = isolated_world(document, cx));
+        let ar = JSAutoRealm::new(*cx, world.get());
+        let options = CompileOptionsWrapper::new(*cx, document.url().as_str(), 1);
+        rooted!(in(*cx) let script = Compile1(
+            *cx,
+            options.ptr,
+            &mut transform_str_to_source_text(source),
+        ));
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        if script.is_null() || !JS_ExecuteScript(*cx, script.handle(), rval.handle_mut()) {
+            report_pending_exception(*cx, false, InRealm::Entered(&ar));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use js::rust::{CustomAutoRooterGuard, HandleObject};
+use js::typedarray::{Float32, Float32Array};
+use servo_media::audio::node::{AudioNodeInit, AudioNodeMessage};
+use servo_media::audio::wave_shaper_node::{
+    OverSampleType as ServoMediaOverSampleType, WaveShaperNodeMessage, WaveShaperNodeOptions,
+};
+
+use crate::dom::audionode::AudioNode;
+use crate::dom::baseaudiocontext::BaseAudioContext;
+use crate::dom::bindings::buffer_source::HeapBufferSource;
+use crate::dom::bindings::codegen::Bindings::AudioNodeBinding::{
+    ChannelCountMode, ChannelInterpretation,
+};
+use crate::dom::bindings::codegen::Bindings::WaveShaperNodeBinding::{
+    OverSampleType, WaveShaperNodeMethods, WaveShaperOptions,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+
+#[dom_struct]
+pub struct WaveShaperNode {
+    node: AudioNode,
+    /// The last non-null curve, kept as a copy of what content passed in.
+    #[ignore_malloc_size_of = "mozjs"]
+    curve: HeapBufferSource<Float32>,
+    has_curve: Cell<bool>,
+    /// <https://webaudio.github.io/web-audio-api/#dom-waveshapernode-curve-set>
+    curve_set: Cell<bool>,
+    oversample: Cell<OverSampleType>,
+}
+
+impl WaveShaperNode {
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_inherited(
+        _window: &Window,
+        context: &BaseAudioContext,
+        options: &WaveShaperOptions,
+    ) -> Fallible<WaveShaperNode> {
+        let node_options =
+            options
+                .parent
+                .unwrap_or(2, ChannelCountMode::Max, ChannelInterpretation::Speakers);
+        let curve = options
+            .curve
+            .as_ref()
+            .map(|curve| curve.iter().map(|value| **value).collect::<Vec<f32>>());
+        if curve.as_ref().map_or(false, |curve| curve.len() < 2) {
+            return Err(Error::InvalidState);
+        }
+        let node = AudioNode::new_inherited(
+            AudioNodeInit::WaveShaperNode(WaveShaperNodeOptions {
+                curve: curve.clone(),
+                oversample: options.oversample.into(),
+            }),
+            context,
+            node_options,
+            1, // inputs
+            1, // outputs
+        )?;
+        let node = WaveShaperNode {
+            node,
+            curve: HeapBufferSource::default(),
+            has_curve: Cell::new(false),
+            curve_set: Cell::new(false),
+            oversample: Cell::new(options.oversample),
+        };
+        if let Some(curve) = curve {
+            node.store_curve(&curve)?;
+        }
+        Ok(node)
+    }
+
+    pub fn new(
+        window: &Window,
+        context: &BaseAudioContext,
+        options: &WaveShaperOptions,
+    ) -> Fallible<DomRoot<WaveShaperNode>> {
+        Self::new_with_proto(window, None, context, options)
+    }
+
+    #[allow(crown::unrooted_must_root)]
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        context: &BaseAudioContext,
+        options: &WaveShaperOptions,
+    ) -> Fallible<DomRoot<WaveShaperNode>> {
+        let node = WaveShaperNode::new_inherited(window, context, options)?;
+        Ok(reflect_dom_object_with_proto(Box::new(node), window, proto))
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        context: &BaseAudioContext,
+        options: &WaveShaperOptions,
+    ) -> Fallible<DomRoot<WaveShaperNode>> {
+        WaveShaperNode::new_with_proto(window, proto, context, options)
+    }
+
+    fn store_curve(&self, curve: &[f32]) -> Fallible<()> {
+        self.curve
+            .set_data(GlobalScope::get_cx(), curve)
+            .map_err(|_| Error::JSFailed)?;
+        self.has_curve.set(true);
+        self.curve_set.set(true);
+        Ok(())
+    }
+}
+
+impl WaveShaperNodeMethods for WaveShaperNode {
+    /// <https://webaudio.github.io/web-audio-api/#dom-waveshapernode-curve>
+    fn GetCurve(&self, _cx: JSContext) -> Option<Float32Array> {
+        if !self.has_curve.get() {
+            return None;
+        }
+        self.curve.buffer_to_option()
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-waveshapernode-curve>
+    fn SetCurve(&self, new_curve: Option<CustomAutoRooterGuard<Float32Array>>) -> Fallible<()> {
+        let new_curve = match new_curve {
+            Some(new_curve) => new_curve.to_vec(),
+            None => {
+                self.has_curve.set(false);
+                self.node.message(AudioNodeMessage::WaveShaperNode(
+                    WaveShaperNodeMessage::SetCurve(None),
+                ));
+                return Ok(());
+            },
+        };
+        if self.curve_set.get() || new_curve.len() < 2 {
+            return Err(Error::InvalidState);
+        }
+        self.store_curve(&new_curve)?;
+        self.node.message(AudioNodeMessage::WaveShaperNode(
+            WaveShaperNodeMessage::SetCurve(Some(new_curve)),
+        ));
+        Ok(())
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-waveshapernode-oversample>
+    fn Oversample(&self) -> OverSampleType {
+        self.oversample.get()
+    }
+
+    /// <https://webaudio.github.io/web-audio-api/#dom-waveshapernode-oversample>
+    fn SetOversample(&self, oversample: OverSampleType) {
+        self.oversample.set(oversample);
+        self.node.message(AudioNodeMessage::WaveShaperNode(
+            WaveShaperNodeMessage::SetOverSample(oversample.into()),
+        ));
+    }
+}
+
+impl From<OverSampleType> for ServoMediaOverSampleType {
+    fn from(oversample: OverSampleType) -> Self {
+        match oversample {
+            OverSampleType::None => ServoMediaOverSampleType::None,
+            OverSampleType::_2x => ServoMediaOverSampleType::Double,
+            OverSampleType::_4x => ServoMediaOverSampleType::Quadruple,
+        }
+    }
+}
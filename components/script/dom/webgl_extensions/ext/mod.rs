@@ -7,6 +7,7 @@ use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGL
 
 pub mod angleinstancedarrays;
 pub mod extblendminmax;
+pub mod extcolorbufferfloat;
 pub mod extcolorbufferhalffloat;
 pub mod extfragdepth;
 pub mod extshadertexturelod;
@@ -21,3 +22,4 @@ pub mod oesvertexarrayobject;
 pub mod webglcolorbufferfloat;
 pub mod webglcompressedtextureetc1;
 pub mod webglcompressedtextures3tc;
+pub mod webglcompressedtextures3tcsrgb;
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use canvas_traits::webgl::{TexFormat, WebGLVersion};
+use canvas_traits::webgl::TexFormat;
 use dom_struct::dom_struct;
 
 use super::{WebGLExtension, WebGLExtensionSpec, WebGLExtensions};
@@ -34,7 +34,7 @@ impl WebGLExtension for WEBGLCompressedTextureS3TC {
     }
 
     fn spec() -> WebGLExtensionSpec {
-        WebGLExtensionSpec::Specific(WebGLVersion::WebGL1)
+        WebGLExtensionSpec::All
     }
 
     fn is_supported(ext: &WebGLExtensions) -> bool {
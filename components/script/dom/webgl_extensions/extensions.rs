@@ -22,6 +22,7 @@ use crate::dom::bindings::codegen::Bindings::OESTextureHalfFloatBinding::OESText
 use crate::dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectConstants;
 use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use crate::dom::bindings::trace::JSTraceable;
+use crate::dom::extcolorbufferfloat::EXTColorBufferFloat;
 use crate::dom::extcolorbufferhalffloat::EXTColorBufferHalfFloat;
 use crate::dom::oestexturefloat::OESTextureFloat;
 use crate::dom::oestexturehalffloat::OESTextureHalfFloat;
@@ -413,6 +414,7 @@ impl WebGLExtensions {
     fn register_all_extensions(&self) {
         self.register::<ext::angleinstancedarrays::ANGLEInstancedArrays>();
         self.register::<ext::extblendminmax::EXTBlendMinmax>();
+        self.register::<ext::extcolorbufferfloat::EXTColorBufferFloat>();
         self.register::<ext::extcolorbufferhalffloat::EXTColorBufferHalfFloat>();
         self.register::<ext::extfragdepth::EXTFragDepth>();
         self.register::<ext::extshadertexturelod::EXTShaderTextureLod>();
@@ -427,6 +429,7 @@ impl WebGLExtensions {
         self.register::<ext::webglcolorbufferfloat::WEBGLColorBufferFloat>();
         self.register::<ext::webglcompressedtextureetc1::WEBGLCompressedTextureETC1>();
         self.register::<ext::webglcompressedtextures3tc::WEBGLCompressedTextureS3TC>();
+        self.register::<ext::webglcompressedtextures3tcsrgb::WEBGLCompressedTextureS3TCsRGB>();
     }
 
     pub fn enable_element_index_uint(&self) {
@@ -453,6 +456,10 @@ impl WebGLExtensions {
         self.glsl_version >= min_glsl_version
     }
 
+    pub fn is_color_buffer_float_enabled(&self) -> bool {
+        self.is_enabled::<EXTColorBufferFloat>()
+    }
+
     pub fn is_half_float_buffer_renderable(&self) -> bool {
         self.is_enabled::<EXTColorBufferHalfFloat>() || self.is_enabled::<OESTextureHalfFloat>()
     }
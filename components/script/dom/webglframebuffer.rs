@@ -360,21 +360,16 @@ impl WebGLFramebuffer {
             constants::ALPHA,
             constants::LUMINANCE,
             constants::LUMINANCE_ALPHA,
-            constants::R11F_G11F_B10F,
-            constants::R16F,
             constants::R16I,
             constants::R16UI,
-            constants::R32F,
             constants::R32I,
             constants::R32UI,
             constants::R8,
             constants::R8_SNORM,
             constants::R8I,
             constants::R8UI,
-            constants::RG16F,
             constants::RG16I,
             constants::RG16UI,
-            constants::RG32F,
             constants::RG32I,
             constants::RG32UI,
             constants::RG8,
@@ -394,10 +389,8 @@ impl WebGLFramebuffer {
             constants::RGB8I,
             constants::RGB8UI,
             constants::RGB9_E5,
-            constants::RGBA16F,
             constants::RGBA16I,
             constants::RGBA16UI,
-            constants::RGBA32F,
             constants::RGBA32I,
             constants::RGBA32UI,
             constants::RGBA8,
@@ -407,14 +400,35 @@ impl WebGLFramebuffer {
             constants::SRGB8,
             constants::SRGB8_ALPHA8,
         ][..];
+        // https://www.khronos.org/registry/webgl/extensions/EXT_color_buffer_float/
+        let float_color_constraints = &[
+            constants::R11F_G11F_B10F,
+            constants::R16F,
+            constants::R32F,
+            constants::RG16F,
+            constants::RG32F,
+            constants::RGBA16F,
+            constants::RGBA32F,
+        ][..];
         let empty_color_constrains = &[][..];
         let extra_color_constraints = match self.webgl_version {
             WebGLVersion::WebGL1 => empty_color_constrains,
             WebGLVersion::WebGL2 => webgl2_color_constraints,
         };
+        let is_color_buffer_float_enabled = self
+            .upcast::<WebGLObject>()
+            .context()
+            .extension_manager()
+            .is_color_buffer_float_enabled();
+        let extra_float_color_constraints = if is_color_buffer_float_enabled {
+            float_color_constraints
+        } else {
+            empty_color_constrains
+        };
         let color_constraints = webgl1_color_constraints
             .iter()
-            .chain(extra_color_constraints.iter());
+            .chain(extra_color_constraints.iter())
+            .chain(extra_float_color_constraints.iter());
 
         let has_c = self.colors.iter().any(|att| att.borrow().is_some());
         for attachment in self.colors.iter() {
@@ -198,6 +198,26 @@ impl WebGLRenderbuffer {
                     constants::RGB8
                 }
             },
+            // https://www.khronos.org/registry/webgl/extensions/EXT_color_buffer_float/
+            constants::R16F |
+            constants::RG16F |
+            constants::RGBA16F |
+            constants::R32F |
+            constants::RG32F |
+            constants::RGBA32F |
+            constants::R11F_G11F_B10F
+                if webgl_version != WebGLVersion::WebGL1 =>
+            {
+                if !self
+                    .upcast()
+                    .context()
+                    .extension_manager()
+                    .is_color_buffer_float_enabled()
+                {
+                    return Err(WebGLError::InvalidEnum);
+                }
+                internal_format
+            },
             EXTColorBufferHalfFloatConstants::RGBA16F_EXT |
             EXTColorBufferHalfFloatConstants::RGB16F_EXT => {
                 if !self
@@ -23,7 +23,7 @@ use crossbeam_channel::{unbounded, Sender, TryRecvError};
 use cssparser::{Parser, ParserInput, SourceLocation};
 use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType};
 use dom_struct::dom_struct;
-use embedder_traits::{EmbedderMsg, PromptDefinition, PromptOrigin, PromptResult};
+use embedder_traits::{EmbedderMsg, PromptDefinition, PromptOrigin, PromptResult, SensorType};
 use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect};
 use euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use ipc_channel::ipc::{self, IpcSender};
@@ -48,7 +48,9 @@ use parking_lot::Mutex as ParkMutex;
 use profile_traits::ipc as ProfiledIpc;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan as TimeProfilerChan;
-use script_layout_interface::message::{Msg, QueryMsg, Reflow, ReflowGoal, ScriptReflow};
+use script_layout_interface::message::{
+    Msg, PrintLayout, QueryMsg, Reflow, ReflowGoal, ScriptReflow,
+};
 use script_layout_interface::rpc::{
     ContentBoxResponse, ContentBoxesResponse, LayoutRPC, NodeScrollIdResponse,
     ResolvedStyleResponse, TextIndexResponse,
@@ -57,12 +59,13 @@ use script_layout_interface::{Layout, PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
 use script_traits::{
     ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData, ScriptMsg,
-    ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
+    ScriptToConstellationChan, ScrollState, SensorReading, StructuredSerializedData, TimerEventId,
     TimerSchedulerMsg, WebrenderIpcSender, WindowSizeData, WindowSizeType,
 };
 use selectors::attr::CaseSensitivity;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_geometry::{f32_rect_to_au_rect, MaxRect};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::dom::OpaqueNode;
@@ -80,7 +83,10 @@ use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPixel};
 use webrender_api::{DocumentId, ExternalScrollId};
 
 use super::bindings::trace::HashMapTracedValues;
+use crate::dom::audiocontext::AudioContext;
 use crate::dom::bindings::cell::{DomRefCell, Ref};
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::AudioContextState;
+use crate::dom::bindings::codegen::Bindings::BaseAudioContextBinding::BaseAudioContext_Binding::BaseAudioContextMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
@@ -90,6 +96,7 @@ use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryList_Binding::MediaQueryListMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -112,6 +119,10 @@ use crate::dom::bluetooth::BluetoothExtraPermissionData;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
+use crate::dom::devicemotionevent::DeviceMotionEvent;
+use crate::dom::devicemotioneventacceleration::DeviceMotionEventAcceleration;
+use crate::dom::devicemotioneventrotationrate::DeviceMotionEventRotationRate;
+use crate::dom::deviceorientationevent::{DeviceOrientation, DeviceOrientationEvent};
 use crate::dom::document::{AnimationFrameCallback, Document, ReflowTriggerCondition};
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventStatus};
@@ -121,17 +132,21 @@ use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlmediaelement::HTMLMediaElement;
 use crate::dom::identityhub::Identities;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
-use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
+use crate::dom::node::{
+    document_from_node, from_untrusted_node_address, Node, NodeDamage, ShadowIncluding,
+};
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
 use crate::dom::screen::Screen;
 use crate::dom::selection::Selection;
+use crate::dom::sensor::Sensor;
 use crate::dom::storage::Storage;
 use crate::dom::testrunner::TestRunner;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
@@ -176,6 +191,7 @@ pub enum ReflowReason {
     MissingExplicitReflow,
     MouseEvent,
     PendingReflow,
+    Print,
     Query,
     RefreshTick,
     RequestAnimationFrame,
@@ -241,6 +257,10 @@ pub struct Window {
     #[no_trace]
     window_size: Cell<WindowSizeData>,
 
+    /// How the document is laid out while it is printed.
+    #[no_trace]
+    print_layout: Cell<Option<PrintLayout>>,
+
     /// A handle for communicating messages to the bluetooth thread.
     #[ignore_malloc_size_of = "channels are hard"]
     #[no_trace]
@@ -281,6 +301,19 @@ pub struct Window {
     /// All the MediaQueryLists we need to update
     media_query_lists: DOMTracker<MediaQueryList>,
 
+    /// The AudioContexts to mute along with the media elements of the document.
+    audio_contexts: DOMTracker<AudioContext>,
+
+    /// Whether the embedder muted the webview of this window.
+    audio_muted: Cell<bool>,
+
+    /// Whether this window is playing audio, as last reported to the constellation.
+    audible: Cell<bool>,
+
+    /// The languages that the embedder set for the webview of this window, most preferred
+    /// first, or none for the default ones.
+    languages: DomRefCell<Vec<String>>,
+
     test_runner: MutNullableDom<TestRunner>,
 
     /// A handle for communicating messages to the WebGL thread, if available.
@@ -359,6 +392,22 @@ pub struct Window {
 
     /// <https://dom.spec.whatwg.org/#window-current-event>
     current_event: DomRefCell<Option<Dom<Event>>>,
+
+    /// The generic sensors of this window that are started.
+    active_sensors: DomRefCell<Vec<Dom<Sensor>>>,
+
+    /// The device sensors the embedder was asked to read for this window.
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    #[no_trace]
+    requested_sensors: DomRefCell<HashSet<SensorType>>,
+
+    /// The latest rate of rotation around the z, x and y axes, in degrees per
+    /// second, which is reported along with the next device motion event.
+    #[no_trace]
+    last_rotation_rate: Cell<Option<(f64, f64, f64)>>,
+
+    /// When the last acceleration was read, to report device motion intervals.
+    last_motion_timestamp: Cell<Option<f64>>,
 }
 
 impl Window {
@@ -992,6 +1041,16 @@ impl WindowMethods for Window {
     // https://html.spec.whatwg.org/multipage/#windoweventhandlers
     window_event_handlers!();
 
+    // https://w3c.github.io/deviceorientation/#dom-window-ondeviceorientation
+    event_handler!(
+        deviceorientation,
+        GetOndeviceorientation,
+        SetOndeviceorientation
+    );
+
+    // https://w3c.github.io/deviceorientation/#dom-window-ondevicemotion
+    event_handler!(devicemotion, GetOndevicemotion, SetOndevicemotion);
+
     // https://developer.mozilla.org/en-US/docs/Web/API/Window/screen
     fn Screen(&self) -> DomRoot<Screen> {
         self.screen.or_init(|| Screen::new(self))
@@ -1686,9 +1745,144 @@ impl Window {
         if let Some(performance) = self.performance.get() {
             performance.clear_and_disable_performance_entry_buffer();
         }
+        self.active_sensors.borrow_mut().clear();
+        self.set_requested_sensors(HashSet::new());
         self.ignore_all_tasks();
     }
 
+    /// Start delivering device sensor readings to `sensor`.
+    pub(crate) fn add_sensor(&self, sensor: &Sensor) {
+        self.active_sensors.borrow_mut().push(Dom::from_ref(sensor));
+        self.update_requested_sensors();
+    }
+
+    /// Stop delivering device sensor readings to `sensor`.
+    pub(crate) fn remove_sensor(&self, sensor: &Sensor) {
+        self.active_sensors
+            .borrow_mut()
+            .retain(|active_sensor| &**active_sensor != sensor);
+        self.update_requested_sensors();
+    }
+
+    /// Called whenever listeners for `ty` events are added to or removed from
+    /// this window.
+    pub(crate) fn event_listeners_changed(&self, ty: &Atom) {
+        if &**ty == "deviceorientation" || &**ty == "devicemotion" {
+            self.update_requested_sensors();
+        }
+    }
+
+    /// Ask the embedder to start reading the device sensors that content in this
+    /// window listens to, and to stop reading the ones it no longer does.
+    fn update_requested_sensors(&self) {
+        if !pref!(dom.sensors.enabled) {
+            return;
+        }
+        let mut sensors: HashSet<SensorType> = self
+            .active_sensors
+            .borrow()
+            .iter()
+            .map(|sensor| sensor.sensor_type())
+            .collect();
+        let target = self.upcast::<EventTarget>();
+        if target.has_listeners_for(&Atom::from("deviceorientation")) {
+            sensors.insert(SensorType::Orientation);
+        }
+        if target.has_listeners_for(&Atom::from("devicemotion")) {
+            sensors.insert(SensorType::Accelerometer);
+            sensors.insert(SensorType::Gyroscope);
+        }
+        self.set_requested_sensors(sensors);
+    }
+
+    fn set_requested_sensors(&self, sensors: HashSet<SensorType>) {
+        let mut requested_sensors = self.requested_sensors.borrow_mut();
+        for sensor in sensors.difference(&requested_sensors) {
+            self.send_to_embedder(EmbedderMsg::StartSensor(*sensor));
+        }
+        for sensor in requested_sensors.difference(&sensors) {
+            self.send_to_embedder(EmbedderMsg::StopSensor(*sensor));
+        }
+        *requested_sensors = sensors;
+    }
+
+    /// Deliver a device sensor reading to the started sensors and the device
+    /// event listeners of this window.
+    pub fn handle_sensor_reading(&self, reading: SensorReading) {
+        if !pref!(dom.sensors.enabled) {
+            return;
+        }
+        let timestamp = *self.Performance().Now();
+        let sensor_type = reading.sensor_type();
+        let sensors: Vec<DomRoot<Sensor>> = self
+            .active_sensors
+            .borrow()
+            .iter()
+            .filter(|sensor| sensor.sensor_type() == sensor_type)
+            .map(|sensor| DomRoot::from_ref(&**sensor))
+            .collect();
+        for sensor in sensors {
+            sensor.handle_reading(reading, timestamp);
+        }
+
+        let target = self.upcast::<EventTarget>();
+        match reading {
+            SensorReading::Orientation {
+                alpha,
+                beta,
+                gamma,
+                absolute,
+            } => {
+                let type_ = Atom::from("deviceorientation");
+                if !target.has_listeners_for(&type_) {
+                    return;
+                }
+                let orientation = DeviceOrientation {
+                    alpha: Some(alpha),
+                    beta: Some(beta),
+                    gamma: Some(gamma),
+                    absolute,
+                };
+                let event = DeviceOrientationEvent::new(self, type_, false, false, orientation);
+                event.upcast::<Event>().fire(target);
+            },
+            SensorReading::Gyroscope { x, y, z } => {
+                self.last_rotation_rate
+                    .set(Some((z.to_degrees(), x.to_degrees(), y.to_degrees())));
+            },
+            SensorReading::Accelerometer { x, y, z } => {
+                // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-interval
+                let interval = self
+                    .last_motion_timestamp
+                    .replace(Some(timestamp))
+                    .map_or(0., |last_timestamp| timestamp - last_timestamp);
+                let type_ = Atom::from("devicemotion");
+                if !target.has_listeners_for(&type_) {
+                    return;
+                }
+                let acceleration_including_gravity =
+                    DeviceMotionEventAcceleration::new(self, Some(x), Some(y), Some(z));
+                let rotation_rate = self.last_rotation_rate.get().map(|(alpha, beta, gamma)| {
+                    DeviceMotionEventRotationRate::new(self, Some(alpha), Some(beta), Some(gamma))
+                });
+                // Gravity can't be told apart from the device's own acceleration
+                // without a linear acceleration sensor, so `acceleration` stays null.
+                let event = DeviceMotionEvent::new(
+                    self,
+                    type_,
+                    false,
+                    false,
+                    None,
+                    Some(&acceleration_including_gravity),
+                    rotation_rate.as_deref(),
+                    interval,
+                );
+                event.upcast::<Event>().fire(target);
+            },
+            SensorReading::AmbientLight { .. } => {},
+        }
+    }
+
     /// <https://drafts.csswg.org/cssom-view/#dom-window-scroll>
     pub fn scroll(&self, x_: f64, y_: f64, behavior: ScrollBehavior) {
         // Step 3
@@ -1882,6 +2076,7 @@ impl Window {
             pending_restyles,
             animation_timeline_value: document.current_animation_timeline_value(),
             animations: document.animations().sets.clone(),
+            print_layout: self.print_layout.get(),
         };
 
         let _ = self.with_layout(move |layout| layout.process(Msg::Reflow(reflow)));
@@ -2303,6 +2498,11 @@ impl Window {
         self.window_size.get()
     }
 
+    /// Lay out the document for printing from the next reflow, or stop doing so.
+    pub(crate) fn set_print_layout(&self, print_layout: Option<PrintLayout>) {
+        self.print_layout.set(print_layout);
+    }
+
     pub fn get_url(&self) -> ServoUrl {
         self.Document().url()
     }
@@ -2465,6 +2665,7 @@ impl Window {
         self.visible.set(visible);
         if visible {
             self.upcast::<GlobalScope>().speed_up_timers();
+            self.Document().resume_animation_frames();
         } else {
             self.upcast::<GlobalScope>().slow_down_timers();
         }
@@ -2474,6 +2675,62 @@ impl Window {
         self.visible.get()
     }
 
+    pub fn track_audio_context(&self, audio_context: &AudioContext) {
+        self.audio_contexts.track(audio_context);
+    }
+
+    pub fn audio_muted(&self) -> bool {
+        self.audio_muted.get()
+    }
+
+    /// Mute or unmute the media elements of the document and the AudioContexts of this
+    /// window, without changing their `muted` attributes or states.
+    pub fn set_audio_muted(&self, muted: bool) {
+        if self.audio_muted.replace(muted) == muted {
+            return;
+        }
+        for media_element in self.media_elements() {
+            media_element.apply_mute();
+        }
+        self.audio_contexts
+            .for_each(|audio_context| audio_context.base().set_muted(muted));
+    }
+
+    pub fn languages(&self) -> Ref<Vec<String>> {
+        self.languages.borrow()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#event-languagechange>
+    pub fn set_languages(&self, languages: Vec<String>) {
+        if *self.languages.borrow() == languages {
+            return;
+        }
+        *self.languages.borrow_mut() = languages;
+        self.upcast::<EventTarget>()
+            .fire_event(Atom::from("languagechange"));
+    }
+
+    /// Tell the constellation whether this window is playing audio, if that changed.
+    /// Muting doesn't change whether it does.
+    pub fn update_audio_state(&self) {
+        let mut audible = self
+            .media_elements()
+            .any(|media_element| media_element.is_audible());
+        self.audio_contexts.for_each(|audio_context| {
+            audible |= audio_context.base().State() == AudioContextState::Running;
+        });
+        if self.audible.replace(audible) != audible {
+            self.send_to_constellation(ScriptMsg::AudioStateChanged(audible));
+        }
+    }
+
+    fn media_elements(&self) -> impl Iterator<Item = DomRoot<HTMLMediaElement>> {
+        self.Document()
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::Yes)
+            .filter_map(DomRoot::downcast::<HTMLMediaElement>)
+    }
+
     pub fn unminified_js_dir(&self) -> Option<String> {
         self.unminified_js_dir.borrow().clone()
     }
@@ -2599,6 +2856,7 @@ impl Window {
             page_clip_rect: Cell::new(MaxRect::max_rect()),
             resize_event: Default::default(),
             window_size: Cell::new(window_size),
+            print_layout: Cell::new(None),
             current_viewport: Cell::new(Rect::zero()),
             suppress_reflow: Cell::new(true),
             pending_reflow_count: Default::default(),
@@ -2609,6 +2867,10 @@ impl Window {
             error_reporter,
             scroll_offsets: Default::default(),
             media_query_lists: DOMTracker::new(),
+            audio_contexts: DOMTracker::new(),
+            audio_muted: Cell::new(false),
+            languages: Default::default(),
+            audible: Cell::new(false),
             test_runner: Default::default(),
             webgl_chan,
             webxr_registry,
@@ -2630,6 +2892,10 @@ impl Window {
             visible: Cell::new(true),
             layout_marker: DomRefCell::new(Rc::new(Cell::new(true))),
             current_event: DomRefCell::new(None),
+            active_sensors: Default::default(),
+            requested_sensors: Default::default(),
+            last_rotation_rate: Cell::new(None),
+            last_motion_timestamp: Cell::new(None),
         });
 
         unsafe { WindowBinding::Wrap(JSContext::from_ptr(runtime.cx()), win) }
@@ -6,7 +6,7 @@ use std::cell::Cell;
 use std::ptr;
 
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, WindowFeatures};
 use html5ever::local_name;
 use indexmap::map::IndexMap;
 use ipc_channel::ipc;
@@ -285,6 +285,8 @@ impl WindowProxy {
         &self,
         name: DOMString,
         noopener: bool,
+        url: Option<ServoUrl>,
+        features: WindowFeatures,
     ) -> Option<DomRoot<WindowProxy>> {
         let (chan, port) = ipc::channel().unwrap();
         let window = self
@@ -293,10 +295,17 @@ impl WindowProxy {
             .and_then(|id| ScriptThread::find_document(id))
             .and_then(|doc| Some(DomRoot::from_ref(doc.window())))
             .unwrap();
-        let msg = EmbedderMsg::AllowOpeningWebView(chan);
-        window.send_to_embedder(msg);
-        if port.recv().unwrap() {
-            let new_top_level_browsing_context_id = TopLevelBrowsingContextId::new();
+        // The constellation blocks popups opened without transient user activation, and
+        // asks the embedder about the others, which picks the id of their webview.
+        let msg = ScriptMsg::AllowOpeningWebView(
+            url,
+            String::from(name.clone()),
+            noopener,
+            features,
+            chan,
+        );
+        window.send_to_constellation(msg);
+        if let Some(new_top_level_browsing_context_id) = port.recv().unwrap() {
             let new_browsing_context_id =
                 BrowsingContextId::from(new_top_level_browsing_context_id);
             let new_pipeline_id = PipelineId::new();
@@ -468,27 +477,53 @@ impl WindowProxy {
         url: USVString,
         target: DOMString,
         features: DOMString,
+    ) -> Fallible<Option<DomRoot<WindowProxy>>> {
+        // Step 5
+        let tokenized_features = tokenize_open_features(features);
+        // Step 7-9
+        let noreferrer = parse_open_feature_boolean(&tokenized_features, "noreferrer");
+        let noopener = parse_open_feature_boolean(&tokenized_features, "noopener");
+        let features = parse_window_features(&tokenized_features);
+        self.open_with_features(url, target, noopener, noreferrer, features)
+    }
+
+    /// The window open steps, with features that are already parsed, such as those of a
+    /// popup that was blocked and is opened again.
+    pub fn open_with_features(
+        &self,
+        url: USVString,
+        target: DOMString,
+        noopener: bool,
+        noreferrer: bool,
+        features: WindowFeatures,
     ) -> Fallible<Option<DomRoot<WindowProxy>>> {
         // Step 4.
         let non_empty_target = match target.as_ref() {
             "" => DOMString::from("_blank"),
             _ => target,
         };
-        // Step 5
-        let tokenized_features = tokenize_open_features(features);
-        // Step 7-9
-        let noreferrer = parse_open_feature_boolean(&tokenized_features, "noreferrer");
-        let noopener = if noreferrer {
-            true
+        let noopener = noopener || noreferrer;
+        let existing_document = self
+            .currently_active
+            .get()
+            .and_then(|id| ScriptThread::find_document(id))
+            .unwrap();
+        // Step 14.1, parsed before choosing a browsing context so that a blocked popup can
+        // say what it would have loaded.
+        let url = if url.is_empty() {
+            None
         } else {
-            parse_open_feature_boolean(&tokenized_features, "noopener")
-        };
-        // Step 10, 11
-        let (chosen, new) = match self.choose_browsing_context(non_empty_target, noopener) {
-            (Some(chosen), new) => (chosen, new),
-            (None, _) => return Ok(None),
+            match existing_document.url().join(&url) {
+                Ok(url) => Some(url),
+                Err(_) => return Err(Error::Syntax),
+            }
         };
-        // TODO Step 12, set up browsing context features.
+        // Step 10, 11, 12
+        let (chosen, new) =
+            match self.choose_browsing_context(non_empty_target, noopener, url.clone(), features) {
+                (Some(chosen), new) => (chosen, new),
+                (None, _) => return Ok(None),
+            };
         let target_document = match chosen.document() {
             Some(target_document) => target_document,
             None => return Ok(None),
@@ -496,17 +531,7 @@ impl WindowProxy {
         let target_window = target_document.window();
         // Step 13, and 14.4, will have happened elsewhere,
         // since we've created a new browsing context and loaded it with about:blank.
-        if !url.is_empty() {
-            let existing_document = self
-                .currently_active
-                .get()
-                .and_then(|id| ScriptThread::find_document(id))
-                .unwrap();
-            // Step 14.1
-            let url = match existing_document.url().join(&url) {
-                Ok(url) => url,
-                Err(_) => return Err(Error::Syntax),
-            };
+        if let Some(url) = url {
             // Step 14.3
             let referrer = if noreferrer {
                 Referrer::NoReferrer
@@ -517,7 +542,7 @@ impl WindowProxy {
             let referrer_policy = target_document.get_referrer_policy();
             let pipeline_id = target_window.upcast::<GlobalScope>().pipeline_id();
             let secure = target_window.upcast::<GlobalScope>().is_secure_context();
-            let load_data = LoadData::new(
+            let mut load_data = LoadData::new(
                 LoadOrigin::Script(existing_document.origin().immutable().clone()),
                 url,
                 Some(pipeline_id),
@@ -525,6 +550,7 @@ impl WindowProxy {
                 referrer_policy,
                 Some(secure),
             );
+            load_data.initiator_pipeline_id = Some(existing_document.window().pipeline_id());
             let replacement_flag = if new {
                 HistoryEntryReplacement::Enabled
             } else {
@@ -541,10 +567,14 @@ impl WindowProxy {
     }
 
     // https://html.spec.whatwg.org/multipage/#the-rules-for-choosing-a-browsing-context-given-a-browsing-context-name
+    // `url` is the URL that a new auxiliary browsing context would load, and `features` the
+    // features of its window, which the embedder is told about.
     pub fn choose_browsing_context(
         &self,
         name: DOMString,
         noopener: bool,
+        url: Option<ServoUrl>,
+        features: WindowFeatures,
     ) -> (Option<DomRoot<WindowProxy>>, bool) {
         match name.to_lowercase().as_ref() {
             "" | "_self" => {
@@ -562,7 +592,10 @@ impl WindowProxy {
                 // Step 5
                 (Some(DomRoot::from_ref(self.top())), false)
             },
-            "_blank" => (self.create_auxiliary_browsing_context(name, noopener), true),
+            "_blank" => (
+                self.create_auxiliary_browsing_context(name, noopener, url, features),
+                true,
+            ),
             _ => {
                 // Step 6.
                 // TODO: expand the search to all 'familiar' bc,
@@ -570,7 +603,10 @@ impl WindowProxy {
                 // See https://html.spec.whatwg.org/multipage/#familiar-with
                 match ScriptThread::find_window_proxy_by_name(&name) {
                     Some(proxy) => (Some(proxy), false),
-                    None => (self.create_auxiliary_browsing_context(name, noopener), true),
+                    None => (
+                        self.create_auxiliary_browsing_context(name, noopener, url, features),
+                        true,
+                    ),
                 }
             },
         }
@@ -843,6 +879,60 @@ fn parse_open_feature_boolean(tokenized_features: &IndexMap<String, String>, nam
     return false;
 }
 
+// https://html.spec.whatwg.org/multipage/#concept-window-open-features-parse-boolean
+// with a default for the features that aren't set.
+fn window_feature_is_set(
+    tokenized_features: &IndexMap<String, String>,
+    name: &str,
+    default: bool,
+) -> bool {
+    if !tokenized_features.contains_key(name) {
+        return default;
+    }
+    parse_open_feature_boolean(tokenized_features, name)
+}
+
+// https://html.spec.whatwg.org/multipage/#popup-window-is-requested
+fn is_popup_window_requested(tokenized_features: &IndexMap<String, String>) -> bool {
+    // Step 1
+    if tokenized_features.is_empty() {
+        return false;
+    }
+    // Step 2
+    if tokenized_features.contains_key("popup") {
+        return parse_open_feature_boolean(tokenized_features, "popup");
+    }
+    // Step 3-5
+    let location = window_feature_is_set(tokenized_features, "location", false);
+    let toolbar = window_feature_is_set(tokenized_features, "toolbar", false);
+    if !location && !toolbar {
+        return true;
+    }
+    // Step 6-13
+    let is_unset = |name, default| !window_feature_is_set(tokenized_features, name, default);
+    is_unset("menubar", false) ||
+        is_unset("resizable", true) ||
+        is_unset("scrollbars", false) ||
+        is_unset("status", false)
+}
+
+// https://drafts.csswg.org/cssom-view/#the-features-argument-to-the-open()-method
+fn parse_window_features(tokenized_features: &IndexMap<String, String>) -> WindowFeatures {
+    let integer = |name: &str| {
+        let value = tokenized_features.get(name)?;
+        parse_integer(value.chars()).ok()
+    };
+    // Sizes that aren't positive are ignored, as windows can't be that small.
+    let size = |name: &str| integer(name).filter(|size| *size > 0);
+    WindowFeatures {
+        left: integer("left"),
+        top: integer("top"),
+        width: size("width"),
+        height: size("height"),
+        popup: is_popup_window_requested(tokenized_features),
+    }
+}
+
 // This is only called from extern functions,
 // there's no use using the lifetimed handles here.
 // https://html.spec.whatwg.org/multipage/#accessing-other-browsing-contexts
@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::XRAnchorBinding::XRAnchorMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xrsession::{ApiPose, XRSession};
+use crate::dom::xrspace::XRSpace;
+
+/// An anchor fixed at a pose in the device's native space. Anchors are
+/// tracked by the session until they are deleted or the session ends.
+#[dom_struct]
+pub struct XRAnchor {
+    reflector_: Reflector,
+    session: Dom<XRSession>,
+    anchor_space: Dom<XRSpace>,
+    /// <https://immersive-web.github.io/anchors/#xranchor-deleted>
+    deleted: Cell<bool>,
+}
+
+impl XRAnchor {
+    fn new_inherited(session: &XRSession, anchor_space: &XRSpace) -> XRAnchor {
+        XRAnchor {
+            reflector_: Reflector::new(),
+            session: Dom::from_ref(session),
+            anchor_space: Dom::from_ref(anchor_space),
+            deleted: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, session: &XRSession, pose: ApiPose) -> DomRoot<XRAnchor> {
+        let anchor_space = XRSpace::new_anchorspace(global, session, pose);
+        reflect_dom_object(
+            Box::new(XRAnchor::new_inherited(session, &anchor_space)),
+            global,
+        )
+    }
+
+    /// Stop tracking this anchor, as happens when its session ends.
+    pub fn mark_deleted(&self) {
+        self.deleted.set(true);
+    }
+}
+
+impl XRAnchorMethods for XRAnchor {
+    /// <https://immersive-web.github.io/anchors/#dom-xranchor-anchorspace>
+    fn GetAnchorSpace(&self) -> Fallible<DomRoot<XRSpace>> {
+        if self.deleted.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(DomRoot::from_ref(&self.anchor_space))
+    }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xranchor-delete>
+    fn Delete(&self) {
+        if self.deleted.get() {
+            return;
+        }
+        self.session.tracked_anchors().remove(self);
+        self.deleted.set(true);
+    }
+}
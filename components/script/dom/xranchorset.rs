@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::XRAnchorSetBinding::XRAnchorSetMethods;
+use crate::dom::bindings::like::Setlike;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::xranchor::XRAnchor;
+
+#[dom_struct]
+pub struct XRAnchorSet {
+    reflector_: Reflector,
+    anchors: DomRefCell<Vec<Dom<XRAnchor>>>,
+}
+
+impl XRAnchorSet {
+    fn new_inherited() -> XRAnchorSet {
+        XRAnchorSet {
+            reflector_: Reflector::new(),
+            anchors: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<XRAnchorSet> {
+        reflect_dom_object(Box::new(XRAnchorSet::new_inherited()), global)
+    }
+
+    pub fn insert(&self, anchor: &XRAnchor) {
+        self.anchors.borrow_mut().push(Dom::from_ref(anchor));
+    }
+
+    pub fn remove(&self, anchor: &XRAnchor) {
+        self.anchors.borrow_mut().retain(|a| &**a != anchor);
+    }
+
+    /// Stop tracking every anchor in the set, as happens when the session ends.
+    pub fn delete_all(&self) {
+        for anchor in self.anchors.borrow_mut().drain(..) {
+            anchor.mark_deleted();
+        }
+    }
+}
+
+impl XRAnchorSetMethods for XRAnchorSet {
+    fn Size(&self) -> u32 {
+        self.anchors.borrow().len() as u32
+    }
+}
+
+// this error is wrong because if we inline Self::Key and Self::Value all errors are gone
+#[allow(crown::unrooted_must_root)]
+impl Setlike for XRAnchorSet {
+    type Key = DomRoot<XRAnchor>;
+
+    #[inline(always)]
+    fn get_index(&self, index: u32) -> Option<Self::Key> {
+        self.anchors
+            .borrow()
+            .get(index as usize)
+            .map(|anchor| DomRoot::from_ref(&**anchor))
+    }
+    #[inline(always)]
+    fn size(&self) -> u32 {
+        self.anchors.borrow().len() as u32
+    }
+    #[inline(always)]
+    fn add(&self, _key: Self::Key) {
+        unreachable!("readonly");
+    }
+    #[inline(always)]
+    fn has(&self, key: Self::Key) -> bool {
+        self.anchors.borrow().iter().any(|anchor| **anchor == *key)
+    }
+    #[inline(always)]
+    fn clear(&self) {
+        unreachable!("readonly");
+    }
+    #[inline(always)]
+    fn delete(&self, _key: Self::Key) -> bool {
+        unreachable!("readonly");
+    }
+}
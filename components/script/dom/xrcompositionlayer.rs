@@ -2,11 +2,97 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
+
 use dom_struct::dom_struct;
+use webxr_api::{ContextId as WebXRContextId, LayerId};
 
+use crate::dom::bindings::codegen::Bindings::XRCompositionLayerBinding::XRCompositionLayerMethods;
+use crate::dom::bindings::codegen::Bindings::XRWebGLBindingBinding::XRLayerLayout;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::xrlayer::XRLayer;
+use crate::dom::xrsession::XRSession;
 
 #[dom_struct]
 pub struct XRCompositionLayer {
     xr_layer: XRLayer,
+    layout: XRLayerLayout,
+    blend_texture_source_alpha: Cell<bool>,
+    chromatic_aberration_correction: Cell<Option<bool>>,
+    destroyed: Cell<bool>,
+}
+
+impl XRCompositionLayer {
+    pub fn new_inherited(
+        session: &XRSession,
+        context: &WebGLRenderingContext,
+        layer_id: LayerId,
+        layout: XRLayerLayout,
+    ) -> XRCompositionLayer {
+        XRCompositionLayer {
+            xr_layer: XRLayer::new_inherited(session, context, Some(layer_id)),
+            layout,
+            blend_texture_source_alpha: Cell::new(true),
+            chromatic_aberration_correction: Cell::new(None),
+            destroyed: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn is_destroyed(&self) -> bool {
+        self.destroyed.get()
+    }
+
+    pub(crate) fn session(&self) -> &XRSession {
+        self.xr_layer.session()
+    }
+
+    pub(crate) fn context(&self) -> &WebGLRenderingContext {
+        self.xr_layer.context()
+    }
+}
+
+impl XRCompositionLayerMethods for XRCompositionLayer {
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-layout>
+    fn Layout(&self) -> XRLayerLayout {
+        self.layout
+    }
+
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-blendtexturesourcealpha>
+    fn BlendTextureSourceAlpha(&self) -> bool {
+        self.blend_texture_source_alpha.get()
+    }
+
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-blendtexturesourcealpha>
+    fn SetBlendTextureSourceAlpha(&self, value: bool) {
+        self.blend_texture_source_alpha.set(value)
+    }
+
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-chromaticaberrationcorrection>
+    fn GetChromaticAberrationCorrection(&self) -> Option<bool> {
+        self.chromatic_aberration_correction.get()
+    }
+
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-chromaticaberrationcorrection>
+    fn SetChromaticAberrationCorrection(&self, value: Option<bool>) {
+        // The runtime does not expose this setting, so the hint is only stored.
+        self.chromatic_aberration_correction.set(value)
+    }
+
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-needsredraw>
+    fn NeedsRedraw(&self) -> bool {
+        // Only projection layers are supported, and they are redrawn every frame.
+        false
+    }
+
+    /// <https://immersive-web.github.io/layers/#dom-xrcompositionlayer-destroy>
+    fn Destroy(&self) {
+        if self.destroyed.replace(true) {
+            return;
+        }
+        if let Some(layer_id) = self.xr_layer.layer_id() {
+            let context_id = WebXRContextId::from(self.xr_layer.context_id());
+            self.session()
+                .with_session(|session| session.destroy_layer(context_id, layer_id));
+        }
+    }
 }
@@ -3,9 +3,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::rc::Rc;
 
 use dom_struct::dom_struct;
-use webxr_api::{Frame, LayerId, SubImages};
+use js::rust::CustomAutoRooterGuard;
+use js::typedarray::Float32Array;
+use webxr_api::{Frame, InputFrame, LayerId, SubImages};
 
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
 use crate::dom::bindings::error::Error;
@@ -13,12 +16,15 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::xranchorset::XRAnchorSet;
 use crate::dom::xrhittestresult::XRHitTestResult;
 use crate::dom::xrhittestsource::XRHitTestSource;
 use crate::dom::xrjointpose::XRJointPose;
 use crate::dom::xrjointspace::XRJointSpace;
 use crate::dom::xrpose::XRPose;
 use crate::dom::xrreferencespace::XRReferenceSpace;
+use crate::dom::xrrigidtransform::XRRigidTransform;
 use crate::dom::xrsession::{ApiPose, XRSession};
 use crate::dom::xrspace::XRSpace;
 use crate::dom::xrviewerpose::XRViewerPose;
@@ -63,6 +69,14 @@ impl XRFrame {
         space.get_pose(&self.data)
     }
 
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+
+    pub fn inputs(&self) -> &[InputFrame] {
+        &self.data.inputs
+    }
+
     pub fn get_sub_images(&self, layer_id: LayerId) -> Option<&SubImages> {
         self.data
             .sub_images
@@ -167,6 +181,98 @@ impl XRFrameMethods for XRFrame {
         )))
     }
 
+    #[allow(unsafe_code)]
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-filljointradii>
+    fn FillJointRadii(
+        &self,
+        joint_spaces: Vec<DomRoot<XRJointSpace>>,
+        mut radii: CustomAutoRooterGuard<Float32Array>,
+    ) -> Result<bool, Error> {
+        // Step 1.
+        if !self.active.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2.
+        if joint_spaces
+            .iter()
+            .any(|space| self.session != space.upcast::<XRSpace>().session())
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        if joint_spaces.len() > radii.len() {
+            return Err(Error::Type(String::from(
+                "The radii array is smaller than the number of joints.",
+            )));
+        }
+
+        // Steps 4-5. No JS code can run while we write into the array.
+        let radii = unsafe { radii.as_mut_slice() };
+        let mut all_valid = true;
+        for (space, radius) in joint_spaces.iter().zip(radii.iter_mut()) {
+            match space.frame(&self.data) {
+                Some(joint_frame) => *radius = joint_frame.radius,
+                None => {
+                    *radius = f32::NAN;
+                    all_valid = false;
+                },
+            }
+        }
+
+        // Step 6.
+        Ok(all_valid)
+    }
+
+    #[allow(unsafe_code)]
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-fillposes>
+    fn FillPoses(
+        &self,
+        spaces: Vec<DomRoot<XRSpace>>,
+        base_space: &XRSpace,
+        mut transforms: CustomAutoRooterGuard<Float32Array>,
+    ) -> Result<bool, Error> {
+        // Step 1.
+        if !self.active.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2.
+        if self.session != base_space.session() ||
+            spaces.iter().any(|space| self.session != space.session())
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        if spaces.len() * 16 > transforms.len() {
+            return Err(Error::Type(String::from(
+                "The transforms array is smaller than 16 times the number of spaces.",
+            )));
+        }
+
+        // Steps 4-5. No JS code can run while we write into the array.
+        let transforms = unsafe { transforms.as_mut_slice() };
+        let base_pose = self.get_pose(base_space);
+        let mut all_valid = true;
+        for (space, matrix) in spaces.iter().zip(transforms.chunks_mut(16)) {
+            match (self.get_pose(space), base_pose) {
+                (Some(pose), Some(base_pose)) => {
+                    let pose = pose.then(&base_pose.inverse());
+                    matrix.copy_from_slice(&pose.to_transform().to_array());
+                },
+                _ => {
+                    matrix.fill(f32::NAN);
+                    all_valid = false;
+                },
+            }
+        }
+
+        // Step 6.
+        Ok(all_valid)
+    }
+
     /// <https://immersive-web.github.io/hit-test/#dom-xrframe-gethittestresults>
     fn GetHitTestResults(&self, source: &XRHitTestSource) -> Vec<DomRoot<XRHitTestResult>> {
         self.data
@@ -176,4 +282,27 @@ impl XRFrameMethods for XRFrame {
             .map(|r| XRHitTestResult::new(&self.global(), *r, self))
             .collect()
     }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xrframe-createanchor>
+    fn CreateAnchor(&self, pose: &XRRigidTransform, space: &XRSpace) -> Rc<Promise> {
+        if self.session != space.session() || !self.active.get() {
+            let p = Promise::new(&self.global());
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        let space = if let Some(space) = self.get_pose(space) {
+            space
+        } else {
+            let p = Promise::new(&self.global());
+            p.reject_error(Error::InvalidState);
+            return p;
+        };
+        let pose = pose.transform().then(&space);
+        self.session.create_anchor(pose)
+    }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xrframe-trackedanchors>
+    fn TrackedAnchors(&self) -> DomRoot<XRAnchorSet> {
+        self.session.tracked_anchors()
+    }
 }
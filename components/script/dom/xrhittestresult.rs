@@ -2,13 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::rc::Rc;
+
 use dom_struct::dom_struct;
 use webxr_api::HitTestResult;
 
+use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
 use crate::dom::bindings::codegen::Bindings::XRHitTestResultBinding::XRHitTestResultMethods;
+use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
 use crate::dom::xrframe::XRFrame;
 use crate::dom::xrpose::XRPose;
 use crate::dom::xrspace::XRSpace;
@@ -50,4 +55,16 @@ impl XRHitTestResultMethods for XRHitTestResult {
         let pose = self.result.space.then(&base.inverse());
         Some(XRPose::new(&self.global(), pose.cast_unit()))
     }
+
+    /// <https://immersive-web.github.io/anchors/#dom-xrhittestresult-createanchor>
+    fn CreateAnchor(&self) -> Rc<Promise> {
+        if !self.frame.is_active() {
+            let p = Promise::new(&self.global());
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+        self.frame
+            .Session()
+            .create_anchor(self.result.space.cast_unit())
+    }
 }
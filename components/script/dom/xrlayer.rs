@@ -11,6 +11,7 @@ use crate::dom::bindings::root::Dom;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::xrframe::XRFrame;
+use crate::dom::xrprojectionlayer::XRProjectionLayer;
 use crate::dom::xrsession::XRSession;
 use crate::dom::xrwebgllayer::XRWebGLLayer;
 
@@ -27,7 +28,6 @@ pub struct XRLayer {
 }
 
 impl XRLayer {
-    #[allow(dead_code)]
     pub fn new_inherited(
         session: &XRSession,
         context: &WebGLRenderingContext,
@@ -61,6 +61,8 @@ impl XRLayer {
         // TODO: Implement this for other layer types
         if let Some(this) = self.downcast::<XRWebGLLayer>() {
             this.begin_frame(frame)
+        } else if let Some(this) = self.downcast::<XRProjectionLayer>() {
+            this.begin_frame(frame)
         } else {
             unimplemented!()
         }
@@ -70,6 +72,8 @@ impl XRLayer {
         // TODO: Implement this for other layer types
         if let Some(this) = self.downcast::<XRWebGLLayer>() {
             this.end_frame(frame)
+        } else if let Some(this) = self.downcast::<XRProjectionLayer>() {
+            this.end_frame(frame)
         } else {
             unimplemented!()
         }
@@ -2,11 +2,130 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use canvas_traits::webgl::WebGLTextureId;
 use dom_struct::dom_struct;
+use webxr_api::{LayerId, SubImages};
 
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
+use crate::dom::bindings::codegen::Bindings::XRProjectionLayerBinding::XRProjectionLayerMethods;
+use crate::dom::bindings::codegen::Bindings::XRWebGLBindingBinding::XRLayerLayout;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::webglrenderingcontext::WebGLRenderingContext;
+use crate::dom::webgltexture::WebGLTexture;
 use crate::dom::xrcompositionlayer::XRCompositionLayer;
+use crate::dom::xrframe::XRFrame;
+use crate::dom::xrlayer::XRLayer;
+use crate::dom::xrsession::XRSession;
+use crate::dom::xrview::XRView;
+use crate::dom::xrwebglsubimage::XRWebGLSubImage;
 
 #[dom_struct]
 pub struct XRProjectionLayer {
     composition_layer: XRCompositionLayer,
+    ignore_depth_values: bool,
+    /// The sub images handed out by the runtime for the current animation frame
+    #[ignore_malloc_size_of = "Defined in webxr"]
+    #[no_trace]
+    sub_images: DomRefCell<Option<SubImages>>,
+}
+
+impl XRProjectionLayer {
+    fn new_inherited(
+        session: &XRSession,
+        context: &WebGLRenderingContext,
+        layer_id: LayerId,
+        layout: XRLayerLayout,
+    ) -> XRProjectionLayer {
+        XRProjectionLayer {
+            composition_layer: XRCompositionLayer::new_inherited(
+                session, context, layer_id, layout,
+            ),
+            ignore_depth_values: false,
+            sub_images: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        session: &XRSession,
+        context: &WebGLRenderingContext,
+        layer_id: LayerId,
+        layout: XRLayerLayout,
+    ) -> DomRoot<XRProjectionLayer> {
+        reflect_dom_object(
+            Box::new(XRProjectionLayer::new_inherited(
+                session, context, layer_id, layout,
+            )),
+            global,
+        )
+    }
+
+    pub(crate) fn session(&self) -> &XRSession {
+        self.composition_layer.session()
+    }
+
+    pub fn begin_frame(&self, frame: &XRFrame) -> Option<()> {
+        debug!("XRProjectionLayer begin frame");
+        if self.composition_layer.is_destroyed() {
+            return None;
+        }
+        let layer_id = self.upcast::<XRLayer>().layer_id()?;
+        *self.sub_images.borrow_mut() = frame.get_sub_images(layer_id).cloned();
+        Some(())
+    }
+
+    pub fn end_frame(&self, _frame: &XRFrame) -> Option<()> {
+        debug!("XRProjectionLayer end frame");
+        self.sub_images.borrow_mut().take()?;
+        self.composition_layer.context().Flush();
+        Some(())
+    }
+
+    /// The sub image of this layer the content should render `view` into,
+    /// if the runtime has handed out the layer's textures for this frame.
+    pub(crate) fn view_sub_image(&self, view: &XRView) -> Option<DomRoot<XRWebGLSubImage>> {
+        let sub_images = self.sub_images.borrow();
+        let sub_image = sub_images
+            .as_ref()?
+            .view_sub_images
+            .get(view.viewport_index())?;
+        let context = self.composition_layer.context();
+        let session = self.session();
+
+        // TODO: Cache these textures
+        let color_texture_id = WebGLTextureId::maybe_new(sub_image.color_texture)?;
+        let color_texture = WebGLTexture::new_webxr(context, color_texture_id, session);
+        let depth_stencil_texture = match sub_image.depth_stencil_texture {
+            Some(id) => Some(WebGLTexture::new_webxr(
+                context,
+                WebGLTextureId::maybe_new(id)?,
+                session,
+            )),
+            None => None,
+        };
+        let size = session
+            .with_session(|session| session.recommended_framebuffer_resolution())
+            .unwrap_or(sub_image.viewport.size)
+            .to_u32();
+
+        Some(XRWebGLSubImage::new(
+            &self.global(),
+            sub_image.viewport,
+            &color_texture,
+            depth_stencil_texture.as_deref(),
+            sub_image.texture_array_index,
+            size,
+        ))
+    }
+}
+
+impl XRProjectionLayerMethods for XRProjectionLayer {
+    /// <https://immersive-web.github.io/layers/#dom-xrprojectionlayer-ignoredepthvalues>
+    fn IgnoreDepthValues(&self) -> bool {
+        self.ignore_depth_values
+    }
 }
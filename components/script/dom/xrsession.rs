@@ -13,10 +13,11 @@ use ipc_channel::ipc::IpcReceiver;
 use ipc_channel::router::ROUTER;
 use metrics::ToMs;
 use profile_traits::ipc;
+use servo_config::pref;
 use webxr_api::{
     self, util, ApiSpace, ContextId as WebXRContextId, Display, EntityTypes, EnvironmentBlendMode,
-    Event as XREvent, Frame, FrameUpdateEvent, HitTestId, HitTestSource, Ray, SelectEvent,
-    SelectKind, Session, SessionId, View, Viewer, Visibility,
+    Event as XREvent, FingerJoint, Frame, FrameUpdateEvent, HitTestId, HitTestSource, InputId,
+    Joint, Ray, SelectEvent, SelectKind, Session, SessionId, View, Viewer, Visibility,
 };
 
 use super::bindings::trace::HashMapTracedValues;
@@ -45,8 +46,11 @@ use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::promise::Promise;
+use crate::dom::xranchor::XRAnchor;
+use crate::dom::xranchorset::XRAnchorSet;
 use crate::dom::xrframe::XRFrame;
 use crate::dom::xrhittestsource::XRHitTestSource;
+use crate::dom::xrinputsource::XRInputSource;
 use crate::dom::xrinputsourcearray::XRInputSourceArray;
 use crate::dom::xrinputsourceevent::XRInputSourceEvent;
 use crate::dom::xrreferencespace::XRReferenceSpace;
@@ -89,9 +93,16 @@ pub struct XRSession {
     next_hit_test_id: Cell<HitTestId>,
     #[ignore_malloc_size_of = "defined in webxr"]
     pending_hit_test_promises: DomRefCell<HashMapTracedValues<HitTestId, Rc<Promise>>>,
+    /// <https://immersive-web.github.io/anchors/#xrsession-set-of-tracked-anchors>
+    tracked_anchors: MutNullableDom<XRAnchorSet>,
     /// Opaque framebuffers need to know the session is "outside of a requestAnimationFrame"
     /// <https://immersive-web.github.io/webxr/#opaque-framebuffer>
     outside_raf: Cell<bool>,
+    /// Hand input sources whose thumb and index finger are currently pinched together,
+    /// when pinches are emulating the primary action.
+    #[ignore_malloc_size_of = "defined in webxr"]
+    #[no_trace]
+    pinching_inputs: DomRefCell<Vec<InputId>>,
 }
 
 impl XRSession {
@@ -121,7 +132,9 @@ impl XRSession {
             ended: Cell::new(false),
             next_hit_test_id: Cell::new(HitTestId(0)),
             pending_hit_test_promises: DomRefCell::new(HashMapTracedValues::new()),
+            tracked_anchors: Default::default(),
             outside_raf: Cell::new(true),
+            pinching_inputs: DomRefCell::new(vec![]),
         }
     }
 
@@ -167,9 +180,45 @@ impl XRSession {
 
     // https://immersive-web.github.io/layers/#feature-descriptor-layers
     pub fn has_layers_feature(&self) -> bool {
-        // We do not support creating layers other than projection layers
-        // https://github.com/servo/servo/issues/27493
-        false
+        self.session
+            .borrow()
+            .granted_features()
+            .iter()
+            .any(|f| f == "layers")
+    }
+
+    pub fn tracked_anchors(&self) -> DomRoot<XRAnchorSet> {
+        self.tracked_anchors
+            .or_init(|| XRAnchorSet::new(&self.global()))
+    }
+
+    /// <https://immersive-web.github.io/anchors/#create-new-anchor-object>
+    ///
+    /// The anchor is kept at `pose`, in native space, for as long as the session
+    /// tracks it.
+    pub fn create_anchor(&self, pose: ApiPose) -> Rc<Promise> {
+        let p = Promise::new(&self.global());
+
+        if self
+            .session
+            .borrow()
+            .granted_features()
+            .iter()
+            .find(|f| &**f == "anchors")
+            .is_none()
+        {
+            p.reject_error(Error::NotSupported);
+            return p;
+        }
+        if self.ended.get() {
+            p.reject_error(Error::InvalidState);
+            return p;
+        }
+
+        let anchor = XRAnchor::new(&self.global(), self, pose);
+        self.tracked_anchors().insert(&anchor);
+        p.resolve_native(&anchor);
+        p
     }
 
     fn setup_raf_loop(&self, frame_receiver: IpcReceiver<Frame>) {
@@ -274,6 +323,7 @@ impl XRSession {
                 self.ended.set(true);
                 // Step 3-4
                 self.global().as_window().Navigator().Xr().end_session(self);
+                self.tracked_anchors().delete_all();
                 // Step 5: We currently do not have any such promises
                 // Step 6 is happening n the XR session
                 // https://immersive-web.github.io/webxr/#dom-xrsession-end step 3
@@ -285,49 +335,12 @@ impl XRSession {
                 event.upcast::<Event>().fire(self.upcast());
             },
             XREvent::Select(input, kind, ty, frame) => {
-                use servo_atoms::Atom;
-                const START_ATOMS: [Atom; 2] = [atom!("selectstart"), atom!("squeezestart")];
-                const EVENT_ATOMS: [Atom; 2] = [atom!("select"), atom!("squeeze")];
-                const END_ATOMS: [Atom; 2] = [atom!("selectend"), atom!("squeezeend")];
-
                 // https://immersive-web.github.io/webxr/#primary-action
                 let source = self.input_sources.find(input);
-                let atom_index = if kind == SelectKind::Squeeze { 1 } else { 0 };
                 if let Some(source) = source {
                     let frame = XRFrame::new(&self.global(), self, frame);
                     frame.set_active(true);
-                    if ty == SelectEvent::Start {
-                        let event = XRInputSourceEvent::new(
-                            &self.global(),
-                            START_ATOMS[atom_index].clone(),
-                            false,
-                            false,
-                            &frame,
-                            &source,
-                        );
-                        event.upcast::<Event>().fire(self.upcast());
-                    } else {
-                        if ty == SelectEvent::Select {
-                            let event = XRInputSourceEvent::new(
-                                &self.global(),
-                                EVENT_ATOMS[atom_index].clone(),
-                                false,
-                                false,
-                                &frame,
-                                &source,
-                            );
-                            event.upcast::<Event>().fire(self.upcast());
-                        }
-                        let event = XRInputSourceEvent::new(
-                            &self.global(),
-                            END_ATOMS[atom_index].clone(),
-                            false,
-                            false,
-                            &frame,
-                            &source,
-                        );
-                        event.upcast::<Event>().fire(self.upcast());
-                    }
+                    self.fire_select_events(&source, kind, ty, &frame);
                     frame.set_active(false);
                 }
             },
@@ -533,9 +546,106 @@ impl XRSession {
         });
     }
 
+    fn fire_select_events(
+        &self,
+        source: &XRInputSource,
+        kind: SelectKind,
+        ty: SelectEvent,
+        frame: &XRFrame,
+    ) {
+        use servo_atoms::Atom;
+        const START_ATOMS: [Atom; 2] = [atom!("selectstart"), atom!("squeezestart")];
+        const EVENT_ATOMS: [Atom; 2] = [atom!("select"), atom!("squeeze")];
+        const END_ATOMS: [Atom; 2] = [atom!("selectend"), atom!("squeezeend")];
+
+        let atom_index = if kind == SelectKind::Squeeze { 1 } else { 0 };
+        if ty == SelectEvent::Start {
+            let event = XRInputSourceEvent::new(
+                &self.global(),
+                START_ATOMS[atom_index].clone(),
+                false,
+                false,
+                frame,
+                source,
+            );
+            event.upcast::<Event>().fire(self.upcast());
+        } else {
+            if ty == SelectEvent::Select {
+                let event = XRInputSourceEvent::new(
+                    &self.global(),
+                    EVENT_ATOMS[atom_index].clone(),
+                    false,
+                    false,
+                    frame,
+                    source,
+                );
+                event.upcast::<Event>().fire(self.upcast());
+            }
+            let event = XRInputSourceEvent::new(
+                &self.global(),
+                END_ATOMS[atom_index].clone(),
+                false,
+                false,
+                frame,
+                source,
+            );
+            event.upcast::<Event>().fire(self.upcast());
+        }
+    }
+
     /// <https://immersive-web.github.io/webxr/#xrframe-apply-frame-updates>
-    fn apply_frame_updates(&self, _frame: &XRFrame) {
-        // TODO: add a comment about why this is empty right now!
+    fn apply_frame_updates(&self, frame: &XRFrame) {
+        // Input sources and their poses are already up to date in the frame data;
+        // the only update left is turning pinches into primary actions.
+        if pref!(dom.webxr.hands.pinch_select) {
+            self.emulate_pinch_select(frame);
+        }
+    }
+
+    /// Some runtimes track hands without reporting a primary action for them. Treat
+    /// touching the tips of the thumb and the index finger as one, with some
+    /// hysteresis so that tracking jitter does not end the pinch early.
+    /// <https://immersive-web.github.io/webxr-hand-input/#primary-action>
+    fn emulate_pinch_select(&self, frame: &XRFrame) {
+        // In meters, between the centers of the two finger tips.
+        const PINCH_START_DISTANCE: f32 = 0.015;
+        const PINCH_END_DISTANCE: f32 = 0.03;
+
+        let inputs = frame.inputs();
+        self.pinching_inputs
+            .borrow_mut()
+            .retain(|id| inputs.iter().any(|input| input.id == *id));
+
+        for input in inputs {
+            let hand = match input.hand {
+                Some(ref hand) => hand,
+                None => continue,
+            };
+            let distance = match (
+                hand.get(Joint::ThumbPhalanxTip),
+                hand.get(Joint::Index(FingerJoint::PhalanxTip)),
+            ) {
+                (Some(thumb), Some(index)) => {
+                    (thumb.pose.translation - index.pose.translation).length()
+                },
+                _ => continue,
+            };
+            let was_pinching = self.pinching_inputs.borrow().contains(&input.id);
+            let ty = if !was_pinching && distance < PINCH_START_DISTANCE {
+                self.pinching_inputs.borrow_mut().push(input.id);
+                SelectEvent::Start
+            } else if was_pinching && distance > PINCH_END_DISTANCE {
+                self.pinching_inputs
+                    .borrow_mut()
+                    .retain(|id| *id != input.id);
+                SelectEvent::Select
+            } else {
+                continue;
+            };
+            if let Some(source) = self.input_sources.find(input.id) {
+                self.fire_select_events(&source, SelectKind::Select, ty, frame);
+            }
+        }
     }
 
     fn handle_frame_event(&self, event: FrameUpdateEvent) {
@@ -825,6 +935,7 @@ impl XRSessionMethods for XRSession {
         // shuts itself down
         self.ended.set(true);
         global.as_window().Navigator().Xr().end_session(self);
+        self.tracked_anchors().delete_all();
         self.session.borrow_mut().end_session();
         p
     }
@@ -23,6 +23,10 @@ pub struct XRSpace {
     input_source: MutNullableDom<XRInputSource>,
     /// If we're an input space, are we an aim space or a grip space?
     is_grip_space: bool,
+    /// If we're an anchor space, the pose of the anchor in native space.
+    #[ignore_malloc_size_of = "defined in euclid"]
+    #[no_trace]
+    anchor_pose: Option<ApiPose>,
 }
 
 impl XRSpace {
@@ -32,6 +36,7 @@ impl XRSpace {
             session: Dom::from_ref(session),
             input_source: Default::default(),
             is_grip_space: false,
+            anchor_pose: None,
         }
     }
 
@@ -45,6 +50,7 @@ impl XRSpace {
             session: Dom::from_ref(session),
             input_source: MutNullableDom::new(Some(input)),
             is_grip_space,
+            anchor_pose: None,
         }
     }
 
@@ -60,11 +66,34 @@ impl XRSpace {
         )
     }
 
+    pub fn new_anchorspace(
+        global: &GlobalScope,
+        session: &XRSession,
+        pose: ApiPose,
+    ) -> DomRoot<XRSpace> {
+        reflect_dom_object(
+            Box::new(XRSpace {
+                eventtarget: EventTarget::new_inherited(),
+                session: Dom::from_ref(session),
+                input_source: Default::default(),
+                is_grip_space: false,
+                anchor_pose: Some(pose),
+            }),
+            global,
+        )
+    }
+
     pub fn space(&self) -> Space {
         if let Some(rs) = self.downcast::<XRReferenceSpace>() {
             rs.space()
         } else if let Some(j) = self.downcast::<XRJointSpace>() {
             j.space()
+        } else if let Some(pose) = self.anchor_pose {
+            // The local space's origin is the origin of native space
+            Space {
+                base: BaseSpace::Local,
+                offset: cast_transform(pose),
+            }
         } else if let Some(source) = self.input_source.get() {
             let base = if self.is_grip_space {
                 BaseSpace::Grip(source.id())
@@ -92,6 +121,9 @@ impl XRSpace {
             reference.get_pose(base_pose)
         } else if let Some(joint) = self.downcast::<XRJointSpace>() {
             joint.get_pose(base_pose)
+        } else if let Some(pose) = self.anchor_pose {
+            // Anchors are stationary in native space
+            Some(pose)
         } else if let Some(source) = self.input_source.get() {
             // XXXManishearth we should be able to request frame information
             // for inputs when necessary instead of always loading it
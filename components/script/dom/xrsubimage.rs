@@ -15,6 +15,15 @@ pub struct XRSubImage {
     viewport: Dom<XRViewport>,
 }
 
+impl XRSubImage {
+    pub fn new_inherited(viewport: &XRViewport) -> XRSubImage {
+        XRSubImage {
+            reflector: Reflector::new(),
+            viewport: Dom::from_ref(viewport),
+        }
+    }
+}
+
 impl XRSubImageMethods for XRSubImage {
     /// <https://immersive-web.github.io/layers/#dom-xrsubimage-viewport>
     fn Viewport(&self) -> DomRoot<XRViewport> {
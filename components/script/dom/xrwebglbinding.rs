@@ -4,16 +4,20 @@
 
 use dom_struct::dom_struct;
 use js::rust::HandleObject;
+use webxr_api::{ContextId as WebXRContextId, LayerInit};
 
+use crate::dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
+use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
 use crate::dom::bindings::codegen::Bindings::XRViewBinding::XREye;
 use crate::dom::bindings::codegen::Bindings::XRWebGLBindingBinding::XRWebGLBinding_Binding::XRWebGLBindingMethods;
 use crate::dom::bindings::codegen::Bindings::XRWebGLBindingBinding::{
-    XRCubeLayerInit, XRCylinderLayerInit, XREquirectLayerInit, XRProjectionLayerInit,
-    XRQuadLayerInit, XRTextureType,
+    XRCubeLayerInit, XRCylinderLayerInit, XREquirectLayerInit, XRLayerLayout,
+    XRProjectionLayerInit, XRQuadLayerInit, XRTextureType,
 };
 use crate::dom::bindings::codegen::UnionTypes::WebGLRenderingContextOrWebGL2RenderingContext;
 use crate::dom::bindings::error::{Error, Fallible};
-use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, Reflector};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::window::Window;
@@ -78,11 +82,59 @@ impl XRWebGLBindingMethods for XRWebGLBinding {
     /// <https://immersive-web.github.io/layers/#dom-xrwebglbinding-createprojectionlayer>
     fn CreateProjectionLayer(
         &self,
-        _: XRTextureType,
-        _: &XRProjectionLayerInit,
+        texture_type: XRTextureType,
+        init: &XRProjectionLayerInit,
     ) -> Fallible<DomRoot<XRProjectionLayer>> {
-        // https://github.com/servo/servo/issues/27468
-        Err(Error::NotSupported)
+        // Step 1.
+        if self.session.is_ended() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2. Projection layers are only composited by immersive sessions.
+        if !self.session.is_immersive() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3. The runtime only hands out 2D textures, shared by all views.
+        if texture_type == XRTextureType::Texture_array {
+            return Err(Error::NotSupported);
+        }
+
+        // "Allocate and initialize resources compatible with session’s XR device,
+        // including GPU accessible memory buffers, as required to support the
+        // compositing of layer." The layer's textures are handed to the XR
+        // runtime's compositor directly, rather than blitted from a framebuffer.
+        let context_id = WebXRContextId::from(self.context.context_id());
+        let layer_init = LayerInit::ProjectionLayer {
+            depth: init.depth,
+            stencil: init.stencil,
+            alpha: init.alpha,
+            scale_factor: *init.scaleFactor as f32,
+        };
+        let layer_id = self
+            .session
+            .with_session(|session| session.create_layer(context_id, layer_init))
+            .map_err(|_| Error::Operation)?;
+
+        // The views are laid out side by side in the shared texture.
+        let layout = if self
+            .session
+            .with_session(|session| session.viewports().len()) >
+            1
+        {
+            XRLayerLayout::Stereo_left_right
+        } else {
+            XRLayerLayout::Mono
+        };
+
+        // Ensure that we finish setting up this layer before continuing.
+        self.context.Finish();
+
+        Ok(XRProjectionLayer::new(
+            &self.global(),
+            &self.session,
+            &self.context,
+            layer_id,
+            layout,
+        ))
     }
 
     /// <https://immersive-web.github.io/layers/#dom-xrwebglbinding-createquadlayer>
@@ -91,6 +143,7 @@ impl XRWebGLBindingMethods for XRWebGLBinding {
         _: XRTextureType,
         _: &Option<XRQuadLayerInit>,
     ) -> Fallible<DomRoot<XRQuadLayer>> {
+        // The XR runtimes can only composite projection layers.
         // https://github.com/servo/servo/issues/27493
         Err(Error::NotSupported)
     }
@@ -101,6 +154,7 @@ impl XRWebGLBindingMethods for XRWebGLBinding {
         _: XRTextureType,
         _: &Option<XRCylinderLayerInit>,
     ) -> Fallible<DomRoot<XRCylinderLayer>> {
+        // The XR runtimes can only composite projection layers.
         // https://github.com/servo/servo/issues/27493
         Err(Error::NotSupported)
     }
@@ -124,21 +178,33 @@ impl XRWebGLBindingMethods for XRWebGLBinding {
     /// <https://immersive-web.github.io/layers/#dom-xrwebglbinding-getsubimage>
     fn GetSubImage(
         &self,
-        _: &XRCompositionLayer,
-        _: &XRFrame,
+        layer: &XRCompositionLayer,
+        frame: &XRFrame,
         _: XREye,
     ) -> Fallible<DomRoot<XRWebGLSubImage>> {
-        // https://github.com/servo/servo/issues/27468
+        if layer.session() != &*self.session || &*frame.Session() != &*self.session {
+            return Err(Error::InvalidState);
+        }
+        // Projection layers must use getViewSubImage instead.
+        if layer.is::<XRProjectionLayer>() {
+            return Err(Error::InvalidState);
+        }
+        // No other layer types can be created yet.
+        // https://github.com/servo/servo/issues/27493
         Err(Error::NotSupported)
     }
 
     /// <https://immersive-web.github.io/layers/#dom-xrwebglbinding-getviewsubimage>
     fn GetViewSubImage(
         &self,
-        _: &XRProjectionLayer,
-        _: &XRView,
+        layer: &XRProjectionLayer,
+        view: &XRView,
     ) -> Fallible<DomRoot<XRWebGLSubImage>> {
-        // https://github.com/servo/servo/issues/27468
-        Err(Error::NotSupported)
+        if layer.session() != &*self.session || view.session() != &*self.session {
+            return Err(Error::InvalidState);
+        }
+        // The layer only has sub images while an animation frame is running
+        // with the layer in the session's render state.
+        layer.view_sub_image(view).ok_or(Error::InvalidState)
     }
 }
@@ -244,6 +244,19 @@ impl XRWebGLLayer {
                 .ok()?;
         }
 
+        // https://immersive-web.github.io/webxr/#opaque-framebuffer
+        // The runtime may hand back a texture that still holds an earlier frame, so
+        // start from transparent black. In alpha-blend and additive sessions this is
+        // what lets the real world show through wherever the content doesn't draw.
+        let mut clear_bits = constants::COLOR_BUFFER_BIT;
+        if self.depth {
+            clear_bits |= constants::DEPTH_BUFFER_BIT;
+        }
+        if self.stencil {
+            clear_bits |= constants::STENCIL_BUFFER_BIT;
+        }
+        context.initialize_framebuffer(clear_bits);
+
         // Restore the old bindings
         context.send_command(WebGLCommand::BindTexture(target, saved_texture_id));
         if let Some(framebuffer_target) = saved_framebuffer_target {
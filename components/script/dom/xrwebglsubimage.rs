@@ -3,13 +3,16 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
-use euclid::Size2D;
+use euclid::{Rect, Size2D};
 use webxr_api::Viewport;
 
 use crate::dom::bindings::codegen::Bindings::XRWebGLSubImageBinding::XRWebGLSubImage_Binding::XRWebGLSubImageMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::webgltexture::WebGLTexture;
 use crate::dom::xrsubimage::XRSubImage;
+use crate::dom::xrviewport::XRViewport;
 
 #[dom_struct]
 pub struct XRWebGLSubImage {
@@ -21,6 +24,45 @@ pub struct XRWebGLSubImage {
     size: Size2D<u32, Viewport>,
 }
 
+impl XRWebGLSubImage {
+    fn new_inherited(
+        viewport: &XRViewport,
+        color_texture: &WebGLTexture,
+        depth_stencil_texture: Option<&WebGLTexture>,
+        image_index: Option<u32>,
+        size: Size2D<u32, Viewport>,
+    ) -> XRWebGLSubImage {
+        XRWebGLSubImage {
+            xr_sub_image: XRSubImage::new_inherited(viewport),
+            color_texture: Dom::from_ref(color_texture),
+            depth_stencil_texture: depth_stencil_texture.map(Dom::from_ref),
+            image_index,
+            size,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        viewport: Rect<i32, Viewport>,
+        color_texture: &WebGLTexture,
+        depth_stencil_texture: Option<&WebGLTexture>,
+        image_index: Option<u32>,
+        size: Size2D<u32, Viewport>,
+    ) -> DomRoot<XRWebGLSubImage> {
+        let viewport = XRViewport::new(global, viewport);
+        reflect_dom_object(
+            Box::new(XRWebGLSubImage::new_inherited(
+                &viewport,
+                color_texture,
+                depth_stencil_texture,
+                image_index,
+                size,
+            )),
+            global,
+        )
+    }
+}
+
 impl XRWebGLSubImageMethods for XRWebGLSubImage {
     /// <https://immersive-web.github.io/layers/#dom-xrwebglsubimage-colortexture>
     fn ColorTexture(&self) -> DomRoot<WebGLTexture> {
@@ -0,0 +1,214 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The favicons of top-level documents, which are fetched and decoded here so that the
+//! embedder can show them in its tabs. The favicon is picked again from the icons that
+//! `link` elements declare whenever they change, and `/favicon.ico` is used for HTTP(S)
+//! documents that are loaded without declaring one.
+//!
+//! <https://html.spec.whatwg.org/multipage/#rel-icon>
+
+use std::sync::{Arc, Mutex};
+
+use embedder_traits::{EmbedderMsg, Favicon};
+use euclid::Size2D;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::request::{Destination, RequestBuilder};
+use net_traits::{
+    FetchMetadata, FetchResponseListener, NetworkError, ResourceFetchTiming, ResourceTimingType,
+};
+use servo_url::ServoUrl;
+use style::str::HTML_SPACE_CHARACTERS;
+
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::Document;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmllinkelement::HTMLLinkElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+
+/// An icon that a `link` element declares.
+pub(crate) struct IconLink {
+    pub url: ServoUrl,
+    /// The largest width or height in its `sizes`, or 0 when it has none.
+    pub size: u32,
+}
+
+/// The largest width or height in the value of a `sizes` attribute, or 0 when it has
+/// none, like when it only has `any`.
+pub(crate) fn largest_size(sizes: &str) -> u32 {
+    sizes
+        .split(HTML_SPACE_CHARACTERS)
+        .filter_map(|size| {
+            let (width, height) = size.split_once(|c| c == 'x' || c == 'X')?;
+            Some(width.parse::<u32>().ok()?.max(height.parse().ok()?))
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Pick the favicon of a document again, after its icons changed or it was completely loaded,
+/// and fetch it if it isn't the one picked already.
+pub(crate) fn update_favicon(document: &Document) {
+    let window = document.window();
+    if !window.is_top_level() || document.browsing_context().is_none() {
+        return;
+    }
+
+    // The largest icon, and the last of the ones that are as large.
+    let icon = document
+        .upcast::<Node>()
+        .traverse_preorder(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLLinkElement>)
+        .filter_map(|link| link.icon())
+        .fold(None, |best: Option<IconLink>, icon| match best {
+            Some(best) if best.size > icon.size => Some(best),
+            _ => Some(icon),
+        });
+    let url = icon
+        .map(|icon| icon.url)
+        .or_else(|| default_favicon_url(document));
+
+    if *document.favicon_url().borrow() == url {
+        return;
+    }
+    *document.favicon_url().borrow_mut() = url.clone();
+    match url {
+        Some(url) => {
+            window.send_to_embedder(EmbedderMsg::NewFavicon(url.clone()));
+            fetch_favicon(document, url);
+        },
+        None => window.send_to_embedder(EmbedderMsg::FaviconChanged(None)),
+    }
+}
+
+/// The `/favicon.ico` of a document that is loaded from HTTP(S).
+fn default_favicon_url(document: &Document) -> Option<ServoUrl> {
+    if !document.completely_loaded() {
+        return None;
+    }
+    let url = document.url();
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.join("/favicon.ico").ok()
+}
+
+fn fetch_favicon(document: &Document, url: ServoUrl) {
+    let context = Arc::new(Mutex::new(FaviconContext {
+        document: Trusted::new(document),
+        url: url.clone(),
+        bytes: vec![],
+        resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+    }));
+
+    let (action_sender, action_receiver) = ipc::channel().unwrap();
+    let (task_source, canceller) = document
+        .window()
+        .task_manager()
+        .networking_task_source_with_canceller();
+    let listener = NetworkListener {
+        context,
+        task_source,
+        canceller: Some(canceller),
+    };
+    ROUTER.add_route(
+        action_receiver.to_opaque(),
+        Box::new(move |message| {
+            listener.notify_fetch(message.to().unwrap());
+        }),
+    );
+
+    let request = RequestBuilder::new(url, document.global().get_referrer())
+        .origin(document.origin().immutable().clone())
+        .destination(Destination::Image)
+        .pipeline_id(Some(document.global().pipeline_id()));
+
+    // Favicons do not delay the load event of the document.
+    document
+        .loader_mut()
+        .fetch_async_background(request, action_sender);
+}
+
+struct FaviconContext {
+    document: Trusted<Document>,
+    url: ServoUrl,
+    bytes: Vec<u8>,
+    resource_timing: ResourceFetchTiming,
+}
+
+impl FetchResponseListener for FaviconContext {
+    fn process_request_body(&mut self) {}
+    fn process_request_eof(&mut self) {}
+    fn process_response(&mut self, _metadata: Result<FetchMetadata, NetworkError>) {}
+
+    fn process_response_chunk(&mut self, mut payload: Vec<u8>) {
+        self.bytes.append(&mut payload);
+    }
+
+    fn process_response_eof(&mut self, response: Result<ResourceFetchTiming, NetworkError>) {
+        let document = self.document.root();
+        // Another icon was picked while this one was loading.
+        if document.favicon_url().borrow().as_ref() != Some(&self.url) {
+            return;
+        }
+        let favicon = match response {
+            Ok(_) => decode_favicon(&self.url, &self.bytes),
+            Err(error) => {
+                debug!("Fetching favicon {} failed ({:?}).", self.url, error);
+                None
+            },
+        };
+        document
+            .window()
+            .send_to_embedder(EmbedderMsg::FaviconChanged(favicon));
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for FaviconContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (
+            InitiatorType::LocalName("link".to_owned()),
+            self.url.clone(),
+        )
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.document.root().global()
+    }
+}
+
+impl PreInvoke for FaviconContext {}
+
+fn decode_favicon(url: &ServoUrl, bytes: &[u8]) -> Option<Favicon> {
+    let image = match image::load_from_memory(bytes) {
+        Ok(image) => image.into_rgba8(),
+        Err(error) => {
+            debug!("Decoding favicon {} failed ({:?}).", url, error);
+            return None;
+        },
+    };
+    Some(Favicon {
+        url: url.clone(),
+        size: Size2D::new(image.width() as i32, image.height() as i32),
+        pixels: image.into_raw(),
+    })
+}
@@ -0,0 +1,322 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Find in page, where the embedder searches the text of a webview. The constellation
+//! asks every document of the webview for its matches, ranks the documents in the order
+//! of the frame tree, then asks the document with the active match to select it.
+//!
+//! Matches are found within a run of text, so a query doesn't match text that is split
+//! between elements. Layout can't tell where the characters of a run are, so the compositor
+//! highlights the boxes of the runs of text that have matches over the documents, and the
+//! active match is selected as well. The documents send where their matches and iframes are
+//! again whenever they scroll, for the constellation to place the highlights in the webview.
+
+use app_units::Au;
+use embedder_traits::FindOptions;
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
+use msg::constellation_msg::BrowsingContextId;
+use script_traits::{FindHighlights, ScriptMsg};
+use webrender_api::units::DeviceIntRect;
+
+use crate::caret_browsing::{is_browsable, utf8_to_utf16};
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::ScrollBehavior;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::text::Text;
+
+/// A match of the last search in a run of text, with its offsets in UTF-16 code units
+/// like the offsets of the selection API.
+#[derive(JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+pub(crate) struct FindMatch {
+    text: Dom<Text>,
+    start: u32,
+    end: u32,
+}
+
+/// Find the matches of `query` in the runs of text of `document`, replacing those of the
+/// last search, and tell the constellation how many there are.
+pub(crate) fn find(document: &Document, query: &str, options: FindOptions) {
+    let query_chars: Vec<char> = query.chars().collect();
+    let mut document_matches = document.find_matches().borrow_mut();
+    let find_matches = document_matches.insert(vec![]);
+    if let Some(body) = document.GetBody().filter(|_| !query_chars.is_empty()) {
+        let runs = body
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Text>)
+            .filter(|text| is_browsable(text));
+        for text in runs {
+            let data = String::from(text.upcast::<CharacterData>().Data());
+            for (start, end) in find_in_run(&data, &query_chars, options.case_sensitive) {
+                find_matches.push(FindMatch {
+                    text: Dom::from_ref(&*text),
+                    start: utf8_to_utf16(&data, start),
+                    end: utf8_to_utf16(&data, end),
+                });
+            }
+        }
+    }
+    let count = find_matches.len();
+    drop(document_matches);
+    document.find_active_match().set(None);
+    document
+        .window()
+        .send_to_constellation(ScriptMsg::FindMatches(query.to_owned(), count));
+    send_highlights(document);
+}
+
+/// Forget the matches of the last search of `document`, keeping its active match selected.
+pub(crate) fn stop_finding(document: &Document) {
+    *document.find_matches().borrow_mut() = None;
+    document.find_active_match().set(None);
+}
+
+/// Select the match of the last search with this index and scroll to it, telling the
+/// constellation where it is, or clear the selection if there is no such match.
+pub(crate) fn activate_match(document: &Document, index: Option<usize>) {
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return,
+    };
+    document.find_active_match().set(index);
+    let active_match = index.and_then(|index| {
+        let find_matches = document.find_matches().borrow();
+        let find_match = find_matches.as_ref()?.get(index)?;
+        Some((
+            DomRoot::from_ref(&*find_match.text),
+            find_match.start,
+            find_match.end,
+        ))
+    });
+    let (text, start, end) = match active_match {
+        Some(active_match) => active_match,
+        None => {
+            selection.RemoveAllRanges();
+            return send_highlights(document);
+        },
+    };
+
+    let node = text.upcast::<Node>();
+    let _ = selection.Collapse(Some(node), start);
+    let _ = selection.Extend(node, end);
+    let rect = node.content_boxes().into_iter().next();
+    if let Some(rect) = rect {
+        scroll_into_view(document, node, rect);
+    }
+    let rect = rect.map(|rect| visible_rect(document, node, rect));
+    document
+        .window()
+        .send_to_constellation(ScriptMsg::ActiveFindMatch(rect));
+    send_highlights(document);
+}
+
+/// Scroll the iframe of `browsing_context_id` into view, so that the active match of the
+/// document in it, whose box in device pixels relative to the viewport of that document is
+/// `rect`, is visible, and tell the constellation where it is now.
+pub(crate) fn scroll_frame_into_view(
+    document: &Document,
+    browsing_context_id: BrowsingContextId,
+    rect: DeviceIntRect,
+) {
+    let iframe = document
+        .iter_iframes()
+        .find(|iframe| iframe.browsing_context_id() == Some(browsing_context_id));
+    let iframe = match iframe {
+        Some(iframe) => iframe,
+        None => return,
+    };
+    let node = iframe.upcast::<Node>();
+    let frame = match node.bounding_content_box() {
+        Some(frame) => frame,
+        None => {
+            return document
+                .window()
+                .send_to_constellation(ScriptMsg::ActiveFindMatch(None));
+        },
+    };
+    let ratio = document.window().device_pixel_ratio().get();
+    let rect = rect.to_f32().to_untyped().scale(1. / ratio, 1. / ratio);
+    let rect = Rect::new(
+        Point2D::new(
+            frame.origin.x + Au::from_f32_px(rect.origin.x),
+            frame.origin.y + Au::from_f32_px(rect.origin.y),
+        ),
+        Size2D::new(
+            Au::from_f32_px(rect.size.width),
+            Au::from_f32_px(rect.size.height),
+        ),
+    );
+    let rect = rect.intersection(&frame).unwrap_or(frame);
+    scroll_into_view(document, node, rect);
+    let rect = visible_rect(document, node, rect);
+    document
+        .window()
+        .send_to_constellation(ScriptMsg::ActiveFindMatch(Some(rect)));
+    send_highlights(document);
+}
+
+/// Tell the constellation where the matches of the last search of `document` are, and
+/// where its iframes are, unless the search was stopped.
+pub(crate) fn send_highlights(document: &Document) {
+    let active_match = document.find_active_match().get();
+    let mut highlights = FindHighlights::default();
+    {
+        let find_matches = document.find_matches().borrow();
+        let find_matches = match find_matches.as_ref() {
+            Some(find_matches) => find_matches,
+            None => return,
+        };
+        // The matches of a run are next to each other, and highlight the same boxes.
+        let mut runs: Vec<(&Text, bool)> = vec![];
+        for (index, find_match) in find_matches.iter().enumerate() {
+            let is_active = Some(index) == active_match;
+            match runs.last_mut() {
+                Some((text, active)) if *text == &*find_match.text => *active |= is_active,
+                _ => runs.push((&*find_match.text, is_active)),
+            }
+        }
+        for (text, is_active) in runs {
+            let rects = run_rects(document, text);
+            if is_active {
+                highlights.active_match = rects;
+            } else {
+                highlights.matches.extend(rects);
+            }
+        }
+    }
+    highlights.frames = document
+        .iter_iframes()
+        .filter_map(|iframe| {
+            let browsing_context_id = iframe.browsing_context_id()?;
+            let node = iframe.upcast::<Node>();
+            let rect = node.bounding_content_box()?;
+            Some((browsing_context_id, visible_rect(document, node, rect)))
+        })
+        .collect();
+    document
+        .window()
+        .send_to_constellation(ScriptMsg::FindHighlights(highlights));
+}
+
+/// The boxes of a run of text, in device pixels relative to the viewport.
+fn run_rects(document: &Document, text: &Text) -> Vec<DeviceIntRect> {
+    let node = text.upcast::<Node>();
+    node.content_boxes()
+        .into_iter()
+        .map(|rect| visible_rect(document, node, rect))
+        .collect()
+}
+
+/// Convert `rect`, a box of `node` from layout, to device pixels relative to the viewport,
+/// moving it by how far the scroll containers that `node` is in are scrolled, which layout
+/// doesn't account for.
+fn visible_rect(document: &Document, node: &Node, rect: Rect<Au>) -> DeviceIntRect {
+    let window = document.window();
+    let offset = node
+        .ancestors()
+        .map(|ancestor| window.scroll_offset_query(&ancestor))
+        .fold(Vector2D::zero(), |offset, scroll| {
+            offset + Vector2D::new(Au::from_f32_px(scroll.x), Au::from_f32_px(scroll.y))
+        });
+    document.viewport_device_rect(rect.translate(-offset))
+}
+
+/// Scroll the scroll containers that `node` is in, innermost first, and then the viewport
+/// of `document`, so that `rect`, a box of `node` from layout, is visible.
+fn scroll_into_view(document: &Document, node: &Node, rect: Rect<Au>) {
+    let window = document.window();
+    let body = document.GetBody();
+    let mut rect = rect;
+    for ancestor in node.ancestors() {
+        let element = match ancestor.downcast::<Element>() {
+            Some(element) => element,
+            None => continue,
+        };
+        // Those scroll with the viewport.
+        if *element.root_element() == *element ||
+            body.as_deref() == element.downcast::<HTMLElement>()
+        {
+            continue;
+        }
+        if !element.has_scrolling_box() || !element.has_overflow() {
+            continue;
+        }
+        let scrollport = match ancestor.bounding_content_box() {
+            Some(scrollport) => scrollport,
+            None => continue,
+        };
+        let scroll = window.scroll_offset_query(&ancestor);
+        let mut offset = Vector2D::new(Au::from_f32_px(scroll.x), Au::from_f32_px(scroll.y));
+        if let Some(origin) = scroll_position(scrollport.translate(offset), rect) {
+            offset = Vector2D::new(
+                (origin.x - scrollport.origin.x).max(Au(0)),
+                (origin.y - scrollport.origin.y).max(Au(0)),
+            );
+            element.scroll(
+                offset.x.to_f64_px(),
+                offset.y.to_f64_px(),
+                ScrollBehavior::Instant,
+            );
+        }
+        // The box as it is seen from outside of the scroll container.
+        let scrolled = rect.translate(-offset);
+        rect = scrolled.intersection(&scrollport).unwrap_or(scrolled);
+    }
+    if let Some(origin) = scroll_position(window.current_viewport(), rect) {
+        window.scroll(
+            origin.x.to_f64_px(),
+            origin.y.to_f64_px(),
+            ScrollBehavior::Instant,
+        );
+    }
+}
+
+/// Where to move `visible`, the part of a scrolled area that shows, so that `rect` is in the
+/// middle of it, unless all of it already shows.
+fn scroll_position(visible: Rect<Au>, rect: Rect<Au>) -> Option<Point2D<Au>> {
+    if visible.contains_rect(&rect) {
+        return None;
+    }
+    let x = if rect.min_x() >= visible.min_x() && rect.max_x() <= visible.max_x() {
+        visible.origin.x
+    } else {
+        rect.origin.x
+    };
+    let y = rect.origin.y + rect.size.height / 2 - visible.size.height / 2;
+    Some(Point2D::new(x, y))
+}
+
+/// The non-overlapping matches of `query` in `data`, as ranges of bytes.
+fn find_in_run(data: &str, query: &[char], case_sensitive: bool) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = data.char_indices().collect();
+    let mut matches = vec![];
+    let mut index = 0;
+    while index + query.len() <= chars.len() {
+        let is_match = chars[index..index + query.len()]
+            .iter()
+            .zip(query)
+            .all(|(&(_, a), &b)| {
+                a == b || (!case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+            });
+        if !is_match {
+            index += 1;
+            continue;
+        }
+        let end = chars
+            .get(index + query.len())
+            .map_or(data.len(), |&(end, _)| end);
+        matches.push((chars[index].0, end));
+        index += query.len();
+    }
+    matches
+}
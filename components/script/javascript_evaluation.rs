@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The scripts that the embedder runs in documents with `Servo::evaluate_javascript`. A
+//! script runs like a classic script of the document, and its completion value is sent
+//! back as JSON, serialized like `JSON.stringify` does. When the value is a promise, the
+//! value it is fulfilled with is sent once it settles instead.
+//!
+//! The exceptions of these scripts, and the reasons their promises are rejected with, are
+//! sent back to the embedder rather than reported to the document.
+
+use std::slice;
+
+use embedder_traits::{JavaScriptEvaluationError, JavaScriptException};
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::{Compile1, JS_ClearPendingException};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::{IsPromiseObject, JS_ExecuteScript, JS_GetPendingException, JS_Stringify};
+use js::rust::{transform_str_to_source_text, CompileOptionsWrapper, HandleObject, HandleValue};
+
+use crate::dom::bindings::error::ErrorInfo;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::settings_stack::AutoEntryScript;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::window::Window;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+type ResultSender = IpcSender<Result<String, JavaScriptEvaluationError>>;
+
+/// Run a script of the embedder in the global of `window`, and send its result with `sender`.
+#[allow(unsafe_code)]
+pub(crate) fn evaluate_javascript(window: &Window, source: &str, sender: ResultSender) {
+    let global = window.upcast::<GlobalScope>();
+    let cx = GlobalScope::get_cx();
+    let realm = enter_realm(global);
+    let _aes = AutoEntryScript::new(global);
+
+    rooted!(in(*cx) let mut rval = UndefinedValue());
+    unsafe {
+        let options = CompileOptionsWrapper::new(*cx, global.get_url().as_str(), 1);
+        rooted!(in(*cx) let script = Compile1(
+            *cx,
+            options.ptr,
+            &mut transform_str_to_source_text(source),
+        ));
+        if script.is_null() || !JS_ExecuteScript(*cx, script.handle(), rval.handle_mut()) {
+            let _ = sender.send(Err(take_pending_exception(cx)));
+            return;
+        }
+
+        if rval.is_object() {
+            rooted!(in(*cx) let object = rval.to_object());
+            if IsPromiseObject(object.handle()) {
+                let promise = Promise::new_with_js_promise(object.handle(), cx);
+                let handler = PromiseNativeHandler::new(
+                    global,
+                    Some(Box::new(EvaluationPromiseHandler {
+                        sender: sender.clone(),
+                        rejected: false,
+                    })),
+                    Some(Box::new(EvaluationPromiseHandler {
+                        sender,
+                        rejected: true,
+                    })),
+                );
+                promise.append_native_handler(&handler, InRealm::Entered(&realm));
+                return;
+            }
+        }
+    }
+    let _ = sender.send(serialize(cx, rval.handle()));
+}
+
+/// Sends the result of a script once the promise it returned settles.
+#[derive(JSTraceable, MallocSizeOf)]
+struct EvaluationPromiseHandler {
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    sender: ResultSender,
+    /// Whether this handles the rejection of the promise rather than its fulfillment.
+    rejected: bool,
+}
+
+impl Callback for EvaluationPromiseHandler {
+    fn callback(&self, cx: JSContext, value: HandleValue, _realm: InRealm) {
+        let result = if self.rejected {
+            Err(exception_from_value(cx, value))
+        } else {
+            serialize(cx, value)
+        };
+        let _ = self.sender.send(result);
+    }
+}
+
+/// Serialize a value to JSON. The values that `JSON.stringify` doesn't serialize, like
+/// `undefined` and functions, are `null`.
+#[allow(unsafe_code)]
+fn serialize(cx: JSContext, value: HandleValue) -> Result<String, JavaScriptEvaluationError> {
+    unsafe extern "C" fn write(string: *const u16, length: u32, data: *mut libc::c_void) -> bool {
+        let json = &mut *(data as *mut Vec<u16>);
+        json.extend_from_slice(slice::from_raw_parts(string, length as usize));
+        true
+    }
+
+    let mut json: Vec<u16> = vec![];
+    rooted!(in(*cx) let mut value = value.get());
+    rooted!(in(*cx) let space = UndefinedValue());
+    let serialized = unsafe {
+        JS_Stringify(
+            *cx,
+            value.handle_mut(),
+            HandleObject::null(),
+            space.handle(),
+            Some(write),
+            &mut json as *mut Vec<u16> as *mut libc::c_void,
+        )
+    };
+    if !serialized {
+        // Like the exception of a cycle or a BigInt.
+        unsafe { JS_ClearPendingException(*cx) };
+        return Err(JavaScriptEvaluationError::SerializationFailed);
+    }
+    if json.is_empty() {
+        return Ok("null".to_owned());
+    }
+    Ok(String::from_utf16_lossy(&json))
+}
+
+#[allow(unsafe_code)]
+unsafe fn take_pending_exception(cx: JSContext) -> JavaScriptEvaluationError {
+    rooted!(in(*cx) let mut exception = UndefinedValue());
+    let got_exception = JS_GetPendingException(*cx, exception.handle_mut());
+    JS_ClearPendingException(*cx);
+    if !got_exception {
+        // The script was terminated, like a slow script can be.
+        return JavaScriptEvaluationError::Exception(JavaScriptException {
+            message: "The script was terminated".to_owned(),
+            filename: String::new(),
+            line_number: 0,
+            column: 0,
+        });
+    }
+    exception_from_value(cx, exception.handle())
+}
+
+#[allow(unsafe_code)]
+fn exception_from_value(cx: JSContext, value: HandleValue) -> JavaScriptEvaluationError {
+    let info = unsafe { ErrorInfo::from_value(value, *cx) };
+    JavaScriptEvaluationError::Exception(JavaScriptException {
+        message: info.message,
+        filename: info.filename,
+        line_number: info.lineno,
+        column: info.column,
+    })
+}
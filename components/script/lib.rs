@@ -40,14 +40,22 @@ pub mod document_loader;
 mod dom;
 #[warn(deprecated)]
 mod canvas_state;
+#[warn(deprecated)]
+mod caret_browsing;
 mod euclidext;
 #[warn(deprecated)]
 pub mod fetch;
 #[warn(deprecated)]
+mod favicon;
+#[warn(deprecated)]
+mod find_in_page;
+#[warn(deprecated)]
 mod image_listener;
 #[warn(deprecated)]
 mod init;
 #[warn(deprecated)]
+mod javascript_evaluation;
+#[warn(deprecated)]
 mod layout_image;
 
 pub mod layout_dom;
@@ -58,6 +66,8 @@ mod microtask;
 #[warn(deprecated)]
 mod network_listener;
 #[warn(deprecated)]
+mod print;
+#[warn(deprecated)]
 mod realms;
 #[warn(deprecated)]
 mod script_module;
@@ -69,6 +79,8 @@ pub mod script_thread;
 #[warn(deprecated)]
 pub mod serviceworker_manager;
 #[warn(deprecated)]
+pub mod spellcheck;
+#[warn(deprecated)]
 mod stylesheet_loader;
 #[warn(deprecated)]
 mod stylesheet_set;
@@ -8,6 +8,9 @@ use std::os::raw::c_void;
 
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 
+use crate::dom::bindings::reflector::DomObject;
+use crate::script_runtime::get_size;
+
 /// Used by codegen to include the pointer to the `MallocSizeOf` implementation of each
 /// IDL interface. This way we don't have to find the most-derived interface of DOM
 /// objects by hand in code.
@@ -18,3 +21,10 @@ pub unsafe fn malloc_size_of_including_raw_self<T: MallocSizeOf>(
 ) -> usize {
     ops.malloc_size_of(obj) + (*(obj as *const T)).size_of(ops)
 }
+
+/// The size of `object` and of what it owns, as measured by the `MallocSizeOf`
+/// implementation of its most-derived interface.
+#[allow(unsafe_code)]
+pub fn malloc_size_of_dom_object<T: DomObject>(object: &T) -> usize {
+    unsafe { get_size(object.reflector().get_jsobject().get()) }
+}
@@ -0,0 +1,371 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Printing a document to a PDF file. The document is laid out again with print media,
+//! with a viewport as wide as the content box of a page and as tall as all of the pages,
+//! then the compositor paints that layout on its own and the pages are cut out of it.
+//! Each page of the PDF file is an image, with the header and footer written as text.
+//!
+//! Layout can't fragment boxes across pages, so a page may end in the middle of a line
+//! of text, and the `break-*` properties are ignored. Documents too tall for the
+//! compositor to paint at once are cut off after the last page that fits.
+
+use std::io::Write;
+
+use embedder_traits::{PrintError, PrintOrientation, PrintSettings, Screenshot};
+use euclid::Size2D;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use ipc_channel::ipc;
+use script_layout_interface::message::{PrintLayout, ReflowGoal};
+use script_traits::{ScriptMsg, WindowSizeData};
+use style_traits::CSSPixel;
+use webrender_api::units::DeviceIntSize;
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
+use crate::dom::document::Document;
+use crate::dom::window::{ReflowReason, Window};
+
+const CSS_PIXELS_PER_CM: f64 = 96. / 2.54;
+const POINTS_PER_CM: f64 = 72. / 2.54;
+
+/// The height of the tallest layout that is painted for printing, in device pixels,
+/// which is the smallest maximum size of textures that GPUs commonly support.
+const MAX_DEVICE_HEIGHT: f32 = 16384.;
+
+/// The size of the text of headers and footers, in points.
+const FONT_SIZE: f64 = 9.;
+
+/// Print `document` with `settings`, returning the bytes of the PDF file.
+pub(crate) fn print_to_pdf(
+    document: &Document,
+    settings: &PrintSettings,
+) -> Result<Vec<u8>, PrintError> {
+    let (page_width, page_height) = match settings.orientation {
+        PrintOrientation::Portrait => (settings.page_width, settings.page_height),
+        PrintOrientation::Landscape => (settings.page_height, settings.page_width),
+    };
+    let margins = settings.margins;
+    let content_width = page_width - margins.left - margins.right;
+    let content_height = page_height - margins.top - margins.bottom;
+    if content_width <= 0. || content_height <= 0. || !(0.1..=2.).contains(&settings.scale) {
+        return Err(PrintError::InvalidSettings);
+    }
+
+    let window = document.window();
+    let window_size = window.window_size();
+    let scroll_position = (window.ScrollX(), window.ScrollY());
+    window.scroll(0., 0., ScrollBehavior::Instant);
+    window.set_print_layout(Some(PrintLayout {
+        backgrounds: settings.background,
+    }));
+
+    // The content box of a page in CSS pixels, at a scale.
+    let page_box = |scale: f64| {
+        Size2D::<f32, CSSPixel>::new(
+            (content_width * CSS_PIXELS_PER_CM / scale) as f32,
+            (content_height * CSS_PIXELS_PER_CM / scale) as f32,
+        )
+    };
+    let mut scale = settings.scale;
+    let mut page = page_box(scale);
+    lay_out(window, window_size, page);
+    let mut scrolling_area = window.scrolling_area_query(None).size;
+    if settings.shrink_to_fit && scrolling_area.width as f32 > page.width {
+        scale = (scale * page.width as f64 / scrolling_area.width as f64).max(0.1);
+        page = page_box(scale);
+        lay_out(window, window_size, page);
+        scrolling_area = window.scrolling_area_query(None).size;
+    }
+
+    let device_pixel_ratio = window_size.device_pixel_ratio.get();
+    let max_page_count = (MAX_DEVICE_HEIGHT / (page.height * device_pixel_ratio)).floor();
+    let page_count = (scrolling_area.height as f32 / page.height)
+        .ceil()
+        .clamp(1., max_page_count.max(1.)) as usize;
+    let layout_size = Size2D::new(page.width, page.height * page_count as f32);
+    lay_out(window, window_size, layout_size);
+    let screenshot = capture(
+        window,
+        (layout_size * window_size.device_pixel_ratio)
+            .round()
+            .to_i32(),
+    );
+
+    window.set_print_layout(None);
+    window.set_window_size(window_size);
+    window.force_reflow(ReflowGoal::Full, ReflowReason::Print, None);
+    window.scroll(
+        scroll_position.0 as f64,
+        scroll_position.1 as f64,
+        ScrollBehavior::Instant,
+    );
+
+    let screenshot = screenshot?;
+    let page_height_in_pixels = (page.height * device_pixel_ratio).round() as u32;
+    let title = String::from(document.Title());
+    let url = document.url();
+    let mut pdf = PdfWriter::new(page_count, &title);
+    for index in 0..page_count {
+        let top = index as u32 * page_height_in_pixels;
+        let rows = page_height_in_pixels.min(screenshot.height.saturating_sub(top));
+        if rows == 0 {
+            break;
+        }
+        let image = page_image(&screenshot, top, rows)?;
+        let text = |template: &Option<String>| {
+            template.as_ref().map(|template| {
+                template
+                    .replace("{title}", &title)
+                    .replace("{url}", url.as_str())
+                    .replace("{page}", &(index + 1).to_string())
+                    .replace("{pages}", &page_count.to_string())
+            })
+        };
+        pdf.page(PdfPage {
+            width: page_width * POINTS_PER_CM,
+            height: page_height * POINTS_PER_CM,
+            image_x: margins.left * POINTS_PER_CM,
+            image_top: margins.top * POINTS_PER_CM,
+            image_width: content_width * POINTS_PER_CM,
+            image_height: content_height * POINTS_PER_CM * rows as f64 /
+                page_height_in_pixels as f64,
+            image_size: (screenshot.width, rows),
+            image,
+            header: text(&settings.header),
+            footer: text(&settings.footer),
+            header_baseline: margins.top * POINTS_PER_CM / 2.,
+            footer_baseline: margins.bottom * POINTS_PER_CM / 2.,
+        });
+    }
+    Ok(pdf.finish())
+}
+
+/// Reflow the document for display with a viewport of `size`, keeping the device pixel
+/// ratio of the window.
+fn lay_out(window: &Window, window_size: WindowSizeData, size: Size2D<f32, CSSPixel>) {
+    window.set_window_size(WindowSizeData {
+        initial_viewport: size,
+        device_pixel_ratio: window_size.device_pixel_ratio,
+    });
+    window.force_reflow(ReflowGoal::Full, ReflowReason::Print, None);
+}
+
+/// Ask the compositor to paint the display list of the last reflow, with a viewport of
+/// `size` in device pixels.
+fn capture(window: &Window, size: DeviceIntSize) -> Result<Screenshot, PrintError> {
+    let epoch = window
+        .with_layout(|layout| layout.current_epoch())
+        .map_err(|_| PrintError::Failed)?;
+    let (sender, receiver) = ipc::channel().map_err(|_| PrintError::Failed)?;
+    window.send_to_constellation(ScriptMsg::CaptureForPrint(size, epoch, sender));
+    match receiver.recv() {
+        Ok(Ok(screenshot)) => Ok(screenshot),
+        _ => Err(PrintError::Failed),
+    }
+}
+
+/// The rows of the screenshot from `top` over white, encoded as the image data of a
+/// PDF file, which is the zlib stream of a PNG file.
+fn page_image(screenshot: &Screenshot, top: u32, rows: u32) -> Result<Vec<u8>, PrintError> {
+    let stride = screenshot.width as usize * 4;
+    let start = top as usize * stride;
+    let rgba = &screenshot.data[start..start + rows as usize * stride];
+    let rgb: Vec<u8> = rgba
+        .chunks_exact(4)
+        .flat_map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let over_white = move |channel: u8| {
+                ((channel as u32 * alpha + 255 * (255 - alpha) + 127) / 255) as u8
+            };
+            [
+                over_white(pixel[0]),
+                over_white(pixel[1]),
+                over_white(pixel[2]),
+            ]
+        })
+        .collect();
+
+    let mut png = vec![];
+    PngEncoder::new(&mut png)
+        .write_image(&rgb, screenshot.width, rows, ColorType::Rgb8)
+        .map_err(|_| PrintError::Failed)?;
+
+    // The data of the IDAT chunks, after the 8 bytes of the signature.
+    let mut data = vec![];
+    let mut chunks = &png[8..];
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+        if &chunks[4..8] == b"IDAT" {
+            data.extend_from_slice(&chunks[8..8 + length]);
+        }
+        chunks = &chunks[12 + length..];
+    }
+    Ok(data)
+}
+
+/// A page of a PDF file, with its lengths in points.
+struct PdfPage {
+    width: f64,
+    height: f64,
+    image_x: f64,
+    /// The distance from the top of the page to the top of the image.
+    image_top: f64,
+    image_width: f64,
+    image_height: f64,
+    /// The size of the image in pixels.
+    image_size: (u32, u32),
+    image: Vec<u8>,
+    header: Option<String>,
+    footer: Option<String>,
+    /// The distance from the top of the page to the baseline of the header.
+    header_baseline: f64,
+    /// The distance from the bottom of the page to the baseline of the footer.
+    footer_baseline: f64,
+}
+
+/// A writer of PDF files whose pages have an image and lines of text. The catalog, the
+/// page tree, the font and the document information come first, then the page, content
+/// stream and image of every page.
+struct PdfWriter {
+    data: Vec<u8>,
+    /// The offset of every object written so far, whose object numbers start at 1.
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn new(page_count: usize, title: &str) -> Self {
+        let mut writer = Self {
+            data: b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+            offsets: vec![],
+        };
+        writer.object(b"<< /Type /Catalog /Pages 2 0 R >>");
+        let kids: Vec<String> = (0..page_count)
+            .map(|index| format!("{} 0 R", 5 + index * 3))
+            .collect();
+        writer.object(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_count
+            )
+            .as_bytes(),
+        );
+        writer.object(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        let mut info = b"<< /Producer (Servo) /Title ".to_vec();
+        info.extend(pdf_string(title));
+        info.extend(b" >>");
+        writer.object(&info);
+        writer
+    }
+
+    fn object(&mut self, body: &[u8]) {
+        self.offsets.push(self.data.len());
+        let _ = writeln!(self.data, "{} 0 obj", self.offsets.len());
+        self.data.extend_from_slice(body);
+        self.data.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn stream(&mut self, dictionary: &str, data: &[u8]) {
+        let mut body =
+            format!("<< {} /Length {} >>\nstream\n", dictionary, data.len()).into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\nendstream");
+        self.object(&body);
+    }
+
+    fn page(&mut self, page: PdfPage) {
+        let number = self.offsets.len() + 1;
+        self.object(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                 /Resources << /Font << /F1 3 0 R >> /XObject << /Im1 {} 0 R >> >> \
+                 /Contents {} 0 R >>",
+                page.width,
+                page.height,
+                number + 2,
+                number + 1
+            )
+            .as_bytes(),
+        );
+
+        let mut contents = format!(
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im1 Do Q\n",
+            page.image_width,
+            page.image_height,
+            page.image_x,
+            page.height - page.image_top - page.image_height
+        )
+        .into_bytes();
+        let lines = [
+            (page.header, page.height - page.header_baseline),
+            (page.footer, page.footer_baseline),
+        ];
+        for (text, baseline) in lines {
+            let text = match text {
+                Some(text) => text,
+                None => continue,
+            };
+            let _ = write!(
+                contents,
+                "BT /F1 {} Tf {:.2} {:.2} Td ",
+                FONT_SIZE, page.image_x, baseline
+            );
+            contents.extend(pdf_string(&text));
+            contents.extend(b" Tj ET\n");
+        }
+        self.stream("", &contents);
+
+        let (width, height) = page.image_size;
+        self.stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                 /BitsPerComponent 8 /Filter /FlateDecode \
+                 /DecodeParms << /Predictor 15 /Colors 3 /BitsPerComponent 8 /Columns {} >>",
+                width, height, width
+            ),
+            &page.image,
+        );
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.data.len();
+        let _ = writeln!(
+            self.data,
+            "xref\n0 {}\n0000000000 65535 f ",
+            self.offsets.len() + 1
+        );
+        for offset in &self.offsets {
+            let _ = writeln!(self.data, "{:010} 00000 n ", offset);
+        }
+        let _ = writeln!(
+            self.data,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 4 0 R >>\nstartxref\n{}\n%%EOF",
+            self.offsets.len() + 1,
+            xref
+        );
+        self.data
+    }
+}
+
+/// A literal string of a PDF file in the encoding of the standard fonts, which covers
+/// Latin-1, with `?` for the characters it can't encode.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut string = vec![b'('];
+    for character in text.chars() {
+        let byte = match character as u32 {
+            0x20..=0x7E | 0xA0..=0xFF => character as u8,
+            _ => b'?',
+        };
+        if matches!(byte, b'(' | b')' | b'\\') {
+            string.push(b'\\');
+        }
+        string.push(byte);
+    }
+    string.push(b')');
+    string
+}
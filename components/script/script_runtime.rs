@@ -33,8 +33,8 @@ use js::jsapi::{
     JS_SetGCParameter, JS_SetGlobalJitCompilerOption, JS_SetOffthreadIonCompilationEnabled,
     JS_SetParallelParsingEnabled, JS_SetSecurityCallbacks, JobQueue, MimeType,
     PromiseRejectionHandlingState, PromiseUserInputEventHandlingState, SetDOMCallbacks,
-    SetGCSliceCallback, SetJobQueue, SetPreserveWrapperCallbacks, SetProcessBuildIdOp,
-    SetPromiseRejectionTrackerCallback, StreamConsumer as JSStreamConsumer,
+    SetGCSliceCallback, SetJobQueue, SetOutOfMemoryCallback, SetPreserveWrapperCallbacks,
+    SetProcessBuildIdOp, SetPromiseRejectionTrackerCallback, StreamConsumer as JSStreamConsumer,
 };
 use js::jsval::UndefinedValue;
 use js::panic::wrap_panic;
@@ -48,6 +48,7 @@ use malloc_size_of::MallocSizeOfOps;
 use msg::constellation_msg::PipelineId;
 use profile_traits::mem::{Report, ReportKind, ReportsChan};
 use profile_traits::path;
+use script_traits::JsGcSettings;
 use servo_config::{opts, pref};
 use style::thread_state::{self, ThreadState};
 
@@ -467,6 +468,8 @@ unsafe fn new_rt_and_cx_with_parent(
 
     JS_InitDestroyPrincipalsCallback(cx, Some(principals::destroy_servo_jsprincipal));
 
+    SetOutOfMemoryCallback(cx, Some(out_of_memory_callback), ptr::null_mut());
+
     // Needed for debug assertions about whether GC is running.
     if cfg!(debug_assertions) {
         JS_SetGCCallback(cx, Some(debug_gc_callback), ptr::null_mut());
@@ -589,13 +592,7 @@ unsafe fn new_rt_and_cx_with_parent(
     // TODO: handle js.throw_on_debugee_would_run (needs new Spidermonkey)
     // TODO: handle js.dump_stack_on_debugee_would_run (needs new Spidermonkey)
     // TODO: handle js.shared_memory.enabled
-    JS_SetGCParameter(
-        cx,
-        JSGCParamKey::JSGC_MAX_BYTES,
-        in_range(pref!(js.mem.max), 1, 0x100)
-            .map(|val| (val * 1024 * 1024) as u32)
-            .unwrap_or(u32::max_value()),
-    );
+    set_gc_settings(cx, &JsGcSettings::default());
     // NOTE: This is disabled above, so enabling it here will do nothing for now.
     JS_SetGCParameter(
         cx,
@@ -607,9 +604,6 @@ unsafe fn new_rt_and_cx_with_parent(
         JSGCParamKey::JSGC_PER_ZONE_GC_ENABLED,
         pref!(js.mem.gc.per_zone.enabled) as u32,
     );
-    if let Some(val) = in_range(pref!(js.mem.gc.incremental.slice_ms), 0, 100_000) {
-        JS_SetGCParameter(cx, JSGCParamKey::JSGC_SLICE_TIME_BUDGET_MS, val as u32);
-    }
     JS_SetGCParameter(
         cx,
         JSGCParamKey::JSGC_COMPACTING_ENABLED,
@@ -666,6 +660,29 @@ unsafe fn new_rt_and_cx_with_parent(
     }
 }
 
+/// Apply the heap limit and incremental slice budget of `settings` to the runtime of
+/// `cx`, using the `js.mem` preferences for those that aren't set.
+#[allow(unsafe_code)]
+pub unsafe fn set_gc_settings(cx: *mut RawJSContext, settings: &JsGcSettings) {
+    let max_heap_mb = settings
+        .max_heap_mb
+        .map(u64::from)
+        .or_else(|| in_range(pref!(js.mem.max), 1, 0x100).map(|val| val as u64));
+    JS_SetGCParameter(
+        cx,
+        JSGCParamKey::JSGC_MAX_BYTES,
+        max_heap_mb
+            .map(|val| (val * 1024 * 1024).min(u32::max_value() as u64) as u32)
+            .unwrap_or(u32::max_value()),
+    );
+    let slice_budget_ms = settings.slice_budget_ms.or_else(|| {
+        in_range(pref!(js.mem.gc.incremental.slice_ms), 0, 100_000).map(|val| val as u32)
+    });
+    if let Some(val) = slice_budget_ms {
+        JS_SetGCParameter(cx, JSGCParamKey::JSGC_SLICE_TIME_BUDGET_MS, val);
+    }
+}
+
 fn in_range<T: PartialOrd + Copy>(val: T, min: T, max: T) -> Option<T> {
     if val < min || val >= max {
         None
@@ -675,7 +692,7 @@ fn in_range<T: PartialOrd + Copy>(val: T, min: T, max: T) -> Option<T> {
 }
 
 #[allow(unsafe_code)]
-unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
+pub(crate) unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
     match get_dom_class(obj) {
         Ok(v) => {
             let dom_object = private_from_object(obj) as *const c_void;
@@ -816,6 +833,22 @@ pub(crate) fn runtime_is_alive() -> bool {
     THREAD_ACTIVE.with(|t| t.get())
 }
 
+thread_local!(static OUT_OF_MEMORY: Cell<bool> = Cell::new(false));
+
+/// Whether the JavaScript engine has run out of memory on this thread, either because
+/// the heap limit was reached or because an allocation failed.
+pub(crate) fn ran_out_of_memory() -> bool {
+    OUT_OF_MEMORY.with(|out_of_memory| out_of_memory.get())
+}
+
+/// Called by the JavaScript engine when it reports running out of memory. There is
+/// no way to recover from it here, so the script thread checks `ran_out_of_memory`
+/// once the task that caused it has returned.
+#[allow(unsafe_code)]
+unsafe extern "C" fn out_of_memory_callback(_cx: *mut RawJSContext, _data: *mut c_void) {
+    OUT_OF_MEMORY.with(|out_of_memory| out_of_memory.set(true));
+}
+
 #[allow(unsafe_code)]
 unsafe extern "C" fn trace_rust_roots(tr: *mut JSTracer, _data: *mut os::raw::c_void) {
     if !THREAD_ACTIVE.with(|t| t.get()) {
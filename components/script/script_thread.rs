@@ -38,7 +38,10 @@ use devtools_traits::{
     CSSError, DevtoolScriptControlMsg, DevtoolsPageInfo, NavigationState,
     ScriptToDevtoolsControlMsg, WorkerId,
 };
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{
+    EmbedderMsg, JavaScriptEvaluationError, NewWebViewRequest, PrintError, UserScript,
+    UserScriptInjectionTime, WebViewMemoryUsage, WebViewPriority,
+};
 use euclid::default::{Point2D, Rect};
 use euclid::Vector2D;
 use gfx::font_cache_thread::FontCacheThread;
@@ -49,10 +52,12 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::GetWindowProxyClass;
 use js::jsapi::{
-    JSContext as UnsafeJSContext, JSTracer, JS_AddInterruptCallback, SetWindowProxyClass,
+    GCReason, JSContext as UnsafeJSContext, JSTracer, JS_AddInterruptCallback, JS_MaybeGC,
+    SetWindowProxyClass, JS_GC,
 };
 use js::jsval::UndefinedValue;
 use js::rust::ParentRuntime;
+use keyboard_types::Modifiers;
 use media::WindowGLContext;
 use metrics::{PaintTimeMetrics, MAX_TASK_NS};
 use mime::{self, Mime};
@@ -76,17 +81,17 @@ use script_layout_interface::message::{Msg, ReflowGoal};
 use script_layout_interface::{Layout, LayoutConfig, LayoutFactory, ScriptThreadFactory};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::CompositorEvent::{
-    CompositionEvent, GamepadEvent, IMEDismissedEvent, KeyboardEvent, MouseButtonEvent,
+    CompositionEvent, GamepadEvent, IMEDismissedEvent, ImeEvent, KeyboardEvent, MouseButtonEvent,
     MouseMoveEvent, ResizeEvent, TouchEvent, WheelEvent,
 };
 use script_traits::{
     AnimationTickType, CompositorEvent, ConstellationControlMsg, DiscardBrowsingContext,
     DocumentActivity, EventResult, HistoryEntryReplacement, InitialScriptState, JsEvalResult,
-    LayoutControlMsg, LayoutMsg, LoadData, LoadOrigin, MediaSessionActionType, MouseButton,
-    MouseEventType, NewLayoutInfo, Painter, ProgressiveWebMetricType, ScriptMsg,
-    ScriptToConstellationChan, StructuredSerializedData, TimerSchedulerMsg, TouchEventType,
-    TouchId, UntrustedNodeAddress, UpdatePipelineIdReason, WebrenderIpcSender, WheelDelta,
-    WindowSizeData, WindowSizeType,
+    LayoutControlMsg, LayoutMsg, LoadData, LoadOrigin, MediaSessionActionType, MemoryPressureLevel,
+    MouseButton, MouseEventType, NewLayoutInfo, Painter, PictureInPictureAction,
+    ProgressiveWebMetricType, ScriptMsg, ScriptToConstellationChan, StructuredSerializedData,
+    TimerSchedulerMsg, TouchEventType, TouchId, UntrustedNodeAddress, UpdatePipelineIdReason,
+    WebrenderIpcSender, WheelDelta, WindowSizeData, WindowSizeType,
 };
 use servo_atoms::Atom;
 use servo_config::opts;
@@ -105,7 +110,7 @@ use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
-use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
 use crate::dom::bindings::conversions::{
     ConversionResult, FromJSValConvertible, StringificationBehavior,
 };
@@ -115,7 +120,7 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{
     Dom, DomRoot, MutNullableDom, RootCollection, ThreadLocalStackRoots,
 };
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::trace::{HashMapTracedValues, JSTraceable};
 use crate::dom::customelementregistry::{
     CallbackReaction, CustomElementDefinition, CustomElementReactionStack,
@@ -125,9 +130,12 @@ use crate::dom::document::{
 };
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::htmlimageelement::HTMLImageElement;
 use crate::dom::identityhub::Identities;
 use crate::dom::mutationobserver::MutationObserver;
 use crate::dom::node::{window_from_node, Node, ShadowIncluding};
@@ -136,18 +144,22 @@ use crate::dom::performancepainttiming::PerformancePaintTiming;
 use crate::dom::serviceworker::TrustedServiceWorkerAddress;
 use crate::dom::servoparser::{ParserContext, ServoParser};
 use crate::dom::uievent::UIEvent;
+use crate::dom::userscripts;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::{CreatorBrowsingContextInfo, WindowProxy};
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::worklet::WorkletThreadPool;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
 use crate::fetch::FetchCanceller;
+use crate::javascript_evaluation;
+use crate::mem::malloc_size_of_dom_object;
 use crate::microtask::{Microtask, MicrotaskQueue};
 use crate::realms::enter_realm;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::{
-    get_reports, new_rt_and_cx, CommonScriptMsg, ContextForRequestInterrupt, JSContext, Runtime,
-    ScriptChan, ScriptPort, ScriptThreadEventCategory,
+    get_reports, new_rt_and_cx, ran_out_of_memory, set_gc_settings, CommonScriptMsg,
+    ContextForRequestInterrupt, JSContext, Runtime, ScriptChan, ScriptPort,
+    ScriptThreadEventCategory,
 };
 use crate::task_manager::TaskManager;
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
@@ -164,7 +176,7 @@ use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::user_interaction::UserInteractionTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::{devtools, webdriver_handlers};
+use crate::{devtools, find_in_page, print, webdriver_handlers};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
 
@@ -208,6 +220,17 @@ struct InProgressLoad {
     activity: DocumentActivity,
     /// Window is visible.
     is_visible: bool,
+    /// Whether caret browsing is enabled in the document being loaded.
+    caret_browsing: bool,
+    /// Whether the webview of the document being loaded is muted.
+    audio_muted: bool,
+    /// The Content-Security-Policy that the embedder enforces in the document being loaded.
+    embedder_csp: Option<String>,
+    /// The scripts and stylesheets that the embedder injects into the document being loaded.
+    #[no_trace]
+    user_scripts: Vec<UserScript>,
+    /// The languages that the embedder set for the document being loaded.
+    languages: Vec<String>,
     /// The requested URL of the load.
     #[no_trace]
     url: ServoUrl,
@@ -251,6 +274,11 @@ impl InProgressLoad {
             window_size: window_size,
             activity: DocumentActivity::FullyActive,
             is_visible: true,
+            caret_browsing: false,
+            audio_muted: false,
+            embedder_csp: None,
+            user_scripts: vec![],
+            languages: vec![],
             url: url,
             origin: origin,
             navigation_start: navigation_start as u64,
@@ -632,6 +660,10 @@ pub struct ScriptThread {
     #[no_trace]
     closed_pipelines: DomRefCell<HashSet<PipelineId>>,
 
+    /// The pipelines whose tasks are held back while there are others to run.
+    #[no_trace]
+    low_priority_pipelines: DomRefCell<HashSet<PipelineId>>,
+
     #[no_trace]
     scheduler_chan: IpcSender<TimerSchedulerMsg>,
 
@@ -1139,6 +1171,15 @@ impl ScriptThread {
         })
     }
 
+    pub fn get_low_priority_pipeline_ids() -> HashSet<PipelineId> {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            root.get().map_or(HashSet::new(), |script_thread| {
+                let script_thread = unsafe { &*script_thread };
+                script_thread.low_priority_pipelines.borrow().clone()
+            })
+        })
+    }
+
     pub fn find_window_proxy(id: BrowsingContextId) -> Option<DomRoot<WindowProxy>> {
         SCRIPT_THREAD_ROOT.with(|root| {
             root.get().and_then(|script_thread| {
@@ -1391,6 +1432,7 @@ impl ScriptThread {
             js_runtime: Rc::new(runtime),
             topmost_mouse_over_target: MutNullableDom::new(Default::default()),
             closed_pipelines: DomRefCell::new(HashSet::new()),
+            low_priority_pipelines: DomRefCell::new(HashSet::new()),
 
             scheduler_chan: state.scheduler_chan,
 
@@ -1468,6 +1510,11 @@ impl ScriptThread {
         while self.handle_msgs() {
             // Go on...
             debug!("Running script thread.");
+            // Crash the webviews of this thread, rather than the whole engine, when the
+            // JavaScript heap is exhausted.
+            if ran_out_of_memory() {
+                panic!("The JavaScript heap ran out of memory");
+            }
         }
         debug!("Stopped script thread.");
     }
@@ -1855,7 +1902,38 @@ impl ScriptThread {
                 Reload(id, ..) => Some(id),
                 PaintMetric(id, ..) => Some(id),
                 ExitFullScreen(id, ..) => Some(id),
+                FullscreenEntered(id, ..) => Some(id),
                 MediaSessionAction(..) => None,
+                PictureInPictureAction(id, ..) => Some(id),
+                ShareCanvasTexture(id, ..) => Some(id),
+                SensorReading(id, ..) => Some(id),
+                EditingCommand(id, ..) => Some(id),
+                GetSelection(id) => Some(id),
+                GetImeSurroundingText(id, ..) => Some(id),
+                ContextMenuAction(id, ..) => Some(id),
+                SetCaretBrowsing(id, ..) => Some(id),
+                Find(id, ..) => Some(id),
+                ActivateFindMatch(id, ..) => Some(id),
+                ScrollFindMatchIntoView(id, ..) => Some(id),
+                StopFinding(id) => Some(id),
+                PrintToPdf(id, ..) => Some(id),
+                OpenBlockedPopup(ref popup) => Some(popup.opener_pipeline),
+                StopLoading(id) => Some(id),
+                SetAudioMuted(id, ..) => Some(id),
+                SetEmbedderCsp(id, ..) => Some(id),
+                SetUserScripts(id, ..) => Some(id),
+                EvaluateJavaScript(id, ..) => Some(id),
+                SetLanguages(id, ..) => Some(id),
+                SetPriority(id, ..) => Some(id),
+                SetSuspended(id, ..) => Some(id),
+                SetSystemFocus(id, ..) => Some(id),
+                MemoryPressure(..) => None,
+                CollectMemoryUsage(..) => None,
+                SetJsGcSettings(..) => None,
+                IdleGc => None,
+                GetScrollOffset(id, ..) => Some(id),
+                ScrollTo(id, ..) => Some(id),
+                PromptToUnload(id) => Some(id),
                 SetWebGPUPort(..) => None,
                 ForLayoutFromConstellation(_, id) => Some(id),
                 ForLayoutFromFontCache(id) => Some(id),
@@ -2077,6 +2155,177 @@ impl ScriptThread {
             ConstellationControlMsg::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action)
             },
+            ConstellationControlMsg::PictureInPictureAction(pipeline_id, action) => {
+                self.handle_picture_in_picture_action(pipeline_id, action)
+            },
+            ConstellationControlMsg::ShareCanvasTexture(pipeline_id, selector) => {
+                self.handle_share_canvas_texture(pipeline_id, selector)
+            },
+            ConstellationControlMsg::SensorReading(pipeline_id, reading) => {
+                match self.documents.borrow().find_window(pipeline_id) {
+                    Some(window) => window.handle_sensor_reading(reading),
+                    None => warn!("Sensor reading sent to closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::EditingCommand(pipeline_id, command) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document.handle_editing_command(command),
+                    None => warn!("Editing command sent to closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::GetSelection(pipeline_id) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document.send_selection_to_embedder(),
+                    None => warn!("Selection requested for closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::GetImeSurroundingText(pipeline_id, sender) => {
+                let text = self
+                    .documents
+                    .borrow()
+                    .find_document(pipeline_id)
+                    .and_then(|document| document.ime_surrounding_text());
+                if let Err(e) = sender.send(text) {
+                    warn!("Sending surrounding text failed ({:?}).", e);
+                }
+            },
+            ConstellationControlMsg::ContextMenuAction(pipeline_id, action) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document.handle_context_menu_action(action),
+                    None => warn!(
+                        "Context menu action sent to closed pipeline {}.",
+                        pipeline_id
+                    ),
+                }
+            },
+            ConstellationControlMsg::SetCaretBrowsing(pipeline_id, enabled) => {
+                self.handle_set_caret_browsing_msg(pipeline_id, enabled)
+            },
+            ConstellationControlMsg::Find(pipeline_id, query, options) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => find_in_page::find(&document, &query, options),
+                    None => warn!("Find sent to closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::ActivateFindMatch(pipeline_id, index) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => find_in_page::activate_match(&document, index),
+                    None => warn!("Find match activated in closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::ScrollFindMatchIntoView(
+                pipeline_id,
+                browsing_context_id,
+                rect,
+            ) => match self.documents.borrow().find_document(pipeline_id) {
+                Some(document) => {
+                    find_in_page::scroll_frame_into_view(&document, browsing_context_id, rect)
+                },
+                None => warn!("Find match scrolled to in closed pipeline {}.", pipeline_id),
+            },
+            ConstellationControlMsg::FullscreenEntered(pipeline_id, entered) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document.handle_fullscreen_entered(entered),
+                    None => warn!("Fullscreen entered in closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::StopFinding(pipeline_id) => {
+                if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+                    find_in_page::stop_finding(&document);
+                }
+            },
+            ConstellationControlMsg::EvaluateJavaScript(pipeline_id, source, sender) => {
+                let window = self.documents.borrow().find_window(pipeline_id);
+                match window {
+                    Some(window) => {
+                        javascript_evaluation::evaluate_javascript(&window, &source, sender)
+                    },
+                    None => {
+                        let _ = sender.send(Err(JavaScriptEvaluationError::FrameNotFound));
+                    },
+                }
+            },
+            ConstellationControlMsg::PrintToPdf(pipeline_id, settings, sender) => {
+                let document = self.documents.borrow().find_document(pipeline_id);
+                let result = match document {
+                    Some(document) => print::print_to_pdf(&document, &settings),
+                    None => Err(PrintError::NoSuchWebView),
+                };
+                let _ = sender.send(result);
+            },
+            ConstellationControlMsg::OpenBlockedPopup(popup) => {
+                self.handle_open_blocked_popup_msg(popup)
+            },
+            ConstellationControlMsg::StopLoading(pipeline_id) => {
+                let document = self.documents.borrow().find_document(pipeline_id);
+                match document {
+                    Some(document) => document.abort(),
+                    None => warn!("Stopped loading closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::SetAudioMuted(pipeline_id, muted) => {
+                self.handle_set_audio_muted_msg(pipeline_id, muted)
+            },
+            ConstellationControlMsg::SetEmbedderCsp(pipeline_id, policy) => {
+                self.handle_set_embedder_csp_msg(pipeline_id, policy)
+            },
+            ConstellationControlMsg::SetUserScripts(pipeline_id, scripts) => {
+                self.handle_set_user_scripts_msg(pipeline_id, scripts)
+            },
+            ConstellationControlMsg::SetLanguages(pipeline_id, languages) => {
+                self.handle_set_languages_msg(pipeline_id, languages)
+            },
+            ConstellationControlMsg::SetSuspended(pipeline_id, suspended) => {
+                self.handle_set_suspended_msg(pipeline_id, suspended)
+            },
+            ConstellationControlMsg::SetPriority(pipeline_id, priority) => {
+                let mut low_priority_pipelines = self.low_priority_pipelines.borrow_mut();
+                match priority {
+                    WebViewPriority::Normal => low_priority_pipelines.remove(&pipeline_id),
+                    WebViewPriority::Low => low_priority_pipelines.insert(pipeline_id),
+                };
+            },
+            ConstellationControlMsg::SetSystemFocus(pipeline_id, has_focus) => {
+                match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document.set_system_focus(has_focus),
+                    None => warn!("System focus set for closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::MemoryPressure(level) => self.handle_memory_pressure(level),
+            ConstellationControlMsg::CollectMemoryUsage(sender) => {
+                let _ = sender.send(self.memory_usage());
+            },
+            ConstellationControlMsg::SetJsGcSettings(settings) => unsafe {
+                set_gc_settings(*self.get_cx(), &settings)
+            },
+            ConstellationControlMsg::IdleGc => unsafe { JS_MaybeGC(*self.get_cx()) },
+            ConstellationControlMsg::GetScrollOffset(pipeline_id, sender) => {
+                let window = self.documents.borrow().find_window(pipeline_id);
+                match window {
+                    Some(window) => {
+                        let _ = sender.send(Vector2D::new(
+                            window.ScrollX() as f32,
+                            window.ScrollY() as f32,
+                        ));
+                    },
+                    None => warn!(
+                        "Scroll offset requested for closed pipeline {}.",
+                        pipeline_id
+                    ),
+                }
+            },
+            ConstellationControlMsg::ScrollTo(pipeline_id, offset) => {
+                let window = self.documents.borrow().find_window(pipeline_id);
+                match window {
+                    Some(window) => {
+                        window.scroll(offset.x as f64, offset.y as f64, ScrollBehavior::Instant)
+                    },
+                    None => warn!("Scroll requested for closed pipeline {}.", pipeline_id),
+                }
+            },
+            ConstellationControlMsg::PromptToUnload(pipeline_id) => {
+                self.handle_prompt_to_unload_msg(pipeline_id)
+            },
             ConstellationControlMsg::SetWebGPUPort(port) => {
                 if self.webgpu_port.borrow().is_some() {
                     warn!("WebGPU port already exists for this content process");
@@ -2130,6 +2379,7 @@ impl ScriptThread {
             WebGPUMsg::FreeRenderPipeline(id) => self.gpu_id_hub.lock().kill_render_pipeline_id(id),
             WebGPUMsg::FreeTexture(id) => self.gpu_id_hub.lock().kill_texture_id(id),
             WebGPUMsg::FreeTextureView(id) => self.gpu_id_hub.lock().kill_texture_view_id(id),
+            WebGPUMsg::FreeQuerySet(id) => self.gpu_id_hub.lock().kill_query_set_id(id),
             WebGPUMsg::Exit => *self.webgpu_port.borrow_mut() = None,
             WebGPUMsg::WebGPUOpResult {
                 device,
@@ -2523,7 +2773,9 @@ impl ScriptThread {
                 scroll_offsets.insert(OpaqueNode(node_address.0 as usize), -*scroll_offset);
             }
         }
-        window.set_scroll_offsets(scroll_offsets)
+        window.set_scroll_offsets(scroll_offsets);
+        // The highlights of the matches of a search move with the document.
+        find_in_page::send_highlights(&window.Document());
     }
 
     fn handle_new_layout(&self, new_layout_info: NewLayoutInfo, origin: MutableOrigin) {
@@ -2568,6 +2820,60 @@ impl ScriptThread {
         reports_chan.send(reports);
     }
 
+    /// The memory that each document uses, with the JavaScript heap of this thread
+    /// divided evenly between them.
+    fn memory_usage(&self) -> Vec<(PipelineId, WebViewMemoryUsage)> {
+        let documents = self.documents.borrow();
+        let js: usize = get_reports(*self.get_cx(), String::new())
+            .iter()
+            .map(|report| report.size)
+            .sum();
+        let js = js / documents.iter().count().max(1);
+
+        documents
+            .iter()
+            .map(|(pipeline_id, document)| {
+                let layout = self.layout_memory_usage(pipeline_id);
+                let mut images = HashSet::new();
+                let mut usage = WebViewMemoryUsage {
+                    js,
+                    layout,
+                    ..Default::default()
+                };
+                for node in document
+                    .upcast::<Node>()
+                    .traverse_preorder(ShadowIncluding::Yes)
+                {
+                    usage.dom += malloc_size_of_dom_object(&*node);
+                    let image = node
+                        .downcast::<HTMLImageElement>()
+                        .and_then(|image| image.current_image());
+                    if let Some(image) = image {
+                        if images.insert(Arc::as_ptr(&image)) {
+                            usage.images += image.bytes.len();
+                        }
+                    }
+                }
+                (pipeline_id, usage)
+            })
+            .collect()
+    }
+
+    /// The memory that the layout of the given document reports to the memory profiler.
+    fn layout_memory_usage(&self, pipeline_id: PipelineId) -> usize {
+        let (sender, receiver) = match ipc::channel() {
+            Ok(channel) => channel,
+            Err(_) => return 0,
+        };
+        match self.layouts.borrow_mut().get_mut(&pipeline_id) {
+            Some(layout) => layout.process(Msg::CollectReports(ReportsChan(sender))),
+            None => return 0,
+        }
+        receiver
+            .try_recv()
+            .map_or(0, |reports| reports.iter().map(|report| report.size).sum())
+    }
+
     /// Updates iframe element after a change in visibility
     fn handle_visibility_change_complete_msg(
         &self,
@@ -2584,6 +2890,113 @@ impl ScriptThread {
         }
     }
 
+    fn handle_set_caret_browsing_msg(&self, id: PipelineId, enabled: bool) {
+        if let Some(document) = self.documents.borrow().find_document(id) {
+            return document.set_caret_browsing(enabled);
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        match loads.iter_mut().find(|load| load.pipeline_id == id) {
+            Some(load) => load.caret_browsing = enabled,
+            None => warn!("Caret browsing set for closed pipeline {}.", id),
+        }
+    }
+
+    fn handle_set_embedder_csp_msg(&self, id: PipelineId, policy: Option<String>) {
+        if let Some(document) = self.documents.borrow().find_document(id) {
+            return document.set_embedder_csp(policy.as_deref());
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        match loads.iter_mut().find(|load| load.pipeline_id == id) {
+            Some(load) => load.embedder_csp = policy,
+            None => warn!("Policy set for closed pipeline {}.", id),
+        }
+    }
+
+    fn handle_set_user_scripts_msg(&self, id: PipelineId, scripts: Vec<UserScript>) {
+        if let Some(document) = self.documents.borrow().find_document(id) {
+            return document.set_user_scripts(scripts);
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        match loads.iter_mut().find(|load| load.pipeline_id == id) {
+            Some(load) => load.user_scripts = scripts,
+            None => warn!("User scripts set for closed pipeline {}.", id),
+        }
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#freeze-steps>
+    /// <https://wicg.github.io/page-lifecycle/#resume-steps>
+    fn handle_set_suspended_msg(&self, id: PipelineId, suspended: bool) {
+        let document = match self.documents.borrow().find_document(id) {
+            Some(document) => document,
+            None => return warn!("Suspended or resumed closed pipeline {}.", id),
+        };
+        let name = if suspended { "freeze" } else { "resume" };
+        document
+            .upcast::<EventTarget>()
+            .fire_bubbling_event(Atom::from(name));
+        if suspended {
+            self.image_cache.purge_unused_images();
+        }
+    }
+
+    /// Open a popup that the embedder allowed after it was blocked, by running the window
+    /// open steps again in the document that tried to open it.
+    fn handle_open_blocked_popup_msg(&self, popup: NewWebViewRequest) {
+        let window = match self.documents.borrow().find_window(popup.opener_pipeline) {
+            Some(window) => window,
+            None => {
+                return warn!(
+                    "Blocked popup opened in closed pipeline {}.",
+                    popup.opener_pipeline
+                );
+            },
+        };
+        let url = popup.url.map_or_else(String::new, ServoUrl::into_string);
+        let result = window.window_proxy().open_with_features(
+            USVString(url),
+            DOMString::from(popup.target_name),
+            popup.noopener,
+            false,
+            popup.features,
+        );
+        if result.is_err() {
+            warn!("Failed to open blocked popup in {}.", popup.opener_pipeline);
+        }
+    }
+
+    fn handle_set_languages_msg(&self, id: PipelineId, languages: Vec<String>) {
+        if let Some(window) = self.documents.borrow().find_window(id) {
+            return window.set_languages(languages);
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        match loads.iter_mut().find(|load| load.pipeline_id == id) {
+            Some(load) => load.languages = languages,
+            None => warn!("Languages set for closed pipeline {}.", id),
+        }
+    }
+
+    fn handle_set_audio_muted_msg(&self, id: PipelineId, muted: bool) {
+        if let Some(window) = self.documents.borrow().find_window(id) {
+            return window.set_audio_muted(muted);
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        match loads.iter_mut().find(|load| load.pipeline_id == id) {
+            Some(load) => load.audio_muted = muted,
+            None => warn!("Audio muted for closed pipeline {}.", id),
+        }
+    }
+
+    /// Free the caches of this script thread that can be rebuilt later, then collect
+    /// the garbage of every document it runs.
+    fn handle_memory_pressure(&self, level: MemoryPressureLevel) {
+        debug!("Memory pressure ({:?}), purging caches.", level);
+        self.image_cache.purge_unused_images();
+        gfx::font_context::invalidate_font_caches();
+        unsafe {
+            JS_GC(*self.get_cx(), GCReason::MEM_PRESSURE);
+        }
+    }
+
     /// Handle visibility change message
     fn handle_visibility_change_msg(&self, id: PipelineId, visible: bool) {
         // Separate message sent since parent script thread could be different (Iframe of different
@@ -2610,6 +3023,26 @@ impl ScriptThread {
         warn!("change visibility message sent to nonexistent pipeline");
     }
 
+    /// Prompt the given document to unload before its webview is closed, and tell the
+    /// constellation whether it may close. A document that hasn't loaded yet has nothing
+    /// to ask.
+    fn handle_prompt_to_unload_msg(&self, id: PipelineId) {
+        let document = self.documents.borrow().find_document(id);
+        let can_unload = match document {
+            Some(document) => {
+                let can_unload = document.prompt_to_unload(false);
+                if can_unload {
+                    document.unload(false);
+                }
+                can_unload
+            },
+            None => true,
+        };
+        let _ = self
+            .script_sender
+            .send((id, ScriptMsg::PromptToUnloadResult(can_unload)));
+    }
+
     /// Handles activity change message
     fn handle_set_document_activity_msg(&self, id: PipelineId, activity: DocumentActivity) {
         debug!(
@@ -2906,6 +3339,7 @@ impl ScriptThread {
         debug!("{id}: Starting pipeline exit.");
 
         self.closed_pipelines.borrow_mut().insert(id);
+        self.low_priority_pipelines.borrow_mut().remove(&id);
 
         // Abort the parser, if any,
         // to prevent any further incoming networking messages from being handled.
@@ -2952,6 +3386,19 @@ impl ScriptThread {
 
             debug!("{id}: Clearing JavaScript runtime");
             window.clear_js_runtime();
+        } else {
+            // Dropping the load of a document that was never created cancels its fetch.
+            let mut incomplete_loads = self.incomplete_loads.borrow_mut();
+            if let Some(idx) = incomplete_loads
+                .iter()
+                .position(|load| load.pipeline_id == id)
+            {
+                incomplete_loads.remove(idx);
+                debug!("{id}: Sending PipelineExited message to constellation");
+                self.script_sender
+                    .send((id, ScriptMsg::PipelineExited))
+                    .ok();
+            }
         }
 
         debug!("{id}: Finished pipeline exit");
@@ -3007,7 +3454,10 @@ impl ScriptThread {
             Some(document) => document,
             None => return warn!("Message sent to closed pipeline {}.", id),
         };
-        if tick_type.contains(AnimationTickType::REQUEST_ANIMATION_FRAME) {
+        // Hidden documents don't get animation frames until they are shown again.
+        if tick_type.contains(AnimationTickType::REQUEST_ANIMATION_FRAME) &&
+            document.window().visible()
+        {
             document.run_the_animation_frame_callbacks();
         }
         if tick_type.contains(AnimationTickType::CSS_ANIMATIONS_AND_TRANSITIONS) {
@@ -3399,6 +3849,10 @@ impl ScriptThread {
             incomplete.canceller,
         );
         document.set_ready_state(DocumentReadyState::Loading);
+        // Before any script of the document runs.
+        document.set_embedder_csp(incomplete.embedder_csp.as_deref());
+        document.set_user_scripts(incomplete.user_scripts);
+        window.set_languages(incomplete.languages);
 
         self.documents
             .borrow_mut()
@@ -3436,6 +3890,8 @@ impl ScriptThread {
         document.set_https_state(metadata.https_state);
         document.set_navigation_start(incomplete.navigation_start_precise);
 
+        userscripts::inject_user_scripts(&document, UserScriptInjectionTime::DocumentStart);
+
         if is_html_document == IsHTMLDocument::NonHTMLDocument {
             ServoParser::parse_xml_document(&document, None, final_url);
         } else {
@@ -3452,6 +3908,14 @@ impl ScriptThread {
             window.alter_resource_utilization(false);
         }
 
+        if incomplete.caret_browsing {
+            document.set_caret_browsing(true);
+        }
+
+        if incomplete.audio_muted {
+            window.set_audio_muted(true);
+        }
+
         document.get_current_parser().unwrap()
     }
 
@@ -3523,6 +3987,7 @@ impl ScriptThread {
                 node_address,
                 point_in_node,
                 pressed_mouse_buttons,
+                modifiers,
             ) => {
                 self.handle_mouse_event(
                     pipeline_id,
@@ -3532,6 +3997,7 @@ impl ScriptThread {
                     node_address,
                     point_in_node,
                     pressed_mouse_buttons,
+                    modifiers,
                 );
             },
 
@@ -3653,6 +4119,14 @@ impl ScriptThread {
                 document.dispatch_composition_event(composition_event);
             },
 
+            ImeEvent(ime_event) => {
+                let document = match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document.dispatch_ime_event(ime_event);
+            },
+
             GamepadEvent(gamepad_event) => {
                 let window = match self.documents.borrow().find_window(pipeline_id) {
                     Some(window) => window,
@@ -3675,6 +4149,7 @@ impl ScriptThread {
         node_address: Option<UntrustedNodeAddress>,
         point_in_node: Option<Point2D<f32>>,
         pressed_mouse_buttons: u16,
+        modifiers: Modifiers,
     ) {
         let document = match self.documents.borrow().find_document(pipeline_id) {
             Some(document) => document,
@@ -3688,6 +4163,7 @@ impl ScriptThread {
                 node_address,
                 point_in_node,
                 pressed_mouse_buttons,
+                modifiers,
             )
         }
     }
@@ -4022,6 +4498,37 @@ impl ScriptThread {
         };
     }
 
+    fn handle_picture_in_picture_action(
+        &self,
+        pipeline_id: PipelineId,
+        action: PictureInPictureAction,
+    ) {
+        match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document.handle_picture_in_picture_action(action),
+            None => warn!("No document for picture-in-picture action"),
+        }
+    }
+
+    fn handle_share_canvas_texture(&self, pipeline_id: PipelineId, selector: String) {
+        let document = match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document,
+            None => return warn!("No document for sharing a canvas texture"),
+        };
+        let image = document
+            .upcast::<Node>()
+            .query_selector(DOMString::from(selector.clone()))
+            .ok()
+            .flatten()
+            .and_then(|element| {
+                element
+                    .downcast::<HTMLCanvasElement>()
+                    .and_then(|canvas| canvas.external_image_id())
+            });
+        document
+            .window()
+            .send_to_constellation(ScriptMsg::CanvasTextureShared(selector, image));
+    }
+
     pub fn enqueue_microtask(job: Microtask) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
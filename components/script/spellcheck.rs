@@ -0,0 +1,486 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The built-in spelling checker of text controls, used when the embedder doesn't
+//! check spelling itself. It reads dictionaries in the Hunspell format, of which it
+//! understands the word list, prefixes and suffixes, and the suggestion hints.
+//!
+//! <https://html.spec.whatwg.org/multipage/#spelling-and-grammar-checking>
+
+use std::collections::HashMap;
+use std::fs;
+use std::iter;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use embedder_traits::{EmbedderMsg, Misspelling};
+use lazy_static::lazy_static;
+use log::debug;
+use servo_config::pref;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::element::Element;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::navigatorinfo;
+use crate::dom::node::window_from_node;
+
+/// How many suggestions are offered for a misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+lazy_static! {
+    /// The dictionaries loaded by the script threads of this process, by language
+    /// tag. `None` records that there is no dictionary for the language.
+    static ref DICTIONARIES: Mutex<HashMap<String, Option<Arc<Dictionary>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Check the spelling of `value`, the value of a text control, and tell the embedder
+/// about its misspelled words if they are not the ones in `last_reported`.
+pub(crate) fn check_text_control(
+    element: &HTMLElement,
+    value: &str,
+    last_reported: &DomRefCell<Vec<Misspelling>>,
+) {
+    if !pref!(dom.spellcheck.enabled) || !element.Spellcheck() {
+        return;
+    }
+    let mut lang = element.upcast::<Element>().get_lang();
+    if lang.is_empty() {
+        lang = navigatorinfo::Language().into();
+    }
+    let misspellings = match dictionary_for(&lang) {
+        Some(dictionary) => dictionary.misspellings(value),
+        None => return,
+    };
+    if *last_reported.borrow() == misspellings {
+        return;
+    }
+    *last_reported.borrow_mut() = misspellings.clone();
+    window_from_node(element).send_to_embedder(EmbedderMsg::Misspellings(misspellings));
+}
+
+/// The dictionary for `lang`, a BCP 47 language tag, if one is installed.
+fn dictionary_for(lang: &str) -> Option<Arc<Dictionary>> {
+    let directory = pref!(dom.spellcheck.dictionaries);
+    if directory.is_empty() {
+        return None;
+    }
+    DICTIONARIES
+        .lock()
+        .unwrap()
+        .entry(lang.to_owned())
+        .or_insert_with(|| load_dictionary(Path::new(&directory), lang).map(Arc::new))
+        .clone()
+}
+
+fn load_dictionary(directory: &Path, lang: &str) -> Option<Dictionary> {
+    // Hunspell dictionaries are named like `en_US`. A dictionary for the language
+    // alone, or for any of its regions, is good enough for its other regions.
+    let mut subtags = lang.split(['-', '_']);
+    let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+    let mut names = vec![];
+    if let Some(region) = subtags.next().filter(|region| region.len() == 2) {
+        names.push(format!("{}_{}", language, region.to_ascii_uppercase()));
+    }
+    names.push(language.clone());
+    if let Ok(entries) = fs::read_dir(directory) {
+        let mut regions: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter_map(|file_name| file_name.strip_suffix(".dic").map(str::to_owned))
+            .filter(|name| name.starts_with(&format!("{}_", language)))
+            .collect();
+        regions.sort();
+        names.extend(regions);
+    }
+
+    for name in names {
+        let aff = fs::read(directory.join(format!("{}.aff", name)));
+        let dic = fs::read(directory.join(format!("{}.dic", name)));
+        if let (Ok(aff), Ok(dic)) = (aff, dic) {
+            debug!("Using the {} spelling dictionary for {}", name, lang);
+            return Some(Dictionary::parse(
+                &String::from_utf8_lossy(&aff),
+                &String::from_utf8_lossy(&dic),
+            ));
+        }
+    }
+    debug!("No spelling dictionary for {}", lang);
+    None
+}
+
+/// How the flags of words and affixes are written.
+#[derive(Clone, Copy)]
+enum FlagFormat {
+    /// One character per flag, the default.
+    Char,
+    /// Two characters per flag.
+    Long,
+    /// Decimal numbers separated by commas.
+    Num,
+}
+
+impl FlagFormat {
+    fn split(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagFormat::Char => flags.chars().map(String::from).collect(),
+            FlagFormat::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                chars.chunks(2).map(|pair| pair.iter().collect()).collect()
+            },
+            FlagFormat::Num => flags.split(',').map(str::to_owned).collect(),
+        }
+    }
+}
+
+/// One character of the condition a root must meet for an affix to apply to it.
+enum CharClass {
+    Any,
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+
+    fn parse_condition(condition: &str) -> Vec<CharClass> {
+        let mut classes = vec![];
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => classes.push(CharClass::Any),
+                '[' => {
+                    let mut set = vec![];
+                    let mut negated = false;
+                    for c in chars.by_ref() {
+                        match c {
+                            ']' => break,
+                            '^' if set.is_empty() && !negated => negated = true,
+                            c => set.push(c),
+                        }
+                    }
+                    classes.push(CharClass::Set {
+                        chars: set,
+                        negated,
+                    });
+                },
+                c => classes.push(CharClass::Set {
+                    chars: vec![c],
+                    negated: false,
+                }),
+            }
+        }
+        classes
+    }
+}
+
+/// A prefix or suffix rule: words with `flag` may have `strip` replaced by `add`.
+struct Affix {
+    flag: String,
+    /// Whether the rule combines with rules of the other kind.
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<CharClass>,
+}
+
+impl Affix {
+    /// The root `word` was made from, if it could have been made with this suffix.
+    fn root_of_suffixed(&self, word: &str) -> Option<String> {
+        let stem = word.strip_suffix(self.add.as_str())?;
+        if stem.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let root = format!("{}{}", stem, self.strip);
+        let chars: Vec<char> = root.chars().collect();
+        let start = chars.len().checked_sub(self.condition.len())?;
+        self.matches_condition(&chars[start..]).then_some(root)
+    }
+
+    /// The root `word` was made from, if it could have been made with this prefix.
+    fn root_of_prefixed(&self, word: &str) -> Option<String> {
+        let stem = word.strip_prefix(self.add.as_str())?;
+        if stem.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let root = format!("{}{}", self.strip, stem);
+        let chars: Vec<char> = root.chars().take(self.condition.len()).collect();
+        if chars.len() < self.condition.len() {
+            return None;
+        }
+        self.matches_condition(&chars).then_some(root)
+    }
+
+    fn matches_condition(&self, chars: &[char]) -> bool {
+        self.condition
+            .iter()
+            .zip(chars)
+            .all(|(class, c)| class.matches(*c))
+    }
+}
+
+pub struct Dictionary {
+    /// The flags of each root word.
+    words: HashMap<String, Vec<String>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    /// The characters to try when suggesting, most common first.
+    try_chars: Vec<char>,
+    /// Common misspellings, and what to replace them with when suggesting.
+    replacements: Vec<(String, String)>,
+}
+
+impl Dictionary {
+    /// Read a dictionary from the contents of its `.aff` and `.dic` files.
+    pub fn parse(aff: &str, dic: &str) -> Dictionary {
+        let mut flag_format = FlagFormat::Char;
+        let mut cross_products = HashMap::new();
+        let mut prefixes = vec![];
+        let mut suffixes = vec![];
+        let mut try_chars = vec![];
+        let mut replacements = vec![];
+        // An affix or replacement entry with a zero stands for an empty string.
+        let not_zero = |field: &str| {
+            if field == "0" {
+                String::new()
+            } else {
+                field.to_owned()
+            }
+        };
+
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => flag_format = FlagFormat::Long,
+                ["FLAG", "num", ..] => flag_format = FlagFormat::Num,
+                ["TRY", chars, ..] => try_chars = chars.chars().collect(),
+                ["REP", count] if count.parse::<usize>().is_ok() => {},
+                ["REP", from, to, ..] => {
+                    replacements.push((from.replace('_', " "), to.replace('_', " ")))
+                },
+                [kind @ ("PFX" | "SFX"), flag, cross_product @ ("Y" | "N"), count]
+                    if count.parse::<usize>().is_ok() =>
+                {
+                    cross_products.insert((*kind, *flag), *cross_product == "Y");
+                },
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    // Continuation flags after the added text are not supported.
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag: flag.to_string(),
+                        cross_product: cross_products
+                            .get(&(*kind, *flag))
+                            .copied()
+                            .unwrap_or(false),
+                        strip: not_zero(strip),
+                        add: not_zero(add),
+                        condition: CharClass::parse_condition(rest.first().unwrap_or(&".")),
+                    };
+                    if *kind == "PFX" {
+                        prefixes.push(affix);
+                    } else {
+                        suffixes.push(affix);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        // The first line is the number of words, and anything after a word and its
+        // flags describes its morphology.
+        let mut words: HashMap<String, Vec<String>> = HashMap::new();
+        for line in dic.lines().skip(1) {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, flag_format.split(flags)),
+                None => (entry, vec![]),
+            };
+            words.entry(word.to_owned()).or_default().extend(flags);
+        }
+
+        Dictionary {
+            words,
+            prefixes,
+            suffixes,
+            try_chars,
+            replacements,
+        }
+    }
+
+    /// Whether `word` is spelled correctly. Capitalized and upper case forms of
+    /// the words in the dictionary are correct too.
+    pub fn check(&self, word: &str) -> bool {
+        if self.check_form(word) {
+            return true;
+        }
+        let mut chars = word.chars();
+        let capitalized =
+            chars.next().map_or(false, char::is_uppercase) && chars.all(|c| !c.is_uppercase());
+        let upper_case = word.chars().all(|c| !c.is_lowercase());
+        if !(capitalized || upper_case) {
+            return false;
+        }
+        let lower_case = word.to_lowercase();
+        if self.check_form(&lower_case) {
+            return true;
+        }
+        // Proper names are capitalized in the dictionary.
+        upper_case && {
+            let mut chars = lower_case.chars();
+            let name: String = chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+                .collect();
+            self.check_form(&name)
+        }
+    }
+
+    fn check_form(&self, word: &str) -> bool {
+        if self.words.contains_key(word) || self.check_suffixed(word, None) {
+            return true;
+        }
+        self.prefixes.iter().any(|prefix| {
+            prefix.root_of_prefixed(word).map_or(false, |root| {
+                self.has_flag(&root, &prefix.flag) ||
+                    (prefix.cross_product && self.check_suffixed(&root, Some(prefix)))
+            })
+        })
+    }
+
+    /// Whether `word` is a root with a suffix, that also allows `prefix` if any.
+    fn check_suffixed(&self, word: &str, prefix: Option<&Affix>) -> bool {
+        self.suffixes.iter().any(|suffix| {
+            if prefix.is_some() && !suffix.cross_product {
+                return false;
+            }
+            suffix.root_of_suffixed(word).map_or(false, |root| {
+                self.has_flag(&root, &suffix.flag) &&
+                    prefix.map_or(true, |prefix| self.has_flag(&root, &prefix.flag))
+            })
+        })
+    }
+
+    fn has_flag(&self, word: &str, flag: &str) -> bool {
+        self.words
+            .get(word)
+            .map_or(false, |flags| flags.iter().any(|f| f == flag))
+    }
+
+    /// Correctly spelled words `word` may be a misspelling of, most likely first.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions: Vec<String> = vec![];
+        let mut consider = |candidate: String| {
+            if suggestions.len() < MAX_SUGGESTIONS &&
+                candidate != word &&
+                !suggestions.contains(&candidate) &&
+                candidate.split(' ').all(|part| self.check(part))
+            {
+                suggestions.push(candidate);
+            }
+        };
+
+        for (from, to) in &self.replacements {
+            for (index, _) in word.match_indices(from.as_str()) {
+                consider(format!(
+                    "{}{}{}",
+                    &word[..index],
+                    to,
+                    &word[index + from.len()..]
+                ));
+            }
+        }
+
+        let chars: Vec<char> = word.chars().collect();
+        let try_chars: Vec<char> = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars.clone()
+        };
+        let edit = |range: Range<usize>, replacement: &[char]| -> String {
+            chars[..range.start]
+                .iter()
+                .chain(replacement)
+                .chain(&chars[range.end..])
+                .collect()
+        };
+        for index in 1..chars.len() {
+            consider(edit(
+                index - 1..index + 1,
+                &[chars[index], chars[index - 1]],
+            ));
+        }
+        for index in 0..chars.len() {
+            for c in &try_chars {
+                consider(edit(index..index + 1, &[*c]));
+            }
+        }
+        for index in 0..chars.len() {
+            consider(edit(index..index + 1, &[]));
+        }
+        for index in 0..=chars.len() {
+            for c in &try_chars {
+                consider(edit(index..index, &[*c]));
+            }
+        }
+        for index in 1..chars.len() {
+            consider(edit(index..index, &[' ']));
+        }
+        suggestions
+    }
+
+    /// The words of `text` that are not spelled correctly.
+    pub fn misspellings(&self, text: &str) -> Vec<Misspelling> {
+        words(text)
+            .into_iter()
+            .filter(|(_, word)| !self.check(word))
+            .map(|(range, word)| Misspelling {
+                range,
+                word: word.to_owned(),
+                suggestions: self.suggest(word),
+            })
+            .collect()
+    }
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+/// The words of `text` worth checking, with their ranges in UTF-16 code units.
+/// Words with digits in them are left out, as they are rarely in dictionaries.
+fn words(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut utf16_offset = 0;
+    // A trailing space ends the last word.
+    for (index, c) in text.char_indices().chain(iter::once((text.len(), ' '))) {
+        let in_word = c.is_alphanumeric() || (start.is_some() && is_apostrophe(c));
+        match start {
+            None if in_word => start = Some((index, utf16_offset)),
+            Some((byte_start, utf16_start)) if !in_word => {
+                let word = text[byte_start..index].trim_end_matches(is_apostrophe);
+                if !word.chars().any(|c| c.is_numeric()) {
+                    let utf16_end = utf16_start + word.encode_utf16().count();
+                    words.push((utf16_start..utf16_end, word));
+                }
+                start = None;
+            },
+            _ => {},
+        }
+        utf16_offset += c.len_utf16();
+    }
+    words
+}
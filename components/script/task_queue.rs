@@ -103,7 +103,12 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
 
     /// Process incoming tasks, immediately sending priority ones downstream,
     /// and categorizing potential throttles.
-    fn process_incoming_tasks(&self, first_msg: T, fully_active: &HashSet<PipelineId>) {
+    fn process_incoming_tasks(
+        &self,
+        first_msg: T,
+        fully_active: &HashSet<PipelineId>,
+        low_priority: &HashSet<PipelineId>,
+    ) {
         // 1. Make any previously stored task from now fully-active document available.
         let mut incoming = self.release_tasks_for_fully_active_documents(fully_active);
 
@@ -119,7 +124,8 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             }
         }
 
-        // 4. Filter tasks from non-priority task-sources.
+        // 4. Filter tasks from non-priority task-sources, and from the documents of
+        // low priority webviews.
         // TODO: This can use `extract_if` once that is stabilized.
         let mut to_be_throttled = Vec::new();
         let mut index = 0;
@@ -130,8 +136,25 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                 Some(task_source) => task_source,
                 None => continue,
             };
+            // Tasks of a document whose webview is no longer low priority still wait
+            // behind its held back ones, so that a task source keeps its order.
+            let is_low_priority = incoming[index - 1]
+                .pipeline_id()
+                .map_or(false, |pipeline_id| {
+                    low_priority.contains(&pipeline_id) ||
+                        self.throttled
+                            .borrow()
+                            .get(task_source)
+                            .map_or(false, |queue| {
+                                queue.iter().any(|task| task.3 == Some(pipeline_id))
+                            })
+                });
 
             match task_source {
+                _ if is_low_priority => {
+                    to_be_throttled.push(incoming.remove(index - 1));
+                    index -= 1;
+                },
                 TaskSourceName::PerformanceTimeline => {
                     to_be_throttled.push(incoming.remove(index - 1));
                     index -= 1; // We've removed an element, so the next has the same index.
@@ -197,8 +220,9 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         // High-watermark: once reached, throttled tasks will be held-back.
         const PER_ITERATION_MAX: u64 = 5;
         let fully_active = ScriptThread::get_fully_active_document_ids();
+        let low_priority = ScriptThread::get_low_priority_pipeline_ids();
         // Always first check for new tasks, but don't reset 'taken_task_counter'.
-        self.process_incoming_tasks(first_msg, &fully_active);
+        self.process_incoming_tasks(first_msg, &fully_active, &low_priority);
         let mut throttled = self.throttled.borrow_mut();
         let mut throttled_length: usize = throttled.values().map(|queue| queue.len()).sum();
         let task_source_names = TaskSourceName::all();
@@ -60,6 +60,11 @@ pub mod size_of {
     }
 }
 
+pub mod csp {
+    pub use crate::dom::bindings::codegen::Bindings::SecurityPolicyViolationEventBinding::SecurityPolicyViolationEventDisposition;
+    pub use crate::dom::securitypolicyviolationevent::CSPViolationReport;
+}
+
 pub mod srcset {
     pub use crate::dom::htmlimageelement::{parse_a_srcset_attribute, Descriptor, ImageSource};
 }
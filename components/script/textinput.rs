@@ -161,6 +161,18 @@ pub struct SelectionState {
     direction: SelectionDirection,
 }
 
+/// The most edits that can be undone in a single text input control.
+const MAX_UNDO_ENTRIES: usize = 100;
+
+/// The state of a text input control before an edit, which is restored when the edit is undone.
+#[derive(JSTraceable, MallocSizeOf)]
+struct UndoEntry {
+    lines: Vec<DOMString>,
+    edit_point: TextPoint,
+    selection_origin: Option<TextPoint>,
+    selection_direction: SelectionDirection,
+}
+
 /// Encapsulated state for handling keyboard input in a single or multiline text input control.
 #[derive(JSTraceable, MallocSizeOf)]
 pub struct TextInput<T: ClipboardProvider> {
@@ -189,6 +201,16 @@ pub struct TextInput<T: ClipboardProvider> {
 
     /// Was last change made by set_content?
     was_last_change_by_set_content: bool,
+
+    /// The states to go back to when undoing edits, most recent last. Setting the content
+    /// from script starts a new history.
+    undo_stack: Vec<UndoEntry>,
+
+    /// The text being composed by an input method, which is part of the content, as a range
+    /// of byte offsets. Any other edit stops the composition.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Defined in std"]
+    composition: Option<Range<UTF8Bytes>>,
 }
 
 /// Resulting action to be taken by the owner of a text input that is handling an event.
@@ -275,6 +297,8 @@ impl<T: ClipboardProvider> TextInput<T> {
             min_length: min_length,
             selection_direction: selection_direction,
             was_last_change_by_set_content: true,
+            undo_stack: vec![],
+            composition: None,
         };
         i.set_content(initial);
         i
@@ -470,6 +494,13 @@ impl<T: ClipboardProvider> TextInput<T> {
             len_of_first_n_code_units(&*insert, allowed_to_insert_count);
         let to_insert = &insert[..last_char_index];
 
+        let undo_entry = UndoEntry {
+            lines: self.lines.clone(),
+            edit_point: self.edit_point,
+            selection_origin: self.selection_origin,
+            selection_direction: self.selection_direction,
+        };
+
         let (start, end) = self.sorted_selection_bounds();
         let UTF8Bytes(start_offset) = start.index;
         let UTF8Bytes(end_offset) = end.index;
@@ -506,12 +537,57 @@ impl<T: ClipboardProvider> TextInput<T> {
             new_lines
         };
 
+        if new_lines != self.lines {
+            if self.undo_stack.len() == MAX_UNDO_ENTRIES {
+                self.undo_stack.remove(0);
+            }
+            self.undo_stack.push(undo_entry);
+        }
         self.lines = new_lines;
         self.was_last_change_by_set_content = false;
+        self.composition = None;
         self.clear_selection();
         self.assert_ok_selection();
     }
 
+    /// Go back to the content and selection from before the last edit. Returns whether
+    /// there was an edit to undo.
+    pub fn undo(&mut self) -> bool {
+        let entry = match self.undo_stack.pop() {
+            Some(entry) => entry,
+            None => return false,
+        };
+        self.lines = entry.lines;
+        self.edit_point = entry.edit_point;
+        self.selection_origin = entry.selection_origin;
+        self.selection_direction = entry.selection_direction;
+        self.was_last_change_by_set_content = false;
+        self.composition = None;
+        self.assert_ok_selection();
+        true
+    }
+
+    /// Put the selected text on the clipboard and remove it.
+    pub fn cut(&mut self) {
+        if let Some(text) = self.get_selection_text() {
+            self.clipboard_provider.set_clipboard_contents(text);
+            self.delete_char(Direction::Backward);
+        }
+    }
+
+    /// Put the selected text on the clipboard.
+    pub fn copy(&mut self) {
+        if let Some(text) = self.get_selection_text() {
+            self.clipboard_provider.set_clipboard_contents(text);
+        }
+    }
+
+    /// Replace the selection with the text on the clipboard.
+    pub fn paste(&mut self) {
+        let contents = self.clipboard_provider.clipboard_contents();
+        self.insert_string(contents);
+    }
+
     /// Return the length in bytes of the current line under the editing point.
     pub fn current_line_length(&self) -> UTF8Bytes {
         self.lines[self.edit_point.line].len_utf8()
@@ -884,23 +960,24 @@ impl<T: ClipboardProvider> TextInput<T> {
                 KeyReaction::RedrawSelection
             })
             .shortcut(CMD_OR_CONTROL, 'X', || {
-                if let Some(text) = self.get_selection_text() {
-                    self.clipboard_provider.set_clipboard_contents(text);
-                    self.delete_char(Direction::Backward);
-                }
+                self.cut();
                 KeyReaction::DispatchInput
             })
             .shortcut(CMD_OR_CONTROL, 'C', || {
-                if let Some(text) = self.get_selection_text() {
-                    self.clipboard_provider.set_clipboard_contents(text);
-                }
+                self.copy();
                 KeyReaction::DispatchInput
             })
             .shortcut(CMD_OR_CONTROL, 'V', || {
-                let contents = self.clipboard_provider.clipboard_contents();
-                self.insert_string(contents);
+                self.paste();
                 KeyReaction::DispatchInput
             })
+            .shortcut(CMD_OR_CONTROL, 'Z', || {
+                if self.undo() {
+                    KeyReaction::DispatchInput
+                } else {
+                    KeyReaction::Nothing
+                }
+            })
             .shortcut(Modifiers::empty(), Key::Delete, || {
                 self.delete_char(Direction::Forward);
                 KeyReaction::DispatchInput
@@ -977,11 +1054,115 @@ impl<T: ClipboardProvider> TextInput<T> {
             .unwrap()
     }
 
+    pub fn handle_compositionupdate(&mut self, event: &CompositionEvent) -> KeyReaction {
+        self.set_composition(event.data());
+        KeyReaction::DispatchInput
+    }
+
     pub fn handle_compositionend(&mut self, event: &CompositionEvent) -> KeyReaction {
-        self.insert_string(event.data());
+        self.commit_composition(event.data());
         KeyReaction::DispatchInput
     }
 
+    /// Replace the text being composed, or the selection when nothing is composed, with
+    /// `text`, which is then composed, with the edit point after it. Composing empty text
+    /// stops the composition.
+    pub fn set_composition(&mut self, text: &str) {
+        self.select_composition();
+        let start = self.selection_start_offset();
+        self.insert_string(text);
+        let end = self.text_point_to_offset(&self.edit_point);
+        if start != end {
+            self.composition = Some(start..end);
+        }
+    }
+
+    /// Replace the text being composed, or the selection when nothing is composed, with
+    /// `text`, and stop composing.
+    pub fn commit_composition(&mut self, text: &str) {
+        self.select_composition();
+        self.insert_string(text);
+    }
+
+    fn select_composition(&mut self) {
+        if let Some(composition) = self.composition.clone() {
+            let UTF8Bytes(start) = composition.start;
+            let UTF8Bytes(end) = composition.end;
+            self.set_selection_range(start as u32, end as u32, SelectionDirection::None);
+        }
+    }
+
+    /// Select `range`, in UTF-16 code units of the text being composed, which input
+    /// methods use to show where the cursor is in it.
+    pub fn select_in_composition(&mut self, range: Range<UTF16CodeUnits>) {
+        let composition = match self.composition.clone() {
+            Some(composition) => composition,
+            None => return,
+        };
+        let content = self.get_content();
+        let composed = &content[UTF8Bytes::unwrap_range(composition.clone())];
+        let UTF8Bytes(start) = composition.start + len_of_first_n_code_units(composed, range.start);
+        let UTF8Bytes(end) = composition.start + len_of_first_n_code_units(composed, range.end);
+        self.set_selection_range(start as u32, end as u32, SelectionDirection::None);
+    }
+
+    /// The text being composed, as a range of UTF-16 code units of the content.
+    pub fn composition_utf16_range(&self) -> Option<Range<UTF16CodeUnits>> {
+        let composition = self.composition.clone()?;
+        Some(self.utf16_offset(composition.start)..self.utf16_offset(composition.end))
+    }
+
+    /// The selection, as a range of UTF-16 code units of the content.
+    pub fn selection_utf16_range(&self) -> Range<UTF16CodeUnits> {
+        self.utf16_offset(self.selection_start_offset())..
+            self.utf16_offset(self.selection_end_offset())
+    }
+
+    /// The byte offset of an offset in UTF-16 code units of the content.
+    pub fn utf8_offset(&self, offset: UTF16CodeUnits) -> UTF8Bytes {
+        len_of_first_n_code_units(&self.get_content(), offset)
+    }
+
+    fn utf16_offset(&self, UTF8Bytes(offset): UTF8Bytes) -> UTF16CodeUnits {
+        UTF16CodeUnits(self.get_content()[..offset].encode_utf16().count())
+    }
+
+    /// Delete `before` UTF-16 code units before the selection and `after` after it, which
+    /// input methods do to replace the text around the edit point.
+    pub fn delete_surrounding_text(&mut self, before: UTF16CodeUnits, after: UTF16CodeUnits) {
+        let UTF8Bytes(start) = self.selection_start_offset();
+        let UTF8Bytes(end) = self.selection_end_offset();
+        let content = self.get_content();
+        let UTF8Bytes(after_len) = len_of_first_n_code_units(&content[end..], after);
+        let mut before_len = 0;
+        let mut before_utf16_len = UTF16CodeUnits::zero();
+        for c in content[..start].chars().rev() {
+            before_utf16_len += UTF16CodeUnits(c.len_utf16());
+            if before_utf16_len > before {
+                break;
+            }
+            before_len += c.len_utf8();
+        }
+
+        self.set_selection_range(
+            end as u32,
+            (end + after_len) as u32,
+            SelectionDirection::None,
+        );
+        self.replace_selection(DOMString::new());
+        self.set_selection_range(
+            (start - before_len) as u32,
+            start as u32,
+            SelectionDirection::None,
+        );
+        self.replace_selection(DOMString::new());
+        self.set_selection_range(
+            (start - before_len) as u32,
+            (end - before_len) as u32,
+            SelectionDirection::None,
+        );
+    }
+
     /// Whether the content is empty.
     pub fn is_empty(&self) -> bool {
         self.lines.len() <= 1 && self.lines.get(0).map_or(true, |line| line.is_empty())
@@ -1048,6 +1229,8 @@ impl<T: ClipboardProvider> TextInput<T> {
         };
 
         self.was_last_change_by_set_content = true;
+        self.undo_stack.clear();
+        self.composition = None;
         self.edit_point = self.edit_point.constrain_to(&self.lines);
 
         if let Some(origin) = self.selection_origin {
@@ -34,6 +34,10 @@ use crate::dom::xmlhttprequest::XHRTimeoutCallback;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_thread::ScriptThread;
 
+/// The shortest delay of the timers of a hidden document when
+/// `js.timers.minimum_duration` isn't set, which makes them fire at most once a second.
+const DEFAULT_HIDDEN_MINIMUM_DURATION: u64 = 1000;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, JSTraceable, MallocSizeOf, Ord, PartialEq, PartialOrd)]
 pub struct OneshotTimerHandle(i32);
 
@@ -257,7 +261,10 @@ impl OneshotTimers {
     }
 
     pub fn slow_down(&self) {
-        let duration = pref!(js.timers.minimum_duration) as u64;
+        let duration = match pref!(js.timers.minimum_duration) {
+            duration if duration > 0 => duration as u64,
+            _ => DEFAULT_HIDDEN_MINIMUM_DURATION,
+        };
         self.js_timers.set_min_duration(MsDuration::new(duration));
     }
 
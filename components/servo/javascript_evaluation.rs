@@ -0,0 +1,74 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The future that `Servo::evaluate_javascript` returns. The script thread of the document
+//! sends the result as JSON, which the router thread of `ipc_channel` parses and wakes the
+//! task waiting for it with, so the embedder can await it in any executor.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use embedder_traits::JavaScriptEvaluationError;
+use ipc_channel::ipc::IpcReceiver;
+use ipc_channel::router::ROUTER;
+use log::warn;
+use serde_json::Value;
+
+#[derive(Default)]
+struct State {
+    result: Option<Result<Value, JavaScriptEvaluationError>>,
+    waker: Option<Waker>,
+}
+
+/// The result of a script that the embedder runs in a document, which is ready once the
+/// script ran and the promise it returned, if any, settled.
+pub struct JavaScriptEvaluation {
+    state: Arc<Mutex<State>>,
+}
+
+impl JavaScriptEvaluation {
+    pub(crate) fn new(receiver: IpcReceiver<Result<String, JavaScriptEvaluationError>>) -> Self {
+        let state = Arc::new(Mutex::new(State::default()));
+        let route_state = state.clone();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let result = match message.to::<Result<String, JavaScriptEvaluationError>>() {
+                    Ok(result) => result,
+                    Err(error) => {
+                        warn!("Failed to receive the result of a script ({:?}).", error);
+                        Err(JavaScriptEvaluationError::SerializationFailed)
+                    },
+                };
+                let result = result.and_then(|json| {
+                    serde_json::from_str(&json)
+                        .map_err(|_| JavaScriptEvaluationError::SerializationFailed)
+                });
+                let mut state = route_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            }),
+        );
+        Self { state }
+    }
+}
+
+impl Future for JavaScriptEvaluation {
+    type Output = Result<Value, JavaScriptEvaluationError>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(context.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
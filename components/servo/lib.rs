@@ -20,9 +20,11 @@
 use std::borrow::{BorrowMut, Cow};
 use std::cmp::max;
 use std::collections::HashMap;
+use std::fs;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use std::vec::Drain;
 
 use bluetooth::BluetoothThreadFactory;
@@ -30,7 +32,7 @@ use bluetooth_traits::BluetoothRequest;
 use canvas::canvas_paint_thread::{self, CanvasPaintThread};
 use canvas::WebGLComm;
 use canvas_traits::webgl::WebGLThreads;
-use compositing::windowing::{EmbedderEvent, EmbedderMethods, WindowMethods};
+use compositing::windowing::{EmbedderEvent, EmbedderMethods, InputEvent, WindowMethods};
 use compositing::{CompositeTarget, IOCompositor, InitialCompositorState, ShutdownState};
 use compositing_traits::{
     CanvasToCompositorMsg, CompositorMsg, CompositorProxy, CompositorReceiver, ConstellationMsg,
@@ -49,8 +51,12 @@ use constellation::{
     UnprivilegedContent,
 };
 use crossbeam_channel::{unbounded, Sender};
-use embedder_traits::{EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
+use embedder_traits::{
+    EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker, FrameSelector, UserScript,
+    UserScriptId,
+};
 use env_logger::Builder as EnvLoggerBuilder;
+use euclid::default::Size2D;
 use euclid::Scale;
 #[cfg(all(
     not(target_os = "windows"),
@@ -68,15 +74,17 @@ use ipc_channel::ipc::{self, IpcSender};
 use log::{error, trace, warn, Log, Metadata, Record};
 use media::{GLPlayerThreads, GlApi, NativeDisplay, WindowGLContext};
 pub use msg::constellation_msg::TopLevelBrowsingContextId;
-use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId};
+use msg::constellation_msg::{PipelineNamespace, PipelineNamespaceId, WebViewGroupId};
 use net::resource_thread::new_resource_threads;
-use net_traits::IpcSend;
+use net_traits::protocols::ProtocolRegistry;
+use net_traits::request_interceptor::RequestInterceptors;
+use net_traits::{ClearDataScope, ClearDataTypes, IpcSend};
 use profile::{mem as profile_mem, time as profile_time};
 use profile_traits::{mem, time};
 use script::serviceworker_manager::ServiceWorkerManager;
 use script::JSEngineSetup;
 use script_layout_interface::LayoutFactory;
-use script_traits::{ScriptToConstellationChan, WindowSizeData};
+use script_traits::{MemoryPressureLevel, ScriptToConstellationChan, WindowSizeData};
 use servo_config::{opts, pref, prefs};
 use servo_media::player::context::GlContext;
 use servo_media::ServoMedia;
@@ -88,9 +96,10 @@ use surfman::{GLApi, GLVersion};
 #[cfg(target_os = "linux")]
 use surfman::{NativeConnection, NativeContext};
 use webrender::{RenderApiSender, ShaderPrecacheFlags};
-use webrender_api::{DocumentId, FontInstanceKey, FontKey, ImageKey};
+use webrender_api::{DocumentId, ExternalImageId, FontInstanceKey, FontKey, ImageKey};
 use webrender_traits::{
-    WebrenderExternalImageHandlers, WebrenderExternalImageRegistry, WebrenderImageHandlerType,
+    WebrenderExternalImageApi, WebrenderExternalImageHandlers, WebrenderExternalImageRegistry,
+    WebrenderImageHandlerType, WebrenderImageSource,
 };
 pub use {
     background_hang_monitor, bluetooth, bluetooth_traits, canvas, canvas_traits, compositing,
@@ -101,6 +110,11 @@ pub use {
     webrender_api, webrender_traits,
 };
 
+mod javascript_evaluation;
+mod program_cache;
+
+pub use javascript_evaluation::JavaScriptEvaluation;
+
 #[cfg(feature = "webdriver")]
 fn webdriver(port: u16, constellation: Sender<ConstellationMsg>) {
     webdriver_server::start_server(port, constellation);
@@ -116,12 +130,63 @@ mod media_platform {
         include!(concat!(env!("OUT_DIR"), "/gstreamer_plugins.rs"));
     }
 
+    use std::env;
+
+    use servo_config::pref;
     use servo_media_gstreamer::GStreamerBackend;
 
     use super::ServoMedia;
 
+    /// Hardware video decoders whose rank is raised above the software decoders, so that
+    /// GStreamer picks them when they're available.
+    #[cfg(target_os = "linux")]
+    const HARDWARE_DECODERS: &[&str] = &[
+        "vah264dec",
+        "vah265dec",
+        "vavp8dec",
+        "vavp9dec",
+        "vaav1dec",
+        "v4l2h264dec",
+        "v4l2h265dec",
+        "v4l2vp8dec",
+        "v4l2vp9dec",
+        "v4l2slh264dec",
+        "v4l2slvp8dec",
+        "v4l2slvp9dec",
+    ];
+    #[cfg(windows)]
+    const HARDWARE_DECODERS: &[&str] = &[
+        "d3d11h264dec",
+        "d3d11h265dec",
+        "d3d11vp8dec",
+        "d3d11vp9dec",
+        "d3d11av1dec",
+    ];
+    #[cfg(target_os = "macos")]
+    const HARDWARE_DECODERS: &[&str] = &["vtdec_hw"];
+    #[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+    const HARDWARE_DECODERS: &[&str] = &[];
+
+    /// Raise the rank of the hardware decoders so that decodebin picks them when available.
+    /// This has to happen before GStreamer is initialized, and any ranks the user already set
+    /// through `GST_PLUGIN_FEATURE_RANK` take precedence.
+    fn prefer_hardware_decoders() {
+        if !pref!(media.hardware_decode.enabled) || HARDWARE_DECODERS.is_empty() {
+            return;
+        }
+        let mut ranks: Vec<String> = HARDWARE_DECODERS
+            .iter()
+            .map(|decoder| format!("{}:MAX", decoder))
+            .collect();
+        if let Ok(existing) = env::var("GST_PLUGIN_FEATURE_RANK") {
+            ranks.push(existing);
+        }
+        env::set_var("GST_PLUGIN_FEATURE_RANK", ranks.join(","));
+    }
+
     #[cfg(any(windows, target_os = "macos"))]
     pub fn init() {
+        prefer_hardware_decoders();
         ServoMedia::init_with_backend(|| {
             let mut plugin_dir = std::env::current_exe().unwrap();
             plugin_dir.pop();
@@ -145,6 +210,7 @@ mod media_platform {
 
     #[cfg(not(any(windows, target_os = "macos")))]
     pub fn init() {
+        prefer_hardware_decoders();
         ServoMedia::init::<GStreamerBackend>();
     }
 }
@@ -174,6 +240,12 @@ pub struct Servo<Window: WindowMethods + 'static + ?Sized> {
     embedder_receiver: EmbedderReceiver,
     messages_for_embedder: Vec<(Option<TopLevelBrowsingContextId>, EmbedderMsg)>,
     profiler_enabled: bool,
+    /// The interceptors that see the requests of fetch before they are loaded.
+    request_interceptors: RequestInterceptors,
+    /// The id of the next script that the embedder adds to inject into documents.
+    next_user_script_id: u64,
+    /// Locks the front buffers of WebGL canvases shared with the embedder.
+    canvas_texture_handler: Box<dyn WebrenderExternalImageApi>,
     /// For single-process Servo instances, this field controls the initialization
     /// and deinitialization of the JS Engine. Multiprocess Servo instances have their
     /// own instance that exists in the content process instead.
@@ -337,6 +409,10 @@ where
                         !opts.debug.disable_subpixel_text_antialiasing,
                     allow_texture_swizzling: pref!(gfx.texture_swizzling.enabled),
                     clear_color: None,
+                    cached_programs: opts
+                        .config_dir
+                        .as_deref()
+                        .map(program_cache::create_program_cache),
                     ..Default::default()
                 },
                 None,
@@ -368,6 +444,7 @@ where
             webgl_threads,
             webxr_layer_grand_manager,
             image_handler,
+            canvas_texture_handler,
         } = WebGLComm::new(
             rendering_context.clone(),
             webrender_api.create_sender(),
@@ -409,10 +486,18 @@ where
             device_pixel_ratio: Scale::new(device_pixel_ratio),
         };
 
+        // The schemes of the embedder that are secure are potentially trustworthy everywhere,
+        // not only in the network threads that load them.
+        let protocols = Arc::new(embedder.get_protocol_handlers());
+        servo_url::set_secure_schemes(protocols.secure_schemes());
+        let request_interceptors = RequestInterceptors::default();
+
         // Create the constellation, which maintains the engine pipelines, including script and
         // layout, as well as the navigation context.
         let constellation_chan = create_constellation(
             user_agent,
+            protocols,
+            request_interceptors.clone(),
             opts.config_dir.clone(),
             embedder_proxy,
             compositor_proxy.clone(),
@@ -471,6 +556,9 @@ where
             embedder_receiver: embedder_receiver,
             messages_for_embedder: Vec::new(),
             profiler_enabled: false,
+            request_interceptors,
+            next_user_script_id: 0,
+            canvas_texture_handler,
             _js_engine_setup: js_engine_setup,
         };
         InitializedServo {
@@ -600,8 +688,8 @@ where
                     .replace_native_surface(native_widget, coords);
                 self.compositor.composite();
             },
-            EmbedderEvent::AllowNavigationResponse(pipeline_id, allowed) => {
-                let msg = ConstellationMsg::AllowNavigationResponse(pipeline_id, allowed);
+            EmbedderEvent::AllowNavigationResponse(pipeline_id, policy) => {
+                let msg = ConstellationMsg::AllowNavigationResponse(pipeline_id, policy);
                 if let Err(e) = self.constellation_chan.send(msg) {
                     warn!(
                         "Sending allow navigation to constellation failed ({:?}).",
@@ -624,6 +712,64 @@ where
                 }
             },
 
+            EmbedderEvent::SetPermission(scope, decision) => {
+                let msg = ConstellationMsg::SetPermission(scope, decision);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetPermission to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::GetPermissionDecisions => {
+                let msg = ConstellationMsg::GetPermissionDecisions;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GetPermissionDecisions to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::ClearPermissionDecisions(origin) => {
+                let msg = ConstellationMsg::ClearPermissionDecisions(origin);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ClearPermissionDecisions to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::GetCookies(scope, url) => {
+                let msg = ConstellationMsg::GetCookies(scope, url);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending GetCookies to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetCookie(scope, cookie) => {
+                let msg = ConstellationMsg::SetCookie(scope, cookie);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetCookie to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::DeleteCookie(scope, cookie) => {
+                let msg = ConstellationMsg::DeleteCookie(scope, cookie);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending DeleteCookie to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ObserveCookieChanges(observe) => {
+                let msg = ConstellationMsg::ObserveCookieChanges(observe);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ObserveCookieChanges to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::MouseWindowEventClass(mouse_window_event) => {
                 self.compositor
                     .on_mouse_window_event_class(mouse_window_event);
@@ -667,6 +813,16 @@ where
                 }
             },
 
+            EmbedderEvent::GoToHistoryIndex(top_level_browsing_context_id, index) => {
+                let msg = ConstellationMsg::GoToHistoryIndex(top_level_browsing_context_id, index);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GoToHistoryIndex to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::Keyboard(key_event) => {
                 let msg = ConstellationMsg::Keyboard(key_event);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -695,6 +851,17 @@ where
                 }
             },
 
+            EmbedderEvent::FullscreenEntered(top_level_browsing_context_id, entered) => {
+                let msg =
+                    ConstellationMsg::FullscreenEntered(top_level_browsing_context_id, entered);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending FullscreenEntered to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::Reload(top_level_browsing_context_id) => {
                 let msg = ConstellationMsg::Reload(top_level_browsing_context_id);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -723,7 +890,11 @@ where
             },
 
             EmbedderEvent::NewWebView(url, top_level_browsing_context_id) => {
-                let msg = ConstellationMsg::NewWebView(url, top_level_browsing_context_id);
+                let msg = ConstellationMsg::NewWebView(
+                    url,
+                    top_level_browsing_context_id,
+                    WebViewGroupId::default(),
+                );
                 if let Err(e) = self.constellation_chan.send(msg) {
                     warn!(
                         "Sending NewBrowser message to constellation failed ({:?}).",
@@ -732,8 +903,19 @@ where
                 }
             },
 
-            EmbedderEvent::FocusWebView(top_level_browsing_context_id) => {
-                let msg = ConstellationMsg::FocusWebView(top_level_browsing_context_id);
+            EmbedderEvent::NewWebViewInGroup(url, top_level_browsing_context_id, group_id) => {
+                let msg =
+                    ConstellationMsg::NewWebView(url, top_level_browsing_context_id, group_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending NewWebViewInGroup message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::FocusWebView(top_level_browsing_context_id, reason) => {
+                let msg = ConstellationMsg::FocusWebView(top_level_browsing_context_id, reason);
                 if let Err(e) = self.constellation_chan.send(msg) {
                     warn!(
                         "Sending FocusBrowser message to constellation failed ({:?}).",
@@ -742,6 +924,33 @@ where
                 }
             },
 
+            EmbedderEvent::BlurWebView(reason) => {
+                let msg = ConstellationMsg::BlurWebView(reason);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending BlurWebView to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::FocusPreviousWebView => {
+                let msg = ConstellationMsg::FocusPreviousWebView;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending FocusPreviousWebView to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::FocusNextWebView => {
+                let msg = ConstellationMsg::FocusNextWebView;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending FocusNextWebView to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::CloseWebView(top_level_browsing_context_id) => {
                 let msg = ConstellationMsg::CloseWebView(top_level_browsing_context_id);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -772,6 +981,26 @@ where
                 }
             },
 
+            EmbedderEvent::PictureInPictureAction(a) => {
+                let msg = ConstellationMsg::PictureInPictureAction(a);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending PictureInPictureAction message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::ShareCanvasTexture(webview_id, selector) => {
+                let msg = ConstellationMsg::ShareCanvasTexture(webview_id, selector);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ShareCanvasTexture message to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::WebViewVisibilityChanged(webview_id, visible) => {
                 let msg = ConstellationMsg::WebViewVisibilityChanged(webview_id, visible);
                 if let Err(e) = self.constellation_chan.send(msg) {
@@ -782,12 +1011,448 @@ where
                 }
             },
 
+            EmbedderEvent::MarkAllWebViewsShown => {
+                let msg = ConstellationMsg::MarkAllWebViewsShown;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending MarkAllWebViewsShown to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::MarkAllWebViewsNotShown => {
+                let msg = ConstellationMsg::MarkAllWebViewsNotShown;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending MarkAllWebViewsNotShown to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             EmbedderEvent::Gamepad(gamepad_event) => {
                 let msg = ConstellationMsg::Gamepad(gamepad_event);
                 if let Err(e) = self.constellation_chan.send(msg) {
                     warn!("Sending Gamepad event to constellation failed ({:?}).", e);
                 }
             },
+
+            EmbedderEvent::SensorReading(reading) => {
+                let msg = ConstellationMsg::SensorReading(reading);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SensorReading to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::EditingCommand(top_level_browsing_context_id, command) => {
+                let msg = ConstellationMsg::EditingCommand(top_level_browsing_context_id, command);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending EditingCommand to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::GetSelection(top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::GetSelection(top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending GetSelection to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetCaretBrowsing(top_level_browsing_context_id, enabled) => {
+                let msg =
+                    ConstellationMsg::SetCaretBrowsing(top_level_browsing_context_id, enabled);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetCaretBrowsing to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetJsGcSettings(top_level_browsing_context_id, settings) => {
+                let msg =
+                    ConstellationMsg::SetJsGcSettings(top_level_browsing_context_id, settings);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetJsGcSettings to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ChangePaintingOrder(top_level_browsing_context_id, change) => {
+                let msg =
+                    ConstellationMsg::ChangePaintingOrder(top_level_browsing_context_id, change);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ChangePaintingOrder to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetWebViewLifecycleState(top_level_browsing_context_id, state) => {
+                let msg = ConstellationMsg::SetWebViewLifecycleState(
+                    top_level_browsing_context_id,
+                    state,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewLifecycleState to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::GetSessionState => {
+                let msg = ConstellationMsg::GetSessionState;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending GetSessionState to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::RestoreSession(session_state) => {
+                let msg = ConstellationMsg::RestoreSession(session_state);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending RestoreSession to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWebViewDevicePixelRatio(top_level_browsing_context_id, ratio) => {
+                let msg = ConstellationMsg::SetWebViewDevicePixelRatio(
+                    top_level_browsing_context_id,
+                    ratio,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewDevicePixelRatio to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::ResizeWebView(webview_id, size) => {
+                let msg = ConstellationMsg::ResizeWebView(webview_id, size);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ResizeWebView to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::GetWebViewMetadata => {
+                let msg = ConstellationMsg::GetWebViewMetadata;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GetWebViewMetadata to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::DuplicateWebView(webview_id) => {
+                let msg = ConstellationMsg::DuplicateWebView(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending DuplicateWebView to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::ReloadCrashedWebView(webview_id) => {
+                let msg = ConstellationMsg::ReloadCrashedWebView(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ReloadCrashedWebView to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetWebViewMuted(webview_id, muted) => {
+                let msg = ConstellationMsg::SetWebViewMuted(webview_id, muted);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetWebViewMuted to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::GetFrameTree(webview_id) => {
+                let msg = ConstellationMsg::GetFrameTree(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending GetFrameTree to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWebViewLimit(limit) => {
+                let msg = ConstellationMsg::SetWebViewLimit(limit);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetWebViewLimit to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWebViewCsp(webview_id, policy) => {
+                let msg = ConstellationMsg::SetWebViewCsp(webview_id, policy);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetWebViewCsp to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWebViewLanguages(webview_id, languages) => {
+                let msg = ConstellationMsg::SetWebViewLanguages(webview_id, languages);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewLanguages to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::NewPrerenderWebView(url, top_level_browsing_context_id) => {
+                let msg = ConstellationMsg::NewPrerenderWebView(url, top_level_browsing_context_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending NewPrerenderWebView to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::ActivatePrerender(webview_id) => {
+                let msg = ConstellationMsg::ActivatePrerender(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ActivatePrerender to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetWebViewPriority(webview_id, priority) => {
+                let msg = ConstellationMsg::SetWebViewPriority(webview_id, priority);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewPriority to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetConstellationTracing(capacity) => {
+                let msg = ConstellationMsg::SetConstellationTracing(capacity);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetConstellationTracing to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::GetConstellationTrace => {
+                let msg = ConstellationMsg::GetConstellationTrace;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GetConstellationTrace to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::GetWebViewMemoryUsage => {
+                let msg = ConstellationMsg::GetWebViewMemoryUsage;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GetWebViewMemoryUsage to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SuspendWebView(webview_id) => {
+                let msg = ConstellationMsg::SuspendWebView(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SuspendWebView to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ResumeWebView(webview_id) => {
+                let msg = ConstellationMsg::ResumeWebView(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ResumeWebView to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWebViewBackgroundColor(webview_id, color) => {
+                let msg = ConstellationMsg::SetWebViewBackgroundColor(webview_id, color);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewBackgroundColor to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetWebViewTransparent(webview_id, transparent) => {
+                let msg = ConstellationMsg::SetWebViewTransparent(webview_id, transparent);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetWebViewTransparent to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::Find(webview_id, query, options) => {
+                let msg = ConstellationMsg::Find(webview_id, query, options);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending Find to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::FindNext(webview_id) => {
+                let msg = ConstellationMsg::FindNext(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending FindNext to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::FindPrevious(webview_id) => {
+                let msg = ConstellationMsg::FindPrevious(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending FindPrevious to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::StopFinding(webview_id) => {
+                let msg = ConstellationMsg::StopFinding(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending StopFinding to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::NewPrivateWebView(url, top_level_browsing_context_id, group_id) => {
+                let msg = ConstellationMsg::NewPrivateWebView(
+                    url,
+                    top_level_browsing_context_id,
+                    group_id,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending NewPrivateWebView to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::OpenBlockedPopup(popup) => {
+                let msg = ConstellationMsg::OpenBlockedPopup(popup);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending OpenBlockedPopup to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::GetPendingNavigations(webview_id) => {
+                let msg = ConstellationMsg::GetPendingNavigations(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GetPendingNavigations to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::CancelPendingNavigation(webview_id) => {
+                let msg = ConstellationMsg::CancelPendingNavigation(webview_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending CancelPendingNavigation to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::SetWebViewRenderTarget(webview_id, target) => {
+                self.compositor
+                    .set_webview_render_target(webview_id, target);
+            },
+
+            EmbedderEvent::ExportWebViewFrames(webview_id, formats) => {
+                self.compositor.export_webview_frames(webview_id, formats);
+            },
+
+            EmbedderEvent::ReadBackWebViewFrames(webview_id, read) => {
+                self.compositor.read_back_webview_frames(webview_id, read);
+            },
+
+            EmbedderEvent::AttachWebViewSurface(webview_id, native_widget) => {
+                self.compositor
+                    .attach_webview_surface(webview_id, native_widget);
+            },
+
+            EmbedderEvent::CaptureScreenshot(webview_id, rect, scale_mode, format, sender) => {
+                let msg = ConstellationMsg::CaptureScreenshot(
+                    webview_id, rect, scale_mode, format, sender,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending CaptureScreenshot to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::PrintToPdf(webview_id, settings, sender) => {
+                let msg = ConstellationMsg::PrintToPdf(webview_id, settings, sender);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending PrintToPdf to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::InjectInputEvents(webview_id, events) => {
+                for event in events {
+                    match event {
+                        InputEvent::Keyboard(key_event) => {
+                            let msg = ConstellationMsg::WebViewKeyboard(webview_id, key_event);
+                            if let Err(e) = self.constellation_chan.send(msg) {
+                                warn!("Sending WebViewKeyboard to constellation failed ({:?}).", e);
+                            }
+                        },
+                        event => self.compositor.on_injected_input_event(webview_id, event),
+                    }
+                }
+            },
+
+            EmbedderEvent::SetWebViewGestureSettings(webview_id, settings) => {
+                self.compositor
+                    .set_webview_gesture_settings(webview_id, settings);
+            },
+
+            EmbedderEvent::Ime(webview_id, event) => {
+                let msg = ConstellationMsg::Ime(webview_id, event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending Ime to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::GetImeSurroundingText(webview_id, sender) => {
+                let msg = ConstellationMsg::GetImeSurroundingText(webview_id, sender);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending GetImeSurroundingText to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::ContextMenuAction(webview_id, browsing_context_id, action) => {
+                let msg =
+                    ConstellationMsg::ContextMenuAction(webview_id, browsing_context_id, action);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending ContextMenuAction to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
         }
         return false;
     }
@@ -848,6 +1513,90 @@ where
         self.compositor.pinch_zoom_level()
     }
 
+    /// The interceptors that see every request before it is loaded, including those of
+    /// redirects. Interceptors can be added and removed at any time, for every webview or
+    /// for one of them.
+    pub fn request_interceptors(&self) -> &RequestInterceptors {
+        &self.request_interceptors
+    }
+
+    /// Inject a script or stylesheet into the documents of a webview, or of every webview,
+    /// whose URLs match its patterns. It is injected into the documents loaded from now on,
+    /// including the ones that are loading, in the order the scripts were added in.
+    pub fn add_user_script(
+        &mut self,
+        webview_id: Option<TopLevelBrowsingContextId>,
+        script: UserScript,
+    ) -> UserScriptId {
+        let id = UserScriptId(self.next_user_script_id);
+        self.next_user_script_id += 1;
+        let msg = ConstellationMsg::AddUserScript(id, webview_id, script);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending AddUserScript to constellation failed ({:?}).", e);
+        }
+        id
+    }
+
+    /// Stop injecting a script into documents. The documents that it was injected into
+    /// keep its effects.
+    pub fn remove_user_script(&self, id: UserScriptId) {
+        let msg = ConstellationMsg::RemoveUserScript(id);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!(
+                "Sending RemoveUserScript to constellation failed ({:?}).",
+                e
+            );
+        }
+    }
+
+    /// Run a script in a frame of a webview, like a classic script of its document, and
+    /// get its completion value as JSON. When the value is a promise, the future is ready
+    /// once it settles. Values that can't be serialized to JSON fail the evaluation.
+    pub fn evaluate_javascript(
+        &self,
+        webview_id: TopLevelBrowsingContextId,
+        frame: FrameSelector,
+        source: impl Into<String>,
+    ) -> JavaScriptEvaluation {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let msg = ConstellationMsg::EvaluateJavaScript(webview_id, frame, source.into(), sender);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!(
+                "Sending EvaluateJavaScript to constellation failed ({:?}).",
+                e
+            );
+        }
+        JavaScriptEvaluation::new(receiver)
+    }
+
+    /// Remove the given kinds of data of an origin, a webview group or everything. With a
+    /// `time_range`, only the cookies and HSTS hosts stored since then are removed; other
+    /// kinds of data are removed regardless of when they were stored.
+    pub fn clear_browsing_data(
+        &self,
+        scope: ClearDataScope,
+        data_types: ClearDataTypes,
+        time_range: Option<SystemTime>,
+    ) {
+        let msg = ConstellationMsg::ClearData(scope, data_types, time_range);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending ClearData to constellation failed ({:?}).", e);
+        }
+    }
+
+    /// Free memory in response to the device running low on it: WebRender's texture
+    /// cache, unused decoded images and fonts, and the garbage of every script thread.
+    /// At [`MemoryPressureLevel::Critical`], the documents kept for going back and
+    /// forward in session history are discarded as well, and loaded again when they are
+    /// navigated to.
+    pub fn notify_memory_pressure(&self, level: MemoryPressureLevel) {
+        self.compositor.notify_memory_pressure();
+        let msg = ConstellationMsg::MemoryPressure(level);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending MemoryPressure to constellation failed ({:?}).", e);
+        }
+    }
+
     pub fn setup_logging(&self) {
         let constellation_chan = self.constellation_chan.clone();
         let env = env_logger::Env::default();
@@ -882,6 +1631,27 @@ where
     pub fn offscreen_framebuffer_id(&self) -> Option<u32> {
         self.compositor.offscreen_framebuffer_id()
     }
+
+    /// Lock the most recently presented frame of a canvas shared through
+    /// [`EmbedderMsg::CanvasTextureShared`], returning the OpenGL texture name and size.
+    ///
+    /// The texture belongs to Servo's rendering context. The canvas cannot present
+    /// into it until [`Servo::unlock_canvas_texture`] is called, so the embedder should
+    /// unlock it once the commands sampling it have been submitted, and lock it again on
+    /// the next frame.
+    pub fn lock_canvas_texture(&mut self, image: ExternalImageId) -> Option<(u32, Size2D<i32>)> {
+        match self.canvas_texture_handler.lock(image.0) {
+            (WebrenderImageSource::TextureHandle(texture_id), size) if texture_id != 0 => {
+                Some((texture_id, size))
+            },
+            _ => None,
+        }
+    }
+
+    /// Give back a texture locked with [`Servo::lock_canvas_texture`].
+    pub fn unlock_canvas_texture(&mut self, image: ExternalImageId) {
+        self.canvas_texture_handler.unlock(image.0);
+    }
 }
 
 fn create_embedder_channel(
@@ -912,6 +1682,8 @@ fn create_compositor_channel(
 
 fn create_constellation(
     user_agent: Cow<'static, str>,
+    protocols: Arc<ProtocolRegistry>,
+    request_interceptors: RequestInterceptors,
     config_dir: Option<PathBuf>,
     embedder_proxy: EmbedderProxy,
     compositor_proxy: CompositorProxy,
@@ -934,8 +1706,52 @@ fn create_constellation(
     let bluetooth_thread: IpcSender<BluetoothRequest> =
         BluetoothThreadFactory::new(embedder_proxy.clone());
 
+    // Each webview group other than the default keeps its persistent data in its own
+    // directory, so that groups behave like separate profiles.
+    let new_group_resource_threads = {
+        let user_agent = user_agent.clone();
+        let protocols = protocols.clone();
+        let request_interceptors = request_interceptors.clone();
+        let devtools_sender = devtools_sender.clone();
+        let time_profiler_chan = time_profiler_chan.clone();
+        let mem_profiler_chan = mem_profiler_chan.clone();
+        let embedder_proxy = embedder_proxy.clone();
+        let config_dir = config_dir.clone();
+        let certificate_path = opts.certificate_path.clone();
+        let ignore_certificate_errors = opts.ignore_certificate_errors;
+        Box::new(move |group_id: WebViewGroupId| {
+            let group_config_dir = config_dir.as_ref().map(|config_dir| {
+                config_dir
+                    .join("webview_groups")
+                    .join(group_id.0.to_string())
+            });
+            if let Some(ref group_config_dir) = group_config_dir {
+                if let Err(error) = fs::create_dir_all(group_config_dir) {
+                    warn!(
+                        "Could not create config dir for {} at {:?}: {}",
+                        group_id, group_config_dir, error
+                    );
+                }
+            }
+            new_resource_threads(
+                user_agent.clone(),
+                protocols.clone(),
+                request_interceptors.clone(),
+                devtools_sender.clone(),
+                time_profiler_chan.clone(),
+                mem_profiler_chan.clone(),
+                embedder_proxy.clone(),
+                group_config_dir,
+                certificate_path.clone(),
+                ignore_certificate_errors,
+            )
+        })
+    };
+
     let (public_resource_threads, private_resource_threads) = new_resource_threads(
         user_agent.clone(),
+        protocols,
+        request_interceptors.clone(),
         devtools_sender.clone(),
         time_profiler_chan.clone(),
         mem_profiler_chan.clone(),
@@ -963,6 +1779,7 @@ fn create_constellation(
         font_cache_thread,
         public_resource_threads,
         private_resource_threads,
+        new_group_resource_threads,
         time_profiler_chan,
         mem_profiler_chan,
         webrender_document,
@@ -972,6 +1789,7 @@ fn create_constellation(
         glplayer_threads,
         player_context,
         user_agent,
+        request_interceptors,
         webrender_external_images: external_images,
         wgpu_image_map,
     };
@@ -1097,6 +1915,7 @@ pub fn run_content_process(token: String) {
     prefs::pref_map()
         .set_all(unprivileged_content.prefs())
         .expect("Failed to set preferences");
+    servo_url::set_secure_schemes(unprivileged_content.secure_schemes());
 
     // Enter the sandbox if necessary.
     if opts::get().sandbox {
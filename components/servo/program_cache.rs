@@ -0,0 +1,129 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A cache of the shader programs that WebRender compiles, kept on disk so that they
+//! don't have to be compiled again every time Servo starts.
+//!
+//! Each program binary is stored in a file named after the digest of its source, which
+//! WebRender computes from the shader source and the name of the GL renderer, so that a
+//! program is never loaded for a different version of the shaders or a different GPU.
+//! The digests of the programs used during startup are listed in a separate file, and
+//! those programs are loaded before the first frame. Entries that can't be read, such as
+//! those written by a build that serialized program binaries differently, are removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use log::{debug, warn};
+use webrender::{ProgramBinary, ProgramCache, ProgramCacheObserver, ProgramSourceDigest};
+
+/// The file listing the digests of the programs used during startup.
+const STARTUP_SHADERS_FILE: &str = "startup_shaders";
+
+/// Create a program cache backed by a directory in `config_dir`, with the programs that
+/// were used during the last startup already loaded.
+pub fn create_program_cache(config_dir: &Path) -> Rc<ProgramCache> {
+    let directory = config_dir.join("shader_cache");
+    if let Err(error) = fs::create_dir_all(&directory) {
+        warn!(
+            "Could not create shader cache at {:?}: {}",
+            directory, error
+        );
+    }
+
+    let disk_cache = DiskProgramCache { directory };
+    let startup_programs = disk_cache.load_startup_programs();
+    let program_cache = ProgramCache::new(Some(Box::new(disk_cache)));
+    for program in startup_programs {
+        program_cache.load_program_binary(program);
+    }
+    program_cache
+}
+
+struct DiskProgramCache {
+    directory: PathBuf,
+}
+
+impl DiskProgramCache {
+    fn path_for_digest(&self, digest: &ProgramSourceDigest) -> PathBuf {
+        self.directory.join(digest.to_string())
+    }
+
+    fn load_program(&self, digest: &str) -> Option<Arc<ProgramBinary>> {
+        let path = self.directory.join(digest);
+        let bytes = fs::read(&path).ok()?;
+        match bincode::deserialize(&bytes) {
+            Ok(program) => Some(Arc::new(program)),
+            Err(error) => {
+                warn!(
+                    "Removing unreadable shader cache entry {:?}: {}",
+                    path, error
+                );
+                let _ = fs::remove_file(&path);
+                None
+            },
+        }
+    }
+
+    fn load_startup_programs(&self) -> Vec<Arc<ProgramBinary>> {
+        let list = match fs::read_to_string(self.directory.join(STARTUP_SHADERS_FILE)) {
+            Ok(list) => list,
+            Err(_) => return vec![],
+        };
+        list.lines()
+            .filter_map(|digest| self.load_program(digest))
+            .collect()
+    }
+}
+
+impl ProgramCacheObserver for DiskProgramCache {
+    fn save_shaders_to_disk(&self, entries: Vec<Arc<ProgramBinary>>) {
+        for entry in entries {
+            let path = self.path_for_digest(entry.source_digest());
+            if path.exists() {
+                continue;
+            }
+            let bytes = match bincode::serialize(&*entry) {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    warn!("Could not serialize shader program: {}", error);
+                    continue;
+                },
+            };
+            if let Err(error) = fs::write(&path, bytes) {
+                warn!("Could not write shader cache entry {:?}: {}", path, error);
+            }
+        }
+    }
+
+    fn set_startup_shaders(&self, entries: Vec<Arc<ProgramBinary>>) {
+        let list: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.source_digest().to_string())
+            .collect();
+        let path = self.directory.join(STARTUP_SHADERS_FILE);
+        if let Err(error) = fs::write(&path, list.join("\n")) {
+            warn!("Could not write startup shader list {:?}: {}", path, error);
+        }
+    }
+
+    fn try_load_shader_from_disk(
+        &self,
+        digest: &ProgramSourceDigest,
+        program_cache: &Rc<ProgramCache>,
+    ) {
+        if let Some(program) = self.load_program(&digest.to_string()) {
+            debug!("Loaded shader program {} from the disk cache", digest);
+            program_cache.load_program_binary(program);
+        }
+    }
+
+    fn notify_program_binary_failed(&self, program_binary: &Arc<ProgramBinary>) {
+        // The driver rejected the binary, most likely because it was updated since the
+        // binary was saved, so compile the program again next time instead.
+        let _ = fs::remove_file(self.path_for_digest(program_binary.source_digest()));
+    }
+}
@@ -918,6 +918,10 @@ mod gl_ext_constants {
     pub const COMPRESSED_RGBA_S3TC_DXT3_EXT: GLenum = 0x83F2;
     pub const COMPRESSED_RGBA_S3TC_DXT5_EXT: GLenum = 0x83F3;
     pub const COMPRESSED_RGB_ETC1_WEBGL: GLenum = 0x8D64;
+    pub const COMPRESSED_SRGB_S3TC_DXT1_EXT: GLenum = 0x8C4C;
+    pub const COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT: GLenum = 0x8C4D;
+    pub const COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT: GLenum = 0x8C4E;
+    pub const COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT: GLenum = 0x8C4F;
 
     pub static COMPRESSIONS: &[GLenum] = &[
         COMPRESSED_RGB_S3TC_DXT1_EXT,
@@ -925,6 +929,10 @@ mod gl_ext_constants {
         COMPRESSED_RGBA_S3TC_DXT3_EXT,
         COMPRESSED_RGBA_S3TC_DXT5_EXT,
         COMPRESSED_RGB_ETC1_WEBGL,
+        COMPRESSED_SRGB_S3TC_DXT1_EXT,
+        COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+        COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+        COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
     ];
 
     pub const ALPHA16F_ARB: u32 = 0x881C;
@@ -961,6 +969,10 @@ gl_enums! {
         CompressedRgbaS3tcDxt3 = gl_ext_constants::COMPRESSED_RGBA_S3TC_DXT3_EXT,
         CompressedRgbaS3tcDxt5 = gl_ext_constants::COMPRESSED_RGBA_S3TC_DXT5_EXT,
         CompressedRgbEtc1 = gl_ext_constants::COMPRESSED_RGB_ETC1_WEBGL,
+        CompressedSrgbS3tcDxt1 = gl_ext_constants::COMPRESSED_SRGB_S3TC_DXT1_EXT,
+        CompressedSrgbAlphaS3tcDxt1 = gl_ext_constants::COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+        CompressedSrgbAlphaS3tcDxt3 = gl_ext_constants::COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+        CompressedSrgbAlphaS3tcDxt5 = gl_ext_constants::COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
         R8 = gl::R8,
         R8SNorm = gl::R8_SNORM,
         R16f = gl::R16F,
@@ -1245,7 +1257,11 @@ impl TexFormat {
             TexFormat::CompressedRgbS3tcDxt1 |
             TexFormat::CompressedRgbaS3tcDxt1 |
             TexFormat::CompressedRgbaS3tcDxt3 |
-            TexFormat::CompressedRgbaS3tcDxt5 => &[TexDataType::UnsignedByte][..],
+            TexFormat::CompressedRgbaS3tcDxt5 |
+            TexFormat::CompressedSrgbS3tcDxt1 |
+            TexFormat::CompressedSrgbAlphaS3tcDxt1 |
+            TexFormat::CompressedSrgbAlphaS3tcDxt3 |
+            TexFormat::CompressedSrgbAlphaS3tcDxt5 => &[TexDataType::UnsignedByte][..],
             _ => &[][..],
         }
     }
@@ -1261,7 +1277,11 @@ impl TexFormat {
             TexFormat::CompressedRgbS3tcDxt1 |
             TexFormat::CompressedRgbaS3tcDxt1 |
             TexFormat::CompressedRgbaS3tcDxt3 |
-            TexFormat::CompressedRgbaS3tcDxt5 => WebGLVersion::WebGL1,
+            TexFormat::CompressedRgbaS3tcDxt5 |
+            TexFormat::CompressedSrgbS3tcDxt1 |
+            TexFormat::CompressedSrgbAlphaS3tcDxt1 |
+            TexFormat::CompressedSrgbAlphaS3tcDxt3 |
+            TexFormat::CompressedSrgbAlphaS3tcDxt5 => WebGLVersion::WebGL1,
             _ => WebGLVersion::WebGL2,
         }
     }
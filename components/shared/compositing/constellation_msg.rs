@@ -4,20 +4,33 @@
 
 use std::collections::HashMap;
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use embedder_traits::Cursor;
+use embedder_traits::{
+    ContextMenuAction, CookieScope, Cursor, FindOptions, FocusReason, FrameSelector, ImeEvent,
+    JavaScriptEvaluationError, NavigationPolicy, NewWebViewRequest, PaintingOrderChange,
+    PermissionDecision, PermissionScope, PrintError, PrintSettings, ScaleMode, Screenshot,
+    ScreenshotError, ScreenshotFormat, SessionState, StoredCookie, SurroundingText, UserScript,
+    UserScriptId, WebViewLifecycleState,
+};
+use euclid::{Rect, Scale};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{
-    BrowsingContextId, PipelineId, TopLevelBrowsingContextId, TraversalDirection,
+    BrowsingContextId, PipelineId, TopLevelBrowsingContextId, TraversalDirection, WebViewGroupId,
+    WebViewId,
 };
+use net_traits::{ClearDataScope, ClearDataTypes};
 use script_traits::{
-    AnimationTickType, CompositorEvent, GamepadEvent, LogEntry, MediaSessionActionType,
+    AnimationTickType, CompositorEvent, EditingCommand, GamepadEvent, JsGcSettings, LogEntry,
+    MediaSessionActionType, MemoryPressureLevel, PictureInPictureAction, SensorReading,
     WebDriverCommandMsg, WindowSizeData, WindowSizeType,
 };
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
+use style_traits::{CSSPixel, DevicePixel};
+use webrender_api::units::DeviceIntSize;
+use webrender_api::ColorF;
 
 /// Messages to the constellation.
 pub enum ConstellationMsg {
@@ -37,13 +50,34 @@ pub enum ConstellationMsg {
     /// Inform the constellation of a key event.
     Keyboard(KeyboardEvent),
     /// Whether to allow script to navigate.
-    AllowNavigationResponse(PipelineId, bool),
+    AllowNavigationResponse(PipelineId, NavigationPolicy),
     /// Request to load a page.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
     /// Clear the network cache.
     ClearCache,
+    /// Store a decision for the webviews of a scope.
+    SetPermission(PermissionScope, PermissionDecision),
+    /// Send the decisions in the permission stores to the embedder.
+    GetPermissionDecisions,
+    /// Forget the permission decisions for an origin, or for every origin.
+    ClearPermissionDecisions(Option<ImmutableOrigin>),
+    /// Send the cookies of the jars of a scope that are sent to a URL, or all of them, to
+    /// the embedder.
+    GetCookies(CookieScope, Option<ServoUrl>),
+    /// Store a cookie in the jars of a scope.
+    SetCookie(CookieScope, StoredCookie),
+    /// Delete a cookie from the jars of a scope.
+    DeleteCookie(CookieScope, StoredCookie),
+    /// Start or stop sending the changes to the cookie jars to the embedder.
+    ObserveCookieChanges(bool),
+    /// Clear the given kinds of data of a scope, optionally only removing what was stored
+    /// since a point in time.
+    ClearData(ClearDataScope, ClearDataTypes, Option<SystemTime>),
     /// Request to traverse the joint session history of the provided browsing context.
     TraverseHistory(TopLevelBrowsingContextId, TraversalDirection),
+    /// Request to traverse the joint session history of the provided browsing context to
+    /// the entry at an index of its flat projection.
+    GoToHistoryIndex(TopLevelBrowsingContextId, usize),
     /// Inform the constellation of a window being resized.
     WindowSize(TopLevelBrowsingContextId, WindowSizeData, WindowSizeType),
     /// Requests that the constellation instruct layout to begin a new tick of the animation.
@@ -55,15 +89,19 @@ pub enum ConstellationMsg {
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<TopLevelBrowsingContextId>, Option<String>, LogEntry),
     /// Create a new top level browsing context.
-    NewWebView(ServoUrl, TopLevelBrowsingContextId),
+    NewWebView(ServoUrl, TopLevelBrowsingContextId, WebViewGroupId),
     /// Close a top level browsing context.
     CloseWebView(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
     SendError(Option<TopLevelBrowsingContextId>, String),
     /// Make a top-level browsing context focused.
-    FocusWebView(TopLevelBrowsingContextId),
-    /// Make none of the top-level browsing contexts focused.
-    BlurWebView,
+    FocusWebView(TopLevelBrowsingContextId, FocusReason),
+    /// Take focus away from the focused top-level browsing context.
+    BlurWebView(FocusReason),
+    /// Focus the webview that was focused before the focused one.
+    FocusPreviousWebView,
+    /// Focus the webview that was focused before the others, undoing `FocusPreviousWebView`.
+    FocusNextWebView,
     /// Forward an event to the script task of the given pipeline.
     ForwardEvent(PipelineId, CompositorEvent),
     /// Requesting a change to the onscreen cursor.
@@ -74,16 +112,143 @@ pub enum ConstellationMsg {
     DisableProfiler,
     /// Request to exit from fullscreen mode
     ExitFullScreen(TopLevelBrowsingContextId),
+    /// Whether the embedder shows the element that a document of a webview asked to show
+    /// fullscreen.
+    FullscreenEntered(TopLevelBrowsingContextId, bool),
     /// Media session action.
     MediaSessionAction(MediaSessionActionType),
+    /// Picture-in-picture control action.
+    PictureInPictureAction(PictureInPictureAction),
+    /// Look up the canvas matching a CSS selector in a webview, to share its texture
+    /// with the embedder.
+    ShareCanvasTexture(TopLevelBrowsingContextId, String),
     /// The visibility of the webview has changed.
     WebViewVisibilityChanged(TopLevelBrowsingContextId, bool),
+    /// Every webview is now shown.
+    MarkAllWebViewsShown,
+    /// Every webview is now hidden.
+    MarkAllWebViewsNotShown,
     /// Virtual keyboard was dismissed
     IMEDismissed,
     /// Compositing done, but external code needs to present.
     ReadyToPresent(TopLevelBrowsingContextId),
     /// Gamepad state has changed
     Gamepad(GamepadEvent),
+    /// A device sensor has a new reading
+    SensorReading(SensorReading),
+    /// Run an editing command in the focused document of a webview.
+    EditingCommand(TopLevelBrowsingContextId, EditingCommand),
+    /// Send what is selected in the focused document of a webview to the embedder.
+    GetSelection(TopLevelBrowsingContextId),
+    /// Turn caret browsing on or off in a webview.
+    SetCaretBrowsing(TopLevelBrowsingContextId, bool),
+    /// Free memory because the device is running low on it.
+    MemoryPressure(MemoryPressureLevel),
+    /// Change the JavaScript heap limit and garbage collection settings of a webview.
+    SetJsGcSettings(TopLevelBrowsingContextId, JsGcSettings),
+    /// The compositor has painted a frame and no animation will paint another soon.
+    CompositorIdle,
+    /// Move a webview up or down in the order in which webviews are painted.
+    ChangePaintingOrder(TopLevelBrowsingContextId, PaintingOrderChange),
+    /// Freeze, discard or reactivate a webview.
+    SetWebViewLifecycleState(TopLevelBrowsingContextId, WebViewLifecycleState),
+    /// Send the state of every webview to the embedder.
+    GetSessionState,
+    /// Open the webviews of a saved session.
+    RestoreSession(SessionState),
+    /// Set the device pixel ratio of a webview, or make it follow the window again.
+    SetWebViewDevicePixelRatio(
+        TopLevelBrowsingContextId,
+        Option<Scale<f32, CSSPixel, DevicePixel>>,
+    ),
+    /// Change the size of the viewport of a webview, in device pixels.
+    ResizeWebView(WebViewId, DeviceIntSize),
+    /// Send the title, URL, favicon and load state of every webview to the embedder.
+    GetWebViewMetadata,
+    /// Open a new webview with a copy of the session history of the given one.
+    DuplicateWebView(WebViewId),
+    /// Load the page of a crashed webview again.
+    ReloadCrashedWebView(WebViewId),
+    /// Mute or unmute every document of a webview.
+    SetWebViewMuted(WebViewId, bool),
+    /// Send the browsing context tree of a webview to the embedder.
+    GetFrameTree(WebViewId),
+    /// Limit the number of webviews that are running or frozen, discarding hidden ones.
+    SetWebViewLimit(Option<usize>),
+    /// Enforce a Content-Security-Policy in every document of a webview.
+    SetWebViewCsp(WebViewId, Option<String>),
+    /// Set the languages of every document of a webview, and of its requests.
+    SetWebViewLanguages(WebViewId, Vec<String>),
+    /// Create a hidden webview that loads a page before it is needed.
+    NewPrerenderWebView(ServoUrl, TopLevelBrowsingContextId),
+    /// Replace the focused webview with a prerendered one.
+    ActivatePrerender(WebViewId),
+    /// Set how promptly the tasks of every document of a webview are run.
+    SetWebViewPriority(WebViewId, WebViewPriority),
+    /// Start or stop tracing the constellation, keeping at most the given number of events.
+    SetConstellationTracing(Option<usize>),
+    /// Send the recorded trace of the constellation to the embedder.
+    GetConstellationTrace,
+    /// Send the memory that the documents of every webview use to the embedder.
+    GetWebViewMemoryUsage,
+    /// Freeze the documents of a webview and purge the caches they use.
+    SuspendWebView(WebViewId),
+    /// Resume the documents of a suspended webview.
+    ResumeWebView(WebViewId),
+    /// Set the color painted behind the documents of a webview.
+    SetWebViewBackgroundColor(WebViewId, Option<ColorF>),
+    /// Set whether a webview is transparent.
+    SetWebViewTransparent(WebViewId, bool),
+    /// Search the text of every document of a webview.
+    Find(WebViewId, String, FindOptions),
+    /// Go to the next match of the last search of a webview.
+    FindNext(WebViewId),
+    /// Go to the previous match of the last search of a webview.
+    FindPrevious(WebViewId),
+    /// Forget the matches of the last search of a webview.
+    StopFinding(WebViewId),
+    /// Create a new private webview in a webview group.
+    NewPrivateWebView(ServoUrl, TopLevelBrowsingContextId, WebViewGroupId),
+    /// Open a popup that was blocked.
+    OpenBlockedPopup(NewWebViewRequest),
+    /// Get the navigations of a webview that haven't replaced their documents yet.
+    GetPendingNavigations(WebViewId),
+    /// Cancel the navigations of a webview and stop loading its documents.
+    CancelPendingNavigation(WebViewId),
+    /// Take a screenshot of a webview, or of a rectangle of its viewport.
+    CaptureScreenshot(
+        WebViewId,
+        Option<Rect<f32, CSSPixel>>,
+        ScaleMode,
+        ScreenshotFormat,
+        IpcSender<Result<Screenshot, ScreenshotError>>,
+    ),
+    /// Print the document of a webview to a PDF file.
+    PrintToPdf(
+        WebViewId,
+        PrintSettings,
+        IpcSender<Result<Vec<u8>, PrintError>>,
+    ),
+    /// Dispatch a key event in the focused browsing context of a webview, whether the
+    /// webview is focused or not.
+    WebViewKeyboard(WebViewId, KeyboardEvent),
+    /// An edit of the input method of the embedder to the focused editable element of a webview.
+    Ime(WebViewId, ImeEvent),
+    /// Get the value of the focused editable element of a webview, for its input method.
+    GetImeSurroundingText(WebViewId, IpcSender<Option<SurroundingText>>),
+    /// Run a standard action of the last context menu of a frame.
+    ContextMenuAction(WebViewId, BrowsingContextId, ContextMenuAction),
+    /// Inject a script into the matching documents of a webview, or of every webview.
+    AddUserScript(UserScriptId, Option<WebViewId>, UserScript),
+    /// Stop injecting a script into the documents loaded from now on.
+    RemoveUserScript(UserScriptId),
+    /// Run a script in the document of a frame of a webview, and send back its result as JSON.
+    EvaluateJavaScript(
+        WebViewId,
+        FrameSelector,
+        String,
+        IpcSender<Result<String, JavaScriptEvaluationError>>,
+    ),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -99,6 +264,7 @@ impl fmt::Debug for ConstellationMsg {
             AllowNavigationResponse(..) => "AllowNavigationResponse",
             LoadUrl(..) => "LoadUrl",
             TraverseHistory(..) => "TraverseHistory",
+            GoToHistoryIndex(..) => "GoToHistoryIndex",
             WindowSize(..) => "WindowSize",
             TickAnimation(..) => "TickAnimation",
             WebDriverCommand(..) => "WebDriverCommand",
@@ -107,19 +273,82 @@ impl fmt::Debug for ConstellationMsg {
             NewWebView(..) => "NewWebView",
             CloseWebView(..) => "CloseWebView",
             FocusWebView(..) => "FocusWebView",
-            BlurWebView => "BlurWebView",
+            BlurWebView(..) => "BlurWebView",
+            FocusPreviousWebView => "FocusPreviousWebView",
+            FocusNextWebView => "FocusNextWebView",
             SendError(..) => "SendError",
             ForwardEvent(..) => "ForwardEvent",
             SetCursor(..) => "SetCursor",
             EnableProfiler(..) => "EnableProfiler",
             DisableProfiler => "DisableProfiler",
             ExitFullScreen(..) => "ExitFullScreen",
+            FullscreenEntered(..) => "FullscreenEntered",
             MediaSessionAction(..) => "MediaSessionAction",
+            PictureInPictureAction(..) => "PictureInPictureAction",
+            ShareCanvasTexture(..) => "ShareCanvasTexture",
             WebViewVisibilityChanged(..) => "WebViewVisibilityChanged",
+            MarkAllWebViewsShown => "MarkAllWebViewsShown",
+            MarkAllWebViewsNotShown => "MarkAllWebViewsNotShown",
             IMEDismissed => "IMEDismissed",
             ClearCache => "ClearCache",
+            SetPermission(..) => "SetPermission",
+            GetPermissionDecisions => "GetPermissionDecisions",
+            ClearPermissionDecisions(..) => "ClearPermissionDecisions",
+            GetCookies(..) => "GetCookies",
+            SetCookie(..) => "SetCookie",
+            DeleteCookie(..) => "DeleteCookie",
+            ObserveCookieChanges(..) => "ObserveCookieChanges",
+            ClearData(..) => "ClearData",
             ReadyToPresent(..) => "ReadyToPresent",
             Gamepad(..) => "Gamepad",
+            SensorReading(..) => "SensorReading",
+            EditingCommand(..) => "EditingCommand",
+            GetSelection(..) => "GetSelection",
+            SetCaretBrowsing(..) => "SetCaretBrowsing",
+            MemoryPressure(..) => "MemoryPressure",
+            SetJsGcSettings(..) => "SetJsGcSettings",
+            CompositorIdle => "CompositorIdle",
+            ChangePaintingOrder(..) => "ChangePaintingOrder",
+            SetWebViewLifecycleState(..) => "SetWebViewLifecycleState",
+            GetSessionState => "GetSessionState",
+            RestoreSession(..) => "RestoreSession",
+            SetWebViewDevicePixelRatio(..) => "SetWebViewDevicePixelRatio",
+            ResizeWebView(..) => "ResizeWebView",
+            GetWebViewMetadata => "GetWebViewMetadata",
+            DuplicateWebView(..) => "DuplicateWebView",
+            ReloadCrashedWebView(..) => "ReloadCrashedWebView",
+            SetWebViewMuted(..) => "SetWebViewMuted",
+            GetFrameTree(..) => "GetFrameTree",
+            SetWebViewLimit(..) => "SetWebViewLimit",
+            SetWebViewCsp(..) => "SetWebViewCsp",
+            SetWebViewLanguages(..) => "SetWebViewLanguages",
+            NewPrerenderWebView(..) => "NewPrerenderWebView",
+            ActivatePrerender(..) => "ActivatePrerender",
+            SetWebViewPriority(..) => "SetWebViewPriority",
+            SetConstellationTracing(..) => "SetConstellationTracing",
+            GetConstellationTrace => "GetConstellationTrace",
+            GetWebViewMemoryUsage => "GetWebViewMemoryUsage",
+            SuspendWebView(..) => "SuspendWebView",
+            ResumeWebView(..) => "ResumeWebView",
+            SetWebViewBackgroundColor(..) => "SetWebViewBackgroundColor",
+            SetWebViewTransparent(..) => "SetWebViewTransparent",
+            Find(..) => "Find",
+            FindNext(..) => "FindNext",
+            FindPrevious(..) => "FindPrevious",
+            StopFinding(..) => "StopFinding",
+            NewPrivateWebView(..) => "NewPrivateWebView",
+            OpenBlockedPopup(..) => "OpenBlockedPopup",
+            GetPendingNavigations(..) => "GetPendingNavigations",
+            CancelPendingNavigation(..) => "CancelPendingNavigation",
+            CaptureScreenshot(..) => "CaptureScreenshot",
+            PrintToPdf(..) => "PrintToPdf",
+            WebViewKeyboard(..) => "WebViewKeyboard",
+            Ime(..) => "Ime",
+            GetImeSurroundingText(..) => "GetImeSurroundingText",
+            ContextMenuAction(..) => "ContextMenuAction",
+            AddUserScript(..) => "AddUserScript",
+            RemoveUserScript(..) => "RemoveUserScript",
+            EvaluateJavaScript(..) => "EvaluateJavaScript",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
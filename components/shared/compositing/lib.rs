@@ -11,8 +11,8 @@ use std::fmt::{Debug, Error, Formatter};
 use canvas::canvas_paint_thread::ImageUpdate;
 pub use constellation_msg::ConstellationMsg;
 use crossbeam_channel::{Receiver, Sender};
-use embedder_traits::EventLoopWaker;
-use euclid::Rect;
+use embedder_traits::{EventLoopWaker, ScaleMode, Screenshot, ScreenshotError, ScreenshotFormat};
+use euclid::{Rect, Scale};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use log::warn;
@@ -23,9 +23,9 @@ use script_traits::{
     AnimationState, ConstellationControlMsg, EventResult, MouseButton, MouseEventType,
     ScriptToCompositorMsg,
 };
-use style_traits::CSSPixel;
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
-use webrender_api::{self, FontInstanceKey, FontKey, ImageKey};
+use style_traits::{CSSPixel, DevicePixel};
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use webrender_api::{self, ColorF, FontInstanceKey, FontKey, ImageKey};
 
 /// Sends messages to the compositor.
 pub struct CompositorProxy {
@@ -65,6 +65,16 @@ impl CompositorReceiver {
     }
 }
 
+/// The layout of a webview that a screenshot is taken of, for one that isn't the layout
+/// that the webview is shown with, like when it is printed.
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenshotLayout {
+    /// The size of the viewport that the webview is laid out with, in device pixels.
+    pub viewport_size: DeviceIntSize,
+    /// The epoch of the display list of this layout, which the screenshot waits for.
+    pub epoch: Epoch,
+}
+
 /// Messages from (or via) the constellation thread to the compositor.
 pub enum CompositorMsg {
     /// Informs the compositor that the constellation has completed shutdown.
@@ -75,10 +85,40 @@ pub enum CompositorMsg {
     ChangeRunningAnimationsState(PipelineId, AnimationState),
     /// Replaces the current frame tree, typically called during main frame navigation.
     SetFrameTree(SendableFrameTree),
+    /// The root pipelines of the webviews to paint, from the bottom to the top.
+    SetPaintingOrder(Vec<PipelineId>),
+    /// The device pixel ratio of a webview, or `None` if it follows the window.
+    SetWebViewDevicePixelRatio(
+        TopLevelBrowsingContextId,
+        Option<Scale<f32, CSSPixel, DevicePixel>>,
+    ),
+    /// The size of the viewport of a webview, or `None` if it is the size of the window.
+    ResizeWebView(TopLevelBrowsingContextId, Option<DeviceIntSize>),
+    /// The color to paint behind the documents of a webview, if any.
+    SetWebViewBackgroundColor(TopLevelBrowsingContextId, Option<ColorF>),
+    /// Whether to clear the viewport of a webview to transparent before painting it.
+    SetWebViewTransparent(TopLevelBrowsingContextId, bool),
+    /// Highlight the matches of the search of a webview, and its active match, over its
+    /// documents. The boxes are in device pixels relative to the viewport of the webview.
+    SetFindHighlights(
+        TopLevelBrowsingContextId,
+        Vec<DeviceIntRect>,
+        Vec<DeviceIntRect>,
+    ),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(Option<Rect<f32, CSSPixel>>, IpcSender<Option<Image>>),
+    /// Paint the root pipeline of a webview on its own and send a screenshot of it.
+    CaptureScreenshot(
+        TopLevelBrowsingContextId,
+        PipelineId,
+        Option<ScreenshotLayout>,
+        Option<Rect<f32, CSSPixel>>,
+        ScaleMode,
+        ScreenshotFormat,
+        IpcSender<Result<Screenshot, ScreenshotError>>,
+    ),
     /// A reply to the compositor asking if the output image is stable.
     IsReadyToSaveImageReply(bool),
     /// Pipeline visibility changed
@@ -102,6 +142,8 @@ pub enum CompositorMsg {
     PendingPaintMetric(PipelineId, Epoch),
     /// The load of a page has completed
     LoadComplete(TopLevelBrowsingContextId),
+    /// A webview was suspended, so the caches of the textures it used can be purged.
+    WebViewSuspended(TopLevelBrowsingContextId),
     /// WebDriver mouse button event
     WebDriverMouseButtonEvent(MouseEventType, MouseButton, f32, f32),
     /// WebDriver mouse move event
@@ -158,8 +200,19 @@ impl Debug for CompositorMsg {
                 write!(f, "ChangeRunningAnimationsState({:?})", state)
             },
             CompositorMsg::SetFrameTree(..) => write!(f, "SetFrameTree"),
+            CompositorMsg::SetPaintingOrder(..) => write!(f, "SetPaintingOrder"),
+            CompositorMsg::SetWebViewDevicePixelRatio(..) => {
+                write!(f, "SetWebViewDevicePixelRatio")
+            },
+            CompositorMsg::ResizeWebView(..) => write!(f, "ResizeWebView"),
+            CompositorMsg::SetWebViewBackgroundColor(..) => {
+                write!(f, "SetWebViewBackgroundColor")
+            },
+            CompositorMsg::SetWebViewTransparent(..) => write!(f, "SetWebViewTransparent"),
+            CompositorMsg::SetFindHighlights(..) => write!(f, "SetFindHighlights"),
             CompositorMsg::TouchEventProcessed(..) => write!(f, "TouchEventProcessed"),
             CompositorMsg::CreatePng(..) => write!(f, "CreatePng"),
+            CompositorMsg::CaptureScreenshot(..) => write!(f, "CaptureScreenshot"),
             CompositorMsg::IsReadyToSaveImageReply(..) => write!(f, "IsReadyToSaveImageReply"),
             CompositorMsg::PipelineVisibilityChanged(..) => write!(f, "PipelineVisibilityChanged"),
             CompositorMsg::PipelineExited(..) => write!(f, "PipelineExited"),
@@ -167,6 +220,7 @@ impl Debug for CompositorMsg {
             CompositorMsg::Dispatch(..) => write!(f, "Dispatch"),
             CompositorMsg::PendingPaintMetric(..) => write!(f, "PendingPaintMetric"),
             CompositorMsg::LoadComplete(..) => write!(f, "LoadComplete"),
+            CompositorMsg::WebViewSuspended(..) => write!(f, "WebViewSuspended"),
             CompositorMsg::WebDriverMouseButtonEvent(..) => write!(f, "WebDriverMouseButtonEvent"),
             CompositorMsg::WebDriverMouseMoveEvent(..) => write!(f, "WebDriverMouseMoveEvent"),
             CompositorMsg::GetClientWindow(..) => write!(f, "GetClientWindow"),
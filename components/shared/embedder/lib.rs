@@ -5,16 +5,21 @@
 pub mod resources;
 
 use std::fmt::{Debug, Error, Formatter};
+use std::ops::Range;
+use std::time::SystemTime;
 
 use crossbeam_channel::{Receiver, Sender};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use log::warn;
-use msg::constellation_msg::{InputMethodType, PipelineId, TopLevelBrowsingContextId};
+use msg::constellation_msg::{
+    BrowsingContextId, InputMethodType, PipelineId, TopLevelBrowsingContextId, WebViewGroupId,
+};
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
-use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, LayoutVector2D};
+use webrender_api::{ExternalImageId, ImageKey};
 pub use webxr_api::MainThreadWaker as EventLoopWaker;
 
 /// A cursor for the window. This is different from a CSS cursor (see
@@ -152,10 +157,18 @@ pub enum EmbedderMsg {
     Prompt(PromptDefinition, PromptOrigin),
     /// Show a context menu to the user
     ShowContextMenu(IpcSender<ContextMenuResult>, Option<String>, Vec<String>),
-    /// Whether or not to allow a pipeline to load a url.
-    AllowNavigationRequest(PipelineId, ServoUrl),
-    /// Whether or not to allow script to open a new tab/browser
-    AllowOpeningWebView(IpcSender<bool>),
+    /// Whether to allow a navigation that script started, to deny it, or to load its URL in
+    /// a new webview instead. The embedder answers with `EmbedderEvent::AllowNavigationResponse`
+    /// whenever it decides, and the navigation waits until then.
+    AllowNavigationRequest(NavigationRequest),
+    /// Whether to allow script to open a new webview, with `window.open` or a link or form
+    /// that targets a new browsing context. The embedder answers with the id of a webview
+    /// for the constellation to create as the auxiliary browsing context of the opener,
+    /// such as a new `TopLevelBrowsingContextId`, or `None` to deny it.
+    AllowOpeningWebView(
+        NewWebViewRequest,
+        IpcSender<Option<TopLevelBrowsingContextId>>,
+    ),
     /// A browser was created
     WebViewOpened(TopLevelBrowsingContextId),
     /// A browser was destroyed
@@ -176,12 +189,21 @@ pub enum EmbedderMsg {
     SetCursor(Cursor),
     /// A favicon was detected
     NewFavicon(ServoUrl),
+    /// The favicon of the top-level document was fetched and decoded, or it has none
+    /// anymore. A new document has no favicon until it sends one.
+    FaviconChanged(Option<Favicon>),
     /// `<head>` tag finished parsing
     HeadParsed,
-    /// The history state has changed.
-    HistoryChanged(Vec<ServoUrl>, usize),
-    /// Enter or exit fullscreen
-    SetFullscreenState(bool),
+    /// The session history of the webview changed, or the title of one of its entries did.
+    /// These are its entries, oldest first, with the index of the current one.
+    HistoryChanged(Vec<HistoryEntry>, usize),
+    /// A document asks to show one of its elements fullscreen, whose box in device pixels
+    /// relative to the viewport of the document this is. The element only becomes fullscreen
+    /// once the embedder answers with `EmbedderEvent::FullscreenEntered`.
+    EnterFullscreen(DeviceIntRect),
+    /// The fullscreen element of the webview left fullscreen, so the embedder can restore
+    /// the size of the webview. This is sent as well after `EmbedderEvent::ExitFullScreen`.
+    ExitFullscreen,
     /// The load of a page has begun
     LoadStart,
     /// The load of a page has completed
@@ -190,10 +212,17 @@ pub enum EmbedderMsg {
     Panic(String, Option<String>),
     /// Open dialog to select bluetooth device.
     GetSelectedBluetoothDevice(Vec<String>, IpcSender<Option<String>>),
-    /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
-    SelectFiles(Vec<FilterPattern>, bool, IpcSender<Option<Vec<String>>>),
-    /// Open interface to request permission specified by prompt.
-    PromptPermission(PermissionPrompt, IpcSender<PermissionRequest>),
+    /// Open a file picker for an `<input type=file>`, and answer with the paths that the
+    /// user picked, or `None` if they cancelled.
+    SelectFiles(FilePickerRequest, IpcSender<Option<Vec<String>>>),
+    /// A document of the webview, of the given origin, asks the user for a permission. The
+    /// embedder can answer whenever the user decides, but the permission is denied if it
+    /// doesn't within the `dom.permissions.prompt_timeout` preference.
+    PromptPermission(
+        PermissionPrompt,
+        ImmutableOrigin,
+        IpcSender<PermissionRequest>,
+    ),
     /// Request to present an IME to the user when an editable element is focused.
     /// If the input is text, the second parameter defines the pre-existing string
     /// text content and the zero-based index into the string locating the insertion point.
@@ -208,12 +237,123 @@ pub enum EmbedderMsg {
     /// Notifies the embedder about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(MediaSessionEvent),
+    /// Notifies the embedder that a video entered or left picture-in-picture, or that
+    /// the picture-in-picture video has a new frame to show.
+    PictureInPictureEvent(PictureInPictureEvent),
+    /// The answer to `EmbedderEvent::ShareCanvasTexture`: the external image of the
+    /// canvas matching the selector, if there is one and it renders with WebGL. Its
+    /// texture can then be locked every frame with `Servo::lock_canvas_texture`.
+    CanvasTextureShared(String, Option<ExternalImageId>),
     /// Report the status of Devtools Server with a token that can be used to bypass the permission prompt.
     OnDevtoolsStarted(Result<u16, ()>, String),
+    /// Report the status of the devtools WebSocket server, which accepts the same token.
+    OnDevtoolsWebSocketStarted(Result<u16, ()>),
     /// Compositing done, but external code needs to present.
     ReadyToPresent,
     /// The given event was delivered to a pipeline in the given browser.
     EventDelivered(CompositorEventVariant),
+    /// Content started listening to a device sensor. The embedder should send
+    /// `EmbedderEvent::SensorReading`s for it until told to stop.
+    StartSensor(SensorType),
+    /// Content no longer listens to a device sensor.
+    StopSensor(SensorType),
+    /// The decisions in the permission stores, with the webviews they apply to, in reply
+    /// to `EmbedderEvent::GetPermissionDecisions`.
+    PermissionDecisions(Vec<(PermissionScope, PermissionDecision)>),
+    /// A document stored the answer to a permission prompt for its webview. Embedders that
+    /// keep the decisions across restarts can save it, and set it again with
+    /// `EmbedderEvent::SetPermission`.
+    PermissionDecisionChanged(PermissionScope, PermissionDecision),
+    /// The cookies in the jars of a scope, with the group of the jar each is in, in reply
+    /// to `EmbedderEvent::GetCookies`.
+    Cookies(Vec<(WebViewGroupId, StoredCookie)>),
+    /// A cookie in the jar of a webview group was set or removed, once
+    /// `EmbedderEvent::ObserveCookieChanges` asked for these.
+    CookieChanged(WebViewGroupId, CookieChange),
+    /// The misspelled words in the value of the text control being edited changed.
+    /// Only sent when the built-in spelling checker is enabled.
+    Misspellings(Vec<Misspelling>),
+    /// What is selected in the focused document, in reply to
+    /// `EmbedderEvent::GetSelection`.
+    Selection(SelectionInfo),
+    /// The caret moved while caret browsing.
+    CaretMoved(CaretPosition),
+    /// The lifecycle state of a webview changed, either because the embedder asked for
+    /// it or because a frozen or discarded webview was focused and became active again.
+    WebViewLifecycleStateChanged(WebViewLifecycleState),
+    /// The state of every webview, in response to `EmbedderEvent::GetSessionState`.
+    SessionState(SessionState),
+    /// What is known about every webview from the bottom to the top of the painting
+    /// order, in response to `EmbedderEvent::GetWebViewMetadata`.
+    WebViewMetadata(Vec<(TopLevelBrowsingContextId, WebViewMetadata)>),
+    /// Whether a webview that `EmbedderEvent::CloseWebView` asked to close let it, once
+    /// beforeunload was dispatched to its documents. A webview that closes is then
+    /// reported with `WebViewClosed`.
+    WebViewCloseResult(bool),
+    /// The content of a webview crashed, and a crash page is shown in its place until the
+    /// embedder sends `ReloadCrashedWebView` or the webview navigates elsewhere.
+    WebViewCrashed,
+    /// Whether a document of the webview started or stopped playing audio. Muting a
+    /// webview doesn't change whether it plays audio.
+    WebViewAudioStateChanged(bool),
+    /// The browsing contexts of a webview and their documents, in answer to
+    /// `GetFrameTree`.
+    FrameTree(FrameTreeNode),
+    /// Webviews that were discarded because more were running or frozen than the limit
+    /// set with `SetWebViewLimit`, least recently focused first.
+    WebViewsDiscarded(Vec<TopLevelBrowsingContextId>),
+    /// A prerendered webview was activated, replacing the given focused webview, which is
+    /// now hidden.
+    PrerenderActivated(Option<TopLevelBrowsingContextId>),
+    /// The events the constellation recorded, as the JSON of a `ConstellationTrace`, in
+    /// answer to `GetConstellationTrace`, or `None` if it isn't tracing.
+    ConstellationTrace(Option<String>),
+    /// The memory that the documents of every webview use, from the bottom to the top of the
+    /// painting order, in answer to `GetWebViewMemoryUsage`.
+    WebViewMemoryUsage(Vec<(TopLevelBrowsingContextId, WebViewMemoryUsage)>),
+    /// The progress of a search that `EmbedderEvent::Find` started.
+    FindResult(FindResult),
+    /// Script tried to open a popup without transient user activation, and it was blocked.
+    PopupBlocked(NewWebViewRequest),
+    /// The navigations of a webview that haven't replaced their documents yet, in answer to
+    /// `GetPendingNavigations`.
+    PendingNavigations(Vec<PendingNavigation>),
+    /// The user opened a context menu that content didn't cancel. Unlike `ShowContextMenu`,
+    /// the embedder builds the menu itself.
+    ContextMenu(ContextMenuData),
+    /// An image to save, in answer to `ContextMenuAction::SaveImage`.
+    SaveImage(SavedImage),
+    /// An element to inspect, in answer to `ContextMenuAction::InspectElement`, with the
+    /// id that the devtools server uses for it in the document of the pipeline.
+    InspectElement(PipelineId, String),
+    /// A navigation of a browsing context of the webview took a step.
+    NavigationEvent(NavigationEvent),
+    /// The certificate of a server that a document is loaded from failed to verify. The
+    /// embedder answers whether to accept it anyway, which the webviews of the group of
+    /// the webview remember for the rest of the session, so that it isn't asked again
+    /// about the same certificate of the same host. The load fails when it isn't accepted.
+    CertificateError(CertificateError, IpcSender<bool>),
+    /// A server or proxy asked for credentials to load a resource. The embedder answers
+    /// with the credentials to try, which the webviews of the group of the webview use again
+    /// for the origin, or the proxy, once they are accepted, or `None` to load the response
+    /// that asked for them.
+    RequestAuthentication(
+        AuthenticationRequest,
+        IpcSender<Option<AuthenticationResponse>>,
+    ),
+}
+
+/// A device sensor content can listen to.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SensorType {
+    /// Acceleration of the device, including gravity.
+    Accelerometer,
+    /// Rate of rotation of the device.
+    Gyroscope,
+    /// Light level of the environment.
+    AmbientLight,
+    /// Orientation of the device.
+    Orientation,
 }
 
 /// The variant of CompositorEvent that was delivered to a pipeline.
@@ -226,6 +366,7 @@ pub enum CompositorEventVariant {
     WheelEvent,
     KeyboardEvent,
     CompositionEvent,
+    ImeEvent,
     IMEDismissedEvent,
     GamepadEvent,
 }
@@ -245,9 +386,11 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::SetClipboardContents(..) => write!(f, "SetClipboardContents"),
             EmbedderMsg::SetCursor(..) => write!(f, "SetCursor"),
             EmbedderMsg::NewFavicon(..) => write!(f, "NewFavicon"),
+            EmbedderMsg::FaviconChanged(..) => write!(f, "FaviconChanged"),
             EmbedderMsg::HeadParsed => write!(f, "HeadParsed"),
             EmbedderMsg::HistoryChanged(..) => write!(f, "HistoryChanged"),
-            EmbedderMsg::SetFullscreenState(..) => write!(f, "SetFullscreenState"),
+            EmbedderMsg::EnterFullscreen(..) => write!(f, "EnterFullscreen"),
+            EmbedderMsg::ExitFullscreen => write!(f, "ExitFullscreen"),
             EmbedderMsg::LoadStart => write!(f, "LoadStart"),
             EmbedderMsg::LoadComplete => write!(f, "LoadComplete"),
             EmbedderMsg::Panic(..) => write!(f, "Panic"),
@@ -264,10 +407,46 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::WebViewBlurred => write!(f, "WebViewUnfocused"),
             EmbedderMsg::ReportProfile(..) => write!(f, "ReportProfile"),
             EmbedderMsg::MediaSessionEvent(..) => write!(f, "MediaSessionEvent"),
+            EmbedderMsg::PictureInPictureEvent(..) => write!(f, "PictureInPictureEvent"),
+            EmbedderMsg::CanvasTextureShared(..) => write!(f, "CanvasTextureShared"),
             EmbedderMsg::OnDevtoolsStarted(..) => write!(f, "OnDevtoolsStarted"),
+            EmbedderMsg::OnDevtoolsWebSocketStarted(..) => write!(f, "OnDevtoolsWebSocketStarted"),
             EmbedderMsg::ShowContextMenu(..) => write!(f, "ShowContextMenu"),
             EmbedderMsg::ReadyToPresent => write!(f, "ReadyToPresent"),
             EmbedderMsg::EventDelivered(..) => write!(f, "HitTestedEvent"),
+            EmbedderMsg::StartSensor(..) => write!(f, "StartSensor"),
+            EmbedderMsg::StopSensor(..) => write!(f, "StopSensor"),
+            EmbedderMsg::PermissionDecisions(..) => write!(f, "PermissionDecisions"),
+            EmbedderMsg::PermissionDecisionChanged(..) => {
+                write!(f, "PermissionDecisionChanged")
+            },
+            EmbedderMsg::Cookies(..) => write!(f, "Cookies"),
+            EmbedderMsg::CookieChanged(..) => write!(f, "CookieChanged"),
+            EmbedderMsg::Misspellings(..) => write!(f, "Misspellings"),
+            EmbedderMsg::Selection(..) => write!(f, "Selection"),
+            EmbedderMsg::CaretMoved(..) => write!(f, "CaretMoved"),
+            EmbedderMsg::WebViewLifecycleStateChanged(..) => {
+                write!(f, "WebViewLifecycleStateChanged")
+            },
+            EmbedderMsg::SessionState(..) => write!(f, "SessionState"),
+            EmbedderMsg::WebViewMetadata(..) => write!(f, "WebViewMetadata"),
+            EmbedderMsg::WebViewCloseResult(..) => write!(f, "WebViewCloseResult"),
+            EmbedderMsg::WebViewCrashed => write!(f, "WebViewCrashed"),
+            EmbedderMsg::WebViewAudioStateChanged(..) => write!(f, "WebViewAudioStateChanged"),
+            EmbedderMsg::FrameTree(..) => write!(f, "FrameTree"),
+            EmbedderMsg::WebViewsDiscarded(..) => write!(f, "WebViewsDiscarded"),
+            EmbedderMsg::PrerenderActivated(..) => write!(f, "PrerenderActivated"),
+            EmbedderMsg::ConstellationTrace(..) => write!(f, "ConstellationTrace"),
+            EmbedderMsg::WebViewMemoryUsage(..) => write!(f, "WebViewMemoryUsage"),
+            EmbedderMsg::FindResult(..) => write!(f, "FindResult"),
+            EmbedderMsg::PopupBlocked(..) => write!(f, "PopupBlocked"),
+            EmbedderMsg::PendingNavigations(..) => write!(f, "PendingNavigations"),
+            EmbedderMsg::ContextMenu(..) => write!(f, "ContextMenu"),
+            EmbedderMsg::SaveImage(..) => write!(f, "SaveImage"),
+            EmbedderMsg::InspectElement(..) => write!(f, "InspectElement"),
+            EmbedderMsg::NavigationEvent(..) => write!(f, "NavigationEvent"),
+            EmbedderMsg::CertificateError(..) => write!(f, "CertificateError"),
+            EmbedderMsg::RequestAuthentication(..) => write!(f, "RequestAuthentication"),
         }
     }
 }
@@ -277,6 +456,18 @@ impl Debug for EmbedderMsg {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FilterPattern(pub String);
 
+/// What an `<input type=file>` lets the user pick.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FilePickerRequest {
+    /// The extensions of the files that the input accepts, which is any file when empty.
+    pub filter_patterns: Vec<FilterPattern>,
+    /// Whether the user can pick more than one file.
+    pub multiple: bool,
+    /// Whether the user picks a directory instead, for inputs with `webkitdirectory`. The
+    /// embedder answers with the path of the directory, and every file in it is uploaded.
+    pub directory: bool,
+}
+
 /// <https://w3c.github.io/mediasession/#mediametadata>
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct MediaMetadata {
@@ -339,8 +530,27 @@ pub enum MediaSessionEvent {
     SetPositionState(MediaPositionState),
 }
 
-/// Enum with variants that match the DOM PermissionName enum
+/// Type of events sent from script to the embedder about the picture-in-picture video.
+///
+/// The embedder is expected to show the frames of the video in a floating surface that
+/// outlives the webview's own rendering, and route the controls of that surface back
+/// through `EmbedderEvent::PictureInPictureAction`.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PictureInPictureEvent {
+    /// A video entered picture-in-picture. The embedder should open a surface of the
+    /// given size.
+    Enter(DeviceIntSize),
+    /// The current frame of the picture-in-picture video, as a WebRender image.
+    FrameUpdated(ImageKey, DeviceIntSize),
+    /// The playback state of the picture-in-picture video changed, so that its
+    /// play/pause control can be updated.
+    PlaybackStateChange(MediaSessionPlaybackState),
+    /// The video left picture-in-picture. The embedder should close its surface.
+    Leave,
+}
+
+/// Enum with variants that match the DOM PermissionName enum
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PermissionName {
     Geolocation,
     Notifications,
@@ -367,4 +577,790 @@ pub enum PermissionPrompt {
 pub enum PermissionRequest {
     Granted,
     Denied,
+    /// The user didn't decide yet. Nothing is remembered, and the document can ask again
+    /// later.
+    Dismissed,
+}
+
+/// What the permission store remembers about a permission for an origin.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PermissionSetting {
+    /// The feature may be used without asking.
+    Granted,
+    /// The feature is refused without asking.
+    Denied,
+    /// The user is asked every time, and their answer is not remembered.
+    Prompt,
+}
+
+/// A decision kept by the permission store.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PermissionDecision {
+    pub origin: ImmutableOrigin,
+    pub name: PermissionName,
+    pub setting: PermissionSetting,
+    /// When the decision stops applying, or `None` if it never does.
+    pub expires: Option<SystemTime>,
+}
+
+/// The webviews that a permission decision applies to. When a document asks for a
+/// permission, the decision for its webview is used first, then the one for its group,
+/// then the one for every webview.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PermissionScope {
+    /// Every webview. These decisions are saved in the config directory, if there is one.
+    #[default]
+    Global,
+    /// The webviews of a group.
+    Group(WebViewGroupId),
+    /// A single webview, until it is closed.
+    WebView(TopLevelBrowsingContextId),
+}
+
+/// A popup that script asked to open. When it is opened without transient user activation,
+/// it is blocked, and the embedder can show that it was and open it anyway with
+/// `EmbedderEvent::OpenBlockedPopup`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NewWebViewRequest {
+    /// The webview that opens the popup.
+    pub opener: TopLevelBrowsingContextId,
+    /// The document that opens the popup.
+    pub opener_pipeline: PipelineId,
+    /// The URL the popup loads, or `None` for `about:blank`.
+    pub url: Option<ServoUrl>,
+    /// The name of the target of the popup, such as `_blank`.
+    pub target_name: String,
+    /// Whether the popup is opened without an opener.
+    pub noopener: bool,
+    /// The features of the window that `window.open` asks for, which links and forms
+    /// don't ask for any of.
+    pub features: WindowFeatures,
+}
+
+/// The features of a new window that the `features` argument of `window.open` asks for.
+/// The embedder can follow them or not, as browsers with tabs often don't.
+/// <https://drafts.csswg.org/cssom-view/#the-features-argument-to-the-open()-method>
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct WindowFeatures {
+    /// The position of the window on the screen, in CSS pixels.
+    pub left: Option<i32>,
+    pub top: Option<i32>,
+    /// The size of the viewport of the window, in CSS pixels.
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Whether a minimal popup window is asked for, rather than a new tab.
+    /// <https://html.spec.whatwg.org/multipage/#popup-window-is-requested>
+    pub popup: bool,
+}
+
+/// A misspelled word in the value of a text control.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Misspelling {
+    /// Where the word is in the value, in UTF-16 code units like the offsets of
+    /// `setSelectionRange`.
+    pub range: Range<usize>,
+    pub word: String,
+    /// The words it may be a misspelling of, most likely first.
+    pub suggestions: Vec<String>,
+}
+
+/// The selection of a document, or of the text control focused in it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SelectionInfo {
+    /// The selected text, which is empty when the selection is collapsed. The text
+    /// of password fields is never sent.
+    pub text: String,
+    /// The boxes around the selected content, in device pixels relative to the
+    /// viewport, which can be used to place selection handles.
+    pub rects: Vec<DeviceIntRect>,
+}
+
+/// Where the caret is while caret browsing, with enough context for assistive
+/// technologies to announce it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CaretPosition {
+    /// The text of the run of text the caret is in.
+    pub text: String,
+    /// The offset of the caret in `text`, in UTF-16 code units.
+    pub offset: usize,
+    /// The text selected by extending the selection from the caret, if any.
+    pub selected_text: String,
+    /// The box the embedder can draw the caret in, in device pixels relative to the
+    /// viewport, if the text is rendered.
+    pub rect: Option<DeviceIntRect>,
+}
+
+/// An edit of the input method of the embedder, such as the one of a CJK language, to the
+/// focused editable element of a webview. Ranges and lengths are in UTF-16 code units.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ImeEvent {
+    /// Start composing text, or replace the text being composed, which is inserted in the
+    /// element with the cursor or the selection at a range of it, if the input method
+    /// shows one. Composing empty text cancels the composition.
+    Preedit(String, Option<Range<usize>>),
+    /// Insert text in place of the text being composed, or of the selection when nothing
+    /// is composed, and stop composing.
+    Commit(String),
+    /// Select a range of the value of the element.
+    SetSelection(Range<usize>),
+    /// Delete text of the given lengths before the selection and after it.
+    DeleteSurroundingText(usize, usize),
+}
+
+/// The value of the focused editable element of a webview, which input methods base
+/// their suggestions on. Ranges are in UTF-16 code units.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct SurroundingText {
+    pub text: String,
+    pub selection: Range<usize>,
+    /// The text being composed, if any.
+    pub composition: Option<Range<usize>>,
+}
+
+/// What the user opened a context menu on, so that embedders can build a native menu
+/// for it. The standard actions of the menu can then be run with
+/// `EmbedderEvent::ContextMenuAction`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ContextMenuData {
+    /// The frame the menu was opened in, which its actions are sent to.
+    pub browsing_context_id: BrowsingContextId,
+    /// Where the menu was opened, in device pixels relative to the viewport of the frame.
+    pub point: DeviceIntPoint,
+    /// The URL of the link the menu was opened on, if any.
+    pub link_url: Option<ServoUrl>,
+    /// The URL of the image the menu was opened on, if any.
+    pub image_url: Option<ServoUrl>,
+    /// The text selected in the frame, which is empty when nothing is. The text of
+    /// password fields is never sent.
+    pub selected_text: String,
+    /// Whether the menu was opened on an element that can be typed in.
+    pub editable: bool,
+}
+
+/// A standard action of a context menu, which applies to what the last menu of a frame
+/// was opened on.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ContextMenuAction {
+    /// Copy the URL of the link to the clipboard.
+    CopyLink,
+    /// Send the image to the embedder with `EmbedderMsg::SaveImage`.
+    SaveImage,
+    /// Send the element to the embedder with `EmbedderMsg::InspectElement`.
+    InspectElement,
+}
+
+/// A decoded image that the user asked to save from a context menu.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SavedImage {
+    pub url: ServoUrl,
+    pub size: DeviceIntSize,
+    /// The pixels of the image, in RGBA8 and from the top row down.
+    pub pixels: Vec<u8>,
+}
+
+/// The decoded favicon of the top-level document of a webview.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Favicon {
+    pub url: ServoUrl,
+    pub size: DeviceIntSize,
+    /// The pixels of the icon, in RGBA8 and from the top row down.
+    pub pixels: Vec<u8>,
+}
+
+/// How `EmbedderEvent::Find` searches the text of a webview.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FindOptions {
+    /// Whether a match must have the same case as the query.
+    pub case_sensitive: bool,
+    /// Whether searching again for the same query goes to the previous match instead of
+    /// the next one, and whether a new search starts from the last match.
+    pub backwards: bool,
+}
+
+/// The progress of a search of the text of a webview, sent each time a document of the
+/// webview was searched and when the active match changes.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FindResult {
+    pub query: String,
+    /// The number of matches in the documents searched so far.
+    pub match_count: usize,
+    /// The index of the active match among all of them, once every document was searched.
+    pub active_match: Option<usize>,
+    /// The box of the run of text the active match is in, in device pixels relative to
+    /// the viewport of the webview, once it was scrolled into view, if the text is rendered.
+    pub active_match_rect: Option<DeviceIntRect>,
+    /// Whether every document of the webview was searched.
+    pub final_update: bool,
+}
+
+/// The size of a screenshot of a webview.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScaleMode {
+    /// One pixel per device pixel of the webview.
+    DevicePixels,
+    /// One pixel per CSS pixel of the webview.
+    CssPixels,
+    /// As large as fits in this many pixels, keeping the aspect ratio.
+    Fit { width: u32, height: u32 },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScreenshotFormat {
+    /// Unpremultiplied 8-bit RGBA pixels, row by row from the top.
+    Rgba8,
+    /// An encoded PNG file.
+    Png,
+}
+
+/// A screenshot of a webview, sent back for `EmbedderEvent::CaptureScreenshot`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub format: ScreenshotFormat,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScreenshotError {
+    /// There is no webview with this id, or it has no document yet.
+    NoSuchWebView,
+    /// The webview couldn't be painted or read back, or the rectangle doesn't intersect
+    /// its viewport.
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PrintOrientation {
+    Portrait,
+    Landscape,
+}
+
+/// The margins of a printed page, in centimetres.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PrintMargins {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64,
+}
+
+/// How `EmbedderEvent::PrintToPdf` prints a webview, with the defaults of WebDriver.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PrintSettings {
+    pub orientation: PrintOrientation,
+    /// The width of a page in portrait orientation, in centimetres.
+    pub page_width: f64,
+    /// The height of a page in portrait orientation, in centimetres.
+    pub page_height: f64,
+    pub margins: PrintMargins,
+    /// How much the content is scaled, from 0.1 to 2.
+    pub scale: f64,
+    /// Whether the backgrounds of elements are printed.
+    pub background: bool,
+    /// Whether the content is scaled down further if it is wider than the pages.
+    pub shrink_to_fit: bool,
+    /// The text printed in the top margin of every page, and the one printed in the
+    /// bottom margin, where `{title}`, `{url}`, `{page}` and `{pages}` are replaced with
+    /// the title and URL of the document, the number of the page and the number of pages.
+    pub header: Option<String>,
+    pub footer: Option<String>,
+}
+
+impl Default for PrintSettings {
+    fn default() -> Self {
+        Self {
+            orientation: PrintOrientation::Portrait,
+            page_width: 21.59,
+            page_height: 27.94,
+            margins: PrintMargins {
+                top: 1.,
+                bottom: 1.,
+                left: 1.,
+                right: 1.,
+            },
+            scale: 1.,
+            background: false,
+            shrink_to_fit: true,
+            header: None,
+            footer: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PrintError {
+    /// There is no webview with this id, or it has no document yet.
+    NoSuchWebView,
+    /// The margins leave no room for the content of the pages.
+    InvalidSettings,
+    /// The document couldn't be laid out or painted.
+    Failed,
+}
+
+/// A browsing context of a webview and the document it shows, with the browsing
+/// contexts of the iframes in that document.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FrameTreeNode {
+    pub browsing_context_id: BrowsingContextId,
+    pub pipeline_id: PipelineId,
+    /// The URL of the document, which changes with fragment navigations.
+    pub url: ServoUrl,
+    /// Whether the document was loaded in an iframe with the sandbox attribute.
+    pub is_sandboxed: bool,
+    /// Whether the document is fully active, rather than suspended along with its
+    /// frozen webview.
+    pub is_active: bool,
+    /// Whether the browsing context is treated as visible, for throttling its timers
+    /// and animations.
+    pub is_visible: bool,
+    pub children: Vec<FrameTreeNode>,
+}
+
+/// A navigation that script started, which waits for the embedder to decide what happens
+/// to it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NavigationRequest {
+    /// The pipeline of the document that would be replaced, which identifies the request
+    /// in the answer.
+    pub pipeline_id: PipelineId,
+    pub browsing_context_id: BrowsingContextId,
+    /// Whether the navigated browsing context is the top-level one of the webview.
+    pub is_top_level: bool,
+    pub url: ServoUrl,
+    /// The browsing context whose document started the navigation, like by following a
+    /// link or submitting a form, when it's known. It can be another frame than the one
+    /// that navigates.
+    pub initiator: Option<BrowsingContextId>,
+    /// Whether the webview has transient user activation, like when the user clicked the
+    /// link that is followed.
+    pub has_user_gesture: bool,
+    pub is_form_submission: bool,
+}
+
+/// What the embedder decides to do with a [`NavigationRequest`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum NavigationPolicy {
+    Allow,
+    Deny,
+    /// Deny the navigation, and load its URL in a new webview of the same group, which
+    /// is reported with `EmbedderMsg::WebViewOpened`.
+    OpenInNewWebView,
+}
+
+/// A navigation of a browsing context of a webview that hasn't replaced its document yet.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PendingNavigation {
+    pub browsing_context_id: BrowsingContextId,
+    /// The URL being loaded, which is the one the last redirect led to while the new
+    /// document is fetched.
+    pub url: ServoUrl,
+    pub state: PendingNavigationState,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PendingNavigationState {
+    /// Waiting for the embedder to answer `EmbedderMsg::AllowNavigationRequest`.
+    AwaitingApproval,
+    /// Fetching the new document, after following this many redirects.
+    Fetching { redirects: usize },
+    /// Loading the new document, which replaces the current one once it has loaded.
+    Loading,
+}
+
+/// A step of the navigation of a browsing context that loads the document of a pipeline.
+/// A navigation that fetches its document is `Started`, `Started` again with `is_redirect`
+/// for each redirect, then gets its response or fails and shows an error page, whose
+/// `DOMContentLoaded` and `LoadComplete` come next like those of any other document.
+/// Documents that aren't fetched, like `about:blank` ones, only have these two steps.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NavigationEvent {
+    pub browsing_context_id: BrowsingContextId,
+    /// The pipeline of the new document, which the steps of a navigation share.
+    pub pipeline_id: PipelineId,
+    /// The URL being loaded, which is the one the last redirect led to.
+    pub url: ServoUrl,
+    pub step: NavigationStep,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum NavigationStep {
+    /// The document started to be fetched, or a redirect was followed.
+    Started {
+        is_redirect: bool,
+        is_form_submission: bool,
+        /// Whether the document of a session history entry is loaded again, or is shown
+        /// again from the history without being loaded, in which case `LoadComplete`
+        /// follows right away.
+        is_history_traversal: bool,
+    },
+    /// The response with the document was received, with its HTTP status if it has one.
+    ResponseReceived {
+        status: Option<u16>,
+    },
+    DOMContentLoaded,
+    LoadComplete,
+    Failed(NavigationError),
+}
+
+/// Why a navigation failed, with the message of the network error.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum NavigationError {
+    /// The request failed, like when the host couldn't be resolved or connected to, or
+    /// the scheme isn't supported.
+    Network(String),
+    /// The certificate of the server couldn't be validated.
+    Certificate(String),
+    /// The fetch was cancelled.
+    Cancelled,
+}
+
+/// A certificate of a server that failed to verify.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CertificateError {
+    /// The URL of the document that was being loaded.
+    pub url: ServoUrl,
+    pub origin: ImmutableOrigin,
+    pub kind: CertificateErrorKind,
+    /// The message of the error of the verification.
+    pub message: String,
+    /// The DER encoded certificates that the server presented, starting with its own.
+    pub chain: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CertificateErrorKind {
+    Expired,
+    NotValidYet,
+    Revoked,
+    /// The certificate wasn't issued by a trusted authority, like self-signed ones.
+    UnknownIssuer,
+    BadSignature,
+    /// The certificate isn't valid for the host of the server.
+    NameMismatch,
+    Other,
+}
+
+/// The challenge of a server or proxy that asked for credentials with a 401 or 407 response.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuthenticationRequest {
+    /// The URL of the resource that was being loaded.
+    pub url: ServoUrl,
+    pub origin: ImmutableOrigin,
+    /// The realm of the challenge, if it has one.
+    pub realm: Option<String>,
+    /// Whether the proxy rather than the server asks for credentials.
+    pub for_proxy: bool,
+    /// How many of the credentials that were tried for this load were refused, which is 0
+    /// the first time the embedder is asked.
+    pub retry_count: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuthenticationResponse {
+    pub username: String,
+    pub password: String,
+}
+
+/// The cookie jars that the embedder manages cookies in. Each webview group has a jar of
+/// its own, and the cookies of private webviews, which are forgotten when they close,
+/// aren't managed.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CookieScope {
+    /// The jar of every webview group.
+    Global,
+    /// The jar of a single group, `WebViewGroupId::default()` being that of the webviews
+    /// that aren't created in another one.
+    Group(WebViewGroupId),
+}
+
+/// A cookie in a cookie jar.
+/// <https://httpwg.org/specs/rfc6265.html#storage-model>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    /// The host the cookie is sent to, and to whose subdomains unless it is host-only.
+    pub domain: String,
+    /// Whether the cookie is only sent to `domain` itself.
+    pub host_only: bool,
+    pub path: String,
+    /// When the cookie expires, or `None` if it is removed at the end of the session.
+    pub expires: Option<SystemTime>,
+    pub secure: bool,
+    pub http_only: bool,
+    /// The `SameSite` attribute of the cookie, if it has one.
+    pub same_site: Option<CookieSameSite>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CookieSameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CookieChange {
+    /// The cookie was added, or replaced the one with the same name, domain and path.
+    Set(StoredCookie),
+    /// The cookie was deleted, or the data of its site was cleared. Cookies that are
+    /// removed because they expired aren't reported.
+    Removed(StoredCookie),
+}
+
+/// An entry of the session history of a webview.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    pub url: ServoUrl,
+    /// The title of the document of the entry, once it has a title that isn't empty.
+    pub title: Option<String>,
+}
+
+/// Where keyboard focus came from when a webview gained it, or went to when it lost it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FocusReason {
+    /// Another webview.
+    WebView,
+    /// The user interface of the embedder, such as its location bar, which receives
+    /// key events while it has focus.
+    EmbedderUi,
+    /// Another native window or application. A webview that loses focus this way stays
+    /// the focused webview, and gets focus back along with its native window.
+    NativeWindow,
+}
+
+/// A change to where a webview is in the painting order, in which later webviews are
+/// painted over earlier ones.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PaintingOrderChange {
+    /// Paint the webview above all others.
+    RaiseToTop,
+    /// Paint the webview below all others.
+    LowerToBottom,
+    /// Move the webview to the given index, counting from the bottom. An index past
+    /// the top moves it to the top.
+    SetIndex(usize),
+}
+
+/// How much of a webview is kept alive, so that embedders with many webviews can
+/// free the resources of those in the background. Focusing a webview makes it active.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum WebViewLifecycleState {
+    /// The webview runs normally.
+    #[default]
+    Active,
+    /// The documents of the webview are kept, but their timers, animations and media
+    /// are suspended, as for documents in the session history.
+    Frozen,
+    /// The documents of the webview are closed, and the current page is loaded again
+    /// when the webview becomes active.
+    Discarded,
+}
+
+/// The memory that the documents of a webview use, in bytes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct WebViewMemoryUsage {
+    /// The JavaScript heaps of the script threads running the documents. A script thread
+    /// has one heap for all its documents, which is divided evenly between them.
+    pub js: usize,
+    /// The display lists and style data of layout.
+    pub layout: usize,
+    /// The decoded images of the image elements.
+    pub images: usize,
+    /// The DOM nodes and what they own outside the JavaScript heap.
+    pub dom: usize,
+}
+
+impl WebViewMemoryUsage {
+    pub fn total(&self) -> usize {
+        self.js + self.layout + self.images + self.dom
+    }
+}
+
+impl std::ops::AddAssign for WebViewMemoryUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.js += other.js;
+        self.layout += other.layout;
+        self.images += other.images;
+        self.dom += other.dom;
+    }
+}
+
+/// How promptly the tasks of the documents of a webview are run, so that the webviews
+/// the user is looking at stay responsive while others do work in the background.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum WebViewPriority {
+    /// Tasks run as soon as possible.
+    #[default]
+    Normal,
+    /// Tasks are held back while the script thread is busy with the tasks of other
+    /// documents, like those of low priority task sources.
+    Low,
+}
+
+/// Whether a webview is loading a page.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum WebViewLoadState {
+    /// The top-level document has finished loading, or nothing was loaded yet.
+    #[default]
+    Idle,
+    /// The top-level document is loading.
+    Loading,
+}
+
+/// What the top-level document of a webview last reported about itself, for embedders
+/// to show in their tabs.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct WebViewMetadata {
+    /// The URL of the current entry of the session history.
+    pub url: Option<ServoUrl>,
+    pub title: Option<String>,
+    pub favicon_url: Option<ServoUrl>,
+    pub load_state: WebViewLoadState,
+    /// The webview whose document opened this one with `window.open`, if it still exists.
+    pub opener: Option<TopLevelBrowsingContextId>,
+}
+
+/// The version of [`SessionState`] written by this version of Servo. Sessions saved
+/// with another version can't be restored.
+pub const SESSION_STATE_VERSION: u32 = 1;
+
+/// The state of every webview, which embedders can save and restore to implement
+/// "restore previous session". It can be serialized in any format serde supports.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionState {
+    /// The [`SESSION_STATE_VERSION`] of the Servo that saved the session.
+    pub version: u32,
+    /// The webviews from the bottom to the top of the painting order.
+    pub webviews: Vec<WebViewSessionState>,
+    /// The indices in `webviews` of the webviews that were focused, from the least to the
+    /// most recently focused.
+    pub focus_order: Vec<usize>,
+    /// Whether the most recently focused webview was still focused.
+    pub is_focused: bool,
+}
+
+/// The state of a webview in a [`SessionState`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WebViewSessionState {
+    /// The URLs of the entries of the session history, oldest first.
+    pub history: Vec<ServoUrl>,
+    /// The index of the current entry in `history`.
+    pub current_index: usize,
+    /// How far the current document was scrolled.
+    pub scroll_offset: LayoutVector2D,
+    /// The group whose cookies, caches and storage the webview uses.
+    pub group_id: WebViewGroupId,
+}
+
+/// Identifies a script that the embedder added with `Servo::add_user_script`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UserScriptId(pub u64);
+
+/// A script or stylesheet that the embedder injects into the documents whose URLs match
+/// its patterns.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UserScript {
+    pub source: UserScriptSource,
+    /// The URL patterns of the documents to inject into, in which `*` matches any
+    /// sequence of characters. Every document matches when there are none.
+    pub matches: Vec<String>,
+    pub injection_time: UserScriptInjectionTime,
+    pub world: UserScriptWorld,
+    /// Whether to inject into the documents of iframes as well as top-level ones.
+    pub all_frames: bool,
+}
+
+impl UserScript {
+    /// Whether the script is injected into a document with the given URL.
+    pub fn matches_url(&self, url: &ServoUrl) -> bool {
+        self.matches.is_empty() ||
+            self.matches
+                .iter()
+                .any(|pattern| matches_pattern(pattern, url.as_str()))
+    }
+}
+
+/// Whether `text` matches a pattern in which `*` matches any sequence of characters.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let mut rest = match text.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<&str> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        // There was no `*`, so the whole text has to be the pattern.
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum UserScriptSource {
+    /// A classic script.
+    Script(String),
+    /// A user stylesheet, which applies to the document without being part of its
+    /// `document.styleSheets`.
+    Stylesheet(String),
+}
+
+/// When a [`UserScript`] is injected into a document.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UserScriptInjectionTime {
+    /// Before any script of the document runs and before it is parsed.
+    DocumentStart,
+    /// Once the document is parsed, before `DOMContentLoaded` is fired.
+    DocumentEnd,
+}
+
+/// Where the scripts of a [`UserScript`] run. Stylesheets ignore it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UserScriptWorld {
+    /// In the global of the document, where the script sees the globals of the page and
+    /// the page sees those of the script.
+    Page,
+    /// In a global of its own, which has its own builtins and exposes the `window` and
+    /// `document` of the page, so the globals of the script and those of the page don't
+    /// collide. The isolated world of a document is shared by its isolated scripts.
+    Isolated,
+}
+
+/// The frame of a webview that `Servo::evaluate_javascript` runs a script in.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FrameSelector {
+    TopLevel,
+    /// The frame that has the focus of the webview, or the top-level one.
+    Focused,
+    /// A frame of the webview, such as one of its [`FrameTreeNode`]s.
+    Frame(BrowsingContextId),
+}
+
+/// Why `Servo::evaluate_javascript` failed.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum JavaScriptEvaluationError {
+    /// The webview or the frame doesn't exist, or the frame has no document.
+    FrameNotFound,
+    /// The script threw an exception, or the promise it returned was rejected.
+    Exception(JavaScriptException),
+    /// The result couldn't be serialized to JSON, like an object with a cycle.
+    SerializationFailed,
+}
+
+/// An exception of a script that the embedder ran.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JavaScriptException {
+    pub message: String,
+    pub filename: String,
+    pub line_number: u32,
+    pub column: u32,
 }
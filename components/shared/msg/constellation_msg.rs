@@ -269,6 +269,17 @@ impl fmt::Display for BrowsingContextGroupId {
     }
 }
 
+/// A group of webviews that share cookies, caches and storage with each other but not
+/// with the webviews of other groups, like a browser profile. Webviews are in the
+/// default group unless the embedder creates them in another one.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct WebViewGroupId(pub u32);
+impl fmt::Display for WebViewGroupId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WebViewGroup{}", self.0)
+    }
+}
+
 thread_local!(pub static TOP_LEVEL_BROWSING_CONTEXT_ID: Cell<Option<TopLevelBrowsingContextId>> = Cell::new(None));
 
 #[derive(
@@ -6,9 +6,10 @@ use std::cmp::{max, min};
 use std::ops::Range;
 use std::path::PathBuf;
 
-use embedder_traits::FilterPattern;
+use embedder_traits::{FilePickerRequest, FilterPattern};
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
+use msg::constellation_msg::WebViewId;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -119,21 +120,28 @@ pub struct SelectedFile {
     pub size: u64,
     // https://w3c.github.io/FileAPI/#dfn-type
     pub type_string: String,
+    /// The path of the file from the directory the user picked, starting with the name of
+    /// the directory, or empty if the user picked the file itself.
+    // https://wicg.github.io/entries-api/#dom-file-webkitrelativepath
+    pub relative_path: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum FileManagerThreadMsg {
-    /// Select a single file. Last field is pre-selected file path for testing
+    /// Select a single file in a webview. Last field is pre-selected file path for testing
     SelectFile(
+        WebViewId,
         Vec<FilterPattern>,
         IpcSender<FileManagerResult<SelectedFile>>,
         FileOrigin,
         Option<String>,
     ),
 
-    /// Select multiple files. Last field is pre-selected file paths for testing
+    /// Select multiple files, or the files of a directory, in a webview. Last field is
+    /// pre-selected file paths for testing
     SelectFiles(
-        Vec<FilterPattern>,
+        WebViewId,
+        FilePickerRequest,
         IpcSender<FileManagerResult<Vec<SelectedFile>>>,
         FileOrigin,
         Option<Vec<String>>,
@@ -139,6 +139,10 @@ pub trait ImageCache: Sync + Send {
 
     /// Inform the image cache about a response for a pending request.
     fn notify_pending_response(&self, id: PendingImageId, action: FetchResponseMsg);
+
+    /// Forget the loaded images that nothing else holds on to, so that their memory
+    /// can be freed. They are fetched again the next time they are requested.
+    fn purge_unused_images(&self);
 }
 
 /// Whether this response passed any CORS checks, and is thus safe to read from
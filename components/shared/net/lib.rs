@@ -7,6 +7,9 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use cookie::Cookie;
+use embedder_traits::{
+    CookieChange, PermissionDecision, PermissionName, PermissionSetting, StoredCookie,
+};
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap, StatusCode};
 use hyper::Error as HyperError;
@@ -19,7 +22,7 @@ use log::warn;
 use malloc_size_of::malloc_size_of_is_0;
 use malloc_size_of_derive::MallocSizeOf;
 use mime::Mime;
-use msg::constellation_msg::HistoryStateId;
+use msg::constellation_msg::{HistoryStateId, PipelineId, WebViewGroupId};
 use num_traits::Zero;
 use rustls::Certificate;
 use serde::{Deserialize, Serialize};
@@ -35,9 +38,11 @@ use crate::storage_thread::StorageThreadMsg;
 pub mod blob_url_store;
 pub mod filemanager_thread;
 pub mod image_cache;
+pub mod protocols;
 pub mod pub_domains;
 pub mod quality;
 pub mod request;
+pub mod request_interceptor;
 pub mod response;
 pub mod storage_thread;
 
@@ -392,6 +397,86 @@ impl IpcSend<StorageThreadMsg> for ResourceThreads {
 // Ignore the sub-fields
 malloc_size_of_is_0!(ResourceThreads);
 
+/// The kinds of data that can be removed for a site, either by the embedder or by
+/// a `Clear-Site-Data` response header.
+/// <https://w3c.github.io/webappsec-clear-site-data/#grammardef-type>
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ClearDataTypes {
+    /// Cached responses.
+    pub cache: bool,
+    /// Cookies, and the credentials kept alongside them.
+    pub cookies: bool,
+    /// `localStorage` and `sessionStorage`.
+    pub web_storage: bool,
+    /// Service worker registrations.
+    pub service_workers: bool,
+    /// The hosts that responses asked to only be loaded over HTTPS with a
+    /// `Strict-Transport-Security` header. Those of the preload list are kept.
+    pub hsts: bool,
+    /// Documents, which are reloaded.
+    pub execution_contexts: bool,
+}
+
+impl ClearDataTypes {
+    pub fn all() -> ClearDataTypes {
+        ClearDataTypes {
+            cache: true,
+            cookies: true,
+            web_storage: true,
+            service_workers: true,
+            hsts: true,
+            execution_contexts: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ClearDataTypes::default()
+    }
+
+    /// <https://w3c.github.io/webappsec-clear-site-data/#parsing>
+    pub fn from_header_value(value: &str) -> ClearDataTypes {
+        let mut types = ClearDataTypes::default();
+        for token in value.split(',') {
+            // Each type is a quoted string, and unknown types are ignored.
+            let token = token.trim();
+            let token = match token.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+                Some(token) => token,
+                None => continue,
+            };
+            match token {
+                "cache" => types.cache = true,
+                "cookies" => types.cookies = true,
+                "storage" => {
+                    types.web_storage = true;
+                    types.service_workers = true;
+                },
+                "executionContexts" => types.execution_contexts = true,
+                "*" => {
+                    // The HSTS hosts of a site protect it rather than identifying its users.
+                    return ClearDataTypes {
+                        hsts: false,
+                        ..ClearDataTypes::all()
+                    };
+                },
+                _ => {},
+            }
+        }
+        types
+    }
+}
+
+/// The data that the embedder clears.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ClearDataScope {
+    /// The data of every origin, in every webview group.
+    Everything,
+    /// The data of the given origins, in every webview group.
+    Origins(Vec<ImmutableOrigin>),
+    /// The data of every origin, in the given webview group. Service worker
+    /// registrations are shared by every group, so they are removed for all of them.
+    Group(WebViewGroupId),
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum IncludeSubdomains {
     Included,
@@ -457,6 +542,14 @@ pub enum CoreResourceMsg {
         CookieSource,
     ),
     DeleteCookies(ServoUrl),
+    /// Retrieve the cookies that would be sent to a URL, or every cookie if none is given
+    GetStoredCookies(Option<ServoUrl>, IpcSender<Vec<StoredCookie>>),
+    /// Store a cookie, replacing the one with the same name, domain and path
+    SetStoredCookie(StoredCookie),
+    /// Delete the cookie with the same name, domain and path as the given one
+    DeleteStoredCookie(StoredCookie),
+    /// Send each change to the cookie jar with the given sender, or stop sending them
+    ObserveCookieChanges(Option<IpcSender<CookieChange>>),
     /// Get a history state by a given history state id
     GetHistoryState(HistoryStateId, IpcSender<Option<Vec<u8>>>),
     /// Set a history state for a given history state id
@@ -467,6 +560,29 @@ pub enum CoreResourceMsg {
     Synchronize(IpcSender<()>),
     /// Clear the network cache.
     ClearCache,
+    /// Retrieve the permission store's setting for a permission on an origin
+    GetPermission(
+        ImmutableOrigin,
+        PermissionName,
+        IpcSender<Option<PermissionSetting>>,
+    ),
+    /// Store a permission decision, replacing any previous one
+    SetPermission(PermissionDecision),
+    /// Retrieve every decision in the permission store
+    GetPermissionDecisions(IpcSender<Vec<PermissionDecision>>),
+    /// Forget the permission decisions for an origin, or for every origin if none is given
+    ClearPermissionDecisions(Option<ImmutableOrigin>),
+    /// Remove cached responses, cookies and HSTS hosts belonging to the given origins, or
+    /// to every origin if none are given. Only data created after the given time is removed, when
+    /// the data records that.
+    ClearData(
+        Option<Vec<ImmutableOrigin>>,
+        ClearDataTypes,
+        Option<SystemTime>,
+    ),
+    /// Prefer the given languages in the Accept-Language header of the requests of a
+    /// pipeline, or send the default header again if there are none.
+    SetPipelineLanguages(PipelineId, Vec<String>),
     /// Send the service worker network mediator for an origin to CoreResourceThread
     NetworkMediator(IpcSender<CustomResponseMediator>, ImmutableOrigin),
     /// Message forwarded to file manager's handler
@@ -833,6 +949,7 @@ pub fn http_percent_encode(bytes: &[u8]) -> String {
 pub enum NetToCompositorMsg {
     AddImage(ImageKey, ImageDescriptor, ImageData),
     GenerateImageKey(IpcSender<ImageKey>),
+    DeleteImages(Vec<ImageKey>),
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -859,6 +976,12 @@ impl WebrenderIpcSender {
             warn!("Error sending image update: {}", e);
         }
     }
+
+    pub fn delete_images(&self, keys: Vec<ImageKey>) {
+        if let Err(e) = self.0.send(NetToCompositorMsg::DeleteImages(keys)) {
+            warn!("Error sending image deletion: {}", e);
+        }
+    }
 }
 
 lazy_static! {
@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Custom URL schemes that the embedder registers handlers for, such as `app://`. Their
+//! URLs are loaded by fetch like those of any other scheme, so they can be used for
+//! documents, subresources, `fetch()` and workers.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+
+use http::{HeaderMap, StatusCode};
+
+use crate::request::Request;
+
+/// The schemes that Servo loads itself, which can't be registered.
+const BUILTIN_SCHEMES: [&str; 12] = [
+    "about",
+    "blob",
+    "chrome",
+    "data",
+    "file",
+    "filesystem",
+    "ftp",
+    "http",
+    "https",
+    "javascript",
+    "ws",
+    "wss",
+];
+
+/// The response of a [`ProtocolHandler`] to a request.
+pub struct ProtocolResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The body, which is read in chunks and streamed to the fetch as they come.
+    pub body: Box<dyn Read + Send>,
+}
+
+impl ProtocolResponse {
+    /// A `200 OK` response with the given headers and body.
+    pub fn new(headers: HeaderMap, body: impl Read + Send + 'static) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers,
+            body: Box::new(body),
+        }
+    }
+
+    /// A `200 OK` response whose whole body is known up front.
+    pub fn from_bytes(headers: HeaderMap, bytes: Vec<u8>) -> Self {
+        Self::new(headers, Cursor::new(bytes))
+    }
+}
+
+/// Loads the URLs of a custom scheme.
+pub trait ProtocolHandler: Send + Sync {
+    /// Load the current URL of `request`. This is called on the network threads, so it
+    /// should return quickly: a body that takes time to produce is better streamed by a
+    /// reader that waits for it. An error fails the fetch with a network error.
+    fn load(&self, request: &Request) -> Result<ProtocolResponse, String>;
+}
+
+/// How requests for the URLs of a custom scheme that need CORS are treated. These URLs
+/// have opaque origins, so even requests from documents of the same scheme need it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ProtocolCors {
+    /// The requests fail, as they do for the other schemes that aren't HTTP.
+    #[default]
+    Blocked,
+    /// The responses are checked for CORS headers, like HTTP responses are.
+    Checked,
+    /// The responses are treated as same-origin ones.
+    Allowed,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProtocolOptions {
+    /// Whether the URLs of the scheme are potentially trustworthy, which makes documents
+    /// and workers loaded from them secure contexts.
+    pub secure: bool,
+    pub cors: ProtocolCors,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ProtocolRegisterError {
+    /// The scheme isn't a valid URL scheme.
+    InvalidScheme,
+    /// Servo loads the scheme itself.
+    BuiltinScheme,
+    /// A handler is registered for the scheme already.
+    AlreadyRegistered,
+}
+
+impl fmt::Display for ProtocolRegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolRegisterError::InvalidScheme => write!(f, "invalid scheme"),
+            ProtocolRegisterError::BuiltinScheme => write!(f, "scheme is built in"),
+            ProtocolRegisterError::AlreadyRegistered => write!(f, "scheme is registered already"),
+        }
+    }
+}
+
+/// The custom schemes that the embedder registered, with their handlers.
+#[derive(Clone, Default)]
+pub struct ProtocolRegistry {
+    handlers: HashMap<String, (Arc<dyn ProtocolHandler>, ProtocolOptions)>,
+}
+
+impl ProtocolRegistry {
+    /// Register the handler of a scheme, which is case-insensitive.
+    pub fn register(
+        &mut self,
+        scheme: &str,
+        handler: impl ProtocolHandler + 'static,
+        options: ProtocolOptions,
+    ) -> Result<(), ProtocolRegisterError> {
+        // <https://url.spec.whatwg.org/#scheme-state>
+        let mut chars = scheme.chars();
+        let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic()) &&
+            chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !valid {
+            return Err(ProtocolRegisterError::InvalidScheme);
+        }
+        let scheme = scheme.to_ascii_lowercase();
+        if BUILTIN_SCHEMES.contains(&&*scheme) {
+            return Err(ProtocolRegisterError::BuiltinScheme);
+        }
+        if self.handlers.contains_key(&scheme) {
+            return Err(ProtocolRegisterError::AlreadyRegistered);
+        }
+        self.handlers.insert(scheme, (Arc::new(handler), options));
+        Ok(())
+    }
+
+    pub fn get(&self, scheme: &str) -> Option<&dyn ProtocolHandler> {
+        self.handlers.get(scheme).map(|(handler, _)| &**handler)
+    }
+
+    pub fn options(&self, scheme: &str) -> Option<ProtocolOptions> {
+        self.handlers.get(scheme).map(|&(_, options)| options)
+    }
+
+    /// How requests for the URLs of a scheme that need CORS are treated.
+    pub fn cors(&self, scheme: &str) -> ProtocolCors {
+        self.options(scheme)
+            .map_or(ProtocolCors::Blocked, |options| options.cors)
+    }
+
+    /// The registered schemes whose URLs are potentially trustworthy.
+    pub fn secure_schemes(&self) -> Vec<String> {
+        self.handlers
+            .iter()
+            .filter(|(_, (_, options))| options.secure)
+            .map(|(scheme, _)| scheme.clone())
+            .collect()
+    }
+}
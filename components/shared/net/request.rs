@@ -246,6 +246,7 @@ pub struct RequestBuilder {
     pub pipeline_id: Option<PipelineId>,
     pub redirect_mode: RedirectMode,
     pub integrity_metadata: String,
+    pub cryptographic_nonce_metadata: String,
     // This is nominally a part of the client's global object.
     // It is copied here to avoid having to reach across the thread
     // boundary every time a redirect occurs.
@@ -283,6 +284,7 @@ impl RequestBuilder {
             pipeline_id: None,
             redirect_mode: RedirectMode::Follow,
             integrity_metadata: "".to_owned(),
+            cryptographic_nonce_metadata: "".to_owned(),
             url_list: vec![],
             parser_metadata: ParserMetadata::Default,
             initiator: Initiator::None,
@@ -373,6 +375,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn cryptographic_nonce_metadata(mut self, nonce_metadata: String) -> RequestBuilder {
+        self.cryptographic_nonce_metadata = nonce_metadata;
+        self
+    }
+
     pub fn parser_metadata(mut self, parser_metadata: ParserMetadata) -> RequestBuilder {
         self.parser_metadata = parser_metadata;
         self
@@ -423,6 +430,7 @@ impl RequestBuilder {
         request.redirect_count = url_list.len() as u32 - 1;
         request.url_list = url_list;
         request.integrity_metadata = self.integrity_metadata;
+        request.cryptographic_nonce_metadata = self.cryptographic_nonce_metadata;
         request.parser_metadata = self.parser_metadata;
         request.csp_list = self.csp_list;
         request.response_tainting = self.response_tainting;
@@ -484,6 +492,8 @@ pub struct Request {
     pub redirect_mode: RedirectMode,
     /// <https://fetch.spec.whatwg.org/#concept-request-integrity-metadata>
     pub integrity_metadata: String,
+    /// <https://fetch.spec.whatwg.org/#concept-request-nonce-metadata>
+    pub cryptographic_nonce_metadata: String,
     // Use the last method on url_list to act as spec current url field, and
     // first method to act as spec url field
     /// <https://fetch.spec.whatwg.org/#concept-request-url-list>
@@ -536,6 +546,7 @@ impl Request {
             cache_mode: CacheMode::Default,
             redirect_mode: RedirectMode::Follow,
             integrity_metadata: String::new(),
+            cryptographic_nonce_metadata: String::new(),
             url_list: vec![url],
             parser_metadata: ParserMetadata::Default,
             redirect_count: 0,
@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Interceptors that the embedder registers to see the requests of fetch before they are
+//! loaded, which can change their headers, redirect them, block them or answer them
+//! with a response of their own.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use http::{HeaderMap, Method};
+use msg::constellation_msg::{PipelineId, WebViewId};
+use servo_url::ServoUrl;
+
+use crate::protocols::ProtocolResponse;
+use crate::request::Destination;
+
+/// A request that is about to be loaded, including each request of its redirects.
+pub struct InterceptedRequest<'a> {
+    /// The webview that the request comes from, if it comes from one.
+    pub webview_id: Option<WebViewId>,
+    pub url: &'a ServoUrl,
+    pub method: &'a Method,
+    pub destination: Destination,
+    /// The headers of the request, which the interceptor can change.
+    pub headers: &'a mut HeaderMap,
+}
+
+pub enum Interception {
+    /// Load the request, with the changes made to its headers.
+    Continue,
+    /// Redirect the request to an HTTP(S) URL, whatever its redirect mode is.
+    Redirect(ServoUrl),
+    /// Fail the request with a network error.
+    Block,
+    /// Answer the request with the given response instead of loading it.
+    Fulfill(ProtocolResponse),
+}
+
+pub trait RequestInterceptor: Send + Sync {
+    /// Decide what happens to a request. This is called on the network threads, so it
+    /// should return quickly; the body of a fulfilling response can be streamed.
+    fn intercept(&self, request: InterceptedRequest) -> Interception;
+}
+
+/// The requests that an interceptor sees.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InterceptorScope {
+    Global,
+    WebView(WebViewId),
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RequestInterceptorId(u64);
+
+#[derive(Default)]
+struct Interceptors {
+    next_id: u64,
+    /// The interceptors in the order they were added in, which is the order they see
+    /// requests in.
+    interceptors: Vec<(
+        RequestInterceptorId,
+        InterceptorScope,
+        Arc<dyn RequestInterceptor>,
+    )>,
+    /// The webviews of the pipelines, as requests only know the pipeline they come from.
+    pipeline_webviews: HashMap<PipelineId, WebViewId>,
+}
+
+/// The request interceptors of the embedder, which are shared by the embedder, the
+/// constellation and the resource threads of every webview group.
+#[derive(Clone, Default)]
+pub struct RequestInterceptors(Arc<RwLock<Interceptors>>);
+
+impl RequestInterceptors {
+    pub fn add(
+        &self,
+        scope: InterceptorScope,
+        interceptor: impl RequestInterceptor + 'static,
+    ) -> RequestInterceptorId {
+        let mut interceptors = self.0.write().unwrap();
+        let id = RequestInterceptorId(interceptors.next_id);
+        interceptors.next_id += 1;
+        interceptors
+            .interceptors
+            .push((id, scope, Arc::new(interceptor)));
+        id
+    }
+
+    pub fn remove(&self, id: RequestInterceptorId) {
+        self.0
+            .write()
+            .unwrap()
+            .interceptors
+            .retain(|&(other_id, _, _)| other_id != id);
+    }
+
+    /// Set the webview of a pipeline, or forget it when it's `None`.
+    pub fn set_pipeline_webview(&self, pipeline_id: PipelineId, webview_id: Option<WebViewId>) {
+        let mut interceptors = self.0.write().unwrap();
+        let pipeline_webviews = &mut interceptors.pipeline_webviews;
+        match webview_id {
+            Some(webview_id) => pipeline_webviews.insert(pipeline_id, webview_id),
+            None => pipeline_webviews.remove(&pipeline_id),
+        };
+    }
+
+    pub fn webview_of_pipeline(&self, pipeline_id: PipelineId) -> Option<WebViewId> {
+        self.0
+            .read()
+            .unwrap()
+            .pipeline_webviews
+            .get(&pipeline_id)
+            .copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.read().unwrap().interceptors.is_empty()
+    }
+
+    /// The interceptors that see the requests of a webview, or those that don't come from
+    /// one, in the order they see them in.
+    pub fn matching(&self, webview_id: Option<WebViewId>) -> Vec<Arc<dyn RequestInterceptor>> {
+        self.0
+            .read()
+            .unwrap()
+            .interceptors
+            .iter()
+            .filter(|(_, scope, _)| match *scope {
+                InterceptorScope::Global => true,
+                InterceptorScope::WebView(id) => Some(id) == webview_id,
+            })
+            .map(|(_, _, interceptor)| interceptor.clone())
+            .collect()
+    }
+}
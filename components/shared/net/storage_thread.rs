@@ -5,7 +5,7 @@
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum StorageType {
@@ -43,6 +43,9 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, ServoUrl, StorageType),
 
+    /// removes the local and session storage data of the given origins, or of every origin
+    ClearData(Option<Vec<ImmutableOrigin>>),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
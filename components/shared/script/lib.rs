@@ -26,7 +26,11 @@ use canvas_traits::webgl::WebGLPipeline;
 use compositor::ScrollTreeNodeId;
 use crossbeam_channel::{RecvTimeoutError, Sender};
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{CompositorEventVariant, Cursor};
+use embedder_traits::{
+    CompositorEventVariant, ContextMenuAction, Cursor, FindOptions, ImeEvent,
+    JavaScriptEvaluationError, NewWebViewRequest, PrintError, PrintSettings, SensorType,
+    SurroundingText, UserScript, WebViewMemoryUsage, WebViewPriority,
+};
 use euclid::default::Point2D;
 use euclid::{Length, Rect, Scale, Size2D, UnknownUnit, Vector2D};
 use gfx_traits::Epoch;
@@ -34,7 +38,7 @@ use http::{HeaderMap, Method};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::Error as IpcError;
 use keyboard_types::webdriver::Event as WebDriverInputEvent;
-use keyboard_types::{CompositionEvent, KeyboardEvent};
+use keyboard_types::{CompositionEvent, KeyboardEvent, Modifiers};
 use libc::c_void;
 use log::warn;
 use malloc_size_of::malloc_size_of_is_0;
@@ -56,7 +60,9 @@ use servo_atoms::Atom;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use style_traits::{CSSPixel, SpeculativePainter};
 use webgpu::identity::WebGPUMsg;
-use webrender_api::units::{DeviceIntSize, DevicePixel, LayoutPixel, LayoutPoint, WorldPoint};
+use webrender_api::units::{
+    DeviceIntRect, DeviceIntSize, DevicePixel, LayoutPixel, LayoutPoint, WorldPoint,
+};
 use webrender_api::{
     BuiltDisplayList, BuiltDisplayListDescriptor, DocumentId, ExternalImageData, ExternalScrollId,
     HitTestFlags, ImageData, ImageDescriptor, ImageKey, PipelineId as WebRenderPipelineId,
@@ -64,9 +70,9 @@ use webrender_api::{
 
 use crate::compositor::CompositorDisplayListInfo;
 pub use crate::script_msg::{
-    DOMMessage, EventResult, HistoryEntryReplacement, IFrameSizeMsg, Job, JobError, JobResult,
-    JobResultValue, JobType, LayoutMsg, LogEntry, SWManagerMsg, SWManagerSenders, ScopeThings,
-    ScriptMsg, ServiceWorkerMsg,
+    DOMMessage, EventResult, FindHighlights, HistoryEntryReplacement, IFrameSizeMsg, Job, JobError,
+    JobResult, JobResultValue, JobType, LayoutMsg, LogEntry, SWManagerMsg, SWManagerSenders,
+    ScopeThings, ScriptMsg, ServiceWorkerMsg,
 };
 use crate::serializable::{BlobData, BlobImpl};
 use crate::transferable::MessagePortImpl;
@@ -170,6 +176,11 @@ pub struct LoadData {
 
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
+    /// Whether the load submits a form.
+    pub is_form_submission: bool,
+    /// The pipeline of the document that started the load, if script started it and it's
+    /// known.
+    pub initiator_pipeline_id: Option<PipelineId>,
 }
 
 /// The result of evaluating a javascript scheme url.
@@ -205,6 +216,8 @@ impl LoadData {
             srcdoc: "".to_string(),
             inherited_secure_context,
             crash: None,
+            is_form_submission: false,
+            initiator_pipeline_id: None,
         }
     }
 }
@@ -293,6 +306,9 @@ pub enum ConstellationControlMsg {
     ResizeInactive(PipelineId, WindowSizeData),
     /// Window switched from fullscreen mode.
     ExitFullScreen(PipelineId),
+    /// Whether the embedder shows the element that the document asked to show fullscreen
+    /// with `ScriptMsg::RequestFullscreen`.
+    FullscreenEntered(PipelineId, bool),
     /// Notifies the script that the document associated with this pipeline should 'unload'.
     UnloadDocument(PipelineId),
     /// Notifies the script that a pipeline should be closed.
@@ -391,6 +407,88 @@ pub enum ConstellationControlMsg {
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
     /// Notifies the media session about a user requested media session action.
     MediaSessionAction(PipelineId, MediaSessionActionType),
+    /// Notifies the picture-in-picture video about a control the user activated.
+    PictureInPictureAction(PipelineId, PictureInPictureAction),
+    /// Look up the canvas matching a CSS selector, to share its texture with the embedder.
+    ShareCanvasTexture(PipelineId, String),
+    /// A device sensor has a new reading.
+    SensorReading(PipelineId, SensorReading),
+    /// Run an editing command from the embedder in the given document.
+    EditingCommand(PipelineId, EditingCommand),
+    /// Send what is selected in the given document to the embedder.
+    GetSelection(PipelineId),
+    /// Send the value of the focused editable element of the given document, for the input
+    /// method of the embedder.
+    GetImeSurroundingText(PipelineId, IpcSender<Option<SurroundingText>>),
+    /// Run a standard action on what the last context menu of the document was opened on.
+    ContextMenuAction(PipelineId, ContextMenuAction),
+    /// Turn caret browsing on or off in the given document.
+    SetCaretBrowsing(PipelineId, bool),
+    /// Find the matches of a query in a document, replacing those of the last search.
+    Find(PipelineId, String, FindOptions),
+    /// Select the match of the last search with this index and scroll to it, or clear the
+    /// selection if the active match is in another document.
+    ActivateFindMatch(PipelineId, Option<usize>),
+    /// Scroll the iframe of the given browsing context into view, so that the active match
+    /// of the document in it is visible. The box of the match is in device pixels relative
+    /// to the viewport of that document.
+    ScrollFindMatchIntoView(PipelineId, BrowsingContextId, DeviceIntRect),
+    /// Forget the matches of the last search in a document.
+    StopFinding(PipelineId),
+    /// Print a document to a PDF file, laying it out with print media meanwhile.
+    PrintToPdf(
+        PipelineId,
+        PrintSettings,
+        IpcSender<Result<Vec<u8>, PrintError>>,
+    ),
+    /// Open a popup that was blocked, now that the embedder allowed it.
+    OpenBlockedPopup(NewWebViewRequest),
+    /// Stop loading a document and its subresources, as `window.stop()` does.
+    StopLoading(PipelineId),
+    /// Mute or unmute the media elements and AudioContexts of the given document,
+    /// because the embedder muted its webview.
+    SetAudioMuted(PipelineId, bool),
+    /// Enforce a Content-Security-Policy in the given document, in addition to its own
+    /// policies, because the embedder set one for its webview.
+    SetEmbedderCsp(PipelineId, Option<String>),
+    /// Set the scripts that the embedder injects into the given document, which are
+    /// the ones of its webview and the global ones, when it is loaded.
+    SetUserScripts(PipelineId, Vec<UserScript>),
+    /// Run a script that the embedder sent in the given document, and send back its result
+    /// as JSON.
+    EvaluateJavaScript(
+        PipelineId,
+        String,
+        IpcSender<Result<String, JavaScriptEvaluationError>>,
+    ),
+    /// Set the languages in `navigator.languages` of the given document, because the
+    /// embedder set them for its webview.
+    SetLanguages(PipelineId, Vec<String>),
+    /// Set how promptly the tasks of the given document are run, because the embedder set
+    /// the priority of its webview.
+    SetPriority(PipelineId, WebViewPriority),
+    /// Fire the `freeze` or `resume` event at the given document, because the embedder
+    /// suspended or resumed its webview.
+    SetSuspended(PipelineId, bool),
+    /// Whether the given document is in the focus chain of the webview with system
+    /// focus, which fires focus or blur events at its window.
+    SetSystemFocus(PipelineId, bool),
+    /// The device is low on memory, so free what can be rebuilt later.
+    MemoryPressure(MemoryPressureLevel),
+    /// Measure the memory that each document of the script thread uses.
+    CollectMemoryUsage(IpcSender<Vec<(PipelineId, WebViewMemoryUsage)>>),
+    /// Change the heap limit and garbage collection settings of the script thread.
+    SetJsGcSettings(JsGcSettings),
+    /// Nothing is being painted, so this is a good time to collect garbage.
+    IdleGc,
+    /// Send how far the viewport of the given document is scrolled.
+    GetScrollOffset(PipelineId, IpcSender<Vector2D<f32, LayoutPixel>>),
+    /// Scroll the viewport of the given document, as when restoring a session.
+    ScrollTo(PipelineId, Vector2D<f32, LayoutPixel>),
+    /// Dispatch beforeunload to the given top-level document and the documents in it,
+    /// because its webview is being closed, and unload them if they let it close.
+    /// Answered with `ScriptMsg::PromptToUnloadResult`.
+    PromptToUnload(PipelineId),
     /// Notifies script thread that WebGPU server has started
     SetWebGPUPort(IpcReceiver<WebGPUMsg>),
     /// A mesage for a layout from the constellation.
@@ -433,7 +531,38 @@ impl fmt::Debug for ConstellationControlMsg {
             Reload(..) => "Reload",
             PaintMetric(..) => "PaintMetric",
             ExitFullScreen(..) => "ExitFullScreen",
+            FullscreenEntered(..) => "FullscreenEntered",
             MediaSessionAction(..) => "MediaSessionAction",
+            PictureInPictureAction(..) => "PictureInPictureAction",
+            ShareCanvasTexture(..) => "ShareCanvasTexture",
+            SensorReading(..) => "SensorReading",
+            EditingCommand(..) => "EditingCommand",
+            GetSelection(..) => "GetSelection",
+            GetImeSurroundingText(..) => "GetImeSurroundingText",
+            ContextMenuAction(..) => "ContextMenuAction",
+            SetCaretBrowsing(..) => "SetCaretBrowsing",
+            Find(..) => "Find",
+            ActivateFindMatch(..) => "ActivateFindMatch",
+            ScrollFindMatchIntoView(..) => "ScrollFindMatchIntoView",
+            StopFinding(..) => "StopFinding",
+            PrintToPdf(..) => "PrintToPdf",
+            OpenBlockedPopup(..) => "OpenBlockedPopup",
+            StopLoading(..) => "StopLoading",
+            SetAudioMuted(..) => "SetAudioMuted",
+            SetEmbedderCsp(..) => "SetEmbedderCsp",
+            SetUserScripts(..) => "SetUserScripts",
+            EvaluateJavaScript(..) => "EvaluateJavaScript",
+            SetLanguages(..) => "SetLanguages",
+            SetPriority(..) => "SetPriority",
+            SetSuspended(..) => "SetSuspended",
+            SetSystemFocus(..) => "SetSystemFocus",
+            MemoryPressure(..) => "MemoryPressure",
+            CollectMemoryUsage(..) => "CollectMemoryUsage",
+            SetJsGcSettings(..) => "SetJsGcSettings",
+            IdleGc => "IdleGc",
+            GetScrollOffset(..) => "GetScrollOffset",
+            ScrollTo(..) => "ScrollTo",
+            PromptToUnload(..) => "PromptToUnload",
             SetWebGPUPort(..) => "SetWebGPUPort",
             ForLayoutFromConstellation(..) => "ForLayoutFromConstellation",
             ForLayoutFromFontCache(..) => "ForLayoutFromFontCache",
@@ -496,7 +625,7 @@ pub enum MouseButton {
 }
 
 /// The types of mouse events
-#[derive(Debug, Deserialize, MallocSizeOf, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum MouseEventType {
     /// Mouse button clicked
     Click,
@@ -544,6 +673,8 @@ pub enum CompositorEvent {
         Option<Point2D<f32>>,
         // Bitmask of MouseButton values representing the currently pressed buttons
         u16,
+        // The modifier keys that were held down
+        Modifiers,
     ),
     /// The mouse was moved over a point (or was moved out of the recognizable region).
     MouseMoveEvent(
@@ -565,6 +696,8 @@ pub enum CompositorEvent {
     KeyboardEvent(KeyboardEvent),
     /// An event from the IME is dispatched.
     CompositionEvent(CompositionEvent),
+    /// An edit of the input method of the embedder to the focused element.
+    ImeEvent(ImeEvent),
     /// Virtual keyboard was dismissed
     IMEDismissedEvent,
     /// Connected gamepad state updated
@@ -581,6 +714,7 @@ impl From<&CompositorEvent> for CompositorEventVariant {
             CompositorEvent::WheelEvent(..) => CompositorEventVariant::WheelEvent,
             CompositorEvent::KeyboardEvent(..) => CompositorEventVariant::KeyboardEvent,
             CompositorEvent::CompositionEvent(..) => CompositorEventVariant::CompositionEvent,
+            CompositorEvent::ImeEvent(..) => CompositorEventVariant::ImeEvent,
             CompositorEvent::IMEDismissedEvent => CompositorEventVariant::IMEDismissedEvent,
             CompositorEvent::GamepadEvent(..) => CompositorEventVariant::GamepadEvent,
         }
@@ -834,6 +968,12 @@ pub enum WebDriverCommandMsg {
         Option<Rect<f32, CSSPixel>>,
         IpcSender<Option<Image>>,
     ),
+    /// Print the document of a webview to a PDF file.
+    Print(
+        TopLevelBrowsingContextId,
+        PrintSettings,
+        IpcSender<Result<Vec<u8>, PrintError>>,
+    ),
 }
 
 /// Resources required by workerglobalscopes
@@ -1093,6 +1233,23 @@ impl From<i32> for MediaSessionActionType {
     }
 }
 
+/// A control activated on the embedder's picture-in-picture surface.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PictureInPictureAction {
+    /// Resume playback of the video.
+    Play,
+    /// Pause playback of the video.
+    Pause,
+    /// Move the playback time backward by a few seconds.
+    SeekBackward,
+    /// Move the playback time forward by a few seconds.
+    SeekForward,
+    /// The embedder resized the picture-in-picture surface to the given size.
+    Resize(u32, u32),
+    /// The user closed the picture-in-picture surface.
+    Close,
+}
+
 /// The result of a hit test in the compositor.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CompositorHitTestResult {
@@ -1353,3 +1510,73 @@ pub enum GamepadUpdateType {
     /// <https://www.w3.org/TR/gamepad/#dfn-represents-a-standard-gamepad-button>
     Button(usize, f64),
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+/// A reading from a device sensor, in the device's coordinate system
+pub enum SensorReading {
+    /// Acceleration including gravity, in m/s²
+    /// <https://w3c.github.io/accelerometer/#accelerometer-sensor-type>
+    Accelerometer { x: f64, y: f64, z: f64 },
+    /// Angular velocity around each axis, in rad/s
+    /// <https://w3c.github.io/gyroscope/#gyroscope-sensor-type>
+    Gyroscope { x: f64, y: f64, z: f64 },
+    /// Illuminance, in lux
+    /// <https://w3c.github.io/ambient-light/#ambient-light-sensor-type>
+    AmbientLight { illuminance: f64 },
+    /// Euler angles of the device, in degrees, and whether they are relative
+    /// to the Earth's frame rather than an arbitrary one
+    /// <https://w3c.github.io/deviceorientation/#deviceorientation>
+    Orientation {
+        alpha: f64,
+        beta: f64,
+        gamma: f64,
+        absolute: bool,
+    },
+}
+
+impl SensorReading {
+    /// The sensor that produced this reading.
+    pub fn sensor_type(&self) -> SensorType {
+        match self {
+            SensorReading::Accelerometer { .. } => SensorType::Accelerometer,
+            SensorReading::Gyroscope { .. } => SensorType::Gyroscope,
+            SensorReading::AmbientLight { .. } => SensorType::AmbientLight,
+            SensorReading::Orientation { .. } => SensorType::Orientation,
+        }
+    }
+}
+
+/// An editing command the embedder can run on the focused text control, or on
+/// the selection of the focused document when no text control is focused.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum EditingCommand {
+    SelectAll,
+    Copy,
+    Cut,
+    Paste,
+    Undo,
+}
+
+/// How low the device is on memory, as reported by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum MemoryPressureLevel {
+    /// Free caches that can be rebuilt without affecting what is shown.
+    Moderate,
+    /// Also discard the documents kept for going back and forward in history, which
+    /// are loaded again when they are navigated to.
+    Critical,
+}
+
+/// The JavaScript heap limit and garbage collection scheduling of the script threads
+/// of a webview. The limits that aren't set come from the `js.mem` preferences.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct JsGcSettings {
+    /// The most memory the JavaScript heap of each script thread may use, in megabytes.
+    /// Going over it makes the webview crash.
+    pub max_heap_mb: Option<u32>,
+    /// How long each slice of an incremental collection may take, in milliseconds.
+    pub slice_budget_ms: Option<u32>,
+    /// Whether to collect garbage when the compositor has finished painting and no
+    /// animation is about to paint again.
+    pub idle_gc: bool,
+}
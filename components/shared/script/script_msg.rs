@@ -7,7 +7,10 @@ use std::fmt;
 
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use embedder_traits::{
+    EmbedderMsg, MediaSessionEvent, PermissionDecision, PermissionName, PermissionSetting,
+    PictureInPictureEvent, Screenshot, ScreenshotError, WindowFeatures,
+};
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::Size2D;
 use gfx_traits::Epoch;
@@ -19,13 +22,14 @@ use msg::constellation_msg::{
 };
 use net_traits::request::RequestBuilder;
 use net_traits::storage_thread::StorageType;
-use net_traits::CoreResourceMsg;
+use net_traits::{ClearDataTypes, CoreResourceMsg};
 use serde::{Deserialize, Serialize};
 use servo_url::{ImmutableOrigin, ServoUrl};
 use smallvec::SmallVec;
 use style_traits::CSSPixel;
 use webgpu::{wgpu, WebGPU, WebGPUResponseResult};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use webrender_api::ExternalImageId;
 
 use crate::{
     AnimationState, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg, DocumentState,
@@ -96,6 +100,19 @@ pub enum HistoryEntryReplacement {
     Disabled,
 }
 
+/// Where the matches of the last search of a document are, in device pixels relative to
+/// its viewport, for the compositor to highlight them.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FindHighlights {
+    /// The boxes of the runs of text that have matches, other than the active one.
+    pub matches: Vec<DeviceIntRect>,
+    /// The boxes of the run of text that has the active match, if it is in this document.
+    pub active_match: Vec<DeviceIntRect>,
+    /// The boxes of the iframes of the document, which the highlights of their documents
+    /// are drawn in.
+    pub frames: Vec<(BrowsingContextId, DeviceIntRect)>,
+}
+
 /// Messages from the script to the constellation.
 #[derive(Deserialize, Serialize)]
 pub enum ScriptMsg {
@@ -223,6 +240,19 @@ pub enum ScriptMsg {
     ScriptNewIFrame(IFrameLoadInfoWithData),
     /// Script has opened a new auxiliary browsing context.
     ScriptNewAuxiliary(AuxiliaryBrowsingContextLoadInfo),
+    /// Whether script may open a new auxiliary browsing context, which would load the URL,
+    /// have the target name and the window features, and whether it would have no opener.
+    /// The answer is the id of the webview to create, which the embedder picked.
+    AllowOpeningWebView(
+        Option<ServoUrl>,
+        String,
+        bool,
+        WindowFeatures,
+        IpcSender<Option<TopLevelBrowsingContextId>>,
+    ),
+    /// Whether the webview of the document has transient user activation, which doesn't
+    /// consume it.
+    HasTransientActivation(IpcSender<bool>),
     /// Mark a new document as active
     ActivateDocument,
     /// Set the document state for a pipeline (used by screenshot / reftests)
@@ -246,6 +276,10 @@ pub enum ScriptMsg {
     ForwardDOMMessage(DOMMessage, ServoUrl),
     /// <https://w3c.github.io/ServiceWorker/#schedule-job-algorithm>
     ScheduleJob(Job),
+    /// Clear the data of the origin of this pipeline, as asked for by a
+    /// `Clear-Site-Data` response header.
+    /// <https://w3c.github.io/webappsec-clear-site-data/#header>
+    ClearSiteData(ClearDataTypes),
     /// Get Window Informations size and position
     GetClientWindow(IpcSender<(DeviceIntSize, DeviceIntPoint)>),
     /// Get the screen size (pixel)
@@ -255,6 +289,14 @@ pub enum ScriptMsg {
     /// Notifies the constellation about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(PipelineId, MediaSessionEvent),
+    /// Notifies the constellation about picture-in-picture events of the given pipeline.
+    PictureInPictureEvent(PipelineId, PictureInPictureEvent),
+    /// Ask the embedder to show an element of the document fullscreen, whose box in device
+    /// pixels relative to the viewport this is. It answers with
+    /// `ConstellationControlMsg::FullscreenEntered`.
+    RequestFullscreen(DeviceIntRect),
+    /// The answer to a `ConstellationControlMsg::ShareCanvasTexture` lookup.
+    CanvasTextureShared(String, Option<ExternalImageId>),
     /// Create a WebGPU Adapter instance
     RequestAdapter(
         IpcSender<Option<WebGPUResponseResult>>,
@@ -265,6 +307,37 @@ pub enum ScriptMsg {
     GetWebGPUChan(IpcSender<Option<WebGPU>>),
     /// Notify the constellation of a pipeline's document's title.
     TitleChanged(PipelineId, String),
+    /// Whether the document allowed its webview to close, in answer to
+    /// `ConstellationControlMsg::PromptToUnload`.
+    PromptToUnloadResult(bool),
+    /// Whether the document started or stopped playing audio.
+    AudioStateChanged(bool),
+    /// The number of matches of a query in the document, in answer to
+    /// `ConstellationControlMsg::Find`.
+    FindMatches(String, usize),
+    /// The box of the match that `ConstellationControlMsg::ActivateFindMatch` selected,
+    /// or that `ConstellationControlMsg::ScrollFindMatchIntoView` scrolled to, in device
+    /// pixels relative to the viewport, if it is rendered.
+    ActiveFindMatch(Option<DeviceIntRect>),
+    /// Where the matches of the last search of the document are, after it was searched,
+    /// its active match changed, or it scrolled.
+    FindHighlights(FindHighlights),
+    /// Take a screenshot of the document as it is laid out for printing, with a viewport
+    /// of this size, once the display list of this epoch is painted.
+    CaptureForPrint(
+        DeviceIntSize,
+        Epoch,
+        IpcSender<Result<Screenshot, ScreenshotError>>,
+    ),
+    /// Get the permission setting that applies to an origin in the webview of this
+    /// pipeline, if there is a decision for it.
+    GetPermission(
+        ImmutableOrigin,
+        PermissionName,
+        IpcSender<Option<PermissionSetting>>,
+    ),
+    /// Store the answer to a permission prompt for the webview of this pipeline.
+    SetPermission(PermissionDecision),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -308,6 +381,8 @@ impl fmt::Debug for ScriptMsg {
             ScriptLoadedURLInIFrame(..) => "ScriptLoadedURLInIFrame",
             ScriptNewIFrame(..) => "ScriptNewIFrame",
             ScriptNewAuxiliary(..) => "ScriptNewAuxiliary",
+            AllowOpeningWebView(..) => "AllowOpeningWebView",
+            HasTransientActivation(..) => "HasTransientActivation",
             ActivateDocument => "ActivateDocument",
             SetDocumentState(..) => "SetDocumentState",
             SetLayoutEpoch(..) => "SetLayoutEpoch",
@@ -319,13 +394,25 @@ impl fmt::Debug for ScriptMsg {
             PipelineExited => "PipelineExited",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
             ScheduleJob(..) => "ScheduleJob",
+            ClearSiteData(..) => "ClearSiteData",
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
             MediaSessionEvent(..) => "MediaSessionEvent",
+            PictureInPictureEvent(..) => "PictureInPictureEvent",
+            RequestFullscreen(..) => "RequestFullscreen",
+            CanvasTextureShared(..) => "CanvasTextureShared",
             RequestAdapter(..) => "RequestAdapter",
             GetWebGPUChan(..) => "GetWebGPUChan",
             TitleChanged(..) => "TitleChanged",
+            PromptToUnloadResult(..) => "PromptToUnloadResult",
+            AudioStateChanged(..) => "AudioStateChanged",
+            FindMatches(..) => "FindMatches",
+            ActiveFindMatch(..) => "ActiveFindMatch",
+            FindHighlights(..) => "FindHighlights",
+            CaptureForPrint(..) => "CaptureForPrint",
+            GetPermission(..) => "GetPermission",
+            SetPermission(..) => "SetPermission",
         };
         write!(formatter, "ScriptMsg::{}", variant)
     }
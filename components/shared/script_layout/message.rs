@@ -157,6 +157,14 @@ pub struct ReflowComplete {
     pub pending_images: Vec<PendingImage>,
 }
 
+/// How a document is laid out while it is printed, with print media and the size of
+/// its pages as the viewport.
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
+pub struct PrintLayout {
+    /// Whether the backgrounds of elements are painted. Only Layout 2020 leaves them out.
+    pub backgrounds: bool,
+}
+
 /// Information needed for a script-initiated reflow.
 pub struct ScriptReflow {
     /// General reflow data.
@@ -183,6 +191,8 @@ pub struct ScriptReflow {
     pub animation_timeline_value: f64,
     /// The set of animations for this document.
     pub animations: DocumentAnimationSet,
+    /// How the document is laid out for printing, if it is being printed.
+    pub print_layout: Option<PrintLayout>,
 }
 
 /// A pending restyle.
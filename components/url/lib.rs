@@ -14,6 +14,7 @@ use std::hash::Hasher;
 use std::net::IpAddr;
 use std::ops::{Index, Range, RangeFrom, RangeFull, RangeTo};
 use std::path::Path;
+use std::sync::RwLock;
 
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,19 @@ use url::{Position, Url};
 
 pub use crate::origin::{ImmutableOrigin, MutableOrigin, OpaqueOrigin};
 
+/// The custom schemes that the embedder registered as secure.
+static SECURE_SCHEMES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Set the custom schemes whose URLs are potentially trustworthy. This is process-wide, so
+/// it has to be set in content processes too.
+pub fn set_secure_schemes(schemes: Vec<String>) {
+    *SECURE_SCHEMES.write().unwrap() = schemes;
+}
+
+pub fn secure_schemes() -> Vec<String> {
+    SECURE_SCHEMES.read().unwrap().clone()
+}
+
 #[derive(Clone, Deserialize, Eq, Hash, MallocSizeOf, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct ServoUrl(#[ignore_malloc_size_of = "Arc"] Arc<Url>);
 
@@ -217,6 +231,17 @@ impl ServoUrl {
 
     /// <https://w3c.github.io/webappsec-secure-contexts/#is-origin-trustworthy>
     pub fn is_origin_trustworthy(&self) -> bool {
+        // The URLs of custom schemes have opaque origins, so the schemes that the
+        // embedder considers authenticated (step 3) are checked first.
+        if SECURE_SCHEMES
+            .read()
+            .unwrap()
+            .iter()
+            .any(|scheme| scheme == self.scheme())
+        {
+            return true;
+        }
+
         // Step 1
         if !self.origin().is_tuple() {
             return false;
@@ -20,6 +20,7 @@ use base64::Engine;
 use capabilities::ServoCapabilities;
 use compositing_traits::ConstellationMsg;
 use crossbeam_channel::{after, select, unbounded, Receiver, Sender};
+use embedder_traits::{PrintError, PrintMargins, PrintOrientation, PrintSettings};
 use euclid::{Rect, Size2D};
 use http::method::Method;
 use image::{DynamicImage, ImageFormat, RgbImage};
@@ -51,9 +52,10 @@ use webdriver::actions::{
 use webdriver::capabilities::{Capabilities, CapabilitiesMatching};
 use webdriver::command::{
     ActionsParameters, AddCookieParameters, GetParameters, JavascriptCommandParameters,
-    LocatorParameters, NewSessionParameters, SendKeysParameters, SwitchToFrameParameters,
-    SwitchToWindowParameters, TimeoutsParameters, WebDriverCommand, WebDriverExtensionCommand,
-    WebDriverMessage, WindowRectParameters,
+    LocatorParameters, NewSessionParameters, PrintOrientation as WebDriverPrintOrientation,
+    PrintParameters, SendKeysParameters, SwitchToFrameParameters, SwitchToWindowParameters,
+    TimeoutsParameters, WebDriverCommand, WebDriverExtensionCommand, WebDriverMessage,
+    WindowRectParameters,
 };
 use webdriver::common::{Cookie, Date, LocatorStrategy, Parameters, WebElement};
 use webdriver::error::{ErrorStatus, WebDriverError, WebDriverResult};
@@ -1638,6 +1640,56 @@ impl Handler {
         }
     }
 
+    fn handle_print(&self, parameters: &PrintParameters) -> WebDriverResult<WebDriverResponse> {
+        let orientation = match parameters.orientation {
+            WebDriverPrintOrientation::Portrait => PrintOrientation::Portrait,
+            WebDriverPrintOrientation::Landscape => PrintOrientation::Landscape,
+        };
+        let settings = PrintSettings {
+            orientation,
+            page_width: parameters.page.width,
+            page_height: parameters.page.height,
+            margins: PrintMargins {
+                top: parameters.margin.top,
+                bottom: parameters.margin.bottom,
+                left: parameters.margin.left,
+                right: parameters.margin.right,
+            },
+            scale: parameters.scale,
+            background: parameters.background,
+            shrink_to_fit: parameters.shrink_to_fit,
+            ..PrintSettings::default()
+        };
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let cmd_msg = WebDriverCommandMsg::Print(
+            self.session()?.top_level_browsing_context_id,
+            settings,
+            sender,
+        );
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+
+        match receiver.recv().unwrap() {
+            Ok(pdf) => Ok(WebDriverResponse::Generic(ValueResponse(
+                serde_json::to_value(base64::engine::general_purpose::STANDARD.encode(pdf))?,
+            ))),
+            Err(PrintError::InvalidSettings) => Err(WebDriverError::new(
+                ErrorStatus::InvalidArgument,
+                "Page is too small for its margins",
+            )),
+            Err(PrintError::NoSuchWebView) => Err(WebDriverError::new(
+                ErrorStatus::NoSuchWindow,
+                "No such window",
+            )),
+            Err(PrintError::Failed) => Err(WebDriverError::new(
+                ErrorStatus::UnknownError,
+                "Printing failed",
+            )),
+        }
+    }
+
     fn handle_get_prefs(
         &self,
         parameters: &GetPrefsParameters,
@@ -1781,6 +1833,7 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
             WebDriverCommand::TakeElementScreenshot(ref x) => {
                 self.handle_take_element_screenshot(x)
             },
+            WebDriverCommand::Print(ref x) => self.handle_print(x),
             WebDriverCommand::Extension(ref extension) => match *extension {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
@@ -45,7 +45,8 @@ use wgpu::instance::RequestAdapterOptions;
 use wgpu::pipeline::{ComputePipelineDescriptor, RenderPipelineDescriptor, ShaderModuleDescriptor};
 use wgpu::resource::{
     BufferDescriptor, BufferMapAsyncStatus, BufferMapCallback, BufferMapCallbackC,
-    BufferMapOperation, SamplerDescriptor, TextureDescriptor, TextureViewDescriptor,
+    BufferMapOperation, QuerySetDescriptor, SamplerDescriptor, TextureDescriptor,
+    TextureViewDescriptor,
 };
 use wgt::{Dx12Compiler, InstanceDescriptor};
 
@@ -149,6 +150,11 @@ pub enum WebGPURequest {
         pipeline_layout_id: id::PipelineLayoutId,
         descriptor: PipelineLayoutDescriptor<'static>,
     },
+    CreateQuerySet {
+        device_id: id::DeviceId,
+        query_set_id: id::QuerySetId,
+        descriptor: QuerySetDescriptor<'static>,
+    },
     CreateRenderPipeline {
         device_id: id::DeviceId,
         render_pipeline_id: id::RenderPipelineId,
@@ -187,6 +193,7 @@ pub enum WebGPURequest {
     },
     DestroyBuffer(id::BufferId),
     DestroyDevice(id::DeviceId),
+    DestroyQuerySet(id::QuerySetId),
     DestroySwapChain {
         external_id: u64,
         image_key: ImageKey,
@@ -195,12 +202,28 @@ pub enum WebGPURequest {
     Exit(IpcSender<()>),
     FreeCommandBuffer(id::CommandBufferId),
     FreeDevice(id::DeviceId),
+    /// Upload a decoded video frame into a texture that backs a `GPUExternalTexture`.
+    ImportExternalTexture {
+        device_id: id::DeviceId,
+        texture_id: id::TextureId,
+        texture_view_id: id::TextureViewId,
+        size: wgt::Extent3d,
+        data: IpcSharedMemory,
+    },
     RenderBundleEncoderFinish {
         render_bundle_encoder: RenderBundleEncoder,
         descriptor: RenderBundleDescriptor<'static>,
         render_bundle_id: id::RenderBundleId,
         device_id: id::DeviceId,
     },
+    ResolveQuerySet {
+        command_encoder_id: id::CommandEncoderId,
+        query_set_id: id::QuerySetId,
+        first_query: u32,
+        query_count: u32,
+        destination_id: id::BufferId,
+        destination_offset: wgt::BufferAddress,
+    },
     RequestAdapter {
         sender: IpcSender<Option<WebGPUResponseResult>>,
         options: RequestAdapterOptions,
@@ -655,6 +678,16 @@ impl<'a> WGPU<'a> {
                             global.device_create_pipeline_layout(device_id, &descriptor, pipeline_layout_id)));
                         self.send_result(device_id, scope_id, result);
                     },
+                    WebGPURequest::CreateQuerySet {
+                        device_id,
+                        query_set_id,
+                        descriptor,
+                    } => {
+                        let global = &self.global;
+                        let result = tuple_to_result(gfx_select!(query_set_id =>
+                            global.device_create_query_set(device_id, &descriptor, query_set_id)));
+                        self.send_result(device_id, scope_id, result);
+                    },
                     WebGPURequest::CreateRenderPipeline {
                         device_id,
                         render_pipeline_id,
@@ -799,6 +832,10 @@ impl<'a> WGPU<'a> {
                         let global = &self.global;
                         gfx_select!(device => global.device_drop(device));
                     },
+                    WebGPURequest::DestroyQuerySet(query_set) => {
+                        let global = &self.global;
+                        gfx_select!(query_set => global.query_set_drop(query_set));
+                    },
                     WebGPURequest::DestroySwapChain {
                         external_id,
                         image_key,
@@ -851,6 +888,69 @@ impl<'a> WGPU<'a> {
                             warn!("Unable to send CleanDevice({:?}) ({:?})", device_id, e);
                         }
                     },
+                    WebGPURequest::ImportExternalTexture {
+                        device_id,
+                        texture_id,
+                        texture_view_id,
+                        size,
+                        data,
+                    } => {
+                        let global = &self.global;
+                        // Video frames are decoded as BGRA8, the same format they
+                        // are handed to WebRender in.
+                        let desc = TextureDescriptor {
+                            label: None,
+                            size,
+                            mip_level_count: 1,
+                            sample_count: 1,
+                            dimension: wgt::TextureDimension::D2,
+                            format: wgt::TextureFormat::Bgra8Unorm,
+                            usage: wgt::TextureUsages::TEXTURE_BINDING |
+                                wgt::TextureUsages::COPY_DST,
+                            view_formats: vec![],
+                        };
+                        let result = tuple_to_result(
+                            gfx_select!(texture_id => global.device_create_texture(
+                                device_id,
+                                &desc,
+                                texture_id
+                            )),
+                        );
+                        if result.is_err() {
+                            self.send_result(device_id, scope_id, result);
+                            continue;
+                        }
+                        let texture_cv = ImageCopyTexture {
+                            texture: texture_id,
+                            mip_level: 0,
+                            origin: wgt::Origin3d::ZERO,
+                            aspect: wgt::TextureAspect::All,
+                        };
+                        let data_layout = wgt::ImageDataLayout {
+                            offset: 0,
+                            bytes_per_row: Some(size.width * 4),
+                            rows_per_image: None,
+                        };
+                        let result = gfx_select!(device_id => global.queue_write_texture(
+                            device_id,
+                            &texture_cv,
+                            &data,
+                            &data_layout,
+                            &size
+                        ));
+                        if result.is_err() {
+                            self.send_result(device_id, scope_id, result);
+                            continue;
+                        }
+                        let result = tuple_to_result(
+                            gfx_select!(texture_view_id => global.texture_create_view(
+                                texture_id,
+                                &TextureViewDescriptor::default(),
+                                texture_view_id
+                            )),
+                        );
+                        self.send_result(device_id, scope_id, result);
+                    },
                     WebGPURequest::RenderBundleEncoderFinish {
                         render_bundle_encoder,
                         descriptor,
@@ -868,6 +968,27 @@ impl<'a> WGPU<'a> {
 
                         self.send_result(device_id, scope_id, result);
                     },
+                    WebGPURequest::ResolveQuerySet {
+                        command_encoder_id,
+                        query_set_id,
+                        first_query,
+                        query_count,
+                        destination_id,
+                        destination_offset,
+                    } => {
+                        let global = &self.global;
+                        let result = gfx_select!(
+                            command_encoder_id => global.command_encoder_resolve_query_set(
+                                command_encoder_id,
+                                query_set_id,
+                                first_query,
+                                query_count,
+                                destination_id,
+                                destination_offset
+                            )
+                        );
+                        self.encoder_record_error(command_encoder_id, &result);
+                    },
                     WebGPURequest::RequestAdapter {
                         sender,
                         options,
@@ -1327,6 +1448,7 @@ webgpu_resource!(WebGPUCommandEncoder, id::CommandEncoderId);
 webgpu_resource!(WebGPUComputePipeline, id::ComputePipelineId);
 webgpu_resource!(WebGPUDevice, id::DeviceId);
 webgpu_resource!(WebGPUPipelineLayout, id::PipelineLayoutId);
+webgpu_resource!(WebGPUQuerySet, id::QuerySetId);
 webgpu_resource!(WebGPUQueue, id::QueueId);
 webgpu_resource!(WebGPURenderBundle, id::RenderBundleId);
 webgpu_resource!(WebGPURenderPipeline, id::RenderPipelineId);
@@ -639,6 +639,13 @@ impl HostTrait for HostCallbacks {
         }
     }
 
+    fn on_devtools_websocket_started(&self, port: Result<u16, ()>) {
+        match port {
+            Ok(p) => info!("Devtools WebSocket server running on port {}", p),
+            Err(()) => error!("Error running devtools WebSocket server"),
+        }
+    }
+
     fn show_context_menu(&self, _title: Option<String>, _items: Vec<String>) {}
 
     fn on_panic(&self, _reason: String, _backtrace: Option<String>) {}
@@ -24,7 +24,8 @@ pub use servo::embedder_traits::{
     ContextMenuResult, MediaSessionPlaybackState, PermissionPrompt, PermissionRequest, PromptResult,
 };
 use servo::embedder_traits::{
-    EmbedderMsg, EmbedderProxy, MediaSessionEvent, PromptDefinition, PromptOrigin,
+    EmbedderMsg, EmbedderProxy, FocusReason, MediaSessionEvent, NavigationPolicy, PromptDefinition,
+    PromptOrigin,
 };
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
@@ -154,6 +155,8 @@ pub trait HostTrait {
     fn on_media_session_set_position_state(&self, duration: f64, position: f64, playback_rate: f64);
     /// Called when devtools server is started
     fn on_devtools_started(&self, port: Result<u16, ()>, token: String);
+    /// Called when the devtools WebSocket server is started
+    fn on_devtools_websocket_started(&self, port: Result<u16, ()>);
     /// Called when we get a panic message from constellation
     fn on_panic(&self, reason: String, backtrace: Option<String>);
 }
@@ -646,18 +649,31 @@ impl ServoGlue {
                 EmbedderMsg::ChangePageTitle(title) => {
                     self.callbacks.host_callbacks.on_title_changed(title);
                 },
-                EmbedderMsg::AllowNavigationRequest(pipeline_id, url) => {
+                EmbedderMsg::AllowNavigationRequest(request) => {
                     if let Some(_browser_id) = browser_id {
-                        let data: bool = self
+                        let allowed: bool = self
                             .callbacks
                             .host_callbacks
-                            .on_allow_navigation(url.to_string());
+                            .on_allow_navigation(request.url.to_string());
+                        let policy = if allowed {
+                            NavigationPolicy::Allow
+                        } else {
+                            NavigationPolicy::Deny
+                        };
                         let window_event =
-                            EmbedderEvent::AllowNavigationResponse(pipeline_id, data);
+                            EmbedderEvent::AllowNavigationResponse(request.pipeline_id, policy);
                         self.events.push(window_event);
                         need_update = true;
                     }
                 },
+                EmbedderMsg::EnterFullscreen(..) => {
+                    // The element is shown over the whole webview, which keeps its size.
+                    if let Some(browser_id) = browser_id {
+                        let event = EmbedderEvent::FullscreenEntered(browser_id, true);
+                        self.events.push(event);
+                        need_update = true;
+                    }
+                },
                 EmbedderMsg::HistoryChanged(entries, current) => {
                     let can_go_back = current > 0;
                     let can_go_forward = current < entries.len() - 1;
@@ -666,7 +682,7 @@ impl ServoGlue {
                         .on_history_changed(can_go_back, can_go_forward);
                     self.callbacks
                         .host_callbacks
-                        .on_url_changed(entries[current].clone().to_string());
+                        .on_url_changed(entries[current].url.to_string());
                 },
                 EmbedderMsg::LoadStart => {
                     self.callbacks.host_callbacks.on_load_started();
@@ -716,26 +732,43 @@ impl ServoGlue {
                             .push(EmbedderEvent::SendError(browser_id, reason));
                     }
                 },
-                EmbedderMsg::AllowOpeningWebView(response_chan) => {
+                EmbedderMsg::RequestAuthentication(_request, sender) => {
+                    // The response that asked for credentials is loaded instead.
+                    if let Err(e) = sender.send(None) {
+                        warn!("Failed to send RequestAuthentication response: {}", e);
+                    }
+                },
+                EmbedderMsg::CertificateError(_error, sender) => {
+                    // The page about the error lets the user accept the certificate anyway.
+                    if let Err(e) = sender.send(false) {
+                        warn!("Failed to send CertificateError response: {}", e);
+                    }
+                },
+                EmbedderMsg::AllowOpeningWebView(_request, response_chan) => {
                     // Note: would be a place to handle pop-ups config.
                     // see Step 7 of #the-rules-for-choosing-a-browsing-context-given-a-browsing-context-name
-                    if let Err(e) = response_chan.send(true) {
+                    let new_webview_id = TopLevelBrowsingContextId::new();
+                    if let Err(e) = response_chan.send(Some(new_webview_id)) {
                         warn!("Failed to send AllowOpeningBrowser response: {}", e);
                     };
                 },
                 EmbedderMsg::WebViewOpened(new_webview_id) => {
                     self.webviews.insert(new_webview_id, WebView {});
                     self.creation_order.push(new_webview_id);
-                    self.events
-                        .push(EmbedderEvent::FocusWebView(new_webview_id));
+                    self.events.push(EmbedderEvent::FocusWebView(
+                        new_webview_id,
+                        FocusReason::WebView,
+                    ));
                 },
                 EmbedderMsg::WebViewClosed(webview_id) => {
                     self.webviews.retain(|&id, _| id != webview_id);
                     self.creation_order.retain(|&id| id != webview_id);
                     self.focused_webview_id = None;
                     if let Some(&newest_webview_id) = self.creation_order.last() {
-                        self.events
-                            .push(EmbedderEvent::FocusWebView(newest_webview_id));
+                        self.events.push(EmbedderEvent::FocusWebView(
+                            newest_webview_id,
+                            FocusReason::WebView,
+                        ));
                     } else {
                         self.events.push(EmbedderEvent::Quit);
                     }
@@ -756,7 +789,7 @@ impl ServoGlue {
                 EmbedderMsg::Shutdown => {
                     self.callbacks.host_callbacks.on_shutdown_complete();
                 },
-                EmbedderMsg::PromptPermission(prompt, sender) => {
+                EmbedderMsg::PromptPermission(prompt, _origin, sender) => {
                     let message = match prompt {
                         PermissionPrompt::Request(permission_name) => {
                             format!("Do you want to grant permission for {:?}?", permission_name)
@@ -772,9 +805,8 @@ impl ServoGlue {
 
                     let result = match self.callbacks.host_callbacks.prompt_yes_no(message, true) {
                         PromptResult::Primary => PermissionRequest::Granted,
-                        PromptResult::Secondary | PromptResult::Dismissed => {
-                            PermissionRequest::Denied
-                        },
+                        PromptResult::Secondary => PermissionRequest::Denied,
+                        PromptResult::Dismissed => PermissionRequest::Dismissed,
                     };
 
                     let _ = sender.send(result);
@@ -815,6 +847,11 @@ impl ServoGlue {
                         .host_callbacks
                         .on_devtools_started(port, token);
                 },
+                EmbedderMsg::OnDevtoolsWebSocketStarted(port) => {
+                    self.callbacks
+                        .host_callbacks
+                        .on_devtools_websocket_started(port);
+                },
                 EmbedderMsg::Panic(reason, backtrace) => {
                     self.callbacks.host_callbacks.on_panic(reason, backtrace);
                 },
@@ -828,9 +865,39 @@ impl ServoGlue {
                 EmbedderMsg::Keyboard(..) |
                 EmbedderMsg::SetCursor(..) |
                 EmbedderMsg::NewFavicon(..) |
+                EmbedderMsg::NavigationEvent(..) |
+                EmbedderMsg::FaviconChanged(..) |
                 EmbedderMsg::HeadParsed |
-                EmbedderMsg::SetFullscreenState(..) |
+                EmbedderMsg::ExitFullscreen |
                 EmbedderMsg::ReportProfile(..) |
+                EmbedderMsg::PictureInPictureEvent(..) |
+                EmbedderMsg::CanvasTextureShared(..) |
+                EmbedderMsg::StartSensor(..) |
+                EmbedderMsg::StopSensor(..) |
+                EmbedderMsg::PermissionDecisions(..) |
+                EmbedderMsg::PermissionDecisionChanged(..) |
+                EmbedderMsg::Cookies(..) |
+                EmbedderMsg::CookieChanged(..) |
+                EmbedderMsg::Misspellings(..) |
+                EmbedderMsg::Selection(..) |
+                EmbedderMsg::CaretMoved(..) |
+                EmbedderMsg::WebViewLifecycleStateChanged(..) |
+                EmbedderMsg::SessionState(..) |
+                EmbedderMsg::WebViewMetadata(..) |
+                EmbedderMsg::WebViewCloseResult(..) |
+                EmbedderMsg::WebViewCrashed |
+                EmbedderMsg::WebViewAudioStateChanged(..) |
+                EmbedderMsg::FrameTree(..) |
+                EmbedderMsg::WebViewsDiscarded(..) |
+                EmbedderMsg::PrerenderActivated(..) |
+                EmbedderMsg::ConstellationTrace(..) |
+                EmbedderMsg::WebViewMemoryUsage(..) |
+                EmbedderMsg::FindResult(..) |
+                EmbedderMsg::PopupBlocked(..) |
+                EmbedderMsg::PendingNavigations(..) |
+                EmbedderMsg::ContextMenu(..) |
+                EmbedderMsg::SaveImage(..) |
+                EmbedderMsg::InspectElement(..) |
                 EmbedderMsg::EventDelivered(..) => {},
             }
         }
@@ -17,8 +17,9 @@ use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
 use log::{debug, error, info, trace, warn};
 use servo::compositing::windowing::{EmbedderEvent, WebRenderDebugOption};
 use servo::embedder_traits::{
-    CompositorEventVariant, ContextMenuResult, EmbedderMsg, FilterPattern, PermissionPrompt,
-    PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
+    AuthenticationRequest, AuthenticationResponse, CertificateError, CompositorEventVariant,
+    ContextMenuResult, EmbedderMsg, FilePickerRequest, FocusReason, NavigationPolicy,
+    PermissionPrompt, PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
 };
 use servo::msg::constellation_msg::{TopLevelBrowsingContextId as WebViewId, TraversalDirection};
 use servo::script_traits::{
@@ -528,32 +529,41 @@ where
                             .push(EmbedderEvent::SendError(webview_id, reason));
                     }
                 },
-                EmbedderMsg::AllowNavigationRequest(pipeline_id, _url) => {
+                EmbedderMsg::AllowNavigationRequest(request) => {
                     if let Some(_webview_id) = webview_id {
                         self.event_queue
-                            .push(EmbedderEvent::AllowNavigationResponse(pipeline_id, true));
+                            .push(EmbedderEvent::AllowNavigationResponse(
+                                request.pipeline_id,
+                                NavigationPolicy::Allow,
+                            ));
                     }
                 },
-                EmbedderMsg::AllowOpeningWebView(response_chan) => {
+                EmbedderMsg::AllowOpeningWebView(_request, response_chan) => {
                     // Note: would be a place to handle pop-ups config.
                     // see Step 7 of #the-rules-for-choosing-a-browsing-context-given-a-browsing-context-name
-                    if let Err(e) = response_chan.send(true) {
+                    // The webview is opened as a new tab, whatever window features it asks for.
+                    let new_webview_id = WebViewId::new();
+                    if let Err(e) = response_chan.send(Some(new_webview_id)) {
                         warn!("Failed to send AllowOpeningWebView response: {}", e);
                     };
                 },
                 EmbedderMsg::WebViewOpened(new_webview_id) => {
                     self.webviews.insert(new_webview_id, WebView {});
                     self.creation_order.push(new_webview_id);
-                    self.event_queue
-                        .push(EmbedderEvent::FocusWebView(new_webview_id));
+                    self.event_queue.push(EmbedderEvent::FocusWebView(
+                        new_webview_id,
+                        FocusReason::WebView,
+                    ));
                 },
                 EmbedderMsg::WebViewClosed(webview_id) => {
                     self.webviews.retain(|&id, _| id != webview_id);
                     self.creation_order.retain(|&id| id != webview_id);
                     self.focused_webview_id = None;
                     if let Some(&newest_webview_id) = self.creation_order.last() {
-                        self.event_queue
-                            .push(EmbedderEvent::FocusWebView(newest_webview_id));
+                        self.event_queue.push(EmbedderEvent::FocusWebView(
+                            newest_webview_id,
+                            FocusReason::WebView,
+                        ));
                     } else {
                         self.event_queue.push(EmbedderEvent::Quit);
                     }
@@ -590,19 +600,27 @@ where
                 EmbedderMsg::SetCursor(cursor) => {
                     self.window.set_cursor(cursor);
                 },
-                EmbedderMsg::NewFavicon(_url) => {
+                EmbedderMsg::NewFavicon(_) | EmbedderMsg::FaviconChanged(_) => {
                     // FIXME: show favicons in the UI somehow
                 },
                 EmbedderMsg::HeadParsed => {
                     // FIXME: surface the loading state in the UI somehow
                 },
-                EmbedderMsg::HistoryChanged(urls, current) => {
-                    self.current_url = Some(urls[current].clone());
-                    self.current_url_string = Some(urls[current].clone().into_string());
+                EmbedderMsg::HistoryChanged(entries, current) => {
+                    let url = entries[current].url.clone();
+                    self.current_url_string = Some(url.clone().into_string());
+                    self.current_url = Some(url);
                     history_changed = true;
                 },
-                EmbedderMsg::SetFullscreenState(state) => {
-                    self.window.set_fullscreen(state);
+                EmbedderMsg::EnterFullscreen(_) => {
+                    self.window.set_fullscreen(true);
+                    if let Some(webview_id) = webview_id {
+                        self.event_queue
+                            .push(EmbedderEvent::FullscreenEntered(webview_id, true));
+                    }
+                },
+                EmbedderMsg::ExitFullscreen => {
+                    self.window.set_fullscreen(false);
                 },
                 EmbedderMsg::LoadStart => {
                     // FIXME: surface the loading state in the UI somehow
@@ -623,11 +641,8 @@ where
                             .push(EmbedderEvent::SendError(None, reason));
                     };
                 },
-                EmbedderMsg::SelectFiles(patterns, multiple_files, sender) => {
-                    let res = match (
-                        opts::get().headless,
-                        get_selected_files(patterns, multiple_files),
-                    ) {
+                EmbedderMsg::SelectFiles(request, sender) => {
+                    let res = match (opts::get().headless, get_selected_files(request)) {
                         (true, _) | (false, None) => sender.send(None),
                         (false, Some(files)) => sender.send(Some(files)),
                     };
@@ -637,7 +652,7 @@ where
                             .push(EmbedderEvent::SendError(None, reason));
                     };
                 },
-                EmbedderMsg::PromptPermission(prompt, sender) => {
+                EmbedderMsg::PromptPermission(prompt, _origin, sender) => {
                     let permission_state = prompt_user(prompt);
                     let _ = sender.send(permission_state);
                 },
@@ -658,10 +673,127 @@ where
                     debug!("MediaSessionEvent received");
                     // TODO(ferjm): MediaSession support for winit based browsers.
                 },
+                EmbedderMsg::CanvasTextureShared(..) => {
+                    debug!("CanvasTextureShared received");
+                    // TODO: Compositing shared canvases for winit based browsers.
+                },
+                EmbedderMsg::PictureInPictureEvent(_) => {
+                    debug!("PictureInPictureEvent received");
+                    // TODO: Picture-in-picture surfaces for winit based browsers.
+                },
+                EmbedderMsg::StartSensor(sensor) => {
+                    debug!("StartSensor received for {:?}", sensor);
+                    // TODO: Read device sensors for winit based browsers.
+                },
+                EmbedderMsg::StopSensor(sensor) => {
+                    debug!("StopSensor received for {:?}", sensor);
+                },
+                EmbedderMsg::PermissionDecisions(decisions) => {
+                    debug!("{} permission decisions are stored", decisions.len());
+                },
+                EmbedderMsg::PermissionDecisionChanged(scope, decision) => {
+                    debug!(
+                        "Permission decision for {:?} changed: {:?}",
+                        scope, decision
+                    );
+                },
+                EmbedderMsg::Cookies(cookies) => {
+                    debug!("{} cookies are stored", cookies.len());
+                },
+                EmbedderMsg::CookieChanged(group_id, change) => {
+                    debug!("Cookie of {} changed: {:?}", group_id, change);
+                },
+                EmbedderMsg::Misspellings(misspellings) => {
+                    debug!("{} misspelled words", misspellings.len());
+                },
+                EmbedderMsg::Selection(selection) => {
+                    debug!("{} characters are selected", selection.text.chars().count());
+                },
+                EmbedderMsg::CaretMoved(position) => {
+                    debug!("Caret moved to offset {}", position.offset);
+                },
+                EmbedderMsg::WebViewLifecycleStateChanged(state) => {
+                    debug!("{:?} is now {:?}", webview_id, state);
+                },
+                EmbedderMsg::SessionState(session_state) => {
+                    debug!("Session has {} webviews", session_state.webviews.len());
+                },
+                EmbedderMsg::WebViewMetadata(metadata) => {
+                    for (webview_id, metadata) in metadata {
+                        debug!("{:?} is {:?}", webview_id, metadata);
+                    }
+                },
+                EmbedderMsg::WebViewCloseResult(closed) => {
+                    debug!("{:?} closed: {}", webview_id, closed);
+                },
+                EmbedderMsg::WebViewCrashed => {
+                    debug!("{:?} crashed", webview_id);
+                },
+                EmbedderMsg::WebViewAudioStateChanged(audible) => {
+                    debug!("{:?} audible: {}", webview_id, audible);
+                },
+                EmbedderMsg::FrameTree(frame_tree) => {
+                    debug!("{:?} frame tree: {:?}", webview_id, frame_tree);
+                },
+                EmbedderMsg::WebViewsDiscarded(webview_ids) => {
+                    debug!("Discarded over the webview limit: {:?}", webview_ids);
+                },
+                EmbedderMsg::PrerenderActivated(replaced) => {
+                    debug!("{:?} activated in place of {:?}", webview_id, replaced);
+                },
+                EmbedderMsg::ConstellationTrace(trace) => {
+                    debug!("Constellation trace: {:?}", trace);
+                },
+                EmbedderMsg::WebViewMemoryUsage(usage) => {
+                    debug!("Memory usage: {:?}", usage);
+                },
+                EmbedderMsg::FindResult(result) => {
+                    debug!("Find result: {:?}", result);
+                },
+                EmbedderMsg::PopupBlocked(popup) => {
+                    debug!("Blocked popup {:?} opened by {}", popup.url, popup.opener);
+                },
+                EmbedderMsg::PendingNavigations(navigations) => {
+                    debug!("Pending navigations: {:?}", navigations);
+                },
+                EmbedderMsg::ContextMenu(data) => {
+                    debug!("Context menu opened on {:?}", data);
+                },
+                EmbedderMsg::SaveImage(image) => {
+                    debug!("Saving the image of {} is not supported", image.url);
+                },
+                EmbedderMsg::InspectElement(pipeline_id, node_id) => {
+                    debug!(
+                        "Inspecting node {} of {} is not supported",
+                        node_id, pipeline_id
+                    );
+                },
+                EmbedderMsg::NavigationEvent(event) => {
+                    debug!(
+                        "Navigation of {}: {:?}",
+                        event.browsing_context_id, event.step
+                    );
+                },
+                EmbedderMsg::CertificateError(error, sender) => {
+                    // When it isn't accepted, the page about the error lets the user
+                    // accept it anyway.
+                    if let Err(e) = sender.send(prompt_certificate_error(&error)) {
+                        warn!("Failed to send CertificateError response: {}", e);
+                    }
+                },
+                EmbedderMsg::RequestAuthentication(request, sender) => {
+                    if let Err(e) = sender.send(prompt_credentials(&request)) {
+                        warn!("Failed to send RequestAuthentication response: {}", e);
+                    }
+                },
                 EmbedderMsg::OnDevtoolsStarted(port, _token) => match port {
                     Ok(p) => info!("Devtools Server running on port {}", p),
                     Err(()) => error!("Error running devtools server"),
                 },
+                EmbedderMsg::OnDevtoolsWebSocketStarted(port) => match port {
+                    Ok(p) => info!("Devtools WebSocket server running on port {}", p),
+                    Err(()) => error!("Error running devtools WebSocket server"),
+                },
                 EmbedderMsg::ShowContextMenu(sender, ..) => {
                     let _ = sender.send(ContextMenuResult::Ignored);
                 },
@@ -721,6 +853,63 @@ fn prompt_user(_prompt: PermissionPrompt) -> PermissionRequest {
     PermissionRequest::Denied
 }
 
+#[cfg(target_os = "linux")]
+fn prompt_certificate_error(error: &CertificateError) -> bool {
+    if opts::get().headless {
+        return false;
+    }
+
+    let message = format!(
+        "The certificate of {} is not valid ({}). Do you want to trust it anyway?",
+        error.origin.ascii_serialization(),
+        error.message
+    );
+    match tinyfiledialogs::message_box_yes_no(
+        "Certificate error",
+        &message,
+        MessageBoxIcon::Warning,
+        YesNo::No,
+    ) {
+        YesNo::Yes => true,
+        YesNo::No => false,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn prompt_certificate_error(_error: &CertificateError) -> bool {
+    // TODO popup only supported on linux
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn prompt_credentials(request: &AuthenticationRequest) -> Option<AuthenticationResponse> {
+    if opts::get().headless {
+        return None;
+    }
+
+    let asker = if request.for_proxy {
+        "The proxy".to_owned()
+    } else {
+        request.origin.ascii_serialization()
+    };
+    let mut message = match request.realm {
+        Some(ref realm) => format!("{} asks for credentials for {}.", asker, realm),
+        None => format!("{} asks for credentials.", asker),
+    };
+    if request.retry_count > 0 {
+        message.push_str(" The last ones were refused.");
+    }
+    let username = tinyfiledialogs::input_box("Authentication", &message, "")?;
+    let password = tinyfiledialogs::password_box("Authentication", &message)?;
+    Some(AuthenticationResponse { username, password })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn prompt_credentials(_request: &AuthenticationRequest) -> Option<AuthenticationResponse> {
+    // TODO popup only supported on linux
+    None
+}
+
 #[cfg(target_os = "linux")]
 fn platform_get_selected_devices(devices: Vec<String>) -> Option<String> {
     thread::Builder::new()
@@ -752,8 +941,10 @@ fn platform_get_selected_devices(devices: Vec<String>) -> Option<String> {
     None
 }
 
-fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Option<Vec<String>> {
-    let picker_name = if multiple_files {
+fn get_selected_files(request: FilePickerRequest) -> Option<Vec<String>> {
+    let picker_name = if request.directory {
+        "Pick a folder"
+    } else if request.multiple {
         "Pick files"
     } else {
         "Pick a file"
@@ -761,8 +952,12 @@ fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Opt
     thread::Builder::new()
         .name("FilePicker".to_owned())
         .spawn(move || {
+            if request.directory {
+                return tinyfiledialogs::select_folder_dialog(picker_name, "").map(|x| vec![x]);
+            }
+
             let mut filters = vec![];
-            for p in patterns {
+            for p in request.filter_patterns {
                 let s = "*.".to_string() + &p.0;
                 filters.push(tiny_dialog_escape(&s))
             }
@@ -773,7 +968,7 @@ fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Opt
                 None
             };
 
-            if multiple_files {
+            if request.multiple {
                 tinyfiledialogs::open_file_dialog_multi(picker_name, "", filter_opt)
             } else {
                 let file = tinyfiledialogs::open_file_dialog(picker_name, "", filter_opt);
@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::borrow::Cow;
+
+use content_security_policy::{
+    CheckResult, CspList, Element, InlineCheckType, PolicyDisposition, PolicySource,
+};
+use script::test::csp::{CSPViolationReport, SecurityPolicyViolationEventDisposition};
+
+const DOCUMENT_URL: &str = "https://example.com/";
+
+fn check_inline_style(
+    policy: &str,
+    disposition: PolicyDisposition,
+    nonce: Option<&str>,
+    type_: InlineCheckType,
+) -> (CheckResult, Vec<CSPViolationReport>) {
+    let csp_list = CspList::parse(policy, PolicySource::Header, disposition);
+    let element = Element {
+        nonce: nonce.map(Cow::Borrowed),
+    };
+    let (result, violations) =
+        csp_list.should_elements_inline_type_behavior_be_blocked(&element, type_, "p { }");
+    let reports = violations
+        .into_iter()
+        .map(|violation| CSPViolationReport::new(violation, DOCUMENT_URL.to_owned(), String::new()))
+        .collect();
+    (result, reports)
+}
+
+#[test]
+fn inline_style_with_matching_nonce_is_allowed() {
+    let (result, reports) = check_inline_style(
+        "style-src 'nonce-abc'",
+        PolicyDisposition::Enforce,
+        Some("abc"),
+        InlineCheckType::Style,
+    );
+    assert_eq!(result, CheckResult::Allowed);
+    assert!(reports.is_empty());
+}
+
+#[test]
+fn inline_style_without_nonce_is_blocked_and_reported() {
+    for nonce in [None, Some("xyz")] {
+        let (result, reports) = check_inline_style(
+            "style-src 'nonce-abc'",
+            PolicyDisposition::Enforce,
+            nonce,
+            InlineCheckType::Style,
+        );
+        assert_eq!(result, CheckResult::Blocked);
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.document_url, DOCUMENT_URL);
+        assert_eq!(report.blocked_url, "inline");
+        assert!(report.effective_directive.starts_with("style-src"));
+        assert_eq!(report.original_policy, "style-src 'nonce-abc'");
+        assert_eq!(
+            report.disposition,
+            SecurityPolicyViolationEventDisposition::Enforce
+        );
+    }
+}
+
+#[test]
+fn style_attribute_is_blocked_without_unsafe_inline() {
+    // Nonces only apply to elements, so they can't allow a style attribute.
+    let (result, reports) = check_inline_style(
+        "style-src 'nonce-abc'",
+        PolicyDisposition::Enforce,
+        Some("abc"),
+        InlineCheckType::StyleAttribute,
+    );
+    assert_eq!(result, CheckResult::Blocked);
+    assert_eq!(reports.len(), 1);
+
+    let (result, reports) = check_inline_style(
+        "style-src 'unsafe-inline'",
+        PolicyDisposition::Enforce,
+        None,
+        InlineCheckType::StyleAttribute,
+    );
+    assert_eq!(result, CheckResult::Allowed);
+    assert!(reports.is_empty());
+}
+
+#[test]
+fn report_only_policy_reports_without_blocking() {
+    let (result, reports) = check_inline_style(
+        "default-src 'self'; style-src 'none'",
+        PolicyDisposition::Report,
+        None,
+        InlineCheckType::Style,
+    );
+    assert_eq!(result, CheckResult::Allowed);
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    assert_eq!(
+        report.disposition,
+        SecurityPolicyViolationEventDisposition::Report
+    );
+    // Source lists are kept, and directives are separated by semicolons.
+    assert_eq!(
+        report.original_policy,
+        "default-src 'self'; style-src 'none'"
+    );
+}
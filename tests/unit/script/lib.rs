@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+#[cfg(test)]
+mod csp;
 #[cfg(test)]
 mod headers;
 #[cfg(test)]
@@ -13,6 +15,8 @@ mod origin;
 #[cfg(all(test, target_pointer_width = "64"))]
 mod size_of;
 #[cfg(test)]
+mod spellcheck;
+#[cfg(test)]
 mod textinput;
 #[cfg(test)]
 mod timeranges;
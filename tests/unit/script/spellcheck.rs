@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::spellcheck::Dictionary;
+
+const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+REP 1
+REP f ph
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX S Y 2
+SFX S y ies [^aeiou]y
+SFX S 0 s [^y]
+";
+
+const DIC: &str = "5
+happy/U
+city/S
+cat/S
+done/U
+Paris
+";
+
+fn dictionary() -> Dictionary {
+    Dictionary::parse(AFF, DIC)
+}
+
+#[test]
+fn test_check_roots_and_affixes() {
+    let dictionary = dictionary();
+    assert!(dictionary.check("happy"));
+    assert!(dictionary.check("unhappy"));
+    assert!(dictionary.check("cities"));
+    assert!(dictionary.check("cats"));
+    assert!(!dictionary.check("citys"));
+    assert!(!dictionary.check("uncat"));
+    assert!(!dictionary.check("dog"));
+}
+
+#[test]
+fn test_check_case() {
+    let dictionary = dictionary();
+    assert!(dictionary.check("Happy"));
+    assert!(dictionary.check("CITIES"));
+    assert!(dictionary.check("PARIS"));
+    assert!(!dictionary.check("paris"));
+    assert!(!dictionary.check("hAppy"));
+}
+
+#[test]
+fn test_suggest() {
+    let dictionary = dictionary();
+    assert_eq!(dictionary.suggest("hapyp")[0], "happy");
+    assert!(dictionary.suggest("catt").contains(&"cat".to_owned()));
+    assert!(dictionary
+        .suggest("catscity")
+        .contains(&"cats city".to_owned()));
+}
+
+#[test]
+fn test_misspellings_ranges_are_utf16() {
+    let dictionary = dictionary();
+    let misspellings = dictionary.misspellings("😀 cats dgo 42x can't");
+    let words: Vec<&str> = misspellings.iter().map(|m| m.word.as_str()).collect();
+    assert_eq!(words, vec!["dgo", "can't"]);
+    assert_eq!(misspellings[0].range, 8..11);
+    assert_eq!(misspellings[1].range, 16..21);
+}
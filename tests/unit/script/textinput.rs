@@ -857,3 +857,81 @@ fn test_select_all() {
         textinput.selection_end()
     );
 }
+
+#[test]
+fn test_textinput_undo() {
+    let mut textinput = text_input(Lines::Multiple, "abc\nde");
+    assert!(!textinput.undo());
+
+    textinput.insert_char('x');
+    textinput.select_all();
+    textinput.delete_char(Direction::Backward);
+    assert_eq!(textinput.get_content(), "");
+
+    assert!(textinput.undo());
+    assert_eq!(textinput.get_content(), "xabc\nde");
+    assert_eq!(textinput.selection_start().index, UTF8Bytes::zero());
+    assert_eq!(textinput.selection_end().line, 1);
+    assert!(textinput.undo());
+    assert_eq!(textinput.get_content(), "abc\nde");
+    assert!(!textinput.undo());
+
+    textinput.insert_char('x');
+    textinput.set_content(DOMString::from("fg"));
+    assert!(!textinput.undo());
+}
+
+#[test]
+fn test_textinput_cut_and_paste() {
+    let mut textinput = text_input(Lines::Single, "abcdef");
+    textinput.set_selection_range(1, 3, SelectionDirection::Forward);
+    textinput.cut();
+    assert_eq!(textinput.get_content(), "adef");
+    textinput.adjust_horizontal_to_line_end(Direction::Forward, Selection::NotSelected);
+    textinput.paste();
+    assert_eq!(textinput.get_content(), "adefbc");
+}
+
+#[test]
+fn test_textinput_composition() {
+    let mut textinput = text_input(Lines::Single, "ab");
+    textinput.adjust_horizontal_to_line_end(Direction::Forward, Selection::NotSelected);
+    textinput.set_composition("に");
+    textinput.set_composition("日本");
+    assert_eq!(textinput.get_content(), "ab日本");
+    assert_eq!(
+        textinput.composition_utf16_range(),
+        Some(UTF16CodeUnits(2)..UTF16CodeUnits(4))
+    );
+
+    textinput.select_in_composition(UTF16CodeUnits(1)..UTF16CodeUnits(1));
+    assert_eq!(
+        textinput.selection_utf16_range(),
+        UTF16CodeUnits(3)..UTF16CodeUnits(3)
+    );
+
+    textinput.commit_composition("日本語");
+    assert_eq!(textinput.get_content(), "ab日本語");
+    assert_eq!(textinput.composition_utf16_range(), None);
+    assert_eq!(
+        textinput.selection_utf16_range(),
+        UTF16CodeUnits(5)..UTF16CodeUnits(5)
+    );
+
+    textinput.set_composition("x");
+    textinput.set_composition("");
+    assert_eq!(textinput.get_content(), "ab日本語");
+    assert_eq!(textinput.composition_utf16_range(), None);
+}
+
+#[test]
+fn test_textinput_delete_surrounding_text() {
+    let mut textinput = text_input(Lines::Single, "abcdef");
+    textinput.set_selection_range(3, 3, SelectionDirection::None);
+    textinput.delete_surrounding_text(UTF16CodeUnits(1), UTF16CodeUnits(2));
+    assert_eq!(textinput.get_content(), "abf");
+    assert_eq!(
+        textinput.selection_utf16_range(),
+        UTF16CodeUnits(2)..UTF16CodeUnits(2)
+    );
+}